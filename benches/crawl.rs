@@ -0,0 +1,63 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use indonesian_media_crawler::detik::DetikCrawler;
+use indonesian_media_crawler::text::clean_paragraph;
+use indonesian_media_crawler::{CrawlContext, Crawler};
+use reqwest::Url;
+use scraper::Html;
+
+fn fixture(name: &str) -> String {
+    std::fs::read_to_string(format!("tests/htmls/{}", name)).expect("Invalid fixture path")
+}
+
+/// A section front page: every link of every fixture, repeated, as a flat list of anchors.
+fn hub_page(ctx: &CrawlContext) -> String {
+    let links: Vec<String> = ["1.html", "2.html", "4.html", "inet.html", "travel.html"]
+        .iter()
+        .flat_map(|f| DetikCrawler::new().extract_urls(ctx, &Html::parse_document(&fixture(f))))
+        .collect();
+    let anchors: String = links
+        .iter()
+        .cycle()
+        .take(5000)
+        .map(|l| format!(r#"<li><a href="{}">{}</a></li>"#, l, l))
+        .collect();
+    format!("<html><body><ul>{}</ul></body></html>", anchors)
+}
+
+fn crawl(c: &mut Criterion) {
+    let crawler = DetikCrawler::new();
+    let url = Url::parse("https://news.detik.com/").expect("Invalid url");
+    let ctx = CrawlContext::new(&url);
+    let article = Html::parse_document(&fixture("4.html"));
+    let hub = Html::parse_document(&hub_page(&ctx));
+
+    let mut group = c.benchmark_group("crawl");
+    group.bench_function("article", |b| {
+        b.iter(|| crawler.crawl(&ctx, black_box(&article)))
+    });
+    group.bench_function("hub", |b| b.iter(|| crawler.crawl(&ctx, black_box(&hub))));
+    group.finish();
+
+    let mut group = c.benchmark_group("extract_links");
+    group.bench_function("article", |b| {
+        b.iter(|| crawler.extract_links(&ctx, black_box(&article)))
+    });
+    group.bench_function("hub", |b| {
+        b.iter(|| crawler.extract_links(&ctx, black_box(&hub)))
+    });
+    group.finish();
+}
+
+fn clean(c: &mut Criterion) {
+    let pathological = format!(
+        "{}{}",
+        r#"<a href="https://news.detik.com/x"><em>  kata   </em><br/>"#.repeat(2000),
+        "<em>unclosed <a href=\"y\">".repeat(500)
+    );
+    c.bench_function("clean_paragraph/pathological", |b| {
+        b.iter(|| clean_paragraph(black_box(&pathological)))
+    });
+}
+
+criterion_group!(benches, crawl, clean);
+criterion_main!(benches);
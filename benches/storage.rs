@@ -0,0 +1,96 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use indonesian_media_crawler::detik::DetikData;
+use indonesian_media_crawler::{Storage, Table};
+use std::time::{Duration, Instant};
+
+const ROWS: usize = 10_000;
+
+fn queued_insert(c: &mut Criterion) {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let _ = std::fs::remove_file("bench_storage.db");
+    let storage = rt.block_on(DetikData::new("bench_storage")).unwrap();
+    let urls: Vec<String> = (0..ROWS)
+        .map(|i| format!("https://news.detik.com/berita/d-{}/judul", i))
+        .collect();
+
+    let storage = &storage;
+    let urls = &urls;
+    let clear = || async move {
+        let query = format!("DELETE FROM {}", storage.queued.get_name());
+        sqlx::query(&query)
+            .execute(storage.queued.get_pool())
+            .await
+            .unwrap();
+    };
+
+    let mut group = c.benchmark_group("queued_insert_10k");
+    group.sample_size(10);
+    group.bench_function("single", |b| {
+        b.to_async(&rt).iter_custom(|iters| async move {
+            let mut total = Duration::ZERO;
+            for _ in 0..iters {
+                clear().await;
+                let start = Instant::now();
+                for url in urls {
+                    storage.queued_insert(url).await.unwrap();
+                }
+                total += start.elapsed();
+            }
+            total
+        })
+    });
+    group.bench_function("batch", |b| {
+        b.to_async(&rt).iter_custom(|iters| async move {
+            let mut total = Duration::ZERO;
+            for _ in 0..iters {
+                clear().await;
+                let start = Instant::now();
+                storage.queued_insert_batch(urls).await.unwrap();
+                total += start.elapsed();
+            }
+            total
+        })
+    });
+    group.finish();
+
+    let _ = std::fs::remove_file("bench_storage.db");
+}
+
+/// Queue links already visited, like the navigation found on every page, with and without
+/// the visited cache.
+fn visited_links(c: &mut Criterion) {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let _ = std::fs::remove_file("bench_visited.db");
+    let storage = rt.block_on(DetikData::new("bench_visited")).unwrap();
+    let urls: Vec<String> = (0..ROWS / 10)
+        .map(|i| format!("https://news.detik.com/kanal-{}", i))
+        .collect();
+    rt.block_on(storage.visited.insert_batch(&urls)).unwrap();
+    let cached = rt
+        .block_on(
+            rt.block_on(DetikData::new("bench_visited"))
+                .unwrap()
+                .with_visited_cache(ROWS),
+        )
+        .unwrap();
+
+    let mut group = c.benchmark_group("visited_links_1k");
+    group.sample_size(10);
+    for (name, storage) in [("uncached", &storage), ("cached", &cached)] {
+        let urls = &urls;
+        group.bench_function(name, |b| {
+            b.to_async(&rt).iter(|| async move {
+                for url in urls {
+                    storage.queued_insert_unseen(url).await.unwrap();
+                }
+            })
+        });
+    }
+    group.finish();
+    eprintln!("{:?}", cached.visited_cache_stats().unwrap());
+
+    let _ = std::fs::remove_file("bench_visited.db");
+}
+
+criterion_group!(benches, queued_insert, visited_links);
+criterion_main!(benches);
@@ -0,0 +1,238 @@
+//! Keyword trends and co-occurrence over the stored detik articles, counted in the database
+//! from the normalized keywords table.
+
+use crate::detik::DetikData;
+use crate::CrawlerError;
+use chrono::NaiveDate;
+use clap::ValueEnum;
+
+/// The periods articles are counted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TimeBucket {
+    Daily,
+    /// Weeks starting on Monday
+    Weekly,
+}
+
+/// The number of articles with a keyword published in a period.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeywordCount {
+    /// The day, or the Monday of the week
+    pub period: NaiveDate,
+    pub keyword: String,
+    pub articles: u32,
+}
+
+/// The number of articles with both keywords.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeywordPair {
+    pub keyword: String,
+    pub other: String,
+    pub articles: u32,
+}
+
+/// The `top_n` keywords of each period, see `DetikArticleTable::keyword_trends`.
+pub async fn keyword_trends(
+    storage: &DetikData,
+    bucket: TimeBucket,
+    top_n: u32,
+) -> Result<Vec<KeywordCount>, CrawlerError> {
+    Ok(storage.results.keyword_trends(bucket, top_n).await?)
+}
+
+/// The keywords found together in at least `min_count` articles, see
+/// `DetikArticleTable::keyword_cooccurrence`.
+pub async fn cooccurrence(
+    storage: &DetikData,
+    min_count: u32,
+) -> Result<Vec<KeywordPair>, CrawlerError> {
+    Ok(storage.results.keyword_cooccurrence(min_count).await?)
+}
+
+/// The trends as CSV with a header.
+pub fn trends_to_csv(trends: &[KeywordCount]) -> String {
+    let mut csv = String::from("period,keyword,articles\n");
+    for trend in trends {
+        csv.push_str(&format!(
+            "{},{},{}\n",
+            trend.period.format("%Y-%m-%d"),
+            csv_field(&trend.keyword),
+            trend.articles
+        ));
+    }
+    csv
+}
+
+/// The pairs as CSV with a header.
+pub fn pairs_to_csv(pairs: &[KeywordPair]) -> String {
+    let mut csv = String::from("keyword,other,articles\n");
+    for pair in pairs {
+        csv.push_str(&format!(
+            "{},{},{}\n",
+            csv_field(&pair.keyword),
+            csv_field(&pair.other),
+            pair.articles
+        ));
+    }
+    csv
+}
+
+/// The titles published more than once the same day, see
+/// `DetikArticleTable::duplicate_titles`, as CSV with a header, the urls separated by spaces.
+pub fn duplicates_to_csv(duplicates: &[(String, Vec<String>)]) -> String {
+    let mut csv = String::from("title,articles,urls\n");
+    for (title, urls) in duplicates {
+        csv.push_str(&format!(
+            "{},{},{}\n",
+            csv_field(title),
+            urls.len(),
+            csv_field(&urls.join(" "))
+        ));
+    }
+    csv
+}
+
+/// `field` quoted when it holds a comma, a quote or a line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detik::DetikArticle;
+    use crate::Storage;
+    use chrono::DateTime;
+
+    fn article(published_date: Option<&str>, keywords: &[&str]) -> DetikArticle {
+        DetikArticle {
+            title: None,
+            published_date: published_date.map(|d| DateTime::parse_from_rfc3339(d).unwrap()),
+            description: None,
+            thumbnail_url: None,
+            author: None,
+            desk: None,
+            category: None,
+            language: None,
+            keywords: keywords.iter().map(ToString::to_string).collect(),
+            keywords_raw: None,
+            videos: vec![],
+            photos: vec![],
+            body_selector: None,
+            paragraphs: vec!["p".to_string()],
+        }
+    }
+
+    #[tokio::test]
+    async fn trends_and_pairs() {
+        if std::path::Path::new("test_analytics.db").is_file() {
+            std::fs::remove_file("test_analytics.db").unwrap();
+        }
+        let storage = DetikData::new("test_analytics").await.unwrap();
+        let articles = [
+            // Monday 2022-12-05, at 00:30 WIB still Sunday in UTC
+            (Some("2022-12-05T00:30:00+07:00"), &["KUHP", "Bali"][..]),
+            (
+                Some("2022-12-06T10:00:00+07:00"),
+                &["kuhp", "bali", "turis"],
+            ),
+            (Some("2022-12-06T11:00:00+07:00"), &["KUHP", "DPR"]),
+            // Sunday 2022-12-11, same week
+            (
+                Some("2022-12-11T20:00:00+07:00"),
+                &["Piala  Dunia", "Maroko"],
+            ),
+            // Monday 2022-12-12, next week
+            (
+                Some("2022-12-12T08:00:00+07:00"),
+                &["piala dunia", "maroko"],
+            ),
+            (
+                Some("2022-12-12T09:00:00+07:00"),
+                &["Piala Dunia", "Portugal"],
+            ),
+            (None, &["kuhp", "bali"]),
+        ];
+        for (i, (date, keywords)) in articles.into_iter().enumerate() {
+            let url = format!("https://news.detik.com/berita/d-{}/a", i);
+            storage
+                .results_insert((url, article(date, keywords)))
+                .await
+                .unwrap();
+        }
+        let day = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        let count = |period: &str, keyword: &str, articles| KeywordCount {
+            period: day(period),
+            keyword: keyword.to_string(),
+            articles,
+        };
+
+        assert_eq!(
+            keyword_trends(&storage, TimeBucket::Weekly, 2)
+                .await
+                .unwrap(),
+            vec![
+                count("2022-12-05", "kuhp", 3),
+                count("2022-12-05", "bali", 2),
+                count("2022-12-12", "piala dunia", 2),
+                count("2022-12-12", "maroko", 1),
+            ]
+        );
+        assert_eq!(
+            keyword_trends(&storage, TimeBucket::Daily, 1)
+                .await
+                .unwrap(),
+            vec![
+                count("2022-12-05", "bali", 1),
+                count("2022-12-06", "kuhp", 2),
+                count("2022-12-11", "maroko", 1),
+                count("2022-12-12", "piala dunia", 2),
+            ]
+        );
+
+        let pair = |keyword: &str, other: &str, articles| KeywordPair {
+            keyword: keyword.to_string(),
+            other: other.to_string(),
+            articles,
+        };
+        assert_eq!(
+            cooccurrence(&storage, 2).await.unwrap(),
+            vec![pair("bali", "kuhp", 3), pair("maroko", "piala dunia", 2)]
+        );
+        assert_eq!(
+            pairs_to_csv(&cooccurrence(&storage, 3).await.unwrap()),
+            "keyword,other,articles\nbali,kuhp,3\n"
+        );
+
+        std::fs::remove_file("test_analytics.db").unwrap();
+    }
+
+    #[test]
+    fn quoted_fields() {
+        let trends = [KeywordCount {
+            period: NaiveDate::from_ymd_opt(2022, 12, 5).unwrap(),
+            keyword: "bali, \"pulau dewata\"".to_string(),
+            articles: 1,
+        }];
+        assert_eq!(
+            trends_to_csv(&trends),
+            "period,keyword,articles\n2022-12-05,\"bali, \"\"pulau dewata\"\"\",1\n"
+        );
+    }
+
+    #[test]
+    fn duplicates_csv() {
+        let duplicates = [(
+            "Banjir, Warga Mengungsi".to_string(),
+            vec!["https://a/1".to_string(), "https://a/2".to_string()],
+        )];
+        assert_eq!(
+            duplicates_to_csv(&duplicates),
+            "title,articles,urls\n\"Banjir, Warga Mengungsi\",2,https://a/1 https://a/2\n"
+        );
+    }
+}
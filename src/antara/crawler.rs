@@ -0,0 +1,221 @@
+use crate::{
+    antara::AntaraArticle, json_ld, text, utils, CrawlContext, Crawler, CrawlerResult,
+    ExtractedLink,
+};
+use chrono::DateTime;
+use lazy_regex::{regex, regex_captures};
+use lazy_static::lazy_static;
+use scraper::{Html, Selector};
+
+const E: &str = "Invalid selector";
+lazy_static! {
+    static ref OG_TYPE: Selector = Selector::parse(r#"meta[property="og:type"]"#).expect(E);
+    static ref TITLE: Selector = Selector::parse(r#"meta[property="og:title"]"#).expect(E);
+    static ref DESCRIPTION: Selector =
+        Selector::parse(r#"meta[property="og:description"]"#).expect(E);
+    static ref THUMBNAIL: Selector = Selector::parse(r#"meta[property="og:image"]"#).expect(E);
+    static ref PUBLISHED_TIME: Selector =
+        Selector::parse(r#"meta[property="article:published_time"]"#).expect(E);
+    static ref KEYWORDS: Selector = Selector::parse(r#"meta[name="keywords"]"#).expect(E);
+    static ref TAG: Selector = Selector::parse(".tags-wrapper a").expect(E);
+    static ref BODY: Selector = Selector::parse(".post-content").expect(E);
+}
+
+const SKIPPED_PREFIXES: [&str; 3] = ["Baca juga", "Baca Juga", "COPYRIGHT"];
+
+#[derive(Debug, Clone)]
+pub struct AntaraCrawler {
+    allowlist: utils::HostAllowlist,
+}
+
+impl Default for AntaraCrawler {
+    fn default() -> Self {
+        AntaraCrawler {
+            allowlist: utils::HostAllowlist::new(["antaranews.com"]),
+        }
+    }
+}
+
+impl AntaraCrawler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    utils::allowlist_builders!(
+        "antaranews.com",
+        "It covers regional sites like megapolitan.antaranews.com."
+    );
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Credits {
+    author: Option<String>,
+    editor: Option<String>,
+}
+
+/// Take the "Pewarta: Name" and "Editor: Name" lines out of a paragraph into `credits`,
+/// returning what is left of it.
+fn take_credits(paragraph: &str, credits: &mut Credits) -> Option<String> {
+    let mut rest = vec![];
+    for line in paragraph.lines() {
+        let plain = regex!(r"<[^>]*>").replace_all(line, "");
+        match regex_captures!(r"(?i)^\s*(pewarta|reporter|editor)\s*:\s*(.*?)\s*$", &plain) {
+            Some((_, key, name)) => {
+                let name = Some(name.to_string()).filter(|s| !s.is_empty());
+                if key.eq_ignore_ascii_case("editor") {
+                    credits.editor = credits.editor.take().or(name);
+                } else {
+                    credits.author = credits.author.take().or(name);
+                }
+            }
+            None => rest.push(line),
+        }
+    }
+    Some(rest.join("\n").trim().to_string()).filter(|s| !s.is_empty())
+}
+
+/// Split the body into paragraphs. Wire copy is mostly text separated by `<br><br>`, with some
+/// `<p>` blocks for promos and the credit lines.
+fn raw_paragraphs(body_html: &str) -> Vec<String> {
+    let html = regex!(r"(?is)<script\b.*?</script>").replace_all(body_html, "");
+    regex!(r"(?i)(<br\s*/?>\s*){2,}|</?p\b[^>]*>")
+        .split(&html)
+        .filter_map(text::clean_paragraph)
+        .collect()
+}
+
+impl Crawler for AntaraCrawler {
+    type Document = AntaraArticle;
+
+    fn can_be_scrapped(&self, doc: &Html) -> bool {
+        let is_article = json_ld::article(doc).is_some()
+            || utils::meta_content(doc, &OG_TYPE).is_some_and(|t| t == "article");
+        is_article && doc.select(&BODY).next().is_some()
+    }
+
+    fn extract_links(&self, ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        utils::links(doc, ctx.url, |url| self.allowlist.allows(url))
+    }
+
+    fn crawl(&self, ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        let links = self.extract_urls(ctx, doc);
+
+        if !self.can_be_scrapped(doc) {
+            return CrawlerResult::Links(links);
+        }
+
+        let ld = json_ld::article(doc);
+        let ld_string = |key: &str| ld.as_ref().and_then(|ld| json_ld::string(ld, key));
+
+        let published_date = ld_string("datePublished")
+            .or_else(|| utils::meta_content(doc, &PUBLISHED_TIME))
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok());
+
+        let mut tags: Vec<String> = doc
+            .select(&TAG)
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if tags.is_empty() {
+            tags = utils::meta_content(doc, &KEYWORDS)
+                .map(|s| {
+                    s.split(',')
+                        .map(|s| s.trim().to_string())
+                        .filter(|s| !s.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default();
+        }
+
+        let mut credits = Credits::default();
+        let mut paragraphs = vec![];
+        if let Some(body) = doc.select(&BODY).next() {
+            for p in raw_paragraphs(&body.inner_html()) {
+                if let Some(p) = take_credits(&p, &mut credits) {
+                    if !text::starts_with_any(&p, &SKIPPED_PREFIXES) {
+                        paragraphs.push(p);
+                    }
+                }
+            }
+        }
+        paragraphs.dedup();
+
+        let author = credits.author.or_else(|| {
+            ld.as_ref()
+                .map(|ld| json_ld::names(ld, "author"))
+                .filter(|names| !names.is_empty())
+                .map(|names| names.join(", "))
+        });
+
+        let article = AntaraArticle {
+            title: utils::meta_content(doc, &TITLE).or_else(|| ld_string("headline")),
+            published_date,
+            description: utils::meta_content(doc, &DESCRIPTION)
+                .or_else(|| ld_string("description")),
+            thumbnail_url: utils::meta_content(doc, &THUMBNAIL),
+            author,
+            editor: credits.editor,
+            tags,
+            paragraphs,
+        };
+        CrawlerResult::document_and_links(article, links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trailing_credits() {
+        let mut credits = Credits::default();
+        let rest = take_credits(
+            "Pewarta: Fianda Sjofjan Rassat\nEditor: <a href=\"x\">Budi Suyanto</a>\nCOPYRIGHT © ANTARA 2022",
+            &mut credits,
+        );
+        assert_eq!(rest.as_deref(), Some("COPYRIGHT © ANTARA 2022"));
+        assert_eq!(
+            credits,
+            Credits {
+                author: Some("Fianda Sjofjan Rassat".to_string()),
+                editor: Some("Budi Suyanto".to_string()),
+            }
+        );
+
+        let mut credits = Credits::default();
+        let rest = take_credits("Editor Pratama memimpin rapat redaksi.", &mut credits);
+        assert_eq!(
+            rest.as_deref(),
+            Some("Editor Pratama memimpin rapat redaksi.")
+        );
+        assert_eq!(credits, Credits::default());
+    }
+
+    #[test]
+    fn br_separated_body() {
+        let body = "Jakarta (ANTARA) - Satu.<br><br>Dua<br>baris.<br /><br />\
+            <script>ads()</script><p>Tiga.</p>";
+        assert_eq!(
+            raw_paragraphs(body),
+            vec!["Jakarta (ANTARA) - Satu.", "Dua\nbaris.", "Tiga."]
+        );
+    }
+
+    #[test]
+    fn regional_hosts() {
+        let crawler = AntaraCrawler::new();
+        let allowed = |s: &str| crawler.allowlist.allows(&reqwest::Url::parse(s).unwrap());
+        assert!(allowed("https://www.antaranews.com/berita/3300001/judul"));
+        assert!(allowed(
+            "https://megapolitan.antaranews.com/berita/220001/judul"
+        ));
+        assert!(!allowed("https://antaranews.com.example.net"));
+
+        let crawler = AntaraCrawler::new().with_allowed_hosts(["megapolitan.antaranews.com"]);
+        let allowed = |s: &str| crawler.allowlist.allows(&reqwest::Url::parse(s).unwrap());
+        assert!(allowed(
+            "https://megapolitan.antaranews.com/berita/220001/judul"
+        ));
+        assert!(!allowed("https://www.antaranews.com/berita/3300001/judul"));
+    }
+}
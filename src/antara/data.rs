@@ -0,0 +1,73 @@
+use super::AntaraArticle;
+use crate::generic::{SiteArticle, SiteArticleTable};
+use crate::SqliteStorage;
+use sqlx::query::Query;
+use sqlx::sqlite::SqliteArguments;
+use sqlx::Sqlite;
+
+impl SiteArticle for AntaraArticle {
+    const COLUMNS: &'static [(&'static str, &'static str)] = &[
+        ("author", "TEXT"),
+        ("editor", "TEXT"),
+        ("description", "TEXT"),
+        ("thumbnail_url", "TEXT"),
+        ("tags", "TEXT"),
+    ];
+
+    fn bind<'q>(
+        self,
+        query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    ) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+        query
+            .bind(self.author)
+            .bind(self.editor)
+            .bind(self.description)
+            .bind(self.thumbnail_url)
+            .bind(self.tags.join("|"))
+    }
+}
+
+pub type AntaraArticleTable = SiteArticleTable<AntaraArticle>;
+
+pub type AntaraData = SqliteStorage<AntaraArticleTable>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils, Storage, Table};
+    use std::path::Path;
+    use tokio::fs;
+
+    #[tokio::test]
+    async fn columns_mapped() {
+        if Path::new("test_antara.db").is_file() {
+            fs::remove_file("test_antara.db").await.unwrap();
+        }
+        let p = AntaraData::new("test_antara").await.unwrap();
+
+        let article = AntaraArticle {
+            title: Some("title".to_string()),
+            published_date: Some(utils::get_now()),
+            description: None,
+            thumbnail_url: None,
+            author: Some("author".to_string()),
+            editor: Some("editor".to_string()),
+            tags: vec!["t1".to_string()],
+            paragraphs: vec!["p1".to_string(), "p2".to_string()],
+        };
+        p.results_insert(("https://www.antaranews.com/berita/1", article))
+            .await
+            .unwrap();
+        let row: (String, String, String) =
+            sqlx::query_as("SELECT author, editor, tags FROM test_antara_results")
+                .fetch_one(p.results.get_pool())
+                .await
+                .unwrap();
+        assert_eq!(
+            row,
+            ("author".to_string(), "editor".to_string(), "t1".to_string(),)
+        );
+
+        fs::remove_file("test_antara.db").await.unwrap();
+    }
+}
@@ -0,0 +1,40 @@
+mod crawler;
+#[cfg(feature = "sqlite")]
+mod data;
+
+pub use crawler::AntaraCrawler;
+#[cfg(feature = "sqlite")]
+pub use data::{AntaraArticleTable, AntaraData};
+
+use crate::Article;
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AntaraArticle {
+    pub title: Option<String>,
+    pub published_date: Option<DateTime<FixedOffset>>,
+    pub description: Option<String>,
+    pub thumbnail_url: Option<String>,
+    /// From the trailing "Pewarta:" line
+    pub author: Option<String>,
+    /// From the trailing "Editor:" line
+    pub editor: Option<String>,
+    pub tags: Vec<String>,
+    pub paragraphs: Vec<String>,
+}
+
+impl Article for AntaraArticle {
+    fn get_paragraphs(&self) -> &[String] {
+        self.paragraphs.as_slice()
+    }
+
+    fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    fn get_published_date(&self) -> Option<DateTime<FixedOffset>> {
+        self.published_date
+    }
+}
@@ -0,0 +1,203 @@
+//! Slowing down on the hosts answering 429 Too Many Requests: no request to the host until
+//! its `Retry-After`, then requests further apart until it answers again. The state of each
+//! host is stored, so a restarted crawl doesn't go back to full speed and get blocked again.
+
+use crate::{CrawlerError, HostState, Storage};
+use chrono::{DateTime, FixedOffset};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::time::Duration;
+
+/// The pause after a 429 without `Retry-After`, times the multiplier of the host.
+pub const BASE_PAUSE: Duration = Duration::from_secs(30);
+
+/// The delay between requests to a host is stretched at most this many times.
+pub const MAX_MULTIPLIER: u32 = 64;
+
+/// The stored state of a host is dropped once it wasn't updated for this long.
+pub const STATE_TTL: Duration = Duration::from_secs(24 * 3600);
+
+/// The backoff of the hosts of a run, each one loaded from the storage the first time the
+/// run fetches from it.
+#[derive(Debug, Default)]
+pub(crate) struct Backoffs {
+    /// The hosts already loaded, `None` for the ones without a backoff
+    hosts: Mutex<HashMap<String, Option<HostState>>>,
+}
+
+impl Backoffs {
+    /// Load the stored state of `host` unless it's already loaded, dropping it when it's
+    /// older than `STATE_TTL` at `now`.
+    pub(crate) async fn load<S: Storage + ?Sized>(
+        &self,
+        storage: &S,
+        host: &str,
+        now: DateTime<FixedOffset>,
+    ) -> Result<(), CrawlerError> {
+        if self.hosts.lock().unwrap().contains_key(host) {
+            return Ok(());
+        }
+        let ttl = chrono::Duration::from_std(STATE_TTL).unwrap();
+        let stored = storage
+            .host_state_get(host)
+            .await?
+            .filter(|state| state.updated_at + ttl > now);
+        self.hosts
+            .lock()
+            .unwrap()
+            .entry(host.to_string())
+            .or_insert(stored);
+        Ok(())
+    }
+
+    /// How long a request to `host` at `now` waits for its backoff to be over.
+    pub(crate) fn pause(&self, host: &str, now: DateTime<FixedOffset>) -> Duration {
+        match self.hosts.lock().unwrap().get(host) {
+            Some(Some(state)) => (state.resume_at - now).to_std().unwrap_or(Duration::ZERO),
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// How many times the delay before a request to `host` is stretched.
+    pub(crate) fn multiplier(&self, host: &str) -> u32 {
+        match self.hosts.lock().unwrap().get(host) {
+            Some(Some(state)) => state.multiplier,
+            _ => 1,
+        }
+    }
+
+    /// Back off from `host`, answering 429 at `now`: its multiplier doubles and it's left
+    /// alone until `retry_after`, or `BASE_PAUSE` times the multiplier. The new state.
+    pub(crate) fn throttled(
+        &self,
+        host: &str,
+        retry_after: Option<Duration>,
+        now: DateTime<FixedOffset>,
+    ) -> HostState {
+        let mut hosts = self.hosts.lock().unwrap();
+        let state = hosts.entry(host.to_string()).or_default();
+        let multiplier = state.as_ref().map_or(1, |state| state.multiplier);
+        let multiplier = (multiplier * 2).min(MAX_MULTIPLIER);
+        let pause = retry_after.unwrap_or(BASE_PAUSE * multiplier);
+        let mut resume_at =
+            now + chrono::Duration::from_std(pause).unwrap_or(chrono::Duration::zero());
+        // The latest of the pauses when the requests already waiting got 429 as well
+        if let Some(previous) = state {
+            resume_at = resume_at.max(previous.resume_at);
+        }
+        state
+            .insert(HostState {
+                host: host.to_string(),
+                multiplier,
+                resume_at,
+                updated_at: now,
+            })
+            .clone()
+    }
+
+    /// Ease off the backoff of `host`, answering at `now`: its multiplier halves. The new
+    /// state, `None` when it didn't change.
+    pub(crate) fn recovered(&self, host: &str, now: DateTime<FixedOffset>) -> Option<HostState> {
+        let mut hosts = self.hosts.lock().unwrap();
+        let state = hosts.get_mut(host)?.as_mut()?;
+        if state.multiplier <= 1 || state.resume_at > now {
+            return None;
+        }
+        state.multiplier /= 2;
+        state.updated_at = now;
+        Some(state.clone())
+    }
+
+    /// The state of the hosts with a backoff, to store when the run stops.
+    pub(crate) fn states(&self) -> Vec<HostState> {
+        self.hosts
+            .lock()
+            .unwrap()
+            .values()
+            .flatten()
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(all(test, feature = "detik"))]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryStorage;
+    use crate::utils;
+
+    #[tokio::test]
+    async fn backoff_doubles_then_halves() {
+        let backoffs = Backoffs::default();
+        let now = utils::get_now();
+        assert_eq!(backoffs.multiplier("news.detik.com"), 1);
+
+        let state = backoffs.throttled("news.detik.com", None, now);
+        assert_eq!(state.multiplier, 2);
+        assert_eq!(state.resume_at, now + chrono::Duration::seconds(60));
+        assert_eq!(
+            backoffs.pause("news.detik.com", now),
+            Duration::from_secs(60)
+        );
+        assert_eq!(backoffs.pause("finance.detik.com", now), Duration::ZERO);
+
+        let state = backoffs.throttled("news.detik.com", Some(Duration::from_secs(5)), now);
+        assert_eq!(state.multiplier, 4);
+        // Never resuming earlier than a previous 429 said
+        assert_eq!(state.resume_at, now + chrono::Duration::seconds(60));
+
+        // Not until it's resumed
+        assert_eq!(backoffs.recovered("news.detik.com", now), None);
+        let later = now + chrono::Duration::seconds(61);
+        assert_eq!(backoffs.pause("news.detik.com", later), Duration::ZERO);
+        assert_eq!(
+            backoffs
+                .recovered("news.detik.com", later)
+                .unwrap()
+                .multiplier,
+            2
+        );
+        backoffs.recovered("news.detik.com", later);
+        assert_eq!(backoffs.recovered("news.detik.com", later), None);
+        assert_eq!(backoffs.multiplier("news.detik.com"), 1);
+        assert_eq!(backoffs.states().len(), 1);
+
+        for _ in 0..10 {
+            backoffs.throttled("news.detik.com", None, later);
+        }
+        assert_eq!(backoffs.multiplier("news.detik.com"), MAX_MULTIPLIER);
+    }
+
+    #[tokio::test]
+    async fn stale_state_dropped() {
+        let storage = MemoryStorage::<crate::detik::DetikArticle>::new();
+        let now = utils::get_now();
+        for (host, age) in [
+            ("news.detik.com", 3600),
+            ("finance.detik.com", 2 * 24 * 3600),
+        ] {
+            let updated_at = now - chrono::Duration::seconds(age);
+            storage
+                .host_state_put(&HostState {
+                    host: host.to_string(),
+                    multiplier: 8,
+                    resume_at: now + chrono::Duration::seconds(60),
+                    updated_at,
+                })
+                .await
+                .unwrap();
+        }
+
+        let backoffs = Backoffs::default();
+        for host in ["news.detik.com", "finance.detik.com"] {
+            backoffs.load(&storage, host, now).await.unwrap();
+        }
+        assert_eq!(backoffs.multiplier("news.detik.com"), 8);
+        assert_eq!(
+            backoffs.pause("news.detik.com", now),
+            Duration::from_secs(60)
+        );
+        assert_eq!(backoffs.multiplier("finance.detik.com"), 1);
+        assert_eq!(backoffs.pause("finance.detik.com", now), Duration::ZERO);
+    }
+}
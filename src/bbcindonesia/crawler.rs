@@ -0,0 +1,206 @@
+use crate::{
+    bbcindonesia::{BbcImage, BbcIndonesiaArticle},
+    json_ld, text, utils, CrawlContext, Crawler, CrawlerResult, ExtractedLink,
+};
+use chrono::DateTime;
+use itertools::Itertools;
+use lazy_regex::regex;
+use lazy_static::lazy_static;
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+
+const E: &str = "Invalid selector";
+lazy_static! {
+    static ref DESCRIPTION: Selector =
+        Selector::parse(r#"meta[property="og:description"]"#).expect(E);
+    static ref THUMBNAIL: Selector = Selector::parse(r#"meta[property="og:image"]"#).expect(E);
+    static ref TEXT_BLOCK: Selector = Selector::parse(
+        r#"main [data-component="text-block"], main [data-component="subheadline-block"]"#
+    )
+    .expect(E);
+    static ref BLOCK: Selector = Selector::parse(
+        r#"main [data-component="text-block"] p, main [data-component="subheadline-block"] h2,
+        main [data-component="image-block"]"#
+    )
+    .expect(E);
+    static ref IMG: Selector = Selector::parse("img").expect(E);
+    static ref CAPTION: Selector = Selector::parse("figcaption").expect(E);
+    static ref TOPIC: Selector = Selector::parse(r#"[data-component="topic-list"] a"#).expect(E);
+}
+
+#[derive(Debug, Clone)]
+pub struct BbcIndonesiaCrawler {
+    allowlist: utils::HostAllowlist,
+}
+
+impl Default for BbcIndonesiaCrawler {
+    fn default() -> Self {
+        BbcIndonesiaCrawler {
+            allowlist: utils::HostAllowlist::new(["bbc.com"]),
+        }
+    }
+}
+
+impl BbcIndonesiaCrawler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    utils::allowlist_builders!(
+        "bbc.com",
+        "Links outside of `/indonesia` are never followed."
+    );
+
+    fn is_allowed(&self, url: &Url) -> bool {
+        self.allowlist.allows(url)
+            && (url.path() == "/indonesia" || url.path().starts_with("/indonesia/"))
+    }
+}
+
+/// The caption without the "Keterangan gambar," label read out to screen readers.
+fn caption(figure: &ElementRef) -> Option<String> {
+    let caption = figure
+        .select(&CAPTION)
+        .next()?
+        .text()
+        .collect::<String>()
+        .split_whitespace()
+        .join(" ");
+    let caption = regex!(r"^Keterangan gambar,\s*").replace(&caption, "");
+    Some(caption.to_string()).filter(|s| !s.is_empty())
+}
+
+fn image(figure: &ElementRef) -> Option<BbcImage> {
+    let url = figure
+        .select(&IMG)
+        .next()
+        .and_then(|img| img.value().attr("src"))
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(ToString::to_string);
+    let caption = caption(figure);
+    (url.is_some() || caption.is_some()).then_some(BbcImage { url, caption })
+}
+
+impl Crawler for BbcIndonesiaCrawler {
+    type Document = BbcIndonesiaArticle;
+
+    fn can_be_scrapped(&self, doc: &Html) -> bool {
+        json_ld::article(doc).is_some() && doc.select(&TEXT_BLOCK).next().is_some()
+    }
+
+    fn extract_links(&self, ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        utils::links(doc, ctx.url, |url| self.is_allowed(url))
+    }
+
+    fn crawl(&self, ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        let links = self.extract_urls(ctx, doc);
+
+        if !self.can_be_scrapped(doc) {
+            return CrawlerResult::Links(links);
+        }
+
+        let ld = json_ld::article(doc);
+        let ld_string = |key: &str| ld.as_ref().and_then(|ld| json_ld::string(ld, key));
+
+        // Articles without a byline credit the publisher as author
+        let author = ld.as_ref().and_then(|ld| {
+            let publisher = json_ld::names(ld, "publisher");
+            let authors: Vec<String> = json_ld::names(ld, "author")
+                .into_iter()
+                .filter(|name| !publisher.contains(name))
+                .collect();
+            Some(authors.join(", ")).filter(|s| !s.is_empty())
+        });
+
+        let mut images = vec![];
+        let mut paragraphs = vec![];
+        for el in doc.select(&BLOCK) {
+            match el.value().name() {
+                "h2" => {
+                    let heading = el.text().collect::<String>().split_whitespace().join(" ");
+                    if !heading.is_empty() {
+                        paragraphs.push(format!("## {}", heading));
+                    }
+                }
+                "p" => {
+                    if let Some(p) = text::clean_paragraph(&el.inner_html()) {
+                        paragraphs.push(p);
+                    }
+                }
+                _ => images.extend(image(&el)),
+            }
+        }
+        paragraphs.dedup();
+
+        let tags = doc
+            .select(&TOPIC)
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .filter(|s| !s.is_empty())
+            .dedup()
+            .collect();
+
+        let article = BbcIndonesiaArticle {
+            title: ld_string("headline"),
+            published_date: ld_string("datePublished")
+                .and_then(|s| DateTime::parse_from_rfc3339(&s).ok()),
+            description: ld_string("description")
+                .or_else(|| utils::meta_content(doc, &DESCRIPTION)),
+            thumbnail_url: utils::meta_content(doc, &THUMBNAIL),
+            author,
+            images,
+            tags,
+            paragraphs,
+        };
+        CrawlerResult::document_and_links(article, links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_image(html: &str) -> Option<BbcImage> {
+        let doc = Html::parse_fragment(html);
+        let figure = doc
+            .select(&Selector::parse("figure").unwrap())
+            .next()
+            .unwrap();
+        image(&figure)
+    }
+
+    #[test]
+    fn images() {
+        assert_eq!(
+            first_image(
+                r#"<figure><img src="https://ichef.bbci.co.uk/1.jpg">
+                <figcaption><span class="visually-hidden">Keterangan gambar,</span>
+                Warga mengungsi.</figcaption></figure>"#
+            ),
+            Some(BbcImage {
+                url: Some("https://ichef.bbci.co.uk/1.jpg".to_string()),
+                caption: Some("Warga mengungsi.".to_string()),
+            })
+        );
+        assert_eq!(
+            first_image("<figure><figcaption> </figcaption></figure>"),
+            None
+        );
+    }
+
+    #[test]
+    fn links_stay_in_indonesia() {
+        let doc = Html::parse_document(
+            r#"<a href="/indonesia/articles/c1">1</a> <a href="/news/world-1">2</a>
+            <a href="https://www.bbc.com/indonesia/">3</a> <a href="/indonesiana">4</a>"#,
+        );
+        let url = reqwest::Url::parse("https://www.bbc.com/indonesia").unwrap();
+        assert_eq!(
+            BbcIndonesiaCrawler::new().extract_urls(&CrawlContext::new(&url), &doc),
+            vec![
+                "https://www.bbc.com/indonesia/articles/c1",
+                "https://www.bbc.com/indonesia"
+            ]
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use super::BbcIndonesiaArticle;
+use crate::generic::{SiteArticle, SiteArticleTable};
+use crate::SqliteStorage;
+use sqlx::query::Query;
+use sqlx::sqlite::SqliteArguments;
+use sqlx::Sqlite;
+
+impl SiteArticle for BbcIndonesiaArticle {
+    const COLUMNS: &'static [(&'static str, &'static str)] = &[
+        ("author", "TEXT"),
+        ("description", "TEXT"),
+        ("thumbnail_url", "TEXT"),
+        ("images", "TEXT"),
+        ("tags", "TEXT"),
+    ];
+
+    fn bind<'q>(
+        self,
+        query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    ) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+        query
+            .bind(self.author)
+            .bind(self.description)
+            .bind(self.thumbnail_url)
+            .bind(serde_json::to_string(&self.images).unwrap_or_default())
+            .bind(self.tags.join("|"))
+    }
+}
+
+pub type BbcIndonesiaArticleTable = SiteArticleTable<BbcIndonesiaArticle>;
+
+pub type BbcIndonesiaData = SqliteStorage<BbcIndonesiaArticleTable>;
+
+#[cfg(test)]
+mod tests {
+    use super::super::BbcImage;
+    use super::*;
+    use crate::{utils, Storage, Table};
+    use std::path::Path;
+    use tokio::fs;
+
+    #[tokio::test]
+    async fn columns_mapped() {
+        if Path::new("test_bbcindonesia.db").is_file() {
+            fs::remove_file("test_bbcindonesia.db").await.unwrap();
+        }
+        let p = BbcIndonesiaData::new("test_bbcindonesia").await.unwrap();
+
+        let article = BbcIndonesiaArticle {
+            title: Some("title".to_string()),
+            published_date: Some(utils::get_now()),
+            description: None,
+            thumbnail_url: None,
+            author: Some("author".to_string()),
+            images: vec![BbcImage {
+                url: Some("https://ichef.bbci.co.uk/1.jpg".to_string()),
+                caption: Some("caption".to_string()),
+            }],
+            tags: vec!["t1".to_string()],
+            paragraphs: vec!["p1".to_string(), "p2".to_string()],
+        };
+        p.results_insert((
+            "https://www.bbc.com/indonesia/articles/c0000000000o",
+            article,
+        ))
+        .await
+        .unwrap();
+        let row: (String, String, String) =
+            sqlx::query_as("SELECT author, images, tags FROM test_bbcindonesia_results")
+                .fetch_one(p.results.get_pool())
+                .await
+                .unwrap();
+        assert_eq!(
+            row,
+            (
+                "author".to_string(),
+                r#"[{"url":"https://ichef.bbci.co.uk/1.jpg","caption":"caption"}]"#.to_string(),
+                "t1".to_string(),
+            )
+        );
+
+        fs::remove_file("test_bbcindonesia.db").await.unwrap();
+    }
+}
@@ -0,0 +1,48 @@
+mod crawler;
+#[cfg(feature = "sqlite")]
+mod data;
+
+pub use crawler::BbcIndonesiaCrawler;
+#[cfg(feature = "sqlite")]
+pub use data::{BbcIndonesiaArticleTable, BbcIndonesiaData};
+
+use crate::Article;
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BbcImage {
+    pub url: Option<String>,
+    pub caption: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BbcIndonesiaArticle {
+    pub title: Option<String>,
+    pub published_date: Option<DateTime<FixedOffset>>,
+    pub description: Option<String>,
+    pub thumbnail_url: Option<String>,
+    /// Only set for bylined articles, most pages are credited to BBC News Indonesia itself
+    pub author: Option<String>,
+    /// Images of the body, in order
+    pub images: Vec<BbcImage>,
+    /// Topic tags
+    pub tags: Vec<String>,
+    /// Subheadings are kept in order as "## Subheading" paragraphs
+    pub paragraphs: Vec<String>,
+}
+
+impl Article for BbcIndonesiaArticle {
+    fn get_paragraphs(&self) -> &[String] {
+        self.paragraphs.as_slice()
+    }
+
+    fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    fn get_published_date(&self) -> Option<DateTime<FixedOffset>> {
+        self.published_date
+    }
+}
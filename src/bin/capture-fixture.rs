@@ -0,0 +1,142 @@
+use clap::Parser;
+use indonesian_media_crawler::antara::AntaraCrawler;
+use indonesian_media_crawler::bbcindonesia::BbcIndonesiaCrawler;
+use indonesian_media_crawler::cnbcindonesia::CnbcIndonesiaCrawler;
+use indonesian_media_crawler::detik::DetikCrawler;
+use indonesian_media_crawler::idntimes::IdnTimesCrawler;
+use indonesian_media_crawler::jawapos::{JawaPosCrawler, RADAR_HOSTS};
+use indonesian_media_crawler::kompas::KompasCrawler;
+use indonesian_media_crawler::kontan::KontanCrawler;
+use indonesian_media_crawler::kumparan::KumparanCrawler;
+use indonesian_media_crawler::liputan6::Liputan6Crawler;
+use indonesian_media_crawler::mediaindonesia::MediaIndonesiaCrawler;
+use indonesian_media_crawler::merdeka::MerdekaCrawler;
+use indonesian_media_crawler::pikiranrakyat::PikiranRakyatCrawler;
+use indonesian_media_crawler::republika::RepublikaCrawler;
+use indonesian_media_crawler::sindonews::SindonewsCrawler;
+use indonesian_media_crawler::tempo::TempoCrawler;
+use indonesian_media_crawler::tirto::TirtoCrawler;
+use indonesian_media_crawler::tribunnews::TribunnewsCrawler;
+use indonesian_media_crawler::{charset, fixture, http_client, Site};
+use reqwest::Url;
+use std::path::{Path, PathBuf};
+
+/// Save a page under tests/htmls together with its golden .expected.json
+#[derive(Parser)]
+struct Cli {
+    /// Page to capture
+    #[arg(required_unless_present = "update")]
+    url: Option<String>,
+
+    /// Fixture name, written as <dir>/<name>.html and <dir>/<name>.expected.json
+    #[arg(required_unless_present = "update")]
+    name: Option<String>,
+
+    /// Keep <script> tags (JSON-LD is always kept)
+    #[arg(long)]
+    keep_scripts: bool,
+
+    /// Regenerate the .expected.json of every existing fixture instead of capturing
+    #[arg(long, conflicts_with_all = ["url", "name"])]
+    update: bool,
+
+    /// Crawler producing the .expected.json
+    #[arg(long, value_enum, default_value_t = Site::Detik)]
+    site: Site,
+
+    /// Defaults to tests/htmls for detik and tests/htmls/<site> for the others
+    #[arg(long)]
+    dir: Option<PathBuf>,
+}
+
+fn default_dir(site: Site) -> PathBuf {
+    match site {
+        Site::Detik => PathBuf::from("tests/htmls"),
+        site => PathBuf::from("tests/htmls").join(site.name()),
+    }
+}
+
+fn expected_json(site: Site, url: &Url, html: &str) -> serde_json::Result<String> {
+    match site {
+        Site::Detik => fixture::expected_json(&DetikCrawler::new(), url, html),
+        Site::Kompas => fixture::expected_json(&KompasCrawler::new(), url, html),
+        Site::Tempo => fixture::expected_json(&TempoCrawler::new(), url, html),
+        Site::Liputan6 => fixture::expected_json(&Liputan6Crawler::new(), url, html),
+        Site::Tribunnews => fixture::expected_json(&TribunnewsCrawler::new(), url, html),
+        Site::Antara => fixture::expected_json(&AntaraCrawler::new(), url, html),
+        Site::Republika => fixture::expected_json(&RepublikaCrawler::new(), url, html),
+        Site::Kumparan => fixture::expected_json(&KumparanCrawler::new(), url, html),
+        Site::Tirto => fixture::expected_json(&TirtoCrawler::new(), url, html),
+        Site::Merdeka => fixture::expected_json(&MerdekaCrawler::new(), url, html),
+        Site::Sindonews => fixture::expected_json(&SindonewsCrawler::new(), url, html),
+        Site::CnbcIndonesia => fixture::expected_json(&CnbcIndonesiaCrawler::new(), url, html),
+        Site::Kontan => fixture::expected_json(&KontanCrawler::new(), url, html),
+        Site::IdnTimes => fixture::expected_json(&IdnTimesCrawler::new(), url, html),
+        Site::BbcIndonesia => fixture::expected_json(&BbcIndonesiaCrawler::new(), url, html),
+        Site::JawaPos => fixture::expected_json(
+            &JawaPosCrawler::new().with_radar_hosts(RADAR_HOSTS),
+            url,
+            html,
+        ),
+        Site::PikiranRakyat => fixture::expected_json(&PikiranRakyatCrawler::new(), url, html),
+        Site::MediaIndonesia => fixture::expected_json(&MediaIndonesiaCrawler::new(), url, html),
+    }
+}
+
+fn write_expected(
+    site: Site,
+    url: &Url,
+    html_path: &Path,
+    html: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let expected_path = html_path.with_extension("expected.json");
+    std::fs::write(&expected_path, expected_json(site, url, html)?)?;
+    println!("Wrote {}", expected_path.display());
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    let dir = cli.dir.unwrap_or_else(|| default_dir(cli.site));
+
+    if cli.update {
+        let mut fixtures = std::fs::read_dir(&dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        fixtures.retain(|p| p.extension().is_some_and(|ext| ext == "html"));
+        fixtures.sort();
+        let urls = fixture::urls(&dir)?;
+        for path in fixtures {
+            let name = path.file_stem().unwrap_or_default().to_string_lossy();
+            let Some(url) = urls.get(name.as_ref()) else {
+                return Err(format!("No url for {} in {}", name, fixture::URLS_FILE).into());
+            };
+            let html = std::fs::read_to_string(&path)?;
+            write_expected(cli.site, url, &path, &html)?;
+        }
+        return Ok(());
+    }
+
+    let (Some(url), Some(name)) = (cli.url, cli.name) else {
+        unreachable!("clap requires url and name without --update")
+    };
+
+    let url = Url::parse(&url)?;
+    let response = http_client()?
+        .get(url.clone())
+        .send()
+        .await?
+        .error_for_status()?;
+    let html = charset::decode_response(response).await?.text;
+    let html = fixture::sanitize(&html, !cli.keep_scripts);
+
+    std::fs::create_dir_all(&dir)?;
+    let html_path = dir.join(format!("{}.html", name));
+    std::fs::write(&html_path, &html)?;
+    println!("Wrote {}", html_path.display());
+    fixture::save_url(&dir, &name, &url)?;
+    write_expected(cli.site, &url, &html_path, &html)?;
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+//! Crawling within a bandwidth budget, like the monthly transfer cap of a VPS: the crawl stops
+//! once it received `RunOptions::max_bytes`, and its requests are spaced out while it receives
+//! more than `RunOptions::bytes_per_hour`, the delays growing with how far over it is.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+/// The bytes received over this long make the hourly rate.
+pub const RATE_WINDOW: Duration = Duration::from_secs(3600);
+
+/// The bytes received at the start of a crawl are spread over this long at least, so its
+/// first pages don't make for a huge rate.
+pub const MIN_RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// The bytes received lately, against a target rate.
+#[derive(Debug)]
+pub(crate) struct Bandwidth {
+    bytes_per_hour: u64,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    started: Instant,
+    /// The bytes received within `RATE_WINDOW`, oldest first
+    received: VecDeque<(Instant, u64)>,
+    bytes: u64,
+    over: bool,
+}
+
+impl Bandwidth {
+    pub(crate) fn new(bytes_per_hour: u64, now: Instant) -> Self {
+        Bandwidth {
+            bytes_per_hour: bytes_per_hour.max(1),
+            inner: Mutex::new(Inner {
+                started: now,
+                received: VecDeque::new(),
+                bytes: 0,
+                over: false,
+            }),
+        }
+    }
+
+    pub(crate) fn bytes_per_hour(&self) -> u64 {
+        self.bytes_per_hour
+    }
+
+    /// Add `bytes` received at `now`. Whether the rate went over the target, or back under
+    /// it, `None` when it's still on the same side.
+    pub(crate) fn record(&self, bytes: u64, now: Instant) -> Option<bool> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.received.push_back((now, bytes));
+        inner.bytes += bytes;
+        let over = inner.hourly_rate(now) > self.bytes_per_hour;
+        (over != inner.over).then(|| {
+            inner.over = over;
+            over
+        })
+    }
+
+    /// The bytes received per hour lately, see `RATE_WINDOW`.
+    pub(crate) fn hourly_rate(&self, now: Instant) -> u64 {
+        self.inner.lock().unwrap().hourly_rate(now)
+    }
+
+    /// What the delay between two requests is multiplied by at `now`: the rate over the
+    /// target, 1 under it.
+    pub(crate) fn slowdown(&self, now: Instant) -> f64 {
+        let rate = self.hourly_rate(now);
+        (rate as f64 / self.bytes_per_hour as f64).max(1.0)
+    }
+}
+
+impl Inner {
+    fn hourly_rate(&mut self, now: Instant) -> u64 {
+        while let Some(&(at, bytes)) = self.received.front() {
+            if now.duration_since(at) <= RATE_WINDOW {
+                break;
+            }
+            self.received.pop_front();
+            self.bytes -= bytes;
+        }
+        let window = now
+            .duration_since(self.started)
+            .clamp(MIN_RATE_WINDOW, RATE_WINDOW);
+        (self.bytes as f64 * RATE_WINDOW.as_secs_f64() / window.as_secs_f64()) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hourly_rate() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let bandwidth = Bandwidth::new(1_200_000, start);
+
+        // Spread over a minute at the start
+        assert_eq!(bandwidth.record(10_000, at(1)), None);
+        assert_eq!(bandwidth.hourly_rate(at(1)), 600_000);
+        assert_eq!(bandwidth.slowdown(at(1)), 1.0);
+        assert_eq!(bandwidth.record(20_000, at(2)), Some(true));
+        assert_eq!(bandwidth.slowdown(at(2)), 1.5);
+
+        // Then over the time crawled, up to an hour
+        assert_eq!(bandwidth.hourly_rate(at(600)), 180_000);
+        assert_eq!(bandwidth.record(0, at(600)), Some(false));
+        assert_eq!(bandwidth.record(60_000, at(3000)), None);
+        assert_eq!(bandwidth.hourly_rate(at(3600)), 90_000);
+        // The first bytes out of the window
+        assert_eq!(bandwidth.hourly_rate(at(3603)), 60_000);
+    }
+}
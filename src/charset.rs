@@ -0,0 +1,196 @@
+//! Decoding fetched bodies to UTF-8 from the charset they're served in, for the older pages
+//! still served as ISO-8859-1 or declaring the wrong charset.
+
+use encoding_rs::{DecoderResult, Encoding, UTF_8};
+use lazy_regex::regex;
+
+/// Bytes at the start of a body searched for a `<meta>` charset, like browsers do.
+const META_PRESCAN: usize = 1024;
+
+/// Replacement characters a body may get from decoding before it's `is_garbled`, whatever
+/// its length.
+const MIN_GARBLED_REPLACEMENTS: usize = 8;
+
+/// Share of the characters of a body that may be replacement characters from decoding
+/// before it's `is_garbled`.
+const MAX_REPLACEMENT_RATIO: f64 = 0.01;
+
+/// A body decoded to UTF-8.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoded {
+    pub text: String,
+    /// The encoding the body was decoded from
+    pub encoding: &'static Encoding,
+    /// Malformed sequences replaced with `U+FFFD`, replacement characters the body already
+    /// had aren't counted
+    pub replacements: usize,
+}
+
+impl Decoded {
+    /// Whether the body was in another encoding than UTF-8.
+    pub fn is_transcoded(&self) -> bool {
+        self.encoding != UTF_8
+    }
+
+    /// Whether so much of the body failed to decode that its text isn't worth crawling.
+    pub fn is_garbled(&self) -> bool {
+        self.replacements >= MIN_GARBLED_REPLACEMENTS
+            && self.replacements as f64 > self.text.chars().count() as f64 * MAX_REPLACEMENT_RATIO
+    }
+}
+
+/// Decode `body`, served with the `content_type` header. Its encoding is the one of its BOM,
+/// else UTF-8 when it's valid UTF-8, else the first charset other than UTF-8 declared by
+/// `content_type` or a `<meta>` tag, else UTF-8 with its malformed sequences replaced.
+///
+/// A body declared as UTF-8 that isn't, but has a `<meta>` tag declaring its actual charset,
+/// is decoded from that charset.
+pub fn decode(body: &[u8], content_type: Option<&str>) -> Decoded {
+    if let Some((encoding, bom)) = Encoding::for_bom(body) {
+        return decode_with(encoding, &body[bom..]);
+    }
+    if std::str::from_utf8(body).is_ok() {
+        return decode_with(UTF_8, body);
+    }
+    let declared = content_type
+        .and_then(header_charset)
+        .into_iter()
+        .chain(meta_charset(body))
+        .find(|encoding| *encoding != UTF_8);
+    decode_with(declared.unwrap_or(UTF_8), body)
+}
+
+/// The body of `response` decoded like the crawler decodes the pages it fetches, for tools
+/// reading pages with `http_client`.
+pub async fn decode_response(response: reqwest::Response) -> reqwest::Result<Decoded> {
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    Ok(decode(&response.bytes().await?, content_type.as_deref()))
+}
+
+/// The charset of a `Content-Type` header value.
+pub fn header_charset(content_type: &str) -> Option<&'static Encoding> {
+    let caps = regex!(r#"(?i)charset\s*=\s*["']?([\w:.-]+)"#).captures(content_type)?;
+    label(&caps[1])
+}
+
+/// The charset declared by a `<meta charset>` or `<meta http-equiv="Content-Type">` tag at the
+/// start of `body`.
+pub fn meta_charset(body: &[u8]) -> Option<&'static Encoding> {
+    let head = String::from_utf8_lossy(&body[..body.len().min(META_PRESCAN)]);
+    let caps =
+        regex!(r#"(?i)<meta\b[^>]*?charset\s*=\s*["']?([\w:.-]+)"#).captures(head.as_ref())?;
+    label(&caps[1])
+}
+
+/// The encoding of a charset label. UTF-16 labels are taken as UTF-8 like browsers do, a
+/// page declaring them without a BOM being ASCII compatible.
+fn label(label: &str) -> Option<&'static Encoding> {
+    Encoding::for_label(label.as_bytes()).map(|encoding| {
+        if encoding == encoding_rs::UTF_16LE || encoding == encoding_rs::UTF_16BE {
+            UTF_8
+        } else {
+            encoding
+        }
+    })
+}
+
+fn decode_with(encoding: &'static Encoding, body: &[u8]) -> Decoded {
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut text = String::with_capacity(body.len());
+    let mut replacements = 0;
+    let mut input = body;
+    loop {
+        let (result, read) = decoder.decode_to_string_without_replacement(input, &mut text, true);
+        input = &input[read..];
+        match result {
+            DecoderResult::InputEmpty => break,
+            DecoderResult::OutputFull => text.reserve(
+                decoder
+                    .max_utf8_buffer_length_without_replacement(input.len())
+                    .unwrap_or(input.len() * 3)
+                    .max(4),
+            ),
+            DecoderResult::Malformed(..) => {
+                replacements += 1;
+                text.push(char::REPLACEMENT_CHARACTER);
+            }
+        }
+    }
+    Decoded {
+        text,
+        encoding,
+        replacements,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::WINDOWS_1252;
+
+    #[test]
+    fn charsets_declared() {
+        assert_eq!(
+            header_charset("text/html; charset=ISO-8859-1"),
+            Some(WINDOWS_1252)
+        );
+        assert_eq!(header_charset("text/html;charset=\"utf-8\""), Some(UTF_8));
+        assert_eq!(header_charset("text/html"), None);
+        assert_eq!(header_charset("text/html; charset=klingon"), None);
+        assert_eq!(
+            meta_charset(br#"<html><head><meta charset="latin1">"#),
+            Some(WINDOWS_1252)
+        );
+        assert_eq!(
+            meta_charset(
+                br#"<meta http-equiv="Content-Type" content="text/html; charset=windows-1252">"#
+            ),
+            Some(WINDOWS_1252)
+        );
+        assert_eq!(meta_charset(b"<meta name=\"charset\">"), None);
+    }
+
+    #[test]
+    fn latin1_transcoded() {
+        let decoded = decode(b"Caf\xe9 di Jakarta", Some("text/html; charset=iso-8859-1"));
+        assert_eq!(decoded.text, "Café di Jakarta");
+        assert!(decoded.is_transcoded());
+        // What ISO-8859-1 labels stand for
+        assert_eq!(decoded.encoding, WINDOWS_1252);
+        assert_eq!(decoded.replacements, 0);
+    }
+
+    #[test]
+    fn valid_utf8_kept() {
+        // Declared as latin-1, but decoding it so would turn é into Ã©
+        let decoded = decode("Café".as_bytes(), Some("text/html; charset=iso-8859-1"));
+        assert_eq!(decoded.text, "Café");
+        assert!(!decoded.is_transcoded());
+
+        let decoded = decode(b"\xef\xbb\xbfCaf\xc3\xa9", None);
+        assert_eq!(decoded.text, "Café");
+    }
+
+    #[test]
+    fn replacements_counted() {
+        // Already there, not counted
+        let decoded = decode("\u{fffd} ok".as_bytes(), None);
+        assert_eq!(decoded.replacements, 0);
+
+        let decoded = decode(b"Caf\xe9 \xff", Some("text/html; charset=utf-8"));
+        assert_eq!(decoded.text, "Caf\u{fffd} \u{fffd}");
+        assert_eq!(decoded.replacements, 2);
+        assert!(!decoded.is_garbled());
+
+        let garbled = decode(&[0xff; 64], None);
+        assert_eq!(garbled.replacements, 64);
+        assert!(garbled.is_garbled());
+        let mut long = vec![b'a'; 10_000];
+        long.extend([0xff; 8]);
+        assert!(!decode(&long, None).is_garbled());
+    }
+}
@@ -0,0 +1,217 @@
+use crate::charset;
+use crate::dns::{CachedResolver, DnsCache};
+use crate::{utils, CookieJar, CrawlerError, ResponseMeta, RunOptions};
+use chrono::DateTime;
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LANGUAGE,
+    CONTENT_TYPE, LAST_MODIFIED, RETRY_AFTER,
+};
+use reqwest::StatusCode;
+use std::io::{ErrorKind, Write};
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
+
+const USER_AGENT: &str = concat!("indonesian-media-crawler/", env!("CARGO_PKG_VERSION"));
+const TIMEOUT: Duration = Duration::from_secs(30);
+const X_ROBOTS_TAG: &str = "x-robots-tag";
+
+fn builder() -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(TIMEOUT)
+}
+
+/// The HTTP client used for every fetch, so tools see pages the same way the crawler does.
+pub fn http_client() -> reqwest::Result<reqwest::Client> {
+    builder().cookie_store(true).build()
+}
+
+/// The client of a crawl: `http_client` with the default headers of `options`, keeping its
+/// cookies in `cookies`, through `proxy` or else resolving hosts with `dns`. It asks for
+/// gzip or brotli bodies but leaves them compressed, `get_text` decompresses them to count
+/// both sizes.
+pub(crate) fn run_client(
+    options: &RunOptions,
+    cookies: Arc<CookieJar>,
+    proxy: Option<reqwest::Proxy>,
+    dns: Option<Arc<DnsCache>>,
+) -> reqwest::Result<reqwest::Client> {
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT_ENCODING, HeaderValue::from_static("gzip, br"));
+    headers.extend(options.default_headers.clone());
+    let builder = builder()
+        .default_headers(headers)
+        .no_gzip()
+        .no_brotli()
+        .cookie_provider(cookies);
+    match (proxy, dns) {
+        (Some(proxy), _) => builder.proxy(proxy),
+        (None, Some(dns)) => builder.dns_resolver(Arc::new(CachedResolver(dns))),
+        (None, None) => builder,
+    }
+    .build()
+}
+
+/// A body fetched by `get_text`.
+#[derive(Debug)]
+pub(crate) struct Body {
+    pub(crate) text: String,
+    /// Whether the body was decoded to `text` from another charset than UTF-8
+    pub(crate) transcoded: bool,
+    /// Whether so much of the body failed to decode that `text` isn't worth crawling, see
+    /// `charset::Decoded::is_garbled`
+    pub(crate) garbled: bool,
+    /// Bytes read from the connection, compressed or not
+    pub(crate) wire_bytes: u64,
+    /// Bytes once decompressed
+    pub(crate) decoded_bytes: u64,
+    /// The `X-Robots-Tag` headers, joined with commas
+    pub(crate) robots_tag: Option<String>,
+    /// Time from the request to the response headers
+    pub(crate) first_byte: Duration,
+    pub(crate) response: ResponseMeta,
+}
+
+/// The body of `url`, decompressed as it's read and decoded from its charset, see
+/// `charset::decode`. Fails with `CrawlerError::TooLarge` as soon
+/// as it's larger than `max_size` once decompressed, with `CrawlerError::ServerStatus` on a
+/// 5xx response and with `CrawlerError::TooManyRequests` on a 429.
+pub(crate) async fn get_text(
+    client: &reqwest::Client,
+    url: &str,
+    max_size: Option<u64>,
+) -> Result<Body, CrawlerError> {
+    let started = Instant::now();
+    let mut response = client.get(url).send().await?;
+    let first_byte = started.elapsed();
+    if response.status().is_server_error() {
+        return Err(CrawlerError::ServerStatus(response.status().as_u16()));
+    }
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+        // Only the delay in seconds, sites sending the HTTP date form are rare
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok())
+            .map(Duration::from_secs);
+        return Err(CrawlerError::TooManyRequests(retry_after));
+    }
+    let meta = ResponseMeta {
+        final_url: response.url().clone(),
+        status: response.status().as_u16(),
+        last_modified: header(response.headers(), LAST_MODIFIED)
+            .and_then(|value| DateTime::parse_from_rfc2822(value).ok()),
+        content_language: header(response.headers(), CONTENT_LANGUAGE).map(str::to_string),
+        fetched_at: utils::get_now(),
+    };
+    let robots_tag = response
+        .headers()
+        .get_all(X_ROBOTS_TAG)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect::<Vec<_>>();
+    let robots_tag = (!robots_tag.is_empty()).then(|| robots_tag.join(", "));
+    let content_type = header(response.headers(), CONTENT_TYPE).map(str::to_string);
+    let limit = max_size.unwrap_or(u64::MAX);
+    let sink = Limited { buf: vec![], limit };
+    let mut decoder = match response
+        .headers()
+        .get(CONTENT_ENCODING)
+        .and_then(|encoding| encoding.to_str().ok())
+        .map(|encoding| encoding.trim().to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("gzip") | Some("x-gzip") => Decoder::Gzip(flate2::write::GzDecoder::new(sink)),
+        Some("br") => Decoder::Brotli(Box::new(brotli::DecompressorWriter::new(sink, 4096))),
+        _ => Decoder::Identity(sink),
+    };
+
+    let mut wire_bytes = 0;
+    while let Some(chunk) = response.chunk().await? {
+        wire_bytes += chunk.len() as u64;
+        decoder.write_all(&chunk).map_err(|e| too_large(e, limit))?;
+    }
+    let buf = decoder.finish().map_err(|e| too_large(e, limit))?;
+    let decoded = charset::decode(&buf, content_type.as_deref());
+    Ok(Body {
+        decoded_bytes: buf.len() as u64,
+        transcoded: decoded.is_transcoded(),
+        garbled: decoded.is_garbled(),
+        text: decoded.text,
+        wire_bytes,
+        robots_tag,
+        first_byte,
+        response: meta,
+    })
+}
+
+/// The value of the header `name`, when it's text.
+fn header(headers: &HeaderMap, name: HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+/// `CrawlerError::TooLarge` for the error of `Limited`, else an IO error.
+fn too_large(e: std::io::Error, limit: u64) -> CrawlerError {
+    if e.get_ref().is_some_and(|inner| inner.is::<LimitReached>()) {
+        CrawlerError::TooLarge(limit)
+    } else {
+        e.into()
+    }
+}
+
+enum Decoder {
+    Identity(Limited),
+    Gzip(flate2::write::GzDecoder<Limited>),
+    // Boxed, its decoder state is much larger than the others
+    Brotli(Box<brotli::DecompressorWriter<Limited>>),
+}
+
+impl Decoder {
+    fn write_all(&mut self, chunk: &[u8]) -> std::io::Result<()> {
+        match self {
+            Decoder::Identity(sink) => sink.write_all(chunk),
+            Decoder::Gzip(decoder) => decoder.write_all(chunk),
+            Decoder::Brotli(decoder) => decoder.write_all(chunk),
+        }
+    }
+
+    /// The decompressed body, an error when the stream was cut short.
+    fn finish(self) -> std::io::Result<Vec<u8>> {
+        let sink = match self {
+            Decoder::Identity(sink) => sink,
+            Decoder::Gzip(decoder) => decoder.finish()?,
+            Decoder::Brotli(mut decoder) => {
+                decoder.flush()?;
+                decoder.into_inner().map_err(|_| {
+                    std::io::Error::new(ErrorKind::InvalidData, "truncated brotli stream")
+                })?
+            }
+        };
+        Ok(sink.buf)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("body larger than the limit")]
+struct LimitReached;
+
+/// A buffer refusing to grow past `limit` bytes.
+struct Limited {
+    buf: Vec<u8>,
+    limit: u64,
+}
+
+impl Write for Limited {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        if (self.buf.len() + data.len()) as u64 > self.limit {
+            return Err(std::io::Error::other(LimitReached));
+        }
+        self.buf.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
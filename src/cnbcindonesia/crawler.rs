@@ -0,0 +1,211 @@
+use crate::{
+    cnbcindonesia::CnbcIndonesiaArticle, json_ld, text, utils, CrawlContext, Crawler,
+    CrawlerResult, ExtractedLink,
+};
+use chrono::DateTime;
+use itertools::Itertools;
+use lazy_regex::regex;
+use lazy_static::lazy_static;
+use scraper::{ElementRef, Html, Selector};
+
+const E: &str = "Invalid selector";
+lazy_static! {
+    static ref OG_TYPE: Selector = Selector::parse(r#"meta[property="og:type"]"#).expect(E);
+    static ref TITLE: Selector = Selector::parse(r#"meta[property="og:title"]"#).expect(E);
+    static ref DESCRIPTION: Selector =
+        Selector::parse(r#"meta[property="og:description"]"#).expect(E);
+    static ref THUMBNAIL: Selector = Selector::parse(r#"meta[property="og:image"]"#).expect(E);
+    static ref CANONICAL: Selector = Selector::parse(r#"link[rel="canonical"]"#).expect(E);
+    static ref PUBLISHED_TIME: Selector =
+        Selector::parse(r#"meta[property="article:published_time"]"#).expect(E);
+    static ref AUTHOR: Selector = Selector::parse(r#"meta[name="author"]"#).expect(E);
+    static ref TAG: Selector = Selector::parse(".detail_tag a").expect(E);
+    static ref BODY: Selector = Selector::parse(".detail_text").expect(E);
+    static ref BLOCK: Selector = Selector::parse("p, table").expect(E);
+    static ref ROW: Selector = Selector::parse("tr").expect(E);
+    static ref CELL: Selector = Selector::parse("th, td").expect(E);
+    static ref NESTED_TABLE: Selector = Selector::parse("table table").expect(E);
+}
+
+const SKIPPED_PREFIXES: [&str; 2] = ["Baca:", "Saksikan video"];
+
+#[derive(Debug, Clone)]
+pub struct CnbcIndonesiaCrawler {
+    allowlist: utils::HostAllowlist,
+}
+
+impl Default for CnbcIndonesiaCrawler {
+    fn default() -> Self {
+        CnbcIndonesiaCrawler {
+            allowlist: utils::HostAllowlist::new(["cnbcindonesia.com"]),
+        }
+    }
+}
+
+impl CnbcIndonesiaCrawler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    utils::allowlist_builders!("cnbcindonesia.com");
+}
+
+/// The section of `cnbcindonesia.com/<category>/20221214101010-17-1/slug`.
+fn category(url: &reqwest::Url) -> Option<String> {
+    url.path_segments()?
+        .next()
+        .filter(|s| !s.is_empty() && !s.chars().all(|c| c.is_ascii_digit()))
+        .map(ToString::to_string)
+}
+
+/// Remove the "[Gambas:Video CNBC]" and "[Gambas:Highchart]" placeholders left where video
+/// and chart embeds are rendered.
+fn strip_embeds(paragraph: &str) -> Option<String> {
+    let p = regex!(r"\[Gambas:[^\]]*\]").replace_all(paragraph, "");
+    Some(p.trim().to_string()).filter(|p| !p.is_empty())
+}
+
+/// One "cell | cell" line per row. Tables with nested tables or spanning cells are layout
+/// rather than data and are dropped.
+fn flatten_table(table: &ElementRef) -> Option<String> {
+    let is_simple = table.select(&NESTED_TABLE).next().is_none()
+        && table.select(&CELL).all(|cell| {
+            cell.value().attr("colspan").is_none() && cell.value().attr("rowspan").is_none()
+        });
+    if !is_simple {
+        return None;
+    }
+    let lines: Vec<String> = table
+        .select(&ROW)
+        .map(|row| {
+            row.select(&CELL)
+                .map(|cell| cell.text().collect::<String>().split_whitespace().join(" "))
+                .join(" | ")
+        })
+        .filter(|line| !line.replace('|', "").trim().is_empty())
+        .collect();
+    Some(lines.join("\n")).filter(|s| !s.is_empty())
+}
+
+fn is_in_table(el: &ElementRef) -> bool {
+    el.ancestors()
+        .filter_map(ElementRef::wrap)
+        .any(|a| a.value().name() == "table")
+}
+
+impl Crawler for CnbcIndonesiaCrawler {
+    type Document = CnbcIndonesiaArticle;
+
+    fn can_be_scrapped(&self, doc: &Html) -> bool {
+        let is_article = json_ld::article(doc).is_some()
+            || utils::meta_content(doc, &OG_TYPE).is_some_and(|t| t == "article");
+        is_article && doc.select(&BODY).next().is_some()
+    }
+
+    fn extract_links(&self, ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        utils::links(doc, ctx.url, |url| self.allowlist.allows(url))
+    }
+
+    fn crawl(&self, ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        let links = self.extract_urls(ctx, doc);
+
+        if !self.can_be_scrapped(doc) {
+            return CrawlerResult::Links(links);
+        }
+
+        let ld = json_ld::article(doc);
+        let ld_string = |key: &str| ld.as_ref().and_then(|ld| json_ld::string(ld, key));
+
+        let published_date = ld_string("datePublished")
+            .or_else(|| utils::meta_content(doc, &PUBLISHED_TIME))
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok());
+
+        let canonical = doc
+            .select(&CANONICAL)
+            .next()
+            .and_then(|el| el.value().attr("href"))
+            .and_then(|s| reqwest::Url::parse(s.trim()).ok());
+
+        let author = ld
+            .as_ref()
+            .map(|ld| json_ld::names(ld, "author"))
+            .filter(|names| !names.is_empty())
+            .map(|names| names.join(", "))
+            .or_else(|| utils::meta_content(doc, &AUTHOR));
+
+        let tags = doc
+            .select(&TAG)
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        let mut paragraphs = vec![];
+        if let Some(body) = doc.select(&BODY).next() {
+            for el in body.select(&BLOCK).filter(|el| !is_in_table(el)) {
+                let p = if el.value().name() == "table" {
+                    flatten_table(&el)
+                } else {
+                    text::clean_paragraph(&el.inner_html()).and_then(|p| strip_embeds(&p))
+                };
+                if let Some(p) = p {
+                    if !text::starts_with_any(&p, &SKIPPED_PREFIXES) {
+                        paragraphs.push(p);
+                    }
+                }
+            }
+        }
+        paragraphs.dedup();
+
+        let article = CnbcIndonesiaArticle {
+            title: utils::meta_content(doc, &TITLE).or_else(|| ld_string("headline")),
+            published_date,
+            description: utils::meta_content(doc, &DESCRIPTION)
+                .or_else(|| ld_string("description")),
+            thumbnail_url: utils::meta_content(doc, &THUMBNAIL),
+            author,
+            category: category(canonical.as_ref().unwrap_or(ctx.url)),
+            tags,
+            paragraphs,
+        };
+        CrawlerResult::document_and_links(article, links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_table(html: &str) -> Option<String> {
+        let doc = Html::parse_fragment(html);
+        let table = doc
+            .select(&Selector::parse("table").unwrap())
+            .next()
+            .unwrap();
+        flatten_table(&table)
+    }
+
+    #[test]
+    fn tables() {
+        assert_eq!(
+            first_table(
+                "<table><tr><th>Saham</th><th>Harga</th></tr>\
+                 <tr><td> BBCA </td><td>8.550</td></tr><tr><td></td><td></td></tr></table>"
+            )
+            .as_deref(),
+            Some("Saham | Harga\nBBCA | 8.550")
+        );
+        assert_eq!(
+            first_table("<table><tr><td colspan=\"2\">Judul</td></tr></table>"),
+            None
+        );
+    }
+
+    #[test]
+    fn embeds() {
+        assert_eq!(strip_embeds("[Gambas:Video CNBC]"), None);
+        assert_eq!(
+            strip_embeds("IHSG ditutup menguat. [Gambas:Highchart]").as_deref(),
+            Some("IHSG ditutup menguat.")
+        );
+    }
+}
@@ -0,0 +1,40 @@
+mod crawler;
+#[cfg(feature = "sqlite")]
+mod data;
+
+pub use crawler::CnbcIndonesiaCrawler;
+#[cfg(feature = "sqlite")]
+pub use data::{CnbcIndonesiaArticleTable, CnbcIndonesiaData};
+
+use crate::Article;
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CnbcIndonesiaArticle {
+    pub title: Option<String>,
+    pub published_date: Option<DateTime<FixedOffset>>,
+    pub description: Option<String>,
+    pub thumbnail_url: Option<String>,
+    pub author: Option<String>,
+    /// Section from the url: `market`, `news`, `tech`, `lifestyle`, ...
+    pub category: Option<String>,
+    pub tags: Vec<String>,
+    /// Simple tables are flattened into one paragraph of "cell | cell" lines
+    pub paragraphs: Vec<String>,
+}
+
+impl Article for CnbcIndonesiaArticle {
+    fn get_paragraphs(&self) -> &[String] {
+        self.paragraphs.as_slice()
+    }
+
+    fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    fn get_published_date(&self) -> Option<DateTime<FixedOffset>> {
+        self.published_date
+    }
+}
@@ -0,0 +1,251 @@
+//! The paragraphs of the results stored compressed, see `SqliteStorage::with_compression`.
+//!
+//! They're compressed with zstd and a dictionary trained on a sample of the articles already
+//! stored, the articles of a site sharing much of their wording, without one while there are
+//! too few to train it. The `compression` column of a row records how its paragraphs were
+//! stored, so a table holds plain and compressed rows alike and reads both.
+
+use sqlx::query::Query;
+use sqlx::sqlite::{SqliteArguments, SqliteRow};
+use sqlx::{Row, Sqlite};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Read};
+
+/// The codec recorded in the `compression` column, followed by `:<id>` of its dictionary.
+pub const CODEC: &str = "zstd";
+
+/// The zstd level, its default.
+pub const LEVEL: i32 = 3;
+
+/// The maximum size of a trained dictionary, in bytes.
+pub const DICTIONARY_SIZE: usize = 64 * 1024;
+
+/// The articles a dictionary is trained on at most.
+pub const DICTIONARY_SAMPLES: u32 = 2000;
+
+/// The articles needed to train a dictionary, compressing without one below.
+pub const MIN_SAMPLES: usize = 100;
+
+/// How the paragraphs of a row are stored and read back.
+#[derive(Clone, Default)]
+pub struct ParagraphCodec {
+    compressing: bool,
+    /// The dictionary the new rows are compressed with, the latest
+    dictionary: Option<i64>,
+    dictionaries: HashMap<i64, Vec<u8>>,
+}
+
+impl fmt::Debug for ParagraphCodec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut dictionaries: Vec<_> = self.dictionaries.keys().collect();
+        dictionaries.sort();
+        f.debug_struct("ParagraphCodec")
+            .field("compressing", &self.compressing)
+            .field("dictionary", &self.dictionary)
+            .field("dictionaries", &dictionaries)
+            .finish()
+    }
+}
+
+/// The paragraphs of a row as they're written to its `paragraphs` and `compression` columns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stored {
+    Text(String),
+    /// The compressed paragraphs and their `compression` marker
+    Compressed(Vec<u8>, String),
+}
+
+impl Stored {
+    /// Bind the `paragraphs` then the `compression` column to `query`.
+    pub(crate) fn bind<'q>(
+        self,
+        query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    ) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+        match self {
+            Stored::Text(text) => query.bind(text).bind(None::<String>),
+            Stored::Compressed(bytes, marker) => query.bind(bytes).bind(Some(marker)),
+        }
+    }
+}
+
+impl ParagraphCodec {
+    /// Reading the rows compressed with any of `dictionaries`, by id, and storing the new ones
+    /// as text.
+    pub fn new(dictionaries: HashMap<i64, Vec<u8>>) -> Self {
+        ParagraphCodec {
+            compressing: false,
+            dictionary: dictionaries.keys().max().copied(),
+            dictionaries,
+        }
+    }
+
+    /// Like this one, compressing the new rows with its latest dictionary if any.
+    pub fn compressing(self) -> Self {
+        ParagraphCodec {
+            compressing: true,
+            ..self
+        }
+    }
+
+    pub fn is_compressing(&self) -> bool {
+        self.compressing
+    }
+
+    /// The id of the latest dictionary, the one compressing the new rows.
+    pub fn dictionary(&self) -> Option<i64> {
+        self.dictionary
+    }
+
+    /// How `paragraphs` are stored, as text unless compressing. Empty ones are kept as text,
+    /// so they're still found empty.
+    pub fn encode(&self, paragraphs: String) -> io::Result<Stored> {
+        if !self.compressing || paragraphs.is_empty() {
+            return Ok(Stored::Text(paragraphs));
+        }
+        Ok(match self.dictionary {
+            Some(id) => {
+                let mut compressor =
+                    zstd::bulk::Compressor::with_dictionary(LEVEL, &self.dictionaries[&id])?;
+                Stored::Compressed(
+                    compressor.compress(paragraphs.as_bytes())?,
+                    format!("{}:{}", CODEC, id),
+                )
+            }
+            None => Stored::Compressed(
+                zstd::stream::encode_all(paragraphs.as_bytes(), LEVEL)?,
+                CODEC.to_string(),
+            ),
+        })
+    }
+
+    /// The paragraphs of `bytes`, stored with the `compression` marker `marker`.
+    pub fn decode(&self, marker: &str, bytes: &[u8]) -> io::Result<String> {
+        let mut paragraphs = String::new();
+        match dictionary_of(marker)? {
+            Some(id) => {
+                let dictionary = self.dictionaries.get(&id).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("unknown dictionary {}", id),
+                    )
+                })?;
+                zstd::stream::read::Decoder::with_dictionary(bytes, dictionary)?
+                    .read_to_string(&mut paragraphs)?;
+            }
+            None => {
+                zstd::stream::read::Decoder::new(bytes)?.read_to_string(&mut paragraphs)?;
+            }
+        }
+        Ok(paragraphs)
+    }
+
+    /// The `paragraphs` of `row`, decoded after its `compression` column.
+    pub(crate) fn read(&self, row: &SqliteRow) -> Result<Option<String>, sqlx::Error> {
+        let marker: Option<String> = row.try_get("compression")?;
+        let marker = match marker {
+            Some(marker) => marker,
+            None => return row.try_get("paragraphs"),
+        };
+        let bytes: Option<Vec<u8>> = row.try_get("paragraphs")?;
+        bytes
+            .map(|bytes| self.decode(&marker, &bytes))
+            .transpose()
+            .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+    }
+}
+
+/// The dictionary of the `compression` marker `marker`, none for plain zstd.
+fn dictionary_of(marker: &str) -> io::Result<Option<i64>> {
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unknown compression `{}`", marker),
+        )
+    };
+    match marker.strip_prefix(CODEC) {
+        Some("") => Ok(None),
+        Some(id) => id
+            .strip_prefix(':')
+            .and_then(|id| id.parse().ok())
+            .map(Some)
+            .ok_or_else(invalid),
+        None => Err(invalid()),
+    }
+}
+
+/// A dictionary trained on `samples`, the paragraphs of as many articles. None with fewer than
+/// `MIN_SAMPLES` of them, or when they're too alike or too small to train one.
+pub fn train<S: AsRef<[u8]>>(samples: &[S]) -> Option<Vec<u8>> {
+    if samples.len() < MIN_SAMPLES {
+        return None;
+    }
+    match zstd::dict::from_samples(samples, DICTIONARY_SIZE) {
+        Ok(dictionary) => Some(dictionary),
+        Err(e) => {
+            tracing::warn!(
+                "Can't train a dictionary on {} articles: {}",
+                samples.len(),
+                e
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn article(i: usize) -> String {
+        format!(
+            "Jakarta - Pemerintah kota mengumumkan kebijakan baru nomor {} pada hari Senin.\n\
+             Kebijakan tersebut berlaku mulai pekan depan, kata juru bicara kepada wartawan.\n\
+             Warga diminta menyesuaikan diri dengan aturan {} yang baru.",
+            i,
+            i * 7
+        )
+    }
+
+    #[test]
+    fn round_trip() {
+        let samples: Vec<String> = (0..500).map(article).collect();
+        let dictionary = train(&samples).unwrap();
+        let codecs = [
+            ParagraphCodec::default().compressing(),
+            ParagraphCodec::new(HashMap::from([(3, dictionary)])).compressing(),
+        ];
+        for (codec, marker) in codecs.iter().zip(["zstd", "zstd:3"]) {
+            let stored = codec.encode(article(1000)).unwrap();
+            match stored {
+                Stored::Compressed(bytes, stored_marker) => {
+                    assert_eq!(stored_marker, marker);
+                    assert_eq!(codec.decode(marker, &bytes).unwrap(), article(1000));
+                }
+                Stored::Text(_) => panic!("Not compressed with {:?}", codec),
+            }
+        }
+        assert_eq!(
+            codecs[1].encode(String::new()).unwrap(),
+            Stored::Text(String::new())
+        );
+        assert_eq!(
+            ParagraphCodec::default().encode(article(1)).unwrap(),
+            Stored::Text(article(1))
+        );
+    }
+
+    #[test]
+    fn unknown_markers() {
+        let codec = ParagraphCodec::default();
+        for marker in ["gzip", "zstd:", "zstd:x", "zstd:1"] {
+            assert_eq!(
+                codec.decode(marker, b"").unwrap_err().kind(),
+                io::ErrorKind::InvalidData,
+                "{}",
+                marker
+            );
+        }
+        assert!(train(&["Sedikit."; MIN_SAMPLES - 1]).is_none());
+    }
+}
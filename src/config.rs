@@ -0,0 +1,622 @@
+//! The options of a crawl read from a TOML file, `crawl --config crawl.toml`, for the ones
+//! too many or too long to pass as flags. `crawl.example.toml` documents every key.
+//!
+//! ```
+//! use indonesian_media_crawler::config::CrawlConfig;
+//! use std::time::Duration;
+//!
+//! let config = CrawlConfig::from_toml(
+//!     r#"
+//!     request_delay_ms = 200
+//!     languages = ["id"]
+//!
+//!     [retry]
+//!     timeout = [60, 600]
+//!     "#,
+//! )
+//! .unwrap();
+//! let options = config.run_options().unwrap();
+//! assert_eq!(options.request_delay, Duration::from_millis(200));
+//! assert_eq!(options.retry_policy.schedule("timeout").unwrap().delays.len(), 2);
+//! assert!(options.retry_policy.schedule("http_5xx").is_none());
+//!
+//! assert!(CrawlConfig::from_toml("request_dealy_ms = 200").is_err());
+//! ```
+
+use crate::dns::DnsCacheConfig;
+use crate::health::Canary;
+use crate::outage::StorageOutage;
+use crate::politeness::{CoffeeBreak, Distribution, Politeness};
+use crate::proxy::{ProxyConfig, Rotation};
+use crate::quality::QualityAlert;
+use crate::recrawl::HubRecrawl;
+use crate::retry::{RetryPolicy, RetrySchedule};
+use crate::seeds::{SeedProfile, SeedProfiles};
+use crate::selectors::SelectorOverrides;
+#[cfg(feature = "http-api")]
+use crate::server::IngestConfig;
+use crate::throttle::QueueThrottle;
+use crate::{CrawlerError, RunOptions};
+use lazy_regex::Regex;
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::{Map, Number, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tokio::time::Duration;
+
+/// Why a config file can't be used.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Failed to read {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+    /// Not TOML, with the line and column of the error
+    #[error("{0}")]
+    Syntax(String),
+    /// A key not in `CrawlConfig`, or a value of the wrong type
+    #[error("Invalid config: {0}")]
+    Invalid(String),
+    /// A value of the right type that can't be used, with its key
+    #[error("Invalid {0}: {1}")]
+    Value(&'static str, String),
+}
+
+/// A config file. Every key is optional, the ones left out keep the value of
+/// `RunOptions::default()`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CrawlConfig {
+    pub max_fetching: Option<u32>,
+    pub max_parsing: Option<u32>,
+    pub request_delay_ms: Option<u64>,
+    pub politeness: Option<PolitenessConfig>,
+    pub max_results: Option<u64>,
+    pub max_bytes: Option<u64>,
+    pub bytes_per_hour: Option<u64>,
+    pub languages: Option<Vec<String>>,
+    /// Sent with every request, by name
+    pub headers: BTreeMap<String, String>,
+    pub cookie_jar: Option<PathBuf>,
+    pub proxy: Option<ProxyFileConfig>,
+    /// Sizes in bytes, 0 for no limit
+    pub max_decompressed_size: Option<u64>,
+    pub max_parse_size: Option<u64>,
+    pub max_link_extraction_size: Option<u64>,
+    pub follow_links: Option<bool>,
+    pub exit_when_idle: Option<bool>,
+    pub dns_cache: Option<DnsCacheFileConfig>,
+    pub ignore_robots: Option<bool>,
+    /// Hosts like `127.0.0.1:8080` crawled without delays nor robots directives
+    pub disable_politeness_for_hosts: Option<Vec<String>>,
+    pub keep_metadata_on_empty_body: Option<bool>,
+    /// The delays in seconds before each retry of the urls warned with a reason, by reason.
+    /// Replaces the default `RetryPolicy`, no url is retried when it's empty.
+    pub retry: Option<BTreeMap<String, Vec<u64>>>,
+    pub worker_id: Option<String>,
+    pub heartbeat_timeout_secs: Option<u64>,
+    pub queue_throttle: Option<QueueThrottleConfig>,
+    pub revisit_after_days: Option<u64>,
+    pub slow_parse_ms: Option<u64>,
+    pub hub_recrawl: Option<HubRecrawlConfig>,
+    pub quality_alert: Option<QualityAlertConfig>,
+    pub canary: Option<CanaryConfig>,
+    pub storage_outage: Option<StorageOutageConfig>,
+    pub report: Option<PathBuf>,
+    #[cfg(feature = "http-api")]
+    pub status_addr: Option<std::net::SocketAddr>,
+    #[cfg(feature = "http-api")]
+    pub ingest: Option<IngestFileConfig>,
+    /// The profile the crawl starts from, among the built-in ones and `seed_profiles`
+    pub seed_profile: Option<String>,
+    pub seed_profiles: Vec<SeedProfileConfig>,
+    /// CSS selectors replacing the built-in ones of the crawler, by name, e.g.
+    /// `detik::SELECTOR_NAMES`
+    pub selectors: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolitenessConfig {
+    pub min_delay_ms: u64,
+    /// `min_delay_ms` by default
+    pub max_delay_ms: Option<u64>,
+    #[serde(default)]
+    pub distribution: Distribution,
+    pub coffee_break: Option<CoffeeBreakConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CoffeeBreakConfig {
+    pub every: u32,
+    pub min_secs: u64,
+    pub max_secs: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProxyFileConfig {
+    pub urls: Vec<String>,
+    #[serde(default)]
+    pub rotation: Rotation,
+    pub max_failures: Option<u32>,
+    pub quarantine_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DnsCacheFileConfig {
+    pub enabled: bool,
+    pub ttl_secs: Option<u64>,
+    pub negative_ttl_secs: Option<u64>,
+}
+
+impl Default for DnsCacheFileConfig {
+    fn default() -> Self {
+        DnsCacheFileConfig {
+            enabled: true,
+            ttl_secs: None,
+            negative_ttl_secs: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QueueThrottleConfig {
+    pub high_watermark: u32,
+    /// Half the high watermark by default
+    pub low_watermark: Option<u32>,
+    /// A regex matching the article urls, see `QueueThrottle::article_pattern`
+    pub article_pattern: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HubRecrawlConfig {
+    pub min_minutes: u64,
+    pub max_minutes: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QualityAlertConfig {
+    pub window: usize,
+    pub threshold: f64,
+    /// Half the threshold by default
+    pub recover_below: Option<f64>,
+    #[serde(default)]
+    pub pause: bool,
+    pub webhook: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CanaryConfig {
+    pub url: String,
+    /// Only log an unhealthy canary when false
+    #[serde(default = "default_true")]
+    pub abort: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageOutageConfig {
+    pub canary_secs: Option<u64>,
+    pub max_failed_canaries: Option<u32>,
+}
+
+#[cfg(feature = "http-api")]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IngestFileConfig {
+    pub token: String,
+    #[serde(default)]
+    pub priority: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeedProfileConfig {
+    pub name: String,
+    pub allowed_hosts: Vec<String>,
+    pub urls: Vec<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl CrawlConfig {
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let toml =
+            std::fs::read_to_string(path).map_err(|e| ConfigError::Io(path.to_path_buf(), e))?;
+        Self::from_toml(&toml)
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self, ConfigError> {
+        let doc = toml_edit::Document::parse(toml)
+            .map_err(|e| ConfigError::Syntax(e.to_string().trim_end().to_string()))?;
+        serde_json::from_value(item_to_json(doc.as_item()))
+            .map_err(|e| ConfigError::Invalid(e.to_string()))
+    }
+
+    /// `RunOptions::default()` with the values of the file.
+    pub fn run_options(&self) -> Result<RunOptions, ConfigError> {
+        self.apply(RunOptions::default())
+    }
+
+    /// `options` with the values of the file, the others left as they are.
+    pub fn apply(&self, mut options: RunOptions) -> Result<RunOptions, ConfigError> {
+        let secs = Duration::from_secs;
+        let limit = |size: u64| (size > 0).then_some(size);
+
+        if let Some(max) = self.max_fetching {
+            options.max_fetching = max;
+        }
+        if let Some(max) = self.max_parsing {
+            options.max_parsing = max;
+        }
+        if let Some(delay) = self.request_delay_ms {
+            options.request_delay = Duration::from_millis(delay);
+        }
+        if let Some(config) = &self.politeness {
+            let min = Duration::from_millis(config.min_delay_ms);
+            let max = config.max_delay_ms.map_or(min, Duration::from_millis);
+            let mut politeness = Politeness::new(min, max).with_distribution(config.distribution);
+            if let Some(pause) = &config.coffee_break {
+                if pause.min_secs > pause.max_secs {
+                    return Err(ConfigError::Value(
+                        "politeness.coffee_break",
+                        format!(
+                            "min_secs {} above max_secs {}",
+                            pause.min_secs, pause.max_secs
+                        ),
+                    ));
+                }
+                politeness = politeness.with_coffee_break(CoffeeBreak::new(
+                    pause.every,
+                    secs(pause.min_secs),
+                    secs(pause.max_secs),
+                ));
+            }
+            options.politeness = Some(politeness);
+        }
+        if let Some(max) = self.max_results {
+            options.max_results = Some(max);
+        }
+        if let Some(max) = self.max_bytes {
+            options.max_bytes = Some(max);
+        }
+        if let Some(rate) = self.bytes_per_hour {
+            options.bytes_per_hour = Some(rate);
+        }
+        if let Some(languages) = &self.languages {
+            options.languages = Some(languages.clone());
+        }
+        for (name, value) in &self.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| ConfigError::Value("headers", format!("{}: {}", name, e)))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|e| ConfigError::Value("headers", format!("{}: {}", name, e)))?;
+            options.default_headers.insert(name, value);
+        }
+        if let Some(path) = &self.cookie_jar {
+            options.cookie_jar = Some(path.clone());
+        }
+        if let Some(config) = &self.proxy {
+            let mut proxy = ProxyConfig::rotating(config.urls.clone(), config.rotation);
+            if let Some(max) = config.max_failures {
+                proxy.max_failures = max;
+            }
+            if let Some(quarantine) = config.quarantine_secs {
+                proxy.quarantine = secs(quarantine);
+            }
+            options.proxy = Some(proxy);
+        }
+        if let Some(size) = self.max_decompressed_size {
+            options.max_decompressed_size = limit(size);
+        }
+        if let Some(size) = self.max_parse_size {
+            options.max_parse_size = limit(size);
+        }
+        if let Some(size) = self.max_link_extraction_size {
+            options.max_link_extraction_size = limit(size);
+        }
+        if let Some(config) = &self.dns_cache {
+            options.dns_cache = config.enabled.then(|| {
+                let default = DnsCacheConfig::default();
+                DnsCacheConfig {
+                    ttl: config.ttl_secs.map_or(default.ttl, secs),
+                    negative_ttl: config.negative_ttl_secs.map_or(default.negative_ttl, secs),
+                }
+            });
+        }
+        if let Some(follow) = self.follow_links {
+            options.follow_links = follow;
+        }
+        if let Some(exit) = self.exit_when_idle {
+            options.exit_when_idle = exit;
+        }
+        if let Some(ignore) = self.ignore_robots {
+            options.ignore_robots = ignore;
+        }
+        if let Some(hosts) = &self.disable_politeness_for_hosts {
+            options.disable_politeness_for_hosts = hosts.clone();
+        }
+        if let Some(keep) = self.keep_metadata_on_empty_body {
+            options.keep_metadata_on_empty_body = keep;
+        }
+        if let Some(retry) = &self.retry {
+            options.retry_policy =
+                retry
+                    .iter()
+                    .fold(RetryPolicy::none(), |policy, (reason, delays)| {
+                        let delays = delays.iter().copied().map(secs).collect();
+                        policy.retry(reason, RetrySchedule { delays })
+                    });
+        }
+        if let Some(worker_id) = &self.worker_id {
+            options.worker_id = worker_id.clone();
+        }
+        if let Some(timeout) = self.heartbeat_timeout_secs {
+            options.heartbeat_timeout = secs(timeout);
+        }
+        if let Some(config) = &self.queue_throttle {
+            let low = config.low_watermark.unwrap_or(config.high_watermark / 2);
+            let mut throttle = QueueThrottle::new(config.high_watermark, low);
+            if let Some(pattern) = &config.article_pattern {
+                let pattern = Regex::new(pattern).map_err(|e| {
+                    ConfigError::Value("queue_throttle.article_pattern", e.to_string())
+                })?;
+                throttle = throttle.with_article_pattern(pattern);
+            }
+            options.queue_throttle = Some(throttle);
+        }
+        if let Some(days) = self.revisit_after_days {
+            options.revisit_after = Some(secs(days * 24 * 3600));
+        }
+        if let Some(ms) = self.slow_parse_ms {
+            options.slow_parse = Some(Duration::from_millis(ms));
+        }
+        if let Some(config) = &self.hub_recrawl {
+            options.hub_recrawl = Some(HubRecrawl::new(
+                secs(config.min_minutes * 60),
+                secs(config.max_minutes * 60),
+            ));
+        }
+        if let Some(config) = &self.quality_alert {
+            let mut alert =
+                QualityAlert::new(config.window, config.threshold).with_pause(config.pause);
+            if let Some(recover_below) = config.recover_below {
+                alert = alert.with_recover_below(recover_below);
+            }
+            if let Some(webhook) = &config.webhook {
+                let webhook = Url::parse(webhook)
+                    .map_err(|e| ConfigError::Value("quality_alert.webhook", e.to_string()))?;
+                alert = alert.with_webhook(webhook);
+            }
+            options.quality_alert = Some(alert);
+        }
+        if let Some(config) = &self.canary {
+            options.canary = Some(Canary {
+                abort: config.abort,
+                ..Canary::new(config.url.as_str())
+            });
+        }
+        if let Some(config) = &self.storage_outage {
+            let default = StorageOutage::default();
+            options.storage_outage = StorageOutage::new(
+                config.canary_secs.map_or(default.canary_interval, secs),
+                config
+                    .max_failed_canaries
+                    .unwrap_or(default.max_failed_canaries),
+            );
+        }
+        if let Some(path) = &self.report {
+            options.report_path = Some(path.clone());
+        }
+        #[cfg(feature = "http-api")]
+        {
+            if let Some(addr) = self.status_addr {
+                options.status_addr = Some(addr);
+            }
+            if let Some(config) = &self.ingest {
+                options.ingest = Some(IngestConfig {
+                    priority: config.priority,
+                    ..IngestConfig::new(config.token.as_str())
+                });
+            }
+        }
+        Ok(options)
+    }
+
+    /// The `selectors` of the file, among the `known` names of the crawler. Checked before
+    /// the crawl starts, an invalid one is a `CrawlerError::InvalidSelector`.
+    pub fn selector_overrides(&self, known: &[&str]) -> Result<SelectorOverrides, CrawlerError> {
+        SelectorOverrides::parse(&self.selectors, known)
+    }
+
+    /// The built-in profiles and the `seed_profiles` of the file, replacing the built-in ones
+    /// of the same name.
+    pub fn seed_profiles(&self) -> SeedProfiles {
+        self.seed_profiles
+            .iter()
+            .fold(SeedProfiles::builtin(), |profiles, profile| {
+                profiles.with_profile(SeedProfile::new(
+                    &profile.name,
+                    profile.allowed_hosts.iter().cloned(),
+                    profile.urls.iter().cloned(),
+                ))
+            })
+    }
+}
+
+impl RunOptions {
+    /// The options of the TOML file at `path`, see `CrawlConfig`.
+    pub fn from_config_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        CrawlConfig::load(path.as_ref())?.run_options()
+    }
+}
+
+/// The JSON form of a TOML item, for the `Deserialize` of `CrawlConfig`. Dates are kept as
+/// their text.
+fn item_to_json(item: &toml_edit::Item) -> Value {
+    match item {
+        toml_edit::Item::None => Value::Null,
+        toml_edit::Item::Value(value) => value_to_json(value),
+        toml_edit::Item::Table(table) => table_to_json(table.iter()),
+        toml_edit::Item::ArrayOfTables(tables) => Value::Array(
+            tables
+                .iter()
+                .map(|table| table_to_json(table.iter()))
+                .collect(),
+        ),
+    }
+}
+
+fn table_to_json<'a>(entries: impl Iterator<Item = (&'a str, &'a toml_edit::Item)>) -> Value {
+    Value::Object(
+        entries
+            .map(|(key, item)| (key.to_string(), item_to_json(item)))
+            .collect::<Map<_, _>>(),
+    )
+}
+
+fn value_to_json(value: &toml_edit::Value) -> Value {
+    match value {
+        toml_edit::Value::String(s) => Value::String(s.value().clone()),
+        toml_edit::Value::Integer(n) => Value::Number((*n.value()).into()),
+        toml_edit::Value::Float(n) => {
+            Number::from_f64(*n.value()).map_or(Value::Null, Value::Number)
+        }
+        toml_edit::Value::Boolean(b) => Value::Bool(*b.value()),
+        toml_edit::Value::Datetime(date) => Value::String(date.value().to_string()),
+        toml_edit::Value::Array(array) => Value::Array(array.iter().map(value_to_json).collect()),
+        toml_edit::Value::InlineTable(table) => Value::Object(
+            table
+                .iter()
+                .map(|(key, value)| (key.to_string(), value_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example_parsed() {
+        let config = CrawlConfig::from_toml(include_str!("../crawl.example.toml")).unwrap();
+        let options = config.run_options().unwrap();
+        assert_eq!(options.max_fetching, 8);
+        assert_eq!(options.languages, Some(vec!["id".to_string()]));
+        assert_eq!(
+            options.default_headers["accept-language"],
+            HeaderValue::from_static("id-ID")
+        );
+        let politeness = options.politeness.unwrap();
+        assert_eq!(politeness.max_delay, Duration::from_millis(1500));
+        assert_eq!(politeness.distribution, Distribution::Normal);
+        assert_eq!(politeness.coffee_break.unwrap().every, 200);
+        assert_eq!(options.max_link_extraction_size, None);
+        assert!(options.follow_links && !options.exit_when_idle);
+        assert_eq!(
+            options.retry_policy.schedule("timeout").unwrap().delays,
+            vec![Duration::from_secs(600), Duration::from_secs(3600)]
+        );
+        assert!(options.retry_policy.schedule("http_5xx").is_none());
+        let throttle = options.queue_throttle.unwrap();
+        assert_eq!(
+            (throttle.high_watermark, throttle.low_watermark),
+            (50_000, 25_000)
+        );
+        assert!(throttle.is_article("https://news.detik.com/berita/d-6449191/judul"));
+        assert!(!throttle.is_article("https://news.detik.com/indeks"));
+        assert_eq!(
+            options.revisit_after,
+            Some(Duration::from_secs(7 * 24 * 3600))
+        );
+        let alert = options.quality_alert.unwrap();
+        assert_eq!((alert.window, alert.threshold), (200, 0.5));
+        assert!(alert.pause && alert.webhook.is_none());
+        assert_eq!(
+            options.storage_outage,
+            StorageOutage::new(Duration::from_secs(30), 40)
+        );
+        assert_eq!(
+            options.hub_recrawl,
+            Some(HubRecrawl::new(
+                Duration::from_secs(10 * 60),
+                Duration::from_secs(24 * 3600)
+            ))
+        );
+        assert_eq!(config.seed_profile.as_deref(), Some("detik-economy"));
+        assert_eq!(
+            config.seed_profiles().urls("detik-economy").unwrap(),
+            vec![
+                "https://finance.detik.com",
+                "https://finance.detik.com/bursa-dan-valas"
+            ]
+        );
+    }
+
+    #[test]
+    fn defaults_kept() {
+        let options = CrawlConfig::from_toml("").unwrap().run_options().unwrap();
+        let default = RunOptions::default();
+        assert_eq!(options.request_delay, default.request_delay);
+        assert_eq!(options.retry_policy, default.retry_policy);
+        assert_eq!(options.max_decompressed_size, default.max_decompressed_size);
+        assert!(options.politeness.is_none());
+    }
+
+    #[test]
+    fn errors_reported() {
+        let error = CrawlConfig::from_toml("max_fetching = 4\nrequest_dealy_ms = 200")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains("unknown field `request_dealy_ms`"),
+            "{}",
+            error
+        );
+
+        let error = CrawlConfig::from_toml("[politeness]\nmin_delay_ms = 10\njitter = 5")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("unknown field `jitter`"), "{}", error);
+
+        let error = CrawlConfig::from_toml("max_fetching = \"many\"")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("invalid type"), "{}", error);
+
+        let error = CrawlConfig::from_toml("max_fetching = 4\nlanguages = [\"id\"").unwrap_err();
+        assert!(matches!(error, ConfigError::Syntax(_)));
+        assert!(error.to_string().contains("line 2"), "{}", error);
+
+        let error = CrawlConfig::from_toml(
+            "[queue_throttle]\nhigh_watermark = 10\narticle_pattern = \"(\"",
+        )
+        .unwrap()
+        .run_options()
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ConfigError::Value("queue_throttle.article_pattern", _)
+        ));
+
+        let config =
+            CrawlConfig::from_toml("[selectors]\ntitle = \"meta[property=og:title\"").unwrap();
+        assert!(matches!(
+            config.selector_overrides(&["title"]),
+            Err(CrawlerError::InvalidSelector { name, .. }) if name == "title"
+        ));
+
+        let error = RunOptions::from_config_file("missing.toml").unwrap_err();
+        assert!(matches!(error, ConfigError::Io(..)));
+    }
+}
@@ -0,0 +1,97 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use tokio::sync::watch;
+
+/// Handle to pause, resume or stop a running `run_scrapper` from another task.
+#[derive(Debug, Clone)]
+pub struct CrawlController {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    paused: AtomicBool,
+    stopped: watch::Sender<bool>,
+}
+
+impl Default for CrawlController {
+    fn default() -> Self {
+        let (stopped, _) = watch::channel(false);
+        CrawlController {
+            inner: Arc::new(Inner {
+                paused: AtomicBool::new(false),
+                stopped,
+            }),
+        }
+    }
+}
+
+impl CrawlController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop dispatching new urls. Fetches already in flight still complete.
+    pub fn pause(&self) {
+        self.inner.paused.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        self.inner.paused.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.inner.paused.load(Ordering::SeqCst)
+    }
+
+    pub fn stop(&self) {
+        self.inner.stopped.send_replace(true);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        *self.inner.stopped.borrow()
+    }
+
+    /// Resolves once `stop` has been called.
+    pub async fn stopped(&self) {
+        let mut rx = self.inner.stopped.subscribe();
+        while !*rx.borrow_and_update() {
+            if rx.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::{timeout, Duration};
+
+    #[tokio::test]
+    async fn pause_resume_and_stop() {
+        let c = CrawlController::new();
+        assert!(!c.is_paused());
+        c.pause();
+        assert!(c.clone().is_paused());
+        c.resume();
+        assert!(!c.is_paused());
+
+        let waiter = {
+            let c = c.clone();
+            tokio::spawn(async move { c.stopped().await })
+        };
+        assert!(!c.is_stopped());
+        c.stop();
+        timeout(Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(c.is_stopped());
+
+        // Already stopped resolves immediately
+        timeout(Duration::from_secs(1), c.stopped()).await.unwrap();
+    }
+}
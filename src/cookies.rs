@@ -0,0 +1,73 @@
+use crate::CrawlerError;
+use reqwest::{header::HeaderValue, Url};
+use std::io::{BufReader, ErrorKind};
+use std::path::Path;
+use std::sync::RwLock;
+
+/// The cookies of a crawl, shared by every request of the run and saved between runs, so
+/// consent cookies set by a site are only asked for once.
+#[derive(Debug, Default)]
+pub struct CookieJar(RwLock<cookie_store::CookieStore>);
+
+impl CookieJar {
+    /// The jar saved at `path`, empty when there is no file yet.
+    pub fn load(path: &Path) -> Result<Self, CrawlerError> {
+        let file = match std::fs::File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(CookieJar::default()),
+            Err(e) => return Err(e.into()),
+        };
+        let store = cookie_store::CookieStore::load_json(BufReader::new(file))
+            .map_err(|e| std::io::Error::new(ErrorKind::InvalidData, e))?;
+        Ok(CookieJar(RwLock::new(store)))
+    }
+
+    /// Save the unexpired cookies to `path`. Session cookies, without an expiry, are left out.
+    pub fn save(&self, path: &Path) -> Result<(), CrawlerError> {
+        let mut file = std::fs::File::create(path)?;
+        self.0
+            .read()
+            .unwrap()
+            .save_json(&mut file)
+            .map_err(std::io::Error::other)?;
+        Ok(())
+    }
+
+    /// The value of the cookie `name` sent to `url`.
+    pub fn get(&self, url: &Url, name: &str) -> Option<String> {
+        self.0
+            .read()
+            .unwrap()
+            .get_request_values(url)
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value.to_string())
+    }
+}
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let mut store = self.0.write().unwrap();
+        for header in cookie_headers {
+            if let Ok(cookie) = header.to_str() {
+                // Cookies the store refuses, e.g. for another domain, are dropped
+                let _ = store.parse(cookie, url);
+            }
+        }
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let cookies = self
+            .0
+            .read()
+            .unwrap()
+            .get_request_values(url)
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        if cookies.is_empty() {
+            None
+        } else {
+            HeaderValue::from_str(&cookies).ok()
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! How complete the detik corpus is per day: the articles stored for each day against the
+//! number detik lists in its daily sitemap.
+
+use crate::detik::DetikData;
+use crate::{http_client, sitemap, CrawlerError};
+use chrono::NaiveDate;
+use std::collections::{HashMap, HashSet};
+
+/// The daily news sitemap of detik, `{date}` being replaced by the day as `YYYY-MM-DD`.
+pub const DETIK_DAILY_SITEMAP: &str = "https://www.detik.com/sitemap/news/{date}.xml";
+
+/// The articles of one day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayCoverage {
+    pub day: NaiveDate,
+    pub stored: u32,
+    /// The urls in the sitemap of the day, `None` when it wasn't fetched
+    pub published: Option<u32>,
+}
+
+impl DayCoverage {
+    /// The stored articles as a percentage of the published ones. `None` when the published
+    /// total is unknown or zero.
+    pub fn percentage(&self) -> Option<f64> {
+        self.published
+            .filter(|published| *published > 0)
+            .map(|published| f64::from(self.stored) * 100.0 / f64::from(published))
+    }
+}
+
+/// The coverage of every day from `from` to `to`, both included. With `sitemap`, a url
+/// template like `DETIK_DAILY_SITEMAP`, the sitemap of each day is fetched for its
+/// published total, otherwise only the stored articles are counted.
+pub async fn report(
+    storage: &DetikData,
+    from: NaiveDate,
+    to: NaiveDate,
+    sitemap: Option<&str>,
+) -> Result<Vec<DayCoverage>, CrawlerError> {
+    let stored: HashMap<NaiveDate, u32> = storage
+        .results
+        .published_per_day(from, to)
+        .await?
+        .into_iter()
+        .collect();
+    let client = http_client()?;
+
+    let mut days = vec![];
+    for day in from.iter_days().take_while(|day| *day <= to) {
+        let published = match sitemap {
+            Some(template) => Some(published(&client, template, day).await?),
+            None => None,
+        };
+        days.push(DayCoverage {
+            day,
+            stored: stored.get(&day).copied().unwrap_or(0),
+            published,
+        });
+    }
+    Ok(days)
+}
+
+/// The distinct urls of the sitemap of `day` published that day, or without a date.
+async fn published(
+    client: &reqwest::Client,
+    template: &str,
+    day: NaiveDate,
+) -> Result<u32, CrawlerError> {
+    let url = template.replace("{date}", &day.format("%Y-%m-%d").to_string());
+    let xml = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let urls: HashSet<String> = sitemap::parse(&xml)
+        .into_iter()
+        .filter(|entry| {
+            entry
+                .published_date
+                .is_none_or(|date| date.date_naive() == day)
+        })
+        .map(|entry| entry.loc)
+        .collect();
+    Ok(urls.len() as u32)
+}
+
+/// The report as CSV with a header, the unknown totals and percentages left empty.
+pub fn to_csv(days: &[DayCoverage]) -> String {
+    let mut csv = String::from("date,stored,published,coverage\n");
+    for day in days {
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            day.day.format("%Y-%m-%d"),
+            day.stored,
+            day.published.map(|p| p.to_string()).unwrap_or_default(),
+            day.percentage()
+                .map(|p| format!("{:.1}", p))
+                .unwrap_or_default()
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detik::DetikArticle;
+    use crate::Storage;
+    use axum::{extract::Path, Router};
+    use chrono::DateTime;
+
+    /// Serves `/sitemap/<day>.xml` with 4 articles on 2022-12-09, 2 on 2022-12-10 and none
+    /// on the other days.
+    fn start_sitemaps() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().route(
+            "/sitemap/:file",
+            axum::routing::get(|Path(file): Path<String>| async move {
+                let urls: &[(&str, &str)] = match file.as_str() {
+                    "2022-12-09.xml" => &[
+                        ("d-1", "2022-12-09T08:00:00+07:00"),
+                        ("d-2", "2022-12-09T00:30:00+07:00"),
+                        ("d-3", "2022-12-09T10:00:00+07:00"),
+                        ("d-4", "2022-12-09T11:00:00+07:00"),
+                        // Listed twice, or published another day
+                        ("d-4", "2022-12-09T11:00:00+07:00"),
+                        ("d-0", "2022-12-08T11:00:00+07:00"),
+                    ],
+                    "2022-12-10.xml" => &[
+                        ("d-5", "2022-12-10T08:00:00+07:00"),
+                        ("d-6", "2022-12-10T09:00:00+07:00"),
+                    ],
+                    _ => &[],
+                };
+                let urls: String = urls
+                    .iter()
+                    .map(|(id, date)| {
+                        format!(
+                            "<url><loc>https://news.detik.com/berita/{}/a</loc>\
+                             <lastmod>{}</lastmod></url>",
+                            id, date
+                        )
+                    })
+                    .collect();
+                format!("<urlset>{}</urlset>", urls)
+            }),
+        );
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+        format!("http://{}/sitemap/{{date}}.xml", addr)
+    }
+
+    fn article(published_date: &str) -> DetikArticle {
+        DetikArticle {
+            title: None,
+            published_date: Some(DateTime::parse_from_rfc3339(published_date).unwrap()),
+            description: None,
+            thumbnail_url: None,
+            author: None,
+            desk: None,
+            category: None,
+            language: None,
+            keywords: vec![],
+            keywords_raw: None,
+            videos: vec![],
+            photos: vec![],
+            body_selector: None,
+            paragraphs: vec!["p".to_string()],
+        }
+    }
+
+    #[tokio::test]
+    async fn percentages() {
+        if std::path::Path::new("test_coverage.db").is_file() {
+            std::fs::remove_file("test_coverage.db").unwrap();
+        }
+        let storage = DetikData::new("test_coverage").await.unwrap();
+        // 00:30 WIB is still 2022-12-08 in UTC, counted on 2022-12-09
+        for (id, date) in [
+            ("d-1", "2022-12-09T08:00:00+07:00"),
+            ("d-2", "2022-12-09T00:30:00+07:00"),
+            ("d-3", "2022-12-09T10:00:00+07:00"),
+            ("d-5", "2022-12-10T08:00:00+07:00"),
+            ("d-9", "2022-12-12T08:00:00+07:00"),
+        ] {
+            let url = format!("https://news.detik.com/berita/{}/a", id);
+            storage.results_insert((url, article(date))).await.unwrap();
+        }
+        let day = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        let coverage = |d: &str, stored, published| DayCoverage {
+            day: day(d),
+            stored,
+            published,
+        };
+
+        let offline = report(&storage, day("2022-12-09"), day("2022-12-11"), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            offline,
+            vec![
+                coverage("2022-12-09", 3, None),
+                coverage("2022-12-10", 1, None),
+                coverage("2022-12-11", 0, None),
+            ]
+        );
+        assert_eq!(offline[0].percentage(), None);
+
+        let online = report(
+            &storage,
+            day("2022-12-09"),
+            day("2022-12-11"),
+            Some(&start_sitemaps()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            online,
+            vec![
+                coverage("2022-12-09", 3, Some(4)),
+                coverage("2022-12-10", 1, Some(2)),
+                coverage("2022-12-11", 0, Some(0)),
+            ]
+        );
+        assert_eq!(
+            to_csv(&online),
+            "date,stored,published,coverage\n\
+             2022-12-09,3,4,75.0\n\
+             2022-12-10,1,2,50.0\n\
+             2022-12-11,0,0,\n"
+        );
+
+        std::fs::remove_file("test_coverage.db").unwrap();
+    }
+}
@@ -0,0 +1,283 @@
+use crate::{CrawlerError, ResultSummary, Storage, WarnedEntry};
+use ratatui::{
+    backend::Backend,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, Borders, List, ListItem, Paragraph, Sparkline},
+    Frame, Terminal,
+};
+use std::collections::VecDeque;
+use tokio::time::Duration;
+
+const RECENT_RESULTS: u32 = 20;
+const RECENT_WARNINGS: u32 = 10;
+const RATE_HISTORY: usize = 120;
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Counts {
+    pub queued: u32,
+    pub running: u32,
+    pub visited: u32,
+    pub results: u32,
+    pub warned: u32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub counts: Counts,
+    pub recent_results: Vec<ResultSummary>,
+    pub recent_warnings: Vec<WarnedEntry>,
+}
+
+/// Everything the dashboard widgets show, read only through the `Storage` getters.
+pub async fn snapshot<S: Storage>(storage: &S) -> Result<Snapshot, CrawlerError> {
+    Ok(Snapshot {
+        counts: Counts {
+            queued: storage.queued_count().await?,
+            running: storage.running_count().await?,
+            visited: storage.visited_count().await?,
+            results: storage.results_count().await?,
+            warned: storage.warned_count().await?,
+        },
+        recent_results: storage.results_recent(RECENT_RESULTS).await?,
+        recent_warnings: storage.warned_recent(RECENT_WARNINGS).await?,
+    })
+}
+
+/// Number of new results between consecutive refreshes.
+#[derive(Debug)]
+pub struct RateHistory {
+    last_results: Option<u32>,
+    samples: VecDeque<u64>,
+    capacity: usize,
+}
+
+impl RateHistory {
+    pub fn new(capacity: usize) -> Self {
+        RateHistory {
+            last_results: None,
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, results: u32) {
+        if let Some(last) = self.last_results {
+            if self.samples.len() == self.capacity {
+                self.samples.pop_front();
+            }
+            self.samples
+                .push_back(u64::from(results.saturating_sub(last)));
+        }
+        self.last_results = Some(results);
+    }
+
+    pub fn samples(&self) -> Vec<u64> {
+        self.samples.iter().copied().collect()
+    }
+}
+
+/// Refresh the dashboard every second until `q` is pressed. The crawl itself is untouched.
+pub async fn run<S: Storage>(storage: &S) -> Result<(), CrawlerError> {
+    let mut terminal = ratatui::init();
+    let result = run_loop(&mut terminal, storage).await;
+    ratatui::restore();
+    result
+}
+
+async fn run_loop<B: Backend, S: Storage>(
+    terminal: &mut Terminal<B>,
+    storage: &S,
+) -> Result<(), CrawlerError> {
+    let mut rate = RateHistory::new(RATE_HISTORY);
+    loop {
+        let snapshot = snapshot(storage).await?;
+        rate.push(snapshot.counts.results);
+        terminal.draw(|frame| draw(frame, &snapshot, &rate))?;
+
+        let quit = tokio::task::block_in_place(|| -> Result<bool, std::io::Error> {
+            if event::poll(REFRESH_INTERVAL)? {
+                if let Event::Key(key) = event::read()? {
+                    return Ok(key.kind == KeyEventKind::Press && key.code == KeyCode::Char('q'));
+                }
+            }
+            Ok(false)
+        })?;
+        if quit {
+            return Ok(());
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, snapshot: &Snapshot, rate: &RateHistory) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(5),
+            Constraint::Min(0),
+        ])
+        .split(frame.area());
+
+    let c = &snapshot.counts;
+    let counts = Paragraph::new(Line::from(format!(
+        "Queued: {}  Running: {}  Visited: {}  Results: {}  Warned: {}",
+        c.queued, c.running, c.visited, c.results, c.warned
+    )))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Counts (q to quit)"),
+    );
+    frame.render_widget(counts, rows[0]);
+
+    let samples = rate.samples();
+    let sparkline = Sparkline::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Extracted per second"),
+        )
+        .data(&samples)
+        .style(Style::default().fg(Color::Green));
+    frame.render_widget(sparkline, rows[1]);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(rows[2]);
+
+    let results: Vec<ListItem> = snapshot
+        .recent_results
+        .iter()
+        .map(|r| ListItem::new(r.title.clone().unwrap_or_else(|| r.url.clone())))
+        .collect();
+    let results = List::new(results).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Last extracted"),
+    );
+    frame.render_widget(results, columns[0]);
+
+    let warnings: Vec<ListItem> = snapshot
+        .recent_warnings
+        .iter()
+        .map(|w| {
+            ListItem::new(format!(
+                "[{}] {}",
+                w.reason.as_deref().unwrap_or("unknown"),
+                w.url
+            ))
+        })
+        .collect();
+    let warnings = List::new(warnings)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Last warnings"),
+        )
+        .style(Style::default().fg(Color::Yellow));
+    frame.render_widget(warnings, columns[1]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detik::{DetikArticle, DetikData};
+    use crate::Table;
+    use ratatui::backend::TestBackend;
+    use std::path::Path;
+    use tokio::fs;
+
+    fn article(title: &str) -> DetikArticle {
+        DetikArticle {
+            title: Some(title.to_string()),
+            published_date: None,
+            description: None,
+            thumbnail_url: None,
+            author: None,
+            desk: None,
+            category: None,
+            language: None,
+            keywords: vec![],
+            keywords_raw: None,
+            videos: vec![],
+            photos: vec![],
+            body_selector: None,
+            paragraphs: vec!["p".to_string()],
+        }
+    }
+
+    #[tokio::test]
+    async fn snapshot_reads_counts_and_recent() {
+        if Path::new("test_dashboard.db").is_file() {
+            fs::remove_file("test_dashboard.db").await.unwrap();
+        }
+        let p = DetikData::new("test_dashboard").await.unwrap();
+
+        p.queued.insert("q1").await.unwrap();
+        p.queued.insert("q2").await.unwrap();
+        p.running.insert("r1").await.unwrap();
+        p.visited.insert("v1").await.unwrap();
+        p.warned.insert(("w1", "empty_paragraphs")).await.unwrap();
+        p.results.insert(("a1", article("First"))).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        p.results.insert(("a2", article("Second"))).await.unwrap();
+
+        let s = snapshot(&p).await.unwrap();
+        assert_eq!(
+            s.counts,
+            Counts {
+                queued: 2,
+                running: 1,
+                visited: 1,
+                results: 2,
+                warned: 1,
+            }
+        );
+        let titles: Vec<_> = s.recent_results.iter().map(|r| r.title.clone()).collect();
+        assert_eq!(
+            titles,
+            vec![Some("Second".to_string()), Some("First".to_string())]
+        );
+        assert_eq!(s.recent_warnings.len(), 1);
+        assert_eq!(s.recent_warnings[0].url, "w1");
+        assert_eq!(
+            s.recent_warnings[0].reason.as_deref(),
+            Some("empty_paragraphs")
+        );
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal
+            .draw(|frame| draw(frame, &s, &RateHistory::new(RATE_HISTORY)))
+            .unwrap();
+        let rendered: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|c| c.symbol())
+            .collect();
+        assert!(rendered.contains("Results: 2"));
+        assert!(rendered.contains("Second"));
+        assert!(rendered.contains("[empty_paragraphs] w1"));
+
+        fs::remove_file("test_dashboard.db").await.unwrap();
+    }
+
+    #[test]
+    fn rate_history() {
+        let mut rate = RateHistory::new(3);
+        rate.push(10);
+        assert!(rate.samples().is_empty());
+        rate.push(12);
+        rate.push(12);
+        rate.push(17);
+        assert_eq!(rate.samples(), vec![2, 0, 5]);
+        rate.push(18);
+        assert_eq!(rate.samples(), vec![0, 5, 1]);
+    }
+}
@@ -1,6 +1,12 @@
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "sqlite")]
 use sqlx::{Row, SqlitePool};
+#[cfg(feature = "sqlite")]
 use std::fmt::Display;
 
+/// A table of the SQLite database of a site.
+#[cfg(feature = "sqlite")]
 #[async_trait::async_trait]
 pub trait Table {
     type Record<'a>;
@@ -40,3 +46,119 @@ pub trait Table {
             .try_get(0)?)
     }
 }
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResultSummary {
+    pub url: String,
+    pub title: Option<String>,
+    pub published_date: Option<DateTime<FixedOffset>>,
+    pub created_at: Option<DateTime<FixedOffset>>,
+    /// The page its link was first found on, `None` for a seed
+    #[serde(default)]
+    pub referrer: Option<String>,
+}
+
+/// A stored article, with the fields every result table has.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResultEntry {
+    pub url: String,
+    pub title: Option<String>,
+    pub published_date: Option<DateTime<FixedOffset>>,
+    pub created_at: Option<DateTime<FixedOffset>>,
+    /// The page its link was first found on, `None` for a seed
+    pub referrer: Option<String>,
+    pub paragraphs: Vec<String>,
+}
+
+impl ResultEntry {
+    /// The entry of `article` at `url`, before it's stored.
+    pub fn new<A: crate::Article>(url: &str, article: &A) -> Self {
+        ResultEntry {
+            url: url.to_string(),
+            title: article.get_title().map(ToString::to_string),
+            published_date: article.get_published_date(),
+            created_at: None,
+            referrer: None,
+            paragraphs: article.get_paragraphs().to_vec(),
+        }
+    }
+}
+
+/// How much of an article was extracted, stored with its result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtractionStatus {
+    /// Its paragraphs and metadata
+    Full,
+    /// Only its metadata, the body came back empty, see
+    /// `RunOptions::keep_metadata_on_empty_body`
+    MetadataOnly,
+}
+
+impl ExtractionStatus {
+    /// How the status is stored, `full` or `metadata_only`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExtractionStatus::Full => "full",
+            ExtractionStatus::MetadataOnly => "metadata_only",
+        }
+    }
+
+    /// The status stored as `status`, full for anything unknown.
+    pub fn parse(status: &str) -> Self {
+        match status {
+            "metadata_only" => ExtractionStatus::MetadataOnly,
+            _ => ExtractionStatus::Full,
+        }
+    }
+}
+
+/// A version of an article found changed when it was revisited, see `revisit`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Revision {
+    pub url: String,
+    pub title: Option<String>,
+    pub published_date: Option<DateTime<FixedOffset>>,
+    pub paragraphs: Vec<String>,
+    /// What changed from the previous version, see `revisit::diff`
+    pub diff: String,
+    pub created_at: Option<DateTime<FixedOffset>>,
+}
+
+/// The backoff of a host after it answered 429 Too Many Requests, kept across restarts, see
+/// `backoff`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostState {
+    pub host: String,
+    /// How many times the delay between requests to the host is stretched, 1 once it
+    /// recovered
+    pub multiplier: u32,
+    /// No request is made to the host before then
+    pub resume_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+/// How often a hub page, a page of links only, finds new links, kept to tell when it's
+/// crawled again, see `recrawl`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HubState {
+    pub url: String,
+    /// New links found an hour, a moving average over its crawls
+    pub rate: f64,
+    /// The crawls the rate was measured over, none the first time it's crawled
+    pub recrawls: u32,
+    /// How long until it's crawled again
+    pub interval: std::time::Duration,
+    pub crawled_at: DateTime<FixedOffset>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WarnedEntry {
+    pub url: String,
+    pub reason: Option<String>,
+    pub created_at: Option<DateTime<FixedOffset>>,
+    /// Retries already made by the `RetryPolicy`
+    pub attempts: u32,
+    /// When it's queued again, `None` when it's not retried
+    pub next_retry_at: Option<DateTime<FixedOffset>>,
+}
@@ -0,0 +1,66 @@
+//! The articles mixing a short body with a photo carousel, whose slides live on pages of their
+//! own. The captions the carousel doesn't carry are read from those pages while the article is
+//! crawled, so its slides end up in its `photos` rather than crawled one by one.
+
+use super::{crawler, DetikArticle, DetikCrawler};
+use crate::{health, AsyncCrawler, Crawler, CrawlerResult, FetchContext, FetchDecision};
+use reqwest::Url;
+
+/// The slide pages fetched for an article at most, the slides after them are kept without
+/// their caption.
+pub const MAX_SLIDES: usize = 30;
+
+/// A `DetikCrawler` reading the missing captions of the carousel slides from their pages.
+#[derive(Debug, Clone)]
+pub struct CarouselCrawler {
+    crawler: DetikCrawler,
+    max_slides: usize,
+}
+
+impl CarouselCrawler {
+    pub fn new(crawler: DetikCrawler) -> Self {
+        CarouselCrawler {
+            crawler,
+            max_slides: MAX_SLIDES,
+        }
+    }
+
+    /// Fetch at most `max_slides` slide pages per article, `MAX_SLIDES` by default.
+    pub fn with_max_slides(mut self, max_slides: usize) -> Self {
+        self.max_slides = max_slides;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncCrawler for CarouselCrawler {
+    type Document = DetikArticle;
+
+    async fn crawl(&self, ctx: &FetchContext<'_>, html: &str) -> CrawlerResult<DetikArticle> {
+        let mut result = AsyncCrawler::crawl(&self.crawler, ctx, html).await;
+        if let CrawlerResult::DocumentAndLinks(article, _) = &mut result {
+            let missing = article
+                .photos
+                .iter_mut()
+                .filter(|photo| photo.caption.is_none())
+                .filter_map(|photo| Some((photo.slide_url.clone()?, photo)))
+                .take(self.max_slides);
+            for (slide_url, photo) in missing {
+                match ctx.fetch(&slide_url).await {
+                    Ok(slide) => photo.caption = crawler::slide_caption(&slide),
+                    Err(e) => tracing::debug!("No caption read from {}: {}", slide_url, e),
+                }
+            }
+        }
+        result
+    }
+
+    /// The one of the `DetikCrawler`, without fetching the slides.
+    async fn health_check(&self, ctx: &FetchContext<'_>, html: &str) -> health::HealthReport {
+        AsyncCrawler::health_check(&self.crawler, ctx, html).await
+    }
+
+    fn should_fetch(&self, url: &Url) -> FetchDecision {
+        Crawler::should_fetch(&self.crawler, url)
+    }
+}
@@ -1,10 +1,16 @@
-use crate::{detik::DetikArticle, Crawler, CrawlerResult};
+use crate::{
+    detik::{DetikArticle, Photo},
+    lang,
+    rewrite::{self, UrlRewrite},
+    selectors::SelectorOverrides,
+    text::{self, ParagraphCleaner},
+    utils, CrawlContext, Crawler, CrawlerResult, ExtractedLink, FetchDecision, Links, INTERSTITIAL,
+    TRUNCATED,
+};
 use chrono::DateTime;
-use itertools::Itertools;
-use lazy_regex::regex;
+use lazy_regex::Regex;
 use lazy_static::lazy_static;
-use scraper::{Html, Selector};
-use std::borrow::Borrow;
+use scraper::{ElementRef, Html, Selector};
 
 const E: &str = "Invalid selector";
 lazy_static! {
@@ -15,83 +21,443 @@ lazy_static! {
         Selector::parse(r#"meta[property="og:description"]"#).expect(E);
     static ref PUBLISH_DATE: Selector =
         Selector::parse(r#"meta[name="dtk:publishdate"]"#).expect(E);
+    static ref DATE: Selector = Selector::parse(".detail__date, .date").expect(E);
     static ref THUMBNAIL: Selector = Selector::parse(r#"meta[name="thumbnailUrl"]"#).expect(E);
     static ref AUTHOR: Selector = Selector::parse(r#"meta[name="dtk:author"]"#).expect(E);
+    static ref ACCOUNT_TYPE: Selector = Selector::parse(r#"meta[name="dtk:acctype"]"#).expect(E);
     static ref KEYWORDS: Selector = Selector::parse(r#"meta[name="dtk:keywords"]"#).expect(E);
-    static ref BODY1: Selector =
-        Selector::parse(r#"div[class="detail__body-text itp_bodycontent"]"#).expect(E);
-    static ref BODY_SPORT: Selector = Selector::parse(r#"div[class="detail_text"]"#).expect(E);
-    static ref BODY_INET: Selector =
-        Selector::parse(r#"div[class="itp_bodycontent detail__body-text"]"#).expect(E);
-    static ref BODY_TRAVEL: Selector = Selector::parse(r#"div[id="detikdetailtext"]"#).expect(E);
+    static ref BODY: Selector = Selector::parse(&BODY_SELECTORS.join(", ")).expect(E);
+    static ref BODIES: Vec<(&'static str, Selector)> = BODY_SELECTORS
+        .iter()
+        .map(|selector| (*selector, Selector::parse(selector).expect(E)))
+        .collect();
     static ref P: Selector = Selector::parse("p").expect(E);
-    static ref A: Selector = Selector::parse("a").expect(E);
+    static ref IFRAME: Selector = Selector::parse("iframe[src]").expect(E);
+    static ref OG_VIDEO: Selector = Selector::parse(r#"meta[property="og:video"]"#).expect(E);
+    static ref CAROUSEL_SLIDE: Selector =
+        Selector::parse(".detail__media-carousel .carousel__item").expect(E);
+    static ref IMG: Selector = Selector::parse("img").expect(E);
+    static ref ANCHOR: Selector = Selector::parse("a[href]").expect(E);
+    static ref SLIDE_CAPTION: Selector =
+        Selector::parse(".detail__media-caption, figcaption").expect(E);
+    static ref SLIDE_PATH: Regex = Regex::new(r"/d-\d+/[^/]+/foto-\d+/?$").unwrap();
+    // "Baca juga" boxes, also found outside of the body, and the "Berita Terkait" widgets
+    static ref RELATED: Selector =
+        Selector::parse(".linksisip, .lihatjg, .list__terkait, .related").expect(E);
+    // The interstitial ad and the adblock wall served in place of a page until their cookie
+    // is set
+    static ref INTERSTITIAL_WALL: Selector =
+        Selector::parse("#interstitial, .interstitial, .adblock-wall, #adblock-notice").expect(E);
+    static ref DEFAULT_STOP_MARKERS: Vec<Regex> = STOP_MARKERS
+        .iter()
+        .map(|marker| Regex::new(marker).expect("Invalid stop marker"))
+        .collect();
+    static ref DEFAULT_TITLE_SUFFIXES: Vec<Regex> = TITLE_SUFFIXES
+        .iter()
+        .map(|suffix| Regex::new(suffix).expect("Invalid title suffix"))
+        .collect();
+    static ref DESK: Regex = Regex::new(r"(?i)^(tim\s+)?(20)?detik\w*(\.com)?$").unwrap();
+    static ref AUTHOR_SEPARATOR: Regex = Regex::new(r"\s+[-–]\s+").unwrap();
 }
 
-#[derive(Debug)]
-pub struct DetikCrawler;
+/// The body containers of every vertical, matched by class so it doesn't matter in which
+/// order detik writes them. `itp_bodycontent_wrapper` also holds the tags and related links
+/// and must not match. The one an article was read from is its `DetikArticle::body_selector`.
+pub const BODY_SELECTORS: [&str; 6] = [
+    "#detikdetailtext",
+    ".itp_bodycontent",
+    ".detail__body-text",
+    ".detail_text",
+    ".detail__body-regional",
+    ".read__content",
+];
+
+/// Paragraphs from which the rest of the body is navigation or promotion: the page list of
+/// multi-page articles and the video block closing some templates. Matched against the text
+/// of the paragraph, without its tags.
+pub const STOP_MARKERS: [&str; 2] = [
+    r"(?i)^halaman(\s+\d+)+(\s+(selanjutnya|berikutnya))?$",
+    r"(?i)^tonton\s+juga\s+video\b",
+];
+
+/// The names of the selectors replaced by `DetikCrawler::with_selectors`. `body` matches
+/// every body container, see `BODY_SELECTORS`.
+pub const SELECTOR_NAMES: [&str; 13] = [
+    "content_type",
+    "title",
+    "description",
+    "publish_date",
+    "date",
+    "thumbnail",
+    "author",
+    "account_type",
+    "keywords",
+    "body",
+    "related",
+    "interstitial",
+    "carousel_slide",
+];
+
+/// The desk some templates append to the `og:title`, " - detikNews" or " | detikcom",
+/// stripped from the titles.
+pub const TITLE_SUFFIXES: [&str; 1] = [r"(?i)\s+[-–|]\s+(tim\s+)?(20)?detik\w*(\.com)?$"];
+
+/// Sections without articles, only photos or infographics, never fetched.
+pub const SKIPPED_PATHS: [&str; 2] = ["/foto-news/", "/pemilu/infografis/"];
+
+/// The slides of the photo carousels, `/d-<id>/<slug>/foto-<n>` under their article, never
+/// fetched on their own: see `CarouselCrawler`.
+pub fn is_slide(url: &reqwest::Url) -> bool {
+    SLIDE_PATH.is_match(url.path())
+}
+
+/// The video site, whose pages hold a player and no article, never fetched.
+pub const VIDEO_HOST: &str = "20.detik.com";
+
+#[derive(Debug, Clone)]
+pub struct DetikCrawler {
+    allowlist: utils::HostAllowlist,
+    https_hosts: Vec<String>,
+    url_rewrites: Vec<UrlRewrite>,
+    cleaner: ParagraphCleaner,
+    title_suffixes: Vec<Regex>,
+    selectors: SelectorOverrides,
+    raw_keywords: bool,
+}
+
+impl Default for DetikCrawler {
+    fn default() -> Self {
+        DetikCrawler {
+            allowlist: utils::HostAllowlist::new(["detik.com"]),
+            https_hosts: utils::HTTPS_HOSTS.iter().map(ToString::to_string).collect(),
+            url_rewrites: UrlRewrite::detik(),
+            cleaner: ParagraphCleaner::new().with_stop_markers(DEFAULT_STOP_MARKERS.clone()),
+            title_suffixes: DEFAULT_TITLE_SUFFIXES.clone(),
+            selectors: SelectorOverrides::default(),
+            raw_keywords: false,
+        }
+    }
+}
+
+impl DetikCrawler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    utils::allowlist_builders!("detik.com");
+
+    /// Upgrade `http://` links to these hosts or their subdomains to https instead of dropping
+    /// them, every supported site by default.
+    pub fn with_https_hosts<I, H>(mut self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = H>,
+        H: Into<String>,
+    {
+        self.https_hosts = hosts.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Also keep the `dtk:keywords` of the articles as they came, in `keywords_raw`, to audit
+    /// their cleanup by `text::clean_keywords`.
+    pub fn with_raw_keywords(mut self, raw_keywords: bool) -> Self {
+        self.raw_keywords = raw_keywords;
+        self
+    }
+
+    /// Rewrite the links with these rules before following them, `UrlRewrite::detik()` by
+    /// default.
+    pub fn with_url_rewrites(mut self, rules: Vec<UrlRewrite>) -> Self {
+        self.url_rewrites = rules;
+        self
+    }
+
+    /// Also cut the body at paragraphs matching one of `markers`, on top of `STOP_MARKERS`.
+    pub fn with_stop_markers<I: IntoIterator<Item = Regex>>(mut self, markers: I) -> Self {
+        self.cleaner = self.cleaner.with_stop_markers(markers);
+        self
+    }
+
+    /// Keep every paragraph, even those matching `STOP_MARKERS`.
+    pub fn without_stop_markers(mut self) -> Self {
+        self.cleaner = self.cleaner.without_stop_markers();
+        self
+    }
+
+    /// Also strip these suffixes from the titles, on top of `TITLE_SUFFIXES`.
+    pub fn with_title_suffixes<I: IntoIterator<Item = Regex>>(mut self, suffixes: I) -> Self {
+        self.title_suffixes.extend(suffixes);
+        self
+    }
+
+    /// Read the pages with these selectors in place of the built-in ones of the same name,
+    /// see `SELECTOR_NAMES`.
+    pub fn with_selectors(mut self, selectors: SelectorOverrides) -> Self {
+        self.selectors = selectors;
+        self
+    }
+
+    /// The selector named `name`, `default` unless it's overridden.
+    fn selector<'a>(&'a self, name: &str, default: &'a Selector) -> &'a Selector {
+        self.selectors.get(name).unwrap_or(default)
+    }
+
+    /// `title` without the `title_suffixes`, nor the `authors` some templates write before
+    /// them: "Judul - Rifat Alhamidi - detikJabar" is "Judul".
+    fn clean_title(&self, title: &str, authors: &[&str]) -> String {
+        let mut title = title.trim();
+        loop {
+            let suffix = self
+                .title_suffixes
+                .iter()
+                .find_map(|suffix| suffix.find(title))
+                .map(|m| m.start())
+                .or_else(|| {
+                    let separator = AUTHOR_SEPARATOR.find_iter(title).last()?;
+                    let last = &title[separator.end()..];
+                    authors
+                        .iter()
+                        .any(|author| last.trim().eq_ignore_ascii_case(author))
+                        .then(|| separator.start())
+                });
+            match suffix.filter(|&start| start > 0) {
+                Some(start) => title = title[..start].trim_end(),
+                None => break,
+            }
+        }
+        title.to_string()
+    }
+
+    /// Clean the paragraphs with `cleaner` instead, which replaces the stop markers too.
+    pub fn with_paragraph_cleaner(mut self, cleaner: ParagraphCleaner) -> Self {
+        self.cleaner = cleaner;
+        self
+    }
+
+    /// The links to follow, with whether their anchor is in the body or a related-articles
+    /// widget.
+    fn anchor_links<'a>(
+        &'a self,
+        url: &'a reqwest::Url,
+        doc: &'a Html,
+    ) -> impl Iterator<Item = (ExtractedLink, bool)> + 'a {
+        self.allowed_anchors(url, doc)
+            .map(|(url, a)| (utils::extracted_link(url, &a), self.is_related(&a)))
+    }
+
+    /// The anchors to follow with their rewritten url.
+    fn allowed_anchors<'a>(
+        &'a self,
+        url: &'a reqwest::Url,
+        doc: &'a Html,
+    ) -> impl Iterator<Item = (reqwest::Url, ElementRef<'a>)> + 'a {
+        utils::anchor_elements(doc, url, &self.https_hosts)
+            .map(|(url, a)| (rewrite::rewrite(url, &self.url_rewrites), a))
+            .filter(|(url, _)| self.allowlist.allows(url))
+    }
+
+    /// The links of an article, a url linked from both the body and the navigation being
+    /// related.
+    fn classified_links(&self, url: &reqwest::Url, doc: &Html) -> Links {
+        let (related, other): (Vec<_>, Vec<_>) = self
+            .anchor_links(url, doc)
+            .partition(|(_, related)| *related);
+        let urls = |links: Vec<(ExtractedLink, bool)>| -> Vec<String> {
+            utils::unique_links(links.into_iter().map(|(link, _)| link))
+                .into_iter()
+                .map(|link| link.url)
+                .collect()
+        };
+        let related = urls(related);
+        let other = urls(other)
+            .into_iter()
+            .filter(|url| !related.contains(url))
+            .collect();
+        Links { related, other }
+    }
+
+    /// Whether the anchor `a` is editorial: in the body or a related-articles widget.
+    fn is_related(&self, a: &ElementRef) -> bool {
+        let body = self.selector("body", &BODY);
+        let related = self.selector("related", &RELATED);
+        a.ancestors()
+            .filter_map(ElementRef::wrap)
+            .any(|el| body.matches(&el) || related.matches(&el))
+    }
+}
+
+/// The videos of an article: the 20detik embeds of its body, as the anchors the cleaner
+/// drops from the paragraphs or as player iframes, then its `og:video`.
+fn videos(url: &reqwest::Url, doc: &Html, bodies: &[ElementRef], raw: &[String]) -> Vec<String> {
+    let absolute = |video: &str| url.join(video.trim()).ok();
+    let anchors = raw
+        .iter()
+        .filter_map(|p| text::embed_url(p))
+        .filter_map(|video| absolute(&video));
+    let iframes = bodies
+        .iter()
+        .flat_map(|el| el.select(&IFRAME))
+        .filter_map(|iframe| absolute(iframe.value().attr("src")?))
+        .filter(|video| {
+            video
+                .host_str()
+                .is_some_and(|host| utils::host_matches(host, "20.detik.com"))
+        });
+    let og_video = doc
+        .select(&OG_VIDEO)
+        .filter_map(|meta| absolute(meta.value().attr("content")?));
+    let videos = anchors
+        .chain(iframes)
+        .chain(og_video)
+        .map(String::from)
+        .collect();
+    utils::first_seen(videos, String::as_str)
+}
+
+/// The people and the desk credited in a `dtk:author`, "Rifat Alhamidi - detikJabar" or
+/// "Tim detikcom": the desks, matched by name, aren't authors.
+fn split_author(author: &str) -> (Vec<&str>, Option<&str>) {
+    let (desks, people): (Vec<&str>, Vec<&str>) = AUTHOR_SEPARATOR
+        .split(author.trim())
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .partition(|part| DESK.is_match(part));
+    (people, desks.first().copied())
+}
+
+/// The slides of the photo carousel of an article, with the captions the carousel carries in
+/// their `data-caption` or `figcaption`. Slides without an image are left out.
+fn photos(url: &reqwest::Url, doc: &Html, slides: &Selector) -> Vec<Photo> {
+    let absolute = |link: &str| url.join(link.trim()).ok().map(String::from);
+    doc.select(slides)
+        .filter_map(|slide| {
+            let img = slide.select(&IMG).next()?.value();
+            let src = img.attr("data-src").or_else(|| img.attr("src"))?;
+            let caption = match slide.value().attr("data-caption") {
+                Some(caption) => caption_text(caption),
+                None => slide
+                    .select(&SLIDE_CAPTION)
+                    .next()
+                    .and_then(|el| caption_text(&el.text().collect::<String>())),
+            };
+            let slide_url = slide
+                .value()
+                .attr("data-slide-url")
+                .or_else(|| slide.select(&ANCHOR).next()?.value().attr("href"))
+                .and_then(absolute);
+            Some(Photo {
+                url: absolute(src)?,
+                caption,
+                slide_url,
+            })
+        })
+        .collect()
+}
+
+/// The caption of the slide page `html`, from its caption block else its `og:description`.
+pub(super) fn slide_caption(html: &str) -> Option<String> {
+    let doc = Html::parse_document(html);
+    doc.select(&SLIDE_CAPTION)
+        .find_map(|el| caption_text(&el.text().collect::<String>()))
+        .or_else(|| caption_text(&utils::meta_content(&doc, &DESCRIPTION)?))
+}
+
+/// `caption` with its whitespace collapsed, none when it's blank.
+fn caption_text(caption: &str) -> Option<String> {
+    Some(caption.split_whitespace().collect::<Vec<_>>().join(" ")).filter(|c| !c.is_empty())
+}
+
+/// Regional pages write the content type in other cases or with stray whitespace.
+fn is_single_page_news(content_type: &str) -> bool {
+    content_type.trim().eq_ignore_ascii_case("singlepagenews")
+}
+
+/// The channel of an "acc-detikjateng" or "acc-wolipop" account type, here `jateng` and
+/// `wolipop`.
+fn category(account_type: &str) -> Option<String> {
+    let account = account_type.trim().strip_prefix("acc-")?;
+    Some(account.strip_prefix("detik").unwrap_or(account))
+        .filter(|s| !s.is_empty())
+        .map(str::to_lowercase)
+}
 
 impl Crawler for DetikCrawler {
     type Document = DetikArticle;
 
     fn can_be_scrapped(&self, doc: &Html) -> bool {
-        match doc.select(&CONTENT_TYPE).next() {
-            Some(content_type) => {
-                matches!(content_type.value().attr("content"), Some("singlepagenews"))
-            }
+        match doc
+            .select(self.selector("content_type", &CONTENT_TYPE))
+            .next()
+        {
+            Some(content_type) => content_type
+                .value()
+                .attr("content")
+                .is_some_and(is_single_page_news),
             None => false,
         }
     }
 
-    fn extract_links(&self, doc: &Html) -> Vec<String> {
-        doc.select(&A)
-            .into_iter()
-            .filter_map(|a| a.value().attr("href"))
-            .map(str::trim)
-            .filter(|l| {
-                !l.is_empty()
-                    && !l.starts_with('#')
-                    && l.contains("detik.com")
-                    && l.starts_with("https://")
-            })
-            .filter_map(|s| match reqwest::Url::parse(s).ok() {
-                Some(url) => url.host().and_then(|host| {
-                    if host.to_string().contains("detik.com") {
-                        Some(s)
-                    } else {
-                        None
-                    }
-                }),
-                None => None,
-            })
-            .map(|s| s.trim_end_matches('/'))
-            .sorted()
-            .dedup()
-            .map(ToString::to_string)
-            .collect()
+    fn extract_links(&self, ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        utils::unique_links(
+            self.anchor_links(ctx.final_url(), doc)
+                .map(|(link, _)| link),
+        )
     }
 
-    fn crawl(&self, doc: &Html) -> CrawlerResult<Self::Document> {
-        let links = self.extract_links(doc);
+    /// The urls of `extract_links`, without reading the anchor texts: index pages are the
+    /// hot path of a crawl.
+    fn extract_urls(&self, ctx: &CrawlContext, doc: &Html) -> Vec<String> {
+        let urls = self
+            .allowed_anchors(ctx.final_url(), doc)
+            .map(|(url, _)| utils::link_url(url))
+            .collect();
+        utils::first_seen(urls, String::as_str)
+    }
 
+    /// Not the `SKIPPED_PATHS`, the slides of the carousels nor the pages of `VIDEO_HOST`.
+    fn should_fetch(&self, url: &reqwest::Url) -> FetchDecision {
+        let video = url
+            .host_str()
+            .is_some_and(|host| utils::host_matches(host, VIDEO_HOST));
+        if video || is_slide(url) || SKIPPED_PATHS.iter().any(|path| url.path().contains(path)) {
+            FetchDecision::SkipPermanently
+        } else {
+            FetchDecision::Fetch
+        }
+    }
+
+    fn crawl(&self, ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
         if !self.can_be_scrapped(doc) {
-            return CrawlerResult::Links(links);
+            if doc
+                .select(self.selector("interstitial", &INTERSTITIAL_WALL))
+                .next()
+                .is_some()
+            {
+                return CrawlerResult::Skipped(INTERSTITIAL.to_string(), vec![]);
+            }
+            return CrawlerResult::Links(self.extract_urls(ctx, doc));
+        }
+        // The page is where its HTTP redirects ended, e.g. the mobile site of the url linked
+        let page_url = ctx.final_url();
+        let mut links = self.classified_links(page_url, doc);
+        // The end of the body, and the stop markers cutting it, may be missing
+        if ctx.truncated {
+            return CrawlerResult::Skipped(TRUNCATED.to_string(), links.into());
         }
 
         let title = doc
-            .select(&TITLE)
+            .select(self.selector("title", &TITLE))
             .next()
             .and_then(|el| el.value().attr("content"))
             .map(ToString::to_string);
 
         let description = doc
-            .select(&DESCRIPTION)
+            .select(self.selector("description", &DESCRIPTION))
             .next()
             .and_then(|el| el.value().attr("content"))
             .map(ToString::to_string);
 
         let published_date = doc
-            .select(&PUBLISH_DATE)
+            .select(self.selector("publish_date", &PUBLISH_DATE))
             .next()
             .and_then(|el| el.value().attr("content"))
             .and_then(|published_date| {
@@ -100,70 +466,85 @@ impl Crawler for DetikCrawler {
                     "%Y/%m/%d %H:%M:%S %z",
                 )
                 .ok()
-            });
+            })
+            .or_else(|| {
+                doc.select(self.selector("date", &DATE))
+                    .find_map(|el| utils::parse_indonesian_datetime(&el.text().collect::<String>()))
+            })
+            // When the page doesn't print it, the last change of the page is the closest
+            .or_else(|| ctx.response.and_then(|response| response.last_modified));
 
         let thumbnail_url = doc
-            .select(&THUMBNAIL)
+            .select(self.selector("thumbnail", &THUMBNAIL))
             .next()
             .and_then(|el| el.value().attr("content"))
             .map(ToString::to_string);
 
-        let author = doc
-            .select(&AUTHOR)
+        let (authors, desk) = doc
+            .select(self.selector("author", &AUTHOR))
             .next()
             .and_then(|el| el.value().attr("content"))
-            .map(ToString::to_string);
+            .map(split_author)
+            .unwrap_or_default();
+        let title = title.map(|title| self.clean_title(&title, &authors));
+        let author = Some(authors.join(", ")).filter(|author| !author.is_empty());
+        let desk = desk.map(ToString::to_string);
 
-        let keywords = doc
-            .select(&KEYWORDS)
+        let category = doc
+            .select(self.selector("account_type", &ACCOUNT_TYPE))
             .next()
-            .and_then(|el| {
-                el.value().attr("content").map(|s| {
-                    s.split(',')
-                        .map(|s| s.trim().to_string())
-                        .collect::<Vec<_>>()
-                })
-            })
-            .unwrap_or_default();
+            .and_then(|el| el.value().attr("content"))
+            .and_then(category);
 
-        let mut paragraphs = vec![];
-        for el in doc
-            .select(&BODY1)
-            .chain(doc.select(&BODY_SPORT))
-            .chain(doc.select(&BODY_INET))
-            .chain(doc.select(&BODY_TRAVEL))
-        {
-            let raw_paragraphs = el.select(&P);
-            for p in raw_paragraphs {
-                if p.value().attr("style").is_none() {
-                    let p = p.inner_html().trim().replace('\n', " ");
-
-                    if p.starts_with("<strong>Lihat juga")
-                        || (p.starts_with("<a") && p.ends_with("</a>") && p.contains("embed"))
-                    {
-                        continue;
-                    }
-
-                    let p = regex!(r"\s+").replace_all(p.borrow(), " ");
-                    let p = regex!(r"(<em>|</em>)").replace_all(p.borrow(), "");
-                    let p = regex!(r"<br>").replace_all(p.borrow(), "\n");
-                    let p = regex!(r"<a.*?>(?P<text>.*?)</a>").replace_all(p.borrow(), "${text}");
-                    let p = regex!(r"<strong>-+</strong>").replace_all(p.borrow(), " ");
-                    let p = p.into_owned().trim_start_matches('\n').trim().to_string();
-
-                    if p.starts_with("<strong>Artikel ini telah naik") {
-                        continue;
-                    }
-                    if !p.is_empty() {
-                        paragraphs.push(p);
-                    }
-                }
+        let keywords_raw = doc
+            .select(self.selector("keywords", &KEYWORDS))
+            .next()
+            .and_then(|el| el.value().attr("content"));
+        let keywords = keywords_raw.map(text::clean_keywords).unwrap_or_default();
+        let keywords_raw = keywords_raw
+            .filter(|_| self.raw_keywords)
+            .map(ToString::to_string);
+
+        // Containers nested in another one, e.g. `.detail__body-text` in `#detikdetailtext`,
+        // would repeat its paragraphs
+        let body = self.selector("body", &BODY);
+        let bodies: Vec<ElementRef> = doc
+            .select(body)
+            .filter(|el| {
+                !el.ancestors()
+                    .filter_map(ElementRef::wrap)
+                    .any(|a| body.matches(&a))
+            })
+            .collect();
+        let raw: Vec<String> = bodies
+            .iter()
+            .flat_map(|el| el.select(&P))
+            .filter(|p| p.value().attr("style").is_none())
+            .map(|p| p.inner_html())
+            .collect();
+        let body_selector = bodies.first().and_then(|body| {
+            BODIES
+                .iter()
+                .find(|(_, selector)| selector.matches(body))
+                .map(|(name, _)| name.to_string())
+                .or_else(|| self.selectors.css("body").map(ToString::to_string))
+        });
+        let videos = videos(page_url, doc, &bodies, &raw);
+        let photos = photos(
+            page_url,
+            doc,
+            self.selector("carousel_slide", &CAROUSEL_SLIDE),
+        );
+        // Linked so they're marked visited by `should_fetch`, whether the carousel has anchors
+        // to them or only data attributes
+        for slide_url in photos.iter().filter_map(|photo| photo.slide_url.as_ref()) {
+            let vetoed = reqwest::Url::parse(slide_url)
+                .is_ok_and(|url| self.allowlist.allows(&url) && is_slide(&url));
+            if vetoed && !links.related.contains(slide_url) && !links.other.contains(slide_url) {
+                links.other.push(slide_url.clone());
             }
         }
-        paragraphs.dedup();
-        if Some("") == paragraphs.last().map(String::as_str) {
-            paragraphs.pop();
-        }
+        let paragraphs = self.cleaner.paragraphs(&raw);
 
         let detik_article = DetikArticle {
             title,
@@ -171,9 +552,444 @@ impl Crawler for DetikCrawler {
             description,
             thumbnail_url,
             author,
+            desk,
+            category,
+            language: lang::detect(&paragraphs).map(ToString::to_string),
             keywords,
+            keywords_raw,
+            videos,
+            photos,
+            body_selector,
             paragraphs,
         };
         CrawlerResult::DocumentAndLinks(detik_article, links)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ResponseMeta;
+
+    #[test]
+    fn content_types() {
+        assert!(is_single_page_news("singlepagenews"));
+        assert!(is_single_page_news(" SinglePageNews "));
+        assert!(!is_single_page_news("singlepagevideo"));
+    }
+
+    fn paragraphs(body: &str) -> Vec<String> {
+        paragraphs_with(DetikCrawler::new(), body)
+    }
+
+    fn paragraphs_with(crawler: DetikCrawler, body: &str) -> Vec<String> {
+        article_with(crawler, body).paragraphs
+    }
+
+    fn article_with(crawler: DetikCrawler, body: &str) -> DetikArticle {
+        let doc = Html::parse_document(&format!(
+            r#"<meta name="dtk:contenttype" content="singlepagenews">{}"#,
+            body
+        ));
+        let url = reqwest::Url::parse("https://news.detik.com/berita/d-1/judul").unwrap();
+        match crawler.crawl(&CrawlContext::new(&url), &doc) {
+            CrawlerResult::DocumentAndLinks(article, _) => article,
+            _ => panic!("Not an article"),
+        }
+    }
+
+    #[test]
+    fn body_specificity() {
+        // The wrapper and navigation around the body stay out
+        assert_eq!(
+            paragraphs(
+                r#"<nav><p>Menu</p></nav><div class="itp_bodycontent_wrapper detail__body">
+                <div class="text-detail itp_bodycontent"><p>Isi.</p></div>
+                <div class="detail__body-tag"><p>Tag: flu</p></div></div>"#
+            ),
+            vec!["Isi."]
+        );
+        // Nested containers are read once
+        assert_eq!(
+            paragraphs(
+                r#"<div id="detikdetailtext"><div class="detail__body-text"><p>Satu.</p></div>
+                <p>Dua.</p></div>"#
+            ),
+            vec!["Satu.", "Dua."]
+        );
+        assert!(paragraphs(r#"<div class="detail__body"><p>Menu</p></div>"#).is_empty());
+    }
+
+    #[test]
+    fn stop_markers() {
+        let body = r#"<div class="detail__body-text"><p>Satu.</p>
+            <p>Halaman rumah warga terendam banjir.</p>
+            <p><strong>Tonton juga video:</strong> Banjir di Pasuruan</p><p>Judul video</p>
+            <p>Halaman <a href="?page=1">1</a> <a href="?page=2">2</a> Selanjutnya</p></div>"#;
+        assert_eq!(
+            paragraphs(body),
+            vec!["Satu.", "Halaman rumah warga terendam banjir."]
+        );
+        assert_eq!(
+            paragraphs(r#"<div class="detail__body-text"><p>Satu.</p><p>Halaman 1 2 3</p></div>"#),
+            vec!["Satu."]
+        );
+
+        let extended =
+            DetikCrawler::new().with_stop_markers([Regex::new("^Halaman rumah").unwrap()]);
+        assert_eq!(paragraphs_with(extended, body), vec!["Satu."]);
+        assert_eq!(
+            paragraphs_with(DetikCrawler::new().without_stop_markers(), body).len(),
+            5
+        );
+    }
+
+    #[test]
+    fn vetoed_urls() {
+        let crawler = DetikCrawler::new();
+        let decision = |url: &str| crawler.should_fetch(&reqwest::Url::parse(url).unwrap());
+        for url in [
+            "https://news.detik.com/foto-news/d-6455003/potret-pernikahan",
+            "https://news.detik.com/pemilu/infografis/d-6455012/jadwal-pemilu",
+            "https://20.detik.com/detikupdate/20221210-221210009/video-ginting",
+            "https://news.detik.com/berita/d-6455015/banjir-bandang-terjang-garut/foto-3",
+        ] {
+            assert_eq!(decision(url), FetchDecision::SkipPermanently, "{}", url);
+        }
+        for url in [
+            "https://news.detik.com/berita/d-6453785/kpu-tetapkan",
+            "https://news.detik.com/pemilu",
+            "https://www.detik.com/tag/foto-news",
+            "https://news.detik.com/berita/d-6455015/foto-warga-garut",
+        ] {
+            assert_eq!(decision(url), FetchDecision::Fetch, "{}", url);
+        }
+    }
+
+    #[test]
+    fn raw_keywords() {
+        let body = r#"<meta name="dtk:keywords" content="Viral, viral , KRL">
+            <div class="detail__body-text"><p>Satu.</p></div>"#;
+        let article = article_with(DetikCrawler::new(), body);
+        assert_eq!(article.keywords, vec!["viral", "krl"]);
+        assert_eq!(article.keywords_raw, None);
+
+        let article = article_with(DetikCrawler::new().with_raw_keywords(true), body);
+        assert_eq!(article.keywords, vec!["viral", "krl"]);
+        assert_eq!(article.keywords_raw.as_deref(), Some("Viral, viral , KRL"));
+    }
+
+    #[test]
+    fn desks_split_out() {
+        let article = |title: &str, author: &str| {
+            let body = format!(
+                r#"<meta property="og:title" content="{}"><meta name="dtk:author" content="{}">
+                <div class="detail__body-text"><p>Satu.</p></div>"#,
+                title, author
+            );
+            let article = article_with(DetikCrawler::new(), &body);
+            (article.title.unwrap(), article.author, article.desk)
+        };
+        assert_eq!(
+            article("Banjir di Bandung - detikNews", "Tim detikcom"),
+            (
+                "Banjir di Bandung".to_string(),
+                None,
+                Some("Tim detikcom".to_string())
+            )
+        );
+        assert_eq!(
+            article(
+                "Banjir di Bandung - Rifat Alhamidi - detikJabar",
+                "Rifat Alhamidi - detikJabar"
+            ),
+            (
+                "Banjir di Bandung".to_string(),
+                Some("Rifat Alhamidi".to_string()),
+                Some("detikJabar".to_string())
+            )
+        );
+        // Nor a name in the middle of the title, nor a title that's only a desk, are cut
+        assert_eq!(
+            article(
+                "Rifat Alhamidi - Wartawan Bandung | detikcom",
+                "Rifat Alhamidi"
+            ),
+            (
+                "Rifat Alhamidi - Wartawan Bandung".to_string(),
+                Some("Rifat Alhamidi".to_string()),
+                None
+            )
+        );
+        assert_eq!(article("detikFinance", "").0, "detikFinance");
+
+        let crawler =
+            DetikCrawler::new().with_title_suffixes([Regex::new(r"\s+\| Wolipop$").unwrap()]);
+        let body = r#"<meta property="og:title" content="Tren Hijab | Wolipop">
+            <div class="detail__body-text"><p>Satu.</p></div>"#;
+        assert_eq!(
+            article_with(crawler, body).title.as_deref(),
+            Some("Tren Hijab")
+        );
+    }
+
+    #[test]
+    fn embedded_videos() {
+        let body = r#"<meta property="og:video" content="https://20.detik.com/embed/3">
+            <div class="detail__body-text"><p>Satu.</p>
+            <p><a href="https://20.detik.com/embed/1" class="embedvideo">embed</a></p>
+            <iframe src="//20.detik.com/embed/2?autoplay=0"></iframe>
+            <iframe src="https://ads.example.com/banner"></iframe>
+            <p><a href="https://20.detik.com/embed/1" class="embedvideo">embed</a></p>
+            <p>Dua.</p></div>"#;
+        let article = article_with(DetikCrawler::new(), body);
+        assert_eq!(
+            article.videos,
+            vec![
+                "https://20.detik.com/embed/1",
+                "https://20.detik.com/embed/2?autoplay=0",
+                "https://20.detik.com/embed/3"
+            ]
+        );
+        assert_eq!(article.paragraphs, vec!["Satu.", "Dua."]);
+
+        let plain = article_with(
+            DetikCrawler::new(),
+            r#"<div class="detail__body-text"><p>Satu.</p><p>Dua.</p></div>"#,
+        );
+        assert!(plain.videos.is_empty());
+        assert_eq!(plain.paragraphs, article.paragraphs);
+    }
+
+    #[test]
+    fn truncated_page() {
+        let url = reqwest::Url::parse("https://news.detik.com/berita/d-1/judul").unwrap();
+        let doc = Html::parse_document(
+            r#"<meta name="dtk:contenttype" content="singlepagenews">
+            <div class="detail__body-text"><p>Satu.</p><a href="/berita/d-2/lain">Lain</a>"#,
+        );
+        let ctx = CrawlContext {
+            truncated: true,
+            ..CrawlContext::new(&url)
+        };
+        match DetikCrawler::new().crawl(&ctx, &doc) {
+            CrawlerResult::Skipped(reason, links) => {
+                assert_eq!(reason, TRUNCATED);
+                assert_eq!(links, vec!["https://news.detik.com/berita/d-2/lain"]);
+            }
+            _ => panic!("Not skipped"),
+        }
+        assert!(matches!(
+            DetikCrawler::new().crawl(&CrawlContext::new(&url), &doc),
+            CrawlerResult::DocumentAndLinks(..)
+        ));
+    }
+
+    #[test]
+    fn interstitial() {
+        let url = reqwest::Url::parse("https://news.detik.com/berita/d-1/judul").unwrap();
+        let crawl = |html: &str| {
+            DetikCrawler::new().crawl(&CrawlContext::new(&url), &Html::parse_document(html))
+        };
+        assert!(matches!(
+            crawl(r#"<div class="adblock-wall"><a href="/berita/d-1/judul">Lanjut</a></div>"#),
+            CrawlerResult::Skipped(reason, links) if reason == INTERSTITIAL && links.is_empty()
+        ));
+        // An article is never an interstitial
+        assert_eq!(
+            paragraphs(
+                r#"<div id="interstitial"></div><div class="detail__body-text"><p>Isi.</p></div>"#
+            ),
+            vec!["Isi."]
+        );
+    }
+
+    #[test]
+    fn http_links_upgraded() {
+        let doc = Html::parse_document(
+            r#"<a href="http://finance.detik.com/d-4/d/">Lama</a>
+            <a href="https://finance.detik.com/d-1/a">Baru</a>
+            <a href="http://example.com/d-2/b">Lain</a>"#,
+        );
+        let url = reqwest::Url::parse("https://news.detik.com/berita/d-3/c").unwrap();
+        let ctx = CrawlContext::new(&url);
+        assert_eq!(
+            DetikCrawler::new().extract_urls(&ctx, &doc),
+            vec![
+                "https://finance.detik.com/d-4/d",
+                "https://finance.detik.com/d-1/a"
+            ]
+        );
+        // Without the upgrade the http link is dropped along with the third-party one
+        let crawler = DetikCrawler::new().with_https_hosts(Vec::<String>::new());
+        assert_eq!(
+            crawler.extract_urls(&ctx, &doc),
+            vec!["https://finance.detik.com/d-1/a"]
+        );
+    }
+
+    #[test]
+    fn anchor_texts() {
+        let doc = Html::parse_document(
+            r#"<p><strong>Baca juga:</strong> <a href="/berita/d-1/judul/">Judul
+            <em>Berita</em> Lain</a></p>
+            <a href="/berita/d-1/judul"><img src="x.jpg"></a>
+            <a href="https://news.detik.com/berita/d-2/iklan" rel=" sponsored ">Iklan</a>"#,
+        );
+        let url = reqwest::Url::parse("https://news.detik.com/berita/d-3/c").unwrap();
+        assert_eq!(
+            DetikCrawler::new().extract_links(&CrawlContext::new(&url), &doc),
+            vec![
+                ExtractedLink {
+                    url: "https://news.detik.com/berita/d-1/judul".to_string(),
+                    text: "Judul Berita Lain".to_string(),
+                    rel: None,
+                },
+                ExtractedLink {
+                    url: "https://news.detik.com/berita/d-2/iklan".to_string(),
+                    text: "Iklan".to_string(),
+                    rel: Some("sponsored".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn related_links() {
+        let doc = Html::parse_document(
+            r#"<meta name="dtk:contenttype" content="singlepagenews">
+            <nav><a href="https://news.detik.com/indeks">Indeks</a>
+            <a href="https://news.detik.com/berita/d-2/dua">Terpopuler</a></nav>
+            <div class="detail__body-text"><p>Satu.</p>
+            <table class="linksisip"><tr><td><div class="lihatjg"><strong>Baca juga:</strong>
+            <a href="https://news.detik.com/berita/d-2/dua">Dua</a></div></td></tr></table></div>
+            <div class="list__terkait"><a href="https://news.detik.com/berita/d-3/tiga">Tiga</a></div>
+            <footer><a href="https://www.detik.com/redaksi">Redaksi</a></footer>"#,
+        );
+        let url = reqwest::Url::parse("https://news.detik.com/berita/d-1/satu").unwrap();
+        match DetikCrawler::new().crawl(&CrawlContext::new(&url), &doc) {
+            CrawlerResult::DocumentAndLinks(_, links) => assert_eq!(
+                links,
+                Links {
+                    related: vec![
+                        "https://news.detik.com/berita/d-2/dua".to_string(),
+                        "https://news.detik.com/berita/d-3/tiga".to_string(),
+                    ],
+                    other: vec![
+                        "https://news.detik.com/indeks".to_string(),
+                        "https://www.detik.com/redaksi".to_string(),
+                    ],
+                }
+            ),
+            _ => panic!("Not an article"),
+        }
+    }
+
+    #[test]
+    fn mobile_and_amp_links() {
+        let doc = Html::parse_document(
+            r#"<a href="https://m.detik.com/news/berita/d-1/judul">Mobile</a>
+            <a href="/berita/d-1/judul/amp">AMP</a>
+            <a href="https://news.detik.com/berita/d-1/judul">Desktop</a>"#,
+        );
+        let url = reqwest::Url::parse("https://news.detik.com/berita/d-3/c").unwrap();
+        let ctx = CrawlContext::new(&url);
+        assert_eq!(
+            DetikCrawler::new().extract_urls(&ctx, &doc),
+            vec!["https://news.detik.com/berita/d-1/judul"]
+        );
+        assert_eq!(
+            DetikCrawler::new()
+                .with_url_rewrites(vec![])
+                .extract_urls(&ctx, &doc)
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn visible_date_fallback() {
+        let doc = Html::parse_document(
+            r#"<meta name="dtk:contenttype" content="singlepagenews">
+            <div class="detail__date">Sabtu, 10 Des 2022 13:19 WIB</div>
+            <div class="detail__body-text"><p>Isi.</p></div>"#,
+        );
+        let url = reqwest::Url::parse("https://news.detik.com/berita/d-1/judul").unwrap();
+        match DetikCrawler::new().crawl(&CrawlContext::new(&url), &doc) {
+            CrawlerResult::DocumentAndLinks(article, _) => assert_eq!(
+                article.published_date,
+                DateTime::parse_from_rfc3339("2022-12-10T13:19:00+07:00").ok()
+            ),
+            _ => panic!("Not an article"),
+        }
+    }
+
+    /// The response of `url` redirected to `final_url`, last modified 2022-12-11 01:00 GMT.
+    fn response(final_url: &str) -> ResponseMeta {
+        ResponseMeta {
+            last_modified: DateTime::parse_from_rfc2822("Sun, 11 Dec 2022 01:00:00 GMT").ok(),
+            ..ResponseMeta::new(
+                reqwest::Url::parse(final_url).unwrap(),
+                DateTime::parse_from_rfc3339("2022-12-12T00:00:00+07:00").unwrap(),
+            )
+        }
+    }
+
+    #[test]
+    fn last_modified_fallback() {
+        let url = reqwest::Url::parse("https://news.detik.com/berita/d-1/judul").unwrap();
+        let response = response(url.as_str());
+        let ctx = CrawlContext::new(&url).with_response(&response);
+        let published_date = |html: &str| {
+            let doc = Html::parse_document(&format!(
+                r#"<meta name="dtk:contenttype" content="singlepagenews">{}
+                <div class="detail__body-text"><p>Isi.</p></div>"#,
+                html
+            ));
+            match DetikCrawler::new().crawl(&ctx, &doc) {
+                CrawlerResult::DocumentAndLinks(article, _) => article.published_date,
+                _ => panic!("Not an article"),
+            }
+        };
+        assert_eq!(published_date(""), response.last_modified);
+        assert_eq!(
+            published_date(r#"<meta name="dtk:publishdate" content="2022/12/10 13:19:00">"#),
+            DateTime::parse_from_rfc3339("2022-12-10T13:19:00+07:00").ok()
+        );
+        assert_eq!(
+            published_date(r#"<div class="detail__date">Sabtu, 10 Des 2022 13:19 WIB</div>"#),
+            DateTime::parse_from_rfc3339("2022-12-10T13:19:00+07:00").ok()
+        );
+    }
+
+    #[test]
+    fn final_url_as_page() {
+        let doc = Html::parse_document(
+            r#"<meta name="dtk:contenttype" content="singlepagenews">
+            <div class="detail__body-text"><p>Isi.</p><a href="/berita/d-2/lain">Lain</a></div>"#,
+        );
+        let url = reqwest::Url::parse("https://news.detik.com/berita/d-1/judul").unwrap();
+        let redirected = response("https://finance.detik.com/berita/d-1/judul");
+        let links = |ctx: &CrawlContext| match DetikCrawler::new().crawl(ctx, &doc) {
+            CrawlerResult::DocumentAndLinks(_, links) => Vec::from(links),
+            _ => panic!("Not an article"),
+        };
+        assert_eq!(
+            links(&CrawlContext::new(&url).with_response(&redirected)),
+            vec!["https://finance.detik.com/berita/d-2/lain"]
+        );
+        assert_eq!(
+            links(&CrawlContext::new(&url)),
+            vec!["https://news.detik.com/berita/d-2/lain"]
+        );
+    }
+
+    #[test]
+    fn categories() {
+        assert_eq!(category("acc-detikjateng"), Some("jateng".to_string()));
+        assert_eq!(category("acc-detikSulsel"), Some("sulsel".to_string()));
+        assert_eq!(category("acc-wolipop"), Some("wolipop".to_string()));
+        assert_eq!(category("acc-detik"), None);
+        assert_eq!(category("detiknews"), None);
+    }
+}
@@ -1,14 +1,43 @@
 use super::DetikArticle;
-use crate::{utils, CrawlerError, Storage, Table};
-use sqlx::{sqlite::SqliteConnectOptions, Row, SqlitePool};
+use crate::analytics::{KeywordCount, KeywordPair, TimeBucket};
+use crate::compression::ParagraphCodec;
+use crate::simhash::{self, NearDuplicate};
+use crate::{text, utils, ExtractionStatus, ResultTable, SqliteStorage, Table, UrlKey};
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+use std::sync::Arc;
+
+/// The columns of the results table `r` read back into a `DetikArticle` by `article_from_row`.
+const ARTICLE_COLUMNS: &str =
+    "COALESCE(r.url, r.id) AS id, r.title, r.published_date, r.description,
+     r.thumbnail_url, r.author, r.desk, r.category, r.language, r.keywords, r.keywords_raw,
+     r.videos, r.photos, r.body_selector, r.paragraphs, r.compression";
 
-pub struct UrlTable {
+pub struct DetikArticleTable {
+    name: String,
+    pool: SqlitePool,
+    authors: AuthorTable,
+    keywords: KeywordTable,
+    codec: Arc<ParagraphCodec>,
+}
+
+/// The number of stored articles of an author.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorCount {
+    pub name: String,
+    pub articles: u32,
+}
+
+/// Every author byline of the results with its `text::normalize_author` form, so the
+/// variants of a name count as one author.
+struct AuthorTable {
     name: String,
     pool: SqlitePool,
 }
 
 #[async_trait::async_trait]
-impl Table for UrlTable {
+impl Table for AuthorTable {
     type Record<'a> = &'a str;
 
     fn get_name(&self) -> &str {
@@ -24,37 +53,91 @@ impl Table for UrlTable {
             let query = format!(
                 "CREATE TABLE {} (
                     id TEXT PRIMARY KEY,
+                    name TEXT,
                     created_at DATETIME
                  )",
                 &self.name
             );
             sqlx::query(query.as_str()).execute(self.get_pool()).await?;
+            let query = format!("CREATE INDEX {0}_name ON {0} (name)", &self.name);
+            sqlx::query(query.as_str()).execute(self.get_pool()).await?;
         }
         Ok(())
     }
 
-    async fn insert<'a>(&self, record: Self::Record<'a>) -> Result<(), sqlx::Error> {
-        let timestamp = utils::get_now();
-        let mut tx = self.get_pool().begin().await?;
+    async fn insert<'a>(&self, author: Self::Record<'a>) -> Result<(), sqlx::Error> {
+        let name = match text::normalize_author(author) {
+            Some(name) => name,
+            None => return Ok(()),
+        };
         let query = format!(
-            "INSERT OR IGNORE INTO {} (id, created_at) VALUES (?, ?)",
+            "INSERT OR REPLACE INTO {} (id, name, created_at) VALUES (?, ?, ?)",
             &self.name
         );
         sqlx::query(&query)
-            .bind(record)
-            .bind(timestamp)
-            .execute(&mut tx)
+            .bind(author)
+            .bind(name)
+            .bind(utils::get_now())
+            .execute(self.get_pool())
             .await?;
-        tx.commit().await?;
         Ok(())
     }
 }
 
-pub struct DetikArticleTable {
+/// The `text::normalize_keyword` forms of the keywords of every article, one row each, so
+/// keywords can be counted in the database.
+struct KeywordTable {
     name: String,
     pool: SqlitePool,
 }
 
+#[async_trait::async_trait]
+impl Table for KeywordTable {
+    type Record<'a> = (&'a str, &'a [String]);
+
+    fn get_name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn get_pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    async fn create(&self) -> Result<(), sqlx::Error> {
+        if !utils::is_table_exists(self.get_pool(), &self.name).await? {
+            let query = format!(
+                "CREATE TABLE {} (
+                    id TEXT,
+                    keyword TEXT,
+                    PRIMARY KEY (id, keyword)
+                 )",
+                &self.name
+            );
+            sqlx::query(query.as_str()).execute(self.get_pool()).await?;
+            let query = format!("CREATE INDEX {0}_keyword ON {0} (keyword)", &self.name);
+            sqlx::query(query.as_str()).execute(self.get_pool()).await?;
+        }
+        Ok(())
+    }
+
+    async fn insert<'a>(&self, (url, keywords): Self::Record<'a>) -> Result<(), sqlx::Error> {
+        let mut tx = self.get_pool().begin().await?;
+        let query = format!(
+            "INSERT OR IGNORE INTO {} (id, keyword) VALUES (?, ?)",
+            &self.name
+        );
+        for keyword in keywords.iter().filter_map(|k| text::normalize_keyword(k)) {
+            sqlx::query(&query)
+                .bind(url)
+                .bind(keyword)
+                .execute(&mut tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
 #[async_trait::async_trait]
 impl Table for DetikArticleTable {
     type Record<'a> = (&'a str, DetikArticle);
@@ -76,16 +159,66 @@ impl Table for DetikArticleTable {
                             created_at DATETIME,
                             title TEXT,
                             author TEXT,
+                            desk TEXT,
+                            category TEXT,
+                            language TEXT,
                             published_date DATETIME,
                             description TEXT,
                             thumbnail_url TEXT,
                             keywords TEXT,
-                            paragraphs TEXT
+                            keywords_raw TEXT,
+                            videos TEXT,
+                            photos TEXT,
+                            body_selector TEXT,
+                            paragraphs TEXT,
+                            compression TEXT,
+                            simhash INTEGER,
+                            title_key TEXT
                         )
                     "#,
                 &self.name
             );
             sqlx::query(query.as_str()).execute(self.get_pool()).await?;
+        } else {
+            // Result tables created before regional categories, languages, hashes, videos, raw
+            // keywords, normalized titles, body selectors, desks or photos were recorded, or
+            // paragraphs compressed
+            for (column, kind) in [
+                ("category", "TEXT"),
+                ("language", "TEXT"),
+                ("simhash", "INTEGER"),
+                ("videos", "TEXT"),
+                ("keywords_raw", "TEXT"),
+                ("title_key", "TEXT"),
+                ("body_selector", "TEXT"),
+                ("compression", "TEXT"),
+                ("desk", "TEXT"),
+                ("photos", "TEXT"),
+            ] {
+                if utils::add_column(self.get_pool(), &self.name, column, kind).await? {
+                    match column {
+                        "simhash" => self.backfill_simhash().await?,
+                        "title_key" => self.backfill_title_keys().await?,
+                        _ => {}
+                    }
+                }
+            }
+        }
+        for column in ["simhash", "title_key"] {
+            let query = format!(
+                "CREATE INDEX IF NOT EXISTS {0}_{1} ON {0} ({1})",
+                &self.name, column
+            );
+            sqlx::query(query.as_str()).execute(self.get_pool()).await?;
+        }
+
+        if !utils::is_table_exists(self.get_pool(), &self.authors.name).await? {
+            self.authors.create().await?;
+            self.backfill_authors().await?;
+        }
+        if !utils::is_table_exists(self.get_pool(), &self.keywords.name).await? {
+            self.keywords.create().await?;
+            self.backfill_keywords().await?;
         }
         Ok(())
     }
@@ -100,200 +233,614 @@ impl Table for DetikArticleTable {
                 description, 
                 thumbnail_url, 
                 author, 
+                desk, 
+                category, 
+                language, 
                 keywords, 
+                keywords_raw, 
+                videos, 
+                photos, 
+                body_selector, 
                 paragraphs, 
-                created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+                compression, 
+                simhash, 
+                title_key, 
+                created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
             self.name
         );
-        sqlx::query(&query)
+        let hash = simhash::simhash(&record.paragraphs).map(|hash| hash as i64);
+        let title_key = record.title.as_deref().and_then(utils::normalize_title);
+        let author = record.author.clone();
+        let paragraphs = self.codec.encode(record.paragraphs.join("\n"))?;
+        let query = sqlx::query(&query)
             .bind(url.trim())
             .bind(record.title)
             .bind(record.published_date)
             .bind(record.description)
             .bind(record.thumbnail_url)
             .bind(record.author)
+            .bind(record.desk)
+            .bind(record.category)
+            .bind(record.language)
             .bind(record.keywords.join("|"))
-            .bind(record.paragraphs.join("\n"))
+            .bind(record.keywords_raw)
+            // A JSON array, urls may hold any separator
+            .bind(serde_json::Value::from(record.videos.clone()).to_string())
+            .bind(serde_json::json!(record.photos).to_string())
+            .bind(record.body_selector);
+        paragraphs
+            .bind(query)
+            .bind(hash)
+            .bind(title_key)
             .bind(utils::get_now())
             .execute(&mut tx)
             .await?;
         tx.commit().await?;
+        if let Some(author) = author {
+            self.authors.insert(&author).await?;
+        }
+        self.keywords.insert((url.trim(), &record.keywords)).await?;
         Ok(())
     }
 }
 
-pub struct DetikData {
-    pub name: String,
-    pub queued: UrlTable,
-    pub visited: UrlTable,
-    pub warned: UrlTable,
-    pub results: DetikArticleTable,
-    pub running: UrlTable,
-    pool: SqlitePool,
-}
-
-impl DetikData {
-    pub async fn new(name: &str) -> Result<DetikData, CrawlerError> {
-        let opt = SqliteConnectOptions::new()
-            .filename(format!("{}.db", name))
-            .create_if_missing(true);
-        let pool = SqlitePool::connect_with(opt).await?;
-        let p = DetikData {
-            name: name.to_string(),
-            queued: UrlTable {
-                name: format!("{}_queued", name),
-                pool: pool.clone(),
-            },
-            running: UrlTable {
-                name: format!("{}_running", name),
-                pool: pool.clone(),
-            },
-            visited: UrlTable {
-                name: format!("{}_visited", name),
-                pool: pool.clone(),
-            },
-            warned: UrlTable {
-                name: format!("{}_warned", name),
-                pool: pool.clone(),
-            },
-            results: DetikArticleTable {
-                name: format!("{}_results", name),
-                pool: pool.clone(),
-            },
-            pool,
-        };
+impl DetikArticleTable {
+    /// Every stored article with its url, oldest first. Line breaks inside a paragraph come
+    /// back as separate paragraphs.
+    pub async fn articles(&self) -> Result<Vec<(String, DetikArticle)>, sqlx::Error> {
+        self.articles_where("", None).await
+    }
 
-        for table in &[&p.queued, &p.running, &p.visited, &p.warned] {
-            if !utils::is_table_exists(&p.pool, &table.name).await? {
-                tracing::debug!("Crate table {}", table.name);
-                table.create().await?;
-            } else {
-                tracing::debug!("Use table {}", table.name);
-            }
+    /// Like `articles`, without those stored with their metadata only, see
+    /// `ExtractionStatus::MetadataOnly`.
+    pub async fn full_articles(&self) -> Result<Vec<(String, DetikArticle)>, sqlx::Error> {
+        // A database opened read-only may predate the column, its results being full
+        if !utils::is_column_exists(&self.pool, &self.name, "extraction_status").await? {
+            return self.articles().await;
         }
-        if !utils::is_table_exists(&p.pool, &p.results.name).await? {
-            tracing::debug!("Crate table {}", p.results.name);
-            p.results.create().await?;
-        } else {
-            tracing::debug!("Use table {}", p.results.name);
+        self.articles_where(
+            "WHERE r.extraction_status = ?",
+            Some(ExtractionStatus::Full.as_str()),
+        )
+        .await
+    }
+
+    /// At most `limit` stored articles with their url, stored after the one of `cursor`,
+    /// oldest first. Each comes with its own cursor, the next page starting after the last of
+    /// them, and `0` starting from the first article.
+    pub async fn results_after(
+        &self,
+        cursor: i64,
+        limit: u32,
+    ) -> Result<Vec<(i64, String, DetikArticle)>, sqlx::Error> {
+        self.articles_after(cursor, limit, None).await
+    }
+
+    /// Like `results_after`, without those stored with their metadata only, see
+    /// `ExtractionStatus::MetadataOnly`.
+    pub async fn full_results_after(
+        &self,
+        cursor: i64,
+        limit: u32,
+    ) -> Result<Vec<(i64, String, DetikArticle)>, sqlx::Error> {
+        // A database opened read-only may predate the column, its results being full
+        if !utils::is_column_exists(&self.pool, &self.name, "extraction_status").await? {
+            return self.results_after(cursor, limit).await;
         }
+        self.articles_after(cursor, limit, Some(ExtractionStatus::Full.as_str()))
+            .await
+    }
 
-        Ok(p)
+    /// The articles of every byline of the author `name`, oldest first. `name` is
+    /// normalized like the bylines, so any of its variants can be given.
+    pub async fn articles_by_author(
+        &self,
+        name: &str,
+    ) -> Result<Vec<(String, DetikArticle)>, sqlx::Error> {
+        let name = match text::normalize_author(name) {
+            Some(name) => name,
+            None => return Ok(vec![]),
+        };
+        let filter = format!(
+            "JOIN {} a ON a.id = r.author WHERE a.name = ?",
+            self.authors.name
+        );
+        self.articles_where(&filter, Some(&name)).await
     }
-}
 
-#[async_trait::async_trait]
-impl Storage for DetikData {
-    type Record = DetikArticle;
+    /// The `limit` authors with the most articles, published since `since` when given, most
+    /// articles first.
+    pub async fn top_authors(
+        &self,
+        limit: u32,
+        since: Option<DateTime<FixedOffset>>,
+    ) -> Result<Vec<AuthorCount>, sqlx::Error> {
+        // datetime() compares the dates in UTC whatever their offset
+        let query = format!(
+            "SELECT a.name AS name, COUNT(*) AS articles
+             FROM {} r JOIN {} a ON a.id = r.author
+             WHERE ?1 IS NULL OR datetime(r.published_date) >= datetime(?1)
+             GROUP BY a.name
+             ORDER BY articles DESC, a.name
+             LIMIT ?2",
+            self.name, self.authors.name
+        );
+        let mut authors = vec![];
+        for row in sqlx::query(&query)
+            .bind(since)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?
+        {
+            authors.push(AuthorCount {
+                name: row.try_get("name")?,
+                articles: row.try_get("articles")?,
+            });
+        }
+        Ok(authors)
+    }
 
-    async fn queued_get(&self) -> Result<Vec<String>, CrawlerError> {
-        let mut urls: Vec<String> = vec![];
+    /// The number of articles read from each of the `BODY_SELECTORS`, published since `since`
+    /// when given, most articles first. None for the articles without a body, or stored before
+    /// their selector was recorded.
+    pub async fn body_selectors(
+        &self,
+        since: Option<DateTime<FixedOffset>>,
+    ) -> Result<Vec<(Option<String>, u32)>, sqlx::Error> {
+        let query = format!(
+            "SELECT body_selector, COUNT(*) AS articles FROM {}
+             WHERE ?1 IS NULL OR datetime(published_date) >= datetime(?1)
+             GROUP BY body_selector
+             ORDER BY articles DESC, body_selector",
+            self.name
+        );
+        let mut selectors = vec![];
+        for row in sqlx::query(&query)
+            .bind(since)
+            .fetch_all(&self.pool)
+            .await?
+        {
+            selectors.push((row.try_get("body_selector")?, row.try_get("articles")?));
+        }
+        Ok(selectors)
+    }
 
-        // Get queue
-        let query = format!("SELECT id FROM {} ORDER BY created_at", self.queued.name);
-        for row in sqlx::query(&query).fetch_all(&self.pool).await? {
-            urls.push(row.try_get("id")?);
+    /// The number of articles published on each day from `from` to `to`, both included,
+    /// days in WIB. Days without articles are left out.
+    pub async fn published_per_day(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, u32)>, sqlx::Error> {
+        // date() reads the dates in UTC, shifted to WIB they fall on their day there
+        let query = format!(
+            "SELECT date(published_date, '+7 hours') AS day, COUNT(*) AS articles FROM {}
+             WHERE day BETWEEN ? AND ?
+             GROUP BY day
+             ORDER BY day",
+            self.name
+        );
+        let mut days = vec![];
+        for row in sqlx::query(&query)
+            .bind(from)
+            .bind(to)
+            .fetch_all(&self.pool)
+            .await?
+        {
+            days.push((row.try_get("day")?, row.try_get("articles")?));
         }
+        Ok(days)
+    }
 
-        Ok(urls)
+    /// Record the bylines of the articles stored before authors were normalized, or
+    /// normalize every byline again after the normalization changed.
+    pub async fn backfill_authors(&self) -> Result<(), sqlx::Error> {
+        let query = format!(
+            "SELECT DISTINCT author FROM {} WHERE author IS NOT NULL",
+            self.name
+        );
+        for row in sqlx::query(&query).fetch_all(&self.pool).await? {
+            let author: String = row.try_get("author")?;
+            self.authors.insert(&author).await?;
+        }
+        Ok(())
     }
 
-    async fn queued_get_n(&self, n: u32) -> Result<Vec<String>, CrawlerError> {
-        let mut in_progress: Vec<String> = vec![];
+    /// Record the keywords of the articles stored before keywords were normalized.
+    pub async fn backfill_keywords(&self) -> Result<(), sqlx::Error> {
         let query = format!(
-            "SELECT id FROM {} ORDER BY created_at LIMIT ?",
-            self.queued.get_name()
+            "SELECT id, keywords FROM {} WHERE keywords IS NOT NULL",
+            self.name
         );
-        for row in sqlx::query(&query).bind(n).fetch_all(&self.pool).await? {
-            in_progress.push(row.try_get("id")?);
+        for row in sqlx::query(&query).fetch_all(&self.pool).await? {
+            let id: String = row.try_get("id")?;
+            let keywords: String = row.try_get("keywords")?;
+            let keywords: Vec<String> = keywords.split('|').map(ToString::to_string).collect();
+            self.keywords.insert((&id, &keywords)).await?;
         }
-        Ok(in_progress)
+        Ok(())
     }
 
-    async fn queued_insert<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
-        let item = item.as_ref();
-        Ok(self.queued.insert(item).await?)
+    /// The `top_n` keywords of the most articles published in each period of `bucket`,
+    /// oldest period first. Articles without a published date are left out.
+    pub async fn keyword_trends(
+        &self,
+        bucket: TimeBucket,
+        top_n: u32,
+    ) -> Result<Vec<KeywordCount>, sqlx::Error> {
+        // The day in WIB, see `published_per_day`, or the Monday of its week
+        let period = match bucket {
+            TimeBucket::Daily => "date(r.published_date, '+7 hours')",
+            TimeBucket::Weekly => "date(r.published_date, '+7 hours', 'weekday 0', '-6 days')",
+        };
+        let query = format!(
+            "WITH counts AS (
+                SELECT {period} AS period, k.keyword AS keyword, COUNT(*) AS articles
+                FROM {results} r JOIN {keywords} k ON k.id = r.id
+                WHERE r.published_date IS NOT NULL
+                GROUP BY period, k.keyword
+             ), ranked AS (
+                SELECT period, keyword, articles, ROW_NUMBER() OVER (
+                    PARTITION BY period ORDER BY articles DESC, keyword
+                ) AS place
+                FROM counts
+             )
+             SELECT period, keyword, articles FROM ranked
+             WHERE place <= ?
+             ORDER BY period, place",
+            period = period,
+            results = self.name,
+            keywords = self.keywords.name,
+        );
+        let mut trends = vec![];
+        for row in sqlx::query(&query)
+            .bind(top_n)
+            .fetch_all(&self.pool)
+            .await?
+        {
+            trends.push(KeywordCount {
+                period: row.try_get("period")?,
+                keyword: row.try_get("keyword")?,
+                articles: row.try_get("articles")?,
+            });
+        }
+        Ok(trends)
     }
 
-    async fn queued_delete<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
-        let item = item.as_ref();
-        Ok(self.queued.delete(item).await?)
+    /// The pairs of keywords found together in at least `min_count` articles, most articles
+    /// first, each pair in alphabetical order.
+    pub async fn keyword_cooccurrence(
+        &self,
+        min_count: u32,
+    ) -> Result<Vec<KeywordPair>, sqlx::Error> {
+        let query = format!(
+            "SELECT a.keyword AS keyword, b.keyword AS other, COUNT(*) AS articles
+             FROM {0} a JOIN {0} b ON a.id = b.id AND a.keyword < b.keyword
+             GROUP BY a.keyword, b.keyword
+             HAVING articles >= ?
+             ORDER BY articles DESC, keyword, other",
+            self.keywords.name
+        );
+        let mut pairs = vec![];
+        for row in sqlx::query(&query)
+            .bind(min_count)
+            .fetch_all(&self.pool)
+            .await?
+        {
+            pairs.push(KeywordPair {
+                keyword: row.try_get("keyword")?,
+                other: row.try_get("other")?,
+                articles: row.try_get("articles")?,
+            });
+        }
+        Ok(pairs)
     }
 
-    async fn queued_is_exists<I: AsRef<str> + Send>(&self, item: I) -> Result<bool, CrawlerError> {
-        let item = item.as_ref();
-        Ok(self.queued.is_exist(item).await?)
+    /// The articles of the results table `r` joined and filtered by `filter`, with `arg`
+    /// bound to its parameter.
+    async fn articles_where(
+        &self,
+        filter: &str,
+        arg: Option<&str>,
+    ) -> Result<Vec<(String, DetikArticle)>, sqlx::Error> {
+        let query = format!(
+            "SELECT {} FROM {} r {} ORDER BY r.created_at",
+            ARTICLE_COLUMNS, self.name, filter
+        );
+        let mut query = sqlx::query(&query);
+        if let Some(arg) = arg {
+            query = query.bind(arg);
+        }
+        let mut articles = vec![];
+        for row in query.fetch_all(&self.pool).await? {
+            articles.push((row.try_get("id")?, article_from_row(&row, &self.codec)?));
+        }
+        Ok(articles)
     }
 
-    async fn running_get(&self) -> Result<Vec<String>, CrawlerError> {
-        let mut in_progress: Vec<String> = vec![];
+    /// A page of `results_after`, with the `extraction_status` given only.
+    async fn articles_after(
+        &self,
+        cursor: i64,
+        limit: u32,
+        extraction_status: Option<&str>,
+    ) -> Result<Vec<(i64, String, DetikArticle)>, sqlx::Error> {
+        // The rowid, unique where two articles may share their created_at
+        let status = match extraction_status {
+            Some(_) => "AND r.extraction_status = ?3",
+            None => "",
+        };
         let query = format!(
-            "SELECT id FROM {} ORDER BY created_at",
-            self.running.get_name()
+            "SELECT r.rowid AS cursor, {} FROM {} r WHERE r.rowid > ?1 {} ORDER BY r.rowid LIMIT ?2",
+            ARTICLE_COLUMNS, self.name, status
         );
-        for row in sqlx::query(&query).fetch_all(&self.pool).await? {
-            in_progress.push(row.try_get("id")?);
+        let mut articles = vec![];
+        for row in sqlx::query(&query)
+            .bind(cursor)
+            .bind(limit)
+            .bind(extraction_status)
+            .fetch_all(&self.pool)
+            .await?
+        {
+            articles.push((
+                row.try_get("cursor")?,
+                row.try_get("id")?,
+                article_from_row(&row, &self.codec)?,
+            ));
         }
-        Ok(in_progress)
+        Ok(articles)
     }
 
-    async fn running_insert<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
-        let item = item.as_ref();
-        Ok(self.running.insert(item.as_ref()).await?)
+    /// The urls of the other articles whose SimHash is at most `max_hamming_distance` bits
+    /// from the one of `url`, closest first. Empty when `url` isn't stored or has no words.
+    pub async fn find_near_duplicates(
+        &self,
+        url: &str,
+        max_hamming_distance: u32,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let url = url.trim();
+        let query = format!("SELECT simhash FROM {} WHERE id = ?", self.name);
+        let hash: Option<i64> = match sqlx::query(&query)
+            .bind(UrlKey::new(url).as_str())
+            .fetch_optional(&self.pool)
+            .await?
+        {
+            Some(row) => row.try_get("simhash")?,
+            None => None,
+        };
+        let hash = match hash {
+            Some(hash) => hash as u64,
+            None => return Ok(vec![]),
+        };
+
+        let mut duplicates: Vec<(u32, String)> = self
+            .simhashes()
+            .await?
+            .into_iter()
+            .filter(|(other_url, _)| other_url != url)
+            .map(|(other_url, other)| (simhash::hamming_distance(hash, other), other_url))
+            .filter(|(distance, _)| *distance <= max_hamming_distance)
+            .collect();
+        duplicates.sort_by_key(|(distance, _)| *distance);
+        Ok(duplicates.into_iter().map(|(_, url)| url).collect())
     }
 
-    async fn running_delete<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
-        let item = item.as_ref();
-        Ok(self.running.delete(item).await?)
+    /// Every pair of stored articles at most `threshold` bits apart, see
+    /// `simhash::near_duplicates`.
+    pub async fn near_duplicate_report(
+        &self,
+        threshold: u32,
+    ) -> Result<Vec<NearDuplicate>, sqlx::Error> {
+        Ok(simhash::near_duplicates(
+            &self.simhashes().await?,
+            threshold,
+        ))
     }
 
-    async fn running_count(&self) -> Result<u32, CrawlerError> {
-        Ok(self.running.count().await?)
+    /// The url and SimHash of every article with words, oldest first.
+    async fn simhashes(&self) -> Result<Vec<(String, u64)>, sqlx::Error> {
+        let query = format!(
+            "SELECT COALESCE(url, id) AS id, simhash FROM {} WHERE simhash IS NOT NULL
+             ORDER BY created_at, rowid",
+            self.name
+        );
+        let mut hashes = vec![];
+        for row in sqlx::query(&query).fetch_all(&self.pool).await? {
+            let hash: i64 = row.try_get("simhash")?;
+            hashes.push((row.try_get("id")?, hash as u64));
+        }
+        Ok(hashes)
     }
 
-    async fn running_is_exists<I: AsRef<str> + Send>(&self, item: I) -> Result<bool, CrawlerError> {
-        let item = item.as_ref();
-        Ok(self.running.is_exist(item).await?)
+    /// Hash the articles stored before the `simhash` column was added.
+    async fn backfill_simhash(&self) -> Result<(), sqlx::Error> {
+        let query = format!(
+            "SELECT id, paragraphs FROM {} WHERE simhash IS NULL",
+            self.name
+        );
+        let rows = sqlx::query(&query).fetch_all(&self.pool).await?;
+        let update = format!("UPDATE {} SET simhash = ? WHERE id = ?", self.name);
+        let mut tx = self.pool.begin().await?;
+        for row in rows {
+            let id: String = row.try_get("id")?;
+            let paragraphs: Option<String> = row.try_get("paragraphs")?;
+            let paragraphs: Vec<&str> = paragraphs.as_deref().unwrap_or("").split('\n').collect();
+            sqlx::query(&update)
+                .bind(simhash::simhash(&paragraphs).map(|hash| hash as i64))
+                .bind(id)
+                .execute(&mut tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
     }
 
-    async fn visited_delete<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
-        let item = item.as_ref();
-        Ok(self.visited.delete(item).await?)
+    /// The titles of the articles published on the same day, in WIB, since `since` when
+    /// given, with the urls of those articles, the titles of the most articles first. Titles
+    /// are compared normalized, see `utils::normalize_title`, and given as first published.
+    pub async fn duplicate_titles(
+        &self,
+        since: Option<DateTime<FixedOffset>>,
+    ) -> Result<Vec<(String, Vec<String>)>, sqlx::Error> {
+        let query = format!(
+            "WITH filtered AS (
+                SELECT rowid, COALESCE(url, id) AS url, title, title_key, published_date,
+                    date(published_date, '+7 hours') AS day
+                FROM {}
+                WHERE title_key IS NOT NULL AND published_date IS NOT NULL
+                    AND (?1 IS NULL OR datetime(published_date) >= datetime(?1))
+             ), duplicated AS (
+                SELECT title_key, day FROM filtered
+                GROUP BY title_key, day
+                HAVING COUNT(*) > 1
+             )
+             SELECT f.url AS url, f.title AS title, f.title_key AS title_key, f.day AS day
+             FROM filtered f JOIN duplicated d ON d.title_key = f.title_key AND d.day = f.day
+             ORDER BY f.day DESC, f.title_key, datetime(f.published_date), f.rowid",
+            self.name
+        );
+        let mut duplicates: Vec<(String, Vec<String>)> = vec![];
+        let mut last: Option<(String, String)> = None;
+        for row in sqlx::query(&query)
+            .bind(since)
+            .fetch_all(&self.pool)
+            .await?
+        {
+            let group = (row.try_get("title_key")?, row.try_get("day")?);
+            let url: String = row.try_get("url")?;
+            match duplicates.last_mut() {
+                Some((_, urls)) if last.as_ref() == Some(&group) => urls.push(url),
+                _ => {
+                    duplicates.push((row.try_get("title")?, vec![url]));
+                    last = Some(group);
+                }
+            }
+        }
+        // Stable, the latest day first among as many articles
+        duplicates.sort_by_key(|(_, urls)| std::cmp::Reverse(urls.len()));
+        Ok(duplicates)
     }
 
-    async fn visited_is_exists<I: AsRef<str> + Send>(&self, item: I) -> Result<bool, CrawlerError> {
-        let item = item.as_ref();
-        Ok(self.visited.is_exist(item).await?)
+    /// Normalize the titles of the articles stored before the `title_key` column was added.
+    async fn backfill_title_keys(&self) -> Result<(), sqlx::Error> {
+        let query = format!(
+            "SELECT id, title FROM {} WHERE title IS NOT NULL AND title_key IS NULL",
+            self.name
+        );
+        let rows = sqlx::query(&query).fetch_all(&self.pool).await?;
+        let update = format!("UPDATE {} SET title_key = ? WHERE id = ?", self.name);
+        let mut tx = self.pool.begin().await?;
+        for row in rows {
+            let id: String = row.try_get("id")?;
+            let title: String = row.try_get("title")?;
+            sqlx::query(&update)
+                .bind(utils::normalize_title(&title))
+                .bind(id)
+                .execute(&mut tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
     }
+}
+
+/// The article of a row selected with `ARTICLE_COLUMNS`, its paragraphs decoded with `codec`.
+/// Line breaks inside a paragraph come back as separate paragraphs.
+fn article_from_row(row: &SqliteRow, codec: &ParagraphCodec) -> Result<DetikArticle, sqlx::Error> {
+    let split = |s: Option<String>, sep: char| -> Vec<String> {
+        s.map(|s| {
+            s.split(sep)
+                .filter(|s| !s.is_empty())
+                .map(ToString::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+    };
+    Ok(DetikArticle {
+        title: row.try_get("title")?,
+        published_date: row.try_get("published_date")?,
+        description: row.try_get("description")?,
+        thumbnail_url: row.try_get("thumbnail_url")?,
+        author: row.try_get("author")?,
+        desk: row.try_get("desk")?,
+        category: row.try_get("category")?,
+        language: row.try_get("language")?,
+        keywords: split(row.try_get("keywords")?, '|'),
+        keywords_raw: row.try_get("keywords_raw")?,
+        videos: row
+            .try_get::<Option<String>, _>("videos")?
+            .and_then(|videos| serde_json::from_str(&videos).ok())
+            .unwrap_or_default(),
+        photos: row
+            .try_get::<Option<String>, _>("photos")?
+            .and_then(|photos| serde_json::from_str(&photos).ok())
+            .unwrap_or_default(),
+        body_selector: row.try_get("body_selector")?,
+        paragraphs: split(codec.read(row)?, '\n'),
+    })
+}
 
-    async fn visited_insert<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
-        let item = item.as_ref();
-        Ok(self.visited.insert(item).await?)
+#[async_trait::async_trait]
+impl ResultTable for DetikArticleTable {
+    type Article = DetikArticle;
+
+    fn new(name: String, pool: SqlitePool) -> Self {
+        let site = name.strip_suffix("_results").unwrap_or(&name);
+        let authors = AuthorTable {
+            name: format!("{}_authors", site),
+            pool: pool.clone(),
+        };
+        let keywords = KeywordTable {
+            name: format!("{}_keywords", site),
+            pool: pool.clone(),
+        };
+        DetikArticleTable {
+            name,
+            pool,
+            authors,
+            keywords,
+            codec: Default::default(),
+        }
     }
 
-    async fn results_count(&self) -> Result<u32, CrawlerError> {
-        Ok(self.results.count().await?)
+    fn record(url: &str, article: DetikArticle) -> (&str, DetikArticle) {
+        (url, article)
     }
 
-    async fn results_insert<I: AsRef<str> + Send>(
-        &self,
-        (url, record): (I, Self::Record),
-    ) -> Result<(), CrawlerError> {
-        let url = url.as_ref();
-        Ok(self.results.insert((url, record)).await?)
+    async fn rekey(&self, from: &str, to: &str) -> Result<(), sqlx::Error> {
+        let query = format!(
+            "UPDATE OR IGNORE {} SET id = ? WHERE id = ?",
+            self.keywords.name
+        );
+        sqlx::query(&query)
+            .bind(to)
+            .bind(from)
+            .execute(&self.pool)
+            .await?;
+        let query = format!("DELETE FROM {} WHERE id = ?", self.keywords.name);
+        sqlx::query(&query).bind(from).execute(&self.pool).await?;
+        Ok(())
+    }
+
+    async fn forget(&self, id: &str) -> Result<(), sqlx::Error> {
+        let query = format!("DELETE FROM {} WHERE id = ?", self.keywords.name);
+        sqlx::query(&query).bind(id).execute(&self.pool).await?;
+        Ok(())
     }
 
-    async fn warned_insert<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
-        let item = item.as_ref();
-        Ok(self.warned.insert(item).await?)
+    fn set_codec(&mut self, codec: Arc<ParagraphCodec>) {
+        self.codec = codec;
     }
 }
 
+pub type DetikData = SqliteStorage<DetikArticleTable>;
+
 #[cfg(test)]
 mod tests {
-    use super::super::DetikArticle;
+    use super::super::{DetikArticle, Photo};
     use super::*;
     use crate::utils::get_now;
+    use crate::{CrawlerError, Storage, MAX_KEY_LEN};
     use std::path::Path;
     use tokio::fs;
 
@@ -315,10 +862,7 @@ mod tests {
 
     macro_rules! assert_eq_fut_strings {
         ($fut:expr, $($item:expr),+) => {
-            let mut v = Vec::new();
-            $(
-                v.push($item.to_string());
-            )+
+            let v: Vec<String> = vec![$($item.to_string()),+];
             assert_eq!($fut.await.unwrap(), v);
         };
         ($fut:expr) => {
@@ -377,7 +921,7 @@ mod tests {
 
         assert_eq!(p.warned.count().await.unwrap(), 0);
         assert!(!p.warned.is_exist("warned").await.unwrap());
-        insert!(p.warned, "warned");
+        insert!(p.warned, ("warned", "empty_paragraphs"));
         assert_eq!(p.warned.count().await.unwrap(), 1);
         assert!(p.warned.is_exist("warned").await.unwrap());
         delete!(p.warned, "warned");
@@ -386,8 +930,15 @@ mod tests {
 
         let d = DetikArticle {
             author: Some("author".into()),
+            desk: None,
+            category: Some("jateng".into()),
+            language: None,
             description: Some("description".into()),
             keywords: vec!["k1".to_string(), "k2".to_string()],
+            keywords_raw: Some("K1, k2 ,k1".to_string()),
+            videos: vec!["https://20.detik.com/embed/1?a|b".to_string()],
+            photos: vec![],
+            body_selector: None,
             paragraphs: vec!["p1".to_string(), "p2".to_string()],
             published_date: Some(get_now()),
             thumbnail_url: None,
@@ -406,6 +957,723 @@ mod tests {
         fs::remove_file("test2.db").await.unwrap();
     }
 
+    #[tokio::test]
+    async fn columns_added_to_old_tables() {
+        if Path::new("test9.db").is_file() {
+            fs::remove_file("test9.db").await.unwrap();
+        }
+        let p = DetikData::new("test9").await.unwrap();
+        for index in ["simhash", "title_key"] {
+            sqlx::query(&format!("DROP INDEX test9_results_{}", index))
+                .execute(p.results.get_pool())
+                .await
+                .unwrap();
+        }
+        let columns = [
+            "category",
+            "language",
+            "simhash",
+            "videos",
+            "keywords_raw",
+            "title_key",
+            "body_selector",
+            "compression",
+            "desk",
+            "photos",
+        ];
+        for column in columns {
+            sqlx::query(&format!("ALTER TABLE test9_results DROP COLUMN {}", column))
+                .execute(p.results.get_pool())
+                .await
+                .unwrap();
+        }
+        sqlx::query(
+            "INSERT INTO test9_results (id, title, paragraphs) VALUES ('old', 'Old!', 'p1\np2')",
+        )
+        .execute(p.results.get_pool())
+        .await
+        .unwrap();
+        drop(p);
+
+        let p = DetikData::new("test9").await.unwrap();
+        let d = DetikArticle {
+            author: None,
+            desk: None,
+            category: Some("sumut".into()),
+            language: Some("id".into()),
+            description: None,
+            keywords: vec![],
+            keywords_raw: None,
+            videos: vec![],
+            photos: vec![],
+            body_selector: None,
+            paragraphs: vec!["p1".to_string()],
+            published_date: None,
+            thumbnail_url: None,
+            title: None,
+        };
+        insert!(p.results, ("results", d));
+        assert!(p.results.is_exist("results").await.unwrap());
+        // The article stored before the column was added got its hash too
+        assert_eq!(p.results.simhashes().await.unwrap().len(), 2);
+        let key: Option<String> =
+            sqlx::query_scalar("SELECT title_key FROM test9_results WHERE id = 'old'")
+                .fetch_one(p.results.get_pool())
+                .await
+                .unwrap();
+        assert_eq!(key.as_deref(), Some("old"));
+
+        fs::remove_file("test9.db").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn read_articles_back() {
+        if Path::new("test10.db").is_file() {
+            fs::remove_file("test10.db").await.unwrap();
+        }
+        let p = DetikData::new("test10").await.unwrap();
+        let d = DetikArticle {
+            author: Some("author".into()),
+            desk: Some("detikJateng".into()),
+            category: Some("jateng".into()),
+            language: Some("en".into()),
+            description: None,
+            keywords: vec!["k1".to_string(), "k2".to_string()],
+            keywords_raw: None,
+            videos: vec!["https://20.detik.com/embed/1?a|b".to_string()],
+            photos: vec![Photo {
+                url: "https://akcdn.detik.net.id/community/media/visual/1.jpg".to_string(),
+                caption: Some("Foto \"1\"".to_string()),
+                slide_url: Some("https://news.detik.com/d-1/a/foto-1".to_string()),
+            }],
+            body_selector: Some(".detail__body-text".to_string()),
+            paragraphs: vec!["p1".to_string(), "p2".to_string()],
+            published_date: Some(get_now()),
+            thumbnail_url: None,
+            title: Some("title".to_string()),
+        };
+        insert!(p.results, ("https://news.detik.com/d-1/a", d.clone()));
+        let empty = DetikArticle {
+            keywords: vec![],
+            keywords_raw: None,
+            videos: vec![],
+            desk: None,
+            photos: vec![],
+            body_selector: None,
+            paragraphs: vec![],
+            ..d.clone()
+        };
+        insert!(p.results, ("https://news.detik.com/d-2/b", empty.clone()));
+
+        assert_eq!(
+            p.results.articles().await.unwrap(),
+            vec![
+                ("https://news.detik.com/d-1/a".to_string(), d),
+                ("https://news.detik.com/d-2/b".to_string(), empty)
+            ]
+        );
+
+        fs::remove_file("test10.db").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn near_duplicates() {
+        if Path::new("test11.db").is_file() {
+            fs::remove_file("test11.db").await.unwrap();
+        }
+        let p = DetikData::new("test11").await.unwrap();
+        let article = |paragraphs: &[&str]| DetikArticle {
+            author: None,
+            desk: None,
+            category: None,
+            language: None,
+            description: None,
+            keywords: vec![],
+            keywords_raw: None,
+            videos: vec![],
+            photos: vec![],
+            body_selector: None,
+            paragraphs: paragraphs.iter().map(ToString::to_string).collect(),
+            published_date: None,
+            thumbnail_url: None,
+            title: None,
+        };
+        let wire = [
+            "Kepala Dinas Pariwisata Badung memastikan tak akan ada sweeping terhadap turis \
+             asing yang liburan ke Bali imbas disahkannya pasal zina dalam KUHP.",
+            "Rudiarta menyebut pemerintah daerah sudah berkoordinasi dengan para pelaku usaha \
+             pariwisata agar informasi soal KUHP baru tidak disalahartikan oleh wisatawan.",
+            "Menurut dia, pasal tersebut merupakan delik aduan sehingga hanya bisa diproses \
+             jika ada laporan dari suami, istri, orang tua, atau anak.",
+            "Sejumlah asosiasi hotel dan biro perjalanan sebelumnya khawatir aturan baru itu \
+             membuat wisatawan mancanegara membatalkan rencana liburan mereka.",
+        ];
+        let mut relede = wire;
+        relede[0] = "<strong>Badung</strong> - Kadispar Badung menjamin tak akan ada sweeping \
+                     terhadap turis asing yang liburan ke Bali imbas disahkannya pasal zina \
+                     dalam KUHP.";
+        let other = [
+            "Maroko vs Portugal akan bermain malam ini di perempatfinal Piala Dunia 2022, \
+             Cristiano Ronaldo kembali jadi cadangan.",
+            "Pelatih Fernando Santos kembali memainkan Goncalo Ramos sebagai penyerang utama \
+             setelah mencetak hattrick ke gawang Swiss di babak 16 besar.",
+        ];
+        insert!(
+            p.results,
+            ("https://news.detik.com/d-1/wire", article(&wire))
+        );
+        insert!(
+            p.results,
+            ("https://news.detik.com/d-2/other", article(&other))
+        );
+        insert!(
+            p.results,
+            ("https://news.detik.com/d-3/relede", article(&relede))
+        );
+        insert!(
+            p.results,
+            ("https://news.detik.com/d-4/copy", article(&wire))
+        );
+        insert!(
+            p.results,
+            ("https://news.detik.com/d-5/empty", article(&[]))
+        );
+
+        let report = p.results.near_duplicate_report(10).await.unwrap();
+        let pairs: Vec<(&str, &str)> = report
+            .iter()
+            .map(|d| (d.url.as_str(), d.other_url.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                (
+                    "https://news.detik.com/d-1/wire",
+                    "https://news.detik.com/d-4/copy"
+                ),
+                (
+                    "https://news.detik.com/d-1/wire",
+                    "https://news.detik.com/d-3/relede"
+                ),
+                (
+                    "https://news.detik.com/d-3/relede",
+                    "https://news.detik.com/d-4/copy"
+                ),
+            ]
+        );
+        assert_eq!(report[0].distance, 0);
+
+        assert_eq!(
+            p.results
+                .find_near_duplicates("https://news.detik.com/d-1/wire", 10)
+                .await
+                .unwrap(),
+            vec![
+                "https://news.detik.com/d-4/copy",
+                "https://news.detik.com/d-3/relede"
+            ]
+        );
+        assert_eq!(
+            p.results
+                .find_near_duplicates("https://news.detik.com/d-1/wire", 0)
+                .await
+                .unwrap(),
+            vec!["https://news.detik.com/d-4/copy"]
+        );
+        for url in [
+            "https://news.detik.com/d-2/other",
+            "https://news.detik.com/d-5/empty",
+            "https://news.detik.com/d-6/missing",
+        ] {
+            assert!(p
+                .results
+                .find_near_duplicates(url, 10)
+                .await
+                .unwrap()
+                .is_empty());
+        }
+
+        fs::remove_file("test11.db").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn authors_backfilled() {
+        if Path::new("test12.db").is_file() {
+            fs::remove_file("test12.db").await.unwrap();
+        }
+        let p = DetikData::new("test12").await.unwrap();
+        for (id, author) in [("1", "andi saputra"), ("2", "Andi Saputra - detikNews")] {
+            sqlx::query("INSERT INTO test12_results (id, author) VALUES (?, ?)")
+                .bind(id)
+                .bind(author)
+                .execute(p.results.get_pool())
+                .await
+                .unwrap();
+        }
+        sqlx::query("DROP TABLE test12_authors")
+            .execute(p.results.get_pool())
+            .await
+            .unwrap();
+        drop(p);
+
+        let p = DetikData::new("test12").await.unwrap();
+        assert_eq!(
+            p.results.top_authors(10, None).await.unwrap(),
+            vec![AuthorCount {
+                name: "Andi Saputra".to_string(),
+                articles: 2
+            }]
+        );
+
+        fs::remove_file("test12.db").await.unwrap();
+    }
+
+    fn article() -> DetikArticle {
+        DetikArticle {
+            author: None,
+            desk: None,
+            category: None,
+            language: None,
+            description: None,
+            keywords: vec!["Banjir".to_string()],
+            keywords_raw: None,
+            videos: vec![],
+            photos: vec![],
+            body_selector: None,
+            paragraphs: vec!["p1".to_string()],
+            published_date: Some(get_now()),
+            thumbnail_url: None,
+            title: Some("title".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn long_urls_keyed_by_hash() {
+        if Path::new("test13.db").is_file() {
+            fs::remove_file("test13.db").await.unwrap();
+        }
+        let p = DetikData::new("test13").await.unwrap();
+        let base = "https://news.detik.com/d-1?q=";
+        let at_limit = format!("{}{}", base, "a".repeat(MAX_KEY_LEN - base.len()));
+        let over_limit = format!("{}b", at_limit);
+        let control = "https://news.detik.com/d-2\u{7}".to_string();
+
+        assert_eq!(UrlKey::new(&at_limit).as_str(), at_limit);
+        assert_eq!(UrlKey::new(&at_limit).url(), None);
+        assert!(UrlKey::new(&over_limit).as_str().starts_with("sha256:"));
+        assert_eq!(UrlKey::new(&over_limit).url(), Some(over_limit.as_str()));
+        assert!(UrlKey::new(&control).as_str().starts_with("sha256:"));
+
+        for url in [&at_limit, &over_limit, &control] {
+            p.queued_insert(url).await.unwrap();
+        }
+        assert_eq_fut_strings!(p.queued_get(), &at_limit, &over_limit, &control);
+        assert!(p.queued_is_exists(&over_limit).await.unwrap());
+        let claimed: Vec<String> = p
+            .queued_claim_n("w", 3)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|queued| queued.url.to_string())
+            .collect();
+        assert_eq!(claimed, vec![at_limit.clone(), over_limit.clone(), control]);
+
+        p.visited_insert(&over_limit).await.unwrap();
+        assert!(p.visited_is_exists(&over_limit).await.unwrap());
+        p.warned_insert(&over_limit, "timeout").await.unwrap();
+        assert_eq!(
+            p.warned_get(&over_limit).await.unwrap().unwrap().url,
+            over_limit
+        );
+        assert_eq!(p.warned_recent(1).await.unwrap()[0].url, over_limit);
+        p.results_insert((&over_limit, article())).await.unwrap();
+        p.results_insert((&over_limit, article())).await.unwrap();
+        assert_eq!(p.results.count().await.unwrap(), 1);
+        assert_eq!(p.results_recent(1).await.unwrap()[0].url, over_limit);
+
+        // No key is longer than a digest
+        for table in [
+            "queued", "running", "visited", "warned", "results", "keywords",
+        ] {
+            let query = format!("SELECT MAX(length(id)) FROM test13_{}", table);
+            let longest: i64 = sqlx::query_scalar(&query)
+                .fetch_one(p.results.get_pool())
+                .await
+                .unwrap();
+            assert!(longest <= MAX_KEY_LEN as i64, "{}", table);
+        }
+
+        fs::remove_file("test13.db").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn long_keys_rekeyed() {
+        if Path::new("test14.db").is_file() {
+            fs::remove_file("test14.db").await.unwrap();
+        }
+        let p = DetikData::new("test14").await.unwrap();
+        let url = format!("https://news.detik.com/d-1?q={}", "a".repeat(MAX_KEY_LEN));
+        // Stored by their full url before keys were hashed
+        for table in ["queued", "visited", "warned"] {
+            let query = format!("INSERT INTO test14_{} (id) VALUES (?)", table);
+            sqlx::query(&query)
+                .bind(&url)
+                .execute(p.results.get_pool())
+                .await
+                .unwrap();
+        }
+        p.results.insert((url.as_str(), article())).await.unwrap();
+        drop(p);
+
+        let p = DetikData::new("test14").await.unwrap();
+        let key = UrlKey::new(&url);
+        assert_eq_fut_strings!(p.queued_get(), &url);
+        assert!(p.visited_is_exists(&url).await.unwrap());
+        assert_eq!(p.warned_get(&url).await.unwrap().unwrap().url, url);
+        assert_eq!(p.results_recent(1).await.unwrap()[0].url, url);
+        let keywords: Vec<String> = sqlx::query_scalar("SELECT id FROM test14_keywords")
+            .fetch_all(p.results.get_pool())
+            .await
+            .unwrap();
+        assert_eq!(keywords, vec![key.as_str().to_string()]);
+
+        fs::remove_file("test14.db").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn metadata_only_results() {
+        if Path::new("test15.db").is_file() {
+            fs::remove_file("test15.db").await.unwrap();
+        }
+        let p = DetikData::new("test15").await.unwrap();
+        let empty = DetikArticle {
+            description: Some("Ringkasan".to_string()),
+            paragraphs: vec![],
+            ..article()
+        };
+
+        p.results_insert_metadata_only(("1", empty.clone()), Some("index"))
+            .await
+            .unwrap();
+        assert_eq!(
+            p.results_status("1").await.unwrap(),
+            Some(ExtractionStatus::MetadataOnly)
+        );
+        assert_eq!(p.results.articles().await.unwrap().len(), 1);
+        assert!(p.results.full_articles().await.unwrap().is_empty());
+
+        // The full article replaces it, keeping where it was first found
+        p.results_insert_with_referrer(("1", article()), Some("other"))
+            .await
+            .unwrap();
+        assert_eq!(
+            p.results_status("1").await.unwrap(),
+            Some(ExtractionStatus::Full)
+        );
+        let stored = p.results_get("1").await.unwrap().unwrap();
+        assert_eq!(stored.paragraphs, vec!["p1"]);
+        assert_eq!(stored.referrer.as_deref(), Some("index"));
+        assert_eq!(p.results.full_articles().await.unwrap().len(), 1);
+
+        // But is never replaced by its metadata
+        p.results_insert_metadata_only(("1", empty), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            p.results_status("1").await.unwrap(),
+            Some(ExtractionStatus::Full)
+        );
+        assert_eq!(
+            p.results_get("1").await.unwrap().unwrap().paragraphs,
+            vec!["p1"]
+        );
+        assert_eq!(p.results_status("2").await.unwrap(), None);
+
+        fs::remove_file("test15.db").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn results_pages() {
+        if Path::new("test16.db").is_file() {
+            fs::remove_file("test16.db").await.unwrap();
+        }
+        let p = DetikData::new("test16").await.unwrap();
+        for url in ["1", "2", "3", "4", "5"] {
+            insert!(p.results, (url, article()));
+        }
+        let empty = DetikArticle {
+            paragraphs: vec![],
+            ..article()
+        };
+        p.results_insert_metadata_only(("6", empty), None)
+            .await
+            .unwrap();
+
+        let mut cursor = 0;
+        let mut pages = vec![];
+        loop {
+            let page = p.results.results_after(cursor, 2).await.unwrap();
+            cursor = match page.last() {
+                Some((last, _, _)) => *last,
+                None => break,
+            };
+            pages.push(page.into_iter().map(|(_, url, _)| url).collect::<Vec<_>>());
+        }
+        assert_eq!(pages, vec![vec!["1", "2"], vec!["3", "4"], vec!["5", "6"]]);
+
+        let (cursor, _, _) = p.results.full_results_after(0, 4).await.unwrap()[3];
+        let rest = p.results.full_results_after(cursor, 4).await.unwrap();
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].1, "5");
+        assert_eq!(rest[0].2.paragraphs, vec!["p1"]);
+
+        fs::remove_file("test16.db").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn duplicate_titles() {
+        if Path::new("test17.db").is_file() {
+            fs::remove_file("test17.db").await.unwrap();
+        }
+        let p = DetikData::new("test17").await.unwrap();
+        // 23:00 UTC is the next day in WIB
+        let published =
+            |date: &str| Some(DateTime::parse_from_rfc3339(&format!("{}+00:00", date)).unwrap());
+        for (url, title, date) in [
+            (
+                "1",
+                "Banjir Jakarta, Warga Mengungsi",
+                "2023-01-01T23:00:00",
+            ),
+            (
+                "2",
+                "BANJIR JAKARTA: Warga Mengungsi!",
+                "2023-01-02T01:00:00",
+            ),
+            ("3", "Banjir jakarta warga mengungsi", "2023-01-02T02:00:00"),
+            (
+                "4",
+                "Banjir Jakarta, Warga Mengungsi",
+                "2023-01-03T02:00:00",
+            ),
+            ("5", "Harga Cabai Naik", "2023-01-03T03:00:00"),
+            ("6", "Harga cabai naik.", "2023-01-03T04:00:00"),
+            ("7", "Harga Cabai Naik", "2023-01-01T04:00:00"),
+        ] {
+            let article = DetikArticle {
+                title: Some(title.to_string()),
+                published_date: published(date),
+                ..article()
+            };
+            insert!(p.results, (url, article));
+        }
+        let untitled = DetikArticle {
+            title: None,
+            ..article()
+        };
+        insert!(p.results, ("8", untitled.clone()));
+        insert!(p.results, ("9", untitled));
+
+        let duplicates = p.results.duplicate_titles(None).await.unwrap();
+        assert_eq!(
+            duplicates,
+            vec![
+                (
+                    "Banjir Jakarta, Warga Mengungsi".to_string(),
+                    vec!["1".to_string(), "2".to_string(), "3".to_string()]
+                ),
+                (
+                    "Harga Cabai Naik".to_string(),
+                    vec!["5".to_string(), "6".to_string()]
+                ),
+            ]
+        );
+
+        let since = published("2023-01-02T00:00:00");
+        let duplicates = p.results.duplicate_titles(since).await.unwrap();
+        // As many articles, the latest day first
+        assert_eq!(duplicates.len(), 2);
+        assert_eq!(duplicates[0].1, vec!["5", "6"]);
+        assert_eq!(duplicates[1].1, vec!["2", "3"]);
+
+        fs::remove_file("test17.db").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn read_only_storage_unavailable() {
+        if Path::new("test18.db").is_file() {
+            fs::remove_file("test18.db").await.unwrap();
+        }
+        let p = DetikData::new("test18").await.unwrap();
+        p.queued_insert("https://news.detik.com/a").await.unwrap();
+        drop(p);
+
+        let p = DetikData::open_read_only("test18").await.unwrap();
+        assert_eq!(p.queued_count().await.unwrap(), 1);
+        let e = p
+            .visited_insert("https://news.detik.com/a")
+            .await
+            .unwrap_err();
+        assert!(matches!(e, CrawlerError::StorageUnavailable(_)), "{:?}", e);
+        // Other database errors stay as they were
+        let e = CrawlerError::from(sqlx::Error::RowNotFound);
+        assert!(matches!(e, CrawlerError::DatabaseError(_)), "{:?}", e);
+
+        fs::remove_file("test18.db").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn body_selector_counts() {
+        if Path::new("test19.db").is_file() {
+            fs::remove_file("test19.db").await.unwrap();
+        }
+        let p = DetikData::new("test19").await.unwrap();
+        let published =
+            |date: &str| Some(DateTime::parse_from_rfc3339(&format!("{}+07:00", date)).unwrap());
+        for (url, selector, date) in [
+            ("1", Some(".detail__body-text"), "2023-01-01T10:00:00"),
+            ("2", Some(".detail__body-text"), "2023-01-02T10:00:00"),
+            ("3", Some(".detail__body-text"), "2023-01-03T10:00:00"),
+            ("4", Some("#detikdetailtext"), "2023-01-01T10:00:00"),
+            ("5", Some("#detikdetailtext"), "2023-01-03T10:00:00"),
+            ("6", Some(".read__content"), "2023-01-01T10:00:00"),
+            ("7", None, "2023-01-03T10:00:00"),
+        ] {
+            let article = DetikArticle {
+                body_selector: selector.map(ToString::to_string),
+                published_date: published(date),
+                ..article()
+            };
+            insert!(p.results, (url, article));
+        }
+
+        let count =
+            |selector: Option<&str>, articles| (selector.map(ToString::to_string), articles);
+        assert_eq!(
+            p.results.body_selectors(None).await.unwrap(),
+            vec![
+                count(Some(".detail__body-text"), 3),
+                count(Some("#detikdetailtext"), 2),
+                count(None, 1),
+                count(Some(".read__content"), 1),
+            ]
+        );
+        // `.read__content` no longer matching
+        let since = published("2023-01-02T00:00:00");
+        assert_eq!(
+            p.results.body_selectors(since).await.unwrap(),
+            vec![
+                count(Some(".detail__body-text"), 2),
+                count(None, 1),
+                count(Some("#detikdetailtext"), 1),
+            ]
+        );
+
+        fs::remove_file("test19.db").await.unwrap();
+    }
+
+    /// An article of a synthetic corpus, its wording much like the others'.
+    fn corpus_article(i: usize) -> DetikArticle {
+        const PLACES: [&str; 5] = ["Jakarta", "Bandung", "Surabaya", "Medan", "Makassar"];
+        const SUBJECTS: [&str; 4] = ["Pemerintah kota", "Polisi", "Warga", "Dinas kesehatan"];
+        let rng = fastrand::Rng::with_seed(i as u64);
+        let paragraphs = (0..4 + rng.usize(..4))
+            .map(|_| {
+                format!(
+                    "{} - {} {} mengatakan bahwa {} orang terdampak pada {} pekan ini, \
+                     dan penanganan akan terus dilakukan bersama pihak terkait.",
+                    PLACES[rng.usize(..PLACES.len())],
+                    SUBJECTS[rng.usize(..SUBJECTS.len())],
+                    PLACES[rng.usize(..PLACES.len())],
+                    rng.u32(1..10_000),
+                    ["Senin", "Selasa", "Rabu", "Kamis", "Jumat"][rng.usize(..5)],
+                )
+            })
+            .collect();
+        DetikArticle {
+            paragraphs,
+            published_date: None,
+            ..article()
+        }
+    }
+
+    async fn stored_size(p: &DetikData) -> i64 {
+        sqlx::query_scalar("SELECT SUM(length(CAST(paragraphs AS BLOB))) FROM test20_results")
+            .fetch_one(p.results.get_pool())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn compressed_paragraphs() {
+        if Path::new("test20.db").is_file() {
+            fs::remove_file("test20.db").await.unwrap();
+        }
+        let p = DetikData::new("test20").await.unwrap();
+        // Not compressing
+        assert!(p.compress_existing(10).await.is_err());
+        for i in 0..300 {
+            insert!(p.results, (i.to_string().as_str(), corpus_article(i)));
+        }
+        drop(p);
+
+        let p = DetikData::new("test20")
+            .await
+            .unwrap()
+            .with_compression()
+            .await
+            .unwrap();
+        for i in 300..600 {
+            insert!(p.results, (i.to_string().as_str(), corpus_article(i)));
+        }
+        // Plain and compressed rows read alike
+        let read_back = |articles: Vec<(String, DetikArticle)>| {
+            for (url, article) in articles {
+                assert_eq!(article, corpus_article(url.parse().unwrap()), "{}", url);
+            }
+        };
+        read_back(p.results.articles().await.unwrap());
+        for url in ["1", "301"] {
+            assert_eq!(
+                p.results_get(url).await.unwrap().unwrap().paragraphs,
+                corpus_article(url.parse().unwrap()).paragraphs
+            );
+        }
+        let compressed: u32 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM test20_results WHERE compression = 'zstd:1'")
+                .fetch_one(p.results.get_pool())
+                .await
+                .unwrap();
+        assert_eq!(compressed, 300);
+
+        // The older rows, in batches
+        assert_eq!(p.compress_existing(64).await.unwrap(), 300);
+        assert_eq!(p.compress_existing(64).await.unwrap(), 0);
+        // Less than half their size as text
+        let plain: usize = (0..600)
+            .map(|i| corpus_article(i).paragraphs.join("\n").len())
+            .sum();
+        let size = stored_size(&p).await as usize;
+        assert!(size * 2 < plain, "{} bytes from {}", size, plain);
+        drop(p);
+
+        // Read back without compressing the new rows
+        let p = DetikData::new("test20").await.unwrap();
+        read_back(p.results.articles().await.unwrap());
+        let p = DetikData::open_read_only("test20").await.unwrap();
+        read_back(
+            p.results
+                .results_after(0, 1000)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|(_, url, article)| (url, article))
+                .collect(),
+        );
+
+        fs::remove_file("test20.db").await.unwrap();
+    }
+
     #[tokio::test]
     async fn get_queue() {
         if Path::new("test3.db").is_file() {
@@ -489,6 +1757,22 @@ mod tests {
         fs::remove_file("test5.db").await.unwrap();
     }
 
+    #[tokio::test]
+    async fn queued_insert_batch() {
+        if Path::new("test7.db").is_file() {
+            fs::remove_file("test7.db").await.unwrap();
+        }
+        let p = DetikData::new("test7").await.unwrap();
+
+        insert!(p.queued, "2");
+        p.queued_insert_batch(&["1", "2", "3"]).await.unwrap();
+        assert_eq!(p.queued.count().await.unwrap(), 3);
+        assert!(p.queued.is_exist("1").await.unwrap());
+        assert!(p.queued.is_exist("3").await.unwrap());
+
+        fs::remove_file("test7.db").await.unwrap();
+    }
+
     #[tokio::test]
     async fn merge_queue_and_running() {
         if Path::new("test6.db").is_file() {
@@ -505,4 +1789,80 @@ mod tests {
 
         fs::remove_file("test6.db").await.unwrap();
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn concurrent_handle_pattern() {
+        if Path::new("test8.db").is_file() {
+            fs::remove_file("test8.db").await.unwrap();
+        }
+        let p = std::sync::Arc::new(DetikData::new("test8").await.unwrap());
+
+        const TASKS: usize = 50;
+        let article = |i: usize| format!("https://news.detik.com/d-{}", i);
+        // 20 links per page: 15 shared hub links and 5 links to other articles
+        let links = |i: usize| -> Vec<String> {
+            (0..15)
+                .map(|j| format!("https://news.detik.com/hub-{}", (i * 7 + j) % 40))
+                .chain((1..=5).map(|j| article((i + j * 3) % TASKS)))
+                .collect()
+        };
+
+        for i in 0..TASKS {
+            p.queued_insert(article(i)).await.unwrap();
+        }
+
+        let mut tasks = tokio::task::JoinSet::new();
+        for i in 0..TASKS {
+            let p = p.clone();
+            let url = article(i);
+            let links = links(i);
+            tasks.spawn(async move {
+                p.running_insert(&url).await?;
+                p.queued_delete(&url).await?;
+                let d = DetikArticle {
+                    author: None,
+                    desk: None,
+                    category: None,
+                    language: None,
+                    description: None,
+                    keywords: vec![],
+                    keywords_raw: None,
+                    videos: vec![],
+                    photos: vec![],
+                    body_selector: None,
+                    paragraphs: vec![url.clone()],
+                    published_date: None,
+                    thumbnail_url: None,
+                    title: Some(url.clone()),
+                };
+                p.results_insert((url.as_str(), d)).await?;
+                p.visited_insert(&url).await?;
+                for link in links {
+                    p.queued_insert_unseen(link).await?;
+                }
+                p.running_delete(&url).await?;
+                Ok::<_, CrawlerError>(())
+            });
+        }
+        while let Some(result) = tasks.join_next().await {
+            result.unwrap().unwrap();
+        }
+
+        let hubs: std::collections::HashSet<String> = (0..TASKS)
+            .flat_map(links)
+            .filter(|l| l.contains("hub"))
+            .collect();
+        assert_eq!(p.results.count().await.unwrap(), TASKS as u32);
+        assert_eq!(p.visited.count().await.unwrap(), TASKS as u32);
+        assert_eq!(p.running.count().await.unwrap(), 0);
+        assert_eq!(p.queued.count().await.unwrap(), hubs.len() as u32);
+
+        let queued = p.queued_get().await.unwrap();
+        for url in queued {
+            assert!(!p.visited.is_exist(url.as_str()).await.unwrap(), "{}", url);
+            assert!(!p.running.is_exist(url.as_str()).await.unwrap(), "{}", url);
+        }
+
+        fs::remove_file("test8.db").await.unwrap();
+    }
 }
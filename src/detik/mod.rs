@@ -1,25 +1,65 @@
+mod carousel;
 mod crawler;
+#[cfg(feature = "sqlite")]
 mod data;
 
-pub use crawler::DetikCrawler;
-pub use data::DetikData;
+pub use carousel::CarouselCrawler;
+pub use crawler::{DetikCrawler, BODY_SELECTORS, SELECTOR_NAMES};
+#[cfg(feature = "sqlite")]
+pub use data::{AuthorCount, DetikArticleTable, DetikData};
 
-use crate::Article;
+use crate::{text, Article};
 
 use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
 use std::{fmt, string::String};
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DetikArticle {
     pub title: Option<String>,
     pub published_date: Option<DateTime<FixedOffset>>,
     pub description: Option<String>,
     pub thumbnail_url: Option<String>,
     pub author: Option<String>,
+    /// The desk credited in `dtk:author` instead of, or along with, a person: `detikNews`,
+    /// `Tim detikcom`, ... see `DetikCrawler::with_title_suffixes`
+    #[serde(default)]
+    pub desk: Option<String>,
+    /// Channel from `dtk:acctype`: `news`, `finance`, ... or the region of the regional
+    /// sites: `jateng`, `jatim`, `sulsel`, `sumut`, ...
+    pub category: Option<String>,
+    /// ISO 639-1 code detected from the paragraphs, see `lang::detect`
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Cleaned up with `text::clean_keywords`
     pub keywords: Vec<String>,
+    /// The `dtk:keywords` as they came, kept with `DetikCrawler::with_raw_keywords`
+    #[serde(default)]
+    pub keywords_raw: Option<String>,
+    /// The 20detik videos embedded in the body and the `og:video` of the page
+    #[serde(default)]
+    pub videos: Vec<String>,
+    /// The photo carousel embedded in the body, its slides read into the article rather than
+    /// crawled on their own, see `CarouselCrawler`
+    #[serde(default)]
+    pub photos: Vec<Photo>,
+    /// Which of `BODY_SELECTORS` the paragraphs were read from, the first one when
+    /// several matched, none when no body was found
+    #[serde(default)]
+    pub body_selector: Option<String>,
     pub paragraphs: Vec<String>,
 }
 
+/// A slide of the photo carousel of an article.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Photo {
+    pub url: String,
+    pub caption: Option<String>,
+    /// The page of the slide, where its caption is read from when the carousel doesn't carry
+    /// it
+    pub slide_url: Option<String>,
+}
+
 impl fmt::Display for DetikArticle {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(
@@ -33,6 +73,18 @@ impl fmt::Display for DetikArticle {
             writeln!(f, "Author          : None")?;
         };
 
+        if let Some(d) = self.desk.as_ref() {
+            writeln!(f, "Desk            : {}", d)?;
+        } else {
+            writeln!(f, "Desk            : None")?;
+        };
+
+        if let Some(d) = self.category.as_ref() {
+            writeln!(f, "Category        : {}", d)?;
+        } else {
+            writeln!(f, "Category        : None")?;
+        };
+
         if let Some(d) = self.published_date.as_ref() {
             writeln!(f, "Published Date  : {}", d)?;
         } else {
@@ -51,6 +103,13 @@ impl fmt::Display for DetikArticle {
             writeln!(f, "Thumbnail       : None")?;
         };
         writeln!(f, "Keywords        : {}", self.keywords.join(", "))?;
+        writeln!(f, "Photos          : ")?;
+        for photo in &self.photos {
+            match &photo.caption {
+                Some(caption) => writeln!(f, "> {} ({})", photo.url, caption)?,
+                None => writeln!(f, "> {}", photo.url)?,
+            }
+        }
         writeln!(f, "Paragraphs      : ")?;
         for p in &self.paragraphs {
             writeln!(f, "> {}", p.replace('\n', "\n  "))?;
@@ -60,72 +119,107 @@ impl fmt::Display for DetikArticle {
     }
 }
 
-impl Article for DetikArticle {
-    fn get_paragraphs(&self) -> &[String] {
-        self.paragraphs.as_slice()
+impl DetikArticle {
+    /// The article as markdown for archiving: a front matter block with the metadata, the
+    /// title as heading, the thumbnail and the paragraphs.
+    pub fn to_markdown(&self) -> String {
+        let quote = |s: &str| serde_json::Value::from(s).to_string();
+        let fields = [
+            ("title", self.title.as_deref().map(quote)),
+            ("author", self.author.as_deref().map(quote)),
+            ("desk", self.desk.as_deref().map(quote)),
+            ("category", self.category.as_deref().map(quote)),
+            ("language", self.language.as_deref().map(quote)),
+            (
+                "published_date",
+                self.published_date.map(|d| quote(&d.to_rfc3339())),
+            ),
+            ("description", self.description.as_deref().map(quote)),
+            ("thumbnail_url", self.thumbnail_url.as_deref().map(quote)),
+            (
+                "keywords",
+                Some(&self.keywords)
+                    .filter(|k| !k.is_empty())
+                    .map(|k| serde_json::Value::from(k.clone()).to_string()),
+            ),
+            (
+                "videos",
+                Some(&self.videos)
+                    .filter(|v| !v.is_empty())
+                    .map(|v| serde_json::Value::from(v.clone()).to_string()),
+            ),
+        ];
+        let front_matter: Vec<String> = fields
+            .into_iter()
+            .filter_map(|(key, value)| Some(format!("{}: {}", key, value?)))
+            .collect();
+
+        let mut blocks = vec![];
+        if !front_matter.is_empty() {
+            blocks.push(format!("---\n{}\n---", front_matter.join("\n")));
+        }
+        let title = self.title.as_deref().map(text::escape_markdown);
+        if let Some(title) = &title {
+            blocks.push(format!("# {}", title));
+        }
+        if let Some(url) = &self.thumbnail_url {
+            let caption = self
+                .description
+                .as_deref()
+                .map(text::escape_markdown)
+                .or(title)
+                .unwrap_or_default();
+            blocks.push(format!("![{}]({})", caption, url.replace(' ', "%20")));
+        }
+        blocks.extend(self.paragraphs.iter().map(|p| text::markdown_text(p)));
+        blocks.extend(self.photos.iter().map(|photo| {
+            let caption = photo.caption.as_deref().map(text::escape_markdown);
+            format!(
+                "![{}]({})",
+                caption.unwrap_or_default(),
+                photo.url.replace(' ', "%20")
+            )
+        }));
+        let mut md = blocks.join("\n\n");
+        md.push('\n');
+        md
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::detik::crawler::DetikCrawler;
-    use crate::Crawler;
-    use crate::CrawlerResult;
+    /// The article as plain text: the title, the date line and the paragraphs.
+    pub fn to_plaintext(&self) -> String {
+        let mut header = vec![];
+        if let Some(title) = &self.title {
+            header.push(title.clone());
+        }
+        if let Some(date) = self.published_date {
+            header.push(date.format("%Y-%m-%d %H:%M %:z").to_string());
+        }
 
-    use super::*;
-    use pretty_assertions::assert_eq;
-    use scraper::html::Html;
-    use std::fs;
+        let mut blocks = vec![];
+        if !header.is_empty() {
+            blocks.push(header.join("\n"));
+        }
+        blocks.extend(self.paragraphs.iter().map(|p| text::plain_text(p)));
+        let mut plain = blocks.join("\n\n");
+        plain.push('\n');
+        plain
+    }
+}
 
-    #[test]
-    fn test_parsing_document_and_links() {
-        let s = DetikCrawler {};
-        let html = fs::read_to_string("tests/htmls/1.html").expect("Invalid file url");
-        let html = Html::parse_document(&html);
+impl Article for DetikArticle {
+    fn get_paragraphs(&self) -> &[String] {
+        self.paragraphs.as_slice()
+    }
 
-        let res = s.crawl(&html);
-        assert!(matches!(&res, CrawlerResult::DocumentAndLinks(_, _)));
+    fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
 
-        let CrawlerResult::DocumentAndLinks(extracted_doc, _) = res else {
-            unreachable!()
-        };
+    fn get_published_date(&self) -> Option<DateTime<FixedOffset>> {
+        self.published_date
+    }
 
-        let doc = DetikArticle {
-            title: Some(
-                "Polisi soal Pistol di Kasus Cekcok Pemobil vs Pemotor: Cuma Diperlihatkan"
-                    .to_string(),
-            ),
-            published_date: Some(
-                DateTime::parse_from_str("2022/12/10 13:19:56 +0700", "%Y/%m/%d %H:%M:%S %z")
-                    .expect("Invalid date format"),
-            ),
-            description: Some("Polisi menjelaskan soal pistol yang dibawa pengemudi mobil yang cekcok dengan pemotor di Jaksel. Pistol itu tak ditodongkan, hanya diperlihatkan.".to_string()),
-            thumbnail_url: Some("https://akcdn.detik.net.id/community/media/visual/2020/03/05/043c2d4e-732c-4ff2-8922-32d98c0f0a7e_169.jpeg?w=650".to_string()),
-            author: Some("Mulia Budi".to_string()),
-            keywords: vec![
-                "pria berpistol".to_string(),
-                "cekcok".to_string(),
-                "cekcok di jalan".to_string(),
-                "viral".to_string(),
-                "polsek kebayoran lama".to_string(),
-                "jabodetabek".to_string()
-            ],
-            paragraphs: vec![
-                "Polisi masih mendalami percekcokan antara pemotor dan pemobil yang dinarasikan membawa pistol di Cipulir, Kabayoran Lama, Jakarta Selatan (Jaksel). Korban atau pemotor pria berinisial CE, telah membuat laporan terkait kejadian itu.".to_string(),
-                r#""Korbanya kita dampingi buat laporan, korbannya, kemarin. Kemarin kita dampingi untuk buat laporan, terus diambil keterangannya terhadap kejadian waktu itu," kata Kapolsek Kabayoran Lama, Kompol Widya Agustiono saat dihubungi wartawan, Sabtu (10/12/2022)."#.to_string(),
-                "Widya mengatakan pemobil atau pria berkemeja biru muda dalam video tersebut menyimpan benda yang dicurigai merupakan pistol di pinggang. Dia menyebut pria itu tak mengacungkan benda menyerupai pistol itu pada CE.".to_string(),
-                r#""Kalau dari keterangannya (korban), dia (pria berkemeja biru) mengeluarkan, memperlihatkan, setelah itu ditaruh di pinggang, seperti itu. Kalau langsung mengacungkan, keterangannya belum ada," ujarnya."#.to_string(),
-                "Widya mengatakan pihaknya belum bisa memastikan apakah benda yang dibawa pelaku itu pistol asli atau hanya replika. Dia menegaskan polisi masih mengusut kasus tersebut.".to_string(),
-                r#""(Terduga) pelakunya masih penyelidikan, belum (diketahui pistol beneran atau replika), karena kita harus berhasil dulu mengidentifikasi," ujar Widya."#.to_string(),
-                "Sebagai informasi, dalam video yang beredar, pria berkemeja biru muda tampak berusaha menyerang pria yang mengenakan sweater putih. Pria berkemeja biru muda itu juga terlihat memukul wajah pria sweater putih tersebut.".to_string(),
-                "Sebelumnya, sebuah video yang memperlihatkan percekcokan dua orang pria di Cipulir, Kebayoran Lama, Jakarta Selatan (Jaksel), viral di media sosial. Salah satu pria berkemeja biru muda dalam video itu dinarasikan membawa pistol.".to_string(),
-                "Dalam video yang beredar, pria berkemeja biru muda tampak cekcok dengan pria yang mengenakan sweater putih. Warga tampak berkerumun melihat keributan tersebut.".to_string(),
-                "Pria berbaju biru muda itu tampak berusaha menyerang pria berbaju putih. Dia juga sempat menampar wajah pria baju putih tersebut.".to_string(),
-                "Kemudian, seorang satpam mencoba melerai keributan tersebut. Pria berbaju biru muda itu dinarasikan membawa pistol hingga sempat menodongkan pistol tersebut.".to_string(),
-                r#""Videoin...videoin..videoin, beceng..beceng...bawa beceng. Viralin...viralin, bawa beceng itu dia," kata perekam suara dalam video tersebut."#.to_string(),
-                "Peristiwa itu terjadi pada Rabu (7/12/2022) sekitar pukul 21.45 WIB. Disebut-sebut percekcokan itu terjadi antara pengemudi mobil dengan pengemudi motor.".to_string()
-            ],
-        };
-        assert_eq!(extracted_doc, doc);
+    fn get_language(&self) -> Option<String> {
+        self.language.clone()
     }
 }
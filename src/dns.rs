@@ -0,0 +1,253 @@
+//! A cache of the host lookups of a crawl: each host is resolved once per TTL however many
+//! tasks fetch from it, and a host that failed to resolve is left alone for a shorter while
+//! instead of every task waiting out its own DNS timeout.
+
+use hyper::client::connect::dns::Name;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
+
+/// Resolves host names, the system resolver outside of tests.
+#[async_trait::async_trait]
+pub trait Resolver: Send + Sync {
+    async fn lookup(&self, host: &str) -> std::io::Result<Vec<IpAddr>>;
+}
+
+/// The resolver of the OS, `getaddrinfo` on a blocking thread.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemResolver;
+
+#[async_trait::async_trait]
+impl Resolver for SystemResolver {
+    async fn lookup(&self, host: &str) -> std::io::Result<Vec<IpAddr>> {
+        let addrs = tokio::net::lookup_host((host, 0)).await?;
+        Ok(addrs.map(|addr| addr.ip()).collect())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DnsCacheConfig {
+    /// How long the addresses of a host are kept
+    pub ttl: Duration,
+    /// How long a host that failed to resolve is skipped
+    pub negative_ttl: Duration,
+}
+
+impl Default for DnsCacheConfig {
+    fn default() -> Self {
+        DnsCacheConfig {
+            ttl: Duration::from_secs(300),
+            negative_ttl: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DnsStats {
+    /// Lookups answered from the cache, resolved or failed
+    pub hits: u64,
+    /// Lookups that went to the resolver
+    pub misses: u64,
+    /// Lookups that went to the resolver and failed
+    pub failures: u64,
+    /// Urls put back in the queue because their host failed to resolve
+    pub requeued: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    result: Result<Vec<IpAddr>, String>,
+    expires: Instant,
+}
+
+type Slot = Arc<tokio::sync::Mutex<Option<Entry>>>;
+
+pub struct DnsCache {
+    resolver: Box<dyn Resolver>,
+    config: DnsCacheConfig,
+    /// One slot per host, locked while it's being resolved so the other lookups wait for it
+    hosts: std::sync::Mutex<HashMap<String, Slot>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    failures: AtomicU64,
+    requeued: AtomicU64,
+}
+
+impl DnsCache {
+    pub fn new<R: Resolver + 'static>(resolver: R, config: DnsCacheConfig) -> Self {
+        DnsCache {
+            resolver: Box::new(resolver),
+            config,
+            hosts: Default::default(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+            requeued: AtomicU64::new(0),
+        }
+    }
+
+    /// The addresses of `host`, or why it didn't resolve, from the cache while it's fresh.
+    pub async fn lookup(&self, host: &str) -> Result<Vec<IpAddr>, String> {
+        self.lookup_at(host, Instant::now()).await
+    }
+
+    async fn lookup_at(&self, host: &str, now: Instant) -> Result<Vec<IpAddr>, String> {
+        let host = host.to_ascii_lowercase();
+        let slot = Arc::clone(self.hosts.lock().unwrap().entry(host.clone()).or_default());
+        let mut entry = slot.lock().await;
+        if let Some(entry) = entry.as_ref().filter(|entry| entry.expires > now) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return entry.result.clone();
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let result = match self.resolver.lookup(&host).await {
+            Ok(addrs) if addrs.is_empty() => Err(format!("no address for {}", host)),
+            Ok(addrs) => Ok(addrs),
+            Err(e) => Err(e.to_string()),
+        };
+        let ttl = if result.is_ok() {
+            self.config.ttl
+        } else {
+            self.failures.fetch_add(1, Ordering::Relaxed);
+            self.config.negative_ttl
+        };
+        *entry = Some(Entry {
+            result: result.clone(),
+            expires: now + ttl,
+        });
+        result
+    }
+
+    pub(crate) fn inc_requeued(&self) {
+        self.requeued.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> DnsStats {
+        DnsStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            requeued: self.requeued.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// The cache as the resolver of a reqwest client, so a fetch doesn't resolve its host again.
+pub(crate) struct CachedResolver(pub(crate) Arc<DnsCache>);
+
+impl reqwest::dns::Resolve for CachedResolver {
+    fn resolve(&self, name: Name) -> reqwest::dns::Resolving {
+        let cache = Arc::clone(&self.0);
+        Box::pin(async move {
+            let addrs = cache.lookup(name.as_str()).await?;
+            let addrs: reqwest::dns::Addrs =
+                Box::new(addrs.into_iter().map(|ip| SocketAddr::new(ip, 0)));
+            Ok(addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Resolves the hosts starting with `up` to 10.0.0.1 and fails the others, recording
+    /// every lookup.
+    #[derive(Clone, Default)]
+    struct FakeResolver {
+        lookups: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Resolver for FakeResolver {
+        async fn lookup(&self, host: &str) -> std::io::Result<Vec<IpAddr>> {
+            self.lookups.lock().unwrap().push(host.to_string());
+            tokio::task::yield_now().await;
+            if host.starts_with("up") {
+                Ok(vec![IpAddr::from([10, 0, 0, 1])])
+            } else {
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "timed out",
+                ))
+            }
+        }
+    }
+
+    fn cache() -> (DnsCache, FakeResolver) {
+        let resolver = FakeResolver::default();
+        let config = DnsCacheConfig {
+            ttl: Duration::from_secs(60),
+            negative_ttl: Duration::from_secs(10),
+        };
+        (DnsCache::new(resolver.clone(), config), resolver)
+    }
+
+    #[tokio::test]
+    async fn positive() {
+        let (cache, resolver) = cache();
+        let now = Instant::now();
+        for host in ["up.detik.com", "UP.detik.com", "up.detik.com"] {
+            assert_eq!(
+                cache.lookup_at(host, now).await,
+                Ok(vec![IpAddr::from([10, 0, 0, 1])])
+            );
+        }
+        assert_eq!(*resolver.lookups.lock().unwrap(), vec!["up.detik.com"]);
+        assert_eq!(
+            cache.stats(),
+            DnsStats {
+                hits: 2,
+                misses: 1,
+                ..DnsStats::default()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn negative() {
+        let (cache, resolver) = cache();
+        let now = Instant::now();
+        assert!(cache.lookup_at("down.detik.com", now).await.is_err());
+        assert!(cache.lookup_at("down.detik.com", now).await.is_err());
+        assert_eq!(resolver.lookups.lock().unwrap().len(), 1);
+        assert_eq!(cache.stats().failures, 1);
+
+        // Concurrent lookups of a new host wait for the first one
+        let lookups = (0..5).map(|_| cache.lookup_at("down.cdn.detik.com", now));
+        let results = futures::future::join_all(lookups).await;
+        assert!(results.iter().all(Result::is_err));
+        assert_eq!(resolver.lookups.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn expiry() {
+        let (cache, resolver) = cache();
+        let now = Instant::now();
+        cache.lookup_at("up.detik.com", now).await.unwrap();
+        cache.lookup_at("down.detik.com", now).await.unwrap_err();
+
+        // The failure expires first
+        let later = now + Duration::from_secs(11);
+        cache.lookup_at("up.detik.com", later).await.unwrap();
+        cache.lookup_at("down.detik.com", later).await.unwrap_err();
+        assert_eq!(resolver.lookups.lock().unwrap().len(), 3);
+
+        let much_later = now + Duration::from_secs(61);
+        cache.lookup_at("up.detik.com", much_later).await.unwrap();
+        assert_eq!(
+            *resolver.lookups.lock().unwrap(),
+            vec![
+                "up.detik.com",
+                "down.detik.com",
+                "down.detik.com",
+                "up.detik.com"
+            ]
+        );
+    }
+}
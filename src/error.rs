@@ -1,5 +1,64 @@
 #[derive(Debug, thiserror::Error)]
 pub enum CrawlerError {
+    #[cfg(feature = "sqlite")]
     #[error("Database error")]
-    DatabaseError(#[from] sqlx::error::Error),
+    DatabaseError(#[source] sqlx::error::Error),
+    #[error("HTTP client error")]
+    HttpError(#[from] reqwest::Error),
+    #[error("IO error")]
+    IoError(#[from] std::io::Error),
+    /// A body larger than `RunOptions::max_decompressed_size`, once decompressed
+    #[error("Body larger than {0} bytes")]
+    TooLarge(u64),
+    /// A server error response, 5xx, whose body isn't worth crawling
+    #[error("HTTP status {0}")]
+    ServerStatus(u16),
+    /// A 429 Too Many Requests response, with the delay of its `Retry-After` header
+    #[error("Too many requests")]
+    TooManyRequests(Option<std::time::Duration>),
+    /// The `RunOptions::canary` article the crawler no longer extracts, e.g. after the site
+    /// changed its markup
+    #[error("Unhealthy canary {0}: {1}")]
+    UnhealthyCanary(String, crate::health::HealthReport),
+    /// The storage no longer takes writes, e.g. its disk is full or its filesystem went
+    /// read-only. The crawl waits for it, see `outage`.
+    #[error("Storage unavailable: {0}")]
+    StorageUnavailable(String),
+    /// A selector of `SelectorOverrides`, by name, that the crawler can't use
+    #[error("Invalid selector {name}: {source}")]
+    InvalidSelector {
+        name: String,
+        source: crate::selectors::SelectorError,
+    },
+    #[cfg(feature = "http-api")]
+    #[error("HTTP server error")]
+    ServerError(#[from] hyper::Error),
+}
+
+/// The primary result codes of SQLite, an extended code holding one in its low byte.
+#[cfg(feature = "sqlite")]
+const SQLITE_READONLY: i32 = 8;
+#[cfg(feature = "sqlite")]
+const SQLITE_FULL: i32 = 13;
+
+#[cfg(feature = "sqlite")]
+impl From<sqlx::error::Error> for CrawlerError {
+    fn from(e: sqlx::error::Error) -> Self {
+        let unavailable = match &e {
+            sqlx::Error::Database(db) => db
+                .code()
+                .and_then(|code| code.parse::<i32>().ok())
+                .is_some_and(|code| matches!(code & 0xff, SQLITE_READONLY | SQLITE_FULL)),
+            sqlx::Error::Io(io) => matches!(
+                io.kind(),
+                std::io::ErrorKind::StorageFull | std::io::ErrorKind::ReadOnlyFilesystem
+            ),
+            _ => false,
+        };
+        if unavailable {
+            CrawlerError::StorageUnavailable(e.to_string())
+        } else {
+            CrawlerError::DatabaseError(e)
+        }
+    }
 }
@@ -0,0 +1,166 @@
+//! Exporting the stored detik articles to a JSONL file, one article a line, resumable after
+//! an interruption: every page of articles written is recorded in a `.progress` file next to
+//! the export, removed once it's done.
+
+use crate::detik::{DetikArticle, DetikArticleTable};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// The articles read, and the progress recorded, at a time by default.
+pub const CHECKPOINT_EVERY: u32 = 1000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ExportError {
+    #[error("Database error")]
+    DatabaseError(#[from] sqlx::Error),
+    #[error("IO error")]
+    IoError(#[from] std::io::Error),
+    #[error("Invalid checkpoint {0}: {1}")]
+    InvalidCheckpoint(PathBuf, serde_json::Error),
+    #[error("No checkpoint {0} to resume from")]
+    NoCheckpoint(PathBuf),
+    /// An export shorter than its checkpoint, so not the one the checkpoint was written for
+    #[error("{path} is {found} bytes, shorter than the {expected} of its checkpoint")]
+    Mismatch {
+        path: PathBuf,
+        expected: u64,
+        found: u64,
+    },
+}
+
+/// How far an export went.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// The cursor of the last article written, see `DetikArticleTable::results_after`
+    pub cursor: i64,
+    /// The length of the export once it was written
+    pub offset: u64,
+    /// The articles written
+    pub rows: u64,
+}
+
+impl Checkpoint {
+    /// The `.progress` file of the export `path`.
+    pub fn path(path: &Path) -> PathBuf {
+        let mut progress = path.as_os_str().to_owned();
+        progress.push(".progress");
+        PathBuf::from(progress)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ExportError> {
+        let json = match std::fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(ExportError::NoCheckpoint(path.to_path_buf()))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        serde_json::from_str(&json)
+            .map_err(|e| ExportError::InvalidCheckpoint(path.to_path_buf(), e))
+    }
+
+    /// Write to `path` through a temporary file, so an interruption leaves the previous
+    /// checkpoint rather than half of this one.
+    fn save(&self, path: &Path) -> Result<(), ExportError> {
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        std::fs::write(&tmp, serde_json::to_string(self).unwrap())?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+/// A line of the export.
+#[derive(Serialize)]
+struct Line<'a> {
+    url: &'a str,
+    #[serde(flatten)]
+    article: &'a DetikArticle,
+}
+
+/// The options of `export_jsonl`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExportOptions {
+    /// Also the articles stored with their metadata only
+    pub include_metadata_only: bool,
+    /// The articles read, and the progress recorded, at a time
+    pub checkpoint_every: u32,
+    /// Continue the export `path` from its checkpoint instead of starting over
+    pub resume: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        ExportOptions {
+            include_metadata_only: false,
+            checkpoint_every: CHECKPOINT_EVERY,
+            resume: false,
+        }
+    }
+}
+
+/// Write the articles of `results` to `path`, oldest first, returning the number written
+/// including those of the export resumed. Whatever was written after the last checkpoint of
+/// a resumed export is cut and written again.
+pub async fn export_jsonl(
+    results: &DetikArticleTable,
+    path: &Path,
+    options: ExportOptions,
+) -> Result<u64, ExportError> {
+    let progress = Checkpoint::path(path);
+    let mut checkpoint = Checkpoint::default();
+    let file = if options.resume {
+        checkpoint = Checkpoint::load(&progress)?;
+        let mut file = OpenOptions::new().write(true).open(path)?;
+        let found = file.metadata()?.len();
+        if found < checkpoint.offset {
+            return Err(ExportError::Mismatch {
+                path: path.to_path_buf(),
+                expected: checkpoint.offset,
+                found,
+            });
+        }
+        file.set_len(checkpoint.offset)?;
+        file.seek(SeekFrom::Start(checkpoint.offset))?;
+        file
+    } else {
+        remove(&progress)?;
+        File::create(path)?
+    };
+    let mut out = BufWriter::new(file);
+
+    let limit = options.checkpoint_every.max(1);
+    loop {
+        let page = if options.include_metadata_only {
+            results.results_after(checkpoint.cursor, limit).await?
+        } else {
+            results.full_results_after(checkpoint.cursor, limit).await?
+        };
+        let last = match page.last() {
+            Some((cursor, _, _)) => *cursor,
+            None => break,
+        };
+        for (_, url, article) in &page {
+            let line = serde_json::to_string(&Line { url, article }).unwrap();
+            writeln!(out, "{}", line)?;
+            checkpoint.offset += line.len() as u64 + 1;
+        }
+        out.flush()?;
+        checkpoint.cursor = last;
+        checkpoint.rows += page.len() as u64;
+        checkpoint.save(&progress)?;
+    }
+    out.flush()?;
+    remove(&progress)?;
+    Ok(checkpoint.rows)
+}
+
+/// Remove the file `path` unless it's already gone.
+fn remove(path: &Path) -> Result<(), ExportError> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
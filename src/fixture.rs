@@ -0,0 +1,140 @@
+use crate::{CrawlContext, Crawler};
+use lazy_regex::regex;
+use reqwest::Url;
+use scraper::Html;
+use serde::Serialize;
+use std::{collections::BTreeMap, io, path::Path};
+
+/// The file in each fixture dir mapping fixture names to the url they were captured from.
+pub const URLS_FILE: &str = "urls.json";
+
+/// Prepare a captured page for `tests/htmls`: optionally drop `<script>` tags (JSON-LD is kept
+/// since crawlers read it) and make sure the document declares its charset as UTF-8.
+pub fn sanitize(html: &str, strip_scripts: bool) -> String {
+    let html = if strip_scripts {
+        regex!(r"(?is)<script\b([^>]*)>.*?</script\s*>")
+            .replace_all(html, |caps: &lazy_regex::Captures| {
+                if caps[1].to_lowercase().contains("application/ld+json") {
+                    caps[0].to_string()
+                } else {
+                    String::new()
+                }
+            })
+            .into_owned()
+    } else {
+        html.to_string()
+    };
+
+    let html = regex!(r#"(?i)<meta\s+charset\s*=\s*["']?[\w-]+["']?\s*/?>"#).replace_all(&html, "");
+    let html = regex!(r#"(?i)<meta\s+http-equiv\s*=\s*["']?content-type["']?[^>]*>"#)
+        .replace_all(&html, "");
+
+    let charset = r#"<meta charset="utf-8">"#;
+    match regex!(r"(?i)<head\b[^>]*>").find(&html) {
+        Some(head) => format!("{}{}{}", &html[..head.end()], charset, &html[head.end()..]),
+        None => format!("{}{}", charset, html),
+    }
+}
+
+/// Run the crawler over a fixture captured from `url` and serialize the result as the golden
+/// `.expected.json`.
+pub fn expected_json<C>(crawler: &C, url: &Url, html: &str) -> serde_json::Result<String>
+where
+    C: Crawler,
+    C::Document: Serialize,
+{
+    let doc = Html::parse_document(html);
+    let ctx = CrawlContext::new(url);
+    let mut json = serde_json::to_string_pretty(&crawler.crawl(&ctx, &doc))?;
+    json.push('\n');
+    Ok(json)
+}
+
+/// The urls of the fixtures in `dir` by name, empty when `dir` has no `URLS_FILE`.
+pub fn urls(dir: &Path) -> io::Result<BTreeMap<String, Url>> {
+    let urls: BTreeMap<String, String> = match std::fs::read_to_string(dir.join(URLS_FILE)) {
+        Ok(json) => serde_json::from_str(&json)?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+        Err(e) => return Err(e),
+    };
+    urls.into_iter()
+        .map(|(name, url)| {
+            let url =
+                Url::parse(&url).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            Ok((name, url))
+        })
+        .collect()
+}
+
+/// Record that the fixture `name` of `dir` was captured from `url`.
+pub fn save_url(dir: &Path, name: &str, url: &Url) -> io::Result<()> {
+    let mut urls: BTreeMap<String, String> = urls(dir)?
+        .into_iter()
+        .map(|(name, url)| (name, url.into()))
+        .collect();
+    urls.insert(name.to_string(), url.to_string());
+    let mut json = serde_json::to_string_pretty(&urls)?;
+    json.push('\n');
+    std::fs::write(dir.join(URLS_FILE), json)
+}
+
+#[cfg(all(test, feature = "detik"))]
+mod tests {
+    use super::*;
+    use crate::detik::{DetikArticle, DetikCrawler};
+    use crate::CrawlerResult;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn strip_scripts_keeps_json_ld() {
+        let html = r#"<html><head><script src="a.js"></script><SCRIPT>var a = "<p>";</SCRIPT><script type="application/ld+json">{"a": 1}</script></head><body><p>x</p><script
+            async>b()</script></body></html>"#;
+        assert_eq!(
+            sanitize(html, true),
+            r#"<html><head><meta charset="utf-8"><script type="application/ld+json">{"a": 1}</script></head><body><p>x</p></body></html>"#
+        );
+    }
+
+    #[test]
+    fn keep_scripts() {
+        let html = r#"<html><head><script>a()</script></head></html>"#;
+        assert_eq!(
+            sanitize(html, false),
+            r#"<html><head><meta charset="utf-8"><script>a()</script></head></html>"#
+        );
+    }
+
+    #[test]
+    fn replace_existing_charset() {
+        let html = r#"<html><head lang="id"><title>t</title><meta charset="iso-8859-1"/><meta http-equiv="Content-Type" content="text/html; charset=windows-1252"></head></html>"#;
+        assert_eq!(
+            sanitize(html, true),
+            r#"<html><head lang="id"><meta charset="utf-8"><title>t</title></head></html>"#
+        );
+    }
+
+    #[test]
+    fn expected_json_round_trip() {
+        let html = std::fs::read_to_string("tests/htmls/1.html").expect("Invalid file url");
+        let url = &urls(Path::new("tests/htmls")).unwrap()["1"];
+        let json = expected_json(&DetikCrawler::new(), url, &html).unwrap();
+        let parsed: CrawlerResult<DetikArticle> = serde_json::from_str(&json).unwrap();
+
+        let CrawlerResult::DocumentAndLinks(doc, links) = parsed else {
+            panic!("Expected a document")
+        };
+        assert_eq!(
+            doc.title.as_deref(),
+            Some("Polisi soal Pistol di Kasus Cekcok Pemobil vs Pemotor: Cuma Diperlihatkan")
+        );
+        assert!(!links.is_empty());
+    }
+
+    #[test]
+    fn no_head() {
+        assert_eq!(
+            sanitize("<p>x</p>", true),
+            r#"<meta charset="utf-8"><p>x</p>"#
+        );
+    }
+}
@@ -0,0 +1,568 @@
+use super::GenericArticle;
+use crate::compression::ParagraphCodec;
+use crate::recrawl::LinkSnapshot;
+use crate::{
+    utils, Article, Bucket, CrawlerError, ExtractionStatus, HostState, HubState, QueuedUrl,
+    ResultEntry, ResultSummary, ResultTable, Revision, SqliteStorage, Storage, Table, UrlBucket,
+    WarnedEntry,
+};
+use chrono::{DateTime, FixedOffset};
+use sqlx::query::Query;
+use sqlx::sqlite::SqliteArguments;
+use sqlx::{Sqlite, SqlitePool};
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Columns of every results table besides `id`, `created_at` and those of the site.
+const COMMON_COLUMNS: [(&str, &str); 4] = [
+    ("title", "TEXT"),
+    ("published_date", "DATETIME"),
+    ("paragraphs", "TEXT"),
+    ("compression", "TEXT"),
+];
+
+/// An article stored by a `SiteArticleTable`, its title, published date and paragraphs in
+/// the common columns.
+pub trait SiteArticle: Article + Send + Sync + 'static {
+    /// The other columns, also added to the results tables of older schemas.
+    const COLUMNS: &'static [(&'static str, &'static str)];
+
+    /// Bind the values of `COLUMNS`, in their order, to `query`.
+    fn bind<'q>(
+        self,
+        query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    ) -> Query<'q, Sqlite, SqliteArguments<'q>>;
+}
+
+impl SiteArticle for GenericArticle {
+    const COLUMNS: &'static [(&'static str, &'static str)] = &[
+        ("authors", "TEXT"),
+        ("description", "TEXT"),
+        ("thumbnail_url", "TEXT"),
+        ("keywords", "TEXT"),
+        ("tags", "TEXT"),
+        ("category", "TEXT"),
+        ("images", "TEXT"),
+        ("canonical_url", "TEXT"),
+    ];
+
+    fn bind<'q>(
+        self,
+        query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    ) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+        query
+            .bind(self.authors.join("|"))
+            .bind(self.description)
+            .bind(self.thumbnail_url)
+            .bind(self.keywords.join("|"))
+            .bind(self.tags.join("|"))
+            .bind(self.category)
+            .bind(self.images.join("\n"))
+            .bind(self.canonical_url)
+    }
+}
+
+/// The results table of a site, one column per field of its articles.
+pub struct SiteArticleTable<A> {
+    name: String,
+    pool: SqlitePool,
+    codec: Arc<ParagraphCodec>,
+    article: PhantomData<fn(A)>,
+}
+
+impl<A: SiteArticle> SiteArticleTable<A> {
+    fn columns() -> impl Iterator<Item = &'static (&'static str, &'static str)> {
+        COMMON_COLUMNS.iter().chain(A::COLUMNS)
+    }
+}
+
+pub type GenericArticleTable = SiteArticleTable<GenericArticle>;
+
+#[async_trait::async_trait]
+impl<A: SiteArticle> Table for SiteArticleTable<A> {
+    type Record<'a> = (&'a str, A);
+
+    fn get_name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn get_pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    async fn create(&self) -> Result<(), sqlx::Error> {
+        if !utils::is_table_exists(self.get_pool(), &self.name).await? {
+            let columns = Self::columns()
+                .map(|(column, kind)| format!("{} {}", column, kind))
+                .collect::<Vec<_>>()
+                .join(",\n");
+            let query = format!(
+                "CREATE TABLE {} (
+                    id TEXT PRIMARY KEY,
+                    created_at DATETIME,
+                    {}
+                 )",
+                &self.name, columns
+            );
+            sqlx::query(query.as_str()).execute(self.get_pool()).await?;
+        } else {
+            for (column, kind) in Self::columns() {
+                utils::add_column(self.get_pool(), &self.name, column, kind).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn insert<'a>(&self, (url, record): Self::Record<'a>) -> Result<(), sqlx::Error> {
+        let mut tx = self.get_pool().begin().await?;
+        let columns = A::COLUMNS
+            .iter()
+            .map(|(column, _)| format!("{}, ", column))
+            .collect::<String>();
+        let query = format!(
+            "INSERT OR IGNORE INTO {} (
+                id, title, published_date, {}paragraphs, compression, created_at
+             ) VALUES (?, ?, ?, {}?, ?, ?)",
+            self.name,
+            columns,
+            "?, ".repeat(A::COLUMNS.len())
+        );
+        let title = record.get_title().map(str::to_string);
+        let published_date = record.get_published_date();
+        let paragraphs = self.codec.encode(record.get_paragraphs().join("\n"))?;
+        let query = sqlx::query(&query)
+            .bind(url.trim())
+            .bind(title)
+            .bind(published_date);
+        paragraphs
+            .bind(record.bind(query))
+            .bind(utils::get_now())
+            .execute(&mut tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+impl<A: SiteArticle> ResultTable for SiteArticleTable<A> {
+    type Article = A;
+
+    fn new(name: String, pool: SqlitePool) -> Self {
+        SiteArticleTable {
+            name,
+            pool,
+            codec: Default::default(),
+            article: PhantomData,
+        }
+    }
+
+    fn record(url: &str, article: A) -> (&str, A) {
+        (url, article)
+    }
+
+    fn set_codec(&mut self, codec: Arc<ParagraphCodec>) {
+        self.codec = codec;
+    }
+}
+
+/// The `Storage` of any site crawling `GenericArticle`s, its tables prefixed with the site
+/// name in the database at `path`.
+pub struct SiteStorage {
+    inner: SqliteStorage<GenericArticleTable>,
+}
+
+impl SiteStorage {
+    pub async fn new(site_name: &str, path: impl AsRef<Path>) -> Result<Self, CrawlerError> {
+        Ok(SiteStorage {
+            inner: SqliteStorage::new_at(site_name, path).await?,
+        })
+    }
+
+    /// See `SqliteStorage::open_read_only`.
+    pub async fn open_read_only(
+        site_name: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<Self, CrawlerError> {
+        Ok(SiteStorage {
+            inner: SqliteStorage::open_read_only_at(site_name, path).await?,
+        })
+    }
+
+    /// See `SqliteStorage::with_visited_cache`.
+    pub async fn with_visited_cache(self, hits: usize) -> Result<Self, CrawlerError> {
+        Ok(SiteStorage {
+            inner: self.inner.with_visited_cache(hits).await?,
+        })
+    }
+
+    /// See `SqliteStorage::with_compression`.
+    pub async fn with_compression(self) -> Result<Self, CrawlerError> {
+        Ok(SiteStorage {
+            inner: self.inner.with_compression().await?,
+        })
+    }
+
+    pub fn inner(&self) -> &SqliteStorage<GenericArticleTable> {
+        &self.inner
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for SiteStorage {
+    type Record = GenericArticle;
+
+    fn bucket(&self, bucket: Bucket) -> &dyn UrlBucket {
+        self.inner.bucket(bucket)
+    }
+
+    async fn queued_insert_with_meta<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        referrer: Option<&str>,
+        depth: u32,
+    ) -> Result<(), CrawlerError> {
+        self.inner
+            .queued_insert_with_meta(item, referrer, depth)
+            .await
+    }
+
+    async fn queued_insert_prioritized<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        referrer: Option<&str>,
+        depth: u32,
+        priority: i32,
+    ) -> Result<(), CrawlerError> {
+        self.inner
+            .queued_insert_prioritized(item, referrer, depth, priority)
+            .await
+    }
+
+    async fn queued_insert_with_priority<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        priority: i32,
+    ) -> Result<(), CrawlerError> {
+        self.inner.queued_insert_with_priority(item, priority).await
+    }
+
+    async fn queued_claim_n(
+        &self,
+        worker_id: &str,
+        n: u32,
+    ) -> Result<Vec<QueuedUrl>, CrawlerError> {
+        self.inner.queued_claim_n(worker_id, n).await
+    }
+
+    async fn running_claim<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        worker_id: &str,
+    ) -> Result<bool, CrawlerError> {
+        self.inner.running_claim(item, worker_id).await
+    }
+
+    async fn running_touch(
+        &self,
+        worker_id: &str,
+        now: DateTime<FixedOffset>,
+    ) -> Result<(), CrawlerError> {
+        self.inner.running_touch(worker_id, now).await
+    }
+
+    async fn running_reap(
+        &self,
+        stale_before: DateTime<FixedOffset>,
+    ) -> Result<Vec<String>, CrawlerError> {
+        self.inner.running_reap(stale_before).await
+    }
+
+    async fn running_release(&self, worker_id: &str) -> Result<Vec<String>, CrawlerError> {
+        self.inner.running_release(worker_id).await
+    }
+
+    async fn running_requeue<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        self.inner.running_requeue(item).await
+    }
+
+    async fn results_count(&self) -> Result<u32, CrawlerError> {
+        self.inner.results_count().await
+    }
+
+    async fn results_recent(&self, n: u32) -> Result<Vec<ResultSummary>, CrawlerError> {
+        self.inner.results_recent(n).await
+    }
+
+    async fn results_get(&self, url: &str) -> Result<Option<ResultEntry>, CrawlerError> {
+        self.inner.results_get(url).await
+    }
+
+    async fn results_insert_with_referrer<I: AsRef<str> + Send>(
+        &self,
+        record: (I, Self::Record),
+        referrer: Option<&str>,
+    ) -> Result<(), CrawlerError> {
+        self.inner
+            .results_insert_with_referrer(record, referrer)
+            .await
+    }
+
+    async fn results_insert_metadata_only<I: AsRef<str> + Send>(
+        &self,
+        record: (I, Self::Record),
+        referrer: Option<&str>,
+    ) -> Result<(), CrawlerError> {
+        self.inner
+            .results_insert_metadata_only(record, referrer)
+            .await
+    }
+
+    async fn results_status(&self, url: &str) -> Result<Option<ExtractionStatus>, CrawlerError> {
+        self.inner.results_status(url).await
+    }
+
+    async fn results_revise<I: AsRef<str> + Send>(
+        &self,
+        record: (I, Self::Record),
+        diff: &str,
+    ) -> Result<(), CrawlerError> {
+        self.inner.results_revise(record, diff).await
+    }
+
+    async fn revisions_get(&self, url: &str) -> Result<Vec<Revision>, CrawlerError> {
+        self.inner.revisions_get(url).await
+    }
+
+    async fn revisit_schedule(
+        &self,
+        url: &str,
+        due_at: DateTime<FixedOffset>,
+    ) -> Result<(), CrawlerError> {
+        self.inner.revisit_schedule(url, due_at).await
+    }
+
+    async fn revisit_due(
+        &self,
+        now: DateTime<FixedOffset>,
+        n: u32,
+    ) -> Result<Vec<String>, CrawlerError> {
+        self.inner.revisit_due(now, n).await
+    }
+
+    async fn warned_insert<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        reason: &str,
+    ) -> Result<(), CrawlerError> {
+        self.inner.warned_insert(item, reason).await
+    }
+
+    async fn warned_count(&self) -> Result<u32, CrawlerError> {
+        self.inner.warned_count().await
+    }
+
+    async fn warned_recent(&self, n: u32) -> Result<Vec<WarnedEntry>, CrawlerError> {
+        self.inner.warned_recent(n).await
+    }
+
+    async fn warned_get(&self, item: &str) -> Result<Option<WarnedEntry>, CrawlerError> {
+        self.inner.warned_get(item).await
+    }
+
+    async fn warned_delete(&self, item: &str) -> Result<(), CrawlerError> {
+        self.inner.warned_delete(item).await
+    }
+
+    async fn warned_schedule(
+        &self,
+        item: &str,
+        reason: &str,
+        attempts: u32,
+        next_retry_at: Option<DateTime<FixedOffset>>,
+    ) -> Result<(), CrawlerError> {
+        self.inner
+            .warned_schedule(item, reason, attempts, next_retry_at)
+            .await
+    }
+
+    async fn warned_due(
+        &self,
+        now: DateTime<FixedOffset>,
+        n: u32,
+    ) -> Result<Vec<WarnedEntry>, CrawlerError> {
+        self.inner.warned_due(now, n).await
+    }
+
+    async fn host_state_get(&self, host: &str) -> Result<Option<HostState>, CrawlerError> {
+        self.inner.host_state_get(host).await
+    }
+
+    async fn host_state_put(&self, state: &HostState) -> Result<(), CrawlerError> {
+        self.inner.host_state_put(state).await
+    }
+
+    async fn hub_get(&self, url: &str) -> Result<Option<HubState>, CrawlerError> {
+        self.inner.hub_get(url).await
+    }
+
+    async fn hub_put(&self, state: &HubState) -> Result<(), CrawlerError> {
+        self.inner.hub_put(state).await
+    }
+
+    async fn hubs_get(&self) -> Result<Vec<HubState>, CrawlerError> {
+        self.inner.hubs_get().await
+    }
+
+    async fn hub_snapshot_get(&self, url: &str) -> Result<Option<LinkSnapshot>, CrawlerError> {
+        self.inner.hub_snapshot_get(url).await
+    }
+
+    async fn hub_snapshot_put(
+        &self,
+        url: &str,
+        snapshot: &LinkSnapshot,
+    ) -> Result<(), CrawlerError> {
+        self.inner.hub_snapshot_put(url, snapshot).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::Row;
+    use tokio::fs;
+
+    async fn remove(path: &str) {
+        if Path::new(path).is_file() {
+            fs::remove_file(path).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn results_roundtrip() {
+        remove("test_generic.db").await;
+        let p = SiteStorage::new("idntimes", "test_generic.db")
+            .await
+            .unwrap();
+
+        let article = GenericArticle {
+            title: Some("title".to_string()),
+            authors: vec!["a1".to_string(), "a2".to_string()],
+            published_date: Some(utils::get_now()),
+            tags: vec!["t1".to_string()],
+            category: Some("news".to_string()),
+            paragraphs: vec!["p1".to_string(), "p2".to_string()],
+            images: vec!["https://cdn.idntimes.com/1.jpg".to_string()],
+            ..GenericArticle::default()
+        };
+        p.results_insert(("https://www.idntimes.com/news/a/b/judul", article))
+            .await
+            .unwrap();
+        assert!(p
+            .inner()
+            .results
+            .is_exist("https://www.idntimes.com/news/a/b/judul")
+            .await
+            .unwrap());
+        assert_eq!(p.inner().results.get_name(), "idntimes_results");
+        let recent = p.results_recent(1).await.unwrap();
+        assert_eq!(recent[0].title.as_deref(), Some("title"));
+
+        let row = sqlx::query("SELECT authors, images FROM idntimes_results")
+            .fetch_one(p.inner().results.get_pool())
+            .await
+            .unwrap();
+        assert_eq!(row.get::<String, _>("authors"), "a1|a2");
+        assert_eq!(
+            row.get::<String, _>("images"),
+            "https://cdn.idntimes.com/1.jpg"
+        );
+
+        fs::remove_file("test_generic.db").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn compressed_results_roundtrip() {
+        remove("test_generic_compressed.db").await;
+        let article = |i: u32| GenericArticle {
+            title: Some(format!("Judul {}", i)),
+            paragraphs: vec![format!("Paragraf pertama {}.", i), "Paragraf kedua.".into()],
+            ..GenericArticle::default()
+        };
+        let url = |i: u32| format!("https://www.idntimes.com/news/a/{}", i);
+        let p = SiteStorage::new("idntimes", "test_generic_compressed.db")
+            .await
+            .unwrap();
+        p.results_insert((url(1), article(1))).await.unwrap();
+        // Too few results to train a dictionary on
+        let p = p.with_compression().await.unwrap();
+        p.results_insert((url(2), article(2))).await.unwrap();
+
+        let markers: Vec<Option<String>> =
+            sqlx::query_scalar("SELECT compression FROM idntimes_results ORDER BY rowid")
+                .fetch_all(p.inner().results.get_pool())
+                .await
+                .unwrap();
+        assert_eq!(markers, vec![None, Some("zstd".to_string())]);
+        for i in [1, 2] {
+            assert_eq!(
+                p.results_get(&url(i)).await.unwrap().unwrap().paragraphs,
+                article(i).paragraphs
+            );
+        }
+
+        fs::remove_file("test_generic_compressed.db").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn sites_share_a_database() {
+        remove("test_generic_shared.db").await;
+        let a = SiteStorage::new("a", "test_generic_shared.db")
+            .await
+            .unwrap();
+        let b = SiteStorage::new("b", "test_generic_shared.db")
+            .await
+            .unwrap();
+
+        a.queued_insert("https://a.com/1").await.unwrap();
+        assert_eq!(a.queued_count().await.unwrap(), 1);
+        assert_eq!(b.queued_count().await.unwrap(), 0);
+
+        fs::remove_file("test_generic_shared.db").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn columns_added_to_old_tables() {
+        remove("test_generic_old.db").await;
+        let p = SiteStorage::new("old", "test_generic_old.db")
+            .await
+            .unwrap();
+        let pool = p.inner().results.get_pool();
+        sqlx::query("DROP TABLE old_results")
+            .execute(pool)
+            .await
+            .unwrap();
+        // The schema of a site specific table
+        sqlx::query(
+            "CREATE TABLE old_results (id TEXT PRIMARY KEY, created_at DATETIME,
+             title TEXT, author TEXT, paragraphs TEXT)",
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+
+        p.inner().results.create().await.unwrap();
+        for (column, _) in GenericArticleTable::columns() {
+            assert!(utils::is_column_exists(pool, "old_results", column)
+                .await
+                .unwrap());
+        }
+        p.results_insert(("https://old.com/1", GenericArticle::default()))
+            .await
+            .unwrap();
+        assert_eq!(p.results_count().await.unwrap(), 1);
+
+        fs::remove_file("test_generic_old.db").await.unwrap();
+    }
+}
@@ -0,0 +1,102 @@
+#[cfg(feature = "sqlite")]
+mod data;
+
+#[cfg(feature = "sqlite")]
+pub use data::{GenericArticleTable, SiteArticle, SiteArticleTable, SiteStorage};
+
+#[cfg(feature = "detik")]
+use crate::detik::DetikArticle;
+use crate::Article;
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+/// An article with the fields most sites provide, so a new site only needs a `Crawler` and
+/// stores its results with `SiteStorage`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenericArticle {
+    pub title: Option<String>,
+    pub authors: Vec<String>,
+    pub published_date: Option<DateTime<FixedOffset>>,
+    pub description: Option<String>,
+    pub thumbnail_url: Option<String>,
+    /// From `<meta name="keywords">`
+    pub keywords: Vec<String>,
+    /// Tags linked from the page
+    pub tags: Vec<String>,
+    pub category: Option<String>,
+    pub paragraphs: Vec<String>,
+    /// Urls of the images in the body
+    pub images: Vec<String>,
+    pub canonical_url: Option<String>,
+}
+
+impl Article for GenericArticle {
+    fn get_paragraphs(&self) -> &[String] {
+        self.paragraphs.as_slice()
+    }
+
+    fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    fn get_published_date(&self) -> Option<DateTime<FixedOffset>> {
+        self.published_date
+    }
+}
+
+#[cfg(feature = "detik")]
+impl From<DetikArticle> for GenericArticle {
+    fn from(article: DetikArticle) -> Self {
+        GenericArticle {
+            title: article.title,
+            authors: article.author.into_iter().collect(),
+            published_date: article.published_date,
+            description: article.description,
+            thumbnail_url: article.thumbnail_url,
+            keywords: article.keywords,
+            tags: vec![],
+            category: article.category,
+            paragraphs: article.paragraphs,
+            images: vec![],
+            canonical_url: None,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "detik"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_detik() {
+        let detik = DetikArticle {
+            title: Some("title".to_string()),
+            published_date: None,
+            description: Some("description".to_string()),
+            thumbnail_url: None,
+            author: Some("author".to_string()),
+            desk: None,
+            category: Some("jateng".to_string()),
+            language: None,
+            keywords: vec!["k1".to_string()],
+            keywords_raw: None,
+            videos: vec![],
+            photos: vec![],
+            body_selector: None,
+            paragraphs: vec!["p1".to_string()],
+        };
+        assert_eq!(
+            GenericArticle::from(detik),
+            GenericArticle {
+                title: Some("title".to_string()),
+                authors: vec!["author".to_string()],
+                description: Some("description".to_string()),
+                keywords: vec!["k1".to_string()],
+                category: Some("jateng".to_string()),
+                paragraphs: vec!["p1".to_string()],
+                ..GenericArticle::default()
+            }
+        );
+    }
+}
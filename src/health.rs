@@ -0,0 +1,154 @@
+//! Whether a crawler still extracts the articles of its site, to notice early when the site
+//! changed its markup instead of warning on every article.
+
+use crate::{Article, CrawlerResult};
+use serde::Serialize;
+use std::fmt;
+
+/// The fields of an article a `HealthReport` looks for, in the order they're listed.
+pub const FIELDS: [&str; 3] = ["title", "published_date", "paragraphs"];
+
+/// What a crawler got out of a page known to be an article.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HealthReport {
+    /// Why no article came out of the page, `None` when one did
+    pub not_article: Option<String>,
+    /// The `FIELDS` the article came back without, all of them when there's no article
+    pub missing: Vec<&'static str>,
+    /// Number of paragraphs of the article
+    pub paragraphs: usize,
+}
+
+impl HealthReport {
+    /// The report of a crawler returning `result` for an article page.
+    pub fn of<A: Article>(result: &CrawlerResult<A>) -> Self {
+        let article = match result {
+            CrawlerResult::DocumentAndLinks(article, _) => article,
+            CrawlerResult::Links(_) => return Self::not_article("only links were found"),
+            CrawlerResult::Skipped(reason, _) => {
+                return Self::not_article(&format!("skipped as {}", reason))
+            }
+        };
+        let found = [
+            article
+                .get_title()
+                .is_some_and(|title| !title.trim().is_empty()),
+            article.get_published_date().is_some(),
+            !article.get_paragraphs().is_empty(),
+        ];
+        HealthReport {
+            not_article: None,
+            missing: FIELDS
+                .into_iter()
+                .zip(found)
+                .filter(|(_, found)| !found)
+                .map(|(field, _)| field)
+                .collect(),
+            paragraphs: article.get_paragraphs().len(),
+        }
+    }
+
+    fn not_article(reason: &str) -> Self {
+        HealthReport {
+            not_article: Some(reason.to_string()),
+            missing: FIELDS.to_vec(),
+            paragraphs: 0,
+        }
+    }
+
+    /// Whether an article came out with a title and paragraphs. A missing published date
+    /// alone is only worth a look.
+    pub fn is_healthy(&self) -> bool {
+        self.not_article.is_none() && !self.missing.contains(&"title") && self.paragraphs > 0
+    }
+}
+
+impl fmt::Display for HealthReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(reason) = &self.not_article {
+            return write!(f, "not an article, {}", reason);
+        }
+        write!(f, "{} paragraphs", self.paragraphs)?;
+        if !self.missing.is_empty() {
+            write!(f, ", missing {}", self.missing.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// An article fetched before crawling, whose `HealthReport` tells whether the crawler still
+/// extracts the articles of the site.
+#[derive(Debug, Clone)]
+pub struct Canary {
+    /// A recent article the crawler is known to extract
+    pub url: String,
+    /// Fail the crawl when the canary is unhealthy or can't be fetched, instead of only
+    /// logging it
+    pub abort: bool,
+}
+
+impl Canary {
+    /// A canary aborting the crawl when unhealthy.
+    pub fn new(url: impl Into<String>) -> Self {
+        Canary {
+            url: url.into(),
+            abort: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Links;
+
+    struct Page {
+        title: Option<String>,
+        paragraphs: Vec<String>,
+    }
+
+    impl Article for Page {
+        fn get_paragraphs(&self) -> &[String] {
+            &self.paragraphs
+        }
+
+        fn get_title(&self) -> Option<&str> {
+            self.title.as_deref()
+        }
+    }
+
+    fn report(title: Option<&str>, paragraphs: &[&str]) -> HealthReport {
+        let page = Page {
+            title: title.map(ToString::to_string),
+            paragraphs: paragraphs.iter().map(ToString::to_string).collect(),
+        };
+        HealthReport::of(&CrawlerResult::DocumentAndLinks(page, Links::default()))
+    }
+
+    #[test]
+    fn missing_fields() {
+        let healthy = report(Some("Judul"), &["Isi."]);
+        assert_eq!(healthy.missing, vec!["published_date"]);
+        assert!(healthy.is_healthy());
+        assert_eq!(healthy.to_string(), "1 paragraphs, missing published_date");
+
+        let broken = report(Some(" "), &[]);
+        assert_eq!(broken.missing, FIELDS.to_vec());
+        assert!(!broken.is_healthy());
+        assert!(!report(Some("Judul"), &[]).is_healthy());
+    }
+
+    #[test]
+    fn not_articles() {
+        let links = HealthReport::of(&CrawlerResult::<Page>::Links(vec![]));
+        assert_eq!(links.missing, FIELDS.to_vec());
+        assert!(!links.is_healthy());
+        assert_eq!(links.to_string(), "not an article, only links were found");
+
+        let skipped = HealthReport::of(&CrawlerResult::<Page>::Skipped(
+            "PAYWALL".to_string(),
+            vec![],
+        ));
+        assert_eq!(skipped.not_article.as_deref(), Some("skipped as PAYWALL"));
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{
+    generic::GenericArticle, json_ld, text, utils, CrawlContext, Crawler, CrawlerResult,
+    ExtractedLink,
+};
+use chrono::DateTime;
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use scraper::{ElementRef, Html, Selector};
+
+const E: &str = "Invalid selector";
+lazy_static! {
+    static ref OG_TYPE: Selector = Selector::parse(r#"meta[property="og:type"]"#).expect(E);
+    static ref TITLE: Selector = Selector::parse(r#"meta[property="og:title"]"#).expect(E);
+    static ref DESCRIPTION: Selector =
+        Selector::parse(r#"meta[property="og:description"]"#).expect(E);
+    static ref THUMBNAIL: Selector = Selector::parse(r#"meta[property="og:image"]"#).expect(E);
+    static ref CANONICAL: Selector = Selector::parse(r#"link[rel="canonical"]"#).expect(E);
+    static ref PUBLISHED_TIME: Selector =
+        Selector::parse(r#"meta[property="article:published_time"]"#).expect(E);
+    static ref AUTHOR: Selector = Selector::parse(r#"meta[name="author"]"#).expect(E);
+    static ref KEYWORDS: Selector = Selector::parse(r#"meta[name="keywords"]"#).expect(E);
+    static ref TAG: Selector = Selector::parse(".tag-list a, .content-tag a").expect(E);
+    static ref BODY: Selector = Selector::parse("#article-content").expect(E);
+    static ref BLOCK: Selector = Selector::parse("p, h2, h3").expect(E);
+    static ref IMG: Selector = Selector::parse("img").expect(E);
+}
+
+const SKIPPED_PREFIXES: [&str; 2] = ["Baca Juga", "Baca juga"];
+
+/// Containers of the "Baca Juga" cards and the quiz and poll widgets embedded in the body.
+const SKIPPED_CLASSES: [&str; 3] = ["baca-juga", "quiz-widget", "polling-widget"];
+
+#[derive(Debug, Clone)]
+pub struct IdnTimesCrawler {
+    allowlist: utils::HostAllowlist,
+}
+
+impl Default for IdnTimesCrawler {
+    fn default() -> Self {
+        IdnTimesCrawler {
+            allowlist: utils::HostAllowlist::new(["idntimes.com"]),
+        }
+    }
+}
+
+impl IdnTimesCrawler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    utils::allowlist_builders!("idntimes.com");
+}
+
+/// The section of `idntimes.com/<category>/<subcategory>/<author>/<slug>`.
+fn category(url: &reqwest::Url) -> Option<String> {
+    url.path_segments()?
+        .next()
+        .filter(|s| !s.is_empty())
+        .map(ToString::to_string)
+}
+
+fn is_heading(el: &ElementRef) -> bool {
+    matches!(el.value().name(), "h2" | "h3")
+}
+
+/// Listicle headings become "## 1. Heading", the number and title often being in separate
+/// spans.
+fn heading(el: &ElementRef) -> Option<String> {
+    let s = el.text().collect::<String>().split_whitespace().join(" ");
+    Some(s)
+        .filter(|s| !s.is_empty())
+        .map(|s| format!("## {}", s))
+}
+
+impl Crawler for IdnTimesCrawler {
+    type Document = GenericArticle;
+
+    fn can_be_scrapped(&self, doc: &Html) -> bool {
+        let is_article = json_ld::article(doc).is_some()
+            || utils::meta_content(doc, &OG_TYPE).is_some_and(|t| t == "article");
+        is_article && doc.select(&BODY).next().is_some()
+    }
+
+    fn extract_links(&self, ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        utils::links(doc, ctx.url, |url| self.allowlist.allows(url))
+    }
+
+    fn crawl(&self, ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        let links = self.extract_urls(ctx, doc);
+
+        if !self.can_be_scrapped(doc) {
+            return CrawlerResult::Links(links);
+        }
+
+        let ld = json_ld::article(doc);
+        let ld_string = |key: &str| ld.as_ref().and_then(|ld| json_ld::string(ld, key));
+
+        let published_date = ld_string("datePublished")
+            .or_else(|| utils::meta_content(doc, &PUBLISHED_TIME))
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok());
+
+        let canonical = doc
+            .select(&CANONICAL)
+            .next()
+            .and_then(|el| el.value().attr("href"))
+            .and_then(|s| reqwest::Url::parse(s.trim()).ok());
+
+        let authors = ld
+            .as_ref()
+            .map(|ld| json_ld::names(ld, "author"))
+            .filter(|names| !names.is_empty())
+            .or_else(|| utils::meta_content(doc, &AUTHOR).map(|s| vec![s]))
+            .unwrap_or_default();
+
+        let keywords = utils::meta_content(doc, &KEYWORDS)
+            .map(|s| {
+                s.split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let tags = doc
+            .select(&TAG)
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        let mut paragraphs = vec![];
+        let mut images = vec![];
+        if let Some(body) = doc.select(&BODY).next() {
+            images = body
+                .select(&IMG)
+                .filter_map(|img| img.value().attr("src"))
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(ToString::to_string)
+                .dedup()
+                .collect();
+            for el in body.select(&BLOCK).filter(|el| {
+                !SKIPPED_CLASSES
+                    .iter()
+                    .any(|class| utils::has_ancestor_class(el, class))
+            }) {
+                let p = if is_heading(&el) {
+                    heading(&el)
+                } else {
+                    text::clean_paragraph(&el.inner_html())
+                };
+                if let Some(p) = p {
+                    if !text::starts_with_any(&p, &SKIPPED_PREFIXES) {
+                        paragraphs.push(p);
+                    }
+                }
+            }
+        }
+        paragraphs.dedup();
+
+        let article = GenericArticle {
+            title: utils::meta_content(doc, &TITLE).or_else(|| ld_string("headline")),
+            authors,
+            published_date,
+            description: utils::meta_content(doc, &DESCRIPTION)
+                .or_else(|| ld_string("description")),
+            thumbnail_url: utils::meta_content(doc, &THUMBNAIL),
+            keywords,
+            tags,
+            category: category(canonical.as_ref().unwrap_or(ctx.url)),
+            paragraphs,
+            images,
+            canonical_url: canonical.map(String::from),
+        };
+        CrawlerResult::document_and_links(article, links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headings() {
+        let doc = Html::parse_fragment(
+            "<h2><span class=\"number\">1.</span>\n <span>Pantai Kuta</span></h2><h3> </h3>",
+        );
+        let headings: Vec<_> = doc
+            .select(&Selector::parse("h2, h3").unwrap())
+            .map(|el| heading(&el))
+            .collect();
+        assert_eq!(headings, vec![Some("## 1. Pantai Kuta".to_string()), None]);
+    }
+
+    #[test]
+    fn categories() {
+        let category = |s: &str| category(&reqwest::Url::parse(s).unwrap());
+        assert_eq!(
+            category("https://www.idntimes.com/travel/destination/ayu/pantai-di-bali"),
+            Some("travel".to_string())
+        );
+        assert_eq!(category("https://www.idntimes.com/"), None);
+    }
+}
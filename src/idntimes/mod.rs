@@ -0,0 +1,5 @@
+//! IDN Times articles are stored as `GenericArticle`s in a `SiteStorage`.
+
+mod crawler;
+
+pub use crawler::IdnTimesCrawler;
@@ -0,0 +1,246 @@
+use crate::{
+    jawapos::JawaPosArticle, json_ld, text, utils, CrawlContext, Crawler, CrawlerResult,
+    ExtractedLink,
+};
+use chrono::DateTime;
+use itertools::Itertools;
+use lazy_regex::{regex_captures, regex_is_match};
+use lazy_static::lazy_static;
+use scraper::{Html, Selector};
+
+const E: &str = "Invalid selector";
+lazy_static! {
+    static ref OG_TYPE: Selector = Selector::parse(r#"meta[property="og:type"]"#).expect(E);
+    static ref TITLE: Selector = Selector::parse(r#"meta[property="og:title"]"#).expect(E);
+    static ref DESCRIPTION: Selector =
+        Selector::parse(r#"meta[property="og:description"]"#).expect(E);
+    static ref THUMBNAIL: Selector = Selector::parse(r#"meta[property="og:image"]"#).expect(E);
+    static ref CANONICAL: Selector = Selector::parse(r#"link[rel="canonical"]"#).expect(E);
+    static ref PUBLISHED_TIME: Selector =
+        Selector::parse(r#"meta[property="article:published_time"]"#).expect(E);
+    static ref CREDIT: Selector = Selector::parse(".read__info__author > *").expect(E);
+    static ref TAG: Selector = Selector::parse(".tag__list a").expect(E);
+    static ref BODY: Selector = Selector::parse(".read__content").expect(E);
+    static ref P: Selector = Selector::parse("p").expect(E);
+}
+
+const SKIPPED_PREFIXES: [&str; 2] = ["Baca Juga", "Baca juga"];
+
+/// Sites of the Radar regional network, served with the national template.
+pub const RADAR_HOSTS: [&str; 6] = [
+    "radarsurabaya.jawapos.com",
+    "radarmalang.jawapos.com",
+    "radarsemarang.jawapos.com",
+    "radarbogor.id",
+    "radarbanyumas.co.id",
+    "radarcirebon.com",
+];
+
+#[derive(Debug, Clone)]
+pub struct JawaPosCrawler {
+    allowlist: utils::HostAllowlist,
+    radar_hosts: Vec<String>,
+}
+
+impl Default for JawaPosCrawler {
+    fn default() -> Self {
+        JawaPosCrawler {
+            allowlist: utils::HostAllowlist::new(["jawapos.com"]),
+            radar_hosts: vec![],
+        }
+    }
+}
+
+impl JawaPosCrawler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    utils::allowlist_builders!(
+        "jawapos.com",
+        "Radar subdomains are not followed unless given to `with_radar_hosts`."
+    );
+
+    /// Also follow links to these Radar regional sites, e.g. `RADAR_HOSTS`. None by default.
+    pub fn with_radar_hosts<I, H>(mut self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = H>,
+        H: Into<String>,
+    {
+        self.radar_hosts = hosts.into_iter().map(Into::into).collect();
+        self
+    }
+
+    fn is_allowed(&self, url: &reqwest::Url) -> bool {
+        if utils::is_url_allowed(url, &self.radar_hosts, self.allowlist.allows_http()) {
+            return true;
+        }
+        let is_radar = url.host_str().is_some_and(|host| host.starts_with("radar"));
+        !is_radar && self.allowlist.allows(url)
+    }
+}
+
+/// The section of `jawapos.com/<category>/14/12/2022/slug`.
+fn category(url: &reqwest::Url) -> Option<String> {
+    url.path_segments()?
+        .next()
+        .filter(|s| !s.is_empty())
+        .map(ToString::to_string)
+}
+
+/// The "---" and "***" separators between sections of a story.
+fn is_separator(paragraph: &str) -> bool {
+    regex_is_match!(r"^(<[^>]+>)*\s*[-–—*]{3,}\s*(</[^>]+>)*$", paragraph)
+}
+
+/// The key and name of a "Reporter: Name" or "Editor: Name" credit.
+fn credit(line: &str) -> Option<(String, String)> {
+    let (_, key, name) =
+        regex_captures!(r"(?i)^\s*(reporter|penulis|editor)\s*:\s*(.*?)\s*$", line)?;
+    Some((key.to_lowercase(), name.split_whitespace().join(" ")))
+        .filter(|(_, name)| !name.is_empty())
+}
+
+impl Crawler for JawaPosCrawler {
+    type Document = JawaPosArticle;
+
+    fn can_be_scrapped(&self, doc: &Html) -> bool {
+        let is_article = json_ld::article(doc).is_some()
+            || utils::meta_content(doc, &OG_TYPE).is_some_and(|t| t == "article");
+        is_article && doc.select(&BODY).next().is_some()
+    }
+
+    fn extract_links(&self, ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        utils::links(doc, ctx.url, |url| self.is_allowed(url))
+    }
+
+    fn crawl(&self, ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        let links = self.extract_urls(ctx, doc);
+
+        if !self.can_be_scrapped(doc) {
+            return CrawlerResult::Links(links);
+        }
+
+        let ld = json_ld::article(doc);
+        let ld_string = |key: &str| ld.as_ref().and_then(|ld| json_ld::string(ld, key));
+
+        let published_date = ld_string("datePublished")
+            .or_else(|| utils::meta_content(doc, &PUBLISHED_TIME))
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok());
+
+        let canonical = doc
+            .select(&CANONICAL)
+            .next()
+            .and_then(|el| el.value().attr("href"))
+            .and_then(|s| reqwest::Url::parse(s.trim()).ok());
+
+        let mut author = None;
+        let mut editor = None;
+        for (key, name) in doc
+            .select(&CREDIT)
+            .filter_map(|el| credit(&el.text().collect::<String>()))
+        {
+            match key.as_str() {
+                "editor" => editor = editor.or(Some(name)),
+                _ => author = author.or(Some(name)),
+            }
+        }
+        let author = author.or_else(|| {
+            ld.as_ref()
+                .map(|ld| json_ld::names(ld, "author"))
+                .filter(|names| !names.is_empty())
+                .map(|names| names.join(", "))
+        });
+
+        let tags = doc
+            .select(&TAG)
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        let mut paragraphs = vec![];
+        if let Some(body) = doc.select(&BODY).next() {
+            for p in body
+                .select(&P)
+                .filter(|p| !utils::has_ancestor_class(p, "baca-juga"))
+            {
+                if let Some(p) = text::clean_paragraph(&p.inner_html()) {
+                    if !text::starts_with_any(&p, &SKIPPED_PREFIXES) && !is_separator(&p) {
+                        paragraphs.push(p);
+                    }
+                }
+            }
+        }
+        paragraphs.dedup();
+
+        let article = JawaPosArticle {
+            title: utils::meta_content(doc, &TITLE).or_else(|| ld_string("headline")),
+            published_date,
+            description: utils::meta_content(doc, &DESCRIPTION)
+                .or_else(|| ld_string("description")),
+            thumbnail_url: utils::meta_content(doc, &THUMBNAIL),
+            author,
+            editor,
+            category: category(canonical.as_ref().unwrap_or(ctx.url)),
+            tags,
+            paragraphs,
+        };
+        CrawlerResult::document_and_links(article, links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separators() {
+        assert!(is_separator("---"));
+        assert!(is_separator("<p>***</p>"));
+        assert!(is_separator("———"));
+        assert!(!is_separator("Skor akhir 3-1 untuk Persebaya."));
+    }
+
+    #[test]
+    fn credits() {
+        assert_eq!(
+            credit("Reporter : Dimas  Ryandi"),
+            Some(("reporter".to_string(), "Dimas Ryandi".to_string()))
+        );
+        assert_eq!(
+            credit("EDITOR: Ilham Safutra"),
+            Some(("editor".to_string(), "Ilham Safutra".to_string()))
+        );
+        assert_eq!(credit("Editor:"), None);
+        assert_eq!(credit("Foto: Dok. JawaPos"), None);
+    }
+
+    #[test]
+    fn radar_hosts_are_opt_in() {
+        let allowed = |crawler: &JawaPosCrawler, s: &str| {
+            crawler.is_allowed(&reqwest::Url::parse(s).unwrap())
+        };
+        let national = JawaPosCrawler::new();
+        assert!(allowed(
+            &national,
+            "https://www.jawapos.com/nasional/1/judul"
+        ));
+        assert!(!allowed(
+            &national,
+            "https://radarsurabaya.jawapos.com/kriminal/1/judul"
+        ));
+        assert!(!allowed(
+            &national,
+            "https://radarbogor.id/2022/12/14/judul"
+        ));
+
+        let radar = JawaPosCrawler::new().with_radar_hosts(RADAR_HOSTS);
+        assert!(allowed(&radar, "https://www.jawapos.com/nasional/1/judul"));
+        assert!(allowed(
+            &radar,
+            "https://radarsurabaya.jawapos.com/kriminal/1/judul"
+        ));
+        assert!(allowed(&radar, "https://radarbogor.id/2022/12/14/judul"));
+        assert!(!allowed(&radar, "https://radarkudus.jawapos.com/1/judul"));
+    }
+}
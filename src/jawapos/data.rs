@@ -0,0 +1,80 @@
+use super::JawaPosArticle;
+use crate::generic::{SiteArticle, SiteArticleTable};
+use crate::SqliteStorage;
+use sqlx::query::Query;
+use sqlx::sqlite::SqliteArguments;
+use sqlx::Sqlite;
+
+impl SiteArticle for JawaPosArticle {
+    const COLUMNS: &'static [(&'static str, &'static str)] = &[
+        ("author", "TEXT"),
+        ("editor", "TEXT"),
+        ("category", "TEXT"),
+        ("description", "TEXT"),
+        ("thumbnail_url", "TEXT"),
+        ("tags", "TEXT"),
+    ];
+
+    fn bind<'q>(
+        self,
+        query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    ) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+        query
+            .bind(self.author)
+            .bind(self.editor)
+            .bind(self.category)
+            .bind(self.description)
+            .bind(self.thumbnail_url)
+            .bind(self.tags.join("|"))
+    }
+}
+
+pub type JawaPosArticleTable = SiteArticleTable<JawaPosArticle>;
+
+pub type JawaPosData = SqliteStorage<JawaPosArticleTable>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils, Storage, Table};
+    use std::path::Path;
+    use tokio::fs;
+
+    #[tokio::test]
+    async fn columns_mapped() {
+        if Path::new("test_jawapos.db").is_file() {
+            fs::remove_file("test_jawapos.db").await.unwrap();
+        }
+        let p = JawaPosData::new("test_jawapos").await.unwrap();
+
+        let article = JawaPosArticle {
+            title: Some("title".to_string()),
+            published_date: Some(utils::get_now()),
+            description: None,
+            thumbnail_url: None,
+            author: Some("author".to_string()),
+            editor: None,
+            category: Some("nasional".to_string()),
+            tags: vec!["t1".to_string()],
+            paragraphs: vec!["p1".to_string(), "p2".to_string()],
+        };
+        p.results_insert(("https://www.jawapos.com/nasional/14/12/2022/judul", article))
+            .await
+            .unwrap();
+        let row: (String, String, String) =
+            sqlx::query_as("SELECT author, category, tags FROM test_jawapos_results")
+                .fetch_one(p.results.get_pool())
+                .await
+                .unwrap();
+        assert_eq!(
+            row,
+            (
+                "author".to_string(),
+                "nasional".to_string(),
+                "t1".to_string(),
+            )
+        );
+
+        fs::remove_file("test_jawapos.db").await.unwrap();
+    }
+}
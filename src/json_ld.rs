@@ -0,0 +1,117 @@
+use lazy_static::lazy_static;
+use scraper::{Html, Selector};
+use serde_json::Value;
+
+lazy_static! {
+    static ref LD_JSON: Selector =
+        Selector::parse(r#"script[type="application/ld+json"]"#).expect("Invalid selector");
+}
+
+const ARTICLE_TYPES: [&str; 4] = [
+    "NewsArticle",
+    "Article",
+    "ReportageNewsArticle",
+    "BlogPosting",
+];
+
+fn flatten(value: Value, out: &mut Vec<Value>) {
+    match value {
+        Value::Array(items) => items.into_iter().for_each(|v| flatten(v, out)),
+        Value::Object(mut object) => match object.remove("@graph") {
+            Some(graph) => flatten(graph, out),
+            None => out.push(Value::Object(object)),
+        },
+        _ => {}
+    }
+}
+
+/// Every JSON-LD object of the page, with arrays and `@graph` lists unwrapped. Blocks that
+/// fail to parse are ignored, sites often leave raw newlines in them.
+pub(crate) fn objects(doc: &Html) -> Vec<Value> {
+    let mut out = vec![];
+    for script in doc.select(&LD_JSON) {
+        let raw = script.text().collect::<String>();
+        if let Ok(value) = serde_json::from_str::<Value>(raw.trim()) {
+            flatten(value, &mut out);
+        }
+    }
+    out
+}
+
+fn is_article(object: &Value) -> bool {
+    match object.get("@type") {
+        Some(Value::String(t)) => ARTICLE_TYPES.contains(&t.as_str()),
+        Some(Value::Array(types)) => types
+            .iter()
+            .any(|t| t.as_str().is_some_and(|t| ARTICLE_TYPES.contains(&t))),
+        _ => false,
+    }
+}
+
+/// The first article object of the page.
+pub(crate) fn article(doc: &Html) -> Option<Value> {
+    objects(doc).into_iter().find(is_article)
+}
+
+/// A trimmed, non-empty string field.
+pub(crate) fn string(object: &Value, key: &str) -> Option<String> {
+    object
+        .get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(ToString::to_string)
+}
+
+/// Names of a field holding a string, a `{"name": ..}` object or a list of either, as used
+/// for `author` and `image`-like fields.
+pub(crate) fn names(object: &Value, key: &str) -> Vec<String> {
+    let name = |v: &Value| match v {
+        Value::String(s) => Some(s.trim().to_string()),
+        Value::Object(_) => string(v, "name").or_else(|| string(v, "url")),
+        _ => None,
+    };
+    match object.get(key) {
+        Some(Value::Array(items)) => items.iter().filter_map(name).collect(),
+        Some(value) => name(value).into_iter().collect(),
+        None => vec![],
+    }
+    .into_iter()
+    .filter(|s| !s.is_empty())
+    .collect()
+}
+
+/// Whether the article declares itself paywalled through `isAccessibleForFree`.
+pub(crate) fn is_paywalled(object: &Value) -> bool {
+    match object.get("isAccessibleForFree") {
+        Some(Value::Bool(free)) => !free,
+        Some(Value::String(free)) => free.eq_ignore_ascii_case("false"),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_article_in_graph() {
+        let doc = Html::parse_document(
+            r#"<html><head>
+            <script type="application/ld+json">{"@type": "WebSite", "name": "Site"}</script>
+            <script type="application/ld+json">not json</script>
+            <script type="application/ld+json">{"@context": "https://schema.org", "@graph": [
+                {"@type": "BreadcrumbList"},
+                {"@type": ["NewsArticle"], "headline": " Judul ",
+                 "author": [{"@type": "Person", "name": "A"}, "B"],
+                 "isAccessibleForFree": "False"}
+            ]}</script>
+            </head></html>"#,
+        );
+        assert_eq!(objects(&doc).len(), 3);
+        let article = article(&doc).unwrap();
+        assert_eq!(string(&article, "headline").as_deref(), Some("Judul"));
+        assert_eq!(names(&article, "author"), vec!["A", "B"]);
+        assert!(is_paywalled(&article));
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{
+    kompas::KompasArticle, text::ParagraphCleaner, utils, CrawlContext, Crawler, CrawlerResult,
+    ExtractedLink,
+};
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone};
+use itertools::Itertools;
+use lazy_regex::regex_captures;
+use lazy_static::lazy_static;
+use scraper::{Html, Selector};
+
+const E: &str = "Invalid selector";
+lazy_static! {
+    static ref OG_TYPE: Selector = Selector::parse(r#"meta[property="og:type"]"#).expect(E);
+    static ref TITLE: Selector = Selector::parse(r#"meta[property="og:title"]"#).expect(E);
+    static ref READ_TITLE: Selector = Selector::parse("h1.read__title").expect(E);
+    static ref DESCRIPTION: Selector =
+        Selector::parse(r#"meta[property="og:description"]"#).expect(E);
+    static ref PUBLISHED_TIME: Selector =
+        Selector::parse(r#"meta[property="article:published_time"]"#).expect(E);
+    static ref PUBLISHED_DATE: Selector =
+        Selector::parse(r#"meta[name="content_PublishedDate"]"#).expect(E);
+    static ref READ_TIME: Selector = Selector::parse(".read__time").expect(E);
+    static ref THUMBNAIL: Selector = Selector::parse(r#"meta[property="og:image"]"#).expect(E);
+    static ref CREDIT_ITEM: Selector = Selector::parse(".read__credit__item").expect(E);
+    static ref CREDIT: Selector = Selector::parse(".read__credit").expect(E);
+    static ref AUTHOR: Selector = Selector::parse(r#"meta[name="content_author"]"#).expect(E);
+    static ref EDITOR: Selector = Selector::parse(r#"meta[name="content_editor"]"#).expect(E);
+    static ref KEYWORDS: Selector = Selector::parse(r#"meta[name="keywords"]"#).expect(E);
+    static ref BODY: Selector = Selector::parse(".read__content").expect(E);
+    static ref SHOW_ALL: Selector = Selector::parse(r#"a[href*="page=all"]"#).expect(E);
+    static ref P: Selector = Selector::parse("p").expect(E);
+    static ref CLEANER: ParagraphCleaner =
+        ParagraphCleaner::new().with_skipped_prefixes(SKIPPED_PREFIXES);
+}
+
+/// Paragraphs that are links to other articles or promotion rather than article text.
+const SKIPPED_PREFIXES: [&str; 4] = [
+    "Baca juga",
+    "Baca Juga",
+    "Dapatkan update",
+    "Simak breaking news",
+];
+
+#[derive(Debug, Clone)]
+pub struct KompasCrawler {
+    allowlist: utils::HostAllowlist,
+    excluded_hosts: Vec<String>,
+}
+
+impl Default for KompasCrawler {
+    fn default() -> Self {
+        KompasCrawler {
+            allowlist: utils::HostAllowlist::new(["kompas.com"]),
+            excluded_hosts: vec![
+                "klasika.kompas.com".to_string(),
+                "iklan.kompas.com".to_string(),
+            ],
+        }
+    }
+}
+
+impl KompasCrawler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    utils::allowlist_builders!("kompas.com");
+
+    /// Never follow links to these hosts or their subdomains, the classifieds by default.
+    pub fn with_excluded_hosts<I, H>(mut self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = H>,
+        H: Into<String>,
+    {
+        self.excluded_hosts = hosts.into_iter().map(Into::into).collect();
+        self
+    }
+
+    fn is_allowed(&self, url: &reqwest::Url) -> bool {
+        self.allowlist.allows(url)
+            && url.host_str().is_some_and(|host| {
+                !self
+                    .excluded_hosts
+                    .iter()
+                    .any(|excluded| utils::host_matches(host, excluded))
+            })
+    }
+}
+
+fn wib() -> FixedOffset {
+    FixedOffset::east_opt(7 * 3600).expect("Valid offset")
+}
+
+/// Parse the visible "Kompas.com - 12/12/2022, 10:30 WIB" line.
+fn parse_read_time(s: &str) -> Option<DateTime<FixedOffset>> {
+    let (_, day, month, year, hour, minute, zone) = regex_captures!(
+        r"(\d{1,2})/(\d{1,2})/(\d{4}),\s*(\d{1,2}):(\d{2})\s*(WIB|WITA|WIT)?",
+        s
+    )?;
+    let offset = match zone {
+        "WITA" => FixedOffset::east_opt(8 * 3600)?,
+        "WIT" => FixedOffset::east_opt(9 * 3600)?,
+        _ => wib(),
+    };
+    let naive = NaiveDateTime::parse_from_str(
+        &format!("{}/{}/{} {}:{}", day, month, year, hour, minute),
+        "%d/%m/%Y %H:%M",
+    )
+    .ok()?;
+    offset.from_local_datetime(&naive).single()
+}
+
+fn published_date(doc: &Html) -> Option<DateTime<FixedOffset>> {
+    utils::meta_content(doc, &PUBLISHED_TIME)
+        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+        .or_else(|| {
+            utils::meta_content(doc, &PUBLISHED_DATE).and_then(|s| {
+                NaiveDateTime::parse_from_str(&s, "%Y-%m-%d %H:%M:%S")
+                    .ok()
+                    .and_then(|naive| wib().from_local_datetime(&naive).single())
+            })
+        })
+        .or_else(|| {
+            doc.select(&READ_TIME)
+                .next()
+                .and_then(|el| parse_read_time(&el.text().collect::<String>()))
+        })
+}
+
+/// The "Penulis" and "Editor" names from the credit lines, either one item per role or
+/// a single "Penulis : A | Editor : B" line on older pages.
+fn credits(doc: &Html) -> (Option<String>, Option<String>) {
+    let (mut author, mut editor) = (None, None);
+    let items = match doc.select(&CREDIT_ITEM).next() {
+        Some(_) => doc.select(&CREDIT_ITEM),
+        None => doc.select(&CREDIT),
+    };
+    for el in items {
+        let line = el.text().collect::<Vec<_>>().join(" ");
+        for part in line.split('|') {
+            let part = part.split_whitespace().join(" ");
+            let (role, name) = match part.split_once(' ') {
+                Some((role, name)) => (role, name.trim_start_matches(':').trim()),
+                None => continue,
+            };
+            if name.is_empty() {
+                continue;
+            }
+            match role {
+                "Penulis" if author.is_none() => author = Some(name.to_string()),
+                "Editor" if editor.is_none() => editor = Some(name.to_string()),
+                _ => {}
+            }
+        }
+    }
+    (author, editor)
+}
+
+impl Crawler for KompasCrawler {
+    type Document = KompasArticle;
+
+    fn can_be_scrapped(&self, doc: &Html) -> bool {
+        let is_article = doc
+            .select(&OG_TYPE)
+            .next()
+            .and_then(|el| el.value().attr("content"))
+            .is_none_or(|og_type| og_type == "article");
+        is_article && doc.select(&BODY).next().is_some()
+    }
+
+    fn extract_links(&self, ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        utils::unique_links(
+            utils::anchor_elements(doc, ctx.url, &utils::HTTPS_HOSTS)
+                .filter(|(url, _)| self.is_allowed(url))
+                .map(|(mut url, a)| {
+                    url.set_fragment(None);
+                    utils::extracted_link(url, &a)
+                }),
+        )
+    }
+
+    fn crawl(&self, ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        let links = self.extract_urls(ctx, doc);
+
+        // Long articles are split over ?page=2, ?page=3... Only the "Show All" variant,
+        // which is among the links, holds the whole text
+        if !self.can_be_scrapped(doc) || doc.select(&SHOW_ALL).next().is_some() {
+            return CrawlerResult::Links(links);
+        }
+
+        let title = utils::meta_content(doc, &TITLE).or_else(|| {
+            doc.select(&READ_TITLE)
+                .next()
+                .map(|el| el.text().collect::<String>().trim().to_string())
+        });
+        let (author, editor) = credits(doc);
+
+        let keywords = utils::meta_content(doc, &KEYWORDS)
+            .map(|s| {
+                s.split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let raw = doc
+            .select(&BODY)
+            .flat_map(|el| el.select(&P))
+            .filter(|p| !utils::has_ancestor_class(p, "ads-on-body"))
+            .map(|p| p.inner_html());
+        let paragraphs = CLEANER.paragraphs(raw);
+
+        let article = KompasArticle {
+            title,
+            published_date: published_date(doc),
+            description: utils::meta_content(doc, &DESCRIPTION),
+            thumbnail_url: utils::meta_content(doc, &THUMBNAIL),
+            author: author.or_else(|| utils::meta_content(doc, &AUTHOR)),
+            editor: editor.or_else(|| utils::meta_content(doc, &EDITOR)),
+            keywords,
+            paragraphs,
+        };
+        CrawlerResult::document_and_links(article, links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_time() {
+        assert_eq!(
+            parse_read_time("Kompas.com - 12/12/2022, 10:30 WIB"),
+            DateTime::parse_from_rfc3339("2022-12-12T10:30:00+07:00").ok()
+        );
+        assert_eq!(
+            parse_read_time("Kompas.com - 03/01/2023, 07:05 WITA"),
+            DateTime::parse_from_rfc3339("2023-01-03T07:05:00+08:00").ok()
+        );
+        assert_eq!(parse_read_time("Kompas.com - 32/12/2022, 10:30 WIB"), None);
+        assert_eq!(parse_read_time("Kompas.com"), None);
+    }
+
+    #[test]
+    fn excluded_hosts() {
+        let crawler = KompasCrawler::new();
+        let allowed = |s: &str| crawler.is_allowed(&reqwest::Url::parse(s).unwrap());
+        assert!(allowed("https://nasional.kompas.com/read/2022/12/12/1"));
+        assert!(allowed("https://www.kompas.com"));
+        assert!(!allowed("https://klasika.kompas.com/iklan/1"));
+        assert!(!allowed("https://kompas.id/baca/1"));
+        assert!(!allowed("http://tekno.kompas.com/read/1"));
+    }
+}
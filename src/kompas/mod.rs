@@ -0,0 +1,81 @@
+mod crawler;
+#[cfg(feature = "sqlite")]
+mod data;
+
+pub use crawler::KompasCrawler;
+#[cfg(feature = "sqlite")]
+pub use data::{KompasArticleTable, KompasData};
+
+use crate::Article;
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KompasArticle {
+    pub title: Option<String>,
+    pub published_date: Option<DateTime<FixedOffset>>,
+    pub description: Option<String>,
+    pub thumbnail_url: Option<String>,
+    pub author: Option<String>,
+    pub editor: Option<String>,
+    pub keywords: Vec<String>,
+    pub paragraphs: Vec<String>,
+}
+
+impl fmt::Display for KompasArticle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let none = "None".to_string();
+        writeln!(
+            f,
+            "Title           : {}",
+            self.title.as_ref().unwrap_or(&none)
+        )?;
+        writeln!(
+            f,
+            "Author          : {}",
+            self.author.as_ref().unwrap_or(&none)
+        )?;
+        writeln!(
+            f,
+            "Editor          : {}",
+            self.editor.as_ref().unwrap_or(&none)
+        )?;
+        match self.published_date.as_ref() {
+            Some(d) => writeln!(f, "Published Date  : {}", d)?,
+            None => writeln!(f, "Published Date  : None")?,
+        };
+        writeln!(
+            f,
+            "Description     : {}",
+            self.description.as_ref().unwrap_or(&none)
+        )?;
+        writeln!(
+            f,
+            "Thumbnail       : {}",
+            self.thumbnail_url.as_ref().unwrap_or(&none)
+        )?;
+        writeln!(f, "Keywords        : {}", self.keywords.join(", "))?;
+        writeln!(f, "Paragraphs      : ")?;
+        for p in &self.paragraphs {
+            writeln!(f, "> {}", p.replace('\n', "\n  "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Article for KompasArticle {
+    fn get_paragraphs(&self) -> &[String] {
+        self.paragraphs.as_slice()
+    }
+
+    fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    fn get_published_date(&self) -> Option<DateTime<FixedOffset>> {
+        self.published_date
+    }
+}
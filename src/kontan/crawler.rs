@@ -0,0 +1,195 @@
+use crate::{
+    json_ld, kontan::KontanArticle, text, utils, CrawlContext, Crawler, CrawlerResult,
+    ExtractedLink, PAYWALLED,
+};
+use chrono::DateTime;
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use scraper::{Html, Selector};
+
+const E: &str = "Invalid selector";
+lazy_static! {
+    static ref OG_TYPE: Selector = Selector::parse(r#"meta[property="og:type"]"#).expect(E);
+    static ref TITLE: Selector = Selector::parse(r#"meta[property="og:title"]"#).expect(E);
+    static ref DESCRIPTION: Selector =
+        Selector::parse(r#"meta[property="og:description"]"#).expect(E);
+    static ref THUMBNAIL: Selector = Selector::parse(r#"meta[property="og:image"]"#).expect(E);
+    static ref KEYWORDS: Selector = Selector::parse(r#"meta[name="keywords"]"#).expect(E);
+    static ref DATE: Selector = Selector::parse(".detail-date, time").expect(E);
+    static ref CREDIT: Selector = Selector::parse(".detail-credit").expect(E);
+    static ref TAG: Selector = Selector::parse(".tags-detail a").expect(E);
+    static ref BODY: Selector = Selector::parse(".tmpt-desk-kon").expect(E);
+    static ref PAYWALL: Selector = Selector::parse(".kontan-plus-paywall, .box-premium").expect(E);
+    static ref ACTIVE_PAGE: Selector = Selector::parse(".pagination .active").expect(E);
+    static ref P: Selector = Selector::parse("p").expect(E);
+}
+
+const SKIPPED_PREFIXES: [&str; 5] = [
+    "Baca Juga",
+    "Baca juga",
+    "Cek Berita dan Artikel",
+    "Dukung jurnalisme",
+    "Berlangganan",
+];
+
+#[derive(Debug, Clone)]
+pub struct KontanCrawler {
+    allowlist: utils::HostAllowlist,
+}
+
+impl Default for KontanCrawler {
+    fn default() -> Self {
+        KontanCrawler {
+            allowlist: utils::HostAllowlist::new(["kontan.co.id"]),
+        }
+    }
+}
+
+impl KontanCrawler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    utils::allowlist_builders!("kontan.co.id");
+}
+
+/// Reporter and editor of a "Reporter: Name | Editor: Name" line.
+fn credits(line: &str) -> (Option<String>, Option<String>) {
+    let mut reporter = None;
+    let mut editor = None;
+    for part in line.split('|') {
+        if let Some((key, name)) = part.split_once(':') {
+            let name = Some(name.split_whitespace().join(" ")).filter(|s| !s.is_empty());
+            match key.trim().to_lowercase().as_str() {
+                "reporter" => reporter = name,
+                "editor" => editor = name,
+                _ => {}
+            }
+        }
+    }
+    (reporter, editor)
+}
+
+impl Crawler for KontanCrawler {
+    type Document = KontanArticle;
+
+    fn can_be_scrapped(&self, doc: &Html) -> bool {
+        let is_article = json_ld::article(doc).is_some()
+            || utils::meta_content(doc, &OG_TYPE).is_some_and(|t| t == "article");
+        is_article && doc.select(&BODY).next().is_some()
+    }
+
+    fn extract_links(&self, ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        utils::links(doc, ctx.url, |url| self.allowlist.allows(url))
+    }
+
+    fn crawl(&self, ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        // Pagination links are among the links, so every page of an article is crawled
+        let links = self.extract_urls(ctx, doc);
+
+        if !self.can_be_scrapped(doc) {
+            return CrawlerResult::Links(links);
+        }
+
+        let ld = json_ld::article(doc);
+        if ld.as_ref().is_some_and(json_ld::is_paywalled) || doc.select(&PAYWALL).next().is_some() {
+            return CrawlerResult::Skipped(PAYWALLED.to_string(), links);
+        }
+        let ld_string = |key: &str| ld.as_ref().and_then(|ld| json_ld::string(ld, key));
+
+        let published_date = ld_string("datePublished")
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .or_else(|| {
+                doc.select(&DATE)
+                    .find_map(|el| utils::parse_indonesian_datetime(&el.text().collect::<String>()))
+            });
+
+        let (reporter, editor) = doc
+            .select(&CREDIT)
+            .next()
+            .map(|el| credits(&el.text().collect::<String>()))
+            .unwrap_or_default();
+
+        let mut tags: Vec<String> = doc
+            .select(&TAG)
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if tags.is_empty() {
+            tags = utils::meta_content(doc, &KEYWORDS)
+                .map(|s| {
+                    s.split(',')
+                        .map(|s| s.trim().to_string())
+                        .filter(|s| !s.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default();
+        }
+
+        let page = doc
+            .select(&ACTIVE_PAGE)
+            .next()
+            .and_then(|el| el.text().collect::<String>().trim().parse().ok())
+            .unwrap_or(1);
+
+        let mut paragraphs = vec![];
+        if let Some(body) = doc.select(&BODY).next() {
+            for p in body
+                .select(&P)
+                .filter(|p| !utils::has_ancestor_class(p, "kontan-subscribe"))
+            {
+                if let Some(p) = text::clean_paragraph(&p.inner_html()) {
+                    if !text::starts_with_any(&p, &SKIPPED_PREFIXES) {
+                        paragraphs.push(p);
+                    }
+                }
+            }
+        }
+        paragraphs.dedup();
+
+        let article = KontanArticle {
+            title: utils::meta_content(doc, &TITLE).or_else(|| ld_string("headline")),
+            published_date,
+            description: utils::meta_content(doc, &DESCRIPTION)
+                .or_else(|| ld_string("description")),
+            thumbnail_url: utils::meta_content(doc, &THUMBNAIL),
+            reporter,
+            editor,
+            tags,
+            page,
+            paragraphs,
+        };
+        CrawlerResult::document_and_links(article, links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credit_line() {
+        assert_eq!(
+            credits(" Reporter: Dendi Siswanto | Editor:  Handoyo "),
+            (
+                Some("Dendi Siswanto".to_string()),
+                Some("Handoyo".to_string())
+            )
+        );
+        assert_eq!(credits("Penulis: Anna Suci"), (None, None));
+    }
+
+    #[test]
+    fn paywall_declared_in_json_ld() {
+        let doc = Html::parse_document(
+            r#"<script type="application/ld+json">
+            {"@type": "NewsArticle", "headline": "Judul", "isAccessibleForFree": false}
+            </script><div class="tmpt-desk-kon"><p>Paragraf pembuka.</p></div>"#,
+        );
+        let url = reqwest::Url::parse("https://nasional.kontan.co.id/news/judul").unwrap();
+        assert!(matches!(
+            KontanCrawler::new().crawl(&CrawlContext::new(&url), &doc),
+            CrawlerResult::Skipped(reason, _) if reason == PAYWALLED
+        ));
+    }
+}
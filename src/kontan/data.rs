@@ -0,0 +1,81 @@
+use super::KontanArticle;
+use crate::generic::{SiteArticle, SiteArticleTable};
+use crate::SqliteStorage;
+use sqlx::query::Query;
+use sqlx::sqlite::SqliteArguments;
+use sqlx::Sqlite;
+
+impl SiteArticle for KontanArticle {
+    const COLUMNS: &'static [(&'static str, &'static str)] = &[
+        ("reporter", "TEXT"),
+        ("editor", "TEXT"),
+        ("description", "TEXT"),
+        ("thumbnail_url", "TEXT"),
+        ("tags", "TEXT"),
+        ("page", "INTEGER"),
+    ];
+
+    fn bind<'q>(
+        self,
+        query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    ) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+        query
+            .bind(self.reporter)
+            .bind(self.editor)
+            .bind(self.description)
+            .bind(self.thumbnail_url)
+            .bind(self.tags.join("|"))
+            .bind(self.page)
+    }
+}
+
+pub type KontanArticleTable = SiteArticleTable<KontanArticle>;
+
+pub type KontanData = SqliteStorage<KontanArticleTable>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils, Storage, Table};
+    use std::path::Path;
+    use tokio::fs;
+
+    #[tokio::test]
+    async fn columns_mapped() {
+        if Path::new("test_kontan.db").is_file() {
+            fs::remove_file("test_kontan.db").await.unwrap();
+        }
+        let p = KontanData::new("test_kontan").await.unwrap();
+
+        let article = KontanArticle {
+            title: Some("title".to_string()),
+            published_date: Some(utils::get_now()),
+            description: None,
+            thumbnail_url: None,
+            reporter: Some("reporter".to_string()),
+            editor: Some("editor".to_string()),
+            tags: vec!["t1".to_string()],
+            page: 1,
+            paragraphs: vec!["p1".to_string(), "p2".to_string()],
+        };
+        p.results_insert(("https://nasional.kontan.co.id/news/judul-1", article))
+            .await
+            .unwrap();
+        let row: (String, String, String, u32) =
+            sqlx::query_as("SELECT reporter, editor, tags, page FROM test_kontan_results")
+                .fetch_one(p.results.get_pool())
+                .await
+                .unwrap();
+        assert_eq!(
+            row,
+            (
+                "reporter".to_string(),
+                "editor".to_string(),
+                "t1".to_string(),
+                1,
+            )
+        );
+
+        fs::remove_file("test_kontan.db").await.unwrap();
+    }
+}
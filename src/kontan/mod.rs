@@ -0,0 +1,40 @@
+mod crawler;
+#[cfg(feature = "sqlite")]
+mod data;
+
+pub use crawler::KontanCrawler;
+#[cfg(feature = "sqlite")]
+pub use data::{KontanArticleTable, KontanData};
+
+use crate::Article;
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KontanArticle {
+    pub title: Option<String>,
+    pub published_date: Option<DateTime<FixedOffset>>,
+    pub description: Option<String>,
+    pub thumbnail_url: Option<String>,
+    pub reporter: Option<String>,
+    pub editor: Option<String>,
+    pub tags: Vec<String>,
+    /// Page of a multi-page article, each page is stored as its own result
+    pub page: u32,
+    pub paragraphs: Vec<String>,
+}
+
+impl Article for KontanArticle {
+    fn get_paragraphs(&self) -> &[String] {
+        self.paragraphs.as_slice()
+    }
+
+    fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    fn get_published_date(&self) -> Option<DateTime<FixedOffset>> {
+        self.published_date
+    }
+}
@@ -0,0 +1,109 @@
+use super::payload;
+use crate::{
+    json_ld, kumparan::KumparanArticle, utils, CrawlContext, Crawler, CrawlerResult, ExtractedLink,
+};
+use chrono::DateTime;
+use lazy_static::lazy_static;
+use scraper::{Html, Selector};
+
+const E: &str = "Invalid selector";
+lazy_static! {
+    static ref TITLE: Selector = Selector::parse(r#"meta[property="og:title"]"#).expect(E);
+    static ref DESCRIPTION: Selector =
+        Selector::parse(r#"meta[property="og:description"]"#).expect(E);
+    static ref THUMBNAIL: Selector = Selector::parse(r#"meta[property="og:image"]"#).expect(E);
+}
+
+#[derive(Debug, Clone)]
+pub struct KumparanCrawler {
+    allowlist: utils::HostAllowlist,
+}
+
+impl Default for KumparanCrawler {
+    fn default() -> Self {
+        KumparanCrawler {
+            allowlist: utils::HostAllowlist::new(["kumparan.com"]),
+        }
+    }
+}
+
+impl KumparanCrawler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    utils::allowlist_builders!("kumparan.com");
+}
+
+impl Crawler for KumparanCrawler {
+    type Document = KumparanArticle;
+
+    fn can_be_scrapped(&self, doc: &Html) -> bool {
+        payload::story(doc).is_some() || json_ld::article(doc).is_some()
+    }
+
+    fn extract_links(&self, ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        let related = payload::story(doc)
+            .map(|story| story.related_paths())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|path| ctx.url.join(&path).ok())
+            .filter(|url| self.allowlist.allows(url))
+            .map(|url| ExtractedLink::from(url.to_string()));
+
+        utils::unique_links(
+            utils::links(doc, ctx.url, |url| self.allowlist.allows(url))
+                .into_iter()
+                .chain(related),
+        )
+    }
+
+    fn crawl(&self, ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        let links = self.extract_urls(ctx, doc);
+
+        if !self.can_be_scrapped(doc) {
+            return CrawlerResult::Links(links);
+        }
+
+        let ld = json_ld::article(doc);
+        let ld_string = |key: &str| ld.as_ref().and_then(|ld| json_ld::string(ld, key));
+
+        let article = match payload::story(doc) {
+            Some(story) => KumparanArticle {
+                title: story.title.clone().or_else(|| ld_string("headline")),
+                published_date: story
+                    .published_at
+                    .as_deref()
+                    .and_then(|s| DateTime::parse_from_rfc3339(s).ok()),
+                lead: story.lead_text.clone(),
+                thumbnail_url: story
+                    .cover_image
+                    .clone()
+                    .or_else(|| utils::meta_content(doc, &THUMBNAIL)),
+                author: story.user.as_ref().map(|user| user.username.clone()),
+                topics: story.topics.iter().map(|t| t.name.clone()).collect(),
+                paragraphs: story.paragraphs(),
+            },
+            // Without the payload the description is the only text we have
+            None => {
+                let description =
+                    ld_string("description").or_else(|| utils::meta_content(doc, &DESCRIPTION));
+                KumparanArticle {
+                    title: ld_string("headline").or_else(|| utils::meta_content(doc, &TITLE)),
+                    published_date: ld_string("datePublished")
+                        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok()),
+                    lead: description.clone(),
+                    thumbnail_url: utils::meta_content(doc, &THUMBNAIL),
+                    author: ld
+                        .as_ref()
+                        .map(|ld| json_ld::names(ld, "author"))
+                        .filter(|names| !names.is_empty())
+                        .map(|names| names.join(", ")),
+                    topics: vec![],
+                    paragraphs: description.into_iter().collect(),
+                }
+            }
+        };
+        CrawlerResult::document_and_links(article, links)
+    }
+}
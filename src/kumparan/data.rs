@@ -0,0 +1,67 @@
+use super::KumparanArticle;
+use crate::generic::{SiteArticle, SiteArticleTable};
+use crate::SqliteStorage;
+use sqlx::query::Query;
+use sqlx::sqlite::SqliteArguments;
+use sqlx::Sqlite;
+
+impl SiteArticle for KumparanArticle {
+    const COLUMNS: &'static [(&'static str, &'static str)] = &[
+        ("author", "TEXT"),
+        ("lead", "TEXT"),
+        ("thumbnail_url", "TEXT"),
+        ("topics", "TEXT"),
+    ];
+
+    fn bind<'q>(
+        self,
+        query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    ) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+        query
+            .bind(self.author)
+            .bind(self.lead)
+            .bind(self.thumbnail_url)
+            .bind(self.topics.join("|"))
+    }
+}
+
+pub type KumparanArticleTable = SiteArticleTable<KumparanArticle>;
+
+pub type KumparanData = SqliteStorage<KumparanArticleTable>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils, Storage, Table};
+    use std::path::Path;
+    use tokio::fs;
+
+    #[tokio::test]
+    async fn columns_mapped() {
+        if Path::new("test_kumparan.db").is_file() {
+            fs::remove_file("test_kumparan.db").await.unwrap();
+        }
+        let p = KumparanData::new("test_kumparan").await.unwrap();
+
+        let article = KumparanArticle {
+            title: Some("title".to_string()),
+            published_date: Some(utils::get_now()),
+            lead: None,
+            thumbnail_url: None,
+            author: Some("author".to_string()),
+            topics: vec!["t1".to_string()],
+            paragraphs: vec!["p1".to_string(), "p2".to_string()],
+        };
+        p.results_insert(("https://kumparan.com/kumparannews/judul-1", article))
+            .await
+            .unwrap();
+        let row: (String, String) =
+            sqlx::query_as("SELECT author, topics FROM test_kumparan_results")
+                .fetch_one(p.results.get_pool())
+                .await
+                .unwrap();
+        assert_eq!(row, ("author".to_string(), "t1".to_string(),));
+
+        fs::remove_file("test_kumparan.db").await.unwrap();
+    }
+}
@@ -0,0 +1,39 @@
+mod crawler;
+#[cfg(feature = "sqlite")]
+mod data;
+mod payload;
+
+pub use crawler::KumparanCrawler;
+#[cfg(feature = "sqlite")]
+pub use data::{KumparanArticleTable, KumparanData};
+
+use crate::Article;
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KumparanArticle {
+    pub title: Option<String>,
+    pub published_date: Option<DateTime<FixedOffset>>,
+    pub lead: Option<String>,
+    pub thumbnail_url: Option<String>,
+    /// Handle of the publishing account, e.g. `kumparannews`
+    pub author: Option<String>,
+    pub topics: Vec<String>,
+    pub paragraphs: Vec<String>,
+}
+
+impl Article for KumparanArticle {
+    fn get_paragraphs(&self) -> &[String] {
+        self.paragraphs.as_slice()
+    }
+
+    fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    fn get_published_date(&self) -> Option<DateTime<FixedOffset>> {
+        self.published_date
+    }
+}
@@ -0,0 +1,158 @@
+//! The story state embedded by the client-rendered pages, the served html barely has any
+//! article text.
+
+use lazy_static::lazy_static;
+use scraper::{Html, Selector};
+use serde::Deserialize;
+
+lazy_static! {
+    static ref NEXT_DATA: Selector =
+        Selector::parse(r#"script#__NEXT_DATA__"#).expect("Invalid selector");
+}
+
+#[derive(Debug, Deserialize)]
+struct NextData {
+    props: Props,
+}
+
+#[derive(Debug, Deserialize)]
+struct Props {
+    #[serde(rename = "pageProps")]
+    page_props: PageProps,
+}
+
+#[derive(Debug, Deserialize)]
+struct PageProps {
+    story: Option<Story>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Story {
+    pub title: Option<String>,
+    pub lead_text: Option<String>,
+    pub published_at: Option<String>,
+    pub cover_image: Option<String>,
+    pub user: Option<User>,
+    #[serde(default)]
+    pub topics: Vec<Topic>,
+    pub document: Option<Document>,
+    #[serde(default)]
+    pub related_stories: Vec<RelatedStory>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct User {
+    pub username: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Topic {
+    pub name: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct Document {
+    #[serde(default)]
+    pub blocks: Vec<Block>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Block {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub children: Vec<Span>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Span {
+    #[serde(default)]
+    pub text: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct RelatedStory {
+    pub slug: String,
+    pub user: Option<User>,
+}
+
+/// The story of the page, `None` when the payload is missing or has no story.
+pub(crate) fn story(doc: &Html) -> Option<Story> {
+    let raw = doc.select(&NEXT_DATA).next()?.text().collect::<String>();
+    serde_json::from_str::<NextData>(raw.trim())
+        .ok()?
+        .props
+        .page_props
+        .story
+}
+
+impl Story {
+    /// Paragraph blocks in document order, each joined from its spans.
+    pub fn paragraphs(&self) -> Vec<String> {
+        self.document
+            .iter()
+            .flat_map(|document| &document.blocks)
+            .filter(|block| block.kind == "paragraph")
+            .map(|block| {
+                let text: String = block
+                    .children
+                    .iter()
+                    .map(|span| span.text.as_str())
+                    .collect();
+                text.split_whitespace().collect::<Vec<_>>().join(" ")
+            })
+            .filter(|p| !p.is_empty())
+            .collect()
+    }
+
+    /// Paths of related stories, `/<username>/<slug>`.
+    pub fn related_paths(&self) -> Vec<String> {
+        self.related_stories
+            .iter()
+            .filter_map(|story| {
+                let user = story.user.as_ref()?;
+                Some(format!("/{}/{}", user.username, story.slug))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn reconstruct_paragraphs() {
+        let html = r#"<html><body><script id="__NEXT_DATA__" type="application/json">
+            {"props": {"pageProps": {"story": {
+                "title": "Judul",
+                "user": {"username": "kumparannews"},
+                "document": {"blocks": [
+                    {"type": "paragraph", "children": [{"text": "Satu "}, {"text": "dua", "bold": true}, {"text": "."}]},
+                    {"type": "image", "url": "https://blue.kumparan.com/image.jpg"},
+                    {"type": "paragraph", "children": [{"text": "  "}]},
+                    {"type": "paragraph", "children": [{"text": "Tiga\n empat."}]}
+                ]},
+                "relatedStories": [
+                    {"slug": "berita-lain-1", "user": {"username": "kumparanbisnis"}},
+                    {"slug": "tanpa-user"}
+                ]
+            }}}}
+        </script></body></html>"#;
+        let story = story(&Html::parse_document(html)).unwrap();
+        assert_eq!(story.title.as_deref(), Some("Judul"));
+        assert_eq!(story.paragraphs(), vec!["Satu dua.", "Tiga empat."]);
+        assert_eq!(story.related_paths(), vec!["/kumparanbisnis/berita-lain-1"]);
+    }
+
+    #[test]
+    fn missing_payload() {
+        let doc = Html::parse_document(
+            r#"<script id="__NEXT_DATA__">{"props": {"pageProps": {}}}</script>"#,
+        );
+        assert!(story(&doc).is_none());
+        assert!(story(&Html::parse_document("<p>x</p>")).is_none());
+    }
+}
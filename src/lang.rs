@@ -0,0 +1,89 @@
+//! A small language detector telling Indonesian from English articles by their most common
+//! words, enough to keep the odd English or syndicated piece out of an Indonesian corpus.
+
+const INDONESIAN: [&str; 40] = [
+    "yang", "dan", "di", "ke", "dari", "ini", "itu", "dengan", "untuk", "tidak", "dalam", "akan",
+    "pada", "juga", "ada", "adalah", "oleh", "sudah", "karena", "bisa", "atau", "mereka", "kami",
+    "kita", "saat", "lebih", "telah", "tersebut", "menjadi", "hanya", "namun", "bahwa", "para",
+    "seperti", "masih", "belum", "agar", "kata", "dia", "serta",
+];
+
+const ENGLISH: [&str; 40] = [
+    "the", "and", "of", "to", "in", "is", "that", "for", "it", "with", "as", "was", "on", "are",
+    "be", "by", "this", "have", "from", "at", "not", "but", "they", "which", "has", "were", "an",
+    "will", "said", "their", "would", "been", "its", "who", "he", "she", "had", "we", "or", "more",
+];
+
+/// Fewer common words than this and the text is too short to tell.
+const MIN_HITS: usize = 5;
+
+/// The ISO 639-1 code of the language of `paragraphs`, `id` or `en`. `None` when the text
+/// is too short or too mixed to tell.
+pub fn detect<S: AsRef<str>>(paragraphs: &[S]) -> Option<&'static str> {
+    let (mut id, mut en) = (0, 0);
+    for paragraph in paragraphs {
+        for word in paragraph
+            .as_ref()
+            .split(|c: char| !c.is_alphabetic())
+            .filter(|w| !w.is_empty())
+        {
+            let word = word.to_lowercase();
+            if INDONESIAN.contains(&word.as_str()) {
+                id += 1;
+            } else if ENGLISH.contains(&word.as_str()) {
+                en += 1;
+            }
+        }
+    }
+
+    if id + en < MIN_HITS {
+        return None;
+    }
+    // One language has to clearly win, quotes of the other are common
+    if id * 10 >= (id + en) * 7 {
+        Some("id")
+    } else if en * 10 >= (id + en) * 7 {
+        Some("en")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn languages() {
+        let indonesian = [
+            "Kepala Dinas Pariwisata Badung memastikan tak akan ada sweeping terhadap turis \
+             asing yang liburan ke Bali.",
+            "\"Seluruh wisatawan akan tetap aman dan nyaman, saat menikmati liburannya,\" \
+             tegas Rudiarta.",
+        ];
+        assert_eq!(detect(&indonesian), Some("id"));
+
+        let english = [
+            "The head of the Badung tourism office said there will be no raids on foreign \
+             tourists who are on holiday in Bali.",
+            "\"All tourists will remain safe and comfortable,\" he said.",
+        ];
+        assert_eq!(detect(&english), Some("en"));
+
+        // An Indonesian article quoting an English statement is still Indonesian
+        let quoting = [
+            indonesian[0],
+            indonesian[1],
+            "\"It is safe,\" kata dia dalam keterangan yang diterima di Jakarta.",
+        ];
+        assert_eq!(detect(&quoting), Some("id"));
+
+        assert_eq!(detect(&["Foto: Dok. Istimewa"]), None);
+        assert_eq!(detect::<&str>(&[]), None);
+        assert_eq!(
+            detect(&["the and of to in", "yang dan di ke dari"]),
+            None,
+            "Too mixed to tell"
+        );
+    }
+}
@@ -1,72 +1,864 @@
+use chrono::{DateTime, FixedOffset};
+use futures::stream::BoxStream;
+use reqwest::Url;
 use scraper::Html;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::{
-    sync::mpsc,
+    sync::{mpsc, Mutex, OwnedSemaphorePermit, Semaphore},
+    task::{JoinError, JoinSet},
     time::{Duration, Instant},
 };
 use tracing::{debug, info, warn};
 
+#[cfg(all(feature = "detik", feature = "sqlite"))]
+pub mod analytics;
+pub mod antara;
+pub mod backoff;
+pub mod bbcindonesia;
+pub mod budget;
+pub mod charset;
+pub mod cnbcindonesia;
+#[cfg(feature = "sqlite")]
+pub mod compression;
+pub mod config;
+#[cfg(all(feature = "detik", feature = "sqlite"))]
+pub mod coverage;
+#[cfg(feature = "tui")]
+pub mod dashboard;
+#[cfg(feature = "detik")]
 pub mod detik;
+pub mod dns;
+#[cfg(all(feature = "detik", feature = "sqlite"))]
+pub mod export;
+pub mod fixture;
+pub mod generic;
+pub mod health;
+pub mod idntimes;
+pub mod jawapos;
+pub mod kompas;
+pub mod kontan;
+pub mod kumparan;
+pub mod lang;
+pub mod liputan6;
+pub mod logging;
+pub mod mediaindonesia;
+pub mod memory;
+pub mod merdeka;
+pub mod outage;
+pub mod pikiranrakyat;
+pub mod politeness;
+pub mod proxy;
+pub mod quality;
+pub mod recrawl;
+pub mod report;
+pub mod republika;
+pub mod retry;
+pub mod revisit;
+pub mod rewrite;
+pub mod seeds;
+pub mod selectors;
+pub mod simhash;
+pub mod sindonews;
+pub mod sitemap;
+pub mod tempo;
+pub mod throttle;
+pub mod tirto;
+pub mod tribunnews;
+pub mod visited_cache;
 
+mod client;
+mod controller;
+mod cookies;
 mod data;
 mod error;
+mod json_ld;
+mod options;
+#[cfg(feature = "http-api")]
+mod server;
+mod site;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod stats;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod text;
 mod utils;
 
+pub use client::http_client;
+pub use controller::CrawlController;
+pub use cookies::CookieJar;
+#[cfg(feature = "sqlite")]
 pub use data::Table;
+pub use data::{
+    ExtractionStatus, HostState, HubState, ResultEntry, ResultSummary, Revision, WarnedEntry,
+};
 pub use error::CrawlerError;
+pub use options::{default_worker_id, RunOptions};
+#[cfg(feature = "http-api")]
+pub use server::IngestConfig;
+pub use site::Site;
+#[cfg(feature = "sqlite")]
+pub use sqlite::{
+    DictionaryTable, HostStateTable, HubSnapshotTable, HubTable, ResultTable, RevisionTable,
+    RevisitTable, SqliteStorage, UrlKey, UrlTable, WarnedTable, MAX_KEY_LEN,
+};
+pub use stats::{
+    CrawlStats, Histogram, HostLatency, ParseTiming, Percentiles, MAX_LATENCY_HOSTS, OTHER_HOSTS,
+    SLOWEST_PARSES,
+};
 
 pub trait Article {
     fn get_paragraphs(&self) -> &[String];
+
+    fn get_title(&self) -> Option<&str> {
+        None
+    }
+
+    fn get_published_date(&self) -> Option<DateTime<FixedOffset>> {
+        None
+    }
+
+    /// The ISO 639-1 code of the language, detected from the paragraphs unless the article
+    /// records it.
+    fn get_language(&self) -> Option<String> {
+        lang::detect(self.get_paragraphs()).map(ToString::to_string)
+    }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CrawlerResult<A: Article> {
     Links(Vec<String>),
-    DocumentAndLinks(A, Vec<String>),
+    DocumentAndLinks(A, Links),
+    /// An article that must not be stored, e.g. truncated by a paywall. The page is recorded
+    /// as warned with the reason and its links are still followed.
+    Skipped(String, Vec<String>),
+}
+
+impl<A: Article> CrawlerResult<A> {
+    /// An article whose links aren't told apart, all of them in `Links::other`.
+    pub fn document_and_links(document: A, links: Vec<String>) -> Self {
+        CrawlerResult::DocumentAndLinks(document, links.into())
+    }
+
+    /// The same result without any link to follow.
+    pub fn without_links(self) -> Self {
+        match self {
+            CrawlerResult::Links(_) => CrawlerResult::Links(vec![]),
+            CrawlerResult::DocumentAndLinks(document, _) => {
+                CrawlerResult::DocumentAndLinks(document, Links::default())
+            }
+            CrawlerResult::Skipped(reason, _) => CrawlerResult::Skipped(reason, vec![]),
+        }
+    }
+}
+
+/// The links of an article page: the editorial ones, in the body ("Baca juga") or a
+/// related-articles widget, and the others, e.g. navigation, header and footer. Serialized
+/// as a plain list while none is related.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "LinksRepr", into = "LinksRepr")]
+pub struct Links {
+    pub related: Vec<String>,
+    pub other: Vec<String>,
+}
+
+impl Links {
+    /// Every link, the related ones first.
+    pub fn iter(&self) -> impl Iterator<Item = &String> {
+        self.related.iter().chain(&self.other)
+    }
+
+    pub fn len(&self) -> usize {
+        self.related.len() + self.other.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.related.is_empty() && self.other.is_empty()
+    }
+}
+
+impl From<Vec<String>> for Links {
+    fn from(other: Vec<String>) -> Self {
+        Links {
+            related: vec![],
+            other,
+        }
+    }
+}
+
+impl From<Links> for Vec<String> {
+    fn from(links: Links) -> Self {
+        let mut all = links.related;
+        all.extend(links.other);
+        all
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum LinksRepr {
+    All(Vec<String>),
+    Split {
+        related: Vec<String>,
+        other: Vec<String>,
+    },
+}
+
+impl From<LinksRepr> for Links {
+    fn from(repr: LinksRepr) -> Self {
+        match repr {
+            LinksRepr::All(other) => other.into(),
+            LinksRepr::Split { related, other } => Links { related, other },
+        }
+    }
+}
+
+impl From<Links> for LinksRepr {
+    fn from(links: Links) -> Self {
+        if links.related.is_empty() {
+            LinksRepr::All(links.other)
+        } else {
+            LinksRepr::Split {
+                related: links.related,
+                other: links.other,
+            }
+        }
+    }
+}
+
+/// A link found on a page: the url to follow with the text and `rel` of its anchor, to tell
+/// e.g. "Baca juga" links to articles from navigation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtractedLink {
+    pub url: String,
+    /// The trimmed text of the anchor, empty for links not taken from an anchor
+    pub text: String,
+    pub rel: Option<String>,
+}
+
+impl From<String> for ExtractedLink {
+    fn from(url: String) -> Self {
+        ExtractedLink {
+            url,
+            text: String::new(),
+            rel: None,
+        }
+    }
+}
+
+/// Warned reason of articles without any paragraph.
+pub const EMPTY_PARAGRAPHS: &str = "empty_paragraphs";
+
+/// Warned reason of premium articles, whose page only holds the first paragraphs.
+pub const PAYWALLED: &str = "paywalled";
+
+/// Warned reason of articles in a language not in `RunOptions::languages`.
+pub const LANGUAGE: &str = "language";
+
+/// Skipped reason of the interstitial or adblock wall a site serves in place of a page. The
+/// page is fetched again once, with the cookies the interstitial set, and warned with this
+/// reason when it's still not there.
+pub const INTERSTITIAL: &str = "interstitial";
+
+/// Warned reason of pages larger than `RunOptions::max_decompressed_size`.
+pub const TOO_LARGE: &str = "too_large";
+
+/// Warned reason of pages still redirecting with a meta refresh after `MAX_REFRESH_HOPS`.
+pub const REFRESH_LOOP: &str = "refresh_loop";
+
+/// Warned reason of pages whose request timed out, retried by the default `RetryPolicy`.
+pub const TIMEOUT: &str = "timeout";
+
+/// Warned reason of pages answered with a 5xx status, retried by the default `RetryPolicy`.
+pub const HTTP_5XX: &str = "http_5xx";
+
+/// Skipped reason of articles whose page was cut at `RunOptions::max_parse_size`, so their
+/// end may be missing.
+pub const TRUNCATED: &str = "truncated";
+
+/// Warned reason of pages so much of which failed to decode from their charset that their
+/// text is mostly replacement characters, see `charset::Decoded::is_garbled`.
+pub const BAD_ENCODING: &str = "bad_encoding";
+
+/// Meta refresh redirects followed from one queued url.
+pub const MAX_REFRESH_HOPS: usize = 3;
+
+/// The HTTP response a page was crawled from, see `CrawlContext::response`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseMeta {
+    /// The url the request ended at, after its HTTP redirects
+    pub final_url: Url,
+    pub status: u16,
+    /// The `Last-Modified` header, when it's a valid HTTP date
+    pub last_modified: Option<DateTime<FixedOffset>>,
+    /// The `Content-Language` header
+    pub content_language: Option<String>,
+    pub fetched_at: DateTime<FixedOffset>,
+}
+
+impl ResponseMeta {
+    /// A 200 response from `final_url` fetched at `fetched_at`, without any of the headers.
+    pub fn new(final_url: Url, fetched_at: DateTime<FixedOffset>) -> Self {
+        ResponseMeta {
+            final_url,
+            status: 200,
+            last_modified: None,
+            content_language: None,
+            fetched_at,
+        }
+    }
+}
+
+/// The page a `Crawler` is given along with its document.
+#[derive(Debug, Clone, Copy)]
+pub struct CrawlContext<'a> {
+    pub url: &'a Url,
+    /// The document is the start of a page larger than `RunOptions::max_parse_size`
+    pub truncated: bool,
+    /// The response the document was fetched from, `None` for a document crawled from
+    /// elsewhere, e.g. a fixture
+    pub response: Option<&'a ResponseMeta>,
+}
+
+impl<'a> CrawlContext<'a> {
+    pub fn new(url: &'a Url) -> Self {
+        CrawlContext {
+            url,
+            truncated: false,
+            response: None,
+        }
+    }
+
+    pub fn with_response(mut self, response: &'a ResponseMeta) -> Self {
+        self.response = Some(response);
+        self
+    }
+
+    /// The url the page was fetched from after its HTTP redirects, else `url`.
+    pub fn final_url(&self) -> &'a Url {
+        self.response
+            .map_or(self.url, |response| &response.final_url)
+    }
+}
+
+/// Whether the crawl fetches a url, see `Crawler::should_fetch`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchDecision {
+    Fetch,
+    /// Never fetch it: it's marked visited, so it's not queued again
+    SkipPermanently,
+    /// Don't fetch it now: it's dropped without a trace, and queued again when it's found
+    /// again
+    SkipEnqueueOnly,
 }
 
 pub trait Crawler {
     type Document: Article + Send;
 
+    fn can_be_scrapped(&self, doc: &Html) -> bool;
+    fn crawl(&self, ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document>;
+    /// The links to follow in document order, each url once with the first anchor to it.
+    fn extract_links(&self, ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink>;
+
+    /// The urls of `extract_links`.
+    fn extract_urls(&self, ctx: &CrawlContext, doc: &Html) -> Vec<String> {
+        self.extract_links(ctx, doc)
+            .into_iter()
+            .map(|link| link.url)
+            .collect()
+    }
+
+    /// What `crawl` gets out of `sample`, a page known to be an article, e.g. to notice the
+    /// site changed its markup.
+    fn health_check(&self, ctx: &CrawlContext, sample: &Html) -> health::HealthReport {
+        health::HealthReport::of(&self.crawl(ctx, sample))
+    }
+
+    /// Whether `url` is fetched, asked before it's requested and before a link to it is
+    /// queued, so the pages of the site the crawler has no use for cost no request. Every
+    /// url is fetched by default.
+    fn should_fetch(&self, _url: &Url) -> FetchDecision {
+        FetchDecision::Fetch
+    }
+}
+
+/// The article `crawler` extracts from `html`, the page at `url`, for html from another
+/// fetcher or an archive. `None` when the page isn't an article, the crawler skips it or
+/// `url` is invalid.
+///
+/// ```
+/// # #[cfg(feature = "detik")]
+/// # {
+/// use indonesian_media_crawler::{detik::DetikCrawler, parse_article};
+///
+/// let html = r#"<meta name="dtk:contenttype" content="singlepagenews">
+///     <meta property="og:title" content="Judul">
+///     <div class="detail__body-text"><p>Isi berita.</p></div>"#;
+/// let url = "https://news.detik.com/berita/d-1/judul";
+///
+/// let article = parse_article(&DetikCrawler::new(), html, url).unwrap();
+/// assert_eq!(article.title.as_deref(), Some("Judul"));
+/// assert_eq!(article.paragraphs, vec!["Isi berita."]);
+/// # }
+/// ```
+pub fn parse_article<C: Crawler>(crawler: &C, html: &str, url: &str) -> Option<C::Document> {
+    let url = Url::parse(url).ok()?;
+    let doc = Html::parse_document(html);
+    match crawler.crawl(&CrawlContext::new(&url), &doc) {
+        CrawlerResult::DocumentAndLinks(article, _) => Some(article),
+        CrawlerResult::Links(_) | CrawlerResult::Skipped(..) => None,
+    }
+}
+
+/// The links `crawler` would follow from `html`, the page at `url`. Empty when `url` is
+/// invalid.
+///
+/// ```
+/// # #[cfg(feature = "detik")]
+/// # {
+/// use indonesian_media_crawler::{detik::DetikCrawler, parse_links};
+///
+/// let html = r#"<a href="/berita/d-2/lain">Lain</a> <a href="https://example.com">x</a>"#;
+/// let links = parse_links(&DetikCrawler::new(), html, "https://news.detik.com/");
+/// assert_eq!(links, vec!["https://news.detik.com/berita/d-2/lain"]);
+/// # }
+/// ```
+pub fn parse_links<C: Crawler>(crawler: &C, html: &str, url: &str) -> Vec<String> {
+    match Url::parse(url) {
+        Ok(url) => crawler.extract_urls(&CrawlContext::new(&url), &Html::parse_document(html)),
+        Err(_) => vec![],
+    }
+}
+
+/// The `Crawler` trait from before crawlers were given the url of the page. Every
+/// `LegacyCrawler` is a `Crawler` ignoring the url.
+#[deprecated(note = "implement `Crawler`, whose methods also get a `CrawlContext`")]
+pub trait LegacyCrawler {
+    type Document: Article + Send;
+
     fn can_be_scrapped(&self, doc: &Html) -> bool;
     fn crawl(&self, doc: &Html) -> CrawlerResult<Self::Document>;
     fn extract_links(&self, doc: &Html) -> Vec<String>;
 }
 
-lazy_static::lazy_static! {
-    static ref LAST_REQUEST_MUTEX: tokio::sync::Mutex<Option<Instant>> = tokio::sync::Mutex::new(None);
-    static ref REQUEST_DELAY: Duration = Duration::from_millis(50);
-    static ref EXTRACTED_MUTEX: std::sync::Mutex<u64> = std::sync::Mutex::new(0);
+#[allow(deprecated)]
+impl<C: LegacyCrawler> Crawler for C {
+    type Document = C::Document;
+
+    fn can_be_scrapped(&self, doc: &Html) -> bool {
+        LegacyCrawler::can_be_scrapped(self, doc)
+    }
+
+    fn crawl(&self, _ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        LegacyCrawler::crawl(self, doc)
+    }
+
+    fn extract_links(&self, _ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        LegacyCrawler::extract_links(self, doc)
+            .into_iter()
+            .map(ExtractedLink::from)
+            .collect()
+    }
+}
+
+/// A crawler that may fetch more pages while crawling one, e.g. the next pages of a
+/// multi-page article or a JSON payload. Every `Crawler` is an `AsyncCrawler`.
+#[async_trait::async_trait]
+pub trait AsyncCrawler {
+    type Document: Article + Send;
+
+    async fn crawl(&self, ctx: &FetchContext<'_>, html: &str) -> CrawlerResult<Self::Document>;
+
+    /// What `crawl` gets out of `html`, a page known to be an article.
+    async fn health_check(&self, ctx: &FetchContext<'_>, html: &str) -> health::HealthReport
+    where
+        Self: Sync,
+    {
+        health::HealthReport::of(&self.crawl(ctx, html).await)
+    }
+
+    /// Whether `url` is fetched, see `Crawler::should_fetch`.
+    fn should_fetch(&self, _url: &Url) -> FetchDecision {
+        FetchDecision::Fetch
+    }
+}
+
+#[async_trait::async_trait]
+impl<C: Crawler + Sync> AsyncCrawler for C {
+    type Document = C::Document;
+
+    async fn crawl(&self, ctx: &FetchContext<'_>, html: &str) -> CrawlerResult<Self::Document> {
+        let doc = Html::parse_document(html);
+        Crawler::crawl(self, &ctx.crawl_context(), &doc)
+    }
+
+    async fn health_check(&self, ctx: &FetchContext<'_>, html: &str) -> health::HealthReport {
+        let doc = Html::parse_document(html);
+        Crawler::health_check(self, &ctx.crawl_context(), &doc)
+    }
+
+    fn should_fetch(&self, url: &Url) -> FetchDecision {
+        Crawler::should_fetch(self, url)
+    }
+}
+
+/// The page being crawled by an `AsyncCrawler`, and the client of the run to fetch more.
+pub struct FetchContext<'a> {
+    url: &'a Url,
+    truncated: bool,
+    response: Option<&'a ResponseMeta>,
+    state: &'a RunState,
+}
+
+impl<'a> FetchContext<'a> {
+    pub fn url(&self) -> &Url {
+        self.url
+    }
+
+    /// Whether the html given with it is the start of a page larger than
+    /// `RunOptions::max_parse_size`.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// The response the html was fetched from, `None` for the canary.
+    pub fn response(&self) -> Option<&'a ResponseMeta> {
+        self.response
+    }
+
+    pub fn crawl_context(&self) -> CrawlContext<'a> {
+        CrawlContext {
+            url: self.url,
+            truncated: self.truncated,
+            response: self.response,
+        }
+    }
+
+    /// Fetch `url` with the client of the run, waiting for the request delay like every
+    /// other request.
+    pub async fn fetch(&self, url: &str) -> Result<String, CrawlerError> {
+        self.state.fetch(url).await
+    }
+}
+
+/// A queued or running url, with how the crawl came to it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueuedUrl {
+    /// Shared by the stages the url goes through rather than copied
+    pub url: Arc<str>,
+    /// Links followed from a seed to it, 0 for the seeds
+    pub depth: u32,
+    /// The page its link was first found on, `None` for the seeds
+    pub referrer: Option<String>,
+    /// Claimed before the urls of a lower priority, see `Storage::queued_claim_n`
+    pub priority: i32,
+}
+
+impl QueuedUrl {
+    /// A seed: queued from outside the crawl rather than found on a page.
+    pub fn new<S: Into<Arc<str>>>(url: S) -> Self {
+        QueuedUrl {
+            url: url.into(),
+            depth: 0,
+            referrer: None,
+            priority: 0,
+        }
+    }
+}
+
+/// The url sets a crawl moves each url through, from queued to running to visited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Bucket {
+    Queued,
+    Running,
+    Visited,
 }
 
-const MAX_IN_PROGRESS: u32 = 20;
+/// One `Bucket` of a `Storage`: a set of urls in insertion order.
+#[async_trait::async_trait]
+pub trait UrlBucket: Send + Sync {
+    /// The urls, oldest first, at most `limit` of them.
+    async fn urls(&self, limit: Option<u32>) -> Result<Vec<String>, CrawlerError>;
+    /// Add `url`, keeping its place if it's already there.
+    async fn insert(&self, url: &str) -> Result<(), CrawlerError>;
+    async fn insert_batch(&self, urls: &[&str]) -> Result<(), CrawlerError> {
+        for url in urls {
+            self.insert(url).await?;
+        }
+        Ok(())
+    }
+    async fn delete(&self, url: &str) -> Result<(), CrawlerError>;
+    async fn contains(&self, url: &str) -> Result<bool, CrawlerError>;
+    async fn count(&self) -> Result<u32, CrawlerError>;
+    async fn is_empty(&self) -> Result<bool, CrawlerError> {
+        Ok(self.urls(Some(1)).await?.is_empty())
+    }
+    /// The urls, oldest first, read as the stream is polled instead of all at once.
+    fn stream(&self) -> BoxStream<'_, Result<String, CrawlerError>>;
+    /// When the oldest url was added, `None` when it's empty.
+    async fn oldest_added_at(&self) -> Result<Option<DateTime<FixedOffset>>, CrawlerError>;
+}
 
+/// Where a crawl keeps its urls, results and warnings.
+///
+/// A backend provides its buckets through `bucket` and the results and warned methods;
+/// the `queued_*`, `running_*` and `visited_*` methods are implemented over the buckets.
 #[async_trait::async_trait]
-pub trait Storage {
-    type Record: Article;
+pub trait Storage: Sync {
+    type Record: Article + Send;
+
+    fn bucket(&self, bucket: Bucket) -> &dyn UrlBucket;
+
+    async fn queued_get(&self) -> Result<Vec<String>, CrawlerError> {
+        self.bucket(Bucket::Queued).urls(None).await
+    }
+    async fn queued_get_n(&self, n: u32) -> Result<Vec<String>, CrawlerError> {
+        self.bucket(Bucket::Queued).urls(Some(n)).await
+    }
+    async fn queued_insert<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        self.bucket(Bucket::Queued).insert(item.as_ref()).await
+    }
+    async fn queued_insert_batch<I: AsRef<str> + Send + Sync>(
+        &self,
+        items: &[I],
+    ) -> Result<(), CrawlerError> {
+        let items: Vec<&str> = items.iter().map(AsRef::as_ref).collect();
+        self.bucket(Bucket::Queued).insert_batch(&items).await
+    }
+    async fn queued_delete<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        self.bucket(Bucket::Queued).delete(item.as_ref()).await
+    }
+
+    /// Queue `item` as a seed unless it is already queued, running or visited.
+    async fn queued_insert_unseen<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+    ) -> Result<(), CrawlerError> {
+        self.queued_insert_with_meta(item, None, 0).await
+    }
+    /// Queue `item`, found on `referrer` `depth` links away from a seed, unless it is already
+    /// queued, running or visited: a url keeps the referrer it was first found on.
+    async fn queued_insert_with_meta<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        referrer: Option<&str>,
+        depth: u32,
+    ) -> Result<(), CrawlerError>;
+    /// Queue `item` as a seed with `priority`, unless it is already queued, running or
+    /// visited.
+    async fn queued_insert_with_priority<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        priority: i32,
+    ) -> Result<(), CrawlerError>;
+    /// `queued_insert_with_meta` with `priority`. The priority is dropped by default, for the
+    /// storages that don't keep it.
+    async fn queued_insert_prioritized<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        referrer: Option<&str>,
+        depth: u32,
+        _priority: i32,
+    ) -> Result<(), CrawlerError> {
+        self.queued_insert_with_meta(item, referrer, depth).await
+    }
+    async fn queued_is_exists<I: AsRef<str> + Send>(&self, item: I) -> Result<bool, CrawlerError> {
+        self.bucket(Bucket::Queued).contains(item.as_ref()).await
+    }
+    async fn queued_count(&self) -> Result<u32, CrawlerError> {
+        self.bucket(Bucket::Queued).count().await
+    }
+    async fn queued_is_empty(&self) -> Result<bool, CrawlerError> {
+        self.bucket(Bucket::Queued).is_empty().await
+    }
+    /// `queued_get` a url at a time, for queues too large to hold in memory.
+    fn queued_stream(&self) -> BoxStream<'_, Result<String, CrawlerError>> {
+        self.bucket(Bucket::Queued).stream()
+    }
+    /// When the url waiting the longest was queued.
+    async fn queued_oldest_at(&self) -> Result<Option<DateTime<FixedOffset>>, CrawlerError> {
+        self.bucket(Bucket::Queued).oldest_added_at().await
+    }
 
-    async fn queued_get(&self) -> Result<Vec<String>, CrawlerError>;
-    async fn queued_get_n(&self, n: u32) -> Result<Vec<String>, CrawlerError>;
-    async fn queued_insert<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError>;
-    async fn queued_delete<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError>;
-    async fn queued_is_exists<I: AsRef<str> + Send>(&self, item: I) -> Result<bool, CrawlerError>;
+    async fn running_get(&self) -> Result<Vec<String>, CrawlerError> {
+        self.bucket(Bucket::Running).urls(None).await
+    }
+    async fn running_insert<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        self.bucket(Bucket::Running).insert(item.as_ref()).await
+    }
+    async fn running_delete<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        self.bucket(Bucket::Running).delete(item.as_ref()).await
+    }
+    async fn running_count(&self) -> Result<u32, CrawlerError> {
+        self.bucket(Bucket::Running).count().await
+    }
+    async fn running_is_exists<I: AsRef<str> + Send>(&self, item: I) -> Result<bool, CrawlerError> {
+        self.bucket(Bucket::Running).contains(item.as_ref()).await
+    }
 
-    async fn running_get(&self) -> Result<Vec<String>, CrawlerError>;
-    async fn running_insert<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError>;
-    async fn running_delete<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError>;
-    async fn running_count(&self) -> Result<u32, CrawlerError>;
-    async fn running_is_exists<I: AsRef<str> + Send>(&self, item: I) -> Result<bool, CrawlerError>;
+    /// Move at most `n` queued urls to running for `worker_id`, the highest `priority` first
+    /// and the oldest first among the same priority. A url is claimed by one worker only,
+    /// even with several processes sharing the storage, and the queued urls already running
+    /// or visited are dropped in the same step.
+    async fn queued_claim_n(&self, worker_id: &str, n: u32)
+        -> Result<Vec<QueuedUrl>, CrawlerError>;
+    /// Take `item` out of the queue and move it to running for `worker_id`, unless it's
+    /// already running or visited, checked in the same step. Whether it was moved.
+    async fn running_claim<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        worker_id: &str,
+    ) -> Result<bool, CrawlerError>;
+    /// Record that the worker is still crawling the urls it claimed, at `now`.
+    async fn running_touch(
+        &self,
+        worker_id: &str,
+        now: DateTime<FixedOffset>,
+    ) -> Result<(), CrawlerError>;
+    /// Queue again the running urls whose worker wasn't heard from since `stale_before`,
+    /// those of a crashed worker.
+    async fn running_reap(
+        &self,
+        stale_before: DateTime<FixedOffset>,
+    ) -> Result<Vec<String>, CrawlerError>;
+    /// Queue again the running urls of `worker_id`, claimed but not crawled when it stopped.
+    async fn running_release(&self, worker_id: &str) -> Result<Vec<String>, CrawlerError>;
+    /// Queue `item` again from running, left for later, with its referrer, depth and
+    /// priority. Queued like a seed when it isn't running.
+    async fn running_requeue<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError>;
 
-    async fn visited_delete<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError>;
-    async fn visited_is_exists<I: AsRef<str> + Send>(&self, item: I) -> Result<bool, CrawlerError>;
-    async fn visited_insert<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError>;
+    async fn visited_delete<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        self.bucket(Bucket::Visited).delete(item.as_ref()).await
+    }
+    async fn visited_is_exists<I: AsRef<str> + Send>(&self, item: I) -> Result<bool, CrawlerError> {
+        self.bucket(Bucket::Visited).contains(item.as_ref()).await
+    }
+    async fn visited_insert<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        self.bucket(Bucket::Visited).insert(item.as_ref()).await
+    }
+    async fn visited_count(&self) -> Result<u32, CrawlerError> {
+        self.bucket(Bucket::Visited).count().await
+    }
+    /// Every visited url, oldest first, a url at a time.
+    fn visited_stream(&self) -> BoxStream<'_, Result<String, CrawlerError>> {
+        self.bucket(Bucket::Visited).stream()
+    }
 
     async fn results_count(&self) -> Result<u32, CrawlerError>;
+    async fn results_recent(&self, n: u32) -> Result<Vec<ResultSummary>, CrawlerError>;
+    /// The article stored for `url`.
+    async fn results_get(&self, url: &str) -> Result<Option<ResultEntry>, CrawlerError>;
     async fn results_insert<I: AsRef<str> + Send>(
+        &self,
+        record: (I, Self::Record),
+    ) -> Result<(), CrawlerError> {
+        self.results_insert_with_referrer(record, None).await
+    }
+    /// Store the article of `url` with the page its link was found on, `None` for a seed. A
+    /// url already stored keeps its first article, unless only its metadata was stored.
+    async fn results_insert_with_referrer<I: AsRef<str> + Send>(
+        &self,
+        (url, record): (I, Self::Record),
+        referrer: Option<&str>,
+    ) -> Result<(), CrawlerError>;
+    /// Store the article of `url` whose body came back empty, its metadata only being kept
+    /// as `ExtractionStatus::MetadataOnly`. A url already stored is left alone, and the
+    /// article stored once its body is extracted replaces this one.
+    async fn results_insert_metadata_only<I: AsRef<str> + Send>(
+        &self,
+        record: (I, Self::Record),
+        referrer: Option<&str>,
+    ) -> Result<(), CrawlerError>;
+    /// How much of the stored article of `url` was extracted, `None` when it isn't stored.
+    async fn results_status(&self, url: &str) -> Result<Option<ExtractionStatus>, CrawlerError>;
+    /// Store the article of `url` in place of its stored one, keeping the new version as a
+    /// `Revision` with `diff`, what changed.
+    async fn results_revise<I: AsRef<str> + Send>(
         &self,
         (url, record): (I, Self::Record),
+        diff: &str,
     ) -> Result<(), CrawlerError>;
+    /// The revisions of the article of `url`, oldest first.
+    async fn revisions_get(&self, url: &str) -> Result<Vec<Revision>, CrawlerError>;
+    /// Fetch the article of `url` again at `due_at`, in place of when it was due before.
+    async fn revisit_schedule(
+        &self,
+        url: &str,
+        due_at: DateTime<FixedOffset>,
+    ) -> Result<(), CrawlerError>;
+    /// Take at most `n` urls due to be revisited at `now`, the longest due first.
+    async fn revisit_due(
+        &self,
+        now: DateTime<FixedOffset>,
+        n: u32,
+    ) -> Result<Vec<String>, CrawlerError>;
+
+    async fn warned_insert<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        reason: &str,
+    ) -> Result<(), CrawlerError>;
+    async fn warned_count(&self) -> Result<u32, CrawlerError>;
+    async fn warned_recent(&self, n: u32) -> Result<Vec<WarnedEntry>, CrawlerError>;
+    async fn warned_get(&self, item: &str) -> Result<Option<WarnedEntry>, CrawlerError>;
+    async fn warned_delete(&self, item: &str) -> Result<(), CrawlerError>;
+    /// Warn `item` like `warned_insert`, with the retries already made and when it's due
+    /// again, `None` for never.
+    async fn warned_schedule(
+        &self,
+        item: &str,
+        reason: &str,
+        attempts: u32,
+        next_retry_at: Option<DateTime<FixedOffset>>,
+    ) -> Result<(), CrawlerError>;
+    /// At most `n` warned entries due again at `now`, the longest due first.
+    async fn warned_due(
+        &self,
+        now: DateTime<FixedOffset>,
+        n: u32,
+    ) -> Result<Vec<WarnedEntry>, CrawlerError>;
+
+    /// The stored backoff of `host`, see `backoff`. None by default, for the storages that
+    /// don't keep it.
+    async fn host_state_get(&self, _host: &str) -> Result<Option<HostState>, CrawlerError> {
+        Ok(None)
+    }
+    /// Store `state` in place of the backoff of its host. Dropped by default.
+    async fn host_state_put(&self, _state: &HostState) -> Result<(), CrawlerError> {
+        Ok(())
+    }
 
-    async fn warned_insert<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError>;
+    /// The stored recrawl rate of the hub page `url`, see `recrawl`. None by default, for the
+    /// storages that don't keep it.
+    async fn hub_get(&self, _url: &str) -> Result<Option<HubState>, CrawlerError> {
+        Ok(None)
+    }
+    /// Store `state` in place of the recrawl rate of its hub page. Dropped by default.
+    async fn hub_put(&self, _state: &HubState) -> Result<(), CrawlerError> {
+        Ok(())
+    }
+    /// Every stored hub page, the most often crawled first.
+    async fn hubs_get(&self) -> Result<Vec<HubState>, CrawlerError> {
+        Ok(vec![])
+    }
+    /// The links found on the hub page `url` when it was last crawled. None by default.
+    async fn hub_snapshot_get(
+        &self,
+        _url: &str,
+    ) -> Result<Option<recrawl::LinkSnapshot>, CrawlerError> {
+        Ok(None)
+    }
+    /// Store `snapshot` in place of the links of the hub page `url`. Dropped by default.
+    async fn hub_snapshot_put(
+        &self,
+        _url: &str,
+        _snapshot: &recrawl::LinkSnapshot,
+    ) -> Result<(), CrawlerError> {
+        Ok(())
+    }
 
     async fn merge_queue_and_running(&self) -> Result<(), CrawlerError> {
         let running = self.running_get().await?;
@@ -78,161 +870,1320 @@ pub trait Storage {
     }
 }
 
-pub async fn run_scrapper<'a, C, S>(
-    crawler: C,
-    storage: S,
-    initial_queue: Vec<String>,
-) -> Result<(), CrawlerError>
-where
-    C: Crawler + Send + Sync + 'static,
-    S: Storage<Record = C::Document> + Sync + Send + 'static,
-{
-    let storage = Arc::new(storage);
-    let crawler = Arc::new(crawler);
-
-    debug!(
-        "Total (in progress, queue) before merge to queue: ({}, {})",
-        storage.running_get().await?.len(),
-        storage.queued_get().await?.len()
-    );
+/// State shared by the dispatcher and every `handle` task of one run.
+pub(crate) struct RunState {
+    pub(crate) options: RunOptions,
+    pub(crate) stats: CrawlStats,
+    client: reqwest::Client,
+    /// Used instead of `client` when the run goes through proxies
+    proxies: Option<proxy::ProxyPool>,
+    cookies: Arc<CookieJar>,
+    pub(crate) dns: Option<Arc<dns::DnsCache>>,
+    /// Results already stored before this run, used for the running total in logs
+    results_before: u64,
+    last_request: Mutex<Option<Instant>>,
+    /// Draws the delays between requests when the run has a `Politeness`
+    pacer: Option<politeness::Pacer>,
+    /// The hosts the run backs off from after they answered 429
+    backoffs: backoff::Backoffs,
+    /// Warned urls queued again by the `RetryPolicy`, whose warning goes once they're crawled
+    retrying: std::sync::Mutex<HashSet<String>>,
+    /// Stored articles queued again to be revisited, see `revisit`
+    revisiting: std::sync::Mutex<HashSet<String>>,
+    /// Whether only article urls are queued, set by the `QueueThrottle` of the run
+    throttled: AtomicBool,
+    /// Why the crawl stopped, when it stopped itself
+    stop_reason: std::sync::Mutex<Option<report::StopReason>>,
+    /// The articles warned lately, when the run has a `QualityAlert`
+    quality: Option<quality::QualityMonitor>,
+    /// The urls held while the storage takes no writes
+    outage: outage::Outage,
+    /// The bytes received lately, when the run has a `RunOptions::bytes_per_hour`
+    bandwidth: Option<budget::Bandwidth>,
+}
 
-    storage.merge_queue_and_running().await?;
+impl RunState {
+    fn new(options: RunOptions, results_before: u64) -> Result<Self, CrawlerError> {
+        let cookies = Arc::new(match &options.cookie_jar {
+            Some(path) => CookieJar::load(path)?,
+            None => CookieJar::default(),
+        });
+        let proxies = match &options.proxy {
+            Some(config) => proxy::ProxyPool::new(config, |proxy| {
+                client::run_client(&options, Arc::clone(&cookies), Some(proxy), None)
+            })?,
+            None => None,
+        };
+        let dns = match (&options.dns_cache, &proxies) {
+            (Some(config), None) => Some(Arc::new(dns::DnsCache::new(
+                dns::SystemResolver,
+                config.clone(),
+            ))),
+            _ => None,
+        };
+        let pacer = options.politeness.clone().map(politeness::Pacer::new);
+        let quality = options
+            .quality_alert
+            .clone()
+            .map(quality::QualityMonitor::new);
+        let bandwidth = options
+            .bytes_per_hour
+            .map(|bytes_per_hour| budget::Bandwidth::new(bytes_per_hour, Instant::now()));
+        Ok(RunState {
+            client: client::run_client(&options, Arc::clone(&cookies), None, dns.clone())?,
+            proxies,
+            cookies,
+            dns,
+            options,
+            stats: CrawlStats::new(),
+            results_before,
+            last_request: Mutex::new(None),
+            pacer,
+            backoffs: Default::default(),
+            retrying: Default::default(),
+            revisiting: Default::default(),
+            throttled: AtomicBool::new(false),
+            stop_reason: Default::default(),
+            quality,
+            outage: Default::default(),
+            bandwidth,
+        })
+    }
 
-    debug!(
-        "Total (in progress, queue) before merge to queue: ({}, {})",
-        storage.running_get().await?.len(),
-        storage.queued_get().await?.len()
-    );
+    /// Whether `url` was being retried, no longer once it's crawled.
+    fn retried(&self, url: &str) -> bool {
+        self.retrying.lock().unwrap().remove(url)
+    }
 
-    let queue = storage.queued_get().await?;
-    let queue = if queue.is_empty() {
-        for q in &initial_queue {
-            storage.queued_insert(q).await?;
-        }
-        initial_queue
-    } else {
-        queue
-    };
+    /// Whether `url` was being revisited, no longer once it's crawled.
+    fn revisited(&self, url: &str) -> bool {
+        self.revisiting.lock().unwrap().remove(url)
+    }
 
-    info!("Initial queue length: {}", queue.len());
+    /// Stop the crawl for `reason`, unless it's already stopping for another one.
+    fn stop(&self, reason: report::StopReason) {
+        self.stop_reason.lock().unwrap().get_or_insert(reason);
+        self.options.controller.stop();
+    }
 
-    let (tx, mut rx) = mpsc::channel::<Arc<String>>(10);
+    /// Add the `bytes` of a body to the bandwidth budget of the run, stopping it once it
+    /// received `RunOptions::max_bytes`.
+    fn check_budget(&self, bytes: u64) {
+        if let Some(bandwidth) = &self.bandwidth {
+            let rate = bandwidth.bytes_per_hour();
+            match bandwidth.record(bytes, Instant::now()) {
+                Some(true) => info!(
+                    "Receiving more than {} KiB per hour, spacing the requests out",
+                    rate / 1024
+                ),
+                Some(false) => info!("Back under {} KiB per hour", rate / 1024),
+                None => {}
+            }
+        }
+        if self.over_budget() && !self.options.controller.is_stopped() {
+            info!(
+                "Reached max bytes ({} KiB received), stopping",
+                self.stats.bytes_received() / 1024
+            );
+            self.stop(report::StopReason::MaxBytes);
+        }
+    }
 
-    {
-        let mut extracted = EXTRACTED_MUTEX.lock().unwrap();
-        *extracted = u64::from(storage.results_count().await?);
+    /// Whether the run received its `RunOptions::max_bytes`.
+    fn over_budget(&self) -> bool {
+        self.options
+            .max_bytes
+            .is_some_and(|max| self.stats.bytes_received() >= max)
     }
 
-    let tx_clone = tx.clone();
+    /// Hold `url`, when given, in memory when it was left unhandled for `e`, the storage
+    /// taking no writes, pausing the crawl until it does again. Whether it's held.
+    fn hold_on_outage(&self, url: Option<&str>, e: &CrawlerError) -> bool {
+        if !matches!(e, CrawlerError::StorageUnavailable(_)) {
+            return false;
+        }
+        let controller = &self.options.controller;
+        if self.outage.hold(url, controller.is_paused()) {
+            warn!("{}, pausing the crawl until it takes writes again", e);
+            controller.pause();
+        }
+        true
+    }
 
-    let storage_clone = storage.clone();
-    tokio::spawn(async move {
-        loop {
-            let in_progress = storage_clone.running_count().await.unwrap();
-            if in_progress < MAX_IN_PROGRESS {
-                for url in storage_clone
-                    .queued_get_n(MAX_IN_PROGRESS - in_progress)
-                    .await
-                    .unwrap()
-                {
-                    tx_clone.send(Arc::new(url)).await.unwrap();
+    /// Add an article crawled, `warned` or extracted, raising the `QualityAlert` of the run
+    /// when too many of the last ones were warned.
+    fn check_quality(&self, warned: bool) {
+        let (alert, ratio) = match &self.quality {
+            Some(monitor) => match monitor.record(warned) {
+                Some(ratio) => (monitor.alert(), ratio),
+                None => return,
+            },
+            None => return,
+        };
+        warn!(
+            "Quality alert: {:.0}% of the last {} articles warned",
+            ratio * 100.0,
+            alert.window
+        );
+        if let Some(observer) = &self.options.observer {
+            observer.on_quality_alert(ratio, alert.window);
+        }
+        if alert.pause {
+            warn!("Pausing the crawl until it's resumed");
+            self.options.controller.pause();
+        }
+        if let Some(webhook) = &alert.webhook {
+            let payload = quality::AlertPayload {
+                ratio,
+                window: alert.window,
+                threshold: alert.threshold,
+                worker_id: self.options.worker_id.clone(),
+                paused: alert.pause,
+            };
+            let request = self
+                .client
+                .post(webhook.clone())
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(serde_json::to_vec(&payload).unwrap());
+            let webhook = webhook.clone();
+            tokio::spawn(async move {
+                if let Err(e) = request.send().await.and_then(|r| r.error_for_status()) {
+                    warn!(
+                        "Failed to call the quality alert webhook {}: {:?}",
+                        webhook, e
+                    );
                 }
-            }
-            tokio::time::sleep(Duration::from_millis(1000)).await;
+            });
         }
-    });
+    }
 
-    while let Some(url) = rx.recv().await {
-        if storage.running_is_exists(url.as_str()).await? {
-            storage.queued_delete(url.as_str()).await?;
-        } else if storage.visited_is_exists(url.as_str()).await? {
-            storage.queued_delete(url.as_str()).await?;
-        } else {
-            let storage_clone = Arc::clone(&storage);
-            let crawler_clone = Arc::clone(&crawler);
-            tokio::spawn(handle(url, crawler_clone, storage_clone));
+    /// Add the time `url` took to crawl, into `paragraphs`, warning when it's past
+    /// `slow_parse`.
+    fn record_parse(&self, url: &str, duration: Duration, paragraphs: usize) {
+        let timing = ParseTiming {
+            url: url.to_string(),
+            duration_ms: duration.as_millis() as u64,
+            paragraphs,
+        };
+        self.stats.record_parse(&timing);
+        let slow = self.options.slow_parse.is_some_and(|max| duration > max);
+        if slow {
+            warn!(
+                "Slow parse of {}: {:?}, {} paragraphs",
+                url, duration, paragraphs
+            );
+        }
+        if let Some(observer) = &self.options.observer {
+            observer.on_parsed(&timing, slow);
         }
     }
 
-    Ok(())
+    /// Whether `url` is left out of the queue while the crawl is throttled.
+    fn is_throttled(&self, url: &str) -> bool {
+        self.throttled.load(Ordering::Relaxed)
+            && self
+                .options
+                .queue_throttle
+                .as_ref()
+                .is_some_and(|throttle| !throttle.is_article(url))
+    }
+
+    /// Get the body of `url`, at least `request_delay` after the previous request, or the
+    /// delay drawn by the `Politeness` of the run, stretched while its host is backed off.
+    async fn fetch(&self, url: &str) -> Result<String, CrawlerError> {
+        Ok(self.fetch_body(url).await?.text)
+    }
+
+    /// `fetch`, keeping the headers the crawl acts on. The hosts the politeness is disabled
+    /// for are fetched right away, without taking the turn of the others.
+    async fn fetch_body(&self, url: &str) -> Result<client::Body, CrawlerError> {
+        let parsed = Url::parse(url).ok();
+        let host = parsed
+            .as_ref()
+            .and_then(|url| url.host_str().map(str::to_string));
+        let host = host.as_deref().unwrap_or_default();
+        let polite = !parsed
+            .as_ref()
+            .is_some_and(|url| self.options.is_politeness_disabled(url));
+        let mut last_request_mutex = None;
+        if polite {
+            // Waiting out the coffee break of the host before taking the turn of another one
+            if let Some(pacer) = &self.pacer {
+                let pause = pacer.before_request(host, Instant::now());
+                if !pause.is_zero() {
+                    debug!("Pausing {} for {:?}", host, pause);
+                    tokio::time::sleep(pause).await;
+                }
+            }
+            let pause = self.backoffs.pause(host, utils::get_now());
+            if !pause.is_zero() {
+                debug!("Backing off {} for {:?}", host, pause);
+                tokio::time::sleep(pause).await;
+            }
+
+            let mut last_request = self.last_request.lock().await;
+            if let Some(last_request) = last_request.take() {
+                let mut delay = match &self.pacer {
+                    Some(pacer) => pacer.delay(),
+                    None => self.options.request_delay,
+                } * self.backoffs.multiplier(host);
+                if let Some(bandwidth) = &self.bandwidth {
+                    delay = delay.mul_f64(bandwidth.slowdown(Instant::now()));
+                }
+                let duration = Instant::now().duration_since(last_request);
+                if duration < delay {
+                    tokio::time::sleep(delay - duration).await;
+                }
+            }
+            last_request_mutex = Some(last_request);
+        }
+        let now = Instant::now();
+
+        debug!("Visit {}", url);
+        let max_size = self.options.max_decompressed_size;
+        let body = match &self.proxies {
+            Some(proxies) => proxies.get(url, max_size).await,
+            None => client::get_text(&self.client, url, max_size).await,
+        };
+        if let Some(mut last_request) = last_request_mutex {
+            last_request.replace(now);
+        }
+        let body = body?;
+        self.stats.inc_fetched(host);
+        self.stats.add_bytes(body.wire_bytes, body.decoded_bytes);
+        self.check_budget(body.wire_bytes);
+        if body.transcoded {
+            self.stats.inc_transcoded();
+        }
+        self.stats
+            .record_latency(host, now.elapsed(), body.first_byte);
+        Ok(body)
+    }
 }
 
-async fn handle<C, S>(
-    url: Arc<String>,
-    crawler: Arc<C>,
-    storage: Arc<S>,
-) -> Result<(), CrawlerError>
+/// Crawl from the queue of `storage`, or from `initial_queue` when it's empty, until the
+/// crawl is stopped, returning the report of the session.
+pub async fn run_scrapper<C, S>(
+    crawler: C,
+    storage: S,
+    initial_queue: Vec<String>,
+    options: RunOptions,
+) -> Result<report::CrawlReport, CrawlerError>
 where
-    C: Crawler,
-    S: Storage<Record = C::Document>,
+    C: AsyncCrawler + Send + Sync + 'static,
+    S: Storage<Record = C::Document> + Sync + Send + 'static,
 {
-    let url = url.as_str();
+    let storage = Arc::new(storage);
+    let crawler = Arc::new(crawler);
+    let controller = options.controller.clone();
+    let results_before = u64::from(storage.results_count().await?);
+    let state = Arc::new(RunState::new(options, results_before)?);
+    if !state.options.disable_politeness_for_hosts.is_empty() {
+        warn!(
+            "Politeness disabled for {}: fetched without delays, robots directives ignored",
+            state.options.disable_politeness_for_hosts.join(", ")
+        );
+    }
+    if let Some(canary) = &state.options.canary {
+        check_canary(crawler.as_ref(), &state, canary).await?;
+    }
 
-    storage.running_insert(url).await?;
-    storage.queued_delete(url).await?;
+    // Urls left running by a process that stopped without releasing them, this one before
+    // a crash or another worker of the database
+    let stale_before = utils::get_now() - heartbeat_timeout(&state.options);
+    let reaped = storage.running_reap(stale_before).await?;
+    debug!(
+        "Queued again {} urls left running, {} still running",
+        reaped.len(),
+        storage.running_count().await?
+    );
 
-    let html = {
-        let mut last_request_mutex = LAST_REQUEST_MUTEX.lock().await;
-        let last_request = last_request_mutex.take();
-        let now = Instant::now();
-        if let Some(last_request) = last_request {
-            let duration = now.duration_since(last_request);
-            if duration < *REQUEST_DELAY {
-                tokio::time::sleep(*REQUEST_DELAY - duration).await;
+    if storage.queued_is_empty().await? {
+        storage.queued_insert_batch(&initial_queue).await?;
+    }
+
+    info!(
+        "Initial queue length: {} (session {})",
+        storage.queued_count().await?,
+        state.stats.session_id()
+    );
+
+    #[cfg(feature = "http-api")]
+    let server = match state.options.status_addr {
+        Some(addr) => Some(tokio::spawn(server::serve(
+            addr,
+            Arc::clone(&storage),
+            Arc::clone(&state),
+        )?)),
+        None => None,
+    };
+
+    let (tx, mut rx) = mpsc::channel::<(QueuedUrl, OwnedSemaphorePermit)>(10);
+
+    // A permit for each url claimed and not yet parsed by this worker: fetching, waiting for
+    // a parser or being parsed
+    let max_parsing = state.options.max_parsing.max(1) as usize;
+    let permits = state.options.max_fetching.max(1) as usize + 2 * max_parsing;
+    let in_progress = Arc::new(Semaphore::new(permits));
+    let storage_clone = storage.clone();
+    let state_clone = state.clone();
+    let dispatcher = tokio::spawn(async move {
+        let controller = &state_clone.options.controller;
+        let worker_id = state_clone.options.worker_id.as_str();
+        while !controller.is_stopped() && !tx.is_closed() {
+            let available = in_progress.available_permits() as u32;
+            if !controller.is_paused() && available > 0 {
+                let urls = match storage_clone.queued_claim_n(worker_id, available).await {
+                    Ok(urls) => urls,
+                    Err(e) => {
+                        if !state_clone.hold_on_outage(None, &e) {
+                            warn!("Failed to claim queued urls: {:?}", e);
+                        }
+                        vec![]
+                    }
+                };
+                // Nothing in flight here, the urls claimed holding a permit until they're
+                // parsed, and nothing left to claim
+                if urls.is_empty()
+                    && available as usize == permits
+                    && state_clone.options.exits_when_idle()
+                {
+                    match is_idle(storage_clone.as_ref()).await {
+                        Ok(true) => {
+                            info!("Nothing left queued nor running, stopping");
+                            state_clone.stop(report::StopReason::Idle);
+                            return;
+                        }
+                        Ok(false) => {}
+                        Err(e) => warn!("Failed to check whether the crawl is idle: {:?}", e),
+                    }
+                }
+                for queued in urls {
+                    let permit = Arc::clone(&in_progress).acquire_owned().await.unwrap();
+                    if tx.send((queued, permit)).await.is_err() {
+                        // The fetch stage is done, what's left is released with the other
+                        // claims
+                        debug!("Dispatcher stopped: the fetch stage is done");
+                        return;
+                    }
+                }
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_millis(1000)) => {}
+                _ = controller.stopped() => {}
             }
         }
+    });
 
-        debug!("Visit {}", url);
-        let html = reqwest::get(url).await.unwrap().text().await.unwrap();
+    let storage_clone = storage.clone();
+    let state_clone = state.clone();
+    tokio::spawn(async move {
+        let controller = &state_clone.options.controller;
+        let worker_id = state_clone.options.worker_id.as_str();
+        let timeout = heartbeat_timeout(&state_clone.options);
+        while !controller.is_stopped() {
+            let now = utils::get_now();
+            if let Err(e) = storage_clone.running_touch(worker_id, now).await {
+                warn!("Failed to send the heartbeat of {}: {:?}", worker_id, e);
+            }
+            match storage_clone.running_reap(now - timeout).await {
+                Ok(urls) if !urls.is_empty() => {
+                    info!("Queued again {} urls of a stopped worker", urls.len())
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Failed to queue the urls of stopped workers: {:?}", e),
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(state_clone.options.heartbeat_timeout / 4) => {}
+                _ = controller.stopped() => {}
+            }
+        }
+    });
+
+    let storage_clone = storage.clone();
+    let state_clone = state.clone();
+    tokio::spawn(async move {
+        let controller = &state_clone.options.controller;
+        let worker_id = state_clone.options.worker_id.as_str();
+        let policy = state_clone.options.storage_outage;
+        while !controller.is_stopped() {
+            tokio::select! {
+                _ = state_clone.outage.started() => {}
+                _ = controller.stopped() => return,
+            }
+            let mut failed = 0;
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(policy.canary_interval) => {}
+                    _ = controller.stopped() => return,
+                }
+                match outage::canary_write(storage_clone.as_ref(), worker_id).await {
+                    Ok(()) => break,
+                    Err(e) => {
+                        failed += 1;
+                        warn!("Canary write {} failed: {:?}", failed, e);
+                        if failed >= policy.max_failed_canaries {
+                            warn!(
+                                "Storage unavailable after {} canary writes, stopping",
+                                failed
+                            );
+                            state_clone.stop(report::StopReason::StorageUnavailable);
+                            return;
+                        }
+                    }
+                }
+            }
+            let held = match state_clone.outage.end() {
+                Some(held) => held,
+                None => continue,
+            };
+            for url in &held.urls {
+                // Left running otherwise, queued again once the worker is reaped
+                if let Err(e) = storage_clone.running_requeue(url).await {
+                    warn!("Failed to queue {} again: {:?}", url, e);
+                }
+            }
+            info!(
+                "Storage available again, resuming with {} urls queued again",
+                held.urls.len()
+            );
+            if !held.was_paused {
+                controller.resume();
+            }
+        }
+    });
+
+    if !state.options.retry_policy.is_empty() {
+        let storage_clone = storage.clone();
+        let state_clone = state.clone();
+        tokio::spawn(async move {
+            let controller = &state_clone.options.controller;
+            while !controller.is_stopped() {
+                let now = utils::get_now();
+                match retry::requeue_due_at(storage_clone.as_ref(), &state_clone.retrying, now)
+                    .await
+                {
+                    Ok(due) if !due.is_empty() => info!("Retrying {} warned urls", due.len()),
+                    Ok(_) => {}
+                    Err(e) => warn!("Failed to queue the warned urls due again: {:?}", e),
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(retry::CHECK_INTERVAL) => {}
+                    _ = controller.stopped() => {}
+                }
+            }
+        });
+    }
+
+    // The hub pages are fetched again along with the articles
+    let revisit_every = [
+        state.options.revisit_after,
+        state
+            .options
+            .hub_recrawl
+            .map(|recrawl| recrawl.min_interval),
+    ]
+    .into_iter()
+    .flatten()
+    .min();
+    if let Some(revisit_every) = revisit_every {
+        let storage_clone = storage.clone();
+        let state_clone = state.clone();
+        tokio::spawn(async move {
+            let controller = &state_clone.options.controller;
+            while !controller.is_stopped() {
+                let now = utils::get_now();
+                match revisit::requeue_due_at(storage_clone.as_ref(), &state_clone.revisiting, now)
+                    .await
+                {
+                    Ok(due) if !due.is_empty() => info!("Revisiting {} pages", due.len()),
+                    Ok(_) => {}
+                    Err(e) => warn!("Failed to queue the articles due again: {:?}", e),
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(revisit::check_interval(revisit_every)) => {}
+                    _ = controller.stopped() => {}
+                }
+            }
+        });
+    }
+
+    let storage_clone = storage.clone();
+    let state_clone = state.clone();
+    tokio::spawn(async move {
+        let controller = &state_clone.options.controller;
+        let throttle = state_clone.options.queue_throttle.as_ref();
+        while !controller.is_stopped() {
+            let now = utils::get_now();
+            match throttle::check_at(
+                storage_clone.as_ref(),
+                throttle,
+                &state_clone.throttled,
+                now,
+            )
+            .await
+            {
+                Ok(queue) => info!(
+                    "Queue: {} urls, the oldest waiting for {}s",
+                    queue.queued,
+                    queue.oldest_age.map_or(0, |age| age.num_seconds())
+                ),
+                Err(e) => warn!("Failed to check the queue: {:?}", e),
+            }
+            if let Some(bandwidth) = &state_clone.bandwidth {
+                info!(
+                    "Bandwidth: {} KiB received, {} KiB per hour lately (target {} KiB)",
+                    state_clone.stats.bytes_received() / 1024,
+                    bandwidth.hourly_rate(Instant::now()) / 1024,
+                    bandwidth.bytes_per_hour() / 1024
+                );
+            }
+            let latency = state_clone.stats.latency();
+            if latency.fetch.count > 0 {
+                info!(
+                    "Fetch latency: p50 {}ms, p95 {}ms, p99 {}ms (first byte p50 {}ms, p95 {}ms, \
+                     p99 {}ms)",
+                    latency.fetch.p50_ms,
+                    latency.fetch.p95_ms,
+                    latency.fetch.p99_ms,
+                    latency.first_byte.p50_ms,
+                    latency.first_byte.p95_ms,
+                    latency.first_byte.p99_ms
+                );
+                for (host, latency) in state_clone.stats.latencies() {
+                    debug!(
+                        "Fetch latency of {}: p50 {}ms, p95 {}ms, p99 {}ms over {} pages",
+                        host,
+                        latency.fetch.p50_ms,
+                        latency.fetch.p95_ms,
+                        latency.fetch.p99_ms,
+                        latency.fetch.count
+                    );
+                }
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(throttle::CHECK_INTERVAL) => {}
+                _ = controller.stopped() => {}
+            }
+        }
+    });
 
-        last_request_mutex.replace(now);
-        html
+    // The parse stage, taking the pages fetched in the order they came and keeping the
+    // fetchers waiting once `max_parsing` pages are parsed and as many wait
+    let (parse_tx, mut parse_rx) = mpsc::channel::<(Fetched, OwnedSemaphorePermit)>(max_parsing);
+    let crawler_clone = Arc::clone(&crawler);
+    let storage_clone = Arc::clone(&storage);
+    let state_clone = Arc::clone(&state);
+    let parser = tokio::spawn(async move {
+        let parsing = Arc::new(Semaphore::new(max_parsing));
+        let mut parses = JoinSet::new();
+        loop {
+            let (fetched, permit) = tokio::select! {
+                fetched = parse_rx.recv() => match fetched {
+                    Some(fetched) => fetched,
+                    None => break,
+                },
+                Some(result) = parses.join_next() => {
+                    log_task_result(&state_clone.stats, result);
+                    continue;
+                }
+            };
+            let parser = Arc::clone(&parsing).acquire_owned().await.unwrap();
+            let crawler = Arc::clone(&crawler_clone);
+            let storage = Arc::clone(&storage_clone);
+            let state = Arc::clone(&state_clone);
+            parses.spawn(async move {
+                let url = fetched.url.clone();
+                let result = match parse(fetched, &*crawler, storage.as_ref(), &state).await {
+                    Err(e) if state.hold_on_outage(Some(&url), &e) => Ok(()),
+                    result => result,
+                };
+                drop(parser);
+                drop(permit);
+                result
+            });
+        }
+        while let Some(result) = parses.join_next().await {
+            log_task_result(&state_clone.stats, result);
+        }
+    });
+
+    // The fetch stage
+    let fetching = Arc::new(Semaphore::new(state.options.max_fetching.max(1) as usize));
+    let mut tasks = JoinSet::new();
+    loop {
+        let (queued, permit) = tokio::select! {
+            queued = rx.recv() => match queued {
+                Some(queued) => queued,
+                None => break,
+            },
+            Some(result) = tasks.join_next() => {
+                log_task_result(&state.stats, result);
+                continue;
+            }
+            _ = controller.stopped() => break,
+        };
+
+        let url = &*queued.url;
+        let page_url = match admit(crawler.as_ref(), storage.as_ref(), &state, url).await {
+            Ok(Some(page_url)) => page_url,
+            Ok(None) => continue,
+            Err(e) if state.hold_on_outage(Some(url), &e) => continue,
+            Err(e) => return Err(e),
+        };
+        let storage_clone = Arc::clone(&storage);
+        let state_clone = Arc::clone(&state);
+        let fetching = Arc::clone(&fetching);
+        let parse_tx = parse_tx.clone();
+        tasks.spawn(async move {
+            let fetcher = fetching.acquire_owned().await.unwrap();
+            let url = queued.url.clone();
+            let fetched = match fetch(queued, page_url, storage_clone.as_ref(), &state_clone).await
+            {
+                Err(e) if state_clone.hold_on_outage(Some(&url), &e) => return Ok(()),
+                fetched => fetched?,
+            };
+            if let Some(fetched) = fetched {
+                // Only when the parse stage failed, the url is then released as claimed
+                let _ = parse_tx.send((fetched, permit)).await;
+            }
+            drop(fetcher);
+            Ok(())
+        });
+    }
+
+    // Make sure every task of this run sees the end of the crawl, then let the urls
+    // in flight finish and give back the ones claimed but not handled
+    state.stop(report::StopReason::Stopped);
+    drop(rx);
+    if let Err(e) = dispatcher.await {
+        warn!("Dispatcher task failed: {}", e);
+        state.stats.inc_failed_tasks();
+    }
+    while let Some(result) = tasks.join_next().await {
+        log_task_result(&state.stats, result);
+    }
+    drop(parse_tx);
+    if let Err(e) = parser.await {
+        warn!("Parser task failed: {}", e);
+        state.stats.inc_failed_tasks();
+    }
+    // Left running when the storage takes no writes, queued again once the worker is reaped
+    let released = match storage.running_release(&state.options.worker_id).await {
+        Ok(released) => released,
+        Err(CrawlerError::StorageUnavailable(e)) => {
+            warn!(
+                "Failed to release the urls claimed, {} held: storage unavailable: {}",
+                state.outage.held(),
+                e
+            );
+            vec![]
+        }
+        Err(e) => return Err(e),
     };
+    // Stored when they change as well, in case the process is killed
+    for backoff in state.backoffs.states() {
+        match storage.host_state_put(&backoff).await {
+            Ok(()) | Err(CrawlerError::StorageUnavailable(_)) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    debug!(
+        "Queued again {} urls claimed but not handled",
+        released.len()
+    );
+    info!(
+        "Crawl session {} stopped after {:?}: {} fetched ({} bytes received, {} decompressed), \
+         {} extracted, {} warned, {} rejected, {} noindex",
+        state.stats.session_id(),
+        state.stats.uptime(),
+        state.stats.fetched(),
+        state.stats.bytes_received(),
+        state.stats.bytes_decoded(),
+        state.stats.extracted(),
+        state.stats.warned(),
+        state.stats.rejected(),
+        state.stats.noindex()
+    );
+    if let Some(dns) = &state.dns {
+        let stats = dns.stats();
+        info!(
+            "DNS cache: {} hits, {} misses, {} failures, {} urls requeued",
+            stats.hits, stats.misses, stats.failures, stats.requeued
+        );
+    }
+    if let Some(path) = &state.options.cookie_jar {
+        state.cookies.save(path)?;
+    }
+    let stop_reason = state
+        .stop_reason
+        .lock()
+        .unwrap()
+        .unwrap_or(report::StopReason::Stopped);
+    let report = report::CrawlReport::new(
+        &state.stats,
+        &state.options.worker_id,
+        stop_reason,
+        utils::get_now(),
+    );
+    if let Some(path) = &state.options.report_path {
+        report.write(path).await;
+    }
+
+    #[cfg(feature = "http-api")]
+    if let Some(server) = server {
+        if let Err(e) = server.await {
+            warn!("Status server task failed: {}", e);
+        }
+    }
+
+    Ok(report)
+}
+
+fn log_task_result(stats: &CrawlStats, result: Result<Result<(), CrawlerError>, JoinError>) {
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => warn!("Failed to handle url: {:?}", e),
+        Err(e) => {
+            warn!("Handle task failed: {}", e);
+            stats.inc_failed_tasks();
+        }
+    }
+}
+
+/// `RunOptions::heartbeat_timeout` as the time between two timestamps.
+fn heartbeat_timeout(options: &RunOptions) -> chrono::Duration {
+    chrono::Duration::from_std(options.heartbeat_timeout)
+        .unwrap_or_else(|_| chrono::Duration::max_value())
+}
+
+/// Queue the valid `links` the crawler fetches, counting the rejected ones, see
+/// `Crawler::should_fetch`. Only the article links are queued while the crawl is throttled.
+/// The links of a hub not in its `previous` snapshot are queued with `NEW_LINK_PRIORITY`.
+async fn enqueue_links<C, S>(
+    crawler: &C,
+    storage: &S,
+    state: &RunState,
+    links: Vec<String>,
+    referrer: &str,
+    depth: u32,
+    previous: Option<&recrawl::LinkSnapshot>,
+) -> Result<u32, CrawlerError>
+where
+    C: AsyncCrawler + ?Sized,
+    S: Storage + Sync,
+{
+    let mut new_links = 0;
+    for link in links {
+        match utils::validate_crawl_url(&link) {
+            Ok(_) if state.is_throttled(&link) => debug!("Throttled link {}", link),
+            Ok(url) => match crawler.should_fetch(&url) {
+                FetchDecision::Fetch => match previous {
+                    Some(previous) if previous.is_new(&link) => {
+                        new_links += 1;
+                        storage
+                            .queued_insert_prioritized(
+                                link,
+                                Some(referrer),
+                                depth,
+                                recrawl::NEW_LINK_PRIORITY,
+                            )
+                            .await?
+                    }
+                    _ => {
+                        // Only looked up for the hub pages recrawled without a snapshot, to
+                        // spare the lookups otherwise
+                        if previous.is_none()
+                            && state.options.hub_recrawl.is_some()
+                            && !is_seen(storage, &link).await?
+                        {
+                            new_links += 1;
+                        }
+                        storage
+                            .queued_insert_with_meta(link, Some(referrer), depth)
+                            .await?
+                    }
+                },
+                FetchDecision::SkipPermanently => {
+                    debug!("Vetoed link {}", link);
+                    storage.visited_insert(link).await?
+                }
+                FetchDecision::SkipEnqueueOnly => debug!("Vetoed link {} for now", link),
+            },
+            Err(rejection) => {
+                debug!("Rejected link {}: {}", link, rejection);
+                state.stats.inc_rejected();
+            }
+        }
+    }
+    Ok(new_links)
+}
+
+/// Whether nothing is queued nor running, by any worker.
+async fn is_idle<S: Storage + ?Sized>(storage: &S) -> Result<bool, CrawlerError> {
+    Ok(storage.queued_is_empty().await? && storage.running_count().await? == 0)
+}
+
+/// Whether `url` is already queued, running or visited.
+async fn is_seen<S: Storage + Sync>(storage: &S, url: &str) -> Result<bool, CrawlerError> {
+    Ok(storage.visited_is_exists(url).await?
+        || storage.queued_is_exists(url).await?
+        || storage.running_is_exists(url).await?)
+}
 
-    let result = {
-        let doc = Html::parse_document(&html);
-        crawler.crawl(&doc)
+/// The warned reason of a fetch failing with `e` when it may work later on.
+fn transient_reason(e: &CrawlerError) -> Option<&'static str> {
+    match e {
+        CrawlerError::HttpError(e) if e.is_timeout() => Some(TIMEOUT),
+        CrawlerError::ServerStatus(_) => Some(HTTP_5XX),
+        _ => None,
+    }
+}
+
+/// Get the body of `url`, a running url of `page_url`, within the backoff of its host. The
+/// failures a later attempt may get past leave the url done with for now, `None`: queued
+/// again while its host is backed off, or warned and retried later.
+async fn fetch_running<S: Storage + Sync>(
+    storage: &S,
+    state: &RunState,
+    url: &str,
+    page_url: &Url,
+) -> Result<Option<client::Body>, CrawlerError> {
+    let host = page_url.host_str().unwrap_or_default();
+    state.backoffs.load(storage, host, utils::get_now()).await?;
+    let body = match state.fetch_body(url).await {
+        Err(CrawlerError::TooManyRequests(retry_after)) => {
+            // Left for later like an unresolved host, the site answering again once it's
+            // backed off from
+            let backoff = state
+                .backoffs
+                .throttled(host, retry_after, utils::get_now());
+            warn!(
+                "Requeued {}: too many requests, backing off {} until {} ({}x delay)",
+                url, host, backoff.resume_at, backoff.multiplier
+            );
+            storage.host_state_put(&backoff).await?;
+            storage.running_requeue(url).await?;
+            return Ok(None);
+        }
+        Err(CrawlerError::TooLarge(limit)) => {
+            warn!("Skipped {}: larger than {} bytes", url, limit);
+            storage.warned_insert(url, TOO_LARGE).await?;
+            state.stats.inc_warned(TOO_LARGE);
+            storage.running_delete(url).await?;
+            return Ok(None);
+        }
+        Err(e) => match transient_reason(&e) {
+            Some(reason) => {
+                let policy = &state.options.retry_policy;
+                let now = utils::get_now();
+                match retry::warn_at(storage, policy, url, reason, now).await? {
+                    Some(at) => warn!("Skipped {}: {}, retrying at {}", url, e, at),
+                    None => warn!("Skipped {}: {}", url, e),
+                }
+                state.stats.inc_warned(reason);
+                storage.running_delete(url).await?;
+                return Ok(None);
+            }
+            None => return Err(e),
+        },
+        Ok(body) => body,
     };
+    if let Some(backoff) = state.backoffs.recovered(host, utils::get_now()) {
+        debug!("Easing off {}: {}x delay", host, backoff.multiplier);
+        storage.host_state_put(&backoff).await?;
+    }
+    Ok(Some(body))
+}
+
+/// Follow the meta refresh redirects from `html`, the page at `url`, like HTTP redirects:
+/// each page redirecting is visited and the page it redirects to fetched in its place. The
+/// url, parsed url and body of the last page, `None` when it's already crawled, the hops
+/// ran out or a page failed to be fetched, the pages left running then being done with.
+async fn follow_refreshes<S: Storage + Sync>(
+    storage: &S,
+    state: &RunState,
+    url: &Arc<str>,
+    mut page_url: Url,
+    mut body: client::Body,
+) -> Result<Option<(Arc<str>, Url, client::Body)>, CrawlerError> {
+    let mut url = Arc::clone(url);
+    for hop in 0.. {
+        let target = match utils::meta_refresh(&body.text, &page_url) {
+            Some(target) if target != page_url => target,
+            _ => break,
+        };
+        if hop == MAX_REFRESH_HOPS {
+            warn!("Skipped {}: more than {} meta refreshes", url, hop);
+            storage.warned_insert(&url, REFRESH_LOOP).await?;
+            state.stats.inc_warned(REFRESH_LOOP);
+            storage.running_delete(&url).await?;
+            return Ok(None);
+        }
+        debug!("Meta refresh from {} to {}", url, target);
+        storage.visited_insert(&url).await?;
+        storage.running_delete(&url).await?;
 
+        url = utils::link_url(target.clone()).into();
+        if !storage
+            .running_claim(&url, &state.options.worker_id)
+            .await?
+        {
+            return Ok(None);
+        }
+        body = match fetch_running(storage, state, &url, &target).await? {
+            Some(body) => body,
+            None => return Ok(None),
+        };
+        page_url = target;
+    }
+    Ok(Some((url, page_url, body)))
+}
+
+/// Fetch the `canary` article and check the crawler still extracts it, failing when it
+/// doesn't unless the canary only logs.
+async fn check_canary<C: AsyncCrawler + Sync>(
+    crawler: &C,
+    state: &RunState,
+    canary: &health::Canary,
+) -> Result<(), CrawlerError> {
+    let checked = async {
+        let html = state.fetch(&canary.url).await?;
+        let url = Url::parse(&canary.url)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        let ctx = FetchContext {
+            url: &url,
+            truncated: false,
+            response: None,
+            state,
+        };
+        Ok::<_, CrawlerError>(crawler.health_check(&ctx, &html).await)
+    };
+    let report = match checked.await {
+        Ok(report) if report.is_healthy() => {
+            info!("Canary {} is healthy: {}", canary.url, report);
+            return Ok(());
+        }
+        Ok(report) => report,
+        Err(e) if canary.abort => return Err(e),
+        Err(e) => {
+            warn!("Failed to fetch the canary {}: {:?}", canary.url, e);
+            return Ok(());
+        }
+    };
+    if canary.abort {
+        return Err(CrawlerError::UnhealthyCanary(canary.url.clone(), report));
+    }
+    warn!("Canary {} is unhealthy: {}", canary.url, report);
+    Ok(())
+}
+
+/// Crawl `body`, fetched from `page_url`: only its start up to `max_parse_size` is parsed,
+/// and its links are dropped above `max_link_extraction_size`.
+async fn crawl_body<C: AsyncCrawler>(
+    crawler: &C,
+    state: &RunState,
+    page_url: &Url,
+    body: &client::Body,
+) -> CrawlerResult<C::Document> {
+    let html = body.text.as_str();
+    let size = html.len() as u64;
+    let parsed = match state.options.max_parse_size {
+        Some(max) if size > max => {
+            debug!("Parsing {} of the {} bytes of {}", max, size, page_url);
+            state.stats.inc_truncated();
+            utils::truncate_html(html, max as usize)
+        }
+        _ => html,
+    };
+    let ctx = FetchContext {
+        url: page_url,
+        truncated: parsed.len() < html.len(),
+        response: Some(&body.response),
+        state,
+    };
+    let result = crawler.crawl(&ctx, parsed).await;
+    if state
+        .options
+        .max_link_extraction_size
+        .is_some_and(|max| size > max)
+    {
+        debug!("Not following the links of {}: {} bytes", page_url, size);
+        return result.without_links();
+    }
+    result
+}
+
+/// A page downloaded by the fetch stage, waiting to be parsed.
+struct Fetched {
+    queued: QueuedUrl,
+    /// The url of the page, the one `queued` redirects to through meta refreshes
+    url: Arc<str>,
+    page_url: Url,
+    body: client::Body,
+}
+
+/// The claimed `url` as the page to fetch, `None` when it's done with without fetching it:
+/// queued again while the crawl is paused, rejected or vetoed by the crawler.
+async fn admit<C, S>(
+    crawler: &C,
+    storage: &S,
+    state: &RunState,
+    url: &str,
+) -> Result<Option<Url>, CrawlerError>
+where
+    C: AsyncCrawler,
+    S: Storage + Sync,
+{
+    if state.options.controller.is_paused() {
+        // Claimed right before the pause, queued again
+        storage.running_requeue(url).await?;
+        return Ok(None);
+    }
+
+    let page_url = match utils::validate_crawl_url(url) {
+        Ok(page_url) => page_url,
+        Err(rejection) => {
+            warn!("Rejected {}: {}", url, rejection);
+            storage.running_delete(url).await?;
+            storage.warned_insert(url, rejection.reason()).await?;
+            state.stats.inc_rejected();
+            return Ok(None);
+        }
+    };
+    match crawler.should_fetch(&page_url) {
+        FetchDecision::Fetch => {}
+        FetchDecision::SkipPermanently => {
+            debug!("Not fetching {}: vetoed by the crawler", url);
+            storage.visited_insert(url).await?;
+            storage.running_delete(url).await?;
+            return Ok(None);
+        }
+        FetchDecision::SkipEnqueueOnly => {
+            debug!("Not fetching {} for now: vetoed by the crawler", url);
+            storage.running_delete(url).await?;
+            return Ok(None);
+        }
+    }
+    Ok(Some(page_url))
+}
+
+/// Fetch `url`, already validated as `page_url` by the dispatcher, `None` when it's done
+/// with already, e.g. warned for a failed request.
+async fn fetch<S: Storage + Sync>(
+    queued: QueuedUrl,
+    page_url: Url,
+    storage: &S,
+    state: &RunState,
+) -> Result<Option<Fetched>, CrawlerError> {
+    let url = &*queued.url;
+    // Left claimed, queued again once the crawl stopped
+    if state.over_budget() {
+        debug!("Not fetching {}: max bytes received", url);
+        return Ok(None);
+    }
+
+    if let (Some(dns), Some(host)) = (&state.dns, page_url.domain()) {
+        if let Err(e) = dns.lookup(host).await {
+            // Left for later rather than failed: the host is usually back within minutes
+            debug!("Requeued {}: {}", url, e);
+            dns.inc_requeued();
+            storage.running_requeue(url).await?;
+            return Ok(None);
+        }
+    }
+
+    let body = match fetch_running(storage, state, url, &page_url).await? {
+        Some(body) => body,
+        None => return Ok(None),
+    };
+    let (url, page_url, body) =
+        match follow_refreshes(storage, state, &queued.url, page_url, body).await? {
+            Some(page) => page,
+            None => return Ok(None),
+        };
+    if body.garbled {
+        warn!("Skipped {}: failed to decode its charset", url);
+        storage.warned_insert(&url, BAD_ENCODING).await?;
+        state.stats.inc_warned(BAD_ENCODING);
+        storage.running_delete(&url).await?;
+        return Ok(None);
+    }
+    Ok(Some(Fetched {
+        queued,
+        url,
+        page_url,
+        body,
+    }))
+}
+
+/// Crawl a fetched page and store what it's crawled into, done with its url once it returns.
+async fn parse<C, S>(
+    fetched: Fetched,
+    crawler: &C,
+    storage: &S,
+    state: &RunState,
+) -> Result<(), CrawlerError>
+where
+    C: AsyncCrawler,
+    S: Storage<Record = C::Document> + Sync,
+{
+    let Fetched {
+        queued,
+        url,
+        page_url,
+        mut body,
+    } = fetched;
+    let url = &*url;
+    let mut started = Instant::now();
+    let mut result = crawl_body(crawler, state, &page_url, &body).await;
+    if matches!(&result, CrawlerResult::Skipped(reason, _) if reason == INTERSTITIAL) {
+        debug!("Interstitial at {}, fetching it again", url);
+        body = match fetch_running(storage, state, url, &page_url).await? {
+            Some(body) => body,
+            None => return Ok(()),
+        };
+        started = Instant::now();
+        result = crawl_body(crawler, state, &page_url, &body).await;
+    }
+    let paragraphs = match &result {
+        CrawlerResult::DocumentAndLinks(doc, _) => doc.get_paragraphs().len(),
+        _ => 0,
+    };
+    state.record_parse(url, started.elapsed(), paragraphs);
+
+    if !state.options.ignore_robots && !state.options.is_politeness_disabled(&page_url) {
+        let robots = utils::robots(&body.text, body.robots_tag.as_deref());
+        if robots.noindex {
+            if let CrawlerResult::DocumentAndLinks(_, links) = result {
+                debug!("Not storing {}: noindex", url);
+                state.stats.inc_noindex();
+                result = CrawlerResult::Links(links.into());
+            }
+        }
+        if robots.nofollow {
+            debug!("Not following the links of {}: nofollow", url);
+            result = result.without_links();
+        }
+    }
+    if !state.options.follow_links {
+        result = result.without_links();
+    }
+
+    let revisit = state.revisited(url);
     match result {
         CrawlerResult::Links(links) => {
             storage.visited_insert(url).await?;
+            if state.retried(url) {
+                storage.warned_delete(url).await?;
+            }
 
-            for link in links {
-                let link = link.as_str();
-                if !storage.visited_is_exists(link).await?
-                    && !storage.running_is_exists(link).await?
-                    && !storage.queued_is_exists(link).await?
-                {
-                    storage.queued_insert(link).await?;
-                }
+            if let Some(recrawl) = &state.options.hub_recrawl {
+                // The links not on the hub when it was last crawled, rather than not seen
+                let previous = storage.hub_snapshot_get(url).await?;
+                let snapshot = recrawl::LinkSnapshot::new(&links);
+                let depth = queued.depth + 1;
+                let new_links = enqueue_links(
+                    crawler,
+                    storage,
+                    state,
+                    links,
+                    url,
+                    depth,
+                    previous.as_ref(),
+                )
+                .await?;
+                storage.hub_snapshot_put(url, &snapshot).await?;
+
+                let now = utils::get_now();
+                let hub = recrawl.update(storage.hub_get(url).await?.as_ref(), url, new_links, now);
+                debug!(
+                    "Hub {}: {} new links, {:.1} an hour, crawled again in {:?}",
+                    url, new_links, hub.rate, hub.interval
+                );
+                revisit::schedule_at(storage, url, hub.interval, now).await?;
+                storage.hub_put(&hub).await?;
+            } else {
+                enqueue_links(crawler, storage, state, links, url, queued.depth + 1, None).await?;
             }
         }
 
+        CrawlerResult::Skipped(reason, links) => {
+            warn!("Skipped {}: {}", url, reason);
+            storage.warned_insert(url, &reason).await?;
+            state.stats.inc_warned(&reason);
+            state.check_quality(true);
+
+            enqueue_links(crawler, storage, state, links, url, queued.depth + 1, None).await?;
+        }
+
         CrawlerResult::DocumentAndLinks(doc, links) => {
+            // The related links are queued first, so they are crawled before the others
+            let links = Vec::from(links);
+            let language = doc.get_language();
+            state.check_quality(doc.get_paragraphs().is_empty());
             if doc.get_paragraphs().is_empty() {
                 warn!("\nEmpty document extracted: {}\n", url);
+                // Kept when there's a title to tell what the article is about, still warned so
+                // it's crawled again
+                if state.options.keep_metadata_on_empty_body && doc.get_title().is_some() {
+                    storage
+                        .results_insert_metadata_only((url, doc), queued.referrer.as_deref())
+                        .await?;
+                }
                 // We dont insert to visited if there is warning
-                storage.warned_insert(url).await?;
+                storage.warned_insert(url, EMPTY_PARAGRAPHS).await?;
+                state.stats.inc_warned(EMPTY_PARAGRAPHS);
+            } else if !state.options.is_language_allowed(language.as_deref()) {
+                warn!("Skipped {}: language {:?}", url, language);
+                storage.warned_insert(url, LANGUAGE).await?;
+                state.stats.inc_warned(LANGUAGE);
+
+                enqueue_links(crawler, storage, state, links, url, queued.depth + 1, None).await?;
+            } else if revisit {
+                revise(storage, url, doc).await?;
+                storage.visited_insert(url).await?;
+
+                enqueue_links(crawler, storage, state, links, url, queued.depth + 1, None).await?;
             } else {
-                storage.results_insert((url, doc)).await?;
+                storage
+                    .results_insert_with_referrer((url, doc), queued.referrer.as_deref())
+                    .await?;
+                if let Some(revisit_after) = state.options.revisit_after {
+                    revisit::schedule_at(storage, url, revisit_after, utils::get_now()).await?;
+                }
                 storage.visited_insert(url).await?;
+                if state.retried(url) {
+                    storage.warned_delete(url).await?;
+                }
 
+                let extracted = state.stats.inc_extracted();
+                let received = state.stats.bytes_received() / 1024;
+                match state.options.max_bytes {
+                    Some(max) => info!(
+                        "[{}] Insert Result {} ({} of {} KiB received)",
+                        state.results_before + extracted,
+                        url,
+                        received,
+                        max / 1024
+                    ),
+                    None => info!(
+                        "[{}] Insert Result {} ({} KiB received)",
+                        state.results_before + extracted,
+                        url,
+                        received
+                    ),
+                }
+                if state
+                    .options
+                    .max_results
+                    .is_some_and(|max| extracted >= max)
                 {
-                    let mut num = EXTRACTED_MUTEX.lock().unwrap();
-                    info!("[{}] Insert Result {}", *num + 1, url);
-                    *num += 1;
-                }
-
-                for link in links {
-                    let link = link.as_str();
-                    if !storage.visited_is_exists(link).await?
-                        && !storage.running_is_exists(link).await?
-                        && !storage.queued_is_exists(link).await?
-                    {
-                        storage.queued_insert(link).await?;
-                    }
+                    info!("Reached max results ({}), stopping", extracted);
+                    state.stop(report::StopReason::MaxResults);
                 }
+
+                enqueue_links(crawler, storage, state, links, url, queued.depth + 1, None).await?;
             }
         }
     };
@@ -240,3 +2191,1434 @@ where
     storage.running_delete(url).await?;
     Ok(())
 }
+
+/// Store `doc`, the article of `url` fetched again, when it changed since it was stored.
+async fn revise<S: Storage + Sync>(
+    storage: &S,
+    url: &str,
+    doc: S::Record,
+) -> Result<(), CrawlerError> {
+    let stored = storage.results_get(url).await?;
+    let diff = match &stored {
+        Some(stored) => revisit::diff(stored, &ResultEntry::new(url, &doc)),
+        None => Some("stored again".to_string()),
+    };
+    match diff {
+        Some(diff) => {
+            info!("Revised {}: {}", url, diff);
+            storage.results_revise((url, doc), &diff).await
+        }
+        None => {
+            debug!("Unchanged {}", url);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(all(test, feature = "detik"))]
+mod tests {
+    use super::*;
+    use crate::detik::DetikArticle;
+    use crate::memory::MemoryStorage;
+    use crate::testing::{MockCrawler, RecordingStorage};
+    use axum::{http::Uri, Router};
+
+    fn article(paragraphs: &[&str]) -> DetikArticle {
+        DetikArticle {
+            title: Some("Title".to_string()),
+            published_date: None,
+            description: None,
+            thumbnail_url: None,
+            author: None,
+            desk: None,
+            category: None,
+            language: None,
+            keywords: vec![],
+            keywords_raw: None,
+            videos: vec![],
+            photos: vec![],
+            body_selector: None,
+            paragraphs: paragraphs.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    fn start_site() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().fallback(move |uri: Uri| async move {
+            axum::response::Html(MockCrawler::<DetikArticle>::page(&format!(
+                "http://{}{}",
+                addr, uri
+            )))
+        });
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+        format!("http://{}", addr)
+    }
+
+    fn state(options: RunOptions) -> Arc<RunState> {
+        Arc::new(RunState::new(options, 0).unwrap())
+    }
+
+    /// Both stages of a url, one after the other.
+    async fn handle<C, S>(
+        queued: QueuedUrl,
+        page_url: Url,
+        crawler: Arc<C>,
+        storage: Arc<S>,
+        state: Arc<RunState>,
+    ) -> Result<(), CrawlerError>
+    where
+        C: AsyncCrawler,
+        S: Storage<Record = C::Document> + Sync,
+    {
+        match fetch(queued, page_url, storage.as_ref(), &state).await? {
+            Some(fetched) => parse(fetched, crawler.as_ref(), storage.as_ref(), &state).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn handle_one<C>(
+        crawler: C,
+        url: &str,
+        options: RunOptions,
+    ) -> (RecordingStorage<MemoryStorage<DetikArticle>>, Arc<RunState>)
+    where
+        C: AsyncCrawler<Document = DetikArticle> + Send + Sync + 'static,
+    {
+        let storage = RecordingStorage::<_>::memory();
+        let state = state(options);
+        storage.queued_insert(url).await.unwrap();
+        let queued = storage
+            .queued_claim_n(&state.options.worker_id, 1)
+            .await
+            .unwrap();
+        storage.clear();
+        handle(
+            queued.into_iter().next().unwrap(),
+            Url::parse(url).unwrap(),
+            Arc::new(crawler),
+            Arc::new(storage.clone()),
+            Arc::clone(&state),
+        )
+        .await
+        .unwrap();
+        (storage, state)
+    }
+
+    fn link() -> String {
+        "https://news.detik.com/a".to_string()
+    }
+
+    fn methods(storage: &RecordingStorage<MemoryStorage<DetikArticle>>) -> Vec<&'static str> {
+        storage.calls().into_iter().map(|c| c.method).collect()
+    }
+
+    #[tokio::test]
+    async fn handle_links_page() {
+        let base = start_site();
+        let hub = format!("{}/hub", base);
+        let crawler = MockCrawler::new().with(
+            "/hub",
+            CrawlerResult::Links(vec![
+                "https://news.detik.com/a".to_string(),
+                "https://news.detik.com/b".to_string(),
+                "mailto:redaksi@detik.com".to_string(),
+            ]),
+        );
+        let (storage, state) = handle_one(crawler, &hub, RunOptions::default()).await;
+
+        assert_eq!(
+            methods(&storage),
+            vec![
+                "host_state_get",
+                "visited_insert",
+                "queued_insert_with_meta",
+                "queued_insert_with_meta",
+                "running_delete",
+            ]
+        );
+        assert_eq!(
+            storage.inner().queued_get().await.unwrap(),
+            vec!["https://news.detik.com/a", "https://news.detik.com/b"]
+        );
+        assert_eq!(state.stats.fetched(), 1);
+        assert_eq!(state.stats.extracted(), 0);
+        assert_eq!(state.stats.rejected(), 1);
+    }
+
+    #[tokio::test]
+    async fn handle_links_page_vetoed() {
+        let base = start_site();
+        let hub = format!("{}/hub", base);
+        let crawler = MockCrawler::new()
+            .with(
+                "/hub",
+                CrawlerResult::Links(vec![
+                    "https://news.detik.com/a".to_string(),
+                    "https://news.detik.com/foto-news/b".to_string(),
+                    "https://news.detik.com/live/c".to_string(),
+                ]),
+            )
+            .with_decision("/foto-news/", FetchDecision::SkipPermanently)
+            .with_decision("/live/", FetchDecision::SkipEnqueueOnly);
+        let (storage, _) = handle_one(crawler, &hub, RunOptions::default()).await;
+
+        assert_eq!(
+            methods(&storage),
+            vec![
+                "host_state_get",
+                "visited_insert",
+                "queued_insert_with_meta",
+                "visited_insert",
+                "running_delete",
+            ]
+        );
+        assert_eq!(
+            storage.inner().queued_get().await.unwrap(),
+            vec!["https://news.detik.com/a"]
+        );
+        assert!(storage
+            .inner()
+            .visited_is_exists("https://news.detik.com/foto-news/b")
+            .await
+            .unwrap());
+        assert!(!storage
+            .inner()
+            .visited_is_exists("https://news.detik.com/live/c")
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn handle_hub_recrawled() {
+        let base = start_site();
+        let hub = format!("{}/hub", base);
+        let hub_page = |links: &[&str]| {
+            MockCrawler::new().with(
+                "/hub",
+                CrawlerResult::Links(
+                    links
+                        .iter()
+                        .map(|l| format!("https://news.detik.com/{}", l))
+                        .collect(),
+                ),
+            )
+        };
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+        let state = state(RunOptions {
+            hub_recrawl: Some(recrawl::HubRecrawl::new(minutes(10), minutes(24 * 60))),
+            ..RunOptions::default()
+        });
+        let storage = Arc::new(MemoryStorage::<DetikArticle>::new());
+        storage.visited_insert(link()).await.unwrap();
+        let crawl = |crawler: MockCrawler<DetikArticle>| {
+            let (hub, storage, state) = (hub.clone(), storage.clone(), state.clone());
+            async move {
+                storage.queued_insert(&hub).await.unwrap();
+                let queued = QueuedUrl::new(hub.as_str());
+                let page_url = Url::parse(&hub).unwrap();
+                handle(queued, page_url, Arc::new(crawler), storage.clone(), state)
+                    .await
+                    .unwrap();
+                storage.hub_get(&hub).await.unwrap().unwrap()
+            }
+        };
+
+        // First crawled, it's crawled again soon to learn how often it changes
+        let first = crawl(hub_page(&["a", "b", "c"])).await;
+        assert_eq!((first.recrawls, first.interval), (0, minutes(10)));
+        let due = utils::get_now() + chrono::Duration::minutes(11);
+        assert_eq!(
+            revisit::requeue_due_at(storage.as_ref(), &state.revisiting, due)
+                .await
+                .unwrap(),
+            vec![hub.clone()]
+        );
+        storage.queued_delete(&hub).await.unwrap();
+
+        // Nothing new since, the longest interval
+        let second = crawl(hub_page(&["a", "b", "c"])).await;
+        assert_eq!(
+            (second.rate, second.recrawls, second.interval),
+            (0.0, 1, minutes(24 * 60))
+        );
+        storage.visited_delete(&hub).await.unwrap();
+
+        // A new link at once, faster than the shortest interval
+        let third = crawl(hub_page(&["a", "b", "c", "d"])).await;
+        assert!(third.rate > 0.0);
+        assert_eq!((third.recrawls, third.interval), (2, minutes(10)));
+        assert!(storage
+            .queued_is_exists("https://news.detik.com/d")
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn handle_hub_new_links_prioritized() {
+        let base = start_site();
+        let hub = format!("{}/hub", base);
+        let hub_page = |links: &[&str]| {
+            MockCrawler::new().with(
+                "/hub",
+                CrawlerResult::Links(
+                    links
+                        .iter()
+                        .map(|l| format!("https://news.detik.com/{}", l))
+                        .collect(),
+                ),
+            )
+        };
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+        let state = state(RunOptions {
+            hub_recrawl: Some(recrawl::HubRecrawl::new(minutes(10), minutes(24 * 60))),
+            ..RunOptions::default()
+        });
+        let storage = Arc::new(MemoryStorage::<DetikArticle>::new());
+        let crawl = |crawler: MockCrawler<DetikArticle>| {
+            let (hub, storage, state) = (hub.clone(), storage.clone(), state.clone());
+            async move {
+                storage.visited_delete(&hub).await.unwrap();
+                let queued = QueuedUrl::new(hub.as_str());
+                let page_url = Url::parse(&hub).unwrap();
+                handle(queued, page_url, Arc::new(crawler), storage.clone(), state)
+                    .await
+                    .unwrap();
+                // Two at a time, in the order they're claimed
+                let mut claimed = vec![];
+                loop {
+                    let batch = storage.queued_claim_n("worker", 2).await.unwrap();
+                    if batch.is_empty() {
+                        return claimed;
+                    }
+                    for queued in batch {
+                        storage.running_delete(&queued.url).await.unwrap();
+                        claimed.push(queued.url.to_string());
+                    }
+                }
+            }
+        };
+        let link = |l: &str| format!("https://news.detik.com/{}", l);
+
+        // First crawled, none of its links is told new
+        let first = crawl(hub_page(&["a", "b", "c"])).await;
+        assert_eq!(first, vec![link("a"), link("b"), link("c")]);
+
+        // Two links more since, claimed before the others queued again as usual
+        let second = crawl(hub_page(&["a", "d", "b", "c", "e"])).await;
+        assert_eq!(
+            second,
+            vec![link("d"), link("e"), link("a"), link("b"), link("c")]
+        );
+        // Counted as the new links of the hub
+        let hub_state = storage.hub_get(&hub).await.unwrap().unwrap();
+        assert!(hub_state.rate > 0.0);
+        assert_eq!(hub_state.interval, minutes(10));
+    }
+
+    /// The quality alerts raised, with their ratio and window.
+    #[derive(Debug, Default)]
+    struct Alerts(std::sync::Mutex<Vec<(f64, usize)>>);
+
+    impl quality::CrawlObserver for Alerts {
+        fn on_quality_alert(&self, ratio: f64, window: usize) {
+            self.0.lock().unwrap().push((ratio, window));
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_quality_alert() {
+        let base = start_site();
+        let (tx, mut webhook_calls) = mpsc::unbounded_channel();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let webhook = format!("http://{}/alert", listener.local_addr().unwrap());
+        let app = Router::new().route(
+            "/alert",
+            axum::routing::post(move |body: String| async move { tx.send(body).unwrap() }),
+        );
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+
+        let crawler = Arc::new(
+            MockCrawler::new()
+                .with(
+                    "/news/",
+                    CrawlerResult::document_and_links(article(&["p"]), vec![]),
+                )
+                .with(
+                    "/empty/",
+                    CrawlerResult::document_and_links(article(&[]), vec![]),
+                )
+                .with(
+                    "/premium/",
+                    CrawlerResult::Skipped("paywalled".to_string(), vec![]),
+                )
+                .with("/hub", CrawlerResult::Links(vec![])),
+        );
+        let alerts = Arc::new(Alerts::default());
+        let state = state(RunOptions {
+            quality_alert: Some(
+                quality::QualityAlert::new(4, 0.5)
+                    .with_pause(true)
+                    .with_webhook(Url::parse(&webhook).unwrap()),
+            ),
+            observer: Some(alerts.clone()),
+            ..RunOptions::default()
+        });
+        let storage = Arc::new(MemoryStorage::<DetikArticle>::new());
+        let crawl = |paths: &[&str]| {
+            let urls: Vec<String> = paths.iter().map(|p| format!("{}{}", base, p)).collect();
+            let (crawler, storage, state) = (crawler.clone(), storage.clone(), state.clone());
+            async move {
+                for url in urls {
+                    storage.queued_insert(&url).await.unwrap();
+                    let page_url = Url::parse(&url).unwrap();
+                    let queued = QueuedUrl::new(url.as_str());
+                    handle(
+                        queued,
+                        page_url,
+                        crawler.clone(),
+                        storage.clone(),
+                        state.clone(),
+                    )
+                    .await
+                    .unwrap();
+                }
+            }
+        };
+
+        // The hubs don't count, and 1 warned article in 4 is below the threshold
+        crawl(&[
+            "/news/d-1",
+            "/hub/1",
+            "/news/d-2",
+            "/premium/d-3",
+            "/news/d-4",
+        ])
+        .await;
+        assert!(alerts.0.lock().unwrap().is_empty());
+        assert!(!state.options.controller.is_paused());
+
+        // Past the threshold once, staying there
+        crawl(&[
+            "/empty/d-5",
+            "/premium/d-6",
+            "/empty/d-7",
+            "/news/d-8",
+            "/premium/d-9",
+        ])
+        .await;
+        assert_eq!(*alerts.0.lock().unwrap(), vec![(0.5, 4)]);
+        assert!(state.options.controller.is_paused());
+
+        let body = tokio::time::timeout(Duration::from_secs(5), webhook_calls.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        let payload: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(payload["ratio"], 0.5);
+        assert_eq!(payload["window"], 4);
+        assert_eq!(payload["paused"], true);
+        assert!(webhook_calls.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn handle_article_page() {
+        let base = start_site();
+        let url = format!("{}/news/d-1", base);
+        let crawler = MockCrawler::new().with(
+            "/news/",
+            CrawlerResult::document_and_links(article(&["p"]), vec![link()]),
+        );
+        let options = RunOptions {
+            max_results: Some(1),
+            ..RunOptions::default()
+        };
+        let (storage, state) = handle_one(crawler, &url, options).await;
+
+        assert_eq!(
+            methods(&storage),
+            vec![
+                "host_state_get",
+                "results_insert_with_referrer",
+                "visited_insert",
+                "queued_insert_with_meta",
+                "running_delete",
+            ]
+        );
+        assert_eq!(storage.inner().result(&url), Some(article(&["p"])));
+        assert_eq!(state.stats.extracted(), 1);
+        assert!(state.options.controller.is_stopped());
+    }
+
+    #[tokio::test]
+    async fn handle_related_links_first() {
+        let base = start_site();
+        let url = format!("{}/news/d-1", base);
+        let links = Links {
+            related: vec!["https://news.detik.com/b".to_string()],
+            other: vec![link()],
+        };
+        let crawler = MockCrawler::new().with(
+            "/news/",
+            CrawlerResult::DocumentAndLinks(article(&["p"]), links),
+        );
+        let (storage, _) = handle_one(crawler, &url, RunOptions::default()).await;
+
+        assert_eq!(
+            storage.inner().queued_get().await.unwrap(),
+            vec!["https://news.detik.com/b", "https://news.detik.com/a"]
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_empty_article() {
+        let base = start_site();
+        let url = format!("{}/news/d-2", base);
+        let crawler = MockCrawler::new().with(
+            "/news/",
+            CrawlerResult::document_and_links(article(&[]), vec![link()]),
+        );
+        let (storage, state) = handle_one(crawler, &url, RunOptions::default()).await;
+
+        // Links of a warned page are not followed and the page may be retried later
+        assert_eq!(
+            methods(&storage),
+            vec!["host_state_get", "warned_insert", "running_delete"]
+        );
+        assert!(!storage.inner().visited_is_exists(&url).await.unwrap());
+        assert_eq!(storage.inner().results_status(&url).await.unwrap(), None);
+        assert_eq!(state.stats.warned(), 1);
+        assert!(!state.options.controller.is_stopped());
+    }
+
+    #[tokio::test]
+    async fn handle_empty_article_metadata_kept() {
+        let base = start_site();
+        let url = format!("{}/news/d-2", base);
+        let crawler = MockCrawler::new().with(
+            "/news/",
+            CrawlerResult::document_and_links(article(&[]), vec![link()]),
+        );
+        let options = RunOptions {
+            keep_metadata_on_empty_body: true,
+            ..RunOptions::default()
+        };
+        let (storage, state) = handle_one(crawler, &url, options).await;
+
+        assert_eq!(
+            methods(&storage),
+            vec![
+                "host_state_get",
+                "results_insert_metadata_only",
+                "warned_insert",
+                "running_delete"
+            ]
+        );
+        assert_eq!(
+            storage.inner().results_status(&url).await.unwrap(),
+            Some(ExtractionStatus::MetadataOnly)
+        );
+        assert_eq!(storage.inner().result(&url), Some(article(&[])));
+        assert!(!storage.inner().visited_is_exists(&url).await.unwrap());
+        assert_eq!(state.stats.extracted(), 0);
+        assert_eq!(state.stats.warned(), 1);
+    }
+
+    #[tokio::test]
+    async fn handle_skipped_page() {
+        let base = start_site();
+        let url = format!("{}/premium/d-3", base);
+        let crawler = MockCrawler::new().with(
+            "/premium/",
+            CrawlerResult::Skipped("paywalled".to_string(), vec![link()]),
+        );
+        let (storage, state) = handle_one(crawler, &url, RunOptions::default()).await;
+
+        assert_eq!(
+            methods(&storage),
+            vec![
+                "host_state_get",
+                "warned_insert",
+                "queued_insert_with_meta",
+                "running_delete"
+            ]
+        );
+        assert_eq!(storage.inner().results_count().await.unwrap(), 0);
+        assert_eq!(
+            storage.inner().warned_recent(1).await.unwrap()[0]
+                .reason
+                .as_deref(),
+            Some("paywalled")
+        );
+        assert_eq!(state.stats.warned(), 1);
+    }
+
+    #[tokio::test]
+    async fn handle_article_in_other_language() {
+        let base = start_site();
+        let url = format!("{}/news/d-4", base);
+        let english = DetikArticle {
+            language: Some("en".to_string()),
+            ..article(&["The tourists said that it was safe."])
+        };
+        let crawler = MockCrawler::new().with(
+            "/news/",
+            CrawlerResult::document_and_links(english, vec![link()]),
+        );
+        let options = RunOptions {
+            languages: Some(vec!["id".to_string()]),
+            ..RunOptions::default()
+        };
+        let (storage, state) = handle_one(crawler, &url, options).await;
+
+        assert_eq!(
+            methods(&storage),
+            vec![
+                "host_state_get",
+                "warned_insert",
+                "queued_insert_with_meta",
+                "running_delete"
+            ]
+        );
+        assert_eq!(storage.inner().results_count().await.unwrap(), 0);
+        assert_eq!(
+            storage.inner().warned_recent(1).await.unwrap()[0]
+                .reason
+                .as_deref(),
+            Some(LANGUAGE)
+        );
+        assert_eq!(state.stats.warned(), 1);
+    }
+
+    /// Reads the second page of every article through the context.
+    struct TwoPageCrawler;
+
+    #[async_trait::async_trait]
+    impl AsyncCrawler for TwoPageCrawler {
+        type Document = DetikArticle;
+
+        async fn crawl(&self, ctx: &FetchContext<'_>, html: &str) -> CrawlerResult<DetikArticle> {
+            let next = format!("{}?page=2", ctx.url());
+            let continuation = ctx.fetch(&next).await.unwrap();
+            assert_eq!(continuation, MockCrawler::<DetikArticle>::page(&next));
+            let pages = [html, &continuation].map(|page| {
+                if page.contains("page=2") {
+                    "page 2"
+                } else {
+                    "page 1"
+                }
+            });
+            CrawlerResult::document_and_links(article(&pages), vec![])
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_async_crawler_fetching_continuation() {
+        let base = start_site();
+        let url = format!("{}/news/d-4", base);
+        let storage = RecordingStorage::<_>::memory();
+        let state = state(RunOptions::default());
+        handle(
+            QueuedUrl::new(url.as_str()),
+            Url::parse(&url).unwrap(),
+            Arc::new(TwoPageCrawler),
+            Arc::new(storage.clone()),
+            Arc::clone(&state),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            storage.inner().result(&url),
+            Some(article(&["page 1", "page 2"]))
+        );
+        assert_eq!(state.stats.fetched(), 2);
+        assert_eq!(state.stats.extracted(), 1);
+    }
+
+    /// Skips the pages it's given truncated, stores the others.
+    struct TruncationCrawler;
+
+    #[async_trait::async_trait]
+    impl AsyncCrawler for TruncationCrawler {
+        type Document = DetikArticle;
+
+        async fn crawl(&self, ctx: &FetchContext<'_>, html: &str) -> CrawlerResult<DetikArticle> {
+            if ctx.is_truncated() {
+                assert!(html.ends_with('>'));
+                return CrawlerResult::Skipped(TRUNCATED.to_string(), vec![link()]);
+            }
+            CrawlerResult::document_and_links(article(&["Isi."]), vec![link()])
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_huge_pages() {
+        let base = start_site();
+        let url = format!("{}/news/d-5", base);
+        let size = MockCrawler::<DetikArticle>::page(&url).len() as u64;
+
+        let (storage, state) = handle_one(TruncationCrawler, &url, RunOptions::default()).await;
+        assert!(storage.inner().result(&url).is_some());
+        assert_eq!(storage.inner().queued_count().await.unwrap(), 1);
+        assert_eq!(state.stats.truncated(), 0);
+
+        let options = RunOptions {
+            max_parse_size: Some(size - 1),
+            ..RunOptions::default()
+        };
+        let (storage, state) = handle_one(TruncationCrawler, &url, options).await;
+        assert_eq!(
+            storage.inner().warned_recent(1).await.unwrap()[0]
+                .reason
+                .as_deref(),
+            Some(TRUNCATED)
+        );
+        assert_eq!(storage.inner().queued_count().await.unwrap(), 1);
+        assert_eq!(state.stats.truncated(), 1);
+
+        // Stored, its links left out
+        let options = RunOptions {
+            max_link_extraction_size: Some(size - 1),
+            ..RunOptions::default()
+        };
+        let (storage, state) = handle_one(TruncationCrawler, &url, options).await;
+        assert!(storage.inner().result(&url).is_some());
+        assert_eq!(storage.inner().queued_count().await.unwrap(), 0);
+        assert_eq!(state.stats.truncated(), 0);
+    }
+
+    /// Serves detik articles behind an adblock wall setting the `consent` cookie, the article
+    /// telling the `Accept-Language` it was asked in. `/wall/...` is always the wall.
+    fn start_consent_site() -> String {
+        use axum::http::{header, HeaderMap};
+        use axum::response::IntoResponse;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().fallback(|uri: Uri, headers: HeaderMap| async move {
+            let consent = headers
+                .get(header::COOKIE)
+                .and_then(|c| c.to_str().ok())
+                .is_some_and(|c| c.contains("consent=1"));
+            if !consent || uri.path().starts_with("/wall/") {
+                let wall = r#"<div class="adblock-wall">Matikan adblock</div>"#;
+                let cookie = "consent=1; Max-Age=3600; Path=/";
+                return ([(header::SET_COOKIE, cookie)], axum::response::Html(wall))
+                    .into_response();
+            }
+            let language = headers
+                .get(header::ACCEPT_LANGUAGE)
+                .and_then(|l| l.to_str().ok())
+                .unwrap_or("none");
+            axum::response::Html(format!(
+                r#"<meta name="dtk:contenttype" content="singlepagenews">
+                <div class="detail__body-text"><p>Bahasa {}.</p></div>"#,
+                language
+            ))
+            .into_response()
+        });
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+        format!("http://{}", addr)
+    }
+
+    async fn handle_detik(url: &str, state: &Arc<RunState>) -> MemoryStorage<DetikArticle> {
+        let storage = MemoryStorage::new();
+        handle(
+            QueuedUrl::new(url),
+            Url::parse(url).unwrap(),
+            Arc::new(detik::DetikCrawler::new()),
+            Arc::new(storage.clone()),
+            Arc::clone(state),
+        )
+        .await
+        .unwrap();
+        storage
+    }
+
+    #[tokio::test]
+    async fn handle_interstitial_with_cookies() {
+        let jar = std::path::PathBuf::from("test_cookies.json");
+        if jar.is_file() {
+            std::fs::remove_file(&jar).unwrap();
+        }
+        let base = start_consent_site();
+        let url = format!("{}/berita/d-1/judul", base);
+        let mut default_headers = reqwest::header::HeaderMap::new();
+        default_headers.insert(
+            reqwest::header::ACCEPT_LANGUAGE,
+            reqwest::header::HeaderValue::from_static("id-ID"),
+        );
+        let options = RunOptions {
+            default_headers,
+            cookie_jar: Some(jar.clone()),
+            ..RunOptions::default()
+        };
+
+        // The wall, then the article once the cookie is set
+        let state = state(options.clone());
+        let storage = handle_detik(&url, &state).await;
+        assert_eq!(
+            storage.result(&url).unwrap().paragraphs,
+            vec!["Bahasa id-ID."]
+        );
+        assert_eq!(state.stats.fetched(), 2);
+
+        // Fetched again only once
+        let wall = format!("{}/wall/d-2/judul", base);
+        let storage = handle_detik(&wall, &state).await;
+        assert_eq!(storage.results_count().await.unwrap(), 0);
+        assert_eq!(
+            storage.warned_recent(1).await.unwrap()[0].reason.as_deref(),
+            Some(INTERSTITIAL)
+        );
+        assert_eq!(state.stats.fetched(), 4);
+
+        // The next run starts with the cookie
+        state.cookies.save(&jar).unwrap();
+        let page_url = Url::parse(&url).unwrap();
+        assert_eq!(
+            CookieJar::load(&jar).unwrap().get(&page_url, "consent"),
+            Some("1".to_string())
+        );
+        let state = self::state(options);
+        let storage = handle_detik(&url, &state).await;
+        assert!(storage.result(&url).is_some());
+        assert_eq!(state.stats.fetched(), 1);
+
+        std::fs::remove_file(&jar).unwrap();
+    }
+
+    /// Serves the wall of `start_consent_site` the first time a path is requested, then
+    /// fails with the status the path starts with.
+    fn start_failing_consent_site() -> String {
+        use axum::http::StatusCode;
+        use axum::response::IntoResponse;
+        use std::collections::HashSet;
+
+        let requested = Arc::new(std::sync::Mutex::new(HashSet::new()));
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().fallback(move |uri: Uri| {
+            let requested = Arc::clone(&requested);
+            async move {
+                if requested.lock().unwrap().insert(uri.path().to_string()) {
+                    let wall = r#"<div class="adblock-wall">Matikan adblock</div>"#;
+                    return axum::response::Html(wall).into_response();
+                }
+                match uri.path().split('/').nth(1) {
+                    Some("429") => {
+                        (StatusCode::TOO_MANY_REQUESTS, [("retry-after", "1")]).into_response()
+                    }
+                    _ => StatusCode::SERVICE_UNAVAILABLE.into_response(),
+                }
+            }
+        });
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn handle_interstitial_refetch_failed() {
+        let base = start_failing_consent_site();
+        let state = state(RunOptions::default());
+
+        // Warned to be retried like a first fetch failing
+        let url = format!("{}/503/d-1/judul", base);
+        let before = utils::get_now();
+        let storage = handle_detik(&url, &state).await;
+        let warned = storage.warned_get(&url).await.unwrap().unwrap();
+        assert_eq!(warned.reason.as_deref(), Some(HTTP_5XX));
+        assert!(warned.next_retry_at.unwrap() >= before + chrono::Duration::hours(1));
+        assert_eq!(storage.running_count().await.unwrap(), 0);
+        assert!(!storage.visited_is_exists(&url).await.unwrap());
+
+        // Queued again while the host is backed off
+        let url = format!("{}/429/d-2/judul", base);
+        let storage = handle_detik(&url, &state).await;
+        assert_eq!(storage.queued_get().await.unwrap(), vec![url.clone()]);
+        assert_eq!(storage.running_count().await.unwrap(), 0);
+        assert_eq!(storage.warned_count().await.unwrap(), 0);
+        let backoff = storage.host_state_get("127.0.0.1").await.unwrap().unwrap();
+        assert_eq!(backoff.multiplier, 2);
+    }
+
+    /// Serves `body` encoded as `encoding` on every path.
+    fn start_compressed_site(encoding: &'static str, body: Vec<u8>) -> String {
+        use axum::http::header::CONTENT_ENCODING;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app =
+            Router::new().fallback(move || async move { ([(CONTENT_ENCODING, encoding)], body) });
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+        format!("http://{}", addr)
+    }
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        use std::io::Write;
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn brotli(data: &[u8]) -> Vec<u8> {
+        use std::io::Write;
+        let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+        encoder.write_all(data).unwrap();
+        encoder.into_inner()
+    }
+
+    #[tokio::test]
+    async fn handle_compressed_pages() {
+        let html = std::fs::read_to_string("tests/htmls/1.html").unwrap();
+        let state = state(RunOptions {
+            max_decompressed_size: Some(1024 * 1024),
+            ..RunOptions::default()
+        });
+        let mut received = 0;
+        for (encoding, body) in [
+            ("gzip", gzip(html.as_bytes())),
+            ("br", brotli(html.as_bytes())),
+        ] {
+            received += body.len() as u64;
+            let url = format!(
+                "{}/berita/d-6453785/judul",
+                start_compressed_site(encoding, body)
+            );
+            let storage = handle_detik(&url, &state).await;
+            assert_eq!(
+                storage.result(&url),
+                parse_article(&detik::DetikCrawler::new(), &html, &url),
+                "{}",
+                encoding
+            );
+        }
+        assert!(received < html.len() as u64);
+        assert_eq!(state.stats.bytes_received(), received);
+        assert_eq!(state.stats.bytes_decoded(), 2 * html.len() as u64);
+
+        // 2 MiB of spaces in a few KiB, given up on past the limit
+        let bomb = start_compressed_site("gzip", gzip(&vec![b' '; 2 * 1024 * 1024]));
+        let url = format!("{}/berita/d-1/bom", bomb);
+        let storage = handle_detik(&url, &state).await;
+        assert_eq!(
+            storage.warned_recent(1).await.unwrap()[0].reason.as_deref(),
+            Some(TOO_LARGE)
+        );
+        assert!(!storage.running_is_exists(&url).await.unwrap());
+        assert_eq!(state.stats.fetched(), 2);
+        assert_eq!(state.stats.warned(), 1);
+    }
+
+    struct Unreachable;
+
+    #[async_trait::async_trait]
+    impl dns::Resolver for Unreachable {
+        async fn lookup(&self, _host: &str) -> std::io::Result<Vec<std::net::IpAddr>> {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                "timed out",
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_unresolved_host_requeued() {
+        let mut state = RunState::new(RunOptions::default(), 0).unwrap();
+        let dns = Arc::new(dns::DnsCache::new(Unreachable, Default::default()));
+        state.dns = Some(Arc::clone(&dns));
+        let state = Arc::new(state);
+        let url = "https://news.detik.test/berita/d-1/judul";
+
+        for _ in 0..2 {
+            let storage = handle_detik(url, &state).await;
+            assert_eq!(storage.queued_get().await.unwrap(), vec![url]);
+            assert!(!storage.running_is_exists(url).await.unwrap());
+        }
+        assert_eq!(state.stats.fetched(), 0);
+        assert_eq!(
+            dns.stats(),
+            dns::DnsStats {
+                hits: 1,
+                misses: 1,
+                failures: 1,
+                requeued: 2,
+            }
+        );
+    }
+
+    /// Serves the `refresh` fixture, redirecting to the article of the `1` fixture,
+    /// `/loop/<n>` redirecting to `/loop/<n + 1>` and `/to-down` to a failing page.
+    fn start_refreshing_site() -> String {
+        use axum::response::IntoResponse;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().fallback(|uri: Uri| async move {
+            let path = uri.path();
+            if path.starts_with("/down/") {
+                return axum::http::StatusCode::SERVICE_UNAVAILABLE.into_response();
+            }
+            let html = if path == "/to-down" {
+                r#"<meta http-equiv="Refresh" content="0; URL='/down/1'">"#.to_string()
+            } else if let Some(n) = path.strip_prefix("/loop/") {
+                let next = n.parse::<u32>().unwrap() + 1;
+                format!(
+                    r#"<meta http-equiv="Refresh" content="0; URL='/loop/{}'">"#,
+                    next
+                )
+            } else if path.starts_with("/berita/d-1234567/") {
+                std::fs::read_to_string("tests/htmls/refresh.html").unwrap()
+            } else {
+                std::fs::read_to_string("tests/htmls/1.html").unwrap()
+            };
+            axum::response::Html(html).into_response()
+        });
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn handle_meta_refresh() {
+        let base = start_refreshing_site();
+        let state = state(RunOptions::default());
+        let url = format!("{}/berita/d-1234567/kpu-tetapkan", base);
+        let target = format!(
+            "{}/berita/d-6453785/kpu-tetapkan-17-partai-politik-peserta-pemilu-2024",
+            base
+        );
+
+        let storage = handle_detik(&url, &state).await;
+        let html = std::fs::read_to_string("tests/htmls/1.html").unwrap();
+        let expected = parse_article(&detik::DetikCrawler::new(), &html, &target);
+        assert!(expected.is_some());
+        assert_eq!(storage.result(&target), expected);
+        assert_eq!(storage.result(&url), None);
+        assert!(storage.visited_is_exists(&url).await.unwrap());
+        assert!(storage.visited_is_exists(&target).await.unwrap());
+        assert_eq!(storage.running_count().await.unwrap(), 0);
+        assert_eq!(storage.warned_count().await.unwrap(), 0);
+        assert_eq!(state.stats.fetched(), 2);
+
+        let url = format!("{}/loop/0", base);
+        let storage = handle_detik(&url, &state).await;
+        assert_eq!(storage.results_count().await.unwrap(), 0);
+        assert_eq!(
+            storage.warned_recent(1).await.unwrap()[0].url,
+            format!("{}/loop/{}", base, MAX_REFRESH_HOPS)
+        );
+        assert_eq!(
+            storage.warned_recent(1).await.unwrap()[0].reason.as_deref(),
+            Some(REFRESH_LOOP)
+        );
+        assert_eq!(storage.running_count().await.unwrap(), 0);
+        assert_eq!(state.stats.fetched(), 2 + 1 + MAX_REFRESH_HOPS as u64);
+
+        // A target failing is warned to be retried, not left running
+        let url = format!("{}/to-down", base);
+        let target = format!("{}/down/1", base);
+        let storage = handle_detik(&url, &state).await;
+        assert!(storage.visited_is_exists(&url).await.unwrap());
+        let warned = storage.warned_get(&target).await.unwrap().unwrap();
+        assert_eq!(warned.reason.as_deref(), Some(HTTP_5XX));
+        assert!(warned.next_retry_at.is_some());
+        assert_eq!(storage.running_count().await.unwrap(), 0);
+    }
+
+    /// Serves tests/htmls/1.html with the robots meta of its `robots` query and the
+    /// `X-Robots-Tag` of its `header` query.
+    fn start_robots_site() -> String {
+        #[derive(Deserialize)]
+        struct Directives {
+            robots: Option<String>,
+            header: Option<String>,
+        }
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().fallback(
+            |axum::extract::Query(directives): axum::extract::Query<Directives>| async move {
+                let html = std::fs::read_to_string("tests/htmls/1.html").unwrap();
+                let robots = directives
+                    .robots
+                    .unwrap_or_else(|| "index, follow".to_string());
+                let html = html.replace(
+                    r#"name="robots" content="index, follow""#,
+                    &format!(r#"name="robots" content="{}""#, robots),
+                );
+                let mut headers = axum::http::HeaderMap::new();
+                if let Some(header) = directives.header {
+                    headers.insert("x-robots-tag", header.parse().unwrap());
+                }
+                (headers, axum::response::Html(html))
+            },
+        );
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn handle_robots_directives() {
+        let base = start_robots_site();
+        let page = |query: &str| format!("{}/berita/d-6453785/kpu-tetapkan?{}", base, query);
+
+        // (query, stored, links followed)
+        let cases = [
+            ("robots=index,follow", true, true),
+            ("robots=noindex", false, true),
+            ("robots=nofollow", true, false),
+            ("robots=noindex,nofollow", false, false),
+            ("robots=none", false, false),
+            ("header=noindex", false, true),
+            ("header=nofollow&robots=noindex", false, false),
+        ];
+        for ignore_robots in [false, true] {
+            let state = state(RunOptions {
+                ignore_robots,
+                ..RunOptions::default()
+            });
+            for (query, stored, followed) in cases {
+                let url = page(query);
+                let storage = handle_detik(&url, &state).await;
+                let (stored, followed) = (stored || ignore_robots, followed || ignore_robots);
+                assert_eq!(storage.result(&url).is_some(), stored, "{}", query);
+                assert!(storage.visited_is_exists(&url).await.unwrap(), "{}", query);
+                assert_eq!(storage.warned_count().await.unwrap(), 0, "{}", query);
+                let queued = storage.queued_count().await.unwrap();
+                assert_eq!(queued > 0, followed, "{}", query);
+            }
+            let noindex = if ignore_robots { 0 } else { 5 };
+            assert_eq!(state.stats.noindex(), noindex);
+        }
+    }
+
+    #[tokio::test]
+    async fn politeness_disabled_for_exempt_hosts() {
+        let (exempt, other) = (start_robots_site(), start_robots_site());
+        let delay = Duration::from_millis(200);
+        let state = state(RunOptions {
+            request_delay: delay,
+            disable_politeness_for_hosts: vec![exempt.trim_start_matches("http://").to_string()],
+            ..RunOptions::default()
+        });
+        let page = |base: &str| format!("{}/berita/d-6453785/kpu-tetapkan?robots=noindex", base);
+
+        // Fetched right away, however often
+        let start = Instant::now();
+        for _ in 0..5 {
+            state.fetch(&page(&exempt)).await.unwrap();
+        }
+        assert!(start.elapsed() < delay, "{:?}", start.elapsed());
+        // The other port of the same address still waits its turn
+        let start = Instant::now();
+        for _ in 0..3 {
+            state.fetch(&page(&other)).await.unwrap();
+        }
+        assert!(start.elapsed() >= delay * 2, "{:?}", start.elapsed());
+
+        // Its robots directives ignored, unlike those of the other
+        let url = page(&exempt);
+        assert!(handle_detik(&url, &state).await.result(&url).is_some());
+        let url = page(&other);
+        assert!(handle_detik(&url, &state).await.result(&url).is_none());
+        assert_eq!(state.stats.noindex(), 1);
+    }
+
+    /// Answers 503 to the first `failures` requests, then serves tests/htmls/1.html.
+    fn start_flaky_site(failures: usize) -> String {
+        use axum::response::IntoResponse;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let requests = Arc::new(AtomicUsize::new(0));
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().fallback(move || {
+            let requests = Arc::clone(&requests);
+            async move {
+                if requests.fetch_add(1, Ordering::SeqCst) < failures {
+                    return axum::http::StatusCode::SERVICE_UNAVAILABLE.into_response();
+                }
+                let html = std::fs::read_to_string("tests/htmls/1.html").unwrap();
+                axum::response::Html(html).into_response()
+            }
+        });
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn handle_server_error_retried() {
+        let base = start_flaky_site(1);
+        let url = format!("{}/berita/d-6453785/kpu-tetapkan", base);
+        let state = state(RunOptions::default());
+        let storage = MemoryStorage::<DetikArticle>::new();
+        let handle_url = || {
+            handle(
+                QueuedUrl::new(url.as_str()),
+                Url::parse(&url).unwrap(),
+                Arc::new(detik::DetikCrawler::new()),
+                Arc::new(storage.clone()),
+                Arc::clone(&state),
+            )
+        };
+
+        let before = utils::get_now();
+        handle_url().await.unwrap();
+        let warned = storage.warned_get(&url).await.unwrap().unwrap();
+        assert_eq!(warned.reason.as_deref(), Some(HTTP_5XX));
+        assert!(warned.next_retry_at.unwrap() >= before + chrono::Duration::hours(1));
+        assert_eq!(storage.running_count().await.unwrap(), 0);
+        assert!(!storage.visited_is_exists(&url).await.unwrap());
+        assert_eq!(state.stats.warned(), 1);
+
+        let later = utils::get_now() + chrono::Duration::hours(2);
+        let due = retry::requeue_due_at(&storage, &state.retrying, later)
+            .await
+            .unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(storage.queued_get().await.unwrap(), vec![url.clone()]);
+
+        // Crawled once it works again, no longer warned
+        handle_url().await.unwrap();
+        assert!(storage.result(&url).is_some());
+        assert_eq!(storage.warned_count().await.unwrap(), 0);
+        assert!(state.retrying.lock().unwrap().is_empty());
+    }
+
+    fn start_rate_limited_site(retry_after: &'static str) -> String {
+        use axum::response::IntoResponse;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let requests = Arc::new(AtomicUsize::new(0));
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().fallback(move || {
+            let requests = Arc::clone(&requests);
+            async move {
+                if requests.fetch_add(1, Ordering::SeqCst) == 0 {
+                    let status = axum::http::StatusCode::TOO_MANY_REQUESTS;
+                    return (status, [("retry-after", retry_after)]).into_response();
+                }
+                let html = std::fs::read_to_string("tests/htmls/1.html").unwrap();
+                axum::response::Html(html).into_response()
+            }
+        });
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn handle_too_many_requests_backoff_restored() {
+        let base = start_rate_limited_site("2");
+        let url = format!("{}/berita/d-6453785/kpu-tetapkan", base);
+        let host = "127.0.0.1";
+        let storage = MemoryStorage::<DetikArticle>::new();
+        let handle_url = |state: Arc<RunState>| {
+            handle(
+                QueuedUrl::new(url.as_str()),
+                Url::parse(&url).unwrap(),
+                Arc::new(detik::DetikCrawler::new()),
+                Arc::new(storage.clone()),
+                state,
+            )
+        };
+
+        let before = utils::get_now();
+        handle_url(state(RunOptions::default())).await.unwrap();
+        assert_eq!(storage.queued_get().await.unwrap(), vec![url.clone()]);
+        assert_eq!(storage.running_count().await.unwrap(), 0);
+        assert_eq!(storage.warned_count().await.unwrap(), 0);
+        let backoff = storage.host_state_get(host).await.unwrap().unwrap();
+        assert_eq!(backoff.multiplier, 2);
+        assert!(backoff.resume_at >= before + chrono::Duration::seconds(2));
+
+        // A restarted run waits for the backoff it was stopped in, then eases it off
+        let restarted = state(RunOptions::default());
+        handle_url(Arc::clone(&restarted)).await.unwrap();
+        assert!(utils::get_now() >= backoff.resume_at);
+        assert!(storage.result(&url).is_some());
+        assert_eq!(restarted.stats.fetched(), 1);
+        let backoff = storage.host_state_get(host).await.unwrap().unwrap();
+        assert_eq!(backoff.multiplier, 1);
+    }
+
+    #[tokio::test]
+    async fn handle_too_many_requests_priority_kept() {
+        let base = start_rate_limited_site("1");
+        let url = format!("{}/berita/d-6453785/kpu-tetapkan", base);
+        let storage = MemoryStorage::<DetikArticle>::new();
+        storage
+            .queued_insert(format!("{}/berita/d-1/lama", base))
+            .await
+            .unwrap();
+        storage
+            .queued_insert_prioritized(&url, Some(&base), 2, 5)
+            .await
+            .unwrap();
+        let queued = storage.queued_claim_n("worker", 1).await.unwrap();
+        assert_eq!(&*queued[0].url, url);
+
+        handle(
+            queued[0].clone(),
+            Url::parse(&url).unwrap(),
+            Arc::new(detik::DetikCrawler::new()),
+            Arc::new(storage.clone()),
+            state(RunOptions::default()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(storage.running_count().await.unwrap(), 0);
+        assert!(storage.result(&url).is_none());
+        // Backed off from, and still claimed before the older url
+        assert_eq!(storage.queued_claim_n("worker", 1).await.unwrap(), queued);
+    }
+
+    #[test]
+    fn parse_api_over_fixtures() {
+        let dir = std::path::Path::new("tests/htmls");
+        let urls = fixture::urls(dir).unwrap();
+        let crawler = detik::DetikCrawler::new();
+        for name in ["1", "travel", "video"] {
+            let html = std::fs::read_to_string(dir.join(format!("{}.html", name))).unwrap();
+            let url = urls[name].as_str();
+            let doc = Html::parse_document(&html);
+            let ctx = CrawlContext::new(&urls[name]);
+
+            let expected = match Crawler::crawl(&crawler, &ctx, &doc) {
+                CrawlerResult::DocumentAndLinks(article, _) => Some(article),
+                _ => None,
+            };
+            assert_eq!(parse_article(&crawler, &html, url), expected, "{}", name);
+            assert_eq!(
+                parse_links(&crawler, &html, url),
+                crawler.extract_urls(&ctx, &doc),
+                "{}",
+                name
+            );
+        }
+        let html = std::fs::read_to_string(dir.join("1.html")).unwrap();
+        assert!(parse_article(&crawler, &html, urls["1"].as_str()).is_some());
+        assert!(parse_article(&crawler, &html, "not a url").is_none());
+        assert!(parse_links(&crawler, &html, "not a url").is_empty());
+    }
+
+    /// Hub pages each linking to 3 more hubs and 2 articles: the queue grows faster than
+    /// it's crawled.
+    fn growing_crawler() -> MockCrawler<DetikArticle> {
+        let mut crawler = MockCrawler::new();
+        for hub in 0..10 {
+            let links = (0..5)
+                .map(|i| match i {
+                    0..=2 => format!("https://news.detik.com/indeks/{}", hub * 3 + i),
+                    _ => format!("https://news.detik.com/berita/d-{}/judul", hub * 2 + i),
+                })
+                .collect();
+            crawler = crawler.with(format!("/hub/{}/", hub), CrawlerResult::Links(links));
+        }
+        crawler
+    }
+
+    async fn queued_hubs(storage: &MemoryStorage<DetikArticle>) -> usize {
+        let queued = storage.queued_get().await.unwrap();
+        queued.iter().filter(|url| url.contains("/indeks/")).count()
+    }
+
+    #[tokio::test]
+    async fn handle_throttled_queue() {
+        let base = start_site();
+        let storage = Arc::new(MemoryStorage::<DetikArticle>::new());
+        let crawler = Arc::new(growing_crawler());
+        let state = state(RunOptions {
+            queue_throttle: Some(throttle::QueueThrottle::new(6, 3)),
+            ..RunOptions::default()
+        });
+        let check = || async {
+            let throttle = state.options.queue_throttle.as_ref();
+            throttle::check_at(
+                storage.as_ref(),
+                throttle,
+                &state.throttled,
+                utils::get_now(),
+            )
+            .await
+            .unwrap()
+        };
+        let crawl = |hub: u32| {
+            let url = format!("{}/hub/{}/", base, hub);
+            handle(
+                QueuedUrl::new(url.as_str()),
+                Url::parse(&url).unwrap(),
+                Arc::clone(&crawler),
+                Arc::clone(&storage),
+                Arc::clone(&state),
+            )
+        };
+
+        // Below the high watermark every link is queued
+        crawl(0).await.unwrap();
+        assert_eq!(check().await.queued, 5);
+        assert!(!state.throttled.load(Ordering::Relaxed));
+        crawl(1).await.unwrap();
+        let queue = check().await;
+        assert_eq!(queue.queued, 10);
+        assert!(queue.oldest_age.is_some());
+        assert!(state.throttled.load(Ordering::Relaxed));
+
+        // Above it the hubs are left out
+        crawl(2).await.unwrap();
+        crawl(3).await.unwrap();
+        assert_eq!(queued_hubs(&storage).await, 6);
+        assert_eq!(storage.queued_count().await.unwrap(), 14);
+
+        // Still throttled until the queue is below the low watermark
+        for url in storage.queued_get_n(11).await.unwrap() {
+            storage.queued_delete(url).await.unwrap();
+        }
+        assert!(check().await.queued == 3 && state.throttled.load(Ordering::Relaxed));
+        storage
+            .queued_delete(&storage.queued_get_n(1).await.unwrap()[0])
+            .await
+            .unwrap();
+        check().await;
+        assert!(!state.throttled.load(Ordering::Relaxed));
+        crawl(4).await.unwrap();
+        assert_eq!(queued_hubs(&storage).await, 3);
+    }
+}
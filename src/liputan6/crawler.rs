@@ -0,0 +1,235 @@
+use crate::{
+    json_ld, liputan6::Liputan6Article, text, utils, CrawlContext, Crawler, CrawlerResult,
+    ExtractedLink,
+};
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone};
+use lazy_regex::regex_is_match;
+use lazy_static::lazy_static;
+use scraper::{Html, Selector};
+
+const E: &str = "Invalid selector";
+lazy_static! {
+    static ref OG_TYPE: Selector = Selector::parse(r#"meta[property="og:type"]"#).expect(E);
+    static ref TITLE: Selector = Selector::parse(r#"meta[property="og:title"]"#).expect(E);
+    static ref DESCRIPTION: Selector =
+        Selector::parse(r#"meta[property="og:description"]"#).expect(E);
+    static ref DESCRIPTION_META: Selector =
+        Selector::parse(r#"meta[name="description"]"#).expect(E);
+    static ref THUMBNAIL: Selector = Selector::parse(r#"meta[property="og:image"]"#).expect(E);
+    static ref AUTHOR: Selector = Selector::parse(r#"meta[name="author"]"#).expect(E);
+    static ref AUTHOR_NAME: Selector =
+        Selector::parse(".read-page--header--author__name").expect(E);
+    static ref DATETIME: Selector = Selector::parse("time[datetime]").expect(E);
+    static ref KEYWORDS: Selector = Selector::parse(r#"meta[name="keywords"]"#).expect(E);
+    static ref TAG: Selector = Selector::parse(".tags--snippet__name").expect(E);
+    // News pages split the text over one item per page, showbiz pages keep a single body
+    static ref BODY: Selector =
+        Selector::parse(".article-content-body__item-content, .read-page--content-body")
+            .expect(E);
+    static ref ACTIVE_PAGE: Selector = Selector::parse(".paging__link--active").expect(E);
+    static ref P: Selector = Selector::parse("p").expect(E);
+}
+
+const SKIPPED_PREFIXES: [&str; 3] = ["Baca Juga", "Baca juga", "Simak Video"];
+
+#[derive(Debug, Clone)]
+pub struct Liputan6Crawler {
+    allowlist: utils::HostAllowlist,
+    excluded_hosts: Vec<String>,
+    excluded_paths: Vec<String>,
+}
+
+impl Default for Liputan6Crawler {
+    fn default() -> Self {
+        Liputan6Crawler {
+            allowlist: utils::HostAllowlist::new(["liputan6.com"]),
+            excluded_hosts: vec!["video.liputan6.com".to_string()],
+            excluded_paths: vec!["/tag/".to_string(), "/video/".to_string()],
+        }
+    }
+}
+
+impl Liputan6Crawler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    utils::allowlist_builders!("liputan6.com");
+
+    /// Never follow links to these hosts or their subdomains, the video site by default.
+    pub fn with_excluded_hosts<I, H>(mut self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = H>,
+        H: Into<String>,
+    {
+        self.excluded_hosts = hosts.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Never follow links whose path starts with one of these, `/tag/` and `/video/` by default.
+    pub fn with_excluded_paths<I, P>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<String>,
+    {
+        self.excluded_paths = paths.into_iter().map(Into::into).collect();
+        self
+    }
+
+    fn is_allowed(&self, url: &reqwest::Url) -> bool {
+        self.allowlist.allows(url)
+            && url.host_str().is_some_and(|host| {
+                !self
+                    .excluded_hosts
+                    .iter()
+                    .any(|excluded| utils::host_matches(host, excluded))
+            })
+            && !self
+                .excluded_paths
+                .iter()
+                .any(|excluded| url.path().starts_with(excluded.as_str()))
+    }
+}
+
+fn wib() -> FixedOffset {
+    FixedOffset::east_opt(7 * 3600).expect("Valid offset")
+}
+
+fn published_date(doc: &Html, ld: Option<&serde_json::Value>) -> Option<DateTime<FixedOffset>> {
+    ld.and_then(|ld| json_ld::string(ld, "datePublished"))
+        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+        .or_else(|| {
+            // "2022-12-12 10:30:22", in WIB
+            doc.select(&DATETIME)
+                .next()
+                .and_then(|el| el.value().attr("datetime"))
+                .and_then(|s| NaiveDateTime::parse_from_str(s.trim(), "%Y-%m-%d %H:%M:%S").ok())
+                .and_then(|naive| wib().from_local_datetime(&naive).single())
+        })
+}
+
+/// Photo credits left as their own paragraph, e.g. "(Liputan6.com/Johan Tallo)".
+fn is_photo_credit(paragraph: &str) -> bool {
+    regex_is_match!(r"(?i)^(<[^>]+>)*\s*\(?\s*(foto|photo)\s*:", paragraph)
+        || regex_is_match!(
+            r"(?i)^(<[^>]+>)*\s*\([^()]*liputan6\.com/[^()]*\)\s*(</[^>]+>)*$",
+            paragraph
+        )
+}
+
+impl Crawler for Liputan6Crawler {
+    type Document = Liputan6Article;
+
+    fn can_be_scrapped(&self, doc: &Html) -> bool {
+        let is_article = json_ld::article(doc).is_some()
+            || utils::meta_content(doc, &OG_TYPE).is_some_and(|t| t == "article");
+        is_article && doc.select(&BODY).next().is_some()
+    }
+
+    fn extract_links(&self, ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        utils::links(doc, ctx.url, |url| self.is_allowed(url))
+    }
+
+    fn crawl(&self, ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        // Pagination links are among the links, so every page of an article is crawled
+        let links = self.extract_urls(ctx, doc);
+
+        if !self.can_be_scrapped(doc) {
+            return CrawlerResult::Links(links);
+        }
+
+        let ld = json_ld::article(doc);
+        let ld_string = |key: &str| ld.as_ref().and_then(|ld| json_ld::string(ld, key));
+
+        let authors = ld
+            .as_ref()
+            .map(|ld| json_ld::names(ld, "author"))
+            .unwrap_or_default();
+        let author = if authors.is_empty() {
+            doc.select(&AUTHOR_NAME)
+                .next()
+                .map(|el| el.text().collect::<String>().trim().to_string())
+                .filter(|s| !s.is_empty())
+                .or_else(|| utils::meta_content(doc, &AUTHOR))
+        } else {
+            Some(authors.join(", "))
+        };
+
+        let mut tags: Vec<String> = doc
+            .select(&TAG)
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if tags.is_empty() {
+            tags = utils::meta_content(doc, &KEYWORDS)
+                .map(|s| {
+                    s.split(',')
+                        .map(|s| s.trim().to_string())
+                        .filter(|s| !s.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default();
+        }
+
+        let page = doc
+            .select(&ACTIVE_PAGE)
+            .next()
+            .and_then(|el| el.text().collect::<String>().trim().parse().ok())
+            .unwrap_or(1);
+
+        let mut paragraphs = vec![];
+        for el in doc.select(&BODY) {
+            for p in el
+                .select(&P)
+                .filter(|p| !utils::has_ancestor_class(p, "baca-juga-collections"))
+            {
+                if let Some(p) = text::clean_paragraph(&p.inner_html()) {
+                    if !text::starts_with_any(&p, &SKIPPED_PREFIXES) && !is_photo_credit(&p) {
+                        paragraphs.push(p);
+                    }
+                }
+            }
+        }
+        paragraphs.dedup();
+
+        let article = Liputan6Article {
+            title: utils::meta_content(doc, &TITLE).or_else(|| ld_string("headline")),
+            published_date: published_date(doc, ld.as_ref()),
+            description: utils::meta_content(doc, &DESCRIPTION)
+                .or_else(|| utils::meta_content(doc, &DESCRIPTION_META))
+                .or_else(|| ld_string("description")),
+            thumbnail_url: utils::meta_content(doc, &THUMBNAIL),
+            author,
+            tags,
+            page,
+            paragraphs,
+        };
+        CrawlerResult::document_and_links(article, links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn photo_credits() {
+        assert!(is_photo_credit("(Liputan6.com/Johan Tallo)"));
+        assert!(is_photo_credit("<em>(Liputan6.com/Herman Zakharia)</em>"));
+        assert!(is_photo_credit("<strong>Foto:</strong> Antara"));
+        assert!(!is_photo_credit(
+            "Data dari (Liputan6.com/riset) menunjukkan kenaikan."
+        ));
+    }
+
+    #[test]
+    fn excluded_links() {
+        let crawler = Liputan6Crawler::new();
+        let allowed = |s: &str| crawler.is_allowed(&reqwest::Url::parse(s).unwrap());
+        assert!(allowed("https://www.liputan6.com/news/read/5145880/judul"));
+        assert!(allowed("https://www.liputan6.com/showbiz"));
+        assert!(!allowed("https://www.liputan6.com/tag/banjir"));
+        assert!(!allowed("https://www.liputan6.com/video/5145881/judul"));
+        assert!(!allowed("https://video.liputan6.com/read/5145882/judul"));
+    }
+}
@@ -0,0 +1,70 @@
+use super::Liputan6Article;
+use crate::generic::{SiteArticle, SiteArticleTable};
+use crate::SqliteStorage;
+use sqlx::query::Query;
+use sqlx::sqlite::SqliteArguments;
+use sqlx::Sqlite;
+
+impl SiteArticle for Liputan6Article {
+    const COLUMNS: &'static [(&'static str, &'static str)] = &[
+        ("author", "TEXT"),
+        ("description", "TEXT"),
+        ("thumbnail_url", "TEXT"),
+        ("tags", "TEXT"),
+        ("page", "INTEGER"),
+    ];
+
+    fn bind<'q>(
+        self,
+        query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    ) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+        query
+            .bind(self.author)
+            .bind(self.description)
+            .bind(self.thumbnail_url)
+            .bind(self.tags.join("|"))
+            .bind(self.page)
+    }
+}
+
+pub type Liputan6ArticleTable = SiteArticleTable<Liputan6Article>;
+
+pub type Liputan6Data = SqliteStorage<Liputan6ArticleTable>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils, Storage, Table};
+    use std::path::Path;
+    use tokio::fs;
+
+    #[tokio::test]
+    async fn columns_mapped() {
+        if Path::new("test_liputan6.db").is_file() {
+            fs::remove_file("test_liputan6.db").await.unwrap();
+        }
+        let p = Liputan6Data::new("test_liputan6").await.unwrap();
+
+        let article = Liputan6Article {
+            title: Some("title".to_string()),
+            published_date: Some(utils::get_now()),
+            description: None,
+            thumbnail_url: None,
+            author: Some("author".to_string()),
+            tags: vec!["t1".to_string()],
+            page: 1,
+            paragraphs: vec!["p1".to_string(), "p2".to_string()],
+        };
+        p.results_insert(("https://www.liputan6.com/read/1", article))
+            .await
+            .unwrap();
+        let row: (String, String, u32) =
+            sqlx::query_as("SELECT author, tags, page FROM test_liputan6_results")
+                .fetch_one(p.results.get_pool())
+                .await
+                .unwrap();
+        assert_eq!(row, ("author".to_string(), "t1".to_string(), 1,));
+
+        fs::remove_file("test_liputan6.db").await.unwrap();
+    }
+}
@@ -0,0 +1,39 @@
+mod crawler;
+#[cfg(feature = "sqlite")]
+mod data;
+
+pub use crawler::Liputan6Crawler;
+#[cfg(feature = "sqlite")]
+pub use data::{Liputan6ArticleTable, Liputan6Data};
+
+use crate::Article;
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Liputan6Article {
+    pub title: Option<String>,
+    pub published_date: Option<DateTime<FixedOffset>>,
+    pub description: Option<String>,
+    pub thumbnail_url: Option<String>,
+    pub author: Option<String>,
+    pub tags: Vec<String>,
+    /// Page of a multi-page article, each page is stored as its own result
+    pub page: u32,
+    pub paragraphs: Vec<String>,
+}
+
+impl Article for Liputan6Article {
+    fn get_paragraphs(&self) -> &[String] {
+        self.paragraphs.as_slice()
+    }
+
+    fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    fn get_published_date(&self) -> Option<DateTime<FixedOffset>> {
+        self.published_date
+    }
+}
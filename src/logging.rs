@@ -0,0 +1,195 @@
+//! Logging to files instead of, or along with, stderr: rotated daily, hourly or once they
+//! reach a size, and written from a background thread so the crawl never waits on the disk.
+
+use clap::ValueEnum;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use tracing_appender::non_blocking::{NonBlocking, WorkerGuard};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+
+/// When a log file is set aside for a new one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogRotation {
+    /// A file per day, `<prefix>.<yyyy-mm-dd>`
+    #[default]
+    Daily,
+    /// A file per hour, `<prefix>.<yyyy-mm-dd-hh>`
+    Hourly,
+    /// `<prefix>.log` until it reaches `LogFileConfig::max_bytes`, then renamed to
+    /// `<prefix>.log.1`, the previous ones to `<prefix>.log.2` and so on
+    Size,
+}
+
+#[derive(Debug, Clone)]
+pub struct LogFileConfig {
+    pub directory: PathBuf,
+    pub prefix: String,
+    pub rotation: LogRotation,
+    /// Size of a file rotated by `LogRotation::Size`
+    pub max_bytes: u64,
+    /// Files kept besides the current one, the oldest are deleted
+    pub max_files: usize,
+}
+
+impl LogFileConfig {
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Self {
+        LogFileConfig {
+            directory: directory.into(),
+            prefix: "crawler".to_string(),
+            rotation: LogRotation::Daily,
+            max_bytes: 100 * 1024 * 1024,
+            max_files: 14,
+        }
+    }
+}
+
+/// A writer to the log files of `config`, writing from a background thread. The lines are
+/// buffered until then and flushed when the guard is dropped, so it must be kept for as long
+/// as the program logs.
+pub fn file_writer(config: &LogFileConfig) -> io::Result<(NonBlocking, WorkerGuard)> {
+    fs::create_dir_all(&config.directory)?;
+    let rotation = match config.rotation {
+        LogRotation::Daily => Rotation::DAILY,
+        LogRotation::Hourly => Rotation::HOURLY,
+        LogRotation::Size => {
+            let path = config.directory.join(format!("{}.log", config.prefix));
+            let file = SizeRotatingFile::open(path, config.max_bytes, config.max_files)?;
+            return Ok(tracing_appender::non_blocking(file));
+        }
+    };
+    let appender = RollingFileAppender::builder()
+        .rotation(rotation)
+        .filename_prefix(&config.prefix)
+        .max_log_files(config.max_files + 1)
+        .build(&config.directory)
+        .map_err(io::Error::other)?;
+    Ok(tracing_appender::non_blocking(appender))
+}
+
+/// A file renamed with a number once it grows past `max_bytes`, keeping `max_files` of them.
+struct SizeRotatingFile {
+    path: PathBuf,
+    file: File,
+    written: u64,
+    max_bytes: u64,
+    max_files: usize,
+}
+
+impl SizeRotatingFile {
+    fn open(path: PathBuf, max_bytes: u64, max_files: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(SizeRotatingFile {
+            written: file.metadata()?.len(),
+            path,
+            file,
+            max_bytes,
+            max_files,
+        })
+    }
+
+    fn numbered(&self, n: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", n));
+        path.into()
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        for n in (1..self.max_files).rev() {
+            let from = self.numbered(n);
+            if Path::new(&from).exists() {
+                fs::rename(from, self.numbered(n + 1))?;
+            }
+        }
+        if self.max_files > 0 {
+            fs::rename(&self.path, self.numbered(1))?;
+        }
+        self.file = File::create(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for SizeRotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing::info;
+
+    /// Log `events` lines through the files of `config`, flushed once it returns.
+    fn log(config: &LogFileConfig, events: usize) {
+        let (writer, guard) = file_writer(config).unwrap();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(writer)
+            .with_ansi(false)
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            for i in 0..events {
+                info!("event {}", i);
+            }
+        });
+        drop(guard);
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn daily_files() {
+        let dir = temp_dir("crawler_logs_daily");
+        log(&LogFileConfig::new(&dir), 3);
+
+        let files: Vec<PathBuf> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 1);
+        let name = files[0].file_name().unwrap().to_str().unwrap();
+        assert!(name.starts_with("crawler."), "{}", name);
+        let logs = fs::read_to_string(&files[0]).unwrap();
+        assert!(
+            logs.contains("event 0") && logs.contains("event 2"),
+            "{}",
+            logs
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn size_rotation() {
+        let dir = temp_dir("crawler_logs_size");
+        let config = LogFileConfig {
+            rotation: LogRotation::Size,
+            max_bytes: 200,
+            max_files: 2,
+            ..LogFileConfig::new(&dir)
+        };
+        log(&config, 20);
+
+        let current = fs::read_to_string(dir.join("crawler.log")).unwrap();
+        assert!(current.contains("event 19"), "{}", current);
+        assert!(current.len() <= 200);
+        let previous = fs::read_to_string(dir.join("crawler.log.1")).unwrap();
+        assert!(!previous.is_empty() && !previous.contains("event 19"));
+        assert!(dir.join("crawler.log.2").exists());
+        assert!(!dir.join("crawler.log.3").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
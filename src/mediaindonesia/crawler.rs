@@ -0,0 +1,192 @@
+use crate::{
+    json_ld, mediaindonesia::MediaIndonesiaArticle, text, utils, CrawlContext, Crawler,
+    CrawlerResult, ExtractedLink,
+};
+use chrono::DateTime;
+use itertools::Itertools;
+use lazy_regex::regex_captures;
+use lazy_static::lazy_static;
+use scraper::{Html, Selector};
+
+const E: &str = "Invalid selector";
+lazy_static! {
+    static ref OG_TYPE: Selector = Selector::parse(r#"meta[property="og:type"]"#).expect(E);
+    static ref TITLE: Selector = Selector::parse(r#"meta[property="og:title"]"#).expect(E);
+    static ref DESCRIPTION: Selector =
+        Selector::parse(r#"meta[property="og:description"]"#).expect(E);
+    static ref THUMBNAIL: Selector = Selector::parse(r#"meta[property="og:image"]"#).expect(E);
+    static ref CANONICAL: Selector = Selector::parse(r#"link[rel="canonical"]"#).expect(E);
+    static ref PUBLISHED_TIME: Selector =
+        Selector::parse(r#"meta[property="article:published_time"]"#).expect(E);
+    static ref AUTHOR: Selector = Selector::parse(".author a, .author").expect(E);
+    static ref TAG: Selector = Selector::parse(".tag a").expect(E);
+    static ref BODY: Selector = Selector::parse("article .article").expect(E);
+    static ref P: Selector = Selector::parse("p").expect(E);
+}
+
+const SKIPPED_PREFIXES: [&str; 2] = ["Baca Juga", "Baca juga"];
+
+#[derive(Debug, Clone)]
+pub struct MediaIndonesiaCrawler {
+    allowlist: utils::HostAllowlist,
+}
+
+impl Default for MediaIndonesiaCrawler {
+    fn default() -> Self {
+        MediaIndonesiaCrawler {
+            allowlist: utils::HostAllowlist::new(["mediaindonesia.com"]),
+        }
+    }
+}
+
+impl MediaIndonesiaCrawler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    utils::allowlist_builders!("mediaindonesia.com");
+}
+
+/// The section of `mediaindonesia.com/<category>/543210/slug`.
+fn category(url: &reqwest::Url) -> Option<String> {
+    url.path_segments()?
+        .next()
+        .filter(|s| !s.is_empty())
+        .map(ToString::to_string)
+}
+
+/// Split the "(Ant/Z-2)" agency and editor code off the end of a paragraph.
+fn split_agency_code(paragraph: &str) -> Option<(String, String)> {
+    let (_, rest, code, _) = regex_captures!(
+        r"^(.*?)\s*\(([A-Za-z]{1,5}(?:/[A-Za-z]{1,5})*-\d{1,3})\)\s*(</[^>]+>)*$"s,
+        paragraph
+    )?;
+    Some((rest.to_string(), code.to_string()))
+}
+
+impl Crawler for MediaIndonesiaCrawler {
+    type Document = MediaIndonesiaArticle;
+
+    fn can_be_scrapped(&self, doc: &Html) -> bool {
+        let is_article = json_ld::article(doc).is_some()
+            || utils::meta_content(doc, &OG_TYPE).is_some_and(|t| t == "article");
+        is_article && doc.select(&BODY).next().is_some()
+    }
+
+    fn extract_links(&self, ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        utils::links(doc, ctx.url, |url| self.allowlist.allows(url))
+    }
+
+    fn crawl(&self, ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        let links = self.extract_urls(ctx, doc);
+
+        if !self.can_be_scrapped(doc) {
+            return CrawlerResult::Links(links);
+        }
+
+        let ld = json_ld::article(doc);
+        let ld_string = |key: &str| ld.as_ref().and_then(|ld| json_ld::string(ld, key));
+
+        let published_date = ld_string("datePublished")
+            .or_else(|| utils::meta_content(doc, &PUBLISHED_TIME))
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok());
+
+        let canonical = doc
+            .select(&CANONICAL)
+            .next()
+            .and_then(|el| el.value().attr("href"))
+            .and_then(|s| reqwest::Url::parse(s.trim()).ok());
+
+        let author = ld
+            .as_ref()
+            .map(|ld| json_ld::names(ld, "author"))
+            .filter(|names| !names.is_empty())
+            .map(|names| names.join(", "))
+            .or_else(|| {
+                doc.select(&AUTHOR)
+                    .map(|el| el.text().collect::<String>().split_whitespace().join(" "))
+                    .find(|s| !s.is_empty())
+            });
+
+        let tags = doc
+            .select(&TAG)
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        let mut paragraphs = vec![];
+        if let Some(body) = doc.select(&BODY).next() {
+            for p in body
+                .select(&P)
+                .filter(|p| !utils::has_ancestor_class(p, "baca-juga"))
+            {
+                if let Some(p) = text::clean_paragraph(&p.inner_html()) {
+                    if !text::starts_with_any(&p, &SKIPPED_PREFIXES) {
+                        paragraphs.push(p);
+                    }
+                }
+            }
+        }
+        paragraphs.dedup();
+
+        let mut agency_code = None;
+        if let Some((rest, code)) = paragraphs.last().and_then(|p| split_agency_code(p)) {
+            agency_code = Some(code);
+            paragraphs.pop();
+            if !rest.is_empty() {
+                paragraphs.push(rest);
+            }
+        }
+
+        let article = MediaIndonesiaArticle {
+            title: utils::meta_content(doc, &TITLE).or_else(|| ld_string("headline")),
+            published_date,
+            description: utils::meta_content(doc, &DESCRIPTION)
+                .or_else(|| ld_string("description")),
+            thumbnail_url: utils::meta_content(doc, &THUMBNAIL),
+            author,
+            agency_code,
+            category: category(canonical.as_ref().unwrap_or(ctx.url)),
+            tags,
+            paragraphs,
+        };
+        CrawlerResult::document_and_links(article, links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn agency_codes() {
+        assert_eq!(
+            split_agency_code("Rapat dilanjutkan pekan depan. (Ant/Z-2)"),
+            Some((
+                "Rapat dilanjutkan pekan depan.".to_string(),
+                "Ant/Z-2".to_string()
+            ))
+        );
+        assert_eq!(
+            split_agency_code("(OL-13)"),
+            Some((String::new(), "OL-13".to_string()))
+        );
+        assert_eq!(split_agency_code("Skor akhir (2-1)"), None);
+        assert_eq!(split_agency_code("Kata Ketua DPR (RI)"), None);
+    }
+
+    #[test]
+    fn category_from_page_url() {
+        let doc = Html::parse_document(
+            r#"<meta property="og:type" content="article">
+            <article><div class="article"><p>Isi.</p></div></article>"#,
+        );
+        let url = reqwest::Url::parse("https://mediaindonesia.com/humaniora/543400/judul").unwrap();
+        match MediaIndonesiaCrawler::new().crawl(&CrawlContext::new(&url), &doc) {
+            CrawlerResult::DocumentAndLinks(article, _) => {
+                assert_eq!(article.category.as_deref(), Some("humaniora"))
+            }
+            _ => panic!("Not an article"),
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use super::MediaIndonesiaArticle;
+use crate::generic::{SiteArticle, SiteArticleTable};
+use crate::SqliteStorage;
+use sqlx::query::Query;
+use sqlx::sqlite::SqliteArguments;
+use sqlx::Sqlite;
+
+impl SiteArticle for MediaIndonesiaArticle {
+    const COLUMNS: &'static [(&'static str, &'static str)] = &[
+        ("author", "TEXT"),
+        ("agency_code", "TEXT"),
+        ("category", "TEXT"),
+        ("description", "TEXT"),
+        ("thumbnail_url", "TEXT"),
+        ("tags", "TEXT"),
+    ];
+
+    fn bind<'q>(
+        self,
+        query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    ) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+        query
+            .bind(self.author)
+            .bind(self.agency_code)
+            .bind(self.category)
+            .bind(self.description)
+            .bind(self.thumbnail_url)
+            .bind(self.tags.join("|"))
+    }
+}
+
+pub type MediaIndonesiaArticleTable = SiteArticleTable<MediaIndonesiaArticle>;
+
+pub type MediaIndonesiaData = SqliteStorage<MediaIndonesiaArticleTable>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils, Storage, Table};
+    use std::path::Path;
+    use tokio::fs;
+
+    #[tokio::test]
+    async fn columns_mapped() {
+        if Path::new("test_mediaindonesia.db").is_file() {
+            fs::remove_file("test_mediaindonesia.db").await.unwrap();
+        }
+        let p = MediaIndonesiaData::new("test_mediaindonesia")
+            .await
+            .unwrap();
+
+        let article = MediaIndonesiaArticle {
+            title: Some("title".to_string()),
+            published_date: Some(utils::get_now()),
+            description: None,
+            thumbnail_url: None,
+            author: Some("author".to_string()),
+            agency_code: Some("Z-2".to_string()),
+            category: Some("nasional".to_string()),
+            tags: vec!["t1".to_string()],
+            paragraphs: vec!["p1".to_string(), "p2".to_string()],
+        };
+        p.results_insert((
+            "https://mediaindonesia.com/politik-dan-hukum/543210/judul",
+            article,
+        ))
+        .await
+        .unwrap();
+        let row: (String, String, String, String) = sqlx::query_as(
+            "SELECT author, agency_code, category, tags FROM test_mediaindonesia_results",
+        )
+        .fetch_one(p.results.get_pool())
+        .await
+        .unwrap();
+        assert_eq!(
+            row,
+            (
+                "author".to_string(),
+                "Z-2".to_string(),
+                "nasional".to_string(),
+                "t1".to_string(),
+            )
+        );
+
+        fs::remove_file("test_mediaindonesia.db").await.unwrap();
+    }
+}
@@ -0,0 +1,41 @@
+mod crawler;
+#[cfg(feature = "sqlite")]
+mod data;
+
+pub use crawler::MediaIndonesiaCrawler;
+#[cfg(feature = "sqlite")]
+pub use data::{MediaIndonesiaArticleTable, MediaIndonesiaData};
+
+use crate::Article;
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MediaIndonesiaArticle {
+    pub title: Option<String>,
+    pub published_date: Option<DateTime<FixedOffset>>,
+    pub description: Option<String>,
+    pub thumbnail_url: Option<String>,
+    pub author: Option<String>,
+    /// The "(Ant/Z-2)" code of the agency and editor closing the last paragraph
+    pub agency_code: Option<String>,
+    /// Section from the url: `politik-dan-hukum`, `ekonomi`, ...
+    pub category: Option<String>,
+    pub tags: Vec<String>,
+    pub paragraphs: Vec<String>,
+}
+
+impl Article for MediaIndonesiaArticle {
+    fn get_paragraphs(&self) -> &[String] {
+        self.paragraphs.as_slice()
+    }
+
+    fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    fn get_published_date(&self) -> Option<DateTime<FixedOffset>> {
+        self.published_date
+    }
+}
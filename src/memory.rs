@@ -0,0 +1,777 @@
+use crate::recrawl::LinkSnapshot;
+use crate::{
+    utils, Article, Bucket, CrawlerError, ExtractionStatus, HostState, HubState, QueuedUrl,
+    ResultEntry, ResultSummary, Revision, Storage, UrlBucket, WarnedEntry,
+};
+use chrono::{DateTime, FixedOffset};
+use futures::future;
+use futures::stream::{self, BoxStream, StreamExt};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A `Storage` kept entirely in memory, for tests and short throwaway crawls.
+///
+/// Clones share the same data, so one can be kept to inspect after `run_scrapper`
+/// has taken the other.
+#[derive(Debug)]
+pub struct MemoryStorage<R> {
+    queued: Arc<MemoryBucket>,
+    running: Arc<MemoryBucket>,
+    visited: Arc<MemoryBucket>,
+    /// The worker of each claimed running url. Only claimed urls are reaped, the others were
+    /// inserted by the process itself.
+    claims: Arc<Mutex<HashMap<String, Claim>>>,
+    inner: Arc<Mutex<Inner<R>>>,
+}
+
+#[derive(Debug)]
+struct Claim {
+    worker_id: String,
+    heartbeat_at: DateTime<FixedOffset>,
+}
+
+impl<R> Clone for MemoryStorage<R> {
+    fn clone(&self) -> Self {
+        MemoryStorage {
+            queued: Arc::clone(&self.queued),
+            running: Arc::clone(&self.running),
+            visited: Arc::clone(&self.visited),
+            claims: Arc::clone(&self.claims),
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Inner<R> {
+    warned: Vec<WarnedEntry>,
+    /// Url, record, referrer and when it was stored
+    results: Vec<(String, R, Option<String>, DateTime<FixedOffset>)>,
+    results_index: HashMap<String, usize>,
+    /// The results stored with their metadata only
+    metadata_only: HashSet<String>,
+    revisions: Vec<Revision>,
+    /// When each url is due to be revisited
+    revisits: HashMap<String, DateTime<FixedOffset>>,
+    /// The backoff of each host
+    host_states: HashMap<String, HostState>,
+    /// The recrawl rate of each hub page
+    hubs: HashMap<String, HubState>,
+    /// The links found on each hub page
+    hub_snapshots: HashMap<String, LinkSnapshot>,
+}
+
+impl<R> Default for MemoryStorage<R> {
+    fn default() -> Self {
+        MemoryStorage {
+            queued: Arc::default(),
+            running: Arc::default(),
+            visited: Arc::default(),
+            claims: Arc::default(),
+            inner: Arc::new(Mutex::new(Inner {
+                warned: vec![],
+                results: vec![],
+                results_index: HashMap::new(),
+                metadata_only: HashSet::new(),
+                revisions: vec![],
+                revisits: HashMap::new(),
+                host_states: HashMap::new(),
+                hubs: HashMap::new(),
+                hub_snapshots: HashMap::new(),
+            })),
+        }
+    }
+}
+
+impl<R> MemoryStorage<R> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner<R>> {
+        self.inner.lock().unwrap()
+    }
+
+    /// Move the running urls of the claims matching `requeue` back to the queue.
+    fn requeue_claims(&self, requeue: impl Fn(&Claim) -> bool) -> Vec<String> {
+        let mut queued = self.queued.lock();
+        let mut running = self.running.lock();
+        let mut claims = self.claims.lock().unwrap();
+        // Claims of urls done since
+        claims.retain(|url, _| running.contains(url));
+        let urls: Vec<String> = running
+            .order
+            .iter()
+            .filter(|url| claims.get(*url).is_some_and(&requeue))
+            .cloned()
+            .collect();
+        for url in &urls {
+            claims.remove(url);
+            let meta = running.meta.get(url).cloned();
+            running.remove(url);
+            match meta {
+                Some(meta) => queued.insert_queued(meta),
+                None => queued.insert(url),
+            }
+        }
+        urls
+    }
+
+    /// Queue `queued` unless its url is already queued, running or visited.
+    fn insert_unseen(&self, queued: QueuedUrl) {
+        // Hold every bucket, so a url moving from running to visited can't slip between checks.
+        // Locked in the order of the claims, queued first.
+        let url = &*queued.url;
+        let mut urls = self.queued.lock();
+        let running = self.running.lock();
+        let visited = self.visited.lock();
+        if !visited.contains(url) && !running.contains(url) && !urls.contains(url) {
+            urls.insert_queued(queued);
+        }
+    }
+
+    pub fn result(&self, url: &str) -> Option<R>
+    where
+        R: Clone,
+    {
+        let inner = self.lock();
+        inner
+            .results_index
+            .get(url)
+            .map(|&i| inner.results[i].1.clone())
+    }
+
+    pub fn visited(&self) -> Vec<String> {
+        let mut visited = self.visited.lock().order.clone();
+        visited.sort();
+        visited
+    }
+}
+
+#[derive(Debug, Default)]
+struct MemoryBucket {
+    urls: Mutex<Urls>,
+}
+
+#[derive(Debug, Default)]
+struct Urls {
+    order: Vec<String>,
+    /// When each url was added
+    added: HashMap<String, DateTime<FixedOffset>>,
+    /// How the crawl came to the urls inserted with a referrer
+    meta: HashMap<String, QueuedUrl>,
+    /// The urls in the order they're claimed: the highest priority first, the oldest first
+    /// among the same priority
+    by_priority: BTreeMap<(Reverse<i32>, u64), String>,
+    /// The key of each url in `by_priority`
+    keys: HashMap<String, (Reverse<i32>, u64)>,
+    inserted: u64,
+}
+
+impl Urls {
+    fn insert(&mut self, url: &str) {
+        self.insert_with_priority(url, 0);
+    }
+
+    /// Insert `queued` with how the crawl came to it.
+    fn insert_queued(&mut self, queued: QueuedUrl) {
+        if self.insert_with_priority(&queued.url, queued.priority) {
+            self.meta.insert(queued.url.to_string(), queued);
+        }
+    }
+
+    /// Whether `url` was inserted, rather than already there.
+    fn insert_with_priority(&mut self, url: &str, priority: i32) -> bool {
+        if self.added.contains_key(url) {
+            return false;
+        }
+        self.added.insert(url.to_string(), utils::get_now());
+        self.order.push(url.to_string());
+        let key = (Reverse(priority), self.inserted);
+        self.inserted += 1;
+        self.by_priority.insert(key, url.to_string());
+        self.keys.insert(url.to_string(), key);
+        true
+    }
+
+    fn contains(&self, url: &str) -> bool {
+        self.added.contains_key(url)
+    }
+
+    fn remove(&mut self, url: &str) {
+        if self.added.remove(url).is_some() {
+            self.order.retain(|u| u != url);
+            self.forget(url);
+        }
+    }
+
+    /// Remove every url of `urls` at once.
+    fn remove_all(&mut self, urls: &HashSet<String>) {
+        self.order.retain(|url| !urls.contains(url));
+        for url in urls {
+            if self.added.remove(url).is_some() {
+                self.forget(url);
+            }
+        }
+    }
+
+    fn forget(&mut self, url: &str) {
+        self.meta.remove(url);
+        if let Some(key) = self.keys.remove(url) {
+            self.by_priority.remove(&key);
+        }
+    }
+}
+
+impl MemoryBucket {
+    fn lock(&self) -> MutexGuard<'_, Urls> {
+        self.urls.lock().unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+impl UrlBucket for MemoryBucket {
+    async fn urls(&self, limit: Option<u32>) -> Result<Vec<String>, CrawlerError> {
+        let urls = self.lock();
+        let limit = limit.map_or(urls.order.len(), |n| n as usize);
+        Ok(urls.order.iter().take(limit).cloned().collect())
+    }
+
+    /// Copies a url at a time, not the whole bucket. Urls removed meanwhile shift the ones
+    /// after them, which may then be skipped.
+    fn stream(&self) -> BoxStream<'_, Result<String, CrawlerError>> {
+        stream::iter(0..)
+            .map(move |i| self.lock().order.get(i).cloned())
+            .take_while(|url| future::ready(url.is_some()))
+            .filter_map(|url| future::ready(url.map(Ok)))
+            .boxed()
+    }
+
+    async fn insert(&self, url: &str) -> Result<(), CrawlerError> {
+        self.lock().insert(url);
+        Ok(())
+    }
+
+    async fn delete(&self, url: &str) -> Result<(), CrawlerError> {
+        self.lock().remove(url);
+        Ok(())
+    }
+
+    async fn contains(&self, url: &str) -> Result<bool, CrawlerError> {
+        Ok(self.lock().contains(url))
+    }
+
+    async fn count(&self) -> Result<u32, CrawlerError> {
+        Ok(self.lock().order.len() as u32)
+    }
+
+    async fn oldest_added_at(&self) -> Result<Option<DateTime<FixedOffset>>, CrawlerError> {
+        let urls = self.lock();
+        Ok(urls.order.first().map(|url| urls.added[url]))
+    }
+}
+
+#[async_trait::async_trait]
+impl<R: Article + Send + Sync> Storage for MemoryStorage<R> {
+    type Record = R;
+
+    fn bucket(&self, bucket: Bucket) -> &dyn UrlBucket {
+        match bucket {
+            Bucket::Queued => self.queued.as_ref(),
+            Bucket::Running => self.running.as_ref(),
+            Bucket::Visited => self.visited.as_ref(),
+        }
+    }
+
+    async fn queued_insert_with_meta<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        referrer: Option<&str>,
+        depth: u32,
+    ) -> Result<(), CrawlerError> {
+        self.insert_unseen(QueuedUrl {
+            referrer: referrer.map(ToString::to_string),
+            depth,
+            ..QueuedUrl::new(item.as_ref())
+        });
+        Ok(())
+    }
+
+    async fn queued_insert_prioritized<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        referrer: Option<&str>,
+        depth: u32,
+        priority: i32,
+    ) -> Result<(), CrawlerError> {
+        self.insert_unseen(QueuedUrl {
+            referrer: referrer.map(ToString::to_string),
+            depth,
+            priority,
+            ..QueuedUrl::new(item.as_ref())
+        });
+        Ok(())
+    }
+
+    async fn queued_insert_with_priority<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        priority: i32,
+    ) -> Result<(), CrawlerError> {
+        self.insert_unseen(QueuedUrl {
+            priority,
+            ..QueuedUrl::new(item.as_ref())
+        });
+        Ok(())
+    }
+
+    async fn queued_claim_n(
+        &self,
+        worker_id: &str,
+        n: u32,
+    ) -> Result<Vec<QueuedUrl>, CrawlerError> {
+        let heartbeat_at = utils::get_now();
+        let mut queued = self.queued.lock();
+        let mut running = self.running.lock();
+        let visited = self.visited.lock();
+        let mut claims = self.claims.lock().unwrap();
+        let mut claimed = vec![];
+        let mut taken = HashSet::new();
+        for url in queued.by_priority.values() {
+            if claimed.len() == n as usize {
+                break;
+            }
+            taken.insert(url.clone());
+            // Already running or visited, dropped from the queue like the dispatcher always did
+            if running.contains(url) || visited.contains(url) {
+                continue;
+            }
+            running.insert(url);
+            let meta = queued.meta.get(url).cloned();
+            if let Some(meta) = &meta {
+                running.meta.insert(url.clone(), meta.clone());
+            }
+            claims.insert(
+                url.clone(),
+                Claim {
+                    worker_id: worker_id.to_string(),
+                    heartbeat_at,
+                },
+            );
+            claimed.push(meta.unwrap_or_else(|| QueuedUrl::new(url.as_str())));
+        }
+        queued.remove_all(&taken);
+        Ok(claimed)
+    }
+
+    async fn running_claim<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        worker_id: &str,
+    ) -> Result<bool, CrawlerError> {
+        let url = item.as_ref();
+        let mut queued = self.queued.lock();
+        let mut running = self.running.lock();
+        let visited = self.visited.lock();
+        let meta = queued.meta.get(url).cloned();
+        queued.remove(url);
+        if running.contains(url) || visited.contains(url) {
+            return Ok(false);
+        }
+        running.insert(url);
+        if let Some(meta) = meta {
+            running.meta.insert(url.to_string(), meta);
+        }
+        self.claims.lock().unwrap().insert(
+            url.to_string(),
+            Claim {
+                worker_id: worker_id.to_string(),
+                heartbeat_at: utils::get_now(),
+            },
+        );
+        Ok(true)
+    }
+
+    async fn running_touch(
+        &self,
+        worker_id: &str,
+        now: DateTime<FixedOffset>,
+    ) -> Result<(), CrawlerError> {
+        for claim in self.claims.lock().unwrap().values_mut() {
+            if claim.worker_id == worker_id {
+                claim.heartbeat_at = now;
+            }
+        }
+        Ok(())
+    }
+
+    async fn running_reap(
+        &self,
+        stale_before: DateTime<FixedOffset>,
+    ) -> Result<Vec<String>, CrawlerError> {
+        Ok(self.requeue_claims(|claim| claim.heartbeat_at < stale_before))
+    }
+
+    async fn running_release(&self, worker_id: &str) -> Result<Vec<String>, CrawlerError> {
+        Ok(self.requeue_claims(|claim| claim.worker_id == worker_id))
+    }
+
+    async fn running_requeue<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        let url = item.as_ref();
+        let mut queued = self.queued.lock();
+        let mut running = self.running.lock();
+        self.claims.lock().unwrap().remove(url);
+        if !running.contains(url) {
+            drop((queued, running));
+            self.insert_unseen(QueuedUrl::new(url));
+            return Ok(());
+        }
+        let meta = running.meta.get(url).cloned();
+        running.remove(url);
+        queued.insert_queued(meta.unwrap_or_else(|| QueuedUrl::new(url)));
+        Ok(())
+    }
+
+    async fn results_count(&self) -> Result<u32, CrawlerError> {
+        Ok(self.lock().results.len() as u32)
+    }
+
+    async fn results_recent(&self, n: u32) -> Result<Vec<ResultSummary>, CrawlerError> {
+        Ok(self
+            .lock()
+            .results
+            .iter()
+            .rev()
+            .take(n as usize)
+            .map(|(url, record, referrer, created_at)| ResultSummary {
+                url: url.clone(),
+                title: record.get_title().map(ToString::to_string),
+                published_date: record.get_published_date(),
+                created_at: Some(*created_at),
+                referrer: referrer.clone(),
+            })
+            .collect())
+    }
+
+    async fn results_get(&self, url: &str) -> Result<Option<ResultEntry>, CrawlerError> {
+        let inner = self.lock();
+        Ok(inner.results_index.get(url.trim()).map(|&i| {
+            let (url, record, referrer, created_at) = &inner.results[i];
+            ResultEntry {
+                created_at: Some(*created_at),
+                referrer: referrer.clone(),
+                ..ResultEntry::new(url, record)
+            }
+        }))
+    }
+
+    async fn results_insert_with_referrer<I: AsRef<str> + Send>(
+        &self,
+        (url, record): (I, Self::Record),
+        referrer: Option<&str>,
+    ) -> Result<(), CrawlerError> {
+        let url = url.as_ref().trim();
+        let mut inner = self.lock();
+        if inner.metadata_only.remove(url) {
+            // The referrer it was first found on is kept
+            let index = inner.results_index[url];
+            inner.results[index].1 = record;
+            inner.results[index].3 = utils::get_now();
+        } else if !inner.results_index.contains_key(url) {
+            let index = inner.results.len();
+            inner.results_index.insert(url.to_string(), index);
+            inner.results.push((
+                url.to_string(),
+                record,
+                referrer.map(ToString::to_string),
+                utils::get_now(),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn results_insert_metadata_only<I: AsRef<str> + Send>(
+        &self,
+        (url, record): (I, Self::Record),
+        referrer: Option<&str>,
+    ) -> Result<(), CrawlerError> {
+        let url = url.as_ref().trim();
+        let mut inner = self.lock();
+        if !inner.results_index.contains_key(url) {
+            let index = inner.results.len();
+            inner.results_index.insert(url.to_string(), index);
+            inner.results.push((
+                url.to_string(),
+                record,
+                referrer.map(ToString::to_string),
+                utils::get_now(),
+            ));
+            inner.metadata_only.insert(url.to_string());
+        }
+        Ok(())
+    }
+
+    async fn results_status(&self, url: &str) -> Result<Option<ExtractionStatus>, CrawlerError> {
+        let url = url.trim();
+        let inner = self.lock();
+        Ok(if inner.metadata_only.contains(url) {
+            Some(ExtractionStatus::MetadataOnly)
+        } else {
+            inner
+                .results_index
+                .contains_key(url)
+                .then_some(ExtractionStatus::Full)
+        })
+    }
+
+    async fn results_revise<I: AsRef<str> + Send>(
+        &self,
+        (url, record): (I, Self::Record),
+        diff: &str,
+    ) -> Result<(), CrawlerError> {
+        let url = url.as_ref().trim();
+        let now = utils::get_now();
+        let entry = ResultEntry::new(url, &record);
+        let mut inner = self.lock();
+        inner.revisions.push(Revision {
+            url: entry.url,
+            title: entry.title,
+            published_date: entry.published_date,
+            paragraphs: entry.paragraphs,
+            diff: diff.to_string(),
+            created_at: Some(now),
+        });
+        inner.metadata_only.remove(url);
+        match inner.results_index.get(url) {
+            Some(&i) => {
+                let result = &mut inner.results[i];
+                result.1 = record;
+                result.3 = now;
+            }
+            None => {
+                let index = inner.results.len();
+                inner.results_index.insert(url.to_string(), index);
+                inner.results.push((url.to_string(), record, None, now));
+            }
+        }
+        Ok(())
+    }
+
+    async fn revisions_get(&self, url: &str) -> Result<Vec<Revision>, CrawlerError> {
+        Ok(self
+            .lock()
+            .revisions
+            .iter()
+            .filter(|revision| revision.url == url.trim())
+            .cloned()
+            .collect())
+    }
+
+    async fn revisit_schedule(
+        &self,
+        url: &str,
+        due_at: DateTime<FixedOffset>,
+    ) -> Result<(), CrawlerError> {
+        self.lock().revisits.insert(url.to_string(), due_at);
+        Ok(())
+    }
+
+    async fn revisit_due(
+        &self,
+        now: DateTime<FixedOffset>,
+        n: u32,
+    ) -> Result<Vec<String>, CrawlerError> {
+        let mut inner = self.lock();
+        let mut due: Vec<(DateTime<FixedOffset>, String)> = inner
+            .revisits
+            .iter()
+            .filter(|(_, due_at)| **due_at <= now)
+            .map(|(url, due_at)| (*due_at, url.clone()))
+            .collect();
+        due.sort();
+        due.truncate(n as usize);
+        for (_, url) in &due {
+            inner.revisits.remove(url);
+        }
+        Ok(due.into_iter().map(|(_, url)| url).collect())
+    }
+
+    async fn warned_insert<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        reason: &str,
+    ) -> Result<(), CrawlerError> {
+        self.warned_schedule(item.as_ref(), reason, 0, None).await
+    }
+
+    async fn warned_count(&self) -> Result<u32, CrawlerError> {
+        Ok(self.lock().warned.len() as u32)
+    }
+
+    async fn warned_recent(&self, n: u32) -> Result<Vec<WarnedEntry>, CrawlerError> {
+        Ok(self
+            .lock()
+            .warned
+            .iter()
+            .rev()
+            .take(n as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn warned_get(&self, item: &str) -> Result<Option<WarnedEntry>, CrawlerError> {
+        Ok(self.lock().warned.iter().find(|w| w.url == item).cloned())
+    }
+
+    async fn warned_delete(&self, item: &str) -> Result<(), CrawlerError> {
+        self.lock().warned.retain(|w| w.url != item);
+        Ok(())
+    }
+
+    async fn warned_schedule(
+        &self,
+        item: &str,
+        reason: &str,
+        attempts: u32,
+        next_retry_at: Option<DateTime<FixedOffset>>,
+    ) -> Result<(), CrawlerError> {
+        let mut inner = self.lock();
+        inner.warned.retain(|w| w.url != item);
+        inner.warned.push(WarnedEntry {
+            url: item.to_string(),
+            reason: Some(reason.to_string()),
+            created_at: Some(utils::get_now()),
+            attempts,
+            next_retry_at,
+        });
+        Ok(())
+    }
+
+    async fn warned_due(
+        &self,
+        now: DateTime<FixedOffset>,
+        n: u32,
+    ) -> Result<Vec<WarnedEntry>, CrawlerError> {
+        let mut due: Vec<WarnedEntry> = self
+            .lock()
+            .warned
+            .iter()
+            .filter(|w| w.next_retry_at.is_some_and(|at| at <= now))
+            .cloned()
+            .collect();
+        due.sort_by_key(|w| w.next_retry_at);
+        due.truncate(n as usize);
+        Ok(due)
+    }
+
+    async fn host_state_get(&self, host: &str) -> Result<Option<HostState>, CrawlerError> {
+        Ok(self.lock().host_states.get(host).cloned())
+    }
+
+    async fn host_state_put(&self, state: &HostState) -> Result<(), CrawlerError> {
+        self.lock()
+            .host_states
+            .insert(state.host.clone(), state.clone());
+        Ok(())
+    }
+
+    async fn hub_get(&self, url: &str) -> Result<Option<HubState>, CrawlerError> {
+        Ok(self.lock().hubs.get(url).cloned())
+    }
+
+    async fn hub_put(&self, state: &HubState) -> Result<(), CrawlerError> {
+        self.lock().hubs.insert(state.url.clone(), state.clone());
+        Ok(())
+    }
+
+    async fn hubs_get(&self) -> Result<Vec<HubState>, CrawlerError> {
+        let mut hubs: Vec<HubState> = self.lock().hubs.values().cloned().collect();
+        hubs.sort_by(|a, b| a.interval.cmp(&b.interval).then_with(|| a.url.cmp(&b.url)));
+        Ok(hubs)
+    }
+
+    async fn hub_snapshot_get(&self, url: &str) -> Result<Option<LinkSnapshot>, CrawlerError> {
+        Ok(self.lock().hub_snapshots.get(url).cloned())
+    }
+
+    async fn hub_snapshot_put(
+        &self,
+        url: &str,
+        snapshot: &LinkSnapshot,
+    ) -> Result<(), CrawlerError> {
+        self.lock()
+            .hub_snapshots
+            .insert(url.to_string(), snapshot.clone());
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "detik"))]
+mod tests {
+    use super::*;
+    use crate::detik::DetikArticle;
+
+    fn article(title: &str) -> DetikArticle {
+        DetikArticle {
+            title: Some(title.to_string()),
+            published_date: None,
+            description: None,
+            thumbnail_url: None,
+            author: None,
+            desk: None,
+            category: None,
+            language: None,
+            keywords: vec![],
+            keywords_raw: None,
+            videos: vec![],
+            photos: vec![],
+            body_selector: None,
+            paragraphs: vec!["p".to_string()],
+        }
+    }
+
+    #[tokio::test]
+    async fn buckets() {
+        let s = MemoryStorage::<DetikArticle>::new();
+
+        s.queued_insert_batch(&["1", "2", "3", "2"]).await.unwrap();
+        assert_eq!(s.queued_get().await.unwrap(), vec!["1", "2", "3"]);
+        assert_eq!(s.queued_get_n(2).await.unwrap(), vec!["1", "2"]);
+
+        s.running_insert("1").await.unwrap();
+        s.queued_delete("1").await.unwrap();
+        s.running_insert("4").await.unwrap();
+        s.merge_queue_and_running().await.unwrap();
+        assert_eq!(s.queued_get().await.unwrap(), vec!["2", "3", "1", "4"]);
+        assert_eq!(s.running_count().await.unwrap(), 0);
+
+        s.visited_insert("5").await.unwrap();
+        s.running_insert("6").await.unwrap();
+        for url in ["5", "6", "7", "2"] {
+            s.queued_insert_unseen(url).await.unwrap();
+        }
+        assert_eq!(s.queued_get().await.unwrap(), vec!["2", "3", "1", "4", "7"]);
+    }
+
+    #[tokio::test]
+    async fn results_and_warned() {
+        let s = MemoryStorage::new();
+        s.results_insert(("a", article("A"))).await.unwrap();
+        s.results_insert(("b", article("B"))).await.unwrap();
+        s.results_insert(("a", article("A again"))).await.unwrap();
+        assert_eq!(s.results_count().await.unwrap(), 2);
+        assert_eq!(s.result("a").unwrap().title.as_deref(), Some("A"));
+        let recent = s.results_recent(1).await.unwrap();
+        assert_eq!(recent[0].url, "b");
+        assert_eq!(recent[0].title.as_deref(), Some("B"));
+
+        s.warned_insert("w", "empty_paragraphs").await.unwrap();
+        s.warned_insert("w", "paywalled").await.unwrap();
+        assert_eq!(s.warned_count().await.unwrap(), 1);
+        assert_eq!(
+            s.warned_recent(10).await.unwrap()[0].reason.as_deref(),
+            Some("paywalled")
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use crate::{
+    json_ld, merdeka::MerdekaArticle, text, utils, CrawlContext, Crawler, CrawlerResult,
+    ExtractedLink,
+};
+use chrono::DateTime;
+use lazy_regex::{regex, regex_is_match};
+use lazy_static::lazy_static;
+use scraper::{Html, Selector};
+
+const E: &str = "Invalid selector";
+lazy_static! {
+    static ref OG_TYPE: Selector = Selector::parse(r#"meta[property="og:type"]"#).expect(E);
+    static ref TITLE: Selector = Selector::parse(r#"meta[property="og:title"]"#).expect(E);
+    static ref DESCRIPTION: Selector =
+        Selector::parse(r#"meta[property="og:description"]"#).expect(E);
+    static ref THUMBNAIL: Selector = Selector::parse(r#"meta[property="og:image"]"#).expect(E);
+    static ref PUBLISHED_TIME: Selector =
+        Selector::parse(r#"meta[property="article:published_time"]"#).expect(E);
+    static ref KEYWORDS: Selector = Selector::parse(r#"meta[name="keywords"]"#).expect(E);
+    static ref REPORTER: Selector = Selector::parse(".mdk-body-reporter, .reporter").expect(E);
+    static ref TAG: Selector = Selector::parse(".mdk-tag-cloud a").expect(E);
+    // News pages and the regional template of e.g. merdeka.com/jatim
+    static ref BODY: Selector =
+        Selector::parse(".mdk-body-paragraph, .mdk-regional-body").expect(E);
+    static ref ACTIVE_PAGE: Selector = Selector::parse(".mdk-pagination .active").expect(E);
+    static ref P: Selector = Selector::parse("p").expect(E);
+}
+
+const SKIPPED_PREFIXES: [&str; 3] = ["Baca juga", "Baca Juga", "Simak video"];
+
+#[derive(Debug, Clone)]
+pub struct MerdekaCrawler {
+    allowlist: utils::HostAllowlist,
+}
+
+impl Default for MerdekaCrawler {
+    fn default() -> Self {
+        MerdekaCrawler {
+            allowlist: utils::HostAllowlist::new(["merdeka.com"]),
+        }
+    }
+}
+
+impl MerdekaCrawler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    utils::allowlist_builders!("merdeka.com");
+}
+
+/// Photo credits ("©2022 Merdeka.com/Imam Buhori") and the "Advertisement" separators
+/// placed between paragraphs.
+fn is_boilerplate(paragraph: &str) -> bool {
+    regex_is_match!(r"^(<[^>]+>)*\s*(©|&copy;)", paragraph)
+        || regex_is_match!(r"(?i)^(<[^>]+>)*\s*advertisement\s*(</[^>]+>)*$", paragraph)
+}
+
+impl Crawler for MerdekaCrawler {
+    type Document = MerdekaArticle;
+
+    fn can_be_scrapped(&self, doc: &Html) -> bool {
+        let is_article = json_ld::article(doc).is_some()
+            || utils::meta_content(doc, &OG_TYPE).is_some_and(|t| t == "article");
+        is_article && doc.select(&BODY).next().is_some()
+    }
+
+    fn extract_links(&self, ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        utils::links(doc, ctx.url, |url| self.allowlist.allows(url))
+    }
+
+    fn crawl(&self, ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        // Pagination links are among the links, so every page of an article is crawled
+        let links = self.extract_urls(ctx, doc);
+
+        if !self.can_be_scrapped(doc) {
+            return CrawlerResult::Links(links);
+        }
+
+        let ld = json_ld::article(doc);
+        let ld_string = |key: &str| ld.as_ref().and_then(|ld| json_ld::string(ld, key));
+
+        let published_date = ld_string("datePublished")
+            .or_else(|| utils::meta_content(doc, &PUBLISHED_TIME))
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok());
+
+        // "Reporter : Name"
+        let author = doc
+            .select(&REPORTER)
+            .next()
+            .map(|el| el.text().collect::<String>())
+            .map(|s| {
+                regex!(r"(?i)^\s*reporter\s*:?\s*")
+                    .replace(s.trim(), "")
+                    .to_string()
+            })
+            .filter(|s| !s.is_empty())
+            .or_else(|| {
+                ld.as_ref()
+                    .map(|ld| json_ld::names(ld, "author"))
+                    .filter(|names| !names.is_empty())
+                    .map(|names| names.join(", "))
+            });
+
+        let mut tags: Vec<String> = doc
+            .select(&TAG)
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if tags.is_empty() {
+            tags = utils::meta_content(doc, &KEYWORDS)
+                .map(|s| {
+                    s.split(',')
+                        .map(|s| s.trim().to_string())
+                        .filter(|s| !s.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default();
+        }
+
+        let page = doc
+            .select(&ACTIVE_PAGE)
+            .next()
+            .and_then(|el| el.text().collect::<String>().trim().parse().ok())
+            .unwrap_or(1);
+
+        let mut paragraphs = vec![];
+        for el in doc.select(&BODY) {
+            for p in el.select(&P) {
+                if let Some(p) = text::clean_paragraph(&p.inner_html()) {
+                    if !text::starts_with_any(&p, &SKIPPED_PREFIXES) && !is_boilerplate(&p) {
+                        paragraphs.push(p);
+                    }
+                }
+            }
+        }
+        paragraphs.dedup();
+
+        let article = MerdekaArticle {
+            title: utils::meta_content(doc, &TITLE).or_else(|| ld_string("headline")),
+            published_date,
+            description: utils::meta_content(doc, &DESCRIPTION)
+                .or_else(|| ld_string("description")),
+            thumbnail_url: utils::meta_content(doc, &THUMBNAIL),
+            author,
+            tags,
+            page,
+            paragraphs,
+        };
+        CrawlerResult::document_and_links(article, links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boilerplate() {
+        assert!(is_boilerplate("©2022 Merdeka.com/Imam Buhori"));
+        assert!(is_boilerplate("<em>© 2022 Merdeka.com</em>"));
+        assert!(is_boilerplate("ADVERTISEMENT"));
+        assert!(is_boilerplate("<span>Advertisement</span>"));
+        assert!(!is_boilerplate(
+            "Advertisement revenue naik 20 persen tahun ini."
+        ));
+    }
+}
@@ -0,0 +1,70 @@
+use super::MerdekaArticle;
+use crate::generic::{SiteArticle, SiteArticleTable};
+use crate::SqliteStorage;
+use sqlx::query::Query;
+use sqlx::sqlite::SqliteArguments;
+use sqlx::Sqlite;
+
+impl SiteArticle for MerdekaArticle {
+    const COLUMNS: &'static [(&'static str, &'static str)] = &[
+        ("author", "TEXT"),
+        ("description", "TEXT"),
+        ("thumbnail_url", "TEXT"),
+        ("tags", "TEXT"),
+        ("page", "INTEGER"),
+    ];
+
+    fn bind<'q>(
+        self,
+        query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    ) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+        query
+            .bind(self.author)
+            .bind(self.description)
+            .bind(self.thumbnail_url)
+            .bind(self.tags.join("|"))
+            .bind(self.page)
+    }
+}
+
+pub type MerdekaArticleTable = SiteArticleTable<MerdekaArticle>;
+
+pub type MerdekaData = SqliteStorage<MerdekaArticleTable>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils, Storage, Table};
+    use std::path::Path;
+    use tokio::fs;
+
+    #[tokio::test]
+    async fn columns_mapped() {
+        if Path::new("test_merdeka.db").is_file() {
+            fs::remove_file("test_merdeka.db").await.unwrap();
+        }
+        let p = MerdekaData::new("test_merdeka").await.unwrap();
+
+        let article = MerdekaArticle {
+            title: Some("title".to_string()),
+            published_date: Some(utils::get_now()),
+            description: None,
+            thumbnail_url: None,
+            author: Some("author".to_string()),
+            tags: vec!["t1".to_string()],
+            page: 1,
+            paragraphs: vec!["p1".to_string(), "p2".to_string()],
+        };
+        p.results_insert(("https://www.merdeka.com/read/1", article))
+            .await
+            .unwrap();
+        let row: (String, String, u32) =
+            sqlx::query_as("SELECT author, tags, page FROM test_merdeka_results")
+                .fetch_one(p.results.get_pool())
+                .await
+                .unwrap();
+        assert_eq!(row, ("author".to_string(), "t1".to_string(), 1,));
+
+        fs::remove_file("test_merdeka.db").await.unwrap();
+    }
+}
@@ -0,0 +1,198 @@
+#[cfg(feature = "http-api")]
+use crate::server::IngestConfig;
+use crate::{
+    dns::DnsCacheConfig,
+    health::Canary,
+    outage::StorageOutage,
+    politeness::Politeness,
+    proxy::ProxyConfig,
+    quality::{CrawlObserver, QualityAlert},
+    recrawl::HubRecrawl,
+    retry::RetryPolicy,
+    throttle::QueueThrottle,
+    CrawlController,
+};
+use reqwest::header::HeaderMap;
+use reqwest::Url;
+#[cfg(feature = "http-api")]
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct RunOptions {
+    /// Maximum number of urls being fetched at the same time
+    pub max_fetching: u32,
+    /// Maximum number of fetched pages being parsed, and their results stored, at the same
+    /// time. As many more fetched pages wait for a parser, the fetchers waiting after them.
+    pub max_parsing: u32,
+    /// Minimum delay between two requests
+    pub request_delay: Duration,
+    /// Draw the delay between two requests at random instead of waiting `request_delay`,
+    /// with an optional longer pause from a host every so many requests
+    pub politeness: Option<Politeness>,
+    pub controller: CrawlController,
+    /// Stop the crawl once this many results were stored in this run
+    pub max_results: Option<u64>,
+    /// Stop the crawl once it received this many bytes in this run, the pages being fetched
+    /// then still stored
+    pub max_bytes: Option<u64>,
+    /// Space the requests out while more than this many bytes were received per hour lately,
+    /// the delays multiplied by how far over it is, see `budget`
+    pub bytes_per_hour: Option<u64>,
+    /// ISO 639-1 codes of the languages to store, e.g. `["id"]`. Articles detected in
+    /// another language are warned with reason `LANGUAGE`, those too short to tell are kept.
+    pub languages: Option<Vec<String>>,
+    /// Headers sent with every request, e.g. `Accept-Language: id-ID`
+    pub default_headers: HeaderMap,
+    /// Load the cookies of the run from this file and save them back once it stops, so the
+    /// consent cookies of a site are kept between runs
+    pub cookie_jar: Option<PathBuf>,
+    /// Send the requests through these proxies instead of connecting directly
+    pub proxy: Option<ProxyConfig>,
+    /// Give up on bodies larger than this once decompressed, warned with reason `TOO_LARGE`
+    pub max_decompressed_size: Option<u64>,
+    /// Only parse the start of larger bodies, cut after the last tag ending before this size.
+    /// Crawlers are told with `CrawlContext::truncated`.
+    pub max_parse_size: Option<u64>,
+    /// Don't follow the links of bodies larger than this, e.g. the thousands of links of a
+    /// liveblog
+    pub max_link_extraction_size: Option<u64>,
+    /// Queue the links found on the pages crawled. Without, only the urls queued are crawled,
+    /// e.g. a list of articles, and the crawl stops once they're done like with
+    /// `exit_when_idle`.
+    pub follow_links: bool,
+    /// Stop the crawl once nothing is queued nor running, rather than waiting for more urls.
+    /// The retries and revisits not due yet are left for the next crawl.
+    pub exit_when_idle: bool,
+    /// Cache the host lookups, and requeue the urls of hosts that failed to resolve instead
+    /// of fetching them. Not used through proxies, which resolve the hosts themselves.
+    pub dns_cache: Option<DnsCacheConfig>,
+    /// Store the `noindex` pages and follow the links of the `nofollow` ones anyway, the
+    /// directives of their robots meta or `X-Robots-Tag` header
+    pub ignore_robots: bool,
+    /// Hosts, with their port when it's not the default one like `127.0.0.1:8080`, fetched
+    /// without any delay nor backoff and whose robots directives are ignored, for the mock
+    /// servers of the tests and local development. Never meant for a real site.
+    pub disable_politeness_for_hosts: Vec<String>,
+    /// Store the articles whose body came back empty with their metadata only, as
+    /// `ExtractionStatus::MetadataOnly`, when they have a title. They're still warned with
+    /// reason `EMPTY_PARAGRAPHS`.
+    pub keep_metadata_on_empty_body: bool,
+    /// When the urls warned with a transient reason, like `TIMEOUT` or `HTTP_5XX`, are queued
+    /// again during the crawl
+    pub retry_policy: RetryPolicy,
+    /// Names this process among the ones crawling into the same database
+    pub worker_id: String,
+    /// How long a worker can go without a heartbeat before the urls it claimed are queued
+    /// again, as a worker that crashed
+    pub heartbeat_timeout: Duration,
+    /// Queue only the article urls while the queue is too long, so the crawl doesn't turn
+    /// into collecting links
+    pub queue_throttle: Option<QueueThrottle>,
+    /// Fetch the articles again this long after they're stored, keeping a `Revision` of the
+    /// ones changed since and storing their new version, see `revisit`
+    pub revisit_after: Option<Duration>,
+    /// Fetch the hub pages again, sooner the more new links they had, see `recrawl`
+    pub hub_recrawl: Option<HubRecrawl>,
+    /// Warn of the pages taking longer than this to crawl, with their url to keep them as
+    /// fixtures, and tell the `CrawlObserver`
+    pub slow_parse: Option<Duration>,
+    /// Raise an alert when too many of the last articles crawled were warned, see `quality`
+    pub quality_alert: Option<QualityAlert>,
+    /// Told about the events of the crawl, like a `QualityAlert` raised
+    pub observer: Option<Arc<dyn CrawlObserver>>,
+    /// Check the crawler still extracts this article before crawling
+    pub canary: Option<Canary>,
+    /// Write the `CrawlReport` of the session to this file once it stops
+    pub report_path: Option<PathBuf>,
+    /// Pause the crawl while its storage takes no writes, e.g. on a full disk, see `outage`
+    pub storage_outage: StorageOutage,
+    /// Serve the status API on this address while crawling
+    #[cfg(feature = "http-api")]
+    pub status_addr: Option<SocketAddr>,
+    /// Let other systems queue urls and read the stored articles through the status API
+    #[cfg(feature = "http-api")]
+    pub ingest: Option<IngestConfig>,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        RunOptions {
+            max_fetching: 16,
+            max_parsing: 4,
+            request_delay: Duration::from_millis(50),
+            politeness: None,
+            controller: CrawlController::new(),
+            max_results: None,
+            max_bytes: None,
+            bytes_per_hour: None,
+            languages: None,
+            default_headers: HeaderMap::new(),
+            cookie_jar: None,
+            proxy: None,
+            max_decompressed_size: Some(20 * 1024 * 1024),
+            max_parse_size: Some(3 * 1024 * 1024),
+            max_link_extraction_size: Some(8 * 1024 * 1024),
+            follow_links: true,
+            exit_when_idle: false,
+            dns_cache: Some(DnsCacheConfig::default()),
+            ignore_robots: false,
+            disable_politeness_for_hosts: vec![],
+            keep_metadata_on_empty_body: false,
+            retry_policy: RetryPolicy::default(),
+            worker_id: default_worker_id(),
+            heartbeat_timeout: Duration::from_secs(120),
+            queue_throttle: None,
+            revisit_after: None,
+            slow_parse: None,
+            hub_recrawl: None,
+            quality_alert: None,
+            observer: None,
+            canary: None,
+            report_path: None,
+            storage_outage: StorageOutage::default(),
+            #[cfg(feature = "http-api")]
+            status_addr: None,
+            #[cfg(feature = "http-api")]
+            ingest: None,
+        }
+    }
+}
+
+/// `<hostname>-<pid>`, unique among the processes sharing a database.
+pub fn default_worker_id() -> String {
+    let hostname = std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|hostname| hostname.trim().to_string())
+        .filter(|hostname| !hostname.is_empty())
+        .unwrap_or_else(|| "localhost".to_string());
+    format!("{}-{}", hostname, std::process::id())
+}
+
+impl RunOptions {
+    /// Whether the crawl stops once nothing is queued nor running, see `exit_when_idle`.
+    pub fn exits_when_idle(&self) -> bool {
+        self.exit_when_idle || !self.follow_links
+    }
+
+    /// Whether `url` is on one of the hosts of `disable_politeness_for_hosts`.
+    pub(crate) fn is_politeness_disabled(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let authority = url.port().map(|port| format!("{}:{}", host, port));
+        self.disable_politeness_for_hosts
+            .iter()
+            .any(|exempt| exempt == host || authority.as_ref() == Some(exempt))
+    }
+
+    pub(crate) fn is_language_allowed(&self, language: Option<&str>) -> bool {
+        match (&self.languages, language) {
+            (Some(languages), Some(language)) => languages.iter().any(|l| l == language),
+            _ => true,
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Riding out a storage that no longer takes writes, like a full disk or a filesystem gone
+//! read-only: rather than fetching pages it can't store, the crawl is paused with the urls it
+//! couldn't store held in memory, and a canary write is tried every so often. Once one goes
+//! through the held urls are queued again and the crawl resumes, and after too many failed
+//! ones it stops.
+
+use crate::{CrawlerError, Storage};
+use std::sync::Mutex;
+use tokio::sync::Notify;
+use tokio::time::Duration;
+
+/// The time between two canary writes by default.
+pub const CANARY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The canary writes failing in a row by default before the crawl stops, 10 minutes of them.
+pub const MAX_FAILED_CANARIES: u32 = 20;
+
+/// How the crawl waits for its storage to take writes again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StorageOutage {
+    pub canary_interval: Duration,
+    /// Stop the crawl after this many canary writes failed in a row
+    pub max_failed_canaries: u32,
+}
+
+impl Default for StorageOutage {
+    fn default() -> Self {
+        StorageOutage {
+            canary_interval: CANARY_INTERVAL,
+            max_failed_canaries: MAX_FAILED_CANARIES,
+        }
+    }
+}
+
+impl StorageOutage {
+    pub fn new(canary_interval: Duration, max_failed_canaries: u32) -> Self {
+        StorageOutage {
+            canary_interval,
+            max_failed_canaries: max_failed_canaries.max(1),
+        }
+    }
+}
+
+/// Whether `storage` takes writes again: a url of `worker_id` is visited then forgotten, left
+/// visited at worst, which no link leads to.
+pub(crate) async fn canary_write<S: Storage + ?Sized>(
+    storage: &S,
+    worker_id: &str,
+) -> Result<(), CrawlerError> {
+    let url = format!("canary:{}", worker_id);
+    storage.visited_insert(&url).await?;
+    storage.visited_delete(&url).await
+}
+
+/// The urls a run couldn't store while its storage is unavailable.
+#[derive(Debug, Default)]
+pub(crate) struct Outage {
+    held: Mutex<Option<Held>>,
+    started: Notify,
+}
+
+#[derive(Debug)]
+pub(crate) struct Held {
+    pub(crate) urls: Vec<String>,
+    /// Whether the crawl was already paused when the outage started, left paused after it
+    pub(crate) was_paused: bool,
+}
+
+impl Outage {
+    /// Hold `url`, when given, until the storage is available again, the crawl being paused
+    /// or not. Whether it started the outage.
+    pub(crate) fn hold(&self, url: Option<&str>, paused: bool) -> bool {
+        let mut held = self.held.lock().unwrap();
+        let started = held.is_none();
+        let held = held.get_or_insert_with(|| Held {
+            urls: vec![],
+            was_paused: paused,
+        });
+        if let Some(url) = url.filter(|url| !held.urls.iter().any(|held| held == url)) {
+            held.urls.push(url.to_string());
+        }
+        if started {
+            self.started.notify_one();
+        }
+        started
+    }
+
+    /// Resolves once an outage started, right away when one started since the last call.
+    pub(crate) async fn started(&self) {
+        self.started.notified().await
+    }
+
+    /// End the outage, giving back the urls held.
+    pub(crate) fn end(&self) -> Option<Held> {
+        self.held.lock().unwrap().take()
+    }
+
+    /// The urls held, none when the storage is available.
+    pub(crate) fn held(&self) -> usize {
+        self.held
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or(0, |held| held.urls.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::timeout;
+
+    #[tokio::test]
+    async fn urls_held_once() {
+        let outage = Outage::default();
+        assert!(outage.hold(Some("https://news.detik.com/a"), false));
+        assert!(!outage.hold(Some("https://news.detik.com/b"), true));
+        assert!(!outage.hold(Some("https://news.detik.com/a"), true));
+        assert!(!outage.hold(None, true));
+        assert_eq!(outage.held(), 2);
+        // Started before it was waited for
+        timeout(Duration::from_secs(1), outage.started())
+            .await
+            .unwrap();
+
+        let held = outage.end().unwrap();
+        assert_eq!(
+            held.urls,
+            vec!["https://news.detik.com/a", "https://news.detik.com/b"]
+        );
+        assert!(!held.was_paused);
+        assert_eq!(outage.held(), 0);
+        assert!(outage.end().is_none());
+    }
+}
@@ -0,0 +1,182 @@
+use crate::{
+    json_ld, pikiranrakyat::PikiranRakyatArticle, text, utils, CrawlContext, Crawler,
+    CrawlerResult, ExtractedLink,
+};
+use chrono::DateTime;
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use scraper::{ElementRef, Html, Selector};
+
+const E: &str = "Invalid selector";
+lazy_static! {
+    static ref TITLE: Selector = Selector::parse(r#"meta[property="og:title"]"#).expect(E);
+    static ref DESCRIPTION: Selector =
+        Selector::parse(r#"meta[property="og:description"]"#).expect(E);
+    static ref THUMBNAIL: Selector = Selector::parse(r#"meta[property="og:image"]"#).expect(E);
+    static ref CANONICAL: Selector = Selector::parse(r#"link[rel="canonical"]"#).expect(E);
+    static ref TAG: Selector = Selector::parse(".tag__item a").expect(E);
+    static ref BODY: Selector = Selector::parse(".read__content").expect(E);
+    static ref ACTIVE_PAGE: Selector = Selector::parse(".paging__link--active").expect(E);
+    static ref P: Selector = Selector::parse("p").expect(E);
+    static ref A: Selector = Selector::parse("a").expect(E);
+}
+
+const SKIPPED_PREFIXES: [&str; 3] = ["Baca Juga", "Baca juga", "Simak update"];
+
+#[derive(Debug, Clone)]
+pub struct PikiranRakyatCrawler {
+    allowlist: utils::HostAllowlist,
+}
+
+impl Default for PikiranRakyatCrawler {
+    fn default() -> Self {
+        PikiranRakyatCrawler {
+            allowlist: utils::HostAllowlist::new(["pikiran-rakyat.com"]),
+        }
+    }
+}
+
+impl PikiranRakyatCrawler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    utils::allowlist_builders!(
+        "pikiran-rakyat.com",
+        "It covers network sites like portaljember.pikiran-rakyat.com, other domains sharing the \
+         template can be added here."
+    );
+}
+
+/// The section of `pikiran-rakyat.com/<category>/pr-01000001/slug`.
+fn category(url: &reqwest::Url) -> Option<String> {
+    url.path_segments()?
+        .next()
+        .filter(|s| !s.is_empty() && !s.starts_with("pr-"))
+        .map(ToString::to_string)
+}
+
+/// Paragraphs that are nothing but a link, promoting other articles or the Google News
+/// channel.
+fn is_promo(p: &ElementRef) -> bool {
+    let linked = p.select(&A).flat_map(|a| a.text()).join(" ");
+    let text = p.text().collect::<String>();
+    !linked.trim().is_empty() && text.split_whitespace().eq(linked.split_whitespace())
+}
+
+impl Crawler for PikiranRakyatCrawler {
+    type Document = PikiranRakyatArticle;
+
+    fn can_be_scrapped(&self, doc: &Html) -> bool {
+        json_ld::article(doc).is_some() && doc.select(&BODY).next().is_some()
+    }
+
+    fn extract_links(&self, ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        utils::links(doc, ctx.url, |url| self.allowlist.allows(url))
+    }
+
+    fn crawl(&self, ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        // Pagination links are among the links, so every page of an article is crawled
+        let links = self.extract_urls(ctx, doc);
+
+        if !self.can_be_scrapped(doc) {
+            return CrawlerResult::Links(links);
+        }
+
+        let ld = json_ld::article(doc);
+        let ld_string = |key: &str| ld.as_ref().and_then(|ld| json_ld::string(ld, key));
+
+        let canonical = doc
+            .select(&CANONICAL)
+            .next()
+            .and_then(|el| el.value().attr("href"))
+            .and_then(|s| reqwest::Url::parse(s.trim()).ok());
+
+        let author = ld
+            .as_ref()
+            .map(|ld| json_ld::names(ld, "author"))
+            .filter(|names| !names.is_empty())
+            .map(|names| names.join(", "));
+
+        let tags = doc
+            .select(&TAG)
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        let page = doc
+            .select(&ACTIVE_PAGE)
+            .next()
+            .and_then(|el| el.text().collect::<String>().trim().parse().ok())
+            .unwrap_or(1);
+
+        let mut paragraphs = vec![];
+        if let Some(body) = doc.select(&BODY).next() {
+            for p in body.select(&P).filter(|p| !is_promo(p)) {
+                if let Some(p) = text::clean_paragraph(&p.inner_html()) {
+                    if !text::starts_with_any(&p, &SKIPPED_PREFIXES) {
+                        paragraphs.push(p);
+                    }
+                }
+            }
+        }
+        paragraphs.dedup();
+
+        let article = PikiranRakyatArticle {
+            title: ld_string("headline").or_else(|| utils::meta_content(doc, &TITLE)),
+            published_date: ld_string("datePublished")
+                .and_then(|s| DateTime::parse_from_rfc3339(&s).ok()),
+            description: utils::meta_content(doc, &DESCRIPTION)
+                .or_else(|| ld_string("description")),
+            thumbnail_url: utils::meta_content(doc, &THUMBNAIL),
+            author,
+            category: category(canonical.as_ref().unwrap_or(ctx.url)),
+            tags,
+            page,
+            paragraphs,
+        };
+        CrawlerResult::document_and_links(article, links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_p_is_promo(html: &str) -> bool {
+        let doc = Html::parse_fragment(html);
+        let p = doc.select(&P).next().unwrap();
+        is_promo(&p)
+    }
+
+    #[test]
+    fn promos() {
+        assert!(first_p_is_promo(
+            r#"<p><strong><a href="https://x">Simak update artikel pilihan lainnya di Google News</a></strong></p>"#
+        ));
+        assert!(first_p_is_promo(
+            r#"<p> <a href="https://x">Persib</a> <a href="https://y">Menang</a> </p>"#
+        ));
+        assert!(!first_p_is_promo(
+            r#"<p>Pelatih <a href="https://x">Persib</a> puas dengan hasil laga.</p>"#
+        ));
+        assert!(!first_p_is_promo("<p>Tanpa tautan.</p>"));
+    }
+
+    #[test]
+    fn network_hosts() {
+        let crawler = PikiranRakyatCrawler::new();
+        let allowed = |s: &str| crawler.allowlist.allows(&reqwest::Url::parse(s).unwrap());
+        assert!(allowed(
+            "https://www.pikiran-rakyat.com/nasional/pr-01000001/judul"
+        ));
+        assert!(allowed(
+            "https://portaljember.pikiran-rakyat.com/daerah/pr-1/judul"
+        ));
+        assert!(!allowed("https://www.prfmnews.id/berita/pr-1/judul"));
+
+        let crawler = crawler.with_allowed_hosts(["pikiran-rakyat.com", "prfmnews.id"]);
+        let allowed = |s: &str| crawler.allowlist.allows(&reqwest::Url::parse(s).unwrap());
+        assert!(allowed("https://www.prfmnews.id/berita/pr-1/judul"));
+    }
+}
@@ -0,0 +1,84 @@
+use super::PikiranRakyatArticle;
+use crate::generic::{SiteArticle, SiteArticleTable};
+use crate::SqliteStorage;
+use sqlx::query::Query;
+use sqlx::sqlite::SqliteArguments;
+use sqlx::Sqlite;
+
+impl SiteArticle for PikiranRakyatArticle {
+    const COLUMNS: &'static [(&'static str, &'static str)] = &[
+        ("author", "TEXT"),
+        ("category", "TEXT"),
+        ("description", "TEXT"),
+        ("thumbnail_url", "TEXT"),
+        ("tags", "TEXT"),
+        ("page", "INTEGER"),
+    ];
+
+    fn bind<'q>(
+        self,
+        query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    ) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+        query
+            .bind(self.author)
+            .bind(self.category)
+            .bind(self.description)
+            .bind(self.thumbnail_url)
+            .bind(self.tags.join("|"))
+            .bind(self.page)
+    }
+}
+
+pub type PikiranRakyatArticleTable = SiteArticleTable<PikiranRakyatArticle>;
+
+pub type PikiranRakyatData = SqliteStorage<PikiranRakyatArticleTable>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils, Storage, Table};
+    use std::path::Path;
+    use tokio::fs;
+
+    #[tokio::test]
+    async fn columns_mapped() {
+        if Path::new("test_pikiranrakyat.db").is_file() {
+            fs::remove_file("test_pikiranrakyat.db").await.unwrap();
+        }
+        let p = PikiranRakyatData::new("test_pikiranrakyat").await.unwrap();
+
+        let article = PikiranRakyatArticle {
+            title: Some("title".to_string()),
+            published_date: Some(utils::get_now()),
+            description: None,
+            thumbnail_url: None,
+            author: Some("author".to_string()),
+            category: Some("nasional".to_string()),
+            tags: vec!["t1".to_string()],
+            page: 1,
+            paragraphs: vec!["p1".to_string(), "p2".to_string()],
+        };
+        p.results_insert((
+            "https://www.pikiran-rakyat.com/nasional/pr-01000001/judul",
+            article,
+        ))
+        .await
+        .unwrap();
+        let row: (String, String, String, u32) =
+            sqlx::query_as("SELECT author, category, tags, page FROM test_pikiranrakyat_results")
+                .fetch_one(p.results.get_pool())
+                .await
+                .unwrap();
+        assert_eq!(
+            row,
+            (
+                "author".to_string(),
+                "nasional".to_string(),
+                "t1".to_string(),
+                1,
+            )
+        );
+
+        fs::remove_file("test_pikiranrakyat.db").await.unwrap();
+    }
+}
@@ -0,0 +1,277 @@
+//! Randomized delays between requests, so the crawl doesn't fetch at the perfectly regular
+//! cadence some CDNs flag as a bot, with an optional longer pause from a host every so many
+//! requests.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tokio::time::{Duration, Instant};
+
+/// Where the delays are drawn from, injectable for deterministic tests.
+pub trait RandomSource: fmt::Debug + Send + Sync {
+    /// A number in `[0, 1)`.
+    fn next_f64(&self) -> f64;
+}
+
+/// The `RandomSource` of a crawl, a thread local generator seeded by the system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThreadRandom;
+
+impl RandomSource for ThreadRandom {
+    fn next_f64(&self) -> f64 {
+        fastrand::f64()
+    }
+}
+
+/// How the delays are spread between their bounds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Distribution {
+    /// Any delay between the bounds as likely
+    #[default]
+    Uniform,
+    /// Mostly around the middle of the bounds, three standard deviations from either bound
+    Normal,
+}
+
+/// A pause from a host after every `every` requests to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoffeeBreak {
+    pub every: u32,
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl CoffeeBreak {
+    pub fn new(every: u32, min: Duration, max: Duration) -> Self {
+        CoffeeBreak { every, min, max }
+    }
+}
+
+/// Delays between requests drawn from `[min_delay, max_delay]` instead of the fixed
+/// `RunOptions::request_delay`.
+#[derive(Debug, Clone)]
+pub struct Politeness {
+    pub min_delay: Duration,
+    pub max_delay: Duration,
+    pub distribution: Distribution,
+    pub coffee_break: Option<CoffeeBreak>,
+    pub random: Arc<dyn RandomSource>,
+}
+
+impl Politeness {
+    /// Delays drawn uniformly between `min_delay` and `max_delay`, without coffee breaks.
+    pub fn new(min_delay: Duration, max_delay: Duration) -> Self {
+        Politeness {
+            min_delay,
+            max_delay: max_delay.max(min_delay),
+            distribution: Distribution::default(),
+            coffee_break: None,
+            random: Arc::new(ThreadRandom),
+        }
+    }
+
+    pub fn with_distribution(mut self, distribution: Distribution) -> Self {
+        self.distribution = distribution;
+        self
+    }
+
+    pub fn with_coffee_break(mut self, coffee_break: CoffeeBreak) -> Self {
+        self.coffee_break = Some(coffee_break);
+        self
+    }
+
+    pub fn with_random(mut self, random: impl RandomSource + 'static) -> Self {
+        self.random = Arc::new(random);
+        self
+    }
+
+    /// The delay before the next request, after the previous one.
+    pub fn delay(&self) -> Duration {
+        self.draw(self.min_delay, self.max_delay, self.distribution)
+    }
+
+    fn draw(&self, min: Duration, max: Duration, distribution: Distribution) -> Duration {
+        let max = max.max(min);
+        let unit = match distribution {
+            Distribution::Uniform => self.random.next_f64(),
+            Distribution::Normal => {
+                // Box-Muller, `1 - x` keeping the logarithm finite
+                let radius = (-2.0 * (1.0 - self.random.next_f64()).ln()).sqrt();
+                let angle = std::f64::consts::TAU * self.random.next_f64();
+                0.5 + radius * angle.cos() / 6.0
+            }
+        };
+        min + (max - min).mul_f64(unit.clamp(0.0, 1.0))
+    }
+}
+
+/// The requests made to each host and the coffee breaks they're on.
+#[derive(Debug)]
+pub(crate) struct Pacer {
+    politeness: Politeness,
+    hosts: Mutex<HashMap<String, HostPace>>,
+}
+
+#[derive(Debug, Default)]
+struct HostPace {
+    requests: u64,
+    resume_at: Option<Instant>,
+}
+
+impl Pacer {
+    pub(crate) fn new(politeness: Politeness) -> Self {
+        Pacer {
+            politeness,
+            hosts: Mutex::default(),
+        }
+    }
+
+    pub(crate) fn delay(&self) -> Duration {
+        self.politeness.delay()
+    }
+
+    /// How long a request to `host` at `now` waits for the coffee break of the host, starting
+    /// one when the host got `CoffeeBreak::every` requests since the last.
+    pub(crate) fn before_request(&self, host: &str, now: Instant) -> Duration {
+        let mut hosts = self.hosts.lock().unwrap();
+        let pace = hosts.entry(host.to_string()).or_default();
+        if let Some(coffee_break) = &self.politeness.coffee_break {
+            let every = u64::from(coffee_break.every.max(1));
+            if pace.requests > 0 && pace.requests.is_multiple_of(every) {
+                let pause =
+                    self.politeness
+                        .draw(coffee_break.min, coffee_break.max, Distribution::Uniform);
+                let resume_at = pace.resume_at.map_or(now, |at| at.max(now)) + pause;
+                pace.resume_at = Some(resume_at);
+            }
+        }
+        pace.requests += 1;
+        pace.resume_at
+            .map_or(Duration::ZERO, |at| at.saturating_duration_since(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Cycles through `values`.
+    #[derive(Debug)]
+    struct Sequence {
+        values: Vec<f64>,
+        next: AtomicUsize,
+    }
+
+    impl Sequence {
+        fn new(values: &[f64]) -> Self {
+            Sequence {
+                values: values.to_vec(),
+                next: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    impl RandomSource for Sequence {
+        fn next_f64(&self) -> f64 {
+            let i = self.next.fetch_add(1, Ordering::Relaxed);
+            self.values[i % self.values.len()]
+        }
+    }
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn uniform_within_bounds() {
+        let politeness = Politeness::new(ms(200), ms(600))
+            .with_random(Sequence::new(&[0.0, 0.25, 0.5, 0.999_999]));
+        let delays: Vec<_> = (0..4).map(|_| politeness.delay()).collect();
+        assert_eq!(delays[..3], [ms(200), ms(300), ms(400)]);
+        assert!(delays[3] < ms(600) && delays[3] > ms(599));
+
+        let politeness = Politeness::new(ms(200), ms(600));
+        for _ in 0..1000 {
+            let delay = politeness.delay();
+            assert!(ms(200) <= delay && delay <= ms(600), "{:?}", delay);
+        }
+    }
+
+    #[test]
+    fn normal_within_bounds() {
+        // The middle, and far enough out on both sides to be clamped
+        let politeness = Politeness::new(ms(200), ms(600))
+            .with_distribution(Distribution::Normal)
+            .with_random(Sequence::new(&[
+                1.0 - (-0.125f64).exp(),
+                0.25,
+                0.999_999,
+                0.0,
+                0.999_999,
+                0.5,
+            ]));
+        assert_eq!(politeness.delay(), ms(400));
+        assert_eq!(politeness.delay(), ms(600));
+        assert_eq!(politeness.delay(), ms(200));
+
+        let politeness = Politeness::new(ms(200), ms(600)).with_distribution(Distribution::Normal);
+        for _ in 0..1000 {
+            let delay = politeness.delay();
+            assert!(ms(200) <= delay && delay <= ms(600), "{:?}", delay);
+        }
+    }
+
+    #[test]
+    fn coffee_break_cadence() {
+        let pacer = Pacer::new(
+            Politeness::new(ms(10), ms(10))
+                .with_coffee_break(CoffeeBreak::new(3, ms(30_000), ms(60_000)))
+                .with_random(Sequence::new(&[0.5])),
+        );
+        let start = Instant::now();
+        let waits: Vec<_> = (0..7)
+            .map(|_| pacer.before_request("news.detik.com", start))
+            .collect();
+        // A break after every third request, the ones already waiting adding to it
+        assert_eq!(
+            waits,
+            [
+                Duration::ZERO,
+                Duration::ZERO,
+                Duration::ZERO,
+                ms(45_000),
+                ms(45_000),
+                ms(45_000),
+                ms(90_000)
+            ]
+        );
+        // Other hosts aren't paused
+        assert_eq!(
+            pacer.before_request("finance.detik.com", start),
+            Duration::ZERO
+        );
+        // Nor the host once the break is over
+        let later = start + ms(100_000);
+        assert_eq!(
+            pacer.before_request("news.detik.com", later),
+            Duration::ZERO
+        );
+        assert_eq!(
+            pacer.before_request("news.detik.com", later),
+            Duration::ZERO
+        );
+        assert_eq!(pacer.before_request("news.detik.com", later), ms(45_000));
+    }
+
+    #[test]
+    fn no_coffee_break() {
+        let pacer = Pacer::new(Politeness::new(ms(10), ms(20)));
+        let now = Instant::now();
+        for _ in 0..100 {
+            assert_eq!(pacer.before_request("news.detik.com", now), Duration::ZERO);
+        }
+    }
+}
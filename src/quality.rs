@@ -0,0 +1,164 @@
+//! Watching how many of the articles crawled are warned rather than extracted, and raising
+//! an alert when too many are, like after a site changed its markup: the `CrawlObserver` of
+//! the crawl is told, a webhook called and the crawl paused as the `QualityAlert` says.
+
+use crate::ParseTiming;
+use reqwest::Url;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Mutex;
+
+/// When the crawl raises an alert about the articles it warned.
+#[derive(Debug, Clone)]
+pub struct QualityAlert {
+    /// The last articles the ratio of warned ones is taken over, none raised before as many
+    /// were crawled
+    pub window: usize,
+    /// The ratio of warned articles raising the alert
+    pub threshold: f64,
+    /// The ratio of warned articles below which another alert can be raised, half the
+    /// threshold by default
+    pub recover_below: f64,
+    /// Pause the crawl with its `CrawlController` when the alert is raised
+    pub pause: bool,
+    /// POST an `AlertPayload` here when the alert is raised
+    pub webhook: Option<Url>,
+}
+
+impl QualityAlert {
+    pub fn new(window: usize, threshold: f64) -> Self {
+        QualityAlert {
+            window: window.max(1),
+            threshold,
+            recover_below: threshold / 2.0,
+            pause: false,
+            webhook: None,
+        }
+    }
+
+    pub fn with_recover_below(mut self, recover_below: f64) -> Self {
+        self.recover_below = recover_below.min(self.threshold);
+        self
+    }
+
+    pub fn with_pause(mut self, pause: bool) -> Self {
+        self.pause = pause;
+        self
+    }
+
+    pub fn with_webhook(mut self, webhook: Url) -> Self {
+        self.webhook = Some(webhook);
+        self
+    }
+
+    /// Whether the alert is raised with `ratio` warned articles, given whether it was before.
+    pub fn is_alerting(&self, alerting: bool, ratio: f64) -> bool {
+        if alerting {
+            ratio >= self.recover_below
+        } else {
+            ratio >= self.threshold
+        }
+    }
+}
+
+/// Told about the events of a crawl, see `RunOptions::observer`.
+pub trait CrawlObserver: fmt::Debug + Send + Sync {
+    /// `ratio` of the last `window` articles crawled were warned, past the `QualityAlert` of
+    /// the crawl. Not called again before the ratio recovered.
+    fn on_quality_alert(&self, _ratio: f64, _window: usize) {}
+
+    /// A page was crawled in `timing`, `slow` past `RunOptions::slow_parse`.
+    fn on_parsed(&self, _timing: &ParseTiming, _slow: bool) {}
+}
+
+/// The JSON body POSTed to `QualityAlert::webhook`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AlertPayload {
+    pub ratio: f64,
+    pub window: usize,
+    pub threshold: f64,
+    pub worker_id: String,
+    pub paused: bool,
+}
+
+/// Whether each of the last articles crawled was warned.
+#[derive(Debug)]
+pub(crate) struct QualityMonitor {
+    alert: QualityAlert,
+    outcomes: Mutex<Outcomes>,
+}
+
+#[derive(Debug, Default)]
+struct Outcomes {
+    warned: VecDeque<bool>,
+    alerting: bool,
+}
+
+impl QualityMonitor {
+    pub(crate) fn new(alert: QualityAlert) -> Self {
+        QualityMonitor {
+            alert,
+            outcomes: Mutex::default(),
+        }
+    }
+
+    pub(crate) fn alert(&self) -> &QualityAlert {
+        &self.alert
+    }
+
+    /// Add an article crawled, `warned` or extracted. The ratio of warned articles when it
+    /// raises the alert.
+    pub(crate) fn record(&self, warned: bool) -> Option<f64> {
+        let mut outcomes = self.outcomes.lock().unwrap();
+        outcomes.warned.push_back(warned);
+        if outcomes.warned.len() > self.alert.window {
+            outcomes.warned.pop_front();
+        }
+        if outcomes.warned.len() < self.alert.window {
+            return None;
+        }
+        let ratio =
+            outcomes.warned.iter().filter(|&&w| w).count() as f64 / self.alert.window as f64;
+        let was_alerting = outcomes.alerting;
+        outcomes.alerting = self.alert.is_alerting(was_alerting, ratio);
+        (outcomes.alerting && !was_alerting).then_some(ratio)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alert_raised_once() {
+        let monitor = QualityMonitor::new(QualityAlert::new(10, 0.5).with_recover_below(0.2));
+        // Not before the window is full
+        for _ in 0..9 {
+            assert_eq!(monitor.record(true), None);
+        }
+        assert_eq!(monitor.record(true), Some(1.0));
+
+        // Going down and up around the threshold without recovering
+        let mut alerts = vec![];
+        for warned in [[false; 7], [true; 7]].concat() {
+            alerts.extend(monitor.record(warned));
+        }
+        assert!(alerts.is_empty());
+
+        // Recovered, then past the threshold again
+        for _ in 0..10 {
+            assert_eq!(monitor.record(false), None);
+        }
+        let alerts: Vec<f64> = (0..10).filter_map(|_| monitor.record(true)).collect();
+        assert_eq!(alerts, vec![0.5]);
+    }
+
+    #[test]
+    fn below_threshold() {
+        let monitor = QualityMonitor::new(QualityAlert::new(4, 0.5));
+        for warned in [true, false, false, false, true, false, false, false] {
+            assert_eq!(monitor.record(warned), None);
+        }
+    }
+}
@@ -0,0 +1,301 @@
+//! Fetching the hub pages, the pages of links only, again once they likely link to new
+//! articles: each is crawled again sooner the more links it had that weren't seen before,
+//! within the bounds of `RunOptions::hub_recrawl`.
+//!
+//! The links of a hub are kept as a `LinkSnapshot` from one crawl to the next, the links new
+//! on it being told from the snapshot rather than looked up, and queued before the others.
+
+use crate::simhash::fnv1a;
+use crate::HubState;
+use chrono::{DateTime, FixedOffset};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+use tokio::time::Duration;
+
+/// The weight of the last crawl of a hub in its rate, the others fading away.
+pub const SMOOTHING: f64 = 0.3;
+
+/// The new links a crawl of a hub aims to find, its interval being how long it takes to
+/// publish them at its rate.
+pub const TARGET_NEW_LINKS: f64 = 10.0;
+
+/// The bounds of the intervals between two crawls of a hub.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HubRecrawl {
+    pub min_interval: Duration,
+    pub max_interval: Duration,
+}
+
+impl HubRecrawl {
+    pub fn new(min_interval: Duration, max_interval: Duration) -> Self {
+        HubRecrawl {
+            min_interval,
+            max_interval: max_interval.max(min_interval),
+        }
+    }
+
+    /// The state of the hub `url` crawled at `now` with `new_links` links not seen before,
+    /// after its `previous` state. Crawled for the first time, its links tell nothing and it's
+    /// crawled again after `min_interval` to find out.
+    pub fn update(
+        &self,
+        previous: Option<&HubState>,
+        url: &str,
+        new_links: u32,
+        now: DateTime<FixedOffset>,
+    ) -> HubState {
+        let (rate, recrawls) = match previous {
+            None => (0.0, 0),
+            Some(previous) => {
+                // A second at least, a hub crawled twice at once telling nothing
+                let hours = (now - previous.crawled_at)
+                    .to_std()
+                    .unwrap_or_default()
+                    .max(Duration::from_secs(1))
+                    .as_secs_f64()
+                    / 3600.0;
+                let observed = f64::from(new_links) / hours;
+                let rate = if previous.recrawls == 0 {
+                    observed
+                } else {
+                    SMOOTHING * observed + (1.0 - SMOOTHING) * previous.rate
+                };
+                (rate, previous.recrawls.saturating_add(1))
+            }
+        };
+        let interval = if recrawls == 0 {
+            self.min_interval
+        } else {
+            self.interval(rate)
+        };
+        HubState {
+            url: url.to_string(),
+            rate,
+            recrawls,
+            interval,
+            crawled_at: now,
+        }
+    }
+
+    /// The interval of a hub finding `rate` new links an hour: the time to publish
+    /// `TARGET_NEW_LINKS` of them, within the bounds.
+    pub fn interval(&self, rate: f64) -> Duration {
+        if rate <= 0.0 {
+            return self.max_interval;
+        }
+        let secs = TARGET_NEW_LINKS / rate * 3600.0;
+        Duration::try_from_secs_f64(secs)
+            .unwrap_or(self.max_interval)
+            .clamp(self.min_interval, self.max_interval)
+    }
+}
+
+/// The priority the links new on a hub are queued with, the others keeping 0.
+pub const NEW_LINK_PRIORITY: i32 = 10;
+
+/// The links a `LinkSnapshot` keeps at most.
+pub const MAX_SNAPSHOT_LINKS: usize = 2048;
+
+/// The links found on a hub page, as the sorted hashes of at most `MAX_SNAPSHOT_LINKS` of
+/// them: the smallest ones, so the same links are kept from one crawl to the next.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkSnapshot {
+    hashes: Vec<u64>,
+}
+
+impl LinkSnapshot {
+    pub fn new<S: AsRef<str>>(links: &[S]) -> Self {
+        let mut hashes: Vec<u64> = links.iter().map(|link| fnv1a(link.as_ref())).collect();
+        hashes.sort_unstable();
+        hashes.dedup();
+        hashes.truncate(MAX_SNAPSHOT_LINKS);
+        LinkSnapshot { hashes }
+    }
+
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    /// Whether `link` wasn't on the hub. Not when the snapshot is full and `link` is one of
+    /// those it couldn't keep, which it can't tell.
+    pub fn is_new(&self, link: &str) -> bool {
+        let hash = fnv1a(link);
+        let kept = self.hashes.len() < MAX_SNAPSHOT_LINKS
+            || self.hashes.last().is_some_and(|&last| hash <= last);
+        kept && self.hashes.binary_search(&hash).is_err()
+    }
+
+    /// The snapshot compressed: the differences between the hashes as varints, deflated.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoder = DeflateEncoder::new(vec![], Compression::default());
+        let mut previous = 0;
+        for &hash in &self.hashes {
+            let mut delta = hash - previous;
+            previous = hash;
+            loop {
+                let byte = (delta & 0x7f) as u8;
+                delta >>= 7;
+                if delta == 0 {
+                    encoder.write_all(&[byte]).unwrap();
+                    break;
+                }
+                encoder.write_all(&[byte | 0x80]).unwrap();
+            }
+        }
+        encoder.finish().unwrap()
+    }
+
+    /// The snapshot `encode` gave `bytes`, `None` when they're not one.
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let mut varints = vec![];
+        DeflateDecoder::new(bytes).read_to_end(&mut varints).ok()?;
+        let mut hashes = vec![];
+        let (mut delta, mut shift, mut previous) = (0u64, 0, 0u64);
+        for byte in varints {
+            if shift > 63 {
+                return None;
+            }
+            delta |= u64::from(byte & 0x7f) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                previous = previous.checked_add(delta)?;
+                hashes.push(previous);
+                (delta, shift) = (0, 0);
+            }
+        }
+        (shift == 0).then_some(LinkSnapshot { hashes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils;
+
+    fn minutes(minutes: u64) -> Duration {
+        Duration::from_secs(minutes * 60)
+    }
+
+    /// Whether `interval` is `expected`, to the second.
+    fn about(interval: Duration, expected: Duration) -> bool {
+        interval.max(expected) - interval.min(expected) < Duration::from_secs(1)
+    }
+
+    /// The intervals of a hub crawled every time its interval went by, finding
+    /// `new_links_an_hour` new links an hour.
+    fn simulate(recrawl: &HubRecrawl, new_links_an_hour: f64, crawls: usize) -> Vec<Duration> {
+        let mut now = utils::get_now();
+        let mut state = recrawl.update(None, "https://news.detik.com/indeks", 40, now);
+        let mut intervals = vec![state.interval];
+        for _ in 0..crawls {
+            now += chrono::Duration::from_std(state.interval).unwrap();
+            let new_links =
+                (new_links_an_hour * state.interval.as_secs_f64() / 3600.0).round() as u32;
+            state = recrawl.update(Some(&state), &state.url, new_links, now);
+            intervals.push(state.interval);
+        }
+        intervals
+    }
+
+    #[test]
+    fn busy_hub_crawled_more_often() {
+        let recrawl = HubRecrawl::new(minutes(10), minutes(24 * 60));
+        // 30 new links an hour, 10 of them every 20 minutes
+        let intervals = simulate(&recrawl, 30.0, 10);
+        assert_eq!(intervals[0], minutes(10));
+        assert!(
+            intervals[1..].iter().all(|&i| about(i, minutes(20))),
+            "{:?}",
+            intervals
+        );
+
+        // Faster than the bound
+        let intervals = simulate(&recrawl, 600.0, 10);
+        assert!(intervals.iter().all(|&i| i == minutes(10)));
+    }
+
+    #[test]
+    fn quiet_hub_crawled_less_often() {
+        let recrawl = HubRecrawl::new(minutes(10), minutes(24 * 60));
+        // A link every 2 hours: nothing new after the first 10 minutes, then 10 links in 20
+        // hours as the rate picks up
+        let intervals = simulate(&recrawl, 0.5, 30);
+        assert_eq!(intervals[..2], [minutes(10), minutes(24 * 60)]);
+        assert!(
+            intervals[1..].iter().all(|&i| i >= minutes(19 * 60)),
+            "{:?}",
+            intervals
+        );
+        let last = *intervals.last().unwrap();
+        assert!(last <= minutes(21 * 60), "{:?}", intervals);
+
+        // Nothing new, after the first crawl
+        let intervals = simulate(&recrawl, 0.0, 3);
+        assert_eq!(intervals[1..], [minutes(24 * 60); 3]);
+    }
+
+    #[test]
+    fn rate_smoothed() {
+        let recrawl = HubRecrawl::new(minutes(10), minutes(24 * 60));
+        let now = utils::get_now();
+        let state = recrawl.update(None, "https://news.detik.com/indeks", 40, now);
+        assert_eq!((state.rate, state.recrawls), (0.0, 0));
+
+        let now = now + chrono::Duration::hours(1);
+        let state = recrawl.update(Some(&state), &state.url, 20, now);
+        assert_eq!((state.rate, state.recrawls), (20.0, 1));
+        assert!(about(state.interval, minutes(30)));
+
+        // A single quiet crawl only slows it down a little
+        let now = now + chrono::Duration::hours(1);
+        let state = recrawl.update(Some(&state), &state.url, 0, now);
+        assert!((state.rate - 14.0).abs() < 1e-9);
+        assert_eq!(state.recrawls, 2);
+    }
+
+    fn links(range: std::ops::Range<usize>) -> Vec<String> {
+        range
+            .map(|i| format!("https://news.detik.com/berita/d-{}/judul", i))
+            .collect()
+    }
+
+    #[test]
+    fn snapshot_new_links() {
+        let snapshot = LinkSnapshot::new(&links(0..40));
+        assert_eq!(snapshot.len(), 40);
+        assert!(links(0..40).iter().all(|link| !snapshot.is_new(link)));
+        assert!(links(40..80).iter().all(|link| snapshot.is_new(link)));
+
+        // Full, only the links it could have kept are told new
+        let full = LinkSnapshot::new(&links(0..MAX_SNAPSHOT_LINKS * 2));
+        assert_eq!(full.len(), MAX_SNAPSHOT_LINKS);
+        assert!(links(0..MAX_SNAPSHOT_LINKS * 2)
+            .iter()
+            .all(|link| !full.is_new(link)));
+        let new = links(100_000..101_000)
+            .iter()
+            .filter(|link| full.is_new(link))
+            .count();
+        assert!(new > 200 && new < 800, "{}", new);
+    }
+
+    #[test]
+    fn snapshot_encoded() {
+        for snapshot in [
+            LinkSnapshot::default(),
+            LinkSnapshot::new(&["https://news.detik.com/"]),
+            LinkSnapshot::new(&links(0..MAX_SNAPSHOT_LINKS * 2)),
+        ] {
+            let encoded = snapshot.encode();
+            assert!(encoded.len() <= snapshot.len() * 8 + 8);
+            assert_eq!(LinkSnapshot::decode(&encoded), Some(snapshot));
+        }
+        assert_eq!(LinkSnapshot::decode(b"not deflated"), None);
+    }
+}
@@ -0,0 +1,105 @@
+//! The summary of a crawl session, returned by `run_scrapper` and written as JSON once it
+//! stops, to look at a run without querying its database.
+
+use crate::{CrawlStats, HostLatency, ParseTiming};
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::{info, warn};
+
+/// Why a crawl session stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    /// `RunOptions::max_results` results were stored
+    MaxResults,
+    /// `RunOptions::max_bytes` bytes were received
+    MaxBytes,
+    /// Stopped through its `CrawlController`, e.g. on Ctrl-C or by the status API
+    Stopped,
+    /// Nothing was left queued nor running, with `RunOptions::exit_when_idle`
+    Idle,
+    /// The storage took no writes, after `StorageOutage::max_failed_canaries` canary writes
+    StorageUnavailable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrawlReport {
+    pub session_id: String,
+    pub worker_id: String,
+    pub started_at: DateTime<FixedOffset>,
+    pub ended_at: DateTime<FixedOffset>,
+    pub stop_reason: StopReason,
+    pub fetched: u64,
+    pub extracted: u64,
+    pub warned: u64,
+    pub rejected: u64,
+    pub noindex: u64,
+    /// Bodies only parsed up to `RunOptions::max_parse_size`
+    pub truncated: u64,
+    /// Bodies decoded from another charset than UTF-8
+    pub transcoded: u64,
+    pub bytes_received: u64,
+    pub bytes_decoded: u64,
+    /// Tasks of the crawl that panicked, none for a crawl stopped cleanly
+    pub failed_tasks: u64,
+    /// Pages fetched from each host
+    pub hosts: BTreeMap<String, u64>,
+    /// Urls warned for each reason
+    pub warned_reasons: BTreeMap<String, u64>,
+    /// Latencies of every fetch
+    pub latency: HostLatency,
+    /// Latencies of the fetches from each host, see `MAX_LATENCY_HOSTS`
+    pub latency_by_host: BTreeMap<String, HostLatency>,
+    /// The slowest pages to crawl, slowest first, see `SLOWEST_PARSES`
+    pub slowest_parses: Vec<ParseTiming>,
+}
+
+impl CrawlReport {
+    pub(crate) fn new(
+        stats: &CrawlStats,
+        worker_id: &str,
+        stop_reason: StopReason,
+        ended_at: DateTime<FixedOffset>,
+    ) -> Self {
+        CrawlReport {
+            session_id: stats.session_id().to_string(),
+            worker_id: worker_id.to_string(),
+            started_at: stats.started_at(),
+            ended_at,
+            stop_reason,
+            fetched: stats.fetched(),
+            extracted: stats.extracted(),
+            warned: stats.warned(),
+            rejected: stats.rejected(),
+            noindex: stats.noindex(),
+            truncated: stats.truncated(),
+            transcoded: stats.transcoded(),
+            bytes_received: stats.bytes_received(),
+            bytes_decoded: stats.bytes_decoded(),
+            failed_tasks: stats.failed_tasks(),
+            hosts: stats.hosts(),
+            warned_reasons: stats.warned_reasons(),
+            latency: stats.latency(),
+            latency_by_host: stats.latencies(),
+            slowest_parses: stats.slowest_parses(),
+        }
+    }
+
+    /// Write the report to `path`, only logging a failure: the crawl is over either way.
+    pub(crate) async fn write(&self, path: &Path) {
+        let json = match serde_json::to_vec_pretty(self) {
+            Ok(json) => json,
+            Err(e) => return warn!("Failed to serialize the crawl report: {}", e),
+        };
+        match tokio::fs::write(path, json).await {
+            Ok(()) => info!("Crawl report written to {}", path.display()),
+            Err(e) => warn!(
+                "Failed to write the crawl report to {}: {}",
+                path.display(),
+                e
+            ),
+        }
+    }
+}
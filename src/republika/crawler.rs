@@ -0,0 +1,222 @@
+use crate::{
+    json_ld, republika::RepublikaArticle, text, utils, CrawlContext, Crawler, CrawlerResult,
+    ExtractedLink,
+};
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone};
+use lazy_regex::regex_captures;
+use lazy_static::lazy_static;
+use scraper::{Html, Selector};
+
+const E: &str = "Invalid selector";
+lazy_static! {
+    static ref OG_TYPE: Selector = Selector::parse(r#"meta[property="og:type"]"#).expect(E);
+    static ref TITLE: Selector = Selector::parse(r#"meta[property="og:title"]"#).expect(E);
+    static ref DESCRIPTION: Selector =
+        Selector::parse(r#"meta[property="og:description"]"#).expect(E);
+    static ref THUMBNAIL: Selector = Selector::parse(r#"meta[property="og:image"]"#).expect(E);
+    static ref AUTHOR: Selector = Selector::parse(r#"meta[name="author"]"#).expect(E);
+    static ref KEYWORDS: Selector = Selector::parse(r#"meta[name="keywords"]"#).expect(E);
+    static ref TAG: Selector = Selector::parse(".tag-list a, .taglist a").expect(E);
+    static ref DATE: Selector = Selector::parse(".date_detail, .date-detail").expect(E);
+    // News pages use `.article-content`, the khazanah and syariah templates `.artikel`
+    static ref BODY: Selector = Selector::parse(".article-content, .artikel").expect(E);
+    static ref ACTIVE_PAGE: Selector = Selector::parse(".pagination .active").expect(E);
+    static ref P: Selector = Selector::parse("p").expect(E);
+}
+
+const SKIPPED_PREFIXES: [&str; 3] = ["Baca Juga", "Baca juga", "Ikuti News Analysis"];
+
+#[derive(Debug, Clone)]
+pub struct RepublikaCrawler {
+    allowlist: utils::HostAllowlist,
+    strip_dateline: bool,
+}
+
+impl Default for RepublikaCrawler {
+    fn default() -> Self {
+        RepublikaCrawler {
+            allowlist: utils::HostAllowlist::new(["republika.co.id"]),
+            strip_dateline: true,
+        }
+    }
+}
+
+impl RepublikaCrawler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    utils::allowlist_builders!("republika.co.id");
+
+    /// Whether to remove the "REPUBLIKA.CO.ID, JAKARTA --" opening from the first paragraph,
+    /// true by default. Its place is kept in `dateline` either way.
+    pub fn with_dateline_stripped(mut self, strip_dateline: bool) -> Self {
+        self.strip_dateline = strip_dateline;
+        self
+    }
+}
+
+/// Split "REPUBLIKA.CO.ID, JAKARTA -- Text" into the place and the rest of the paragraph.
+fn split_dateline(paragraph: &str) -> Option<(String, String)> {
+    let (_, _, place, rest) = regex_captures!(
+        r"^((?:<[^>]+>)*)\s*REPUBLIKA\.CO\.ID\s*,\s*([^<\-–—]+?)\s*(?:</[^>]+>)*\s*[-–—]+\s*(?:</[^>]+>)*\s*(.*)$"s,
+        paragraph
+    )?;
+    Some((place.to_string(), rest.to_string()))
+}
+
+/// Parse "Rabu 14 Dec 2022 14:45 WIB", the site prints english month names.
+fn parse_date(s: &str) -> Option<DateTime<FixedOffset>> {
+    let (_, day, month, year, time) = regex_captures!(
+        r"(\d{1,2})\s+([A-Za-z]{3})[a-z]*\s+(\d{4}),?\s+(\d{1,2}:\d{2})",
+        s
+    )?;
+    let naive = NaiveDateTime::parse_from_str(
+        &format!("{} {} {} {}", day, month, year, time),
+        "%d %b %Y %H:%M",
+    )
+    .ok()?;
+    FixedOffset::east_opt(7 * 3600)?
+        .from_local_datetime(&naive)
+        .single()
+}
+
+impl Crawler for RepublikaCrawler {
+    type Document = RepublikaArticle;
+
+    fn can_be_scrapped(&self, doc: &Html) -> bool {
+        let is_article = json_ld::article(doc).is_some()
+            || utils::meta_content(doc, &OG_TYPE).is_some_and(|t| t == "article");
+        is_article && doc.select(&BODY).next().is_some()
+    }
+
+    fn extract_links(&self, ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        utils::links(doc, ctx.url, |url| self.allowlist.allows(url))
+    }
+
+    fn crawl(&self, ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        // Pagination links are among the links, so every page of an article is crawled
+        let links = self.extract_urls(ctx, doc);
+
+        if !self.can_be_scrapped(doc) {
+            return CrawlerResult::Links(links);
+        }
+
+        let ld = json_ld::article(doc);
+        let ld_string = |key: &str| ld.as_ref().and_then(|ld| json_ld::string(ld, key));
+
+        let published_date = ld_string("datePublished")
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .or_else(|| {
+                doc.select(&DATE)
+                    .find_map(|el| parse_date(&el.text().collect::<String>()))
+            });
+
+        let authors = ld
+            .as_ref()
+            .map(|ld| json_ld::names(ld, "author"))
+            .unwrap_or_default();
+        let author = if authors.is_empty() {
+            utils::meta_content(doc, &AUTHOR)
+        } else {
+            Some(authors.join(", "))
+        };
+
+        let mut tags: Vec<String> = doc
+            .select(&TAG)
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if tags.is_empty() {
+            tags = utils::meta_content(doc, &KEYWORDS)
+                .map(|s| {
+                    s.split(',')
+                        .map(|s| s.trim().to_string())
+                        .filter(|s| !s.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default();
+        }
+
+        let page = doc
+            .select(&ACTIVE_PAGE)
+            .next()
+            .and_then(|el| el.text().collect::<String>().trim().parse().ok())
+            .unwrap_or(1);
+
+        let mut dateline = None;
+        let mut paragraphs = vec![];
+        if let Some(body) = doc.select(&BODY).next() {
+            for p in body
+                .select(&P)
+                .filter(|p| !utils::has_ancestor_class(p, "baca-juga"))
+            {
+                if let Some(mut p) = text::clean_paragraph(&p.inner_html()) {
+                    if text::starts_with_any(&p, &SKIPPED_PREFIXES) {
+                        continue;
+                    }
+                    if dateline.is_none() && paragraphs.is_empty() {
+                        if let Some((place, rest)) = split_dateline(&p) {
+                            dateline = Some(place);
+                            if self.strip_dateline {
+                                p = rest;
+                            }
+                        }
+                    }
+                    if !p.is_empty() {
+                        paragraphs.push(p);
+                    }
+                }
+            }
+        }
+        paragraphs.dedup();
+
+        let article = RepublikaArticle {
+            title: utils::meta_content(doc, &TITLE).or_else(|| ld_string("headline")),
+            published_date,
+            description: utils::meta_content(doc, &DESCRIPTION)
+                .or_else(|| ld_string("description")),
+            thumbnail_url: utils::meta_content(doc, &THUMBNAIL),
+            author,
+            dateline,
+            tags,
+            page,
+            paragraphs,
+        };
+        CrawlerResult::document_and_links(article, links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn datelines() {
+        assert_eq!(
+            split_dateline("REPUBLIKA.CO.ID, JAKARTA -- Harga beras naik."),
+            Some(("JAKARTA".to_string(), "Harga beras naik.".to_string()))
+        );
+        assert_eq!(
+            split_dateline("<strong>REPUBLIKA.CO.ID, BANDUNG</strong> — Hujan deras."),
+            Some(("BANDUNG".to_string(), "Hujan deras.".to_string()))
+        );
+        assert_eq!(
+            split_dateline("<strong>REPUBLIKA.CO.ID, JAKARTA --</strong> Beras naik."),
+            Some(("JAKARTA".to_string(), "Beras naik.".to_string()))
+        );
+        assert_eq!(
+            split_dateline("Menurut REPUBLIKA.CO.ID, JAKARTA -- x"),
+            None
+        );
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(
+            parse_date("Rabu 14 Dec 2022 14:45 WIB"),
+            DateTime::parse_from_rfc3339("2022-12-14T14:45:00+07:00").ok()
+        );
+        assert_eq!(parse_date("Rabu 14 Des 2022 14:45 WIB"), None);
+    }
+}
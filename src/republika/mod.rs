@@ -0,0 +1,41 @@
+mod crawler;
+#[cfg(feature = "sqlite")]
+mod data;
+
+pub use crawler::RepublikaCrawler;
+#[cfg(feature = "sqlite")]
+pub use data::{RepublikaArticleTable, RepublikaData};
+
+use crate::Article;
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepublikaArticle {
+    pub title: Option<String>,
+    pub published_date: Option<DateTime<FixedOffset>>,
+    pub description: Option<String>,
+    pub thumbnail_url: Option<String>,
+    pub author: Option<String>,
+    /// Place of the "REPUBLIKA.CO.ID, JAKARTA --" opening, e.g. `JAKARTA`
+    pub dateline: Option<String>,
+    pub tags: Vec<String>,
+    /// Page of a multi-page article, each page is stored as its own result
+    pub page: u32,
+    pub paragraphs: Vec<String>,
+}
+
+impl Article for RepublikaArticle {
+    fn get_paragraphs(&self) -> &[String] {
+        self.paragraphs.as_slice()
+    }
+
+    fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    fn get_published_date(&self) -> Option<DateTime<FixedOffset>> {
+        self.published_date
+    }
+}
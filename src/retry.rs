@@ -0,0 +1,231 @@
+//! Queuing again, after a cooling-off period, the urls warned because of a failure that
+//! usually goes away by itself, like a timeout or a 5xx response.
+
+use crate::{CrawlerError, Storage, WarnedEntry, EMPTY_PARAGRAPHS, HTTP_5XX, PAYWALLED, TIMEOUT};
+use chrono::{DateTime, FixedOffset};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tokio::time::Duration;
+
+/// Reasons of pages that would be warned the same way however often they're fetched, never
+/// retried whatever the policy says.
+pub const PERMANENT_REASONS: [&str; 2] = [EMPTY_PARAGRAPHS, PAYWALLED];
+
+/// Warned urls queued again at most this many at a time.
+const DUE_BATCH: u32 = 100;
+
+/// How often the crawl looks for warned urls due again.
+pub(crate) const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The delays before each retry of a warned url, as many retries as delays.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetrySchedule {
+    pub delays: Vec<Duration>,
+}
+
+impl RetrySchedule {
+    /// `attempts` retries, the first `first` after the failure and each next one waiting
+    /// `factor` times longer than the previous one.
+    pub fn exponential(first: Duration, factor: u32, attempts: u32) -> Self {
+        RetrySchedule {
+            delays: (0..attempts).map(|i| first * factor.pow(i)).collect(),
+        }
+    }
+
+    /// The delay before retry number `attempt`, from 0, `None` once they're all used.
+    pub fn delay(&self, attempt: u32) -> Option<Duration> {
+        self.delays.get(attempt as usize).copied()
+    }
+}
+
+/// Which warned reasons are retried and when. By default timeouts after 10 minutes, 1 hour
+/// then 6 hours, and 5xx responses after 1 hour, 6 hours then 36 hours.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    schedules: HashMap<String, RetrySchedule>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::none()
+            .retry(
+                TIMEOUT,
+                RetrySchedule::exponential(Duration::from_secs(600), 6, 3),
+            )
+            .retry(
+                HTTP_5XX,
+                RetrySchedule::exponential(Duration::from_secs(3600), 6, 3),
+            )
+    }
+}
+
+impl RetryPolicy {
+    /// No warned url is retried.
+    pub fn none() -> Self {
+        RetryPolicy {
+            schedules: HashMap::new(),
+        }
+    }
+
+    /// Retry the urls warned with `reason` on `schedule`, unless it's one of
+    /// `PERMANENT_REASONS`.
+    pub fn retry(mut self, reason: &str, schedule: RetrySchedule) -> Self {
+        if !PERMANENT_REASONS.contains(&reason) {
+            self.schedules.insert(reason.to_string(), schedule);
+        }
+        self
+    }
+
+    pub fn schedule(&self, reason: &str) -> Option<&RetrySchedule> {
+        self.schedules.get(reason)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.schedules.is_empty()
+    }
+}
+
+/// Warn `url` with `reason` at `now`, due again when `policy` has a retry left for it. The
+/// retries already made are kept from its previous warning.
+pub(crate) async fn warn_at<S: Storage + ?Sized>(
+    storage: &S,
+    policy: &RetryPolicy,
+    url: &str,
+    reason: &str,
+    now: DateTime<FixedOffset>,
+) -> Result<Option<DateTime<FixedOffset>>, CrawlerError> {
+    let attempts = storage
+        .warned_get(url)
+        .await?
+        .map_or(0, |warned| warned.attempts);
+    let next_retry_at = policy
+        .schedule(reason)
+        .and_then(|schedule| schedule.delay(attempts))
+        .and_then(|delay| chrono::Duration::from_std(delay).ok())
+        .map(|delay| now + delay);
+    storage
+        .warned_schedule(url, reason, attempts, next_retry_at)
+        .await?;
+    Ok(next_retry_at)
+}
+
+/// Queue again the warned urls due at `now`, counting a retry for each and adding them to
+/// `retrying`. They stay warned, without a next retry, until they're crawled again.
+pub(crate) async fn requeue_due_at<S: Storage + ?Sized>(
+    storage: &S,
+    retrying: &Mutex<HashSet<String>>,
+    now: DateTime<FixedOffset>,
+) -> Result<Vec<WarnedEntry>, CrawlerError> {
+    let due = storage.warned_due(now, DUE_BATCH).await?;
+    for warned in &due {
+        let reason = warned.reason.as_deref().unwrap_or_default();
+        storage
+            .warned_schedule(&warned.url, reason, warned.attempts + 1, None)
+            .await?;
+        retrying.lock().unwrap().insert(warned.url.clone());
+        storage.queued_insert(&warned.url).await?;
+    }
+    Ok(due)
+}
+
+#[cfg(all(test, feature = "detik"))]
+mod tests {
+    use super::*;
+    use crate::detik::DetikArticle;
+    use crate::memory::MemoryStorage;
+
+    const URL: &str = "https://news.detik.com/berita/d-1/judul";
+
+    fn hours(n: i64) -> chrono::Duration {
+        chrono::Duration::hours(n)
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::none().retry(
+            HTTP_5XX,
+            RetrySchedule {
+                delays: vec![Duration::from_secs(3600), Duration::from_secs(6 * 3600)],
+            },
+        )
+    }
+
+    /// Fail `URL` at `now` and return when it's next due.
+    async fn fail(
+        storage: &MemoryStorage<DetikArticle>,
+        now: DateTime<FixedOffset>,
+    ) -> Option<DateTime<FixedOffset>> {
+        storage.queued_delete(URL).await.unwrap();
+        warn_at(storage, &policy(), URL, HTTP_5XX, now)
+            .await
+            .unwrap()
+    }
+
+    async fn requeued(storage: &MemoryStorage<DetikArticle>, now: DateTime<FixedOffset>) -> bool {
+        let retrying = Mutex::default();
+        !requeue_due_at(storage, &retrying, now)
+            .await
+            .unwrap()
+            .is_empty()
+            && storage.queued_is_exists(URL).await.unwrap()
+            && retrying.lock().unwrap().contains(URL)
+    }
+
+    #[test]
+    fn exponential_schedule() {
+        let schedule = RetrySchedule::exponential(Duration::from_secs(60), 6, 3);
+        assert_eq!(schedule.delay(0), Some(Duration::from_secs(60)));
+        assert_eq!(schedule.delay(2), Some(Duration::from_secs(36 * 60)));
+        assert_eq!(schedule.delay(3), None);
+
+        let policy = RetryPolicy::default().retry(PAYWALLED, schedule.clone());
+        assert!(policy.schedule(PAYWALLED).is_none());
+        assert!(policy.schedule(EMPTY_PARAGRAPHS).is_none());
+        assert!(policy.schedule(TIMEOUT).is_some());
+    }
+
+    #[tokio::test]
+    async fn schedule_and_cutoff() {
+        let storage = MemoryStorage::new();
+        let start = crate::utils::get_now();
+
+        // First failure, retried an hour later
+        assert_eq!(fail(&storage, start).await, Some(start + hours(1)));
+        assert!(!requeued(&storage, start + chrono::Duration::minutes(59)).await);
+        assert!(requeued(&storage, start + hours(1)).await);
+        let warned = storage.warned_get(URL).await.unwrap().unwrap();
+        assert_eq!((warned.attempts, warned.next_retry_at), (1, None));
+        // Not due again while it waits in the queue
+        assert!(!requeued(&storage, start + hours(100)).await);
+
+        // Failed again, retried 6 hours later
+        let second = start + hours(1);
+        assert_eq!(fail(&storage, second).await, Some(second + hours(6)));
+        assert!(!requeued(&storage, second + hours(5)).await);
+        assert!(requeued(&storage, second + hours(6)).await);
+
+        // Out of retries
+        let third = second + hours(6);
+        assert_eq!(fail(&storage, third).await, None);
+        assert!(!requeued(&storage, third + hours(1000)).await);
+        let warned = storage.warned_get(URL).await.unwrap().unwrap();
+        assert_eq!(warned.attempts, 2);
+        assert_eq!(warned.reason.as_deref(), Some(HTTP_5XX));
+    }
+
+    #[tokio::test]
+    async fn permanent_reasons_not_retried() {
+        let storage = MemoryStorage::<DetikArticle>::new();
+        let now = crate::utils::get_now();
+        let policy = policy().retry(
+            EMPTY_PARAGRAPHS,
+            RetrySchedule::exponential(Duration::ZERO, 1, 3),
+        );
+        for reason in [EMPTY_PARAGRAPHS, PAYWALLED, "language"] {
+            assert_eq!(
+                warn_at(&storage, &policy, URL, reason, now).await.unwrap(),
+                None
+            );
+        }
+        assert!(!requeued(&storage, now + hours(1000)).await);
+    }
+}
@@ -0,0 +1,127 @@
+//! Fetching the stored articles again once `RunOptions::revisit_after` went by, keeping a
+//! revision of the ones edited since they were stored.
+
+use crate::{CrawlerError, ResultEntry, Storage};
+use chrono::{DateTime, FixedOffset};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use tokio::time::Duration;
+
+/// Articles queued again at most this many at a time.
+const DUE_BATCH: u32 = 100;
+
+/// How often the crawl looks for articles due again, unless `revisit_after` is shorter.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often the crawl looks for the articles due again when they're revisited after
+/// `revisit_after`.
+pub(crate) fn check_interval(revisit_after: Duration) -> Duration {
+    revisit_after.clamp(Duration::from_secs(1), CHECK_INTERVAL)
+}
+
+/// Schedule `url`, stored at `now`, to be fetched again after `revisit_after`.
+pub(crate) async fn schedule_at<S: Storage + ?Sized>(
+    storage: &S,
+    url: &str,
+    revisit_after: Duration,
+    now: DateTime<FixedOffset>,
+) -> Result<(), CrawlerError> {
+    let after =
+        chrono::Duration::from_std(revisit_after).unwrap_or_else(|_| chrono::Duration::max_value());
+    match now.checked_add_signed(after) {
+        Some(due_at) => storage.revisit_schedule(url, due_at).await,
+        None => Ok(()),
+    }
+}
+
+/// Queue again the articles due at `now`, adding them to `revisiting`. They're taken out of
+/// visited, so the queue doesn't skip them.
+pub(crate) async fn requeue_due_at<S: Storage + ?Sized>(
+    storage: &S,
+    revisiting: &Mutex<HashSet<String>>,
+    now: DateTime<FixedOffset>,
+) -> Result<Vec<String>, CrawlerError> {
+    let due = storage.revisit_due(now, DUE_BATCH).await?;
+    for url in &due {
+        revisiting.lock().unwrap().insert(url.clone());
+        storage.visited_delete(url).await?;
+        storage.queued_insert(url).await?;
+    }
+    Ok(due)
+}
+
+/// What changed from the `old` to the `new` version of an article, `None` when nothing did.
+pub fn diff(old: &ResultEntry, new: &ResultEntry) -> Option<String> {
+    let mut changes = vec![];
+    if old.title != new.title {
+        changes.push("title changed".to_string());
+    }
+    if old.published_date != new.published_date {
+        changes.push("published date changed".to_string());
+    }
+    // Joined like the result tables store them
+    if old.paragraphs.join("\n") != new.paragraphs.join("\n") {
+        let changed = old
+            .paragraphs
+            .iter()
+            .zip(&new.paragraphs)
+            .filter(|(old, new)| old != new)
+            .count();
+        changes.push(format!(
+            "paragraphs: {} changed, {} added, {} removed",
+            changed,
+            new.paragraphs.len().saturating_sub(old.paragraphs.len()),
+            old.paragraphs.len().saturating_sub(new.paragraphs.len())
+        ));
+    }
+    (!changes.is_empty()).then(|| changes.join("; "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(title: &str, paragraphs: &[&str]) -> ResultEntry {
+        ResultEntry {
+            url: "https://news.detik.com/berita/d-1/judul".to_string(),
+            title: Some(title.to_string()),
+            published_date: None,
+            created_at: None,
+            referrer: None,
+            paragraphs: paragraphs.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn unchanged() {
+        assert_eq!(
+            diff(&entry("A", &["1", "2"]), &entry("A", &["1", "2"])),
+            None
+        );
+    }
+
+    #[test]
+    fn changes_summarized() {
+        assert_eq!(
+            diff(&entry("A", &["1", "2"]), &entry("B", &["1", "2"])),
+            Some("title changed".to_string())
+        );
+        assert_eq!(
+            diff(&entry("A", &["1", "2"]), &entry("A", &["1", "3", "4", "5"])),
+            Some("paragraphs: 1 changed, 2 added, 0 removed".to_string())
+        );
+        assert_eq!(
+            diff(&entry("A", &["1", "2"]), &entry("B", &["0"])),
+            Some("title changed; paragraphs: 1 changed, 0 added, 1 removed".to_string())
+        );
+    }
+
+    #[test]
+    fn interval_clamped() {
+        assert_eq!(
+            check_interval(Duration::from_secs(7 * 24 * 3600)),
+            CHECK_INTERVAL
+        );
+        assert_eq!(check_interval(Duration::ZERO), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,142 @@
+//! Rewrites of the alternate urls of an article, mobile hosts and AMP pages, to the desktop
+//! url a crawler expects, so an article is only queued and stored once.
+
+use reqwest::Url;
+
+/// The detik channels with their own subdomain, `m.detik.com/sport/...` being
+/// `sport.detik.com/...`. The others, like the regional ones, live under `www.detik.com`.
+pub const DETIK_CHANNELS: [&str; 11] = [
+    "news", "finance", "sport", "hot", "inet", "health", "food", "travel", "oto", "wolipop", "20",
+];
+
+/// One rewrite rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UrlRewrite {
+    /// `m.{domain}` to the desktop host: `{channel}.{domain}` without the channel segment
+    /// when the path starts with one of `channels`, else `www.{domain}`
+    MobileHost {
+        domain: String,
+        channels: Vec<String>,
+    },
+    /// Drop the `amp` segments of the path, e.g. `/berita/d-1/judul/amp`
+    StripAmp,
+}
+
+impl UrlRewrite {
+    pub fn mobile_host<I, C>(domain: &str, channels: I) -> Self
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<String>,
+    {
+        UrlRewrite::MobileHost {
+            domain: domain.to_string(),
+            channels: channels.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// The rules of detik: its mobile host and AMP pages.
+    pub fn detik() -> Vec<UrlRewrite> {
+        vec![
+            UrlRewrite::mobile_host("detik.com", DETIK_CHANNELS),
+            UrlRewrite::StripAmp,
+        ]
+    }
+
+    /// `url` rewritten, unchanged when the rule doesn't apply.
+    pub fn apply(&self, mut url: Url) -> Url {
+        match self {
+            UrlRewrite::MobileHost { domain, channels } => {
+                let mobile = url
+                    .host_str()
+                    .and_then(|host| host.strip_prefix("m."))
+                    .is_some_and(|host| host == domain);
+                if !mobile {
+                    return url;
+                }
+                let mut segments: Vec<String> = segments(&url);
+                let host = match segments.first() {
+                    Some(first) if channels.contains(first) => {
+                        format!("{}.{}", segments.remove(0), domain)
+                    }
+                    _ => format!("www.{}", domain),
+                };
+                if url.set_host(Some(&host)).is_ok() {
+                    set_segments(&mut url, &segments);
+                }
+                url
+            }
+            UrlRewrite::StripAmp => {
+                let amp = url
+                    .path_segments()
+                    .is_some_and(|mut segments| segments.any(|s| s == "amp"));
+                if amp {
+                    let segments: Vec<String> =
+                        segments(&url).into_iter().filter(|s| s != "amp").collect();
+                    set_segments(&mut url, &segments);
+                }
+                url
+            }
+        }
+    }
+}
+
+/// `url` after every rule of `rules`, in order.
+pub fn rewrite(url: Url, rules: &[UrlRewrite]) -> Url {
+    rules.iter().fold(url, |url, rule| rule.apply(url))
+}
+
+/// The non-empty segments of the path.
+fn segments(url: &Url) -> Vec<String> {
+    url.path_segments()
+        .map(|segments| {
+            segments
+                .filter(|s| !s.is_empty())
+                .map(ToString::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn set_segments(url: &mut Url, segments: &[String]) {
+    url.set_path(&format!("/{}", segments.join("/")));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detik_alternates() {
+        let rules = UrlRewrite::detik();
+        let rewritten = |url: &str| rewrite(Url::parse(url).unwrap(), &rules).to_string();
+        let desktop = "https://news.detik.com/berita/d-6453785/kpu-tetapkan-17-partai-politik";
+        for url in [
+            "https://m.detik.com/news/berita/d-6453785/kpu-tetapkan-17-partai-politik",
+            "https://news.detik.com/berita/d-6453785/kpu-tetapkan-17-partai-politik/amp",
+            "https://m.detik.com/news/berita/d-6453785/kpu-tetapkan-17-partai-politik/amp",
+            "https://news.detik.com/amp/berita/d-6453785/kpu-tetapkan-17-partai-politik",
+            desktop,
+        ] {
+            assert_eq!(rewritten(url), desktop, "{}", url);
+        }
+
+        assert_eq!(
+            rewritten("https://m.detik.com/sport/sepakbola/d-6454487/maroko-vs-portugal?page=2"),
+            "https://sport.detik.com/sepakbola/d-6454487/maroko-vs-portugal?page=2"
+        );
+        assert_eq!(
+            rewritten("https://m.detik.com/jatim/berita/d-6455006/banjir-pasuruan/amp"),
+            "https://www.detik.com/jatim/berita/d-6455006/banjir-pasuruan"
+        );
+        assert_eq!(rewritten("https://m.detik.com/"), "https://www.detik.com/");
+        // Only whole segments, and only the configured domain
+        assert_eq!(
+            rewritten("https://inet.detik.com/tag/ampli/d-1/amplifier"),
+            "https://inet.detik.com/tag/ampli/d-1/amplifier"
+        );
+        assert_eq!(
+            rewritten("https://m.kompas.com/news/read/1"),
+            "https://m.kompas.com/news/read/1"
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! Named lists of pages to start a crawl from, e.g. the front pages of every detik section,
+//! picked with `--seed-profile` instead of typing the urls.
+//!
+//! ```
+//! use indonesian_media_crawler::seeds::{SeedProfile, SeedProfiles};
+//!
+//! let profiles = SeedProfiles::builtin().with_profile(SeedProfile::new(
+//!     "kompas-news",
+//!     ["kompas.com"],
+//!     ["https://nasional.kompas.com/", "https://regional.kompas.com"],
+//! ));
+//! assert_eq!(
+//!     profiles.urls("kompas-news").unwrap(),
+//!     vec!["https://nasional.kompas.com", "https://regional.kompas.com"]
+//! );
+//! assert!(profiles.urls("detik-all").is_ok());
+//! ```
+
+use crate::utils;
+use std::collections::{BTreeMap, HashSet};
+
+/// Why the urls of a profile can't be crawled.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SeedError {
+    #[error("Unknown seed profile {0}")]
+    Unknown(String),
+    #[error("Seed profile {0} has no url")]
+    Empty(String),
+    /// A url `validate_crawl_url` rejects, with the reason
+    #[error("Invalid seed {0}: {1}")]
+    Invalid(String, String),
+    /// A url outside the hosts of its profile
+    #[error("Seed {0} is not on a host of its profile")]
+    HostNotAllowed(String),
+}
+
+/// The urls of a named profile, each on one of `allowed_hosts` or their subdomains.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeedProfile {
+    pub name: String,
+    pub allowed_hosts: Vec<String>,
+    pub urls: Vec<String>,
+}
+
+impl SeedProfile {
+    pub fn new<H, U>(name: &str, allowed_hosts: H, urls: U) -> Self
+    where
+        H: IntoIterator,
+        H::Item: Into<String>,
+        U: IntoIterator,
+        U::Item: Into<String>,
+    {
+        SeedProfile {
+            name: name.to_string(),
+            allowed_hosts: allowed_hosts.into_iter().map(Into::into).collect(),
+            urls: urls.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// The urls normalized like the links crawlers follow, without their fragment and
+    /// trailing slash, each once. Fails on the first url that isn't crawlable or is off
+    /// the hosts of the profile.
+    pub fn validate(&self) -> Result<Vec<String>, SeedError> {
+        if self.urls.is_empty() {
+            return Err(SeedError::Empty(self.name.clone()));
+        }
+        let mut seen = HashSet::new();
+        let mut urls = vec![];
+        for url in &self.urls {
+            let mut parsed = utils::validate_crawl_url(url)
+                .map_err(|rejection| SeedError::Invalid(url.clone(), rejection.to_string()))?;
+            if !utils::is_url_allowed(&parsed, &self.allowed_hosts, true) {
+                return Err(SeedError::HostNotAllowed(url.clone()));
+            }
+            parsed.set_fragment(None);
+            let url = utils::link_url(parsed);
+            if seen.insert(url.clone()) {
+                urls.push(url);
+            }
+        }
+        Ok(urls)
+    }
+}
+
+/// The profiles `--seed-profile` picks from by name: the built-in ones, and those registered
+/// with `with_profile`, e.g. for other sites.
+#[derive(Debug, Clone, Default)]
+pub struct SeedProfiles {
+    profiles: BTreeMap<String, SeedProfile>,
+}
+
+impl SeedProfiles {
+    /// The detik profiles: `detik-all`, and one per section like `detik-news`.
+    pub fn builtin() -> Self {
+        let detik = |name: &str, urls: Vec<String>| SeedProfile::new(name, ["detik.com"], urls);
+        SeedProfiles::default()
+            .with_profile(detik("detik-all", detik_all_sections()))
+            .with_profile(detik("detik-news", detik_news()))
+            .with_profile(detik("detik-finance", detik_finance()))
+            .with_profile(detik("detik-sport", detik_sport()))
+            .with_profile(detik("detik-hot", detik_hot()))
+            .with_profile(detik("detik-inet", detik_inet()))
+            .with_profile(detik("detik-oto", detik_oto()))
+            .with_profile(detik("detik-travel", detik_travel()))
+            .with_profile(detik("detik-food", detik_food()))
+            .with_profile(detik("detik-health", detik_health()))
+            .with_profile(detik("detik-edu", detik_edu()))
+            .with_profile(detik("detik-regional", detik_regional()))
+    }
+
+    /// Add `profile`, replacing the one of the same name.
+    pub fn with_profile(mut self, profile: SeedProfile) -> Self {
+        self.profiles.insert(profile.name.clone(), profile);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SeedProfile> {
+        self.profiles.get(name)
+    }
+
+    /// The names of the profiles, sorted.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+
+    /// The validated urls of the profile `name`, see `SeedProfile::validate`.
+    pub fn urls(&self, name: &str) -> Result<Vec<String>, SeedError> {
+        self.get(name)
+            .ok_or_else(|| SeedError::Unknown(name.to_string()))?
+            .validate()
+    }
+}
+
+fn urls(urls: &[&str]) -> Vec<String> {
+    urls.iter().map(ToString::to_string).collect()
+}
+
+/// The front pages of every detik section.
+pub fn detik_all_sections() -> Vec<String> {
+    let mut all = urls(&["https://www.detik.com"]);
+    for section in [
+        detik_news(),
+        detik_finance(),
+        detik_sport(),
+        detik_hot(),
+        detik_inet(),
+        detik_oto(),
+        detik_travel(),
+        detik_food(),
+        detik_health(),
+        detik_edu(),
+        detik_regional(),
+    ] {
+        all.extend(section);
+    }
+    all
+}
+
+pub fn detik_news() -> Vec<String> {
+    urls(&[
+        "https://news.detik.com",
+        "https://news.detik.com/berita",
+        "https://news.detik.com/internasional",
+        "https://news.detik.com/indeks",
+    ])
+}
+
+pub fn detik_finance() -> Vec<String> {
+    urls(&[
+        "https://finance.detik.com",
+        "https://finance.detik.com/berita-ekonomi-bisnis",
+        "https://finance.detik.com/bursa-dan-valas",
+        "https://finance.detik.com/indeks",
+    ])
+}
+
+pub fn detik_sport() -> Vec<String> {
+    urls(&[
+        "https://sport.detik.com",
+        "https://sport.detik.com/sepakbola",
+        "https://sport.detik.com/raket",
+        "https://sport.detik.com/indeks",
+    ])
+}
+
+pub fn detik_hot() -> Vec<String> {
+    urls(&["https://hot.detik.com", "https://hot.detik.com/indeks"])
+}
+
+pub fn detik_inet() -> Vec<String> {
+    urls(&["https://inet.detik.com", "https://inet.detik.com/indeks"])
+}
+
+pub fn detik_oto() -> Vec<String> {
+    urls(&["https://oto.detik.com", "https://oto.detik.com/indeks"])
+}
+
+pub fn detik_travel() -> Vec<String> {
+    urls(&[
+        "https://travel.detik.com",
+        "https://travel.detik.com/travel-news",
+        "https://travel.detik.com/indeks",
+    ])
+}
+
+pub fn detik_food() -> Vec<String> {
+    urls(&["https://food.detik.com", "https://food.detik.com/indeks"])
+}
+
+pub fn detik_health() -> Vec<String> {
+    urls(&[
+        "https://health.detik.com",
+        "https://health.detik.com/indeks",
+    ])
+}
+
+pub fn detik_edu() -> Vec<String> {
+    urls(&[
+        "https://www.detik.com/edu",
+        "https://www.detik.com/edu/indeks",
+    ])
+}
+
+/// The regional sections, under `www.detik.com`.
+pub fn detik_regional() -> Vec<String> {
+    urls(&[
+        "https://www.detik.com/jatim",
+        "https://www.detik.com/jateng",
+        "https://www.detik.com/jabar",
+        "https://www.detik.com/sumut",
+        "https://www.detik.com/sulsel",
+        "https://www.detik.com/bali",
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_profiles_valid() {
+        let profiles = SeedProfiles::builtin();
+        for name in profiles.names() {
+            let profile = profiles.get(name).unwrap();
+            let urls = profile.validate().unwrap();
+            assert!(!urls.is_empty(), "{}", name);
+            // Already normalized and deduplicated
+            assert_eq!(urls, profile.urls, "{}", name);
+        }
+        assert_eq!(profiles.urls("detik-all").unwrap(), detik_all_sections());
+        assert!(profiles.urls("detik-all").unwrap().len() > detik_news().len());
+    }
+
+    #[test]
+    fn normalized() {
+        let profile = SeedProfile::new(
+            "p",
+            ["detik.com"],
+            [
+                " https://news.detik.com/ ",
+                "https://news.detik.com#top",
+                "https://finance.detik.com/indeks/",
+            ],
+        );
+        assert_eq!(
+            profile.validate().unwrap(),
+            vec!["https://news.detik.com", "https://finance.detik.com/indeks"]
+        );
+    }
+
+    #[test]
+    fn rejected() {
+        let profiles = SeedProfiles::default()
+            .with_profile(SeedProfile::new(
+                "off-host",
+                ["detik.com"],
+                ["https://kompas.com"],
+            ))
+            .with_profile(SeedProfile::new(
+                "scheme",
+                ["detik.com"],
+                ["ftp://detik.com"],
+            ))
+            .with_profile(SeedProfile::new(
+                "empty",
+                ["detik.com"],
+                Vec::<String>::new(),
+            ));
+        assert_eq!(
+            profiles.urls("off-host"),
+            Err(SeedError::HostNotAllowed("https://kompas.com".to_string()))
+        );
+        assert!(matches!(
+            profiles.urls("scheme"),
+            Err(SeedError::Invalid(..))
+        ));
+        assert_eq!(
+            profiles.urls("empty"),
+            Err(SeedError::Empty("empty".to_string()))
+        );
+        assert_eq!(
+            profiles.urls("none"),
+            Err(SeedError::Unknown("none".to_string()))
+        );
+    }
+}
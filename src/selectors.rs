@@ -0,0 +1,141 @@
+//! Selectors replacing the built-in ones of a crawler, e.g. to follow a template change of a
+//! site from the config file without a new release, see `DetikCrawler::with_selectors`.
+//!
+//! ```
+//! use indonesian_media_crawler::selectors::{SelectorError, SelectorOverrides};
+//! use indonesian_media_crawler::CrawlerError;
+//!
+//! let known = ["title", "body"];
+//! let overrides = SelectorOverrides::parse([("body", ".detail__content")], &known).unwrap();
+//! assert_eq!(overrides.css("body"), Some(".detail__content"));
+//!
+//! match SelectorOverrides::parse([("body", "div[")], &known) {
+//!     Err(CrawlerError::InvalidSelector { name, source }) => {
+//!         assert_eq!(name, "body");
+//!         assert!(matches!(source, SelectorError::Css { .. }));
+//!     }
+//!     _ => panic!("Parsed"),
+//! }
+//! ```
+
+use crate::CrawlerError;
+use scraper::Selector;
+use std::collections::HashMap;
+
+/// Why a selector can't be used, see `CrawlerError::InvalidSelector`.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SelectorError {
+    /// Not valid CSS, with what went wrong and where
+    #[error("`{css}` is not a valid selector: {reason} at column {column}")]
+    Css {
+        css: String,
+        reason: String,
+        column: u32,
+    },
+    /// Not one of the selectors of the crawler
+    #[error("unknown selector, expected one of {}", .0.join(", "))]
+    Unknown(Vec<String>),
+}
+
+/// Selectors by name, parsed from their CSS.
+#[derive(Debug, Clone, Default)]
+pub struct SelectorOverrides {
+    selectors: HashMap<String, (String, Selector)>,
+}
+
+impl SelectorOverrides {
+    /// Parse `selectors`, CSS by name, among the `known` names of a crawler. The first one
+    /// unknown or invalid is a `CrawlerError::InvalidSelector`.
+    pub fn parse<I, N, S>(selectors: I, known: &[&str]) -> Result<Self, CrawlerError>
+    where
+        I: IntoIterator<Item = (N, S)>,
+        N: Into<String>,
+        S: AsRef<str>,
+    {
+        let mut overrides = SelectorOverrides::default();
+        for (name, css) in selectors {
+            let name = name.into();
+            let css = css.as_ref().trim();
+            if !known.contains(&name.as_str()) {
+                let known = known.iter().map(ToString::to_string).collect();
+                return Err(CrawlerError::InvalidSelector {
+                    name,
+                    source: SelectorError::Unknown(known),
+                });
+            }
+            let selector = match Selector::parse(css) {
+                Ok(selector) => selector,
+                Err(e) => {
+                    return Err(CrawlerError::InvalidSelector {
+                        name,
+                        source: SelectorError::Css {
+                            css: css.to_string(),
+                            reason: format!("{:?}", e.kind),
+                            column: e.location.column,
+                        },
+                    })
+                }
+            };
+            overrides
+                .selectors
+                .insert(name, (css.to_string(), selector));
+        }
+        Ok(overrides)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.selectors.is_empty()
+    }
+
+    /// The selector replacing the one named `name`, if any.
+    pub fn get(&self, name: &str) -> Option<&Selector> {
+        self.selectors.get(name).map(|(_, selector)| selector)
+    }
+
+    /// The CSS of the selector replacing the one named `name`, if any.
+    pub fn css(&self, name: &str) -> Option<&str> {
+        self.selectors.get(name).map(|(css, _)| css.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_selectors() {
+        let known = ["title", "body"];
+        let error = |name: &str, css: &str| match SelectorOverrides::parse([(name, css)], &known) {
+            Err(CrawlerError::InvalidSelector { name, source }) => (name, source),
+            Ok(_) => panic!("`{}` parsed", css),
+            Err(e) => panic!("{:?}", e),
+        };
+
+        let (name, source) = error("title", "meta[property=");
+        assert_eq!(name, "title");
+        assert!(
+            matches!(&source, SelectorError::Css { css, .. } if css == "meta[property="),
+            "{:?}",
+            source
+        );
+        let (name, source) = error("bdy", ".detail__body-text");
+        assert_eq!(name, "bdy");
+        assert_eq!(
+            source.to_string(),
+            "unknown selector, expected one of title, body"
+        );
+        assert!(error("body", "")
+            .1
+            .to_string()
+            .starts_with("`` is not a valid selector"));
+
+        let overrides = SelectorOverrides::parse([("title", " h1.title ")], &known).unwrap();
+        assert_eq!(overrides.css("title"), Some("h1.title"));
+        assert!(overrides.get("body").is_none());
+        assert!(
+            SelectorOverrides::parse(Vec::<(String, String)>::new(), &known)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
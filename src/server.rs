@@ -0,0 +1,309 @@
+use crate::dns::DnsStats;
+use crate::{
+    utils, CrawlerError, HostLatency, ResultEntry, ResultSummary, RunState, Storage, WarnedEntry,
+};
+use axum::{
+    extract::{Query, State},
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, future::Future, net::SocketAddr, sync::Arc};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Counts {
+    pub queued: u32,
+    pub running: u32,
+    pub visited: u32,
+    pub results: u32,
+    pub warned: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Rates {
+    pub fetched_per_minute: f64,
+    pub extracted_per_minute: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Status {
+    pub session_id: String,
+    pub uptime_secs: u64,
+    pub paused: bool,
+    pub counts: Counts,
+    pub fetched: u64,
+    pub extracted: u64,
+    pub warned: u64,
+    pub rejected: u64,
+    /// Articles not stored because their page is `noindex`
+    pub noindex: u64,
+    /// Bodies only parsed up to `RunOptions::max_parse_size`
+    pub truncated: u64,
+    /// Bodies decoded from another charset than UTF-8
+    pub transcoded: u64,
+    pub bytes_received: u64,
+    pub bytes_decoded: u64,
+    /// `None` without a DNS cache
+    pub dns: Option<DnsStats>,
+    pub rates: Rates,
+    /// Latencies of every fetch
+    pub latency: HostLatency,
+    /// Latencies of the fetches from each host, see `MAX_LATENCY_HOSTS`
+    pub latency_by_host: BTreeMap<String, HostLatency>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Paused {
+    pub paused: bool,
+}
+
+/// The answer to `POST /enqueue`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Enqueued {
+    /// The urls queued, normalized
+    pub queued: Vec<String>,
+    /// The urls already queued, running, visited or stored
+    pub duplicates: Vec<String>,
+    /// The urls not crawled, with why
+    pub rejected: BTreeMap<String, String>,
+}
+
+/// The ingestion endpoints of the status API, for the urls found outside the crawl:
+/// `POST /enqueue` queues a JSON array of urls and `GET /article?url=` answers the
+/// `ResultEntry` of a url. Their requests must bear the token, as
+/// `Authorization: Bearer <token>`.
+#[derive(Debug, Clone)]
+pub struct IngestConfig {
+    pub token: String,
+    /// `QueuedUrl::priority` of the urls queued through `/enqueue`, claimed before the urls
+    /// of a lower one
+    pub priority: i32,
+}
+
+impl IngestConfig {
+    pub fn new<S: Into<String>>(token: S) -> Self {
+        IngestConfig {
+            token: token.into(),
+            priority: 0,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Limit {
+    n: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ArticleQuery {
+    url: String,
+}
+
+struct AppState<S> {
+    storage: Arc<S>,
+    run: Arc<RunState>,
+}
+
+impl<S> Clone for AppState<S> {
+    fn clone(&self) -> Self {
+        AppState {
+            storage: Arc::clone(&self.storage),
+            run: Arc::clone(&self.run),
+        }
+    }
+}
+
+/// Bind the status API and return the server future, which completes when the crawl stops.
+pub(crate) fn serve<S>(
+    addr: SocketAddr,
+    storage: Arc<S>,
+    run: Arc<RunState>,
+) -> Result<impl Future<Output = Result<(), hyper::Error>>, CrawlerError>
+where
+    S: Storage + Send + Sync + 'static,
+{
+    let controller = run.options.controller.clone();
+    let app = Router::new()
+        .route("/status", get(status::<S>))
+        .route("/recent", get(recent::<S>))
+        .route("/warned", get(warned::<S>))
+        .route("/pause", post(pause::<S>))
+        .route("/resume", post(resume::<S>))
+        .route("/enqueue", post(enqueue::<S>))
+        .route("/article", get(article::<S>))
+        .with_state(AppState { storage, run });
+
+    let server = axum::Server::try_bind(&addr)?.serve(app.into_make_service());
+    tracing::info!("Status API listening on http://{}", server.local_addr());
+    Ok(server.with_graceful_shutdown(async move { controller.stopped().await }))
+}
+
+fn internal_error(e: CrawlerError) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+/// The `IngestConfig` of the run when `headers` bear its token.
+fn authorize<'a>(
+    run: &'a RunState,
+    headers: &HeaderMap,
+) -> Result<&'a IngestConfig, (StatusCode, String)> {
+    let ingest = run.options.ingest.as_ref().ok_or((
+        StatusCode::NOT_FOUND,
+        "The ingestion API is disabled".to_string(),
+    ))?;
+    let expected = format!("Bearer {}", ingest.token);
+    match headers.get(AUTHORIZATION) {
+        Some(value) if value.as_bytes() == expected.as_bytes() => Ok(ingest),
+        _ => Err((
+            StatusCode::UNAUTHORIZED,
+            "Missing or wrong bearer token".to_string(),
+        )),
+    }
+}
+
+async fn status<S: Storage>(
+    State(state): State<AppState<S>>,
+) -> Result<Json<Status>, (StatusCode, String)> {
+    let storage = &state.storage;
+    let stats = &state.run.stats;
+    let counts = async {
+        Ok::<_, CrawlerError>(Counts {
+            queued: storage.queued_count().await?,
+            running: storage.running_count().await?,
+            visited: storage.visited_count().await?,
+            results: storage.results_count().await?,
+            warned: storage.warned_count().await?,
+        })
+    }
+    .await
+    .map_err(internal_error)?;
+
+    Ok(Json(Status {
+        session_id: stats.session_id().to_string(),
+        uptime_secs: stats.uptime().as_secs(),
+        paused: state.run.options.controller.is_paused(),
+        counts,
+        fetched: stats.fetched(),
+        extracted: stats.extracted(),
+        warned: stats.warned(),
+        rejected: stats.rejected(),
+        noindex: stats.noindex(),
+        truncated: stats.truncated(),
+        transcoded: stats.transcoded(),
+        bytes_received: stats.bytes_received(),
+        bytes_decoded: stats.bytes_decoded(),
+        dns: state.run.dns.as_ref().map(|dns| dns.stats()),
+        rates: Rates {
+            fetched_per_minute: stats.per_minute(stats.fetched()),
+            extracted_per_minute: stats.per_minute(stats.extracted()),
+        },
+        latency: stats.latency(),
+        latency_by_host: stats.latencies(),
+    }))
+}
+
+async fn recent<S: Storage>(
+    State(state): State<AppState<S>>,
+    Query(limit): Query<Limit>,
+) -> Result<Json<Vec<ResultSummary>>, (StatusCode, String)> {
+    let n = limit.n.unwrap_or(20);
+    Ok(Json(
+        state
+            .storage
+            .results_recent(n)
+            .await
+            .map_err(internal_error)?,
+    ))
+}
+
+async fn warned<S: Storage>(
+    State(state): State<AppState<S>>,
+    Query(limit): Query<Limit>,
+) -> Result<Json<Vec<WarnedEntry>>, (StatusCode, String)> {
+    let n = limit.n.unwrap_or(50);
+    Ok(Json(
+        state
+            .storage
+            .warned_recent(n)
+            .await
+            .map_err(internal_error)?,
+    ))
+}
+
+async fn pause<S>(State(state): State<AppState<S>>) -> Json<Paused> {
+    state.run.options.controller.pause();
+    tracing::info!("Crawl paused through the status API");
+    Json(Paused { paused: true })
+}
+
+async fn resume<S>(State(state): State<AppState<S>>) -> Json<Paused> {
+    state.run.options.controller.resume();
+    tracing::info!("Crawl resumed through the status API");
+    Json(Paused { paused: false })
+}
+
+async fn enqueue<S: Storage>(
+    State(state): State<AppState<S>>,
+    headers: HeaderMap,
+    Json(urls): Json<Vec<String>>,
+) -> Result<Json<Enqueued>, (StatusCode, String)> {
+    let priority = authorize(&state.run, &headers)?.priority;
+    let storage = &state.storage;
+    let mut enqueued = Enqueued::default();
+    for url in urls {
+        let mut parsed = match utils::validate_crawl_url(&url) {
+            Ok(parsed) => parsed,
+            Err(rejection) => {
+                enqueued.rejected.insert(url, rejection.to_string());
+                continue;
+            }
+        };
+        parsed.set_fragment(None);
+        let url = parsed.to_string();
+        let seen = async {
+            Ok::<_, CrawlerError>(
+                storage.queued_is_exists(&url).await?
+                    || storage.running_is_exists(&url).await?
+                    || storage.visited_is_exists(&url).await?
+                    || storage.results_get(&url).await?.is_some(),
+            )
+        }
+        .await
+        .map_err(internal_error)?;
+        if seen {
+            enqueued.duplicates.push(url);
+        } else {
+            storage
+                .queued_insert_with_priority(&url, priority)
+                .await
+                .map_err(internal_error)?;
+            enqueued.queued.push(url);
+        }
+    }
+    tracing::info!(
+        "Queued {} urls through the ingestion API",
+        enqueued.queued.len()
+    );
+    Ok(Json(enqueued))
+}
+
+async fn article<S: Storage>(
+    State(state): State<AppState<S>>,
+    headers: HeaderMap,
+    Query(query): Query<ArticleQuery>,
+) -> Result<Json<ResultEntry>, (StatusCode, String)> {
+    authorize(&state.run, &headers)?;
+    match state
+        .storage
+        .results_get(&query.url)
+        .await
+        .map_err(internal_error)?
+    {
+        Some(entry) => Ok(Json(entry)),
+        None => Err((
+            StatusCode::NOT_FOUND,
+            format!("No article stored for {}", query.url),
+        )),
+    }
+}
@@ -0,0 +1,163 @@
+//! 64-bit SimHash of the paragraphs of an article, to find near duplicates such as the same
+//! wire copy published with another lede. Similar texts get hashes differing in few bits.
+
+use crate::text;
+
+/// Words per shingle.
+const SHINGLE: usize = 3;
+
+/// The SimHash over the word shingles of `paragraphs`, ignoring tags, case and punctuation.
+/// `None` when there are no words.
+pub fn simhash<S: AsRef<str>>(paragraphs: &[S]) -> Option<u64> {
+    let words: Vec<String> = paragraphs
+        .iter()
+        .flat_map(|p| {
+            text::plain_text(p.as_ref())
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|w| !w.is_empty())
+                .map(str::to_lowercase)
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+
+    let mut weights = [0i64; 64];
+    for shingle in words.windows(SHINGLE.min(words.len())) {
+        let hash = fnv1a(&shingle.join(" "));
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            if (hash >> bit) & 1 == 1 {
+                *weight += 1;
+            } else {
+                *weight -= 1;
+            }
+        }
+    }
+    Some(
+        weights
+            .iter()
+            .enumerate()
+            .filter(|(_, weight)| **weight > 0)
+            .fold(0, |hash, (bit, _)| hash | (1 << bit)),
+    )
+}
+
+/// The number of differing bits.
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Two articles whose hashes are `distance` bits apart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NearDuplicate {
+    pub url: String,
+    pub other_url: String,
+    pub distance: u32,
+}
+
+/// The pairs of `hashes` at most `threshold` bits apart, closest first, each pair in the
+/// order of `hashes`.
+pub fn near_duplicates(hashes: &[(String, u64)], threshold: u32) -> Vec<NearDuplicate> {
+    let mut pairs = vec![];
+    for (i, (url, hash)) in hashes.iter().enumerate() {
+        for (other_url, other) in &hashes[i + 1..] {
+            let distance = hamming_distance(*hash, *other);
+            if distance <= threshold {
+                pairs.push(NearDuplicate {
+                    url: url.clone(),
+                    other_url: other_url.clone(),
+                    distance,
+                });
+            }
+        }
+    }
+    pairs.sort_by_key(|pair| pair.distance);
+    pairs
+}
+
+/// Stable across builds and platforms, unlike `DefaultHasher`, as the hashes are stored.
+pub(crate) fn fnv1a(s: &str) -> u64 {
+    s.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIRE: [&str; 8] = [
+        "Kepala Dinas Pariwisata Badung, I Nyoman Rudiarta memastikan tak akan ada sweeping \
+         terhadap turis asing yang liburan ke Bali imbas disahkannya pasal zina dalam KUHP.",
+        "Rudiarta menyebut pemerintah daerah sudah berkoordinasi dengan para pelaku usaha \
+         pariwisata agar informasi soal KUHP baru tidak disalahartikan oleh wisatawan.",
+        "Menurut dia, pasal tersebut merupakan delik aduan sehingga hanya bisa diproses jika \
+         ada laporan dari suami, istri, orang tua, atau anak.",
+        "\"Seluruh wisatawan akan tetap aman dan nyaman, saat menikmati liburannya di Bali,\" \
+         tegas Rudiarta kepada wartawan, Jumat (9/12/2022).",
+        "Sejumlah asosiasi hotel dan biro perjalanan sebelumnya khawatir aturan baru itu \
+         membuat wisatawan mancanegara membatalkan rencana liburan mereka ke Pulau Dewata.",
+        "Data Badan Pusat Statistik mencatat kunjungan wisatawan mancanegara ke Bali terus \
+         meningkat sejak pintu masuk internasional kembali dibuka pada awal tahun ini.",
+        "Pemerintah provinsi juga menyiapkan surat edaran yang menjelaskan isi KUHP baru \
+         dalam bahasa Inggris untuk dibagikan di bandara dan hotel-hotel di Bali.",
+        "KUHP baru sendiri baru akan berlaku tiga tahun setelah diundangkan, sehingga \
+         aturan mengenai perzinaan belum diterapkan dalam waktu dekat.",
+    ];
+
+    #[test]
+    fn similar_texts_are_close() {
+        let original = simhash(&WIRE).unwrap();
+
+        let mut relede = WIRE;
+        relede[0] = "<strong>Badung</strong> - Pemerintah Kabupaten Badung menjamin tidak ada \
+                     razia terhadap turis asing yang berlibur ke Bali.";
+        assert!(hamming_distance(original, simhash(&relede).unwrap()) <= 10);
+
+        // Tags, case and punctuation are normalized away
+        let reformatted = WIRE.map(|p| format!("<p>{}</p>", p.to_uppercase().replace(',', "")));
+        assert_eq!(simhash(&reformatted), Some(original));
+    }
+
+    #[test]
+    fn dissimilar_texts_are_far() {
+        let other = [
+            "Maroko vs Portugal akan bermain malam ini di perempatfinal Piala Dunia 2022. \
+             Susunan pemainnya sudah keluar, Cristiano Ronaldo jadi cadangan lagi.",
+            "Pelatih Fernando Santos kembali memainkan Goncalo Ramos sebagai penyerang utama \
+             setelah mencetak hattrick ke gawang Swiss di babak 16 besar.",
+            "Maroko menurunkan kekuatan terbaiknya dengan Hakim Ziyech dan Youssef En-Nesyri \
+             di lini depan, sementara Yassine Bounou tetap mengawal gawang.",
+        ];
+        assert!(hamming_distance(simhash(&WIRE).unwrap(), simhash(&other).unwrap()) > 20);
+    }
+
+    #[test]
+    fn short_and_empty_texts() {
+        assert!(simhash(&["Dua kata"]).is_some());
+        assert_eq!(simhash::<&str>(&[]), None);
+        assert_eq!(simhash(&["<br>", "..."]), None);
+        assert_eq!(hamming_distance(0, u64::MAX), 64);
+    }
+
+    #[test]
+    fn pairs_within_threshold() {
+        let hashes = [
+            ("a".to_string(), 0b1111),
+            ("b".to_string(), 0b0111),
+            ("c".to_string(), 0b1111),
+            ("d".to_string(), u64::MAX),
+        ];
+        let pair = |url: &str, other_url: &str, distance| NearDuplicate {
+            url: url.to_string(),
+            other_url: other_url.to_string(),
+            distance,
+        };
+        assert_eq!(
+            near_duplicates(&hashes, 1),
+            vec![pair("a", "c", 0), pair("a", "b", 1), pair("b", "c", 1)]
+        );
+        assert!(near_duplicates(&hashes[..1], 64).is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+use crate::{
+    json_ld, sindonews::SindonewsArticle, text, utils, CrawlContext, Crawler, CrawlerResult,
+    ExtractedLink,
+};
+use chrono::DateTime;
+use lazy_regex::regex_captures;
+use lazy_static::lazy_static;
+use scraper::{Html, Selector};
+
+const E: &str = "Invalid selector";
+lazy_static! {
+    static ref OG_TYPE: Selector = Selector::parse(r#"meta[property="og:type"]"#).expect(E);
+    static ref TITLE: Selector = Selector::parse(r#"meta[property="og:title"]"#).expect(E);
+    static ref DESCRIPTION: Selector =
+        Selector::parse(r#"meta[property="og:description"]"#).expect(E);
+    static ref THUMBNAIL: Selector = Selector::parse(r#"meta[property="og:image"]"#).expect(E);
+    static ref CANONICAL: Selector = Selector::parse(r#"link[rel="canonical"]"#).expect(E);
+    static ref PUBLISHED_TIME: Selector =
+        Selector::parse(r#"meta[property="article:published_time"]"#).expect(E);
+    static ref AUTHOR: Selector = Selector::parse(r#"meta[name="author"]"#).expect(E);
+    static ref KEYWORDS: Selector = Selector::parse(r#"meta[name="keywords"]"#).expect(E);
+    static ref TAG: Selector = Selector::parse(".detail-tag a").expect(E);
+    static ref BODY: Selector = Selector::parse("#detail-desc, .detail-desc").expect(E);
+    static ref ACTIVE_PAGE: Selector = Selector::parse(".pagination .active").expect(E);
+    static ref P: Selector = Selector::parse("p").expect(E);
+}
+
+const SKIPPED_PREFIXES: [&str; 4] = ["Baca Juga", "Baca juga", "Lihat Juga", "Lihat juga"];
+
+#[derive(Debug, Clone)]
+pub struct SindonewsCrawler {
+    allowlist: utils::HostAllowlist,
+}
+
+impl Default for SindonewsCrawler {
+    fn default() -> Self {
+        SindonewsCrawler {
+            allowlist: utils::HostAllowlist::new(["sindonews.com"]),
+        }
+    }
+}
+
+impl SindonewsCrawler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    utils::allowlist_builders!("sindonews.com");
+}
+
+/// The subdomain of `nasional.sindonews.com/read/...`.
+fn category(url: &reqwest::Url) -> Option<String> {
+    url.host_str()?
+        .strip_suffix(".sindonews.com")
+        .filter(|sub| *sub != "www" && !sub.contains('.'))
+        .map(ToString::to_string)
+}
+
+/// Split the "(abc)" reporter initials off the end of a paragraph.
+fn split_initials(paragraph: &str) -> Option<(String, String)> {
+    let (_, rest, initials, _) =
+        regex_captures!(r"^(.*?)\s*\(([a-z]{2,5})\)\s*(</[^>]+>)*$"s, paragraph)?;
+    Some((rest.to_string(), initials.to_string()))
+}
+
+impl Crawler for SindonewsCrawler {
+    type Document = SindonewsArticle;
+
+    fn can_be_scrapped(&self, doc: &Html) -> bool {
+        let is_article = json_ld::article(doc).is_some()
+            || utils::meta_content(doc, &OG_TYPE).is_some_and(|t| t == "article");
+        is_article && doc.select(&BODY).next().is_some()
+    }
+
+    fn extract_links(&self, ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        utils::links(doc, ctx.url, |url| self.allowlist.allows(url))
+    }
+
+    fn crawl(&self, ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        // Pagination links are among the links, so every page of an article is crawled
+        let links = self.extract_urls(ctx, doc);
+
+        if !self.can_be_scrapped(doc) {
+            return CrawlerResult::Links(links);
+        }
+
+        let ld = json_ld::article(doc);
+        let ld_string = |key: &str| ld.as_ref().and_then(|ld| json_ld::string(ld, key));
+
+        let published_date = utils::meta_content(doc, &PUBLISHED_TIME)
+            .or_else(|| ld_string("datePublished"))
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok());
+
+        let canonical = doc
+            .select(&CANONICAL)
+            .next()
+            .and_then(|el| el.value().attr("href"))
+            .and_then(|s| reqwest::Url::parse(s.trim()).ok());
+
+        let author = utils::meta_content(doc, &AUTHOR).or_else(|| {
+            ld.as_ref()
+                .map(|ld| json_ld::names(ld, "author"))
+                .filter(|names| !names.is_empty())
+                .map(|names| names.join(", "))
+        });
+
+        let mut tags: Vec<String> = doc
+            .select(&TAG)
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if tags.is_empty() {
+            tags = utils::meta_content(doc, &KEYWORDS)
+                .map(|s| {
+                    s.split(',')
+                        .map(|s| s.trim().to_string())
+                        .filter(|s| !s.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default();
+        }
+
+        let page = doc
+            .select(&ACTIVE_PAGE)
+            .next()
+            .and_then(|el| el.text().collect::<String>().trim().parse().ok())
+            .unwrap_or(1);
+
+        let mut paragraphs = vec![];
+        if let Some(body) = doc.select(&BODY).next() {
+            for p in body.select(&P).filter(|p| {
+                !utils::has_ancestor_class(p, "baca-juga")
+                    && !utils::has_ancestor_class(p, "lihat-juga")
+            }) {
+                if let Some(p) = text::clean_paragraph(&p.inner_html()) {
+                    if !text::starts_with_any(&p, &SKIPPED_PREFIXES) {
+                        paragraphs.push(p);
+                    }
+                }
+            }
+        }
+        paragraphs.dedup();
+
+        let mut reporter_initials = None;
+        if let Some((rest, initials)) = paragraphs.last().and_then(|p| split_initials(p)) {
+            reporter_initials = Some(initials);
+            paragraphs.pop();
+            if !rest.is_empty() {
+                paragraphs.push(rest);
+            }
+        }
+
+        let article = SindonewsArticle {
+            title: utils::meta_content(doc, &TITLE).or_else(|| ld_string("headline")),
+            published_date,
+            description: utils::meta_content(doc, &DESCRIPTION)
+                .or_else(|| ld_string("description")),
+            thumbnail_url: utils::meta_content(doc, &THUMBNAIL),
+            author,
+            reporter_initials,
+            category: category(canonical.as_ref().unwrap_or(ctx.url)),
+            tags,
+            page,
+            paragraphs,
+        };
+        CrawlerResult::document_and_links(article, links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn initials() {
+        assert_eq!(
+            split_initials("Harga emas turun tipis. (nng)"),
+            Some(("Harga emas turun tipis.".to_string(), "nng".to_string()))
+        );
+        assert_eq!(
+            split_initials("(abd)"),
+            Some((String::new(), "abd".to_string()))
+        );
+        assert_eq!(split_initials("Rapat digelar (Senin) pagi."), None);
+        assert_eq!(split_initials("Kata Ketua DPR (RI)"), None);
+    }
+
+    #[test]
+    fn categories() {
+        let category = |s: &str| category(&reqwest::Url::parse(s).unwrap());
+        assert_eq!(
+            category("https://nasional.sindonews.com/read/1/12/judul"),
+            Some("nasional".to_string())
+        );
+        assert_eq!(category("https://www.sindonews.com/read/1/12/judul"), None);
+    }
+}
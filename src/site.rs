@@ -0,0 +1,84 @@
+use clap::ValueEnum;
+use std::fmt;
+
+/// The news sites with a crawler, as picked with `--site` on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Site {
+    #[cfg(feature = "detik")]
+    Detik,
+    Kompas,
+    Tempo,
+    Liputan6,
+    Tribunnews,
+    Antara,
+    Republika,
+    Kumparan,
+    Tirto,
+    Merdeka,
+    Sindonews,
+    CnbcIndonesia,
+    Kontan,
+    IdnTimes,
+    BbcIndonesia,
+    JawaPos,
+    PikiranRakyat,
+    MediaIndonesia,
+}
+
+impl Site {
+    pub fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "detik")]
+            Site::Detik => "detik",
+            Site::Kompas => "kompas",
+            Site::Tempo => "tempo",
+            Site::Liputan6 => "liputan6",
+            Site::Tribunnews => "tribunnews",
+            Site::Antara => "antara",
+            Site::Republika => "republika",
+            Site::Kumparan => "kumparan",
+            Site::Tirto => "tirto",
+            Site::Merdeka => "merdeka",
+            Site::Sindonews => "sindonews",
+            Site::CnbcIndonesia => "cnbcindonesia",
+            Site::Kontan => "kontan",
+            Site::IdnTimes => "idntimes",
+            Site::BbcIndonesia => "bbcindonesia",
+            Site::JawaPos => "jawapos",
+            Site::PikiranRakyat => "pikiranrakyat",
+            Site::MediaIndonesia => "mediaindonesia",
+        }
+    }
+
+    /// Pages a crawl starts from when its queue is empty.
+    pub fn start_urls(self) -> Vec<String> {
+        let urls: &[&str] = match self {
+            #[cfg(feature = "detik")]
+            Site::Detik => &["https://travel.detik.com/travel-news/d-6454465/kadispar-badung-jamin-wisman-tak-disweeping-imbas-pasal-zina-kuhp"],
+            Site::Kompas => &["https://www.kompas.com"],
+            Site::Tempo => &["https://www.tempo.co"],
+            Site::Liputan6 => &["https://www.liputan6.com"],
+            Site::Tribunnews => &["https://www.tribunnews.com"],
+            Site::Antara => &["https://www.antaranews.com"],
+            Site::Republika => &["https://www.republika.co.id"],
+            Site::Kumparan => &["https://kumparan.com"],
+            Site::Tirto => &["https://tirto.id"],
+            Site::Merdeka => &["https://www.merdeka.com"],
+            Site::Sindonews => &["https://www.sindonews.com"],
+            Site::CnbcIndonesia => &["https://www.cnbcindonesia.com"],
+            Site::Kontan => &["https://www.kontan.co.id"],
+            Site::IdnTimes => &["https://www.idntimes.com"],
+            Site::BbcIndonesia => &["https://www.bbc.com/indonesia"],
+            Site::JawaPos => &["https://www.jawapos.com"],
+            Site::PikiranRakyat => &["https://www.pikiran-rakyat.com"],
+            Site::MediaIndonesia => &["https://mediaindonesia.com"],
+        };
+        urls.iter().map(ToString::to_string).collect()
+    }
+}
+
+impl fmt::Display for Site {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -0,0 +1,102 @@
+//! Reading the XML sitemaps news sites publish, listing their articles with the date each was
+//! published or last modified.
+
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone};
+use lazy_regex::regex;
+
+/// One `<url>` of a sitemap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SitemapEntry {
+    pub loc: String,
+    /// `<news:publication_date>` of a news sitemap, or else `<lastmod>`
+    pub published_date: Option<DateTime<FixedOffset>>,
+}
+
+/// The entries of a sitemap or the sitemaps of a sitemap index, in document order. Entries
+/// without a `<loc>` are skipped, dates that don't parse are left out.
+pub fn parse(xml: &str) -> Vec<SitemapEntry> {
+    regex!(r"(?s)<(?:url|sitemap)\b[^>]*>(.*?)</(?:url|sitemap)>")
+        .captures_iter(xml)
+        .filter_map(|entry| {
+            let entry = &entry[1];
+            let loc = tag_text(entry, "loc")?;
+            let published_date = tag_text(entry, "news:publication_date")
+                .or_else(|| tag_text(entry, "lastmod"))
+                .and_then(|date| parse_date(&date));
+            Some(SitemapEntry {
+                loc,
+                published_date,
+            })
+        })
+        .collect()
+}
+
+/// The trimmed text of the first `<tag>` of `xml`, unwrapped from a CDATA section.
+fn tag_text(xml: &str, tag: &str) -> Option<String> {
+    let start = format!("<{}>", tag);
+    let from = xml.find(&start)? + start.len();
+    let to = from + xml[from..].find(&format!("</{}>", tag))?;
+    let text = xml[from..to].trim();
+    let text = text
+        .strip_prefix("<![CDATA[")
+        .and_then(|t| t.strip_suffix("]]>"))
+        .unwrap_or(text)
+        .trim()
+        .replace("&amp;", "&");
+    Some(text).filter(|t| !t.is_empty())
+}
+
+/// W3C datetimes: a full RFC 3339 date or only the day, taken as midnight WIB.
+fn parse_date(date: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(date).ok().or_else(|| {
+        let day = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+        FixedOffset::east_opt(7 * 3600)?
+            .from_local_datetime(&day.and_hms_opt(0, 0, 0)?)
+            .single()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn news_sitemap() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"
+                    xmlns:news="http://www.google.com/schemas/sitemap-news/0.9">
+              <url>
+                <loc>https://news.detik.com/berita/d-1/a?x=1&amp;y=2</loc>
+                <lastmod>2022-12-10T08:00:00+07:00</lastmod>
+                <news:news>
+                  <news:publication_date>2022-12-09T13:19:00+07:00</news:publication_date>
+                </news:news>
+              </url>
+              <url>
+                <loc><![CDATA[ https://news.detik.com/berita/d-2/b ]]></loc>
+                <lastmod>2022-12-09</lastmod>
+              </url>
+              <url><loc>https://news.detik.com/berita/d-3/c</loc><lastmod>kemarin</lastmod></url>
+              <url><lastmod>2022-12-09</lastmod></url>
+            </urlset>"#;
+        let date = |d: &str| Some(DateTime::parse_from_rfc3339(d).unwrap());
+        assert_eq!(
+            parse(xml),
+            vec![
+                SitemapEntry {
+                    loc: "https://news.detik.com/berita/d-1/a?x=1&y=2".to_string(),
+                    published_date: date("2022-12-09T13:19:00+07:00"),
+                },
+                SitemapEntry {
+                    loc: "https://news.detik.com/berita/d-2/b".to_string(),
+                    published_date: date("2022-12-09T00:00:00+07:00"),
+                },
+                SitemapEntry {
+                    loc: "https://news.detik.com/berita/d-3/c".to_string(),
+                    published_date: None,
+                },
+            ]
+        );
+        assert!(parse("<html><body>Not found</body></html>").is_empty());
+    }
+}
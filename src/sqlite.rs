@@ -0,0 +1,1749 @@
+use crate::compression::{self, ParagraphCodec};
+use crate::recrawl::LinkSnapshot;
+use crate::visited_cache::{Cached, VisitedCache, VisitedCacheStats};
+use crate::{
+    utils, Bucket, CrawlerError, ExtractionStatus, HostState, HubState, QueuedUrl, ResultEntry,
+    ResultSummary, Revision, Storage, Table, UrlBucket, WarnedEntry,
+};
+use chrono::{DateTime, FixedOffset};
+use futures::stream::{BoxStream, StreamExt, TryStreamExt};
+use sha2::{Digest, Sha256};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteRow};
+use sqlx::{Row, Sqlite, SqlitePool, Transaction};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Urls longer than this many bytes are keyed by their hash, see `UrlKey`.
+pub const MAX_KEY_LEN: usize = 512;
+
+/// The `id` a url is stored with in the tables of a `SqliteStorage`: the url itself, or the
+/// SHA-256 of the urls longer than `MAX_KEY_LEN` bytes or with control characters, so a
+/// broken page can't bloat the indexes. The url of a hashed key is kept in the `url` column
+/// and read back in its place.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlKey<'a> {
+    url: &'a str,
+    digest: Option<String>,
+}
+
+impl<'a> UrlKey<'a> {
+    pub fn new(url: &'a str) -> Self {
+        let digest = (url.len() > MAX_KEY_LEN || url.chars().any(char::is_control))
+            .then(|| format!("sha256:{:x}", Sha256::digest(url.as_bytes())));
+        UrlKey { url, digest }
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.digest.as_deref().unwrap_or(self.url)
+    }
+
+    /// The url to keep in the `url` column, only when it's hashed.
+    pub fn url(&self) -> Option<&'a str> {
+        self.digest.as_ref().map(|_| self.url)
+    }
+}
+
+/// Key the rows of `table` stored by urls `UrlKey` hashes, stored before keys were hashed,
+/// returning the old and new keys.
+async fn rekey(pool: &SqlitePool, table: &str) -> Result<Vec<(String, String)>, sqlx::Error> {
+    let query = format!(
+        "SELECT id FROM {} WHERE url IS NULL AND (length(CAST(id AS BLOB)) > ? OR id GLOB ?)",
+        table
+    );
+    let control = format!("*[{}-{}{}-{}]*", '\u{1}', '\u{1f}', '\u{7f}', '\u{9f}');
+    let ids: Vec<String> = sqlx::query_scalar(&query)
+        .bind(MAX_KEY_LEN as i64)
+        .bind(control)
+        .fetch_all(pool)
+        .await?;
+    let update = format!(
+        "UPDATE OR IGNORE {} SET id = ?, url = ? WHERE id = ?",
+        table
+    );
+    // Left when the url was stored again since with its new key
+    let delete = format!("DELETE FROM {} WHERE id = ?", table);
+    let mut rekeyed = vec![];
+    if ids.is_empty() {
+        return Ok(rekeyed);
+    }
+    let mut tx = pool.begin().await?;
+    for id in ids {
+        let key = UrlKey::new(&id);
+        sqlx::query(&update)
+            .bind(key.as_str())
+            .bind(key.url())
+            .bind(&id)
+            .execute(&mut tx)
+            .await?;
+        sqlx::query(&delete).bind(&id).execute(&mut tx).await?;
+        rekeyed.push((id.clone(), key.as_str().to_string()));
+    }
+    tx.commit().await?;
+    Ok(rekeyed)
+}
+
+pub struct UrlTable {
+    name: String,
+    pool: SqlitePool,
+    /// The query of `UrlBucket::stream`, kept for as long as the streams borrowing it
+    stream_query: String,
+    /// The urls of the table in memory, see `SqliteStorage::with_visited_cache`
+    cache: Option<VisitedCache>,
+}
+
+#[async_trait::async_trait]
+impl Table for UrlTable {
+    type Record<'a> = &'a str;
+
+    fn get_name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn get_pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    async fn create(&self) -> Result<(), sqlx::Error> {
+        if !utils::is_table_exists(self.get_pool(), &self.name).await? {
+            let query = format!(
+                "CREATE TABLE {} (
+                    id TEXT PRIMARY KEY,
+                    created_at DATETIME,
+                    url TEXT
+                 )",
+                &self.name
+            );
+            sqlx::query(query.as_str()).execute(self.get_pool()).await?;
+        }
+        Ok(())
+    }
+
+    async fn insert<'a>(&self, record: Self::Record<'a>) -> Result<(), sqlx::Error> {
+        let timestamp = utils::get_now();
+        let mut tx = self.get_pool().begin().await?;
+        let query = format!(
+            "INSERT OR IGNORE INTO {} (id, created_at, url) VALUES (?, ?, ?)",
+            &self.name
+        );
+        let key = UrlKey::new(record);
+        sqlx::query(&query)
+            .bind(key.as_str())
+            .bind(timestamp)
+            .bind(key.url())
+            .execute(&mut tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+impl UrlTable {
+    fn new(name: String, pool: SqlitePool) -> Self {
+        UrlTable {
+            stream_query: format!("SELECT COALESCE(url, id) FROM {} ORDER BY rowid", name),
+            name,
+            pool,
+            cache: None,
+        }
+    }
+
+    /// Whether `key` is in the table by its cache, looked up when the cache only knows it
+    /// likely is. `None` without a cache, and when it wasn't in the table as far as the
+    /// cache knows: another process may have added it since.
+    async fn cached(&self, key: &str) -> Result<Option<bool>, sqlx::Error> {
+        let cache = match &self.cache {
+            Some(cache) => cache,
+            None => return Ok(None),
+        };
+        match cache.get(key) {
+            Cached::Visited => Ok(Some(true)),
+            Cached::NotVisited => Ok(None),
+            Cached::MaybeVisited => {
+                let exists = self.is_exist(key).await?;
+                cache.verified(key, exists);
+                Ok(Some(exists))
+            }
+        }
+    }
+
+    /// Insert all urls in a single transaction.
+    pub async fn insert_batch<I: AsRef<str> + Sync>(&self, items: &[I]) -> Result<(), sqlx::Error> {
+        let timestamp = utils::get_now();
+        let mut tx = self.get_pool().begin().await?;
+        let query = format!(
+            "INSERT OR IGNORE INTO {} (id, created_at, url) VALUES (?, ?, ?)",
+            &self.name
+        );
+        for item in items {
+            let key = UrlKey::new(item.as_ref());
+            sqlx::query(&query)
+                .bind(key.as_str())
+                .bind(timestamp)
+                .bind(key.url())
+                .execute(&mut tx)
+                .await?;
+        }
+        tx.commit().await?;
+        if let Some(cache) = &self.cache {
+            for item in items {
+                cache.insert(UrlKey::new(item.as_ref()).as_str());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl UrlBucket for UrlTable {
+    async fn urls(&self, limit: Option<u32>) -> Result<Vec<String>, CrawlerError> {
+        let mut urls: Vec<String> = vec![];
+        // A negative limit is no limit in SQLite
+        let query = format!(
+            "SELECT COALESCE(url, id) AS id FROM {} ORDER BY created_at LIMIT ?",
+            self.name
+        );
+        let limit = limit.map_or(-1, i64::from);
+        for row in sqlx::query(&query)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?
+        {
+            urls.push(row.try_get("id")?);
+        }
+        Ok(urls)
+    }
+
+    /// Read a row at a time through a cursor, in the order the rows were inserted, which is
+    /// the order of `urls` without sorting the table.
+    fn stream(&self) -> BoxStream<'_, Result<String, CrawlerError>> {
+        sqlx::query_scalar(&self.stream_query)
+            .fetch(&self.pool)
+            .map_err(CrawlerError::from)
+            .boxed()
+    }
+
+    async fn is_empty(&self) -> Result<bool, CrawlerError> {
+        let query = format!("SELECT 1 FROM {} LIMIT 1", self.name);
+        Ok(sqlx::query(&query)
+            .fetch_optional(&self.pool)
+            .await?
+            .is_none())
+    }
+
+    async fn insert(&self, url: &str) -> Result<(), CrawlerError> {
+        Table::insert(self, url).await?;
+        if let Some(cache) = &self.cache {
+            cache.insert(UrlKey::new(url).as_str());
+        }
+        Ok(())
+    }
+
+    async fn insert_batch(&self, urls: &[&str]) -> Result<(), CrawlerError> {
+        Ok(UrlTable::insert_batch(self, urls).await?)
+    }
+
+    async fn delete(&self, url: &str) -> Result<(), CrawlerError> {
+        let key = UrlKey::new(url);
+        // Forgotten first, so it's never taken as still there
+        if let Some(cache) = &self.cache {
+            cache.remove(key.as_str());
+        }
+        Ok(Table::delete(self, key.as_str()).await?)
+    }
+
+    async fn contains(&self, url: &str) -> Result<bool, CrawlerError> {
+        let key = UrlKey::new(url);
+        match self.cached(key.as_str()).await? {
+            Some(exists) => Ok(exists),
+            None => Ok(self.is_exist(key.as_str()).await?),
+        }
+    }
+
+    async fn count(&self) -> Result<u32, CrawlerError> {
+        Ok(Table::count(self).await?)
+    }
+
+    async fn oldest_added_at(&self) -> Result<Option<DateTime<FixedOffset>>, CrawlerError> {
+        let query = format!(
+            "SELECT created_at FROM {} ORDER BY created_at LIMIT 1",
+            self.name
+        );
+        match sqlx::query(&query).fetch_optional(&self.pool).await? {
+            Some(row) => Ok(Some(row.try_get("created_at")?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// The url of a queued or running row, with how the crawl came to it.
+fn queued_url(row: &SqliteRow) -> Result<QueuedUrl, sqlx::Error> {
+    Ok(QueuedUrl {
+        url: row.try_get::<&str, _>("id")?.into(),
+        depth: row.try_get("depth")?,
+        referrer: row.try_get("referrer")?,
+        priority: row.try_get("priority")?,
+    })
+}
+
+/// The paragraphs of a result or revision, stored one per line.
+fn split_paragraphs(paragraphs: Option<String>) -> Vec<String> {
+    paragraphs
+        .filter(|paragraphs| !paragraphs.is_empty())
+        .map(|paragraphs| paragraphs.split('\n').map(ToString::to_string).collect())
+        .unwrap_or_default()
+}
+
+pub struct WarnedTable {
+    name: String,
+    pool: SqlitePool,
+}
+
+#[async_trait::async_trait]
+impl Table for WarnedTable {
+    type Record<'a> = (&'a str, &'a str);
+
+    fn get_name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn get_pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    async fn create(&self) -> Result<(), sqlx::Error> {
+        if !utils::is_table_exists(self.get_pool(), &self.name).await? {
+            let query = format!(
+                "CREATE TABLE {} (
+                    id TEXT PRIMARY KEY,
+                    reason TEXT,
+                    created_at DATETIME,
+                    attempts INTEGER NOT NULL DEFAULT 0,
+                    next_retry_at DATETIME,
+                    url TEXT
+                 )",
+                &self.name
+            );
+            sqlx::query(query.as_str()).execute(self.get_pool()).await?;
+            return Ok(());
+        }
+        // Warned tables created before reasons, then retries, were recorded
+        for (column, definition) in [
+            ("reason", "TEXT"),
+            ("attempts", "INTEGER NOT NULL DEFAULT 0"),
+            ("next_retry_at", "DATETIME"),
+            ("url", "TEXT"),
+        ] {
+            utils::add_column(self.get_pool(), &self.name, column, definition).await?;
+        }
+        Ok(())
+    }
+
+    async fn insert<'a>(&self, (url, reason): Self::Record<'a>) -> Result<(), sqlx::Error> {
+        self.schedule(url, reason, 0, None).await
+    }
+}
+
+impl WarnedTable {
+    /// Insert or replace the warning of `url`, with the retries already made and when it's
+    /// due again.
+    pub async fn schedule(
+        &self,
+        url: &str,
+        reason: &str,
+        attempts: u32,
+        next_retry_at: Option<DateTime<FixedOffset>>,
+    ) -> Result<(), sqlx::Error> {
+        let timestamp = utils::get_now();
+        let mut tx = self.get_pool().begin().await?;
+        let query = format!(
+            "INSERT OR REPLACE INTO {} (id, reason, created_at, attempts, next_retry_at, url)
+             VALUES (?, ?, ?, ?, ?, ?)",
+            &self.name
+        );
+        let key = UrlKey::new(url);
+        sqlx::query(&query)
+            .bind(key.as_str())
+            .bind(reason)
+            .bind(timestamp)
+            .bind(attempts)
+            .bind(next_retry_at)
+            .bind(key.url())
+            .execute(&mut tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// The entries of `clause`, their url read from `url`, see `SqliteStorage::url_of`.
+    fn select(&self, url: &str, clause: &str) -> String {
+        format!(
+            "SELECT {} AS id, reason, created_at, attempts, next_retry_at FROM {} {}",
+            url, self.name, clause
+        )
+    }
+
+    fn entry(row: &SqliteRow) -> Result<WarnedEntry, sqlx::Error> {
+        Ok(WarnedEntry {
+            url: row.try_get("id")?,
+            reason: row.try_get("reason")?,
+            created_at: row.try_get("created_at")?,
+            attempts: row.try_get("attempts")?,
+            next_retry_at: row.try_get("next_retry_at")?,
+        })
+    }
+}
+
+/// When each stored article is due to be fetched again, see `revisit`.
+pub struct RevisitTable {
+    name: String,
+    pool: SqlitePool,
+}
+
+#[async_trait::async_trait]
+impl Table for RevisitTable {
+    type Record<'a> = (&'a str, DateTime<FixedOffset>);
+
+    fn get_name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn get_pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    async fn create(&self) -> Result<(), sqlx::Error> {
+        if !utils::is_table_exists(self.get_pool(), &self.name).await? {
+            let query = format!(
+                "CREATE TABLE {} (
+                    id TEXT PRIMARY KEY,
+                    due_at DATETIME,
+                    url TEXT
+                 )",
+                &self.name
+            );
+            sqlx::query(query.as_str()).execute(self.get_pool()).await?;
+            let query = format!("CREATE INDEX {0}_due_at ON {0} (due_at)", &self.name);
+            sqlx::query(query.as_str()).execute(self.get_pool()).await?;
+        }
+        Ok(())
+    }
+
+    async fn insert<'a>(&self, (url, due_at): Self::Record<'a>) -> Result<(), sqlx::Error> {
+        let query = format!(
+            "INSERT OR REPLACE INTO {} (id, due_at, url) VALUES (?, ?, ?)",
+            &self.name
+        );
+        let key = UrlKey::new(url);
+        sqlx::query(&query)
+            .bind(key.as_str())
+            .bind(due_at)
+            .bind(key.url())
+            .execute(self.get_pool())
+            .await?;
+        Ok(())
+    }
+}
+
+/// The versions of the articles found changed when they were revisited.
+pub struct RevisionTable {
+    name: String,
+    pool: SqlitePool,
+}
+
+#[async_trait::async_trait]
+impl Table for RevisionTable {
+    type Record<'a> = &'a Revision;
+
+    fn get_name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn get_pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    async fn create(&self) -> Result<(), sqlx::Error> {
+        if !utils::is_table_exists(self.get_pool(), &self.name).await? {
+            let query = format!(
+                "CREATE TABLE {} (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    url TEXT,
+                    title TEXT,
+                    published_date DATETIME,
+                    paragraphs TEXT,
+                    diff TEXT,
+                    created_at DATETIME
+                 )",
+                &self.name
+            );
+            sqlx::query(query.as_str()).execute(self.get_pool()).await?;
+            let query = format!("CREATE INDEX {0}_url ON {0} (url)", &self.name);
+            sqlx::query(query.as_str()).execute(self.get_pool()).await?;
+        }
+        Ok(())
+    }
+
+    async fn insert<'a>(&self, revision: Self::Record<'a>) -> Result<(), sqlx::Error> {
+        let query = format!(
+            "INSERT INTO {} (url, title, published_date, paragraphs, diff, created_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
+            &self.name
+        );
+        sqlx::query(&query)
+            .bind(&revision.url)
+            .bind(&revision.title)
+            .bind(revision.published_date)
+            .bind(revision.paragraphs.join("\n"))
+            .bind(&revision.diff)
+            .bind(revision.created_at)
+            .execute(self.get_pool())
+            .await?;
+        Ok(())
+    }
+}
+
+/// The backoff of the hosts that answered 429, see `backoff`.
+pub struct HostStateTable {
+    name: String,
+    pool: SqlitePool,
+}
+
+#[async_trait::async_trait]
+impl Table for HostStateTable {
+    type Record<'a> = &'a HostState;
+
+    fn get_name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn get_pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    async fn create(&self) -> Result<(), sqlx::Error> {
+        if !utils::is_table_exists(self.get_pool(), &self.name).await? {
+            let query = format!(
+                "CREATE TABLE {} (
+                    id TEXT PRIMARY KEY,
+                    multiplier INTEGER NOT NULL,
+                    resume_at DATETIME,
+                    updated_at DATETIME
+                 )",
+                &self.name
+            );
+            sqlx::query(query.as_str()).execute(self.get_pool()).await?;
+        }
+        Ok(())
+    }
+
+    async fn insert<'a>(&self, state: Self::Record<'a>) -> Result<(), sqlx::Error> {
+        let query = format!(
+            "INSERT OR REPLACE INTO {} (id, multiplier, resume_at, updated_at)
+             VALUES (?, ?, ?, ?)",
+            &self.name
+        );
+        sqlx::query(&query)
+            .bind(&state.host)
+            .bind(state.multiplier)
+            .bind(state.resume_at)
+            .bind(state.updated_at)
+            .execute(self.get_pool())
+            .await?;
+        Ok(())
+    }
+}
+
+/// The recrawl rate of the hub pages, see `recrawl`.
+pub struct HubTable {
+    name: String,
+    pool: SqlitePool,
+}
+
+#[async_trait::async_trait]
+impl Table for HubTable {
+    type Record<'a> = &'a HubState;
+
+    fn get_name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn get_pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    async fn create(&self) -> Result<(), sqlx::Error> {
+        if !utils::is_table_exists(self.get_pool(), &self.name).await? {
+            let query = format!(
+                "CREATE TABLE {} (
+                    id TEXT PRIMARY KEY,
+                    rate REAL NOT NULL,
+                    recrawls INTEGER NOT NULL,
+                    interval INTEGER NOT NULL,
+                    crawled_at DATETIME,
+                    url TEXT
+                 )",
+                &self.name
+            );
+            sqlx::query(query.as_str()).execute(self.get_pool()).await?;
+        }
+        Ok(())
+    }
+
+    async fn insert<'a>(&self, state: Self::Record<'a>) -> Result<(), sqlx::Error> {
+        let query = format!(
+            "INSERT OR REPLACE INTO {} (id, rate, recrawls, interval, crawled_at, url)
+             VALUES (?, ?, ?, ?, ?, ?)",
+            &self.name
+        );
+        let key = UrlKey::new(&state.url);
+        sqlx::query(&query)
+            .bind(key.as_str())
+            .bind(state.rate)
+            .bind(state.recrawls)
+            // Whole seconds, the intervals being minutes at least
+            .bind(state.interval.as_secs() as i64)
+            .bind(state.crawled_at)
+            .bind(key.url())
+            .execute(self.get_pool())
+            .await?;
+        Ok(())
+    }
+}
+
+impl HubTable {
+    fn entry(row: &SqliteRow) -> Result<HubState, sqlx::Error> {
+        Ok(HubState {
+            url: row.try_get("id")?,
+            rate: row.try_get("rate")?,
+            recrawls: row.try_get("recrawls")?,
+            interval: std::time::Duration::from_secs(row.try_get::<i64, _>("interval")? as u64),
+            crawled_at: row.try_get("crawled_at")?,
+        })
+    }
+}
+
+/// The links found on the hub pages when they were last crawled, see `recrawl::LinkSnapshot`.
+pub struct HubSnapshotTable {
+    name: String,
+    pool: SqlitePool,
+}
+
+#[async_trait::async_trait]
+impl Table for HubSnapshotTable {
+    type Record<'a> = (&'a str, &'a LinkSnapshot);
+
+    fn get_name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn get_pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    async fn create(&self) -> Result<(), sqlx::Error> {
+        if !utils::is_table_exists(self.get_pool(), &self.name).await? {
+            let query = format!(
+                "CREATE TABLE {} (
+                    id TEXT PRIMARY KEY,
+                    links BLOB NOT NULL,
+                    updated_at DATETIME,
+                    url TEXT
+                 )",
+                &self.name
+            );
+            sqlx::query(query.as_str()).execute(self.get_pool()).await?;
+        }
+        Ok(())
+    }
+
+    async fn insert<'a>(&self, (url, snapshot): Self::Record<'a>) -> Result<(), sqlx::Error> {
+        let query = format!(
+            "INSERT OR REPLACE INTO {} (id, links, updated_at, url) VALUES (?, ?, ?, ?)",
+            &self.name
+        );
+        let key = UrlKey::new(url);
+        sqlx::query(&query)
+            .bind(key.as_str())
+            .bind(snapshot.encode())
+            .bind(utils::get_now())
+            .bind(key.url())
+            .execute(self.get_pool())
+            .await?;
+        Ok(())
+    }
+}
+
+/// The zstd dictionaries the paragraphs of the results are compressed with, see
+/// `compression`.
+pub struct DictionaryTable {
+    name: String,
+    pool: SqlitePool,
+}
+
+#[async_trait::async_trait]
+impl Table for DictionaryTable {
+    type Record<'a> = &'a [u8];
+
+    fn get_name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn get_pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    async fn create(&self) -> Result<(), sqlx::Error> {
+        if !utils::is_table_exists(self.get_pool(), &self.name).await? {
+            let query = format!(
+                "CREATE TABLE {} (
+                    id INTEGER PRIMARY KEY,
+                    data BLOB NOT NULL,
+                    created_at DATETIME
+                 )",
+                &self.name
+            );
+            sqlx::query(query.as_str()).execute(self.get_pool()).await?;
+        }
+        Ok(())
+    }
+
+    async fn insert<'a>(&self, dictionary: Self::Record<'a>) -> Result<(), sqlx::Error> {
+        self.add(dictionary).await?;
+        Ok(())
+    }
+}
+
+impl DictionaryTable {
+    /// Store `dictionary`, returning its id.
+    pub async fn add(&self, dictionary: &[u8]) -> Result<i64, sqlx::Error> {
+        let query = format!(
+            "INSERT INTO {} (data, created_at) VALUES (?, ?)",
+            &self.name
+        );
+        Ok(sqlx::query(&query)
+            .bind(dictionary)
+            .bind(utils::get_now())
+            .execute(self.get_pool())
+            .await?
+            .last_insert_rowid())
+    }
+
+    /// Every dictionary by id, none in a database opened read-only before it had any.
+    pub async fn all(&self) -> Result<HashMap<i64, Vec<u8>>, sqlx::Error> {
+        if !utils::is_table_exists(self.get_pool(), &self.name).await? {
+            return Ok(HashMap::new());
+        }
+        let query = format!("SELECT id, data FROM {}", &self.name);
+        let mut dictionaries = HashMap::new();
+        for row in sqlx::query(&query).fetch_all(self.get_pool()).await? {
+            dictionaries.insert(row.try_get("id")?, row.try_get("data")?);
+        }
+        Ok(dictionaries)
+    }
+}
+
+/// The results table of a site, holding its article type.
+#[async_trait::async_trait]
+pub trait ResultTable: Table + Send + Sync {
+    type Article: crate::Article + Send;
+
+    fn new(name: String, pool: SqlitePool) -> Self;
+    fn record(url: &str, article: Self::Article) -> Self::Record<'_>;
+
+    /// Key the rows of the other tables of the site kept for the article `from` by `to`,
+    /// once its result was keyed again, see `UrlKey`.
+    async fn rekey(&self, _from: &str, _to: &str) -> Result<(), sqlx::Error> {
+        Ok(())
+    }
+
+    /// Delete the rows of the other tables of the site kept for the article `id`, before
+    /// it's stored again.
+    async fn forget(&self, _id: &str) -> Result<(), sqlx::Error> {
+        Ok(())
+    }
+
+    /// Write the paragraphs of the new articles, and read them back, with `codec`. Only the
+    /// tables with a `compression` column store them compressed, see
+    /// `SqliteStorage::with_compression`.
+    fn set_codec(&mut self, _codec: Arc<ParagraphCodec>) {}
+}
+
+/// A `Storage` in `<name>.db` with one table per url bucket plus the site's results table.
+pub struct SqliteStorage<T> {
+    pub name: String,
+    pub queued: UrlTable,
+    pub visited: UrlTable,
+    pub warned: WarnedTable,
+    pub results: T,
+    pub running: UrlTable,
+    pub revisits: RevisitTable,
+    pub revisions: RevisionTable,
+    pub host_states: HostStateTable,
+    pub hubs: HubTable,
+    pub hub_snapshots: HubSnapshotTable,
+    pub dictionaries: DictionaryTable,
+    codec: Arc<ParagraphCodec>,
+    pool: SqlitePool,
+}
+
+impl<T: ResultTable> SqliteStorage<T> {
+    pub async fn new(name: &str) -> Result<Self, CrawlerError> {
+        Self::new_at(name, format!("{}.db", name)).await
+    }
+
+    /// Like `new`, with the tables of `name` in the database at `path`.
+    pub async fn new_at(name: &str, path: impl AsRef<Path>) -> Result<Self, CrawlerError> {
+        let opt = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true);
+        let pool = SqlitePool::connect_with(opt).await?;
+        let mut p = Self::with_pool(name, pool);
+
+        for table in &[&p.queued, &p.running, &p.visited] {
+            if !utils::is_table_exists(&p.pool, &table.name).await? {
+                tracing::debug!("Crate table {}", table.name);
+                table.create().await?;
+            } else {
+                tracing::debug!("Use table {}", table.name);
+            }
+        }
+        // The claims of the workers sharing the database, see `queued_claim_n`
+        for (column, definition) in [("worker_id", "TEXT"), ("heartbeat_at", "DATETIME")] {
+            utils::add_column(&p.pool, &p.running.name, column, definition).await?;
+        }
+        // How the crawl came to a url, kept while it's queued and running
+        for table in [&p.queued, &p.running] {
+            for (column, definition) in [
+                ("referrer", "TEXT"),
+                ("depth", "INTEGER NOT NULL DEFAULT 0"),
+                ("priority", "INTEGER NOT NULL DEFAULT 0"),
+            ] {
+                utils::add_column(&p.pool, &table.name, column, definition).await?;
+            }
+        }
+        // The order `queued_claim_n` takes the queue in
+        let query = format!(
+            "CREATE INDEX IF NOT EXISTS {0}_priority ON {0} (priority DESC, created_at)",
+            p.queued.name
+        );
+        sqlx::query(&query).execute(&p.pool).await?;
+        // Always run create for warned and results, they also add new columns to old tables
+        p.warned.create().await?;
+        p.results.create().await?;
+        p.revisits.create().await?;
+        p.revisions.create().await?;
+        p.host_states.create().await?;
+        p.hubs.create().await?;
+        p.hub_snapshots.create().await?;
+        // Written by `results_insert_with_referrer`, whatever the site's result table
+        for column in ["referrer", "url"] {
+            utils::add_column(&p.pool, p.results.get_name(), column, "TEXT").await?;
+        }
+        // Written by `results_insert_metadata_only`, the results stored before were full
+        utils::add_column(
+            &p.pool,
+            p.results.get_name(),
+            "extraction_status",
+            "TEXT NOT NULL DEFAULT 'full'",
+        )
+        .await?;
+        // The url of the hashed keys, see `UrlKey`
+        for table in [&p.queued, &p.running, &p.visited] {
+            utils::add_column(&p.pool, &table.name, "url", "TEXT").await?;
+        }
+        for table in [
+            &p.queued.name,
+            &p.running.name,
+            &p.visited.name,
+            &p.warned.name,
+        ] {
+            rekey(&p.pool, table).await?;
+        }
+        for (from, to) in rekey(&p.pool, p.results.get_name()).await? {
+            p.results.rekey(&from, &to).await?;
+        }
+        p.dictionaries.create().await?;
+        p.load_dictionaries().await?;
+
+        Ok(p)
+    }
+
+    /// Open an existing database without creating or migrating any table, so it can be
+    /// inspected while another process is crawling into it.
+    pub async fn open_read_only(name: &str) -> Result<Self, CrawlerError> {
+        Self::open_read_only_at(name, format!("{}.db", name)).await
+    }
+
+    pub async fn open_read_only_at(
+        name: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<Self, CrawlerError> {
+        let opt = SqliteConnectOptions::new().filename(path).read_only(true);
+        let pool = SqlitePool::connect_with(opt).await?;
+        let mut p = Self::with_pool(name, pool);
+        p.load_dictionaries().await?;
+        Ok(p)
+    }
+
+    /// Store the paragraphs of the new results compressed, see `compression`, those stored
+    /// before being left as they are until `compress_existing`. The first time, a dictionary
+    /// is trained on a sample of the results, the paragraphs being compressed without one
+    /// while there are too few of them. Unsupported by the results tables without a
+    /// `compression` column.
+    pub async fn with_compression(mut self) -> Result<Self, CrawlerError> {
+        if !utils::is_column_exists(&self.pool, self.results.get_name(), "compression").await? {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "{} can't store compressed paragraphs",
+                    self.results.get_name()
+                ),
+            )
+            .into());
+        }
+        if self.codec.dictionary().is_none() {
+            self.train_dictionary().await?;
+        }
+        self.set_codec(ParagraphCodec::clone(&self.codec).compressing());
+        Ok(self)
+    }
+
+    /// Compress the paragraphs of the results stored as text, `batch` of them per transaction,
+    /// returning how many were. Needs `with_compression`.
+    pub async fn compress_existing(&self, batch: u32) -> Result<u64, CrawlerError> {
+        if !self.codec.is_compressing() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "compression isn't enabled, see `with_compression`",
+            )
+            .into());
+        }
+        let select = format!(
+            "SELECT rowid, paragraphs FROM {}
+             WHERE rowid > ? AND compression IS NULL AND paragraphs IS NOT NULL
+                AND paragraphs != ''
+             ORDER BY rowid LIMIT ?",
+            self.results.get_name()
+        );
+        let update = format!(
+            "UPDATE {} SET paragraphs = ?, compression = ? WHERE rowid = ?",
+            self.results.get_name()
+        );
+        let (mut cursor, mut compressed) = (0, 0);
+        loop {
+            let rows = sqlx::query(&select)
+                .bind(cursor)
+                .bind(batch.max(1))
+                .fetch_all(&self.pool)
+                .await?;
+            if rows.is_empty() {
+                return Ok(compressed);
+            }
+            let mut tx = self.pool.begin().await?;
+            for row in rows {
+                cursor = row.try_get("rowid")?;
+                let stored = self.codec.encode(row.try_get("paragraphs")?)?;
+                stored
+                    .bind(sqlx::query(&update))
+                    .bind(cursor)
+                    .execute(&mut tx)
+                    .await?;
+                compressed += 1;
+            }
+            tx.commit().await?;
+            tracing::info!("Compressed {} results", compressed);
+        }
+    }
+
+    /// Train a dictionary on a sample of the paragraphs of the results, see
+    /// `compression::train`, and read the rows with it.
+    async fn train_dictionary(&mut self) -> Result<(), CrawlerError> {
+        let query = format!(
+            "SELECT paragraphs, compression FROM {}
+             WHERE paragraphs IS NOT NULL AND paragraphs != ''
+             ORDER BY random() LIMIT ?",
+            self.results.get_name()
+        );
+        let mut samples = vec![];
+        for row in sqlx::query(&query)
+            .bind(compression::DICTIONARY_SAMPLES)
+            .fetch_all(&self.pool)
+            .await?
+        {
+            samples.extend(self.codec.read(&row)?);
+        }
+        if let Some(dictionary) = compression::train(&samples) {
+            let id = self.dictionaries.add(&dictionary).await?;
+            tracing::info!("Trained the dictionary {} on {} results", id, samples.len());
+            self.load_dictionaries().await?;
+        }
+        Ok(())
+    }
+
+    /// Read the results with every stored dictionary, writing their paragraphs as text.
+    async fn load_dictionaries(&mut self) -> Result<(), CrawlerError> {
+        let dictionaries = self.dictionaries.all().await?;
+        self.set_codec(ParagraphCodec::new(dictionaries));
+        Ok(())
+    }
+
+    fn set_codec(&mut self, codec: ParagraphCodec) {
+        let codec = Arc::new(codec);
+        self.results.set_codec(Arc::clone(&codec));
+        self.codec = codec;
+    }
+
+    /// Keep the visited urls in a `VisitedCache`, sparing most of the lookups of the links
+    /// already visited: its filter is sized for twice the urls visited so far, all read into
+    /// it, and it remembers the last `hits` urls known to be visited, none being no cache.
+    pub async fn with_visited_cache(mut self, hits: usize) -> Result<Self, CrawlerError> {
+        if hits == 0 {
+            return Ok(self);
+        }
+        let visited = UrlBucket::count(&self.visited).await? as usize;
+        let cache = VisitedCache::new(visited.saturating_mul(2), hits);
+        let query = format!("SELECT id FROM {}", self.visited.name);
+        let mut ids = sqlx::query_scalar::<_, String>(&query).fetch(&self.pool);
+        while let Some(id) = ids.try_next().await? {
+            cache.preload(&id);
+        }
+        drop(ids);
+        self.visited.cache = Some(cache);
+        Ok(self)
+    }
+
+    /// How the visited urls were looked up, `None` without `with_visited_cache`.
+    pub fn visited_cache_stats(&self) -> Option<VisitedCacheStats> {
+        self.visited.cache.as_ref().map(VisitedCache::stats)
+    }
+
+    /// The url of the rows of `table`, as an expression of its columns. A database opened
+    /// read-only may predate the `url` column of the hashed keys.
+    async fn url_of(&self, table: &str) -> Result<&'static str, CrawlerError> {
+        Ok(
+            if utils::is_column_exists(&self.pool, table, "url").await? {
+                "COALESCE(url, id)"
+            } else {
+                "id"
+            },
+        )
+    }
+
+    /// Queue again the urls of `rows`, deleted from running within `tx` and returning their
+    /// `QueuedUrl` columns.
+    async fn requeue_running(
+        &self,
+        mut tx: Transaction<'_, Sqlite>,
+        rows: Vec<SqliteRow>,
+    ) -> Result<Vec<String>, CrawlerError> {
+        let query = format!(
+            "INSERT OR IGNORE INTO {} (id, created_at, referrer, depth, priority, url)
+             VALUES (?, ?, ?, ?, ?, ?)",
+            self.queued.get_name()
+        );
+        let now = utils::get_now();
+        let mut urls = vec![];
+        for row in rows {
+            let queued = queued_url(&row)?;
+            let key = UrlKey::new(&queued.url);
+            sqlx::query(&query)
+                .bind(key.as_str())
+                .bind(now)
+                .bind(&queued.referrer)
+                .bind(queued.depth)
+                .bind(queued.priority)
+                .bind(key.url())
+                .execute(&mut tx)
+                .await?;
+            urls.push(queued.url.to_string());
+        }
+        tx.commit().await?;
+        Ok(urls)
+    }
+
+    /// Queue `queued` unless its url is already queued, running or visited.
+    async fn insert_unseen(&self, queued: QueuedUrl) -> Result<(), CrawlerError> {
+        let key = UrlKey::new(&queued.url);
+        if self.visited.cached(key.as_str()).await? == Some(true) {
+            return Ok(());
+        }
+        // A single statement, so a url moving from running to visited can't slip between checks
+        let query = format!(
+            "INSERT OR IGNORE INTO {queued} (id, created_at, referrer, depth, priority, url)
+             SELECT ?1, ?2, ?3, ?4, ?5, ?6
+             WHERE NOT EXISTS (SELECT 1 FROM {visited} WHERE id = ?1)
+               AND NOT EXISTS (SELECT 1 FROM {running} WHERE id = ?1)",
+            queued = self.queued.get_name(),
+            visited = self.visited.get_name(),
+            running = self.running.get_name(),
+        );
+        sqlx::query(&query)
+            .bind(key.as_str())
+            .bind(utils::get_now())
+            .bind(&queued.referrer)
+            .bind(queued.depth)
+            .bind(queued.priority)
+            .bind(key.url())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Insert a url into running for a worker unless it's already running or visited, bound
+    /// to its key, the time, the worker, its referrer, depth, priority and url.
+    fn claim_query(&self) -> String {
+        format!(
+            "INSERT OR IGNORE INTO {running}
+                (id, created_at, worker_id, heartbeat_at, referrer, depth, priority, url)
+             SELECT ?1, ?2, ?3, ?2, ?4, ?5, ?6, ?7
+             WHERE NOT EXISTS (SELECT 1 FROM {visited} WHERE id = ?1)",
+            running = self.running.get_name(),
+            visited = self.visited.get_name(),
+        )
+    }
+
+    fn with_pool(name: &str, pool: SqlitePool) -> Self {
+        SqliteStorage {
+            name: name.to_string(),
+            queued: UrlTable::new(format!("{}_queued", name), pool.clone()),
+            running: UrlTable::new(format!("{}_running", name), pool.clone()),
+            visited: UrlTable::new(format!("{}_visited", name), pool.clone()),
+            warned: WarnedTable {
+                name: format!("{}_warned", name),
+                pool: pool.clone(),
+            },
+            results: T::new(format!("{}_results", name), pool.clone()),
+            revisits: RevisitTable {
+                name: format!("{}_revisits", name),
+                pool: pool.clone(),
+            },
+            revisions: RevisionTable {
+                name: format!("{}_revisions", name),
+                pool: pool.clone(),
+            },
+            host_states: HostStateTable {
+                name: format!("{}_host_state", name),
+                pool: pool.clone(),
+            },
+            hubs: HubTable {
+                name: format!("{}_hubs", name),
+                pool: pool.clone(),
+            },
+            hub_snapshots: HubSnapshotTable {
+                name: format!("{}_hub_snapshots", name),
+                pool: pool.clone(),
+            },
+            dictionaries: DictionaryTable {
+                name: format!("{}_dictionaries", name),
+                pool: pool.clone(),
+            },
+            codec: Default::default(),
+            pool,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: ResultTable> Storage for SqliteStorage<T> {
+    type Record = T::Article;
+
+    fn bucket(&self, bucket: Bucket) -> &dyn UrlBucket {
+        match bucket {
+            Bucket::Queued => &self.queued,
+            Bucket::Running => &self.running,
+            Bucket::Visited => &self.visited,
+        }
+    }
+
+    async fn queued_insert_with_meta<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        referrer: Option<&str>,
+        depth: u32,
+    ) -> Result<(), CrawlerError> {
+        self.insert_unseen(QueuedUrl {
+            referrer: referrer.map(ToString::to_string),
+            depth,
+            ..QueuedUrl::new(item.as_ref())
+        })
+        .await
+    }
+
+    async fn queued_insert_prioritized<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        referrer: Option<&str>,
+        depth: u32,
+        priority: i32,
+    ) -> Result<(), CrawlerError> {
+        self.insert_unseen(QueuedUrl {
+            referrer: referrer.map(ToString::to_string),
+            depth,
+            priority,
+            ..QueuedUrl::new(item.as_ref())
+        })
+        .await
+    }
+
+    async fn queued_insert_with_priority<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        priority: i32,
+    ) -> Result<(), CrawlerError> {
+        self.insert_unseen(QueuedUrl {
+            priority,
+            ..QueuedUrl::new(item.as_ref())
+        })
+        .await
+    }
+
+    async fn queued_claim_n(
+        &self,
+        worker_id: &str,
+        n: u32,
+    ) -> Result<Vec<QueuedUrl>, CrawlerError> {
+        let now = utils::get_now();
+        // Deleting first takes the write lock, so another process can't claim the same urls
+        let mut tx = self.pool.begin().await?;
+        let query = format!(
+            "DELETE FROM {queued} WHERE id IN
+                (SELECT id FROM {queued} ORDER BY priority DESC, created_at LIMIT ?)
+             RETURNING COALESCE(url, id) AS id, created_at, referrer, depth, priority",
+            queued = self.queued.get_name()
+        );
+        let mut taken = vec![];
+        for row in sqlx::query(&query).bind(n).fetch_all(&mut tx).await? {
+            let created_at: DateTime<FixedOffset> = row.try_get("created_at")?;
+            taken.push((created_at, queued_url(&row)?));
+        }
+        taken.sort_by_key(|(created_at, queued)| (Reverse(queued.priority), *created_at));
+
+        let query = self.claim_query();
+        let mut claimed = vec![];
+        for (_, queued) in taken {
+            let key = UrlKey::new(&queued.url);
+            let inserted = sqlx::query(&query)
+                .bind(key.as_str())
+                .bind(now)
+                .bind(worker_id)
+                .bind(&queued.referrer)
+                .bind(queued.depth)
+                .bind(queued.priority)
+                .bind(key.url())
+                .execute(&mut tx)
+                .await?
+                .rows_affected();
+            // Already running or visited, dropped from the queue like the dispatcher always did
+            if inserted == 1 {
+                claimed.push(queued);
+            }
+        }
+        tx.commit().await?;
+        Ok(claimed)
+    }
+
+    async fn running_claim<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        worker_id: &str,
+    ) -> Result<bool, CrawlerError> {
+        let key = UrlKey::new(item.as_ref());
+        let mut tx = self.pool.begin().await?;
+        // Running with how the crawl came to it, a seed when it wasn't queued
+        let query = format!(
+            "DELETE FROM {} WHERE id = ?
+             RETURNING COALESCE(url, id) AS id, referrer, depth, priority",
+            self.queued.get_name()
+        );
+        let queued = match sqlx::query(&query)
+            .bind(key.as_str())
+            .fetch_optional(&mut tx)
+            .await?
+        {
+            Some(row) => queued_url(&row)?,
+            None => QueuedUrl::new(item.as_ref()),
+        };
+        let inserted = sqlx::query(&self.claim_query())
+            .bind(key.as_str())
+            .bind(utils::get_now())
+            .bind(worker_id)
+            .bind(&queued.referrer)
+            .bind(queued.depth)
+            .bind(queued.priority)
+            .bind(key.url())
+            .execute(&mut tx)
+            .await?
+            .rows_affected();
+        tx.commit().await?;
+        Ok(inserted == 1)
+    }
+
+    async fn running_touch(
+        &self,
+        worker_id: &str,
+        now: DateTime<FixedOffset>,
+    ) -> Result<(), CrawlerError> {
+        let query = format!(
+            "UPDATE {} SET heartbeat_at = ? WHERE worker_id = ?",
+            self.running.get_name()
+        );
+        sqlx::query(&query)
+            .bind(now)
+            .bind(worker_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn running_reap(
+        &self,
+        stale_before: DateTime<FixedOffset>,
+    ) -> Result<Vec<String>, CrawlerError> {
+        // Urls inserted without a claim are as old as their row
+        let query = format!(
+            "DELETE FROM {} WHERE COALESCE(heartbeat_at, created_at) < ?
+             RETURNING COALESCE(url, id) AS id, referrer, depth, priority",
+            self.running.get_name()
+        );
+        let mut tx = self.pool.begin().await?;
+        let rows = sqlx::query(&query)
+            .bind(stale_before)
+            .fetch_all(&mut tx)
+            .await?;
+        self.requeue_running(tx, rows).await
+    }
+
+    async fn running_release(&self, worker_id: &str) -> Result<Vec<String>, CrawlerError> {
+        let query = format!(
+            "DELETE FROM {} WHERE worker_id = ?
+             RETURNING COALESCE(url, id) AS id, referrer, depth, priority",
+            self.running.get_name()
+        );
+        let mut tx = self.pool.begin().await?;
+        let rows = sqlx::query(&query)
+            .bind(worker_id)
+            .fetch_all(&mut tx)
+            .await?;
+        self.requeue_running(tx, rows).await
+    }
+
+    async fn running_requeue<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        let query = format!(
+            "DELETE FROM {} WHERE id = ?
+             RETURNING COALESCE(url, id) AS id, referrer, depth, priority",
+            self.running.get_name()
+        );
+        let mut tx = self.pool.begin().await?;
+        let rows = sqlx::query(&query)
+            .bind(UrlKey::new(item.as_ref()).as_str())
+            .fetch_all(&mut tx)
+            .await?;
+        if rows.is_empty() {
+            tx.commit().await?;
+            return self.insert_unseen(QueuedUrl::new(item.as_ref())).await;
+        }
+        self.requeue_running(tx, rows).await?;
+        Ok(())
+    }
+
+    async fn results_count(&self) -> Result<u32, CrawlerError> {
+        Ok(Table::count(&self.results).await?)
+    }
+
+    async fn results_recent(&self, n: u32) -> Result<Vec<ResultSummary>, CrawlerError> {
+        let mut recent: Vec<ResultSummary> = vec![];
+        // A database opened read-only may predate the column
+        let referrer =
+            if utils::is_column_exists(&self.pool, self.results.get_name(), "referrer").await? {
+                "referrer"
+            } else {
+                "NULL AS referrer"
+            };
+        let query = format!(
+            "SELECT {} AS id, title, published_date, created_at, {} FROM {}
+             ORDER BY created_at DESC LIMIT ?",
+            self.url_of(self.results.get_name()).await?,
+            referrer,
+            self.results.get_name()
+        );
+        for row in sqlx::query(&query).bind(n).fetch_all(&self.pool).await? {
+            recent.push(ResultSummary {
+                url: row.try_get("id")?,
+                title: row.try_get("title")?,
+                published_date: row.try_get("published_date")?,
+                created_at: row.try_get("created_at")?,
+                referrer: row.try_get("referrer")?,
+            });
+        }
+        Ok(recent)
+    }
+
+    async fn results_get(&self, url: &str) -> Result<Option<ResultEntry>, CrawlerError> {
+        let referrer =
+            if utils::is_column_exists(&self.pool, self.results.get_name(), "referrer").await? {
+                "referrer"
+            } else {
+                "NULL AS referrer"
+            };
+        let compression =
+            if utils::is_column_exists(&self.pool, self.results.get_name(), "compression").await? {
+                "compression"
+            } else {
+                "NULL AS compression"
+            };
+        let query = format!(
+            "SELECT {} AS id, title, published_date, created_at, {}, paragraphs, {} FROM {}
+             WHERE id = ?",
+            self.url_of(self.results.get_name()).await?,
+            referrer,
+            compression,
+            self.results.get_name()
+        );
+        let row = sqlx::query(&query)
+            .bind(UrlKey::new(url.trim()).as_str())
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(match row {
+            Some(row) => Some(ResultEntry {
+                url: row.try_get("id")?,
+                title: row.try_get("title")?,
+                published_date: row.try_get("published_date")?,
+                created_at: row.try_get("created_at")?,
+                referrer: row.try_get("referrer")?,
+                paragraphs: split_paragraphs(self.codec.read(&row)?),
+            }),
+            None => None,
+        })
+    }
+
+    async fn results_insert_with_referrer<I: AsRef<str> + Send>(
+        &self,
+        (url, record): (I, Self::Record),
+        referrer: Option<&str>,
+    ) -> Result<(), CrawlerError> {
+        let key = UrlKey::new(url.as_ref().trim());
+        // The first record of a url is kept, and so is its referrer, unless only its metadata
+        // was stored
+        let mut referrer = referrer.map(ToString::to_string);
+        let stored = match self.results_status(url.as_ref()).await? {
+            Some(ExtractionStatus::MetadataOnly) => {
+                let query = format!(
+                    "SELECT referrer FROM {} WHERE id = ?",
+                    self.results.get_name()
+                );
+                let first: Option<String> = sqlx::query_scalar(&query)
+                    .bind(key.as_str())
+                    .fetch_one(&self.pool)
+                    .await?;
+                referrer = first.or(referrer);
+                self.results.delete(key.as_str()).await?;
+                self.results.forget(key.as_str()).await?;
+                false
+            }
+            status => status.is_some(),
+        };
+        self.results.insert(T::record(key.as_str(), record)).await?;
+        if !stored && (referrer.is_some() || key.url().is_some()) {
+            let query = format!(
+                "UPDATE {} SET referrer = ?, url = ? WHERE id = ?",
+                self.results.get_name()
+            );
+            sqlx::query(&query)
+                .bind(referrer)
+                .bind(key.url())
+                .bind(key.as_str())
+                .execute(&self.pool)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn results_insert_metadata_only<I: AsRef<str> + Send>(
+        &self,
+        (url, record): (I, Self::Record),
+        referrer: Option<&str>,
+    ) -> Result<(), CrawlerError> {
+        let url = url.as_ref().trim();
+        if self.results_status(url).await?.is_some() {
+            return Ok(());
+        }
+        self.results_insert_with_referrer((url, record), referrer)
+            .await?;
+        let query = format!(
+            "UPDATE {} SET extraction_status = ? WHERE id = ?",
+            self.results.get_name()
+        );
+        sqlx::query(&query)
+            .bind(ExtractionStatus::MetadataOnly.as_str())
+            .bind(UrlKey::new(url).as_str())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn results_status(&self, url: &str) -> Result<Option<ExtractionStatus>, CrawlerError> {
+        // A database opened read-only may predate the column, its results being full
+        let status =
+            if utils::is_column_exists(&self.pool, self.results.get_name(), "extraction_status")
+                .await?
+            {
+                "extraction_status"
+            } else {
+                "'full'"
+            };
+        let query = format!(
+            "SELECT {} FROM {} WHERE id = ?",
+            status,
+            self.results.get_name()
+        );
+        let status: Option<String> = sqlx::query_scalar(&query)
+            .bind(UrlKey::new(url.trim()).as_str())
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(status.map(|status| ExtractionStatus::parse(&status)))
+    }
+
+    async fn results_revise<I: AsRef<str> + Send>(
+        &self,
+        (url, record): (I, Self::Record),
+        diff: &str,
+    ) -> Result<(), CrawlerError> {
+        let url = url.as_ref().trim();
+        let now = utils::get_now();
+        let entry = ResultEntry::new(url, &record);
+        self.revisions
+            .insert(&Revision {
+                url: entry.url,
+                title: entry.title,
+                published_date: entry.published_date,
+                paragraphs: entry.paragraphs,
+                diff: diff.to_string(),
+                created_at: Some(now),
+            })
+            .await?;
+
+        // Stored again, the referrer it was first found on kept
+        let key = UrlKey::new(url);
+        let query = format!(
+            "SELECT referrer FROM {} WHERE id = ?",
+            self.results.get_name()
+        );
+        let referrer: Option<String> = sqlx::query_scalar(&query)
+            .bind(key.as_str())
+            .fetch_optional(&self.pool)
+            .await?
+            .flatten();
+        self.results.delete(key.as_str()).await?;
+        self.results.forget(key.as_str()).await?;
+        self.results_insert_with_referrer((url, record), referrer.as_deref())
+            .await
+    }
+
+    async fn revisions_get(&self, url: &str) -> Result<Vec<Revision>, CrawlerError> {
+        // A database opened read-only may predate the table
+        if !utils::is_table_exists(&self.pool, self.revisions.get_name()).await? {
+            return Ok(vec![]);
+        }
+        let query = format!(
+            "SELECT url, title, published_date, paragraphs, diff, created_at FROM {}
+             WHERE url = ? ORDER BY id",
+            self.revisions.get_name()
+        );
+        let mut revisions = vec![];
+        for row in sqlx::query(&query)
+            .bind(url.trim())
+            .fetch_all(&self.pool)
+            .await?
+        {
+            revisions.push(Revision {
+                url: row.try_get("url")?,
+                title: row.try_get("title")?,
+                published_date: row.try_get("published_date")?,
+                paragraphs: split_paragraphs(row.try_get("paragraphs")?),
+                diff: row.try_get("diff")?,
+                created_at: row.try_get("created_at")?,
+            });
+        }
+        Ok(revisions)
+    }
+
+    async fn revisit_schedule(
+        &self,
+        url: &str,
+        due_at: DateTime<FixedOffset>,
+    ) -> Result<(), CrawlerError> {
+        Ok(self.revisits.insert((url, due_at)).await?)
+    }
+
+    async fn revisit_due(
+        &self,
+        now: DateTime<FixedOffset>,
+        n: u32,
+    ) -> Result<Vec<String>, CrawlerError> {
+        let query = format!(
+            "DELETE FROM {revisits} WHERE id IN
+                (SELECT id FROM {revisits} WHERE due_at <= ? ORDER BY due_at LIMIT ?)
+             RETURNING COALESCE(url, id) AS id, due_at",
+            revisits = self.revisits.get_name()
+        );
+        let mut due = vec![];
+        for row in sqlx::query(&query)
+            .bind(now)
+            .bind(n)
+            .fetch_all(&self.pool)
+            .await?
+        {
+            let due_at: DateTime<FixedOffset> = row.try_get("due_at")?;
+            let url: String = row.try_get("id")?;
+            due.push((due_at, url));
+        }
+        due.sort();
+        Ok(due.into_iter().map(|(_, url)| url).collect())
+    }
+
+    async fn warned_insert<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        reason: &str,
+    ) -> Result<(), CrawlerError> {
+        let item = item.as_ref();
+        Ok(self.warned.insert((item, reason)).await?)
+    }
+
+    async fn warned_count(&self) -> Result<u32, CrawlerError> {
+        Ok(self.warned.count().await?)
+    }
+
+    async fn warned_recent(&self, n: u32) -> Result<Vec<WarnedEntry>, CrawlerError> {
+        let url = self.url_of(&self.warned.name).await?;
+        let query = self.warned.select(url, "ORDER BY created_at DESC LIMIT ?");
+        let rows = sqlx::query(&query).bind(n).fetch_all(&self.pool).await?;
+        Ok(rows
+            .iter()
+            .map(WarnedTable::entry)
+            .collect::<Result<_, _>>()?)
+    }
+
+    async fn warned_get(&self, item: &str) -> Result<Option<WarnedEntry>, CrawlerError> {
+        let url = self.url_of(&self.warned.name).await?;
+        let query = self.warned.select(url, "WHERE id = ?");
+        let row = sqlx::query(&query)
+            .bind(UrlKey::new(item).as_str())
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.as_ref().map(WarnedTable::entry).transpose()?)
+    }
+
+    async fn warned_delete(&self, item: &str) -> Result<(), CrawlerError> {
+        Ok(Table::delete(&self.warned, UrlKey::new(item).as_str()).await?)
+    }
+
+    async fn warned_schedule(
+        &self,
+        item: &str,
+        reason: &str,
+        attempts: u32,
+        next_retry_at: Option<DateTime<FixedOffset>>,
+    ) -> Result<(), CrawlerError> {
+        Ok(self
+            .warned
+            .schedule(item, reason, attempts, next_retry_at)
+            .await?)
+    }
+
+    async fn warned_due(
+        &self,
+        now: DateTime<FixedOffset>,
+        n: u32,
+    ) -> Result<Vec<WarnedEntry>, CrawlerError> {
+        let url = self.url_of(&self.warned.name).await?;
+        let query = self.warned.select(
+            url,
+            "WHERE next_retry_at <= ? ORDER BY next_retry_at LIMIT ?",
+        );
+        let rows = sqlx::query(&query)
+            .bind(now)
+            .bind(n)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows
+            .iter()
+            .map(WarnedTable::entry)
+            .collect::<Result<_, _>>()?)
+    }
+
+    async fn host_state_get(&self, host: &str) -> Result<Option<HostState>, CrawlerError> {
+        let query = format!(
+            "SELECT id, multiplier, resume_at, updated_at FROM {} WHERE id = ?",
+            self.host_states.get_name()
+        );
+        let row = sqlx::query(&query)
+            .bind(host)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(match row {
+            Some(row) => Some(HostState {
+                host: row.try_get("id")?,
+                multiplier: row.try_get("multiplier")?,
+                resume_at: row.try_get("resume_at")?,
+                updated_at: row.try_get("updated_at")?,
+            }),
+            None => None,
+        })
+    }
+
+    async fn host_state_put(&self, state: &HostState) -> Result<(), CrawlerError> {
+        Ok(self.host_states.insert(state).await?)
+    }
+
+    async fn hub_get(&self, url: &str) -> Result<Option<HubState>, CrawlerError> {
+        let query = format!(
+            "SELECT COALESCE(url, id) AS id, rate, recrawls, interval, crawled_at FROM {}
+             WHERE id = ?",
+            self.hubs.get_name()
+        );
+        let row = sqlx::query(&query)
+            .bind(UrlKey::new(url).as_str())
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.as_ref().map(HubTable::entry).transpose()?)
+    }
+
+    async fn hub_put(&self, state: &HubState) -> Result<(), CrawlerError> {
+        Ok(self.hubs.insert(state).await?)
+    }
+
+    async fn hubs_get(&self) -> Result<Vec<HubState>, CrawlerError> {
+        let query = format!(
+            "SELECT COALESCE(url, id) AS id, rate, recrawls, interval, crawled_at FROM {}
+             ORDER BY interval, COALESCE(url, id)",
+            self.hubs.get_name()
+        );
+        let rows = sqlx::query(&query).fetch_all(&self.pool).await?;
+        Ok(rows.iter().map(HubTable::entry).collect::<Result<_, _>>()?)
+    }
+
+    async fn hub_snapshot_get(&self, url: &str) -> Result<Option<LinkSnapshot>, CrawlerError> {
+        let query = format!(
+            "SELECT links FROM {} WHERE id = ?",
+            self.hub_snapshots.get_name()
+        );
+        let links: Option<Vec<u8>> = sqlx::query_scalar(&query)
+            .bind(UrlKey::new(url).as_str())
+            .fetch_optional(&self.pool)
+            .await?;
+        // Unreadable, the hub's links are all taken as new once more
+        Ok(links.and_then(|links| LinkSnapshot::decode(&links)))
+    }
+
+    async fn hub_snapshot_put(
+        &self,
+        url: &str,
+        snapshot: &LinkSnapshot,
+    ) -> Result<(), CrawlerError> {
+        Ok(self.hub_snapshots.insert((url, snapshot)).await?)
+    }
+}
@@ -0,0 +1,476 @@
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+/// Hosts whose latencies are kept apart, the fetches from the hosts after them are counted
+/// together under `OTHER_HOSTS`.
+pub const MAX_LATENCY_HOSTS: usize = 32;
+pub const OTHER_HOSTS: &str = "other";
+
+/// The slowest pages to crawl kept for the `CrawlReport`.
+pub const SLOWEST_PARSES: usize = 10;
+
+/// Buckets of a `Histogram`: four per doubling from 1ms, the last one counting everything
+/// above 2^17ms (about 2 minutes).
+const BUCKETS: usize = 4 * 17 + 2;
+
+/// Counters of a single `run_scrapper` session.
+#[derive(Debug)]
+pub struct CrawlStats {
+    session_id: String,
+    started: Instant,
+    started_at: DateTime<FixedOffset>,
+    fetched: AtomicU64,
+    extracted: AtomicU64,
+    warned: AtomicU64,
+    /// Urls not fetched or not queued because `validate_crawl_url` rejected them
+    rejected: AtomicU64,
+    /// Articles not stored because their page is `noindex`
+    noindex: AtomicU64,
+    /// Bodies only parsed up to `RunOptions::max_parse_size`
+    truncated: AtomicU64,
+    /// Bodies decoded from another charset than UTF-8
+    transcoded: AtomicU64,
+    /// Body bytes read from the connections, compressed or not
+    bytes_received: AtomicU64,
+    /// Body bytes once decompressed
+    bytes_decoded: AtomicU64,
+    /// Tasks of the crawl that panicked
+    failed_tasks: AtomicU64,
+    /// Pages fetched from each host
+    hosts: Mutex<HashMap<String, u64>>,
+    /// Urls warned for each reason
+    warned_reasons: Mutex<HashMap<String, u64>>,
+    /// Latencies of the first `MAX_LATENCY_HOSTS` hosts fetched from, and of `OTHER_HOSTS`
+    latencies: Mutex<HashMap<String, Latencies>>,
+    /// The `SLOWEST_PARSES` slowest pages to crawl, slowest first
+    slowest_parses: Mutex<Vec<ParseTiming>>,
+}
+
+impl Default for CrawlStats {
+    fn default() -> Self {
+        CrawlStats {
+            session_id: format!(
+                "{}-{}",
+                chrono::Local::now().format("%Y%m%d%H%M%S"),
+                std::process::id()
+            ),
+            started: Instant::now(),
+            started_at: chrono::Local::now().into(),
+            fetched: AtomicU64::new(0),
+            extracted: AtomicU64::new(0),
+            warned: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+            noindex: AtomicU64::new(0),
+            truncated: AtomicU64::new(0),
+            transcoded: AtomicU64::new(0),
+            bytes_received: AtomicU64::new(0),
+            bytes_decoded: AtomicU64::new(0),
+            failed_tasks: AtomicU64::new(0),
+            hosts: Default::default(),
+            warned_reasons: Default::default(),
+            latencies: Default::default(),
+            slowest_parses: Default::default(),
+        }
+    }
+}
+
+impl CrawlStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn session_id(&self) -> &str {
+        self.session_id.as_str()
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn started_at(&self) -> DateTime<FixedOffset> {
+        self.started_at
+    }
+
+    pub fn fetched(&self) -> u64 {
+        self.fetched.load(Ordering::Relaxed)
+    }
+
+    pub fn extracted(&self) -> u64 {
+        self.extracted.load(Ordering::Relaxed)
+    }
+
+    pub fn warned(&self) -> u64 {
+        self.warned.load(Ordering::Relaxed)
+    }
+
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+
+    pub fn noindex(&self) -> u64 {
+        self.noindex.load(Ordering::Relaxed)
+    }
+
+    pub fn truncated(&self) -> u64 {
+        self.truncated.load(Ordering::Relaxed)
+    }
+
+    pub fn transcoded(&self) -> u64 {
+        self.transcoded.load(Ordering::Relaxed)
+    }
+
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received.load(Ordering::Relaxed)
+    }
+
+    pub fn bytes_decoded(&self) -> u64 {
+        self.bytes_decoded.load(Ordering::Relaxed)
+    }
+
+    pub fn failed_tasks(&self) -> u64 {
+        self.failed_tasks.load(Ordering::Relaxed)
+    }
+
+    pub fn hosts(&self) -> BTreeMap<String, u64> {
+        self.hosts.lock().unwrap().clone().into_iter().collect()
+    }
+
+    pub fn warned_reasons(&self) -> BTreeMap<String, u64> {
+        self.warned_reasons
+            .lock()
+            .unwrap()
+            .clone()
+            .into_iter()
+            .collect()
+    }
+
+    /// The latency percentiles of each host, see `MAX_LATENCY_HOSTS`.
+    pub fn latencies(&self) -> BTreeMap<String, HostLatency> {
+        self.latencies
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(host, latencies)| (host.clone(), latencies.percentiles()))
+            .collect()
+    }
+
+    /// The latency percentiles of every fetch, whatever its host.
+    pub fn latency(&self) -> HostLatency {
+        let mut all = Latencies::default();
+        for latencies in self.latencies.lock().unwrap().values() {
+            all.fetch.merge(&latencies.fetch);
+            all.first_byte.merge(&latencies.first_byte);
+        }
+        all.percentiles()
+    }
+
+    /// Record how long a page from `host` took to fetch, and until its response headers.
+    pub(crate) fn record_latency(&self, host: &str, fetch: Duration, first_byte: Duration) {
+        let mut latencies = self.latencies.lock().unwrap();
+        let host = if latencies.contains_key(host)
+            || latencies.keys().filter(|h| *h != OTHER_HOSTS).count() < MAX_LATENCY_HOSTS
+        {
+            host
+        } else {
+            OTHER_HOSTS
+        };
+        let latencies = latencies.entry(host.to_string()).or_default();
+        latencies.fetch.record(fetch);
+        latencies.first_byte.record(first_byte);
+    }
+
+    /// The `SLOWEST_PARSES` slowest pages to crawl so far, slowest first.
+    pub fn slowest_parses(&self) -> Vec<ParseTiming> {
+        self.slowest_parses.lock().unwrap().clone()
+    }
+
+    /// Record how long a page took to crawl, kept when it's among the slowest.
+    pub(crate) fn record_parse(&self, timing: &ParseTiming) {
+        let mut slowest = self.slowest_parses.lock().unwrap();
+        if slowest.len() == SLOWEST_PARSES
+            && slowest
+                .last()
+                .is_some_and(|last| last.duration_ms >= timing.duration_ms)
+        {
+            return;
+        }
+        let at = slowest.partition_point(|t| t.duration_ms >= timing.duration_ms);
+        slowest.insert(at, timing.clone());
+        slowest.truncate(SLOWEST_PARSES);
+    }
+
+    /// Count a page fetched from `host`.
+    pub(crate) fn inc_fetched(&self, host: &str) -> u64 {
+        *self
+            .hosts
+            .lock()
+            .unwrap()
+            .entry(host.to_string())
+            .or_default() += 1;
+        self.fetched.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    pub(crate) fn inc_extracted(&self) -> u64 {
+        self.extracted.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    pub(crate) fn inc_warned(&self, reason: &str) -> u64 {
+        *self
+            .warned_reasons
+            .lock()
+            .unwrap()
+            .entry(reason.to_string())
+            .or_default() += 1;
+        self.warned.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    pub(crate) fn inc_rejected(&self) -> u64 {
+        self.rejected.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    pub(crate) fn inc_noindex(&self) -> u64 {
+        self.noindex.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    pub(crate) fn inc_truncated(&self) -> u64 {
+        self.truncated.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    pub(crate) fn inc_transcoded(&self) -> u64 {
+        self.transcoded.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    pub(crate) fn inc_failed_tasks(&self) -> u64 {
+        self.failed_tasks.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    pub(crate) fn add_bytes(&self, received: u64, decoded: u64) {
+        self.bytes_received.fetch_add(received, Ordering::Relaxed);
+        self.bytes_decoded.fetch_add(decoded, Ordering::Relaxed);
+    }
+
+    /// Average per minute of `count` since the session started.
+    pub fn per_minute(&self, count: u64) -> f64 {
+        let minutes = self.uptime().as_secs_f64() / 60.0;
+        if minutes > 0.0 {
+            count as f64 / minutes
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Latencies {
+    fetch: Histogram,
+    first_byte: Histogram,
+}
+
+impl Latencies {
+    fn percentiles(&self) -> HostLatency {
+        HostLatency {
+            fetch: self.fetch.percentiles(),
+            first_byte: self.first_byte.percentiles(),
+        }
+    }
+}
+
+/// How long a page took to crawl, parsed and read into a result by the crawler.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParseTiming {
+    pub url: String,
+    pub duration_ms: u64,
+    /// The paragraphs of the article crawled, 0 for the other pages
+    pub paragraphs: usize,
+}
+
+/// The latencies of the fetches from a host.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostLatency {
+    /// From the request to the end of the body
+    pub fetch: Percentiles,
+    /// From the request to the response headers
+    pub first_byte: Percentiles,
+}
+
+/// Percentiles of a `Histogram` in milliseconds, the upper bound of their bucket.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Percentiles {
+    pub count: u64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub p99_ms: u64,
+}
+
+/// Durations counted in buckets growing by a quarter of a doubling, so a percentile is read
+/// within 19% of the real one in a fixed amount of memory, however many are recorded.
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    counts: [u64; BUCKETS],
+    count: u64,
+    max: Duration,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Histogram {
+            counts: [0; BUCKETS],
+            count: 0,
+            max: Duration::ZERO,
+        }
+    }
+}
+
+impl Histogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bucket `i` counts the durations above the bound of `i - 1` up to 2^(i/4)ms.
+    fn bucket(duration: Duration) -> usize {
+        let ms = duration.as_secs_f64() * 1000.0;
+        if ms <= 1.0 {
+            0
+        } else {
+            ((ms.log2() * 4.0).ceil() as usize).min(BUCKETS - 1)
+        }
+    }
+
+    fn upper_bound(bucket: usize) -> Duration {
+        Duration::from_secs_f64(2f64.powf(bucket as f64 / 4.0) / 1000.0)
+    }
+
+    pub fn record(&mut self, duration: Duration) {
+        self.counts[Self::bucket(duration)] += 1;
+        self.count += 1;
+        self.max = self.max.max(duration);
+    }
+
+    pub fn merge(&mut self, other: &Histogram) {
+        for (count, other) in self.counts.iter_mut().zip(other.counts) {
+            *count += other;
+        }
+        self.count += other.count;
+        self.max = self.max.max(other.max);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The duration `percentile`% of the recorded ones are at most, `None` when empty.
+    pub fn percentile(&self, percentile: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((percentile / 100.0 * self.count as f64).ceil() as u64).clamp(1, self.count);
+        let mut seen = 0;
+        let bucket = self
+            .counts
+            .iter()
+            .position(|count| {
+                seen += count;
+                seen >= rank
+            })
+            .unwrap_or(BUCKETS - 1);
+        if bucket == BUCKETS - 1 {
+            return Some(self.max);
+        }
+        Some(Self::upper_bound(bucket).min(self.max))
+    }
+
+    pub fn percentiles(&self) -> Percentiles {
+        let ms = |percentile| {
+            self.percentile(percentile)
+                .map_or(0, |duration| duration.as_millis() as u64)
+        };
+        Percentiles {
+            count: self.count,
+            p50_ms: ms(50.0),
+            p95_ms: ms(95.0),
+            p99_ms: ms(99.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles() {
+        let mut histogram = Histogram::new();
+        assert_eq!(histogram.percentile(50.0), None);
+        assert_eq!(histogram.percentiles(), Percentiles::default());
+
+        for ms in 1..=1000 {
+            histogram.record(Duration::from_millis(ms));
+        }
+        assert_eq!(histogram.count(), 1000);
+        for (percentile, exact) in [(50.0, 500.0), (95.0, 950.0), (99.0, 990.0)] {
+            let read = histogram.percentile(percentile).unwrap().as_secs_f64() * 1000.0;
+            assert!(
+                read >= exact && read <= exact * 1.19,
+                "p{}: {}",
+                percentile,
+                read
+            );
+        }
+        // Never above the longest duration recorded
+        assert_eq!(
+            histogram.percentile(100.0),
+            Some(Duration::from_millis(1000))
+        );
+
+        let mut slow = Histogram::new();
+        slow.record(Duration::from_secs(600));
+        assert_eq!(slow.percentile(50.0), Some(Duration::from_secs(600)));
+        histogram.merge(&slow);
+        assert_eq!(histogram.count(), 1001);
+        assert_eq!(histogram.percentile(100.0), Some(Duration::from_secs(600)));
+        assert!(histogram.percentile(99.0).unwrap() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn slowest_parses() {
+        let stats = CrawlStats::new();
+        for ms in [30, 500, 10, 90, 20, 700, 40, 60, 80, 50, 5, 100, 15] {
+            stats.record_parse(&ParseTiming {
+                url: format!("https://news.detik.com/d-{}", ms),
+                duration_ms: ms,
+                paragraphs: 3,
+            });
+        }
+        let slowest: Vec<u64> = stats
+            .slowest_parses()
+            .iter()
+            .map(|t| t.duration_ms)
+            .collect();
+        assert_eq!(slowest, vec![700, 500, 100, 90, 80, 60, 50, 40, 30, 20]);
+    }
+
+    #[test]
+    fn latency_host_cap() {
+        let stats = CrawlStats::new();
+        for i in 0..MAX_LATENCY_HOSTS + 10 {
+            let host = format!("{}.detik.com", i);
+            stats.record_latency(&host, Duration::from_millis(200), Duration::from_millis(50));
+        }
+        stats.record_latency("0.detik.com", Duration::from_millis(400), Duration::ZERO);
+
+        let latencies = stats.latencies();
+        assert_eq!(latencies.len(), MAX_LATENCY_HOSTS + 1);
+        assert_eq!(latencies[OTHER_HOSTS].fetch.count, 10);
+        assert_eq!(latencies["0.detik.com"].fetch.count, 2);
+        assert_eq!(latencies["0.detik.com"].fetch.p99_ms, 400);
+        assert!(!latencies.contains_key("40.detik.com"));
+
+        let all = stats.latency();
+        assert_eq!(all.fetch.count, MAX_LATENCY_HOSTS as u64 + 11);
+        assert_eq!(all.first_byte.p95_ms, 50);
+    }
+}
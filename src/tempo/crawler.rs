@@ -0,0 +1,135 @@
+use crate::{
+    json_ld, tempo::TempoArticle, text, utils, CrawlContext, Crawler, CrawlerResult, ExtractedLink,
+    PAYWALLED,
+};
+use chrono::DateTime;
+use lazy_static::lazy_static;
+use scraper::{Html, Selector};
+
+const E: &str = "Invalid selector";
+lazy_static! {
+    static ref OG_TYPE: Selector = Selector::parse(r#"meta[property="og:type"]"#).expect(E);
+    static ref TITLE: Selector = Selector::parse(r#"meta[property="og:title"]"#).expect(E);
+    static ref DESCRIPTION: Selector =
+        Selector::parse(r#"meta[property="og:description"]"#).expect(E);
+    static ref PUBLISHED_TIME: Selector =
+        Selector::parse(r#"meta[property="article:published_time"]"#).expect(E);
+    static ref THUMBNAIL: Selector = Selector::parse(r#"meta[property="og:image"]"#).expect(E);
+    static ref AUTHOR: Selector = Selector::parse(r#"meta[name="author"]"#).expect(E);
+    static ref KEYWORDS: Selector = Selector::parse(r#"meta[name="keywords"]"#).expect(E);
+    static ref CANONICAL: Selector = Selector::parse(r#"link[rel="canonical"]"#).expect(E);
+    static ref BODY: Selector = Selector::parse("#isi, .detail-konten").expect(E);
+    static ref PAYWALL: Selector = Selector::parse(".paywall, #paywall, .box-premium").expect(E);
+    static ref P: Selector = Selector::parse("p").expect(E);
+}
+
+const SKIPPED_PREFIXES: [&str; 4] = [
+    "Baca juga",
+    "Baca Juga",
+    "Pilihan Editor",
+    "Ikuti berita terkini",
+];
+
+#[derive(Debug, Clone)]
+pub struct TempoCrawler {
+    allowlist: utils::HostAllowlist,
+}
+
+impl Default for TempoCrawler {
+    fn default() -> Self {
+        TempoCrawler {
+            allowlist: utils::HostAllowlist::new(["tempo.co"]),
+        }
+    }
+}
+
+impl TempoCrawler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    utils::allowlist_builders!("tempo.co");
+}
+
+impl Crawler for TempoCrawler {
+    type Document = TempoArticle;
+
+    fn can_be_scrapped(&self, doc: &Html) -> bool {
+        let is_article = json_ld::article(doc).is_some()
+            || utils::meta_content(doc, &OG_TYPE).is_some_and(|t| t == "article");
+        is_article && doc.select(&BODY).next().is_some()
+    }
+
+    fn extract_links(&self, ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        utils::links(doc, ctx.url, |url| self.allowlist.allows(url))
+    }
+
+    fn crawl(&self, ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        let links = self.extract_urls(ctx, doc);
+
+        if !self.can_be_scrapped(doc) {
+            return CrawlerResult::Links(links);
+        }
+
+        let ld = json_ld::article(doc);
+        if ld.as_ref().is_some_and(json_ld::is_paywalled) || doc.select(&PAYWALL).next().is_some() {
+            return CrawlerResult::Skipped(PAYWALLED.to_string(), links);
+        }
+        let ld_string = |key: &str| ld.as_ref().and_then(|ld| json_ld::string(ld, key));
+
+        let published_date = ld_string("datePublished")
+            .or_else(|| utils::meta_content(doc, &PUBLISHED_TIME))
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok());
+
+        let authors = ld
+            .as_ref()
+            .map(|ld| json_ld::names(ld, "author"))
+            .unwrap_or_default();
+        let author = if authors.is_empty() {
+            utils::meta_content(doc, &AUTHOR)
+        } else {
+            Some(authors.join(", "))
+        };
+
+        let canonical_url = doc
+            .select(&CANONICAL)
+            .next()
+            .and_then(|el| el.value().attr("href"))
+            .map(|s| s.trim().to_string())
+            .or_else(|| ld_string("url"));
+
+        let keywords = utils::meta_content(doc, &KEYWORDS)
+            .map(|s| {
+                s.split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut paragraphs = vec![];
+        if let Some(body) = doc.select(&BODY).next() {
+            for p in body.select(&P) {
+                if let Some(p) = text::clean_paragraph(&p.inner_html()) {
+                    if !text::starts_with_any(&p, &SKIPPED_PREFIXES) {
+                        paragraphs.push(p);
+                    }
+                }
+            }
+        }
+        paragraphs.dedup();
+
+        let article = TempoArticle {
+            title: utils::meta_content(doc, &TITLE).or_else(|| ld_string("headline")),
+            published_date,
+            description: utils::meta_content(doc, &DESCRIPTION)
+                .or_else(|| ld_string("description")),
+            thumbnail_url: utils::meta_content(doc, &THUMBNAIL),
+            author,
+            canonical_url,
+            keywords,
+            paragraphs,
+        };
+        CrawlerResult::document_and_links(article, links)
+    }
+}
@@ -0,0 +1,77 @@
+use super::TempoArticle;
+use crate::generic::{SiteArticle, SiteArticleTable};
+use crate::SqliteStorage;
+use sqlx::query::Query;
+use sqlx::sqlite::SqliteArguments;
+use sqlx::Sqlite;
+
+impl SiteArticle for TempoArticle {
+    const COLUMNS: &'static [(&'static str, &'static str)] = &[
+        ("author", "TEXT"),
+        ("description", "TEXT"),
+        ("thumbnail_url", "TEXT"),
+        ("canonical_url", "TEXT"),
+        ("keywords", "TEXT"),
+    ];
+
+    fn bind<'q>(
+        self,
+        query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    ) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+        query
+            .bind(self.author)
+            .bind(self.description)
+            .bind(self.thumbnail_url)
+            .bind(self.canonical_url)
+            .bind(self.keywords.join("|"))
+    }
+}
+
+pub type TempoArticleTable = SiteArticleTable<TempoArticle>;
+
+pub type TempoData = SqliteStorage<TempoArticleTable>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils, Storage, Table};
+    use std::path::Path;
+    use tokio::fs;
+
+    #[tokio::test]
+    async fn columns_mapped() {
+        if Path::new("test_tempo.db").is_file() {
+            fs::remove_file("test_tempo.db").await.unwrap();
+        }
+        let p = TempoData::new("test_tempo").await.unwrap();
+
+        let article = TempoArticle {
+            title: Some("title".to_string()),
+            published_date: Some(utils::get_now()),
+            description: None,
+            thumbnail_url: None,
+            author: Some("author".to_string()),
+            canonical_url: Some("https://nasional.tempo.co/read/1".to_string()),
+            keywords: vec!["k1".to_string()],
+            paragraphs: vec!["p1".to_string(), "p2".to_string()],
+        };
+        p.results_insert(("https://nasional.tempo.co/read/1", article))
+            .await
+            .unwrap();
+        let row: (String, String, String) =
+            sqlx::query_as("SELECT author, canonical_url, keywords FROM test_tempo_results")
+                .fetch_one(p.results.get_pool())
+                .await
+                .unwrap();
+        assert_eq!(
+            row,
+            (
+                "author".to_string(),
+                "https://nasional.tempo.co/read/1".to_string(),
+                "k1".to_string(),
+            )
+        );
+
+        fs::remove_file("test_tempo.db").await.unwrap();
+    }
+}
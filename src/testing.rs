@@ -0,0 +1,582 @@
+//! Test doubles for checking a custom [`Crawler`] or [`Storage`] pipeline without
+//! touching a real site or database.
+//!
+//! ```
+//! use indonesian_media_crawler::detik::DetikArticle;
+//! use indonesian_media_crawler::testing::{MockCrawler, RecordingStorage};
+//! use indonesian_media_crawler::CrawlerResult;
+//!
+//! let crawler = MockCrawler::<DetikArticle>::new()
+//!     .with("/hub", CrawlerResult::Links(vec!["http://site/a".to_string()]));
+//! let storage = RecordingStorage::<_>::memory::<DetikArticle>();
+//! # let _ = (crawler, storage);
+//! ```
+
+use crate::memory::MemoryStorage;
+use crate::recrawl::LinkSnapshot;
+use crate::{
+    Article, Bucket, CrawlContext, Crawler, CrawlerError, CrawlerResult, ExtractedLink,
+    ExtractionStatus, FetchDecision, HostState, HubState, QueuedUrl, ResultEntry, ResultSummary,
+    Revision, Storage, UrlBucket, WarnedEntry,
+};
+use chrono::{DateTime, FixedOffset};
+use futures::stream::BoxStream;
+use scraper::{Html, Selector};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A `Crawler` returning canned results.
+///
+/// Pages are told apart by their canonical url (`<link rel="canonical">`), which
+/// [`MockCrawler::page`] renders for a mock server to serve.
+#[derive(Debug, Clone)]
+pub struct MockCrawler<A: Article> {
+    responses: Vec<(String, CrawlerResult<A>)>,
+    decisions: Vec<(String, FetchDecision)>,
+    delays: Vec<(String, Duration)>,
+}
+
+impl<A: Article> Default for MockCrawler<A> {
+    fn default() -> Self {
+        MockCrawler {
+            responses: vec![],
+            decisions: vec![],
+            delays: vec![],
+        }
+    }
+}
+
+impl<A: Article + Clone> MockCrawler<A> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return `result` for every page whose url contains `url_part`. The first
+    /// match wins, pages matching nothing give `CrawlerResult::Links(vec![])`.
+    pub fn with(mut self, url_part: impl Into<String>, result: CrawlerResult<A>) -> Self {
+        self.responses.push((url_part.into(), result));
+        self
+    }
+
+    /// Decide `decision` for every url containing `url_part`, see `Crawler::should_fetch`.
+    /// The first match wins, urls matching nothing are fetched.
+    pub fn with_decision(mut self, url_part: impl Into<String>, decision: FetchDecision) -> Self {
+        self.decisions.push((url_part.into(), decision));
+        self
+    }
+
+    /// Take `delay` to crawl every page whose url contains `url_part`, like a slow parse.
+    /// The first match wins.
+    pub fn with_delay(mut self, url_part: impl Into<String>, delay: Duration) -> Self {
+        self.delays.push((url_part.into(), delay));
+        self
+    }
+
+    /// A minimal html page the mock crawler recognizes as `url`.
+    pub fn page(url: &str) -> String {
+        format!(
+            r#"<html><head><link rel="canonical" href="{}"></head><body></body></html>"#,
+            url
+        )
+    }
+
+    fn url_of(doc: &Html) -> Option<String> {
+        let selector = Selector::parse(r#"link[rel="canonical"]"#).unwrap();
+        doc.select(&selector)
+            .next()
+            .and_then(|e| e.value().attr("href"))
+            .map(ToString::to_string)
+    }
+
+    fn result_for(&self, doc: &Html) -> CrawlerResult<A> {
+        Self::url_of(doc)
+            .and_then(|url| {
+                self.responses
+                    .iter()
+                    .find(|(part, _)| url.contains(part.as_str()))
+            })
+            .map(|(_, result)| result.clone())
+            .unwrap_or(CrawlerResult::Links(vec![]))
+    }
+}
+
+impl<A: Article + Clone + Send> Crawler for MockCrawler<A> {
+    type Document = A;
+
+    fn can_be_scrapped(&self, _doc: &Html) -> bool {
+        true
+    }
+
+    fn crawl(&self, _ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        let delay = Self::url_of(doc).and_then(|url| {
+            self.delays
+                .iter()
+                .find(|(part, _)| url.contains(part.as_str()))
+                .map(|(_, delay)| *delay)
+        });
+        if let Some(delay) = delay {
+            std::thread::sleep(delay);
+        }
+        self.result_for(doc)
+    }
+
+    fn should_fetch(&self, url: &reqwest::Url) -> FetchDecision {
+        self.decisions
+            .iter()
+            .find(|(part, _)| url.as_str().contains(part.as_str()))
+            .map_or(FetchDecision::Fetch, |(_, decision)| *decision)
+    }
+
+    fn extract_links(&self, _ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        match self.result_for(doc) {
+            CrawlerResult::Links(links) | CrawlerResult::Skipped(_, links) => {
+                links.into_iter().map(ExtractedLink::from).collect()
+            }
+            CrawlerResult::DocumentAndLinks(_, links) => {
+                links.iter().cloned().map(ExtractedLink::from).collect()
+            }
+        }
+    }
+}
+
+/// One call made on a [`RecordingStorage`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call {
+    pub method: &'static str,
+    pub arg: Option<String>,
+}
+
+/// A `Storage` delegating to `S` while recording every trait call in order.
+///
+/// Clones share the recorded calls.
+#[derive(Debug, Clone, Default)]
+pub struct RecordingStorage<S> {
+    inner: S,
+    calls: Arc<Mutex<Vec<Call>>>,
+}
+
+impl<S> RecordingStorage<S> {
+    pub fn new(inner: S) -> Self {
+        RecordingStorage {
+            inner,
+            calls: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn calls(&self) -> Vec<Call> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Names of the methods called with `arg`, in order.
+    pub fn calls_for(&self, arg: &str) -> Vec<&'static str> {
+        self.calls
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|c| c.arg.as_deref() == Some(arg))
+            .map(|c| c.method)
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        self.calls.lock().unwrap().clear();
+    }
+
+    fn record(&self, method: &'static str, arg: Option<&str>) {
+        self.calls.lock().unwrap().push(Call {
+            method,
+            arg: arg.map(ToString::to_string),
+        });
+    }
+}
+
+impl RecordingStorage<()> {
+    pub fn memory<R>() -> RecordingStorage<MemoryStorage<R>> {
+        RecordingStorage::new(MemoryStorage::new())
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: Storage + Send + Sync> Storage for RecordingStorage<S>
+where
+    S::Record: Send,
+{
+    type Record = S::Record;
+
+    fn bucket(&self, bucket: Bucket) -> &dyn UrlBucket {
+        self.inner.bucket(bucket)
+    }
+
+    async fn queued_get(&self) -> Result<Vec<String>, CrawlerError> {
+        self.record("queued_get", None);
+        self.inner.queued_get().await
+    }
+
+    async fn queued_get_n(&self, n: u32) -> Result<Vec<String>, CrawlerError> {
+        self.record("queued_get_n", None);
+        self.inner.queued_get_n(n).await
+    }
+
+    async fn queued_insert<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        self.record("queued_insert", Some(item.as_ref()));
+        self.inner.queued_insert(item).await
+    }
+
+    async fn queued_insert_batch<I: AsRef<str> + Send + Sync>(
+        &self,
+        items: &[I],
+    ) -> Result<(), CrawlerError> {
+        for item in items {
+            self.record("queued_insert_batch", Some(item.as_ref()));
+        }
+        self.inner.queued_insert_batch(items).await
+    }
+
+    async fn queued_delete<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        self.record("queued_delete", Some(item.as_ref()));
+        self.inner.queued_delete(item).await
+    }
+
+    async fn queued_insert_unseen<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+    ) -> Result<(), CrawlerError> {
+        self.record("queued_insert_unseen", Some(item.as_ref()));
+        self.inner.queued_insert_unseen(item).await
+    }
+
+    async fn queued_insert_with_meta<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        referrer: Option<&str>,
+        depth: u32,
+    ) -> Result<(), CrawlerError> {
+        self.record("queued_insert_with_meta", Some(item.as_ref()));
+        self.inner
+            .queued_insert_with_meta(item, referrer, depth)
+            .await
+    }
+
+    async fn queued_insert_prioritized<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        referrer: Option<&str>,
+        depth: u32,
+        priority: i32,
+    ) -> Result<(), CrawlerError> {
+        self.record("queued_insert_prioritized", Some(item.as_ref()));
+        self.inner
+            .queued_insert_prioritized(item, referrer, depth, priority)
+            .await
+    }
+
+    async fn queued_insert_with_priority<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        priority: i32,
+    ) -> Result<(), CrawlerError> {
+        self.record("queued_insert_with_priority", Some(item.as_ref()));
+        self.inner.queued_insert_with_priority(item, priority).await
+    }
+
+    async fn queued_is_exists<I: AsRef<str> + Send>(&self, item: I) -> Result<bool, CrawlerError> {
+        self.record("queued_is_exists", Some(item.as_ref()));
+        self.inner.queued_is_exists(item).await
+    }
+
+    async fn queued_count(&self) -> Result<u32, CrawlerError> {
+        self.record("queued_count", None);
+        self.inner.queued_count().await
+    }
+
+    async fn queued_oldest_at(&self) -> Result<Option<DateTime<FixedOffset>>, CrawlerError> {
+        self.record("queued_oldest_at", None);
+        self.inner.queued_oldest_at().await
+    }
+
+    async fn queued_is_empty(&self) -> Result<bool, CrawlerError> {
+        self.record("queued_is_empty", None);
+        self.inner.queued_is_empty().await
+    }
+
+    fn queued_stream(&self) -> BoxStream<'_, Result<String, CrawlerError>> {
+        self.record("queued_stream", None);
+        self.inner.queued_stream()
+    }
+
+    async fn running_get(&self) -> Result<Vec<String>, CrawlerError> {
+        self.record("running_get", None);
+        self.inner.running_get().await
+    }
+
+    async fn running_insert<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        self.record("running_insert", Some(item.as_ref()));
+        self.inner.running_insert(item).await
+    }
+
+    async fn running_delete<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        self.record("running_delete", Some(item.as_ref()));
+        self.inner.running_delete(item).await
+    }
+
+    async fn running_count(&self) -> Result<u32, CrawlerError> {
+        self.record("running_count", None);
+        self.inner.running_count().await
+    }
+
+    async fn running_is_exists<I: AsRef<str> + Send>(&self, item: I) -> Result<bool, CrawlerError> {
+        self.record("running_is_exists", Some(item.as_ref()));
+        self.inner.running_is_exists(item).await
+    }
+
+    async fn visited_delete<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        self.record("visited_delete", Some(item.as_ref()));
+        self.inner.visited_delete(item).await
+    }
+
+    async fn visited_is_exists<I: AsRef<str> + Send>(&self, item: I) -> Result<bool, CrawlerError> {
+        self.record("visited_is_exists", Some(item.as_ref()));
+        self.inner.visited_is_exists(item).await
+    }
+
+    async fn visited_insert<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        self.record("visited_insert", Some(item.as_ref()));
+        self.inner.visited_insert(item).await
+    }
+
+    async fn visited_count(&self) -> Result<u32, CrawlerError> {
+        self.record("visited_count", None);
+        self.inner.visited_count().await
+    }
+
+    fn visited_stream(&self) -> BoxStream<'_, Result<String, CrawlerError>> {
+        self.record("visited_stream", None);
+        self.inner.visited_stream()
+    }
+
+    async fn queued_claim_n(
+        &self,
+        worker_id: &str,
+        n: u32,
+    ) -> Result<Vec<QueuedUrl>, CrawlerError> {
+        self.record("queued_claim_n", None);
+        self.inner.queued_claim_n(worker_id, n).await
+    }
+
+    async fn running_claim<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        worker_id: &str,
+    ) -> Result<bool, CrawlerError> {
+        self.record("running_claim", Some(item.as_ref()));
+        self.inner.running_claim(item, worker_id).await
+    }
+
+    async fn running_touch(
+        &self,
+        worker_id: &str,
+        now: DateTime<FixedOffset>,
+    ) -> Result<(), CrawlerError> {
+        self.record("running_touch", None);
+        self.inner.running_touch(worker_id, now).await
+    }
+
+    async fn running_reap(
+        &self,
+        stale_before: DateTime<FixedOffset>,
+    ) -> Result<Vec<String>, CrawlerError> {
+        self.record("running_reap", None);
+        self.inner.running_reap(stale_before).await
+    }
+
+    async fn running_release(&self, worker_id: &str) -> Result<Vec<String>, CrawlerError> {
+        self.record("running_release", None);
+        self.inner.running_release(worker_id).await
+    }
+
+    async fn running_requeue<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        self.record("running_requeue", Some(item.as_ref()));
+        self.inner.running_requeue(item).await
+    }
+
+    async fn results_count(&self) -> Result<u32, CrawlerError> {
+        self.record("results_count", None);
+        self.inner.results_count().await
+    }
+
+    async fn results_recent(&self, n: u32) -> Result<Vec<ResultSummary>, CrawlerError> {
+        self.record("results_recent", None);
+        self.inner.results_recent(n).await
+    }
+
+    async fn results_get(&self, url: &str) -> Result<Option<ResultEntry>, CrawlerError> {
+        self.record("results_get", Some(url));
+        self.inner.results_get(url).await
+    }
+
+    async fn results_insert<I: AsRef<str> + Send>(
+        &self,
+        (url, record): (I, Self::Record),
+    ) -> Result<(), CrawlerError> {
+        self.record("results_insert", Some(url.as_ref()));
+        self.inner.results_insert((url, record)).await
+    }
+
+    async fn results_insert_with_referrer<I: AsRef<str> + Send>(
+        &self,
+        (url, record): (I, Self::Record),
+        referrer: Option<&str>,
+    ) -> Result<(), CrawlerError> {
+        self.record("results_insert_with_referrer", Some(url.as_ref()));
+        self.inner
+            .results_insert_with_referrer((url, record), referrer)
+            .await
+    }
+
+    async fn results_insert_metadata_only<I: AsRef<str> + Send>(
+        &self,
+        (url, record): (I, Self::Record),
+        referrer: Option<&str>,
+    ) -> Result<(), CrawlerError> {
+        self.record("results_insert_metadata_only", Some(url.as_ref()));
+        self.inner
+            .results_insert_metadata_only((url, record), referrer)
+            .await
+    }
+
+    async fn results_status(&self, url: &str) -> Result<Option<ExtractionStatus>, CrawlerError> {
+        self.record("results_status", Some(url));
+        self.inner.results_status(url).await
+    }
+
+    async fn results_revise<I: AsRef<str> + Send>(
+        &self,
+        (url, record): (I, Self::Record),
+        diff: &str,
+    ) -> Result<(), CrawlerError> {
+        self.record("results_revise", Some(url.as_ref()));
+        self.inner.results_revise((url, record), diff).await
+    }
+
+    async fn revisions_get(&self, url: &str) -> Result<Vec<Revision>, CrawlerError> {
+        self.record("revisions_get", Some(url));
+        self.inner.revisions_get(url).await
+    }
+
+    async fn revisit_schedule(
+        &self,
+        url: &str,
+        due_at: DateTime<FixedOffset>,
+    ) -> Result<(), CrawlerError> {
+        self.record("revisit_schedule", Some(url));
+        self.inner.revisit_schedule(url, due_at).await
+    }
+
+    async fn revisit_due(
+        &self,
+        now: DateTime<FixedOffset>,
+        n: u32,
+    ) -> Result<Vec<String>, CrawlerError> {
+        self.record("revisit_due", None);
+        self.inner.revisit_due(now, n).await
+    }
+
+    async fn warned_insert<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        reason: &str,
+    ) -> Result<(), CrawlerError> {
+        self.record("warned_insert", Some(item.as_ref()));
+        self.inner.warned_insert(item, reason).await
+    }
+
+    async fn warned_count(&self) -> Result<u32, CrawlerError> {
+        self.record("warned_count", None);
+        self.inner.warned_count().await
+    }
+
+    async fn warned_recent(&self, n: u32) -> Result<Vec<WarnedEntry>, CrawlerError> {
+        self.record("warned_recent", None);
+        self.inner.warned_recent(n).await
+    }
+
+    async fn warned_get(&self, item: &str) -> Result<Option<WarnedEntry>, CrawlerError> {
+        self.record("warned_get", Some(item));
+        self.inner.warned_get(item).await
+    }
+
+    async fn warned_delete(&self, item: &str) -> Result<(), CrawlerError> {
+        self.record("warned_delete", Some(item));
+        self.inner.warned_delete(item).await
+    }
+
+    async fn warned_schedule(
+        &self,
+        item: &str,
+        reason: &str,
+        attempts: u32,
+        next_retry_at: Option<DateTime<FixedOffset>>,
+    ) -> Result<(), CrawlerError> {
+        self.record("warned_schedule", Some(item));
+        self.inner
+            .warned_schedule(item, reason, attempts, next_retry_at)
+            .await
+    }
+
+    async fn warned_due(
+        &self,
+        now: DateTime<FixedOffset>,
+        n: u32,
+    ) -> Result<Vec<WarnedEntry>, CrawlerError> {
+        self.record("warned_due", None);
+        self.inner.warned_due(now, n).await
+    }
+
+    async fn host_state_get(&self, host: &str) -> Result<Option<HostState>, CrawlerError> {
+        self.record("host_state_get", Some(host));
+        self.inner.host_state_get(host).await
+    }
+
+    async fn host_state_put(&self, state: &HostState) -> Result<(), CrawlerError> {
+        self.record("host_state_put", Some(&state.host));
+        self.inner.host_state_put(state).await
+    }
+
+    async fn hub_get(&self, url: &str) -> Result<Option<HubState>, CrawlerError> {
+        self.record("hub_get", Some(url));
+        self.inner.hub_get(url).await
+    }
+
+    async fn hub_put(&self, state: &HubState) -> Result<(), CrawlerError> {
+        self.record("hub_put", Some(&state.url));
+        self.inner.hub_put(state).await
+    }
+
+    async fn hubs_get(&self) -> Result<Vec<HubState>, CrawlerError> {
+        self.record("hubs_get", None);
+        self.inner.hubs_get().await
+    }
+
+    async fn hub_snapshot_get(&self, url: &str) -> Result<Option<LinkSnapshot>, CrawlerError> {
+        self.record("hub_snapshot_get", Some(url));
+        self.inner.hub_snapshot_get(url).await
+    }
+
+    async fn hub_snapshot_put(
+        &self,
+        url: &str,
+        snapshot: &LinkSnapshot,
+    ) -> Result<(), CrawlerError> {
+        self.record("hub_snapshot_put", Some(url));
+        self.inner.hub_snapshot_put(url, snapshot).await
+    }
+
+    async fn merge_queue_and_running(&self) -> Result<(), CrawlerError> {
+        self.record("merge_queue_and_running", None);
+        self.inner.merge_queue_and_running().await
+    }
+}
@@ -0,0 +1,106 @@
+//! Watching the queue of a crawl, and keeping the crawl on articles when the queue grows
+//! faster than it's crawled: above a high watermark only article urls are queued, until the
+//! queue is back below a low watermark.
+
+use crate::{CrawlerError, Storage};
+use chrono::{DateTime, FixedOffset};
+use lazy_regex::{regex, Regex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::time::Duration;
+use tracing::info;
+
+/// How often the crawl looks at its queue.
+pub(crate) const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub struct QueueThrottle {
+    /// Only article urls are queued once the queue holds more urls than this
+    pub high_watermark: u32,
+    /// Every url is queued again once the queue holds fewer urls than this
+    pub low_watermark: u32,
+    /// The urls still queued while throttled, the `/d-<id>/` articles of detik by default
+    pub article_pattern: Regex,
+}
+
+impl QueueThrottle {
+    pub fn new(high_watermark: u32, low_watermark: u32) -> Self {
+        QueueThrottle {
+            high_watermark,
+            low_watermark,
+            article_pattern: Regex::clone(regex!(r"/d-\d+/")),
+        }
+    }
+
+    pub fn with_article_pattern(mut self, pattern: Regex) -> Self {
+        self.article_pattern = pattern;
+        self
+    }
+
+    pub fn is_article(&self, url: &str) -> bool {
+        self.article_pattern.is_match(url)
+    }
+
+    /// Whether the crawl is throttled with `queued` urls, given whether it was before.
+    pub fn is_throttled(&self, throttled: bool, queued: u32) -> bool {
+        if throttled {
+            queued >= self.low_watermark
+        } else {
+            queued > self.high_watermark
+        }
+    }
+}
+
+/// The size of the queue and how long its oldest url has waited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct QueueStats {
+    pub(crate) queued: u32,
+    pub(crate) oldest_age: Option<chrono::Duration>,
+}
+
+/// The queue of `storage` at `now`, turning `throttled` on or off as `throttle` says.
+pub(crate) async fn check_at<S: Storage + ?Sized>(
+    storage: &S,
+    throttle: Option<&QueueThrottle>,
+    throttled: &AtomicBool,
+    now: DateTime<FixedOffset>,
+) -> Result<QueueStats, CrawlerError> {
+    let queued = storage.queued_count().await?;
+    let oldest_age = storage.queued_oldest_at().await?.map(|at| now - at);
+    if let Some(throttle) = throttle {
+        let was = throttled.load(Ordering::Relaxed);
+        let is = throttle.is_throttled(was, queued);
+        if is != was {
+            throttled.store(is, Ordering::Relaxed);
+            if is {
+                info!(
+                    "Queue at {} urls, above {}: only queuing article urls",
+                    queued, throttle.high_watermark
+                );
+            } else {
+                info!(
+                    "Queue at {} urls, below {}: queuing every url again",
+                    queued, throttle.low_watermark
+                );
+            }
+        }
+    }
+    Ok(QueueStats { queued, oldest_age })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hysteresis() {
+        let throttle = QueueThrottle::new(100, 50);
+        assert!(!throttle.is_throttled(false, 100));
+        assert!(throttle.is_throttled(false, 101));
+        assert!(throttle.is_throttled(true, 60));
+        assert!(throttle.is_throttled(true, 50));
+        assert!(!throttle.is_throttled(true, 49));
+
+        assert!(throttle.is_article("https://news.detik.com/berita/d-6453785/judul"));
+        assert!(!throttle.is_article("https://news.detik.com/indeks"));
+    }
+}
@@ -0,0 +1,221 @@
+use crate::{
+    json_ld, text, tirto::TirtoArticle, utils, CrawlContext, Crawler, CrawlerResult, ExtractedLink,
+};
+use chrono::DateTime;
+use itertools::Itertools;
+use lazy_regex::{regex, regex_captures, regex_is_match};
+use lazy_static::lazy_static;
+use scraper::{ElementRef, Html, Selector};
+
+const E: &str = "Invalid selector";
+lazy_static! {
+    static ref TITLE: Selector = Selector::parse(r#"meta[property="og:title"]"#).expect(E);
+    static ref DESCRIPTION: Selector =
+        Selector::parse(r#"meta[property="og:description"]"#).expect(E);
+    static ref THUMBNAIL: Selector = Selector::parse(r#"meta[property="og:image"]"#).expect(E);
+    static ref KEYWORDS: Selector = Selector::parse(r#"meta[name="keywords"]"#).expect(E);
+    static ref SUBTITLE: Selector = Selector::parse(".sub-title, .subtitle").expect(E);
+    static ref CREDIT: Selector = Selector::parse(".reporter-grid > *").expect(E);
+    static ref TAG: Selector = Selector::parse(".tags-wrapper a, .tag-list a").expect(E);
+    static ref BODY: Selector = Selector::parse(".content-text-editor").expect(E);
+    static ref BLOCK: Selector = Selector::parse("p, h2, h3").expect(E);
+}
+
+const SKIPPED_PREFIXES: [&str; 3] = ["Baca juga", "Baca Juga", "Simak juga"];
+
+#[derive(Debug, Clone)]
+pub struct TirtoCrawler {
+    allowlist: utils::HostAllowlist,
+}
+
+impl Default for TirtoCrawler {
+    fn default() -> Self {
+        TirtoCrawler {
+            allowlist: utils::HostAllowlist::new(["tirto.id"]),
+        }
+    }
+}
+
+impl TirtoCrawler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    utils::allowlist_builders!("tirto.id");
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Credits {
+    reporter: Option<String>,
+    author: Option<String>,
+    editor: Option<String>,
+}
+
+impl Credits {
+    /// Record a "Reporter: Name", "Penulis: Name" or "Editor: Name" line, returns false for
+    /// any other line.
+    fn add_line(&mut self, line: &str) -> bool {
+        let line = regex!(r"<[^>]*>").replace_all(line, "");
+        let Some((_, key, name)) =
+            regex_captures!(r"(?i)^\s*(reporter|penulis|editor)\s*:\s*(.*?)\s*$", &line)
+        else {
+            return false;
+        };
+        let field = match key.to_lowercase().as_str() {
+            "reporter" => &mut self.reporter,
+            "penulis" => &mut self.author,
+            _ => &mut self.editor,
+        };
+        if field.is_none() && !name.is_empty() {
+            *field = Some(name.to_string());
+        }
+        true
+    }
+}
+
+/// Caption lines of infographics, e.g. "Infografik Mozaik Bung Hatta. tirto.id/Sabit".
+fn is_infographic_credit(paragraph: &str) -> bool {
+    regex_is_match!(
+        r"(?i)^(<[^>]+>)*\s*(infografi[ks]|foto)\b.*tirto\.id/",
+        paragraph
+    )
+}
+
+fn is_heading(el: &ElementRef) -> bool {
+    matches!(el.value().name(), "h2" | "h3")
+}
+
+impl Crawler for TirtoCrawler {
+    type Document = TirtoArticle;
+
+    fn can_be_scrapped(&self, doc: &Html) -> bool {
+        json_ld::article(doc).is_some() && doc.select(&BODY).next().is_some()
+    }
+
+    fn extract_links(&self, ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        utils::links(doc, ctx.url, |url| self.allowlist.allows(url))
+    }
+
+    fn crawl(&self, ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        let links = self.extract_urls(ctx, doc);
+
+        if !self.can_be_scrapped(doc) {
+            return CrawlerResult::Links(links);
+        }
+
+        let ld = json_ld::article(doc);
+        let ld_string = |key: &str| ld.as_ref().and_then(|ld| json_ld::string(ld, key));
+
+        let mut credits = Credits::default();
+        for el in doc.select(&CREDIT) {
+            credits.add_line(&el.text().collect::<String>());
+        }
+
+        let mut paragraphs = vec![];
+        if let Some(body) = doc.select(&BODY).next() {
+            for el in body.select(&BLOCK).filter(|el| {
+                !utils::has_ancestor_class(el, "baca-juga")
+                    && !el
+                        .ancestors()
+                        .filter_map(ElementRef::wrap)
+                        .any(|a| a.value().name() == "figure")
+            }) {
+                let Some(p) = text::clean_paragraph(&el.inner_html()) else {
+                    continue;
+                };
+                if text::starts_with_any(&p, &SKIPPED_PREFIXES) || is_infographic_credit(&p) {
+                    continue;
+                }
+                // Trailing credit lines belong in their own fields
+                let rest = p.lines().filter(|line| !credits.add_line(line)).join("\n");
+                if rest.is_empty() {
+                    continue;
+                }
+                if is_heading(&el) {
+                    paragraphs.push(format!("## {}", rest));
+                } else {
+                    paragraphs.push(rest);
+                }
+            }
+        }
+        paragraphs.dedup();
+
+        let mut tags: Vec<String> = doc
+            .select(&TAG)
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if tags.is_empty() {
+            tags = utils::meta_content(doc, &KEYWORDS)
+                .map(|s| {
+                    s.split(',')
+                        .map(|s| s.trim().to_string())
+                        .filter(|s| !s.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default();
+        }
+
+        let author = credits.author.or_else(|| {
+            ld.as_ref()
+                .map(|ld| json_ld::names(ld, "author"))
+                .filter(|names| !names.is_empty())
+                .map(|names| names.join(", "))
+        });
+
+        let article = TirtoArticle {
+            title: ld_string("headline").or_else(|| utils::meta_content(doc, &TITLE)),
+            subtitle: doc
+                .select(&SUBTITLE)
+                .next()
+                .map(|el| el.text().collect::<String>().trim().to_string())
+                .filter(|s| !s.is_empty()),
+            published_date: ld_string("datePublished")
+                .and_then(|s| DateTime::parse_from_rfc3339(&s).ok()),
+            description: utils::meta_content(doc, &DESCRIPTION)
+                .or_else(|| ld_string("description")),
+            thumbnail_url: utils::meta_content(doc, &THUMBNAIL),
+            reporter: credits.reporter,
+            author,
+            editor: credits.editor,
+            tags,
+            paragraphs,
+        };
+        CrawlerResult::document_and_links(article, links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credit_lines() {
+        let mut credits = Credits::default();
+        assert!(credits.add_line("Reporter: <a href=\"x\">Riyan Setiawan</a>"));
+        assert!(credits.add_line("Penulis : Riyan Setiawan"));
+        assert!(credits.add_line("EDITOR: Maya Saputri"));
+        assert!(!credits.add_line("Editor senior itu menolak berkomentar."));
+        assert_eq!(
+            credits,
+            Credits {
+                reporter: Some("Riyan Setiawan".to_string()),
+                author: Some("Riyan Setiawan".to_string()),
+                editor: Some("Maya Saputri".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn infographic_credits() {
+        assert!(is_infographic_credit(
+            "Infografik Mozaik Bung Hatta. tirto.id/Sabit"
+        ));
+        assert!(is_infographic_credit(
+            "<em>Infografis Pemilu. tirto.id/Quita</em>"
+        ));
+        assert!(!is_infographic_credit(
+            "Infografis dari BPS menunjukkan kenaikan."
+        ));
+    }
+}
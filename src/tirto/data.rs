@@ -0,0 +1,84 @@
+use super::TirtoArticle;
+use crate::generic::{SiteArticle, SiteArticleTable};
+use crate::SqliteStorage;
+use sqlx::query::Query;
+use sqlx::sqlite::SqliteArguments;
+use sqlx::Sqlite;
+
+impl SiteArticle for TirtoArticle {
+    const COLUMNS: &'static [(&'static str, &'static str)] = &[
+        ("subtitle", "TEXT"),
+        ("reporter", "TEXT"),
+        ("author", "TEXT"),
+        ("editor", "TEXT"),
+        ("description", "TEXT"),
+        ("thumbnail_url", "TEXT"),
+        ("tags", "TEXT"),
+    ];
+
+    fn bind<'q>(
+        self,
+        query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    ) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+        query
+            .bind(self.subtitle)
+            .bind(self.reporter)
+            .bind(self.author)
+            .bind(self.editor)
+            .bind(self.description)
+            .bind(self.thumbnail_url)
+            .bind(self.tags.join("|"))
+    }
+}
+
+pub type TirtoArticleTable = SiteArticleTable<TirtoArticle>;
+
+pub type TirtoData = SqliteStorage<TirtoArticleTable>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils, Storage, Table};
+    use std::path::Path;
+    use tokio::fs;
+
+    #[tokio::test]
+    async fn columns_mapped() {
+        if Path::new("test_tirto.db").is_file() {
+            fs::remove_file("test_tirto.db").await.unwrap();
+        }
+        let p = TirtoData::new("test_tirto").await.unwrap();
+
+        let article = TirtoArticle {
+            title: Some("title".to_string()),
+            subtitle: None,
+            reporter: Some("reporter".to_string()),
+            published_date: Some(utils::get_now()),
+            description: None,
+            thumbnail_url: None,
+            author: Some("author".to_string()),
+            editor: Some("editor".to_string()),
+            tags: vec!["t1".to_string()],
+            paragraphs: vec!["p1".to_string(), "p2".to_string()],
+        };
+        p.results_insert(("https://tirto.id/judul-1", article))
+            .await
+            .unwrap();
+        let row: (String, String, String, String) =
+            sqlx::query_as("SELECT reporter, author, editor, tags FROM test_tirto_results")
+                .fetch_one(p.results.get_pool())
+                .await
+                .unwrap();
+        assert_eq!(
+            row,
+            (
+                "reporter".to_string(),
+                "author".to_string(),
+                "editor".to_string(),
+                "t1".to_string(),
+            )
+        );
+
+        fs::remove_file("test_tirto.db").await.unwrap();
+    }
+}
@@ -0,0 +1,42 @@
+mod crawler;
+#[cfg(feature = "sqlite")]
+mod data;
+
+pub use crawler::TirtoCrawler;
+#[cfg(feature = "sqlite")]
+pub use data::{TirtoArticleTable, TirtoData};
+
+use crate::Article;
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TirtoArticle {
+    pub title: Option<String>,
+    pub subtitle: Option<String>,
+    pub published_date: Option<DateTime<FixedOffset>>,
+    pub description: Option<String>,
+    pub thumbnail_url: Option<String>,
+    pub reporter: Option<String>,
+    /// The "Penulis" credit
+    pub author: Option<String>,
+    pub editor: Option<String>,
+    pub tags: Vec<String>,
+    /// Section headers of long-reads are kept in order, prefixed with "## "
+    pub paragraphs: Vec<String>,
+}
+
+impl Article for TirtoArticle {
+    fn get_paragraphs(&self) -> &[String] {
+        self.paragraphs.as_slice()
+    }
+
+    fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    fn get_published_date(&self) -> Option<DateTime<FixedOffset>> {
+        self.published_date
+    }
+}
@@ -0,0 +1,224 @@
+use crate::{
+    json_ld, text, tribunnews::TribunnewsArticle, utils, CrawlContext, Crawler, CrawlerResult,
+    ExtractedLink,
+};
+use chrono::DateTime;
+use itertools::Itertools;
+use lazy_regex::{regex, Regex};
+use lazy_static::lazy_static;
+use scraper::{ElementRef, Html, Selector};
+
+const E: &str = "Invalid selector";
+lazy_static! {
+    static ref OG_TYPE: Selector = Selector::parse(r#"meta[property="og:type"]"#).expect(E);
+    static ref TITLE: Selector = Selector::parse(r#"meta[property="og:title"]"#).expect(E);
+    static ref DESCRIPTION: Selector =
+        Selector::parse(r#"meta[property="og:description"]"#).expect(E);
+    static ref THUMBNAIL: Selector = Selector::parse(r#"meta[property="og:image"]"#).expect(E);
+    static ref CANONICAL: Selector = Selector::parse(r#"link[rel="canonical"]"#).expect(E);
+    static ref KEYWORDS: Selector = Selector::parse(r#"meta[name="keywords"]"#).expect(E);
+    static ref TIME: Selector = Selector::parse("time").expect(E);
+    static ref PENULIS: Selector = Selector::parse("#penulis").expect(E);
+    static ref EDITOR: Selector = Selector::parse("#editor").expect(E);
+    static ref BODY: Selector = Selector::parse(".txt-article").expect(E);
+    static ref PAGING: Selector = Selector::parse(".paging a").expect(E);
+    static ref P: Selector = Selector::parse("p").expect(E);
+}
+
+const SKIPPED_PREFIXES: [&str; 4] = ["Baca juga", "Baca Juga", "Simak video", "Video:"];
+
+#[derive(Debug, Clone)]
+pub struct TribunnewsCrawler {
+    allowed_hosts: Regex,
+    allow_http: bool,
+}
+
+impl Default for TribunnewsCrawler {
+    fn default() -> Self {
+        TribunnewsCrawler {
+            allowed_hosts: Regex::clone(regex!(r"^([a-z0-9-]+\.)?tribunnews\.com$")),
+            allow_http: false,
+        }
+    }
+}
+
+impl TribunnewsCrawler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only follow links to hosts matching this pattern, tribunnews.com and every regional
+    /// subdomain like jabar.tribunnews.com by default.
+    pub fn with_allowed_hosts(mut self, pattern: Regex) -> Self {
+        self.allowed_hosts = pattern;
+        self
+    }
+
+    /// Also follow plain `http://` links, e.g. to a local mock server.
+    pub fn with_http(mut self, allow_http: bool) -> Self {
+        self.allow_http = allow_http;
+        self
+    }
+
+    fn is_allowed(&self, url: &reqwest::Url) -> bool {
+        let scheme_allowed = match url.scheme() {
+            "https" => true,
+            "http" => self.allow_http,
+            _ => false,
+        };
+        scheme_allowed
+            && url
+                .host_str()
+                .is_some_and(|host| self.allowed_hosts.is_match(host))
+    }
+}
+
+/// The name of a "Penulis: Name" or "Editor: Name" line.
+fn credit(doc: &Html, selector: &Selector) -> Option<String> {
+    doc.select(selector).next().and_then(|el| {
+        let line = el.text().collect::<Vec<_>>().join(" ");
+        let line = line.split_whitespace().join(" ");
+        line.split_once(':')
+            .map(|(_, name)| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+    })
+}
+
+/// The section of `tribunnews.com/<category>/2022/12/12/slug`, or the regional subdomain of
+/// `jabar.tribunnews.com/2022/12/12/slug`.
+fn category(url: &reqwest::Url) -> Option<String> {
+    let first = url.path_segments()?.next().filter(|s| !s.is_empty())?;
+    if !first.chars().all(|c| c.is_ascii_digit()) {
+        return Some(first.to_string());
+    }
+    url.host_str()?
+        .strip_suffix(".tribunnews.com")
+        .filter(|sub| *sub != "www")
+        .map(ToString::to_string)
+}
+
+fn is_in_blockquote(el: &ElementRef) -> bool {
+    el.ancestors()
+        .filter_map(ElementRef::wrap)
+        .any(|a| a.value().name() == "blockquote")
+}
+
+impl Crawler for TribunnewsCrawler {
+    type Document = TribunnewsArticle;
+
+    fn can_be_scrapped(&self, doc: &Html) -> bool {
+        let is_article = json_ld::article(doc).is_some()
+            || utils::meta_content(doc, &OG_TYPE).is_some_and(|t| t == "article");
+        is_article && doc.select(&BODY).next().is_some()
+    }
+
+    fn extract_links(&self, ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        utils::links(doc, ctx.url, |url| self.is_allowed(url))
+    }
+
+    fn crawl(&self, ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        let mut links = self.extract_urls(ctx, doc);
+
+        if !self.can_be_scrapped(doc) {
+            return CrawlerResult::Links(links);
+        }
+
+        let canonical = doc
+            .select(&CANONICAL)
+            .next()
+            .and_then(|el| el.value().attr("href"))
+            .and_then(|s| reqwest::Url::parse(s.trim()).ok());
+
+        // A paged article only shows part of the text, crawl its ?page=all variant instead
+        if doc.select(&PAGING).next().is_some() {
+            if let Some(mut all) = canonical
+                .clone()
+                .filter(|url| url.query() != Some("page=all"))
+            {
+                all.set_query(Some("page=all"));
+                let all = all.to_string();
+                if !links.contains(&all) {
+                    links.push(all);
+                }
+                return CrawlerResult::Links(links);
+            }
+        }
+
+        let ld = json_ld::article(doc);
+        let ld_string = |key: &str| ld.as_ref().and_then(|ld| json_ld::string(ld, key));
+
+        let published_date = ld_string("datePublished")
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .or_else(|| {
+                doc.select(&TIME)
+                    .find_map(|el| utils::parse_indonesian_datetime(&el.text().collect::<String>()))
+            });
+
+        let keywords = utils::meta_content(doc, &KEYWORDS)
+            .map(|s| {
+                s.split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut paragraphs = vec![];
+        if let Some(body) = doc.select(&BODY).next() {
+            for p in body.select(&P).filter(|p| !is_in_blockquote(p)) {
+                if let Some(p) = text::clean_paragraph(&p.inner_html()) {
+                    if !text::starts_with_any(&p, &SKIPPED_PREFIXES) {
+                        paragraphs.push(p);
+                    }
+                }
+            }
+        }
+        paragraphs.dedup();
+
+        let article = TribunnewsArticle {
+            title: utils::meta_content(doc, &TITLE).or_else(|| ld_string("headline")),
+            published_date,
+            description: utils::meta_content(doc, &DESCRIPTION),
+            thumbnail_url: utils::meta_content(doc, &THUMBNAIL),
+            author: credit(doc, &PENULIS),
+            editor: credit(doc, &EDITOR),
+            category: category(canonical.as_ref().unwrap_or(ctx.url)),
+            keywords,
+            paragraphs,
+        };
+        CrawlerResult::document_and_links(article, links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn categories() {
+        let category = |s: &str| category(&reqwest::Url::parse(s).unwrap());
+        assert_eq!(
+            category("https://www.tribunnews.com/nasional/2022/12/12/judul"),
+            Some("nasional".to_string())
+        );
+        assert_eq!(
+            category("https://jabar.tribunnews.com/2022/12/12/judul"),
+            Some("jabar".to_string())
+        );
+        assert_eq!(
+            category("https://www.tribunnews.com/2022/12/12/judul"),
+            None
+        );
+    }
+
+    #[test]
+    fn regional_hosts() {
+        let crawler = TribunnewsCrawler::new();
+        let allowed = |s: &str| crawler.is_allowed(&reqwest::Url::parse(s).unwrap());
+        assert!(allowed("https://www.tribunnews.com/nasional"));
+        assert!(allowed("https://jabar.tribunnews.com/2022/12/12/judul"));
+        assert!(allowed("https://tribunnews.com"));
+        assert!(!allowed("https://tribunnews.com.evil.example"));
+        assert!(!allowed("https://www.tribunnewswiki.com"));
+    }
+}
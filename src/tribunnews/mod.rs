@@ -0,0 +1,40 @@
+mod crawler;
+#[cfg(feature = "sqlite")]
+mod data;
+
+pub use crawler::TribunnewsCrawler;
+#[cfg(feature = "sqlite")]
+pub use data::{TribunnewsArticleTable, TribunnewsData};
+
+use crate::Article;
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TribunnewsArticle {
+    pub title: Option<String>,
+    pub published_date: Option<DateTime<FixedOffset>>,
+    pub description: Option<String>,
+    pub thumbnail_url: Option<String>,
+    pub author: Option<String>,
+    pub editor: Option<String>,
+    /// Section from the url, e.g. `nasional`, or the regional subdomain like `jabar`
+    pub category: Option<String>,
+    pub keywords: Vec<String>,
+    pub paragraphs: Vec<String>,
+}
+
+impl Article for TribunnewsArticle {
+    fn get_paragraphs(&self) -> &[String] {
+        self.paragraphs.as_slice()
+    }
+
+    fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    fn get_published_date(&self) -> Option<DateTime<FixedOffset>> {
+        self.published_date
+    }
+}
@@ -1,6 +1,22 @@
-use chrono::{DateTime, FixedOffset};
+use crate::ExtractedLink;
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone};
+use itertools::Itertools;
+use lazy_regex::regex_captures;
+use lazy_static::lazy_static;
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+#[cfg(feature = "sqlite")]
 use sqlx::SqlitePool;
+use std::collections::HashSet;
 
+lazy_static! {
+    static ref A: Selector = Selector::parse("a").expect("Invalid selector");
+    static ref META_HTTP_EQUIV: Selector =
+        Selector::parse("meta[http-equiv]").expect("Invalid selector");
+    static ref META_NAME: Selector = Selector::parse("meta[name]").expect("Invalid selector");
+}
+
+#[cfg(feature = "sqlite")]
 pub(crate) async fn is_table_exists(
     pool: &SqlitePool,
     table_name: &str,
@@ -14,9 +30,774 @@ pub(crate) async fn is_table_exists(
     )
 }
 
+#[cfg(feature = "sqlite")]
+pub(crate) async fn is_column_exists<'c, E: sqlx::SqliteExecutor<'c>>(
+    executor: E,
+    table_name: &str,
+    column_name: &str,
+) -> Result<bool, sqlx::Error> {
+    // Reading sqlite_master makes SQLite reload a schema altered through another connection,
+    // which pragma_table_info alone doesn't
+    Ok(sqlx::query(
+        "SELECT p.name FROM sqlite_master m, pragma_table_info(m.name) p
+         WHERE m.type = 'table' AND m.name = ? AND p.name = ?",
+    )
+    .bind(table_name)
+    .bind(column_name)
+    .fetch_optional(executor)
+    .await?
+    .is_some())
+}
+
+/// Add `column_name` to an older `table_name` unless it's already there, returning whether
+/// it was added.
+#[cfg(feature = "sqlite")]
+pub(crate) async fn add_column(
+    pool: &SqlitePool,
+    table_name: &str,
+    column_name: &str,
+    definition: &str,
+) -> Result<bool, sqlx::Error> {
+    // On one connection, so its schema is reloaded by the check before being altered
+    let mut conn = pool.acquire().await?;
+    if is_column_exists(&mut *conn, table_name, column_name).await? {
+        return Ok(false);
+    }
+    let query = format!(
+        "ALTER TABLE {} ADD COLUMN {} {}",
+        table_name, column_name, definition
+    );
+    sqlx::query(&query).execute(&mut *conn).await?;
+    Ok(true)
+}
+
 pub(crate) fn get_now() -> DateTime<FixedOffset> {
     DateTime::parse_from_rfc3339(
         &chrono::offset::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
     )
     .unwrap()
 }
+
+/// Whether `host` is `domain` or one of its subdomains.
+pub(crate) fn host_matches(host: &str, domain: &str) -> bool {
+    host == domain
+        || host
+            .strip_suffix(domain)
+            .is_some_and(|sub| sub.ends_with('.'))
+}
+
+/// Whether `url` is https (or http when `allow_http`) on one of `hosts` or their subdomains.
+pub(crate) fn is_url_allowed(url: &Url, hosts: &[String], allow_http: bool) -> bool {
+    let scheme_allowed = match url.scheme() {
+        "https" => true,
+        "http" => allow_http,
+        _ => false,
+    };
+    scheme_allowed
+        && url
+            .host_str()
+            .is_some_and(|host| hosts.iter().any(|domain| host_matches(host, domain)))
+}
+
+/// The hosts a crawler follows links to, with their subdomains, over https unless it also
+/// allows plain http, see `is_url_allowed`.
+#[derive(Debug, Clone)]
+pub(crate) struct HostAllowlist {
+    hosts: Vec<String>,
+    allow_http: bool,
+}
+
+impl HostAllowlist {
+    pub(crate) fn new<I, H>(hosts: I) -> Self
+    where
+        I: IntoIterator<Item = H>,
+        H: Into<String>,
+    {
+        HostAllowlist {
+            hosts: hosts.into_iter().map(Into::into).collect(),
+            allow_http: false,
+        }
+    }
+
+    pub(crate) fn with_hosts<I, H>(self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = H>,
+        H: Into<String>,
+    {
+        HostAllowlist {
+            allow_http: self.allow_http,
+            ..Self::new(hosts)
+        }
+    }
+
+    pub(crate) fn with_http(self, allow_http: bool) -> Self {
+        HostAllowlist { allow_http, ..self }
+    }
+
+    pub(crate) fn allows_http(&self) -> bool {
+        self.allow_http
+    }
+
+    pub(crate) fn allows(&self, url: &Url) -> bool {
+        is_url_allowed(url, &self.hosts, self.allow_http)
+    }
+}
+
+/// The `with_allowed_hosts` and `with_http` builders of a crawler keeping its hosts in an
+/// `allowlist` field, a `HostAllowlist` of `$default` unless set. `$more` ends the doc of
+/// `with_allowed_hosts`.
+macro_rules! allowlist_builders {
+    ($default:literal $(, $more:literal)?) => {
+        #[doc = concat!(
+            "Only follow links to these hosts or their subdomains, `",
+            $default,
+            "` by default.",
+            $(" ", $more)?
+        )]
+        pub fn with_allowed_hosts<I, H>(mut self, hosts: I) -> Self
+        where
+            I: IntoIterator<Item = H>,
+            H: Into<String>,
+        {
+            self.allowlist = self.allowlist.with_hosts(hosts);
+            self
+        }
+
+        /// Also follow plain `http://` links, e.g. to a local mock server.
+        pub fn with_http(mut self, allow_http: bool) -> Self {
+            self.allowlist = self.allowlist.with_http(allow_http);
+            self
+        }
+    };
+}
+pub(crate) use allowlist_builders;
+
+/// The longest url worth crawling, longer ones are tracking or session junk.
+pub(crate) const MAX_URL_LEN: usize = 2048;
+
+/// `url::ParseError`, `url` only being a dependency through reqwest.
+pub(crate) type UrlParseError = <Url as std::str::FromStr>::Err;
+
+/// Why a url is not crawled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum UrlRejection {
+    Invalid(UrlParseError),
+    /// `mailto:`, `javascript:`, `whatsapp:` and every scheme but http(s)
+    Scheme(String),
+    DataUri,
+    EmptyHost,
+    TooLong(usize),
+}
+
+impl UrlRejection {
+    /// The reason stored with the warned url.
+    pub(crate) fn reason(&self) -> &'static str {
+        match self {
+            UrlRejection::Invalid(_) => "invalid_url",
+            UrlRejection::Scheme(_) => "unsupported_scheme",
+            UrlRejection::DataUri => "data_uri",
+            UrlRejection::EmptyHost => "empty_host",
+            UrlRejection::TooLong(_) => "url_too_long",
+        }
+    }
+}
+
+impl std::fmt::Display for UrlRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UrlRejection::Invalid(e) => write!(f, "invalid url: {}", e),
+            UrlRejection::Scheme(scheme) => write!(f, "unsupported scheme {}", scheme),
+            UrlRejection::DataUri => write!(f, "data uri"),
+            UrlRejection::EmptyHost => write!(f, "empty host"),
+            UrlRejection::TooLong(len) => write!(f, "url of {} bytes", len),
+        }
+    }
+}
+
+/// `url` parsed if it is worth fetching: absolute http(s) with a host and at most
+/// `MAX_URL_LEN` bytes.
+pub(crate) fn validate_crawl_url(url: &str) -> Result<Url, UrlRejection> {
+    let url = url.trim();
+    if url.len() > MAX_URL_LEN {
+        return Err(UrlRejection::TooLong(url.len()));
+    }
+    let parsed = match Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(UrlParseError::EmptyHost) => return Err(UrlRejection::EmptyHost),
+        Err(e) => return Err(UrlRejection::Invalid(e)),
+    };
+    match parsed.scheme() {
+        "http" | "https" => {}
+        "data" => return Err(UrlRejection::DataUri),
+        scheme => return Err(UrlRejection::Scheme(scheme.to_string())),
+    }
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err(UrlRejection::EmptyHost);
+    }
+    Ok(parsed)
+}
+
+/// The longest delay of a meta refresh taken as a redirect, longer ones reload the page.
+const MAX_REFRESH_DELAY: u32 = 5;
+
+/// The target of the `<meta http-equiv="refresh" content="0;url=...">` of `html`, the page
+/// at `base`, when it redirects within `MAX_REFRESH_DELAY` seconds.
+pub(crate) fn meta_refresh(html: &str, base: &Url) -> Option<Url> {
+    // Only the rare pages mentioning a refresh are parsed here, the crawler parses them anyway
+    if !html
+        .as_bytes()
+        .windows(7)
+        .any(|w| w.eq_ignore_ascii_case(b"refresh"))
+    {
+        return None;
+    }
+    let doc = Html::parse_document(html);
+    let content = doc
+        .select(&META_HTTP_EQUIV)
+        .find(|meta| {
+            meta.value()
+                .attr("http-equiv")
+                .is_some_and(|equiv| equiv.trim().eq_ignore_ascii_case("refresh"))
+        })?
+        .value()
+        .attr("content")?;
+    let (_, delay, target) = regex_captures!(
+        r#"(?i)^\s*(\d+)\s*[;,]\s*(?:url\s*=\s*)?['"]?([^'"]+)"#,
+        content
+    )?;
+    if delay.parse::<u32>().ok()? > MAX_REFRESH_DELAY {
+        return None;
+    }
+    base.join(target.trim()).ok()
+}
+
+/// The robots directives of a page that change what the crawl does with it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Robots {
+    /// The page must not be stored
+    pub(crate) noindex: bool,
+    /// The links of the page must not be followed
+    pub(crate) nofollow: bool,
+}
+
+impl Robots {
+    /// Add the directives of a comma separated list, e.g. `noindex, nofollow`. Directives
+    /// for a single crawler, like `googlebot: noindex`, aren't for us and are left out.
+    fn add(&mut self, directives: &str) {
+        for directive in directives.split(',') {
+            match directive.trim().to_ascii_lowercase().as_str() {
+                "noindex" => self.noindex = true,
+                "nofollow" => self.nofollow = true,
+                "none" => {
+                    self.noindex = true;
+                    self.nofollow = true;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The directives of the `<meta name="robots">` of `html` and of its `X-Robots-Tag` header.
+pub(crate) fn robots(html: &str, robots_tag: Option<&str>) -> Robots {
+    let mut robots = Robots::default();
+    if let Some(robots_tag) = robots_tag {
+        robots.add(robots_tag);
+    }
+    // Same shortcut as `meta_refresh`, most pages have no robots meta
+    if !html
+        .as_bytes()
+        .windows(6)
+        .any(|w| w.eq_ignore_ascii_case(b"robots"))
+    {
+        return robots;
+    }
+    let doc = Html::parse_document(html);
+    for meta in doc.select(&META_NAME) {
+        let is_robots = meta
+            .value()
+            .attr("name")
+            .is_some_and(|name| name.trim().eq_ignore_ascii_case("robots"));
+        if let Some(content) = meta.value().attr("content").filter(|_| is_robots) {
+            robots.add(content);
+        }
+    }
+    robots
+}
+
+/// The supported sites, all served over https: their `http://` links, left on older pages,
+/// are upgraded rather than dropped.
+pub(crate) const HTTPS_HOSTS: [&str; 18] = [
+    "detik.com",
+    "kompas.com",
+    "tempo.co",
+    "liputan6.com",
+    "tribunnews.com",
+    "antaranews.com",
+    "republika.co.id",
+    "kumparan.com",
+    "tirto.id",
+    "merdeka.com",
+    "sindonews.com",
+    "cnbcindonesia.com",
+    "kontan.co.id",
+    "idntimes.com",
+    "bbc.com",
+    "jawapos.com",
+    "pikiran-rakyat.com",
+    "mediaindonesia.com",
+];
+
+/// `url` on https when it is http on one of `https_hosts` or their subdomains.
+pub(crate) fn upgrade_to_https<H: AsRef<str>>(mut url: Url, https_hosts: &[H]) -> Url {
+    let upgrade = url.scheme() == "http"
+        && url.host_str().is_some_and(|host| {
+            https_hosts
+                .iter()
+                .any(|domain| host_matches(host, domain.as_ref()))
+        });
+    if upgrade {
+        // Switching between two special schemes can't fail
+        let _ = url.set_scheme("https");
+    }
+    url
+}
+
+/// Every anchor with its url: relative ones resolved against `base`, the url of the page,
+/// and http ones on `https_hosts` upgraded to https.
+pub(crate) fn anchor_elements<'a, H: AsRef<str>>(
+    doc: &'a Html,
+    base: &'a Url,
+    https_hosts: &'a [H],
+) -> impl Iterator<Item = (Url, ElementRef<'a>)> + 'a {
+    doc.select(&A)
+        .filter_map(|a| Some((a.value().attr("href")?.trim(), a)))
+        .filter(|(l, _)| !l.is_empty() && !l.starts_with('#'))
+        .filter_map(|(l, a)| Some((base.join(l).ok()?, a)))
+        .map(move |(url, a)| (upgrade_to_https(url, https_hosts), a))
+}
+
+/// `url` without the trailing slash, reusing the string of the url.
+pub(crate) fn link_url(url: Url) -> String {
+    let mut url = String::from(url);
+    let len = url.trim_end_matches('/').len();
+    url.truncate(len);
+    url
+}
+
+/// The link of the anchor `a` to `url`, without the trailing slash.
+pub(crate) fn extracted_link(url: Url, a: &ElementRef) -> ExtractedLink {
+    ExtractedLink {
+        url: link_url(url),
+        text: a.text().collect::<String>().split_whitespace().join(" "),
+        rel: a
+            .value()
+            .attr("rel")
+            .map(str::trim)
+            .filter(|rel| !rel.is_empty())
+            .map(ToString::to_string),
+    }
+}
+
+/// The links of the anchors to the urls `allowed` accepts, see `unique_links`.
+pub(crate) fn links<F: Fn(&Url) -> bool>(doc: &Html, base: &Url, allowed: F) -> Vec<ExtractedLink> {
+    unique_links(
+        anchor_elements(doc, base, &HTTPS_HOSTS)
+            .filter(|(url, _)| allowed(url))
+            .map(|(url, a)| extracted_link(url, &a)),
+    )
+}
+
+/// `links` in their order, only the first link to each url kept.
+pub(crate) fn unique_links<I: IntoIterator<Item = ExtractedLink>>(links: I) -> Vec<ExtractedLink> {
+    first_seen(links.into_iter().collect(), |link| link.url.as_str())
+}
+
+/// `items` in their order without the ones whose `key` was already seen.
+pub(crate) fn first_seen<T, F: Fn(&T) -> &str>(items: Vec<T>, key: F) -> Vec<T> {
+    let kept: Vec<bool> = {
+        let mut seen = HashSet::with_capacity(items.len());
+        items.iter().map(|item| seen.insert(key(item))).collect()
+    };
+    items
+        .into_iter()
+        .zip(kept)
+        .filter_map(|(item, kept)| kept.then_some(item))
+        .collect()
+}
+
+/// The trimmed, non-empty `content` of the first element matching `selector`.
+pub(crate) fn meta_content(doc: &Html, selector: &Selector) -> Option<String> {
+    doc.select(selector)
+        .next()
+        .and_then(|el| el.value().attr("content"))
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(ToString::to_string)
+}
+
+/// Whether one of the ancestors of `el` has the class `class`.
+pub(crate) fn has_ancestor_class(el: &ElementRef, class: &str) -> bool {
+    el.ancestors()
+        .filter_map(ElementRef::wrap)
+        .any(|a| a.value().classes().any(|c| c == class))
+}
+
+/// Full and abbreviated Indonesian names of each month, in lowercase.
+const MONTHS: [&[&str]; 12] = [
+    &["januari", "jan"],
+    &["februari", "pebruari", "feb", "peb"],
+    &["maret", "mar"],
+    &["april", "apr"],
+    &["mei"],
+    &["juni", "jun"],
+    &["juli", "jul"],
+    &["agustus", "agu", "agt", "ags", "agus"],
+    &["september", "sep", "sept"],
+    &["oktober", "okt"],
+    &["november", "nopember", "nov", "nop"],
+    &["desember", "des"],
+];
+
+/// Parse dates printed by Indonesian outlets, e.g. "Senin, 12 Desember 2022 14:01 WIB",
+/// "12 Des 2022, 10:30" or "Rabu, 4 Januari 2023 / 08:30 WITA". WIB, WITA and WIT are
+/// +07:00, +08:00 and +09:00, dates without a zone are in WIB. Dates without a time or with
+/// numeric months are rejected.
+pub(crate) fn parse_indonesian_datetime(s: &str) -> Option<DateTime<FixedOffset>> {
+    let (_, day, month, year, hour, minute, second, zone) = regex_captures!(
+        r"(?i)(?:^|[^\d])(\d{1,2})\s+([a-z]+)\.?\s+(\d{4})\s*[,/|-]?\s*(?:pukul\s+)?(\d{1,2})[:.](\d{2})(?:[:.](\d{2}))?\s*(WITA|WIT|WIB)?\b",
+        s
+    )?;
+    let month = month.to_lowercase();
+    let month = MONTHS
+        .iter()
+        .position(|names| names.contains(&month.as_str()))
+        .map(|i| i as u32 + 1)?;
+    let hours = match zone.to_uppercase().as_str() {
+        "WITA" => 8,
+        "WIT" => 9,
+        _ => 7,
+    };
+    let second = if second.is_empty() {
+        0
+    } else {
+        second.parse().ok()?
+    };
+    let naive = NaiveDate::from_ymd_opt(year.parse().ok()?, month, day.parse().ok()?)?
+        .and_hms_opt(hour.parse().ok()?, minute.parse().ok()?, second)?;
+    FixedOffset::east_opt(hours * 3600)?
+        .from_local_datetime(&naive)
+        .single()
+}
+
+/// The start of `html` up to at most `max_size` bytes, cut right after the last `>` so no
+/// tag is left half written. `html` itself when it's not larger.
+pub(crate) fn truncate_html(html: &str, max_size: usize) -> &str {
+    if html.len() <= max_size {
+        return html;
+    }
+    let mut end = max_size;
+    while !html.is_char_boundary(end) {
+        end -= 1;
+    }
+    let head = &html[..end];
+    match head.rfind('>') {
+        Some(i) => &head[..=i],
+        None => head,
+    }
+}
+
+/// `title` compared with the others: lowercased, punctuation stripped and spaces collapsed.
+/// `None` when nothing is left.
+pub(crate) fn normalize_title(title: &str) -> Option<String> {
+    let stripped: String = title
+        .chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let normalized = stripped.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!normalized.is_empty()).then_some(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn https_upgrade() {
+        let upgraded = |url: &str| upgrade_to_https(Url::parse(url).unwrap(), &HTTPS_HOSTS);
+        assert_eq!(
+            upgraded("http://finance.detik.com/d-1/a?x=1").as_str(),
+            "https://finance.detik.com/d-1/a?x=1"
+        );
+        assert_eq!(
+            upgraded("http://www.kompas.com/").as_str(),
+            "https://www.kompas.com/"
+        );
+        assert_eq!(
+            upgraded("http://example.com/detik.com").as_str(),
+            "http://example.com/detik.com"
+        );
+        assert_eq!(
+            upgraded("http://notdetik.com/a").as_str(),
+            "http://notdetik.com/a"
+        );
+        assert_eq!(
+            upgrade_to_https(Url::parse("http://detik.com/a").unwrap(), &[] as &[&str]).as_str(),
+            "http://detik.com/a"
+        );
+    }
+
+    #[test]
+    fn crawl_urls() {
+        let url = "https://news.detik.com/berita/d-1/judul?page=2";
+        assert_eq!(validate_crawl_url(url).unwrap().as_str(), url);
+        assert!(validate_crawl_url(" http://detik.com/ ").is_ok());
+
+        let rejection = |url: &str| validate_crawl_url(url).unwrap_err();
+        assert!(matches!(rejection("/berita/d-1"), UrlRejection::Invalid(_)));
+        assert!(matches!(
+            rejection("https://exa mple.com"),
+            UrlRejection::Invalid(_)
+        ));
+        assert_eq!(
+            rejection("mailto:redaksi@detik.com"),
+            UrlRejection::Scheme("mailto".to_string())
+        );
+        assert_eq!(
+            rejection("javascript:void(0)"),
+            UrlRejection::Scheme("javascript".to_string())
+        );
+        assert_eq!(
+            rejection("whatsapp://send?text=judul"),
+            UrlRejection::Scheme("whatsapp".to_string())
+        );
+        assert_eq!(
+            rejection("ftp://detik.com/a"),
+            UrlRejection::Scheme("ftp".to_string())
+        );
+        assert_eq!(
+            rejection("data:image/png;base64,iVBORw0KGgo="),
+            UrlRejection::DataUri
+        );
+        assert_eq!(rejection("DATA:text/html,<p>a</p>"), UrlRejection::DataUri);
+        assert_eq!(rejection("https://"), UrlRejection::EmptyHost);
+        assert_eq!(rejection("http://:8080/a"), UrlRejection::EmptyHost);
+
+        let long = format!("https://detik.com/?q={}", "a".repeat(MAX_URL_LEN));
+        assert_eq!(rejection(&long), UrlRejection::TooLong(long.len()));
+        assert_eq!(rejection(&long).reason(), "url_too_long");
+        assert!(validate_crawl_url(&long[..MAX_URL_LEN]).is_ok());
+    }
+
+    #[test]
+    fn indonesian_datetimes() {
+        let cases = [
+            // detik
+            (
+                "Sabtu, 10 Des 2022 13:19 WIB",
+                Some("2022-12-10T13:19:00+07:00"),
+            ),
+            (
+                "Senin, 12 Desember 2022 14:01 WIB",
+                Some("2022-12-12T14:01:00+07:00"),
+            ),
+            // kontan
+            (
+                "Senin, 12 Desember 2022 / 14:05 WIB",
+                Some("2022-12-12T14:05:00+07:00"),
+            ),
+            (
+                "Rabu, 4 Januari 2023 / 08:30 WITA",
+                Some("2023-01-04T08:30:00+08:00"),
+            ),
+            // tribunnews
+            (
+                "Selasa, 3 Januari 2023 07:05 WITA",
+                Some("2023-01-03T07:05:00+08:00"),
+            ),
+            (
+                "Senin, 12 Desember 2022 14:01",
+                Some("2022-12-12T14:01:00+07:00"),
+            ),
+            // liputan6, kumparan, okezone
+            ("12 Des 2022, 10:30 WIB", Some("2022-12-12T10:30:00+07:00")),
+            ("12 Des 2022, 10:30", Some("2022-12-12T10:30:00+07:00")),
+            (
+                "Kamis 15 Desember 2022, 06:09 WIT",
+                Some("2022-12-15T06:09:00+09:00"),
+            ),
+            (
+                "Jumat, 16 Des 2022 - 21:45 WIB",
+                Some("2022-12-16T21:45:00+07:00"),
+            ),
+            (
+                "Diperbarui 5 Agt 2022, 09.15 WIB",
+                Some("2022-08-05T09:15:00+07:00"),
+            ),
+            (
+                "1 Sept 2022 pukul 18.00 WIB",
+                Some("2022-09-01T18:00:00+07:00"),
+            ),
+            (
+                "17 Agustus 2022 10:00:30 WIB",
+                Some("2022-08-17T10:00:30+07:00"),
+            ),
+            ("3 Okt. 2022 08:00", Some("2022-10-03T08:00:00+07:00")),
+            (
+                "9 Nopember 2022 11:11 wib",
+                Some("2022-11-09T11:11:00+07:00"),
+            ),
+            ("29 Peb 2024 12:00", Some("2024-02-29T12:00:00+07:00")),
+            (
+                "Minggu, 1 Mei 2022 00:00 WIB",
+                Some("2022-05-01T00:00:00+07:00"),
+            ),
+            // Without a time the moment of publication is unknown
+            ("Senin, 12 Desember 2022", None),
+            // Numeric months are ambiguous between day-month and month-day
+            ("12/12/2022, 10:30 WIB", None),
+            ("2022-12-12 10:30", None),
+            // Two digit years
+            ("12 Des 22 10:30 WIB", None),
+            // English or misspelt months
+            ("Rabu 14 Dec 2022 14:45 WIB", None),
+            ("Senin, 12 Decembre 2022 14:01", None),
+            // Days and times that don't exist
+            ("Senin, 31 Februari 2022 14:01", None),
+            ("29 Feb 2023 12:00", None),
+            ("12 Des 2022 24:00 WIB", None),
+            ("12 Des 2022 10:61 WIB", None),
+            ("112 Des 2022 10:30 WIB", None),
+            ("", None),
+        ];
+        for (s, expected) in cases {
+            let expected = expected.map(|e| DateTime::parse_from_rfc3339(e).unwrap());
+            assert_eq!(parse_indonesian_datetime(s), expected, "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn meta_refreshes() {
+        let base = Url::parse("https://news.detik.com/berita/d-1/judul").unwrap();
+        let refresh = |content: &str| {
+            let html = format!(
+                r#"<head><meta http-equiv="Refresh" content="{}"></head>"#,
+                content
+            );
+            meta_refresh(&html, &base).map(|url| url.to_string())
+        };
+        assert_eq!(
+            refresh("0;url=/berita/d-2/judul").as_deref(),
+            Some("https://news.detik.com/berita/d-2/judul")
+        );
+        assert_eq!(
+            refresh("3; URL='https://m.detik.com/a'").as_deref(),
+            Some("https://m.detik.com/a")
+        );
+        assert_eq!(refresh("30;url=/berita/d-2/judul"), None);
+        assert_eq!(refresh("0"), None);
+        assert_eq!(meta_refresh("<p>refresh</p>", &base), None);
+    }
+
+    #[test]
+    fn robots_directives() {
+        let meta = |content: &str| format!(r#"<meta name="ROBOTS" content="{}">"#, content);
+        let directives = |noindex, nofollow| Robots { noindex, nofollow };
+        let cases = [
+            (meta("index, follow"), None, directives(false, false)),
+            (meta("noindex"), None, directives(true, false)),
+            (meta("NoFollow"), None, directives(false, true)),
+            (meta("noindex,nofollow"), None, directives(true, true)),
+            (meta("none"), None, directives(true, true)),
+            (
+                "<p>robots</p>".to_string(),
+                Some("noindex"),
+                directives(true, false),
+            ),
+            (meta("nofollow"), Some("noindex"), directives(true, true)),
+            (
+                String::new(),
+                Some("googlebot: noindex"),
+                directives(false, false),
+            ),
+            (
+                r#"<meta name="googlebot" content="noindex">"#.to_string(),
+                None,
+                directives(false, false),
+            ),
+        ];
+        for (html, robots_tag, expected) in cases {
+            assert_eq!(
+                robots(&html, robots_tag),
+                expected,
+                "{} {:?}",
+                html,
+                robots_tag
+            );
+        }
+    }
+
+    #[test]
+    fn truncated_html() {
+        let html = "<p>Ada</p>";
+        assert_eq!(truncate_html(html, 10), html);
+        assert_eq!(truncate_html(html, 100), html);
+        assert_eq!(truncate_html(html, 9), "<p>");
+        assert_eq!(truncate_html(html, 2), "<p");
+        assert_eq!(truncate_html("", 0), "");
+
+        // A liveblog of paragraphs with multi-byte characters, cut at every size
+        let entry = "<div class=\"entry\"><p>Pukul 10.15 – “Gol!” 🎉 ⚽</p></div>\n";
+        let page = format!("<html><body>{}</body></html>", entry.repeat(2000));
+        for max_size in (0..entry.len() * 3).chain([page.len() / 2, page.len() - 1]) {
+            let truncated = truncate_html(&page, max_size);
+            assert!(truncated.len() <= max_size);
+            assert!(page.starts_with(truncated));
+            assert!(
+                truncated.ends_with('>') || !truncated.contains('>'),
+                "{:?}",
+                truncated
+            );
+        }
+        let truncated = truncate_html(&page, page.len() / 2);
+        assert!(truncated.len() > page.len() / 2 - entry.len());
+
+        // No tag before the limit: cut at the last whole character
+        assert_eq!(truncate_html("“”", 4), "“");
+    }
+
+    #[test]
+    fn normalized_titles() {
+        assert_eq!(
+            normalize_title("Banjir Jakarta: 3.000 Warga Mengungsi!").as_deref(),
+            Some("banjir jakarta 3000 warga mengungsi")
+        );
+        assert_eq!(
+            normalize_title("  BANJIR  jakarta, 3000 warga -- mengungsi "),
+            normalize_title("Banjir Jakarta: 3.000 Warga Mengungsi!")
+        );
+        assert_eq!(
+            normalize_title("“Kami Siap,” Kata Menteri").as_deref(),
+            Some("kami siap kata menteri")
+        );
+        assert_eq!(normalize_title(" ?! "), None);
+        assert_eq!(normalize_title(""), None);
+    }
+
+    #[test]
+    fn host_allowlist() {
+        let allowed =
+            |allowlist: &HostAllowlist, url: &str| allowlist.allows(&Url::parse(url).unwrap());
+        let allowlist = HostAllowlist::new(["kompas.com"]);
+        assert!(allowed(&allowlist, "https://kompas.com/read/1"));
+        assert!(allowed(&allowlist, "https://nasional.kompas.com/read/1"));
+        assert!(!allowed(&allowlist, "https://notkompas.com/read/1"));
+        assert!(!allowed(&allowlist, "http://nasional.kompas.com/read/1"));
+
+        let allowlist = allowlist.with_http(true).with_hosts(["127.0.0.1"]);
+        assert!(allowlist.allows_http());
+        assert!(allowed(&allowlist, "http://127.0.0.1/read/1"));
+        assert!(!allowed(&allowlist, "https://nasional.kompas.com/read/1"));
+    }
+}
@@ -0,0 +1,286 @@
+//! An in-memory cache of the visited urls, sparing the storage most of the lookups of the
+//! links found on every page, the navigation of a site being the same from page to page.
+//!
+//! A bloom filter tells the urls surely not visited, which go to the storage as before, from
+//! those likely visited. A small LRU of urls known to be visited answers for the latter, the
+//! others being looked up once and added to it. A url is only ever taken as visited after
+//! the storage said so, or after it was visited through the cache.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::f64::consts::LN_2;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+
+/// The false positive rate the filter is sized for.
+pub const FALSE_POSITIVE_RATE: f64 = 0.01;
+
+/// The urls the filter is sized for at least, whatever was visited before.
+pub const MIN_CAPACITY: usize = 100_000;
+
+/// A set of strings answering "surely not in it" or "likely in it".
+#[derive(Debug, Clone)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    hashes: u32,
+}
+
+impl BloomFilter {
+    /// A filter holding `items` strings with a `false_positive_rate` chance of taking one
+    /// that isn't in it for one that is. It degrades gracefully when more are inserted.
+    pub fn new(items: usize, false_positive_rate: f64) -> Self {
+        let items = items.max(1) as f64;
+        let bits = (-items * false_positive_rate.ln() / (LN_2 * LN_2)).ceil() as usize;
+        let words = bits.max(64).div_ceil(64);
+        let hashes = ((words * 64) as f64 / items * LN_2)
+            .round()
+            .clamp(1.0, 16.0) as u32;
+        BloomFilter {
+            bits: vec![0; words],
+            hashes,
+        }
+    }
+
+    pub fn insert(&mut self, item: &str) {
+        for bit in self.positions(item) {
+            self.bits[bit / 64] |= 1 << (bit % 64);
+        }
+    }
+
+    /// Whether `item` was likely inserted, surely not when `false`.
+    pub fn contains(&self, item: &str) -> bool {
+        self.positions(item)
+            .all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    /// The bits of `item`, by double hashing.
+    fn positions(&self, item: &str) -> impl Iterator<Item = usize> {
+        let hash = |seed: u64| {
+            let mut hasher = DefaultHasher::new();
+            seed.hash(&mut hasher);
+            item.hash(&mut hasher);
+            hasher.finish()
+        };
+        let (h1, h2) = (hash(0), hash(1) | 1);
+        let len = (self.bits.len() * 64) as u64;
+        (0..u64::from(self.hashes))
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % len) as usize)
+    }
+}
+
+/// How the visited urls were looked up through a `VisitedCache`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VisitedCacheStats {
+    /// Answered by the urls known to be visited, without a lookup
+    pub hits: u64,
+    /// Surely not visited by the filter, left to the storage
+    pub misses: u64,
+    /// Likely visited by the filter and looked up, found visited
+    pub verified: u64,
+    /// Likely visited by the filter and looked up, not visited after all
+    pub false_positives: u64,
+}
+
+impl VisitedCacheStats {
+    /// The lookups of the storage, those the cache couldn't answer.
+    pub fn lookups(&self) -> u64 {
+        self.verified + self.false_positives
+    }
+}
+
+/// What a `VisitedCache` knows of a url.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cached {
+    Visited,
+    MaybeVisited,
+    NotVisited,
+}
+
+/// The visited urls, see the module.
+#[derive(Debug)]
+pub struct VisitedCache {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    filter: BloomFilter,
+    hits: Lru,
+    stats: VisitedCacheStats,
+}
+
+impl VisitedCache {
+    /// A cache with a filter sized for `visited` urls, `MIN_CAPACITY` at least, keeping the
+    /// last `hits` urls known to be visited.
+    pub fn new(visited: usize, hits: usize) -> Self {
+        VisitedCache {
+            inner: Mutex::new(Inner {
+                filter: BloomFilter::new(visited.max(MIN_CAPACITY), FALSE_POSITIVE_RATE),
+                hits: Lru::new(hits),
+                stats: VisitedCacheStats::default(),
+            }),
+        }
+    }
+
+    /// Add `url`, visited before the cache was created, to the filter only.
+    pub fn preload(&self, url: &str) {
+        self.inner.lock().unwrap().filter.insert(url);
+    }
+
+    /// Add `url`, just visited.
+    pub fn insert(&self, url: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.filter.insert(url);
+        inner.hits.put(url);
+    }
+
+    /// Forget that `url` was visited. It stays in the filter, which only costs a lookup.
+    pub fn remove(&self, url: &str) {
+        self.inner.lock().unwrap().hits.remove(url);
+    }
+
+    pub fn get(&self, url: &str) -> Cached {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.hits.touch(url) {
+            inner.stats.hits += 1;
+            Cached::Visited
+        } else if inner.filter.contains(url) {
+            Cached::MaybeVisited
+        } else {
+            inner.stats.misses += 1;
+            Cached::NotVisited
+        }
+    }
+
+    /// Record the lookup of `url`, `MaybeVisited` by `get`, which found it `visited` or not.
+    pub fn verified(&self, url: &str, visited: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        if visited {
+            inner.stats.verified += 1;
+            inner.hits.put(url);
+        } else {
+            inner.stats.false_positives += 1;
+        }
+    }
+
+    pub fn stats(&self) -> VisitedCacheStats {
+        self.inner.lock().unwrap().stats
+    }
+}
+
+/// The last `capacity` strings used.
+#[derive(Debug)]
+struct Lru {
+    capacity: usize,
+    ticks: HashMap<String, u64>,
+    order: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+impl Lru {
+    fn new(capacity: usize) -> Self {
+        Lru {
+            capacity,
+            ticks: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// Whether `item` is in it, used last if it is.
+    fn touch(&mut self, item: &str) -> bool {
+        match self.ticks.get_mut(item) {
+            Some(tick) => {
+                let item = self.order.remove(tick).unwrap();
+                self.tick += 1;
+                *tick = self.tick;
+                self.order.insert(self.tick, item);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn put(&mut self, item: &str) {
+        if self.capacity == 0 || self.touch(item) {
+            return;
+        }
+        if self.ticks.len() == self.capacity {
+            if let Some((_, oldest)) = self.order.pop_first() {
+                self.ticks.remove(&oldest);
+            }
+        }
+        self.tick += 1;
+        self.ticks.insert(item.to_string(), self.tick);
+        self.order.insert(self.tick, item.to_string());
+    }
+
+    fn remove(&mut self, item: &str) {
+        if let Some(tick) = self.ticks.remove(item) {
+            self.order.remove(&tick);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(i: usize) -> String {
+        format!("https://news.detik.com/berita/d-{}/judul", i)
+    }
+
+    #[test]
+    fn filter_false_positives() {
+        let mut filter = BloomFilter::new(10_000, FALSE_POSITIVE_RATE);
+        for i in 0..10_000 {
+            filter.insert(&url(i));
+        }
+        assert!((0..10_000).all(|i| filter.contains(&url(i))));
+        let false_positives = (10_000..20_000)
+            .filter(|&i| filter.contains(&url(i)))
+            .count();
+        assert!(false_positives < 200, "{}", false_positives);
+    }
+
+    #[test]
+    fn lru_evicts_least_recent() {
+        let mut lru = Lru::new(2);
+        lru.put("a");
+        lru.put("b");
+        assert!(lru.touch("a"));
+        lru.put("c");
+        assert!(lru.touch("a"));
+        assert!(!lru.touch("b"));
+        assert!(lru.touch("c"));
+        lru.remove("c");
+        assert!(!lru.touch("c"));
+        assert_eq!(lru.ticks.len(), lru.order.len());
+    }
+
+    #[test]
+    fn cache_lookups() {
+        let cache = VisitedCache::new(0, 2);
+        cache.preload(&url(1));
+        cache.insert(&url(2));
+        assert_eq!(cache.get(&url(1)), Cached::MaybeVisited);
+        cache.verified(&url(1), true);
+        assert_eq!(cache.get(&url(1)), Cached::Visited);
+        assert_eq!(cache.get(&url(2)), Cached::Visited);
+        assert_eq!(cache.get(&url(3)), Cached::NotVisited);
+
+        // No longer visited, left to the lookups
+        cache.remove(&url(2));
+        assert_eq!(cache.get(&url(2)), Cached::MaybeVisited);
+        cache.verified(&url(2), false);
+        assert_eq!(
+            cache.stats(),
+            VisitedCacheStats {
+                hits: 2,
+                misses: 1,
+                verified: 1,
+                false_positives: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! Counts the allocations of link extraction, the hot path of a crawl, with a counting
+//! global allocator. Kept to one test so no other test allocates meanwhile.
+
+use indonesian_media_crawler::detik::DetikCrawler;
+use indonesian_media_crawler::{CrawlContext, Crawler};
+use reqwest::Url;
+use scraper::Html;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let result = f();
+    (result, ALLOCATIONS.load(Ordering::Relaxed) - before)
+}
+
+#[test]
+fn link_extraction() {
+    let html = std::fs::read_to_string("tests/htmls/1.html").unwrap();
+    let doc = Html::parse_document(&html);
+    let url = Url::parse("https://news.detik.com/berita/d-6453785/judul").unwrap();
+    let ctx = CrawlContext::new(&url);
+    let crawler = DetikCrawler::new();
+
+    let (links, with_texts) = allocations(|| crawler.extract_links(&ctx, &doc));
+    let (urls, without_texts) = allocations(|| crawler.extract_urls(&ctx, &doc));
+    let anchors = html.matches("<a").count();
+    println!(
+        "{} anchors, {} links: {} allocations with texts, {} without",
+        anchors,
+        urls.len(),
+        with_texts,
+        without_texts
+    );
+
+    assert_eq!(
+        urls,
+        links.into_iter().map(|link| link.url).collect::<Vec<_>>()
+    );
+    assert!(without_texts < with_texts);
+    // The url of each anchor is parsed once, its string growing a few times, and kept as the
+    // link with no copy on top
+    assert!(without_texts < 3 * anchors, "{} allocations", without_texts);
+}
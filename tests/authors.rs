@@ -0,0 +1,122 @@
+//! Stores articles whose bylines spell the same authors differently and checks the author
+//! queries count them as one.
+
+use chrono::{DateTime, FixedOffset};
+use indonesian_media_crawler::detik::{AuthorCount, DetikArticle, DetikData};
+use indonesian_media_crawler::Storage;
+use std::path::Path;
+
+fn article(author: Option<&str>, published_date: &str) -> DetikArticle {
+    DetikArticle {
+        title: Some(format!("{:?} {}", author, published_date)),
+        published_date: Some(DateTime::parse_from_rfc3339(published_date).unwrap()),
+        description: None,
+        thumbnail_url: None,
+        author: author.map(ToString::to_string),
+        desk: None,
+        category: None,
+        language: None,
+        keywords: vec![],
+        keywords_raw: None,
+        videos: vec![],
+        photos: vec![],
+        body_selector: None,
+        paragraphs: vec!["p".to_string()],
+    }
+}
+
+fn count(name: &str, articles: u32) -> AuthorCount {
+    AuthorCount {
+        name: name.to_string(),
+        articles,
+    }
+}
+
+#[tokio::test]
+async fn aggregate_by_normalized_author() {
+    if Path::new("test_authors.db").is_file() {
+        std::fs::remove_file("test_authors.db").unwrap();
+    }
+    let storage = DetikData::new("test_authors").await.unwrap();
+    let articles = [
+        ("d-1", Some("Andi Saputra"), "2022-12-01T08:00:00+07:00"),
+        (
+            "d-2",
+            Some("Andi Saputra - detikNews"),
+            "2022-12-05T08:00:00+07:00",
+        ),
+        ("d-3", Some("andi saputra"), "2022-12-09T08:00:00+07:00"),
+        ("d-4", Some("Mulia Budi"), "2022-12-06T08:00:00+07:00"),
+        (
+            "d-5",
+            Some("MULIA BUDI | detikFinance"),
+            "2022-12-08T08:00:00+07:00",
+        ),
+        ("d-6", Some("Tim detikBali"), "2022-12-09T08:00:00+07:00"),
+        ("d-7", None, "2022-12-09T08:00:00+07:00"),
+    ];
+    for (id, author, published_date) in articles {
+        let url = format!("https://news.detik.com/berita/{}/judul", id);
+        storage
+            .results_insert((url, article(author, published_date)))
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(
+        storage.results.top_authors(10, None).await.unwrap(),
+        vec![
+            count("Andi Saputra", 3),
+            count("Mulia Budi", 2),
+            count("Tim detikBali", 1)
+        ]
+    );
+    assert_eq!(
+        storage.results.top_authors(1, None).await.unwrap(),
+        vec![count("Andi Saputra", 3)]
+    );
+
+    // d-2 at 08:00 WIB is 01:00 UTC, before 07:30 UTC even if its text sorts after it
+    let since = DateTime::<FixedOffset>::parse_from_rfc3339("2022-12-05T07:30:00Z").unwrap();
+    assert_eq!(
+        storage.results.top_authors(10, Some(since)).await.unwrap(),
+        vec![
+            count("Mulia Budi", 2),
+            count("Andi Saputra", 1),
+            count("Tim detikBali", 1)
+        ]
+    );
+
+    let urls = |articles: Vec<(String, DetikArticle)>| -> Vec<String> {
+        articles.into_iter().map(|(url, _)| url).collect()
+    };
+    assert_eq!(
+        urls(
+            storage
+                .results
+                .articles_by_author("ANDI SAPUTRA")
+                .await
+                .unwrap()
+        ),
+        vec![
+            "https://news.detik.com/berita/d-1/judul",
+            "https://news.detik.com/berita/d-2/judul",
+            "https://news.detik.com/berita/d-3/judul"
+        ]
+    );
+    let (_, article) = storage
+        .results
+        .articles_by_author("Mulia Budi - detikNews")
+        .await
+        .unwrap()
+        .remove(1);
+    assert_eq!(article.author.as_deref(), Some("MULIA BUDI | detikFinance"));
+    assert!(storage
+        .results
+        .articles_by_author("Budi")
+        .await
+        .unwrap()
+        .is_empty());
+
+    std::fs::remove_file("test_authors.db").unwrap();
+}
@@ -0,0 +1,142 @@
+//! Crawls pages of a known size within a bandwidth budget: stopped once it received
+//! `RunOptions::max_bytes`, and its requests spaced out while it's over
+//! `RunOptions::bytes_per_hour`.
+
+use axum::{http::Uri, response::Html, Router};
+use indonesian_media_crawler::memory::MemoryStorage;
+use indonesian_media_crawler::report::{CrawlReport, StopReason};
+use indonesian_media_crawler::testing::MockCrawler;
+use indonesian_media_crawler::{run_scrapper, Article, CrawlerResult, RunOptions, Storage};
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex};
+use tokio::time::{timeout, Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Page {
+    paragraphs: Vec<String>,
+}
+
+impl Article for Page {
+    fn get_paragraphs(&self) -> &[String] {
+        &self.paragraphs
+    }
+}
+
+/// The size of every page served.
+const PAGE_SIZE: usize = 10_000;
+
+const ARTICLES: usize = 20;
+
+/// A site serving pages of `PAGE_SIZE` bytes, recording when they were requested.
+fn start_site(requests: Arc<Mutex<Vec<Instant>>>) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new().fallback(move |uri: Uri| {
+        let requests = Arc::clone(&requests);
+        async move {
+            requests.lock().unwrap().push(Instant::now());
+            let page = MockCrawler::<Page>::page(&format!("http://{}{}", addr, uri));
+            let padding = " ".repeat(PAGE_SIZE - page.len());
+            Html(page + &padding)
+        }
+    });
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service()),
+    );
+    addr
+}
+
+fn crawler() -> MockCrawler<Page> {
+    let page = Page {
+        paragraphs: vec!["Isi.".to_string()],
+    };
+    MockCrawler::new().with("/a/", CrawlerResult::document_and_links(page, vec![]))
+}
+
+/// One page fetched at a time, from a list of `articles`.
+fn options() -> RunOptions {
+    RunOptions {
+        max_fetching: 1,
+        request_delay: Duration::from_millis(20),
+        follow_links: false,
+        ..RunOptions::default()
+    }
+}
+
+async fn crawl(
+    storage: &MemoryStorage<Page>,
+    articles: usize,
+    options: RunOptions,
+) -> (CrawlReport, Vec<Instant>) {
+    let requests = Arc::new(Mutex::new(vec![]));
+    let base = format!("http://{}", start_site(Arc::clone(&requests)));
+    let urls = (0..articles).map(|i| format!("{}/a/{}", base, i)).collect();
+    let report = timeout(
+        Duration::from_secs(30),
+        run_scrapper(crawler(), storage.clone(), urls, options),
+    )
+    .await
+    .expect("Crawl did not finish")
+    .unwrap();
+    let requests = requests.lock().unwrap().clone();
+    (report, requests)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stopped_within_a_page_of_max_bytes() {
+    let max_bytes = (PAGE_SIZE * 11 / 2) as u64;
+    let storage = MemoryStorage::<Page>::new();
+    let options = RunOptions {
+        max_bytes: Some(max_bytes),
+        ..options()
+    };
+    let (report, _) = crawl(&storage, ARTICLES, options).await;
+
+    assert_eq!(report.stop_reason, StopReason::MaxBytes);
+    assert!(report.bytes_received >= max_bytes);
+    assert!(
+        report.bytes_received < max_bytes + PAGE_SIZE as u64,
+        "{} bytes received",
+        report.bytes_received
+    );
+    assert_eq!(report.fetched, 6);
+    // The page that went over the budget stored too
+    assert_eq!(storage.results_count().await.unwrap(), 6);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn requests_spaced_out_over_bytes_per_hour() {
+    let gaps = |requests: &[Instant]| -> Vec<Duration> {
+        requests.windows(2).map(|w| w[1] - w[0]).collect()
+    };
+    let (report, requests) = crawl(&MemoryStorage::new(), 6, options()).await;
+    assert_eq!(report.stop_reason, StopReason::Idle);
+    let unthrottled = gaps(&requests);
+
+    // A page over the first minute is 600 KB per hour, twice the target, then more with
+    // every page: the delays double, then grow by as much each time
+    let options = RunOptions {
+        bytes_per_hour: Some(300_000),
+        ..options()
+    };
+    let (report, requests) = crawl(&MemoryStorage::new(), 6, options).await;
+    assert_eq!(report.stop_reason, StopReason::Idle);
+    let throttled = gaps(&requests);
+    assert_eq!(throttled.len(), 5);
+    for (pages, gap) in throttled.iter().enumerate().skip(1) {
+        assert!(
+            *gap >= Duration::from_millis(20 * 2 * pages as u64),
+            "{:?}",
+            throttled
+        );
+    }
+    let total = |gaps: &[Duration]| gaps.iter().sum::<Duration>();
+    assert!(
+        total(&throttled) > total(&unthrottled) * 2,
+        "{:?} against {:?}",
+        throttled,
+        unthrottled
+    );
+}
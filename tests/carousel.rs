@@ -0,0 +1,184 @@
+//! Crawls an article with a photo carousel from 127.0.0.1: the slides whose caption the
+//! carousel lacks are read by the `CarouselCrawler` while it's crawled, and none of them is
+//! fetched on its own, stored or warned.
+
+use axum::{extract::State, http::Uri, response::Html, Router};
+use indonesian_media_crawler::detik::{CarouselCrawler, DetikCrawler, DetikData};
+use indonesian_media_crawler::{run_scrapper, RunOptions, Storage};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+use tokio::time::{timeout, Duration};
+
+type Hits = Arc<Mutex<Vec<String>>>;
+
+const ARTICLE: &str = "/berita/d-6455015/banjir-bandang-terjang-garut-puluhan-rumah-rusak";
+
+fn slide(n: usize) -> String {
+    format!("{}/foto-{}", ARTICLE, n)
+}
+
+/// The slide pages, with their caption or only an `og:description`.
+fn slide_page(n: usize) -> String {
+    let caption = match n {
+        3 => r#"<div class="detail__media-caption">Jembatan   desa putus diterjang arus.</div>"#
+            .to_string(),
+        _ => String::new(),
+    };
+    format!(
+        r#"<html><head><meta name="dtk:contenttype" content="singlepagegallery">
+        <meta property="og:description" content="Foto {} dari 4: tenda pengungsian.">
+        </head><body>{}</body></html>"#,
+        n, caption
+    )
+}
+
+fn site(base: &str) -> HashMap<String, String> {
+    let article = std::fs::read_to_string("tests/htmls/carousel.html")
+        .unwrap()
+        .replace("https://news.detik.com", base);
+    let mut pages = HashMap::from([
+        (
+            "/".to_string(),
+            format!(
+                r#"<html><body><a href="{}{}">Garut</a></body></html>"#,
+                base, ARTICLE
+            ),
+        ),
+        (ARTICLE.to_string(), article),
+    ]);
+    pages.extend((1..=4).map(|n| (slide(n), slide_page(n))));
+    pages
+}
+
+async fn start_site() -> (SocketAddr, Hits) {
+    let hits: Hits = Arc::new(Mutex::new(vec![]));
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let pages = Arc::new(site(&format!("http://{}", addr)));
+
+    let app = Router::new()
+        .fallback(
+            |State((pages, hits)): State<(Arc<HashMap<String, String>>, Hits)>,
+             uri: Uri| async move {
+                hits.lock().unwrap().push(uri.path().to_string());
+                Html(pages.get(uri.path()).cloned().unwrap_or_default())
+            },
+        )
+        .with_state((pages, hits.clone()));
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service()),
+    );
+    (addr, hits)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn slides_merged_into_their_article() {
+    if std::path::Path::new("test_carousel.db").is_file() {
+        std::fs::remove_file("test_carousel.db").unwrap();
+    }
+    let (addr, hits) = start_site().await;
+    let base = format!("http://{}", addr);
+
+    let crawler = CarouselCrawler::new(
+        DetikCrawler::new()
+            .with_allowed_hosts(["127.0.0.1"])
+            .with_http(true),
+    );
+    let options = RunOptions {
+        request_delay: Duration::from_millis(10),
+        exit_when_idle: true,
+        ..RunOptions::default()
+    };
+    let storage = DetikData::new("test_carousel").await.unwrap();
+    timeout(
+        Duration::from_secs(60),
+        run_scrapper(crawler, storage, vec![base.clone()], options),
+    )
+    .await
+    .expect("Crawl did not finish")
+    .unwrap();
+
+    let storage = DetikData::new("test_carousel").await.unwrap();
+    assert_eq!(storage.results_count().await.unwrap(), 1);
+    assert_eq!(storage.warned_count().await.unwrap(), 0);
+
+    let articles = storage.results.articles().await.unwrap();
+    let (url, article) = &articles[0];
+    assert_eq!(url, &format!("{}{}", base, ARTICLE));
+    let captions: Vec<_> = article
+        .photos
+        .iter()
+        .map(|photo| photo.caption.as_deref())
+        .collect();
+    assert_eq!(
+        captions,
+        vec![
+            Some("Banjir bandang menerjang permukiman warga di Garut."),
+            Some("Warga membersihkan lumpur dari rumahnya."),
+            Some("Jembatan desa putus diterjang arus."),
+            Some("Foto 4 dari 4: tenda pengungsian."),
+        ]
+    );
+
+    // Only the slides without a caption in the carousel are fetched, once, for the article
+    let hits = hits.lock().unwrap().clone();
+    for n in 1..=4 {
+        let fetched = hits.iter().filter(|path| **path == slide(n)).count();
+        assert_eq!(fetched, usize::from(n > 2), "{}", slide(n));
+        let url = format!("{}{}", base, slide(n));
+        assert!(
+            storage.visited_is_exists(url.as_str()).await.unwrap(),
+            "{}",
+            url
+        );
+    }
+    assert_eq!(storage.running_count().await.unwrap(), 0);
+    assert!(storage.queued_is_empty().await.unwrap());
+
+    std::fs::remove_file("test_carousel.db").unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn slides_fetched_up_to_max_slides() {
+    if std::path::Path::new("test_carousel_max.db").is_file() {
+        std::fs::remove_file("test_carousel_max.db").unwrap();
+    }
+    let (addr, hits) = start_site().await;
+    let base = format!("http://{}", addr);
+
+    let crawler = CarouselCrawler::new(
+        DetikCrawler::new()
+            .with_allowed_hosts(["127.0.0.1"])
+            .with_http(true),
+    )
+    .with_max_slides(1);
+    let options = RunOptions {
+        request_delay: Duration::from_millis(10),
+        exit_when_idle: true,
+        ..RunOptions::default()
+    };
+    let storage = DetikData::new("test_carousel_max").await.unwrap();
+    timeout(
+        Duration::from_secs(60),
+        run_scrapper(crawler, storage, vec![base.clone()], options),
+    )
+    .await
+    .expect("Crawl did not finish")
+    .unwrap();
+
+    let storage = DetikData::new("test_carousel_max").await.unwrap();
+    let articles = storage.results.articles().await.unwrap();
+    let photos = &articles[0].1.photos;
+    assert_eq!(photos.len(), 4);
+    assert!(photos[2].caption.is_some());
+    assert_eq!(photos[3].caption, None);
+    let hits = hits.lock().unwrap().clone();
+    assert!(!hits.contains(&slide(4)));
+
+    std::fs::remove_file("test_carousel_max.db").unwrap();
+}
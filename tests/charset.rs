@@ -0,0 +1,157 @@
+//! Pages served in another charset than UTF-8, or declaring the wrong one, stored with their
+//! text decoded correctly, and pages that fail to decode warned as `BAD_ENCODING`.
+
+use axum::{
+    extract::Path,
+    http::{header, StatusCode},
+    routing::get,
+    Router,
+};
+use indonesian_media_crawler::memory::MemoryStorage;
+use indonesian_media_crawler::{
+    run_scrapper, Article, CrawlContext, Crawler, CrawlerResult, ExtractedLink, RunOptions,
+    Storage, BAD_ENCODING,
+};
+use scraper::{Html, Selector};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use tokio::time::{sleep, timeout, Duration};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Page {
+    title: Option<String>,
+    paragraphs: Vec<String>,
+}
+
+impl Article for Page {
+    fn get_paragraphs(&self) -> &[String] {
+        &self.paragraphs
+    }
+
+    fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+}
+
+struct PageCrawler;
+
+impl Crawler for PageCrawler {
+    type Document = Page;
+
+    fn can_be_scrapped(&self, _doc: &Html) -> bool {
+        true
+    }
+
+    fn crawl(&self, _ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        let text = |selector: &str| {
+            doc.select(&Selector::parse(selector).unwrap())
+                .map(|e| e.text().collect::<String>())
+                .collect::<Vec<_>>()
+        };
+        let page = Page {
+            title: text("title").pop(),
+            paragraphs: text("p"),
+        };
+        CrawlerResult::document_and_links(page, vec![])
+    }
+
+    fn extract_links(&self, _ctx: &CrawlContext, _doc: &Html) -> Vec<ExtractedLink> {
+        vec![]
+    }
+}
+
+/// `/latin1` served as ISO-8859-1, `/mislabeled` in windows-1252 served as UTF-8 but declaring
+/// its charset in a `<meta>` tag, and `/garbled` not text in any charset.
+fn start_site() -> SocketAddr {
+    let app = Router::new().route(
+        "/:page",
+        get(|Path(page): Path<String>| async move {
+            let (charset, body) = match page.as_str() {
+                "latin1" => ("iso-8859-1", include_bytes!("charset/latin1.html").to_vec()),
+                "mislabeled" => ("utf-8", include_bytes!("charset/mislabeled.html").to_vec()),
+                "garbled" => {
+                    let mut body = b"<html><body><p>".to_vec();
+                    body.extend((0..512).map(|i| 0x80 | (i % 0x40) as u8));
+                    body.extend(b"</p></body></html>");
+                    ("utf-8", body)
+                }
+                _ => return Err(StatusCode::NOT_FOUND),
+            };
+            let content_type = format!("text/html; charset={}", charset);
+            Ok(([(header::CONTENT_TYPE, content_type)], body))
+        }),
+    );
+    let server = axum::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(app.into_make_service());
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pages_decoded_from_their_charset() {
+    let site = start_site();
+    let base = format!("http://{}", site);
+    let url = |page: &str| format!("{}/{}", base, page);
+    let storage = MemoryStorage::<Page>::new();
+    let options = RunOptions {
+        disable_politeness_for_hosts: vec![site.to_string()],
+        ..RunOptions::default()
+    };
+
+    let controller = options.controller.clone();
+    let watched = storage.clone();
+    tokio::spawn(async move {
+        while watched.visited_count().await.unwrap() + watched.warned_count().await.unwrap() < 3
+            || watched.running_count().await.unwrap() > 0
+        {
+            sleep(Duration::from_millis(20)).await;
+        }
+        controller.stop();
+    });
+    let report = timeout(
+        Duration::from_secs(30),
+        run_scrapper(
+            PageCrawler,
+            storage.clone(),
+            vec![url("latin1"), url("mislabeled"), url("garbled")],
+            options,
+        ),
+    )
+    .await
+    .expect("Crawl did not finish")
+    .unwrap();
+
+    let latin1 = storage.results_get(&url("latin1")).await.unwrap().unwrap();
+    assert_eq!(latin1.title.as_deref(), Some("Kafe di Bandung"));
+    assert_eq!(
+        latin1.paragraphs,
+        vec![
+            "Suhu Bandung pagi ini 18°C, pengunjung kafé memadati Jalan Braga.",
+            "Harga kopi naik menjadi Rp 25.000 · kata pemilik, « bahan baku mahal ».",
+        ]
+    );
+
+    let mislabeled = storage
+        .results_get(&url("mislabeled"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(mislabeled.title.as_deref(), Some("Bandara Soekarno–Hatta"));
+    assert_eq!(
+        mislabeled.paragraphs,
+        vec![
+            "Penerbangan dari Soekarno–Hatta tertunda — cuaca buruk.",
+            "Penumpang menyebutnya “pengalaman yang melelahkan”.",
+        ]
+    );
+
+    assert_eq!(storage.results_get(&url("garbled")).await.unwrap(), None);
+    let warned = storage.warned_get(&url("garbled")).await.unwrap().unwrap();
+    assert_eq!(warned.reason.as_deref(), Some(BAD_ENCODING));
+
+    assert_eq!(report.transcoded, 2);
+    assert_eq!(
+        report.warned_reasons,
+        BTreeMap::from([(BAD_ENCODING.to_string(), 1)])
+    );
+}
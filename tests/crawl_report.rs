@@ -0,0 +1,190 @@
+//! The report `run_scrapper` returns and writes once a session stops, checked against what
+//! the crawl stored.
+
+use axum::{http::Uri, response::Html, Router};
+use indonesian_media_crawler::memory::MemoryStorage;
+use indonesian_media_crawler::report::{CrawlReport, StopReason};
+use indonesian_media_crawler::testing::MockCrawler;
+use indonesian_media_crawler::{run_scrapper, Article, CrawlerResult, RunOptions, Storage};
+use std::collections::BTreeMap;
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+use tokio::time::{sleep, timeout, Duration};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Page {
+    steps: Vec<String>,
+}
+
+impl Article for Page {
+    fn get_paragraphs(&self) -> &[String] {
+        &self.steps
+    }
+}
+
+fn start_site() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new().fallback(move |uri: Uri| async move {
+        Html(MockCrawler::<Page>::page(&format!(
+            "http://{}{}",
+            addr, uri
+        )))
+    });
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service()),
+    );
+    addr
+}
+
+/// A hub linking to two articles, an empty one and a paywalled one.
+fn crawler(base: &str) -> MockCrawler<Page> {
+    let page = |steps: &[&str]| Page {
+        steps: steps.iter().map(ToString::to_string).collect(),
+    };
+    MockCrawler::new()
+        .with(
+            "/a/1",
+            CrawlerResult::document_and_links(page(&["Satu"]), vec![]),
+        )
+        .with(
+            "/a/2",
+            CrawlerResult::document_and_links(page(&["Dua"]), vec![]),
+        )
+        .with(
+            "/a/empty",
+            CrawlerResult::document_and_links(page(&[]), vec![]),
+        )
+        .with(
+            "/a/paid",
+            CrawlerResult::Skipped("paywalled".to_string(), vec![]),
+        )
+        .with(
+            "/hub",
+            CrawlerResult::Links(
+                ["1", "2", "empty", "paid"]
+                    .iter()
+                    .map(|page| format!("{}/a/{}", base, page))
+                    .collect(),
+            ),
+        )
+}
+
+async fn crawl(storage: MemoryStorage<Page>, options: RunOptions) -> CrawlReport {
+    let base = format!("http://{}", start_site());
+    let hub = format!("{}/hub", base);
+    timeout(
+        Duration::from_secs(30),
+        run_scrapper(crawler(&base), storage, vec![hub], options),
+    )
+    .await
+    .expect("Crawl did not finish")
+    .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn report_matches_storage() {
+    let path = std::env::temp_dir().join(format!("crawl_report_{}.json", std::process::id()));
+    let storage = MemoryStorage::<Page>::new();
+    let options = RunOptions {
+        request_delay: Duration::from_millis(10),
+        report_path: Some(path.clone()),
+        ..RunOptions::default()
+    };
+
+    // Stop once every page has been handled
+    let controller = options.controller.clone();
+    let watched = storage.clone();
+    tokio::spawn(async move {
+        while watched.visited_count().await.unwrap() + watched.warned_count().await.unwrap() < 5
+            || watched.running_count().await.unwrap() > 0
+        {
+            sleep(Duration::from_millis(20)).await;
+        }
+        controller.stop();
+    });
+    let report = crawl(storage.clone(), options).await;
+
+    assert_eq!(report.stop_reason, StopReason::Stopped);
+    assert!(report.started_at <= report.ended_at);
+    assert_eq!(
+        report.extracted,
+        u64::from(storage.results_count().await.unwrap())
+    );
+    assert_eq!(
+        report.warned,
+        u64::from(storage.warned_count().await.unwrap())
+    );
+    assert_eq!(
+        report.fetched,
+        u64::from(storage.visited_count().await.unwrap() + storage.warned_count().await.unwrap())
+    );
+    assert_eq!(report.hosts, BTreeMap::from([("127.0.0.1".to_string(), 5)]));
+    assert_eq!(report.latency.fetch.count, 5);
+    assert!(report.latency.first_byte.p99_ms <= report.latency.fetch.p99_ms);
+    assert_eq!(
+        report.latency_by_host.keys().collect::<Vec<_>>(),
+        vec!["127.0.0.1"]
+    );
+    let mut reasons = BTreeMap::new();
+    for warned in storage.warned_recent(10).await.unwrap() {
+        *reasons.entry(warned.reason.unwrap()).or_insert(0) += 1;
+    }
+    assert_eq!(report.warned_reasons, reasons);
+    assert_eq!(report.warned_reasons.len(), 2);
+    assert_eq!(report.failed_tasks, 0);
+
+    // The file holds the report returned
+    let written: CrawlReport =
+        serde_json::from_slice(&tokio::fs::read(&path).await.unwrap()).unwrap();
+    assert_eq!(written, report);
+    tokio::fs::remove_file(&path).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unwritable_report_path() {
+    let storage = MemoryStorage::<Page>::new();
+    let options = RunOptions {
+        request_delay: Duration::from_millis(10),
+        max_results: Some(1),
+        report_path: Some(PathBuf::from("/nonexistent/crawl/report.json")),
+        ..RunOptions::default()
+    };
+    let report = crawl(storage.clone(), options).await;
+    assert_eq!(report.stop_reason, StopReason::MaxResults);
+    assert!(report.extracted >= 1);
+    assert_eq!(
+        report.extracted,
+        u64::from(storage.results_count().await.unwrap())
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stopped_without_failed_tasks() {
+    let base = format!("http://{}", start_site());
+    // More articles than the permits of the dispatcher, so it's still sending when it stops
+    let articles: Vec<String> = (0..50).map(|i| format!("{}/a/{}", base, i)).collect();
+    let page = Page {
+        steps: vec!["Satu".to_string()],
+    };
+    let crawler = MockCrawler::new()
+        .with("/a/", CrawlerResult::document_and_links(page, vec![]))
+        .with("/hub", CrawlerResult::Links(articles));
+    let storage = MemoryStorage::<Page>::new();
+    let options = RunOptions {
+        request_delay: Duration::from_millis(1),
+        max_results: Some(3),
+        ..RunOptions::default()
+    };
+    let report = timeout(
+        Duration::from_secs(30),
+        run_scrapper(crawler, storage, vec![format!("{}/hub", base)], options),
+    )
+    .await
+    .expect("Crawl did not finish")
+    .unwrap();
+    assert_eq!(report.stop_reason, StopReason::MaxResults);
+    assert_eq!(report.failed_tasks, 0);
+}
@@ -0,0 +1,150 @@
+//! How a downstream crate can check its own crawl pipeline with the test doubles
+//! from the `testing` feature: canned crawler results, an in-memory store and the
+//! storage calls `run_scrapper` made.
+
+use axum::{http::Uri, response::Html, Router};
+use indonesian_media_crawler::memory::MemoryStorage;
+use indonesian_media_crawler::testing::{MockCrawler, RecordingStorage};
+use indonesian_media_crawler::{run_scrapper, Article, CrawlerResult, RunOptions, Storage};
+use std::net::{SocketAddr, TcpListener};
+use tokio::time::{sleep, timeout, Duration};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Recipe {
+    name: String,
+    steps: Vec<String>,
+}
+
+impl Article for Recipe {
+    fn get_paragraphs(&self) -> &[String] {
+        &self.steps
+    }
+
+    fn get_title(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+}
+
+fn start_site() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new().fallback(move |uri: Uri| async move {
+        Html(MockCrawler::<Recipe>::page(&format!(
+            "http://{}{}",
+            addr, uri
+        )))
+    });
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service()),
+    );
+    addr
+}
+
+/// The storage writes `handle` makes for one url, leaving out the lookups and the
+/// queue bookkeeping of the dispatcher.
+fn writes(storage: &RecordingStorage<MemoryStorage<Recipe>>, url: &str) -> Vec<&'static str> {
+    storage
+        .calls_for(url)
+        .into_iter()
+        .filter(|m| {
+            [
+                "results_insert_with_referrer",
+                "visited_insert",
+                "warned_insert",
+                "running_delete",
+            ]
+            .contains(m)
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn custom_pipeline() {
+    let site = start_site();
+    let base = format!("http://{}", site);
+    let (hub, soup, empty) = (
+        format!("{}/recipes", base),
+        format!("{}/recipes/soup", base),
+        format!("{}/recipes/empty", base),
+    );
+    let soup_recipe = Recipe {
+        name: "Soto ayam".to_string(),
+        steps: vec!["Boil".to_string(), "Serve".to_string()],
+    };
+    let empty_recipe = Recipe {
+        name: "Nothing".to_string(),
+        steps: vec![],
+    };
+
+    // The first matching url part wins, so the specific pages come before the hub
+    let crawler = MockCrawler::new()
+        .with(
+            "/recipes/soup",
+            CrawlerResult::document_and_links(soup_recipe.clone(), vec![hub.clone()]),
+        )
+        .with(
+            "/recipes/empty",
+            CrawlerResult::document_and_links(empty_recipe, vec![]),
+        )
+        .with(
+            "/recipes",
+            CrawlerResult::Links(vec![soup.clone(), empty.clone()]),
+        );
+    let storage = RecordingStorage::<_>::memory::<Recipe>();
+    let options = RunOptions {
+        disable_politeness_for_hosts: vec![site.to_string()],
+        ..RunOptions::default()
+    };
+    let controller = options.controller.clone();
+
+    // Stop once both articles have been handled
+    let watched = storage.clone();
+    tokio::spawn(async move {
+        while watched.inner().results_count().await.unwrap() < 1
+            || watched.inner().warned_count().await.unwrap() < 1
+            || watched.inner().running_count().await.unwrap() > 0
+        {
+            sleep(Duration::from_millis(20)).await;
+        }
+        controller.stop();
+    });
+
+    timeout(
+        Duration::from_secs(30),
+        run_scrapper(crawler, storage.clone(), vec![hub.clone()], options),
+    )
+    .await
+    .expect("Crawl did not finish")
+    .unwrap();
+
+    let memory = storage.inner();
+    assert_eq!(memory.result(&soup), Some(soup_recipe));
+    assert_eq!(memory.result(&empty), None);
+    assert_eq!(memory.visited(), vec![hub.clone(), soup.clone()]);
+    assert_eq!(
+        memory.warned_recent(10).await.unwrap()[0].reason.as_deref(),
+        Some("empty_paragraphs")
+    );
+
+    assert_eq!(
+        writes(&storage, &hub),
+        vec!["visited_insert", "running_delete"]
+    );
+    assert_eq!(
+        writes(&storage, &soup),
+        vec![
+            "results_insert_with_referrer",
+            "visited_insert",
+            "running_delete"
+        ]
+    );
+    assert_eq!(
+        writes(&storage, &empty),
+        vec!["warned_insert", "running_delete"]
+    );
+    // The link back to the visited hub was offered but not queued again
+    assert!(storage.calls_for(&hub).contains(&"queued_insert_with_meta"));
+    assert_eq!(memory.queued_count().await.unwrap(), 0);
+}
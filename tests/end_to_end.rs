@@ -0,0 +1,145 @@
+//! Crawls a tiny interlinked site served from 127.0.0.1 through the whole
+//! fetch, parse, store and enqueue loop of `run_scrapper`.
+
+use axum::{extract::State, http::Uri, response::Html, Router};
+use indonesian_media_crawler::detik::{DetikCrawler, DetikData};
+use indonesian_media_crawler::{run_scrapper, RunOptions, Storage, Table};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+use tokio::time::{timeout, Duration, Instant};
+
+type Hits = Arc<Mutex<Vec<(String, Instant)>>>;
+
+const ARTICLES: [&str; 3] = ["/news/d-1/polisi", "/inet/d-2/kripto", "/travel/d-3/badung"];
+const HUBS: [&str; 2] = ["/", "/section"];
+
+fn links(base: &str, paths: &[&str]) -> String {
+    paths
+        .iter()
+        .map(|p| format!(r#"<a href="{}{}">{}</a>"#, base, p, p))
+        .collect()
+}
+
+fn article(fixture: &str, base: &str, paths: &[&str]) -> String {
+    let html = std::fs::read_to_string(format!("tests/htmls/{}", fixture)).unwrap();
+    let body_end = html.rfind("</body>").unwrap();
+    format!(
+        "{}{}{}",
+        &html[..body_end],
+        links(base, paths),
+        &html[body_end..]
+    )
+}
+
+fn site(base: &str) -> HashMap<&'static str, String> {
+    let hub = |paths: &[&str]| format!("<html><body>{}</body></html>", links(base, paths));
+    HashMap::from([
+        ("/", hub(&["/section", ARTICLES[0]])),
+        ("/section", hub(&["/", ARTICLES[1], ARTICLES[2]])),
+        (ARTICLES[0], article("1.html", base, &["/", ARTICLES[1]])),
+        (ARTICLES[1], article("inet.html", base, &["/section"])),
+        (ARTICLES[2], article("travel.html", base, &[ARTICLES[0]])),
+    ])
+}
+
+async fn start_site() -> (SocketAddr, Hits) {
+    let hits: Hits = Arc::new(Mutex::new(vec![]));
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let pages = Arc::new(site(&format!("http://{}", addr)));
+
+    let app =
+        Router::new()
+            .fallback(
+                |State((pages, hits)): State<(Arc<HashMap<&'static str, String>>, Hits)>,
+                 uri: Uri| async move {
+                    hits.lock()
+                        .unwrap()
+                        .push((uri.path().to_string(), Instant::now()));
+                    Html(pages.get(uri.path()).cloned().unwrap_or_default())
+                },
+            )
+            .with_state((pages, hits.clone()));
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service()),
+    );
+    (addr, hits)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn crawl_local_site() {
+    if std::path::Path::new("test_end_to_end.db").is_file() {
+        std::fs::remove_file("test_end_to_end.db").unwrap();
+    }
+    let (addr, hits) = start_site().await;
+    let base = format!("http://{}", addr);
+    let request_delay = Duration::from_millis(100);
+
+    let crawler = DetikCrawler::new()
+        .with_allowed_hosts(["127.0.0.1"])
+        .with_http(true);
+    let options = RunOptions {
+        request_delay,
+        max_results: Some(ARTICLES.len() as u64),
+        ..RunOptions::default()
+    };
+    let storage = DetikData::new("test_end_to_end").await.unwrap();
+    timeout(
+        Duration::from_secs(60),
+        run_scrapper(crawler, storage, vec![base.clone()], options),
+    )
+    .await
+    .expect("Crawl did not reach max_results")
+    .unwrap();
+
+    let storage = DetikData::new("test_end_to_end").await.unwrap();
+    let hits = hits.lock().unwrap().clone();
+
+    for path in ARTICLES {
+        let url = format!("{}{}", base, path);
+        assert_eq!(
+            hits.iter().filter(|(p, _)| p == path).count(),
+            1,
+            "{} fetched more than once",
+            path
+        );
+        assert!(storage.results.is_exist(url.as_str()).await.unwrap());
+        assert!(storage.visited_is_exists(url.as_str()).await.unwrap());
+    }
+    assert_eq!(storage.results_count().await.unwrap(), 3);
+
+    for path in HUBS {
+        let url = format!("{}{}", base, path)
+            .trim_end_matches('/')
+            .to_string();
+        assert!(
+            storage.visited_is_exists(url.as_str()).await.unwrap(),
+            "{}",
+            url
+        );
+        assert!(!storage.results.is_exist(url.as_str()).await.unwrap());
+    }
+
+    assert_eq!(storage.running_count().await.unwrap(), 0);
+    assert_eq!(storage.warned_count().await.unwrap(), 0);
+
+    // Requests are spaced by the rate limiter, allowing for a little scheduling jitter
+    let tolerance = Duration::from_millis(15);
+    for pair in hits.windows(2) {
+        let gap = pair[1].1.duration_since(pair[0].1);
+        assert!(
+            gap + tolerance >= request_delay,
+            "{} requested {:?} after {}",
+            pair[1].0,
+            gap,
+            pair[0].0
+        );
+    }
+
+    std::fs::remove_file("test_end_to_end.db").unwrap();
+}
@@ -0,0 +1,159 @@
+//! Exports a few hundred articles to JSONL, interrupted by dropping the export future once it
+//! recorded some progress, then resumed: the file must be the one of an uninterrupted export.
+
+use indonesian_media_crawler::detik::{DetikArticle, DetikData};
+use indonesian_media_crawler::export::{export_jsonl, Checkpoint, ExportError, ExportOptions};
+use indonesian_media_crawler::Storage;
+use std::path::{Path, PathBuf};
+use tokio::time::{sleep, timeout, Duration};
+
+const ARTICLES: u64 = 300;
+
+fn article(i: u64) -> DetikArticle {
+    DetikArticle {
+        title: Some(format!("Judul {}", i)),
+        published_date: None,
+        description: Some("Ringkasan \"berita\"".to_string()),
+        thumbnail_url: None,
+        author: Some("Penulis".to_string()),
+        desk: None,
+        category: Some("news".to_string()),
+        language: Some("id".to_string()),
+        keywords: vec!["banjir".to_string(), format!("kata {}", i)],
+        keywords_raw: None,
+        videos: vec![],
+        photos: vec![],
+        body_selector: None,
+        paragraphs: vec![
+            format!("Paragraf pertama {}.", i),
+            "Paragraf kedua.".to_string(),
+        ],
+    }
+}
+
+async fn storage(name: &str) -> DetikData {
+    let db = format!("{}.db", name);
+    if Path::new(&db).is_file() {
+        std::fs::remove_file(&db).unwrap();
+    }
+    let storage = DetikData::new(name).await.unwrap();
+    for i in 0..ARTICLES {
+        let url = format!("https://news.detik.com/berita/d-{}/judul", i);
+        storage.results_insert((url, article(i))).await.unwrap();
+    }
+    storage
+}
+
+/// Wait until the progress of the export `path` records at least `rows` articles.
+async fn progress_past(path: &Path, rows: u64) {
+    loop {
+        if let Ok(checkpoint) = Checkpoint::load(&Checkpoint::path(path)) {
+            if checkpoint.rows >= rows {
+                return;
+            }
+        }
+        sleep(Duration::from_millis(1)).await;
+    }
+}
+
+fn out_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("export_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[tokio::test]
+async fn resumed_export_equals_one_shot() {
+    let storage = storage("test_export").await;
+    let dir = out_dir();
+    let options = ExportOptions {
+        checkpoint_every: 3,
+        ..ExportOptions::default()
+    };
+
+    let one_shot = dir.join("one_shot.jsonl");
+    let rows = export_jsonl(&storage.results, &one_shot, options)
+        .await
+        .unwrap();
+    assert_eq!(rows, ARTICLES);
+    assert!(!Checkpoint::path(&one_shot).exists());
+    let expected = std::fs::read(&one_shot).unwrap();
+    assert_eq!(
+        expected.iter().filter(|&&b| b == b'\n').count() as u64,
+        ARTICLES
+    );
+
+    let resumed = dir.join("resumed.jsonl");
+    tokio::select! {
+        _ = export_jsonl(&storage.results, &resumed, options) => {
+            panic!("Export finished before it was interrupted")
+        }
+        _ = progress_past(&resumed, 30) => {}
+    }
+    let checkpoint = Checkpoint::load(&Checkpoint::path(&resumed)).unwrap();
+    assert!(checkpoint.rows < ARTICLES);
+    assert!(std::fs::metadata(&resumed).unwrap().len() >= checkpoint.offset);
+
+    let resume = ExportOptions {
+        resume: true,
+        ..options
+    };
+    let rows = timeout(
+        Duration::from_secs(60),
+        export_jsonl(&storage.results, &resumed, resume),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(rows, ARTICLES);
+    assert!(!Checkpoint::path(&resumed).exists());
+    assert!(std::fs::read(&resumed).unwrap() == expected);
+
+    // Nothing to resume once it's done
+    match export_jsonl(&storage.results, &resumed, resume).await {
+        Err(ExportError::NoCheckpoint(path)) => assert_eq!(path, Checkpoint::path(&resumed)),
+        other => panic!("Resumed a finished export: {:?}", other),
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+    std::fs::remove_file("test_export.db").unwrap();
+}
+
+#[tokio::test]
+async fn checkpoint_mismatch() {
+    let storage = DetikData::new("test_export_mismatch").await.unwrap();
+    let dir = out_dir();
+    let path = dir.join("truncated.jsonl");
+    std::fs::write(&path, "{}\n").unwrap();
+    let checkpoint = Checkpoint {
+        cursor: 10,
+        offset: 1000,
+        rows: 10,
+    };
+    std::fs::write(
+        Checkpoint::path(&path),
+        serde_json::to_string(&checkpoint).unwrap(),
+    )
+    .unwrap();
+
+    let options = ExportOptions {
+        resume: true,
+        ..ExportOptions::default()
+    };
+    match export_jsonl(&storage.results, &path, options).await {
+        Err(ExportError::Mismatch {
+            expected, found, ..
+        }) => assert_eq!((expected, found), (1000, 3)),
+        other => panic!("Resumed a mismatched export: {:?}", other),
+    }
+    // Left as it was
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "{}\n");
+    assert_eq!(
+        Checkpoint::load(&Checkpoint::path(&path)).unwrap(),
+        checkpoint
+    );
+
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(Checkpoint::path(&path)).unwrap();
+    std::fs::remove_file("test_export_mismatch.db").unwrap();
+}
@@ -0,0 +1,88 @@
+//! The urls `DetikCrawler::should_fetch` vetoes are never requested, whether they're seeds
+//! or linked from a crawled page.
+
+use axum::{extract::State, http::Uri, response::Html, Router};
+use indonesian_media_crawler::detik::{DetikArticle, DetikCrawler};
+use indonesian_media_crawler::memory::MemoryStorage;
+use indonesian_media_crawler::{run_scrapper, RunOptions, Storage};
+use std::sync::{Arc, Mutex};
+use tokio::time::{timeout, Duration};
+
+type Hits = Arc<Mutex<Vec<String>>>;
+
+const ARTICLE: &str = "/news/d-1/polisi";
+const VETOED: [&str; 2] = ["/foto-news/d-2/galeri", "/pemilu/infografis/d-3/jadwal"];
+const VETOED_SEED: &str = "/foto-news/d-4/seed";
+
+/// Serve a hub linking to `ARTICLE` and `VETOED`, counting the requests of every path.
+fn start_site() -> (String, Hits) {
+    let hits: Hits = Arc::new(Mutex::new(vec![]));
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let hub: String = std::iter::once(ARTICLE)
+        .chain(VETOED)
+        .map(|p| format!(r#"<a href="{}{}">{}</a>"#, base, p, p))
+        .collect();
+    let hub = Arc::new(format!("<html><body>{}</body></html>", hub));
+    let article = Arc::new(std::fs::read_to_string("tests/htmls/1.html").unwrap());
+
+    let app = Router::new()
+        .fallback(
+            |State((hub, article, hits)): State<(Arc<String>, Arc<String>, Hits)>,
+             uri: Uri| async move {
+                hits.lock().unwrap().push(uri.path().to_string());
+                match uri.path() {
+                    ARTICLE => Html(article.as_str().to_string()),
+                    _ => Html(hub.as_str().to_string()),
+                }
+            },
+        )
+        .with_state((hub, article, hits.clone()));
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service()),
+    );
+    (base, hits)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn vetoed_urls_not_requested() {
+    let (base, hits) = start_site();
+    let crawler = DetikCrawler::new()
+        .with_allowed_hosts(["127.0.0.1"])
+        .with_http(true);
+    let storage = MemoryStorage::<DetikArticle>::new();
+    let options = RunOptions {
+        request_delay: Duration::from_millis(10),
+        max_results: Some(1),
+        ..RunOptions::default()
+    };
+    // The vetoed seed first, handled before the article ends the crawl
+    let seeds = vec![format!("{}{}", base, VETOED_SEED), format!("{}/hub", base)];
+    timeout(
+        Duration::from_secs(60),
+        run_scrapper(crawler, storage.clone(), seeds, options),
+    )
+    .await
+    .expect("Crawl did not reach max_results")
+    .unwrap();
+
+    let hits = hits.lock().unwrap().clone();
+    assert_eq!(hits, vec!["/hub".to_string(), ARTICLE.to_string()]);
+    assert!(storage
+        .visited_is_exists(format!("{}{}", base, ARTICLE).as_str())
+        .await
+        .unwrap());
+    // Vetoed for good, so they aren't queued again by the next crawl either
+    for path in VETOED.into_iter().chain([VETOED_SEED]) {
+        let url = format!("{}{}", base, path);
+        assert!(
+            storage.visited_is_exists(url.as_str()).await.unwrap(),
+            "{}",
+            url
+        );
+    }
+    assert!(storage.queued_get().await.unwrap().is_empty());
+    assert_eq!(storage.running_count().await.unwrap(), 0);
+}
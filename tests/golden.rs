@@ -0,0 +1,254 @@
+//! Runs each site crawler over its fixtures (`tests/htmls/*.html` for detik,
+//! `tests/htmls/<site>/*.html` for the others) and compares the serialized result with the
+//! sibling `*.expected.json`. Each fixture is crawled as if fetched from its url in the
+//! `urls.json` of its directory. Set `UPDATE_GOLDEN=1` to regenerate the baselines.
+
+use indonesian_media_crawler::antara::AntaraCrawler;
+use indonesian_media_crawler::bbcindonesia::BbcIndonesiaCrawler;
+use indonesian_media_crawler::cnbcindonesia::CnbcIndonesiaCrawler;
+use indonesian_media_crawler::detik::{DetikCrawler, BODY_SELECTORS, SELECTOR_NAMES};
+use indonesian_media_crawler::idntimes::IdnTimesCrawler;
+use indonesian_media_crawler::jawapos::{JawaPosCrawler, RADAR_HOSTS};
+use indonesian_media_crawler::kompas::KompasCrawler;
+use indonesian_media_crawler::kontan::KontanCrawler;
+use indonesian_media_crawler::kumparan::KumparanCrawler;
+use indonesian_media_crawler::liputan6::Liputan6Crawler;
+use indonesian_media_crawler::mediaindonesia::MediaIndonesiaCrawler;
+use indonesian_media_crawler::merdeka::MerdekaCrawler;
+use indonesian_media_crawler::pikiranrakyat::PikiranRakyatCrawler;
+use indonesian_media_crawler::republika::RepublikaCrawler;
+use indonesian_media_crawler::selectors::{SelectorError, SelectorOverrides};
+use indonesian_media_crawler::sindonews::SindonewsCrawler;
+use indonesian_media_crawler::tempo::TempoCrawler;
+use indonesian_media_crawler::tirto::TirtoCrawler;
+use indonesian_media_crawler::tribunnews::TribunnewsCrawler;
+use indonesian_media_crawler::{fixture, parse_article, Crawler, CrawlerError};
+use pretty_assertions::StrComparison;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+fn fixtures(dir: &str) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .expect("Invalid fixture dir")
+        .map(|entry| entry.expect("Invalid fixture entry").path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "html"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn expected_path(html: &Path) -> PathBuf {
+    html.with_extension("expected.json")
+}
+
+/// The article of a serialized `CrawlerResult`, null for the other variants.
+fn document(result: &str) -> serde_json::Value {
+    let result: serde_json::Value = serde_json::from_str(result).expect("Invalid result");
+    result
+        .get("DocumentAndLinks")
+        .map_or(serde_json::Value::Null, |v| v[0].clone())
+}
+
+fn check_golden<C>(dir: &str, crawler: &C)
+where
+    C: Crawler,
+    C::Document: Serialize,
+{
+    let update = std::env::var("UPDATE_GOLDEN").is_ok_and(|v| v == "1");
+    let paths = fixtures(dir);
+    assert!(!paths.is_empty(), "No fixture found in {}", dir);
+
+    let urls = fixture::urls(Path::new(dir)).expect("Invalid urls.json");
+    let mut failed = vec![];
+    for path in paths {
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+        let url = urls
+            .get(name.as_ref())
+            .unwrap_or_else(|| panic!("No url for fixture {} in urls.json", path.display()));
+        let html = std::fs::read_to_string(&path).expect("Invalid fixture file");
+        let actual = fixture::expected_json(crawler, url, &html).expect("Serialize result");
+        assert_eq!(
+            serde_json::to_value(parse_article(crawler, &html, url.as_str())).unwrap(),
+            document(&actual),
+            "parse_article differs from the crawl of {}",
+            path.display()
+        );
+        let expected_path = expected_path(&path);
+
+        if update {
+            std::fs::write(&expected_path, &actual).expect("Write expected file");
+            continue;
+        }
+
+        match std::fs::read_to_string(&expected_path) {
+            Ok(expected) if expected == actual => {}
+            Ok(expected) => {
+                println!(
+                    "{} differs from the crawl result:\n{}",
+                    expected_path.display(),
+                    StrComparison::new(&expected, &actual)
+                );
+                failed.push(path.display().to_string());
+            }
+            Err(_) => {
+                println!("{} is missing", expected_path.display());
+                failed.push(path.display().to_string());
+            }
+        }
+    }
+
+    assert!(
+        failed.is_empty(),
+        "Golden mismatch for {:?}, rerun with UPDATE_GOLDEN=1 to accept the new output",
+        failed
+    );
+}
+
+#[test]
+fn golden_detik() {
+    check_golden("tests/htmls", &DetikCrawler::new());
+}
+
+/// The body container of the fixtures of each vertical, also in their baselines, so a
+/// selector dropped from `BODY_SELECTORS` shows up here first.
+#[test]
+fn detik_body_selectors() {
+    let urls = fixture::urls(Path::new("tests/htmls")).expect("Invalid urls.json");
+    let crawler = DetikCrawler::new();
+    for (name, selector) in [
+        ("finance", ".itp_bodycontent"),
+        ("inet", "#detikdetailtext"),
+        ("travel", "#detikdetailtext"),
+        ("sport", ".detail_text"),
+        ("edu", ".detail__body-text"),
+        ("sulsel", ".detail__body-regional"),
+        ("wolipop", ".read__content"),
+    ] {
+        let html = std::fs::read_to_string(format!("tests/htmls/{}.html", name)).unwrap();
+        let article = parse_article(&crawler, &html, urls[name].as_str())
+            .unwrap_or_else(|| panic!("No article in {}", name));
+        assert_eq!(article.body_selector.as_deref(), Some(selector), "{}", name);
+        assert!(BODY_SELECTORS.contains(&selector));
+    }
+}
+
+/// A template change of detik followed from the config: the body container of the `edu`
+/// fixture renamed, read again with the `body` selector overridden.
+#[test]
+fn detik_selector_overrides() {
+    let urls = fixture::urls(Path::new("tests/htmls")).expect("Invalid urls.json");
+    let url = urls["edu"].as_str();
+    let html = std::fs::read_to_string("tests/htmls/edu.html").unwrap();
+    let expected = parse_article(&DetikCrawler::new(), &html, url).unwrap();
+    let html = html.replace("detail__body-text", "detail__body-content");
+
+    let article = parse_article(&DetikCrawler::new(), &html, url).unwrap();
+    assert!(article.paragraphs.is_empty());
+
+    let overrides =
+        SelectorOverrides::parse([("body", ".detail__body-content")], &SELECTOR_NAMES).unwrap();
+    let crawler = DetikCrawler::new().with_selectors(overrides);
+    let article = parse_article(&crawler, &html, url).unwrap();
+    assert_eq!(article.paragraphs, expected.paragraphs);
+    assert_eq!(
+        article.body_selector.as_deref(),
+        Some(".detail__body-content")
+    );
+    assert_eq!(article.title, expected.title);
+
+    match SelectorOverrides::parse([("body", ".detail__body-content >")], &SELECTOR_NAMES) {
+        Err(CrawlerError::InvalidSelector { name, source }) => {
+            assert_eq!(name, "body");
+            assert!(matches!(source, SelectorError::Css { .. }), "{:?}", source);
+        }
+        other => panic!("Not an invalid selector: {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn golden_kompas() {
+    check_golden("tests/htmls/kompas", &KompasCrawler::new());
+}
+
+#[test]
+fn golden_liputan6() {
+    check_golden("tests/htmls/liputan6", &Liputan6Crawler::new());
+}
+
+#[test]
+fn golden_tempo() {
+    check_golden("tests/htmls/tempo", &TempoCrawler::new());
+}
+
+#[test]
+fn golden_tribunnews() {
+    check_golden("tests/htmls/tribunnews", &TribunnewsCrawler::new());
+}
+
+#[test]
+fn golden_antara() {
+    check_golden("tests/htmls/antara", &AntaraCrawler::new());
+}
+
+#[test]
+fn golden_republika() {
+    check_golden("tests/htmls/republika", &RepublikaCrawler::new());
+}
+
+#[test]
+fn golden_kumparan() {
+    check_golden("tests/htmls/kumparan", &KumparanCrawler::new());
+}
+
+#[test]
+fn golden_tirto() {
+    check_golden("tests/htmls/tirto", &TirtoCrawler::new());
+}
+
+#[test]
+fn golden_merdeka() {
+    check_golden("tests/htmls/merdeka", &MerdekaCrawler::new());
+}
+
+#[test]
+fn golden_sindonews() {
+    check_golden("tests/htmls/sindonews", &SindonewsCrawler::new());
+}
+
+#[test]
+fn golden_cnbcindonesia() {
+    check_golden("tests/htmls/cnbcindonesia", &CnbcIndonesiaCrawler::new());
+}
+
+#[test]
+fn golden_kontan() {
+    check_golden("tests/htmls/kontan", &KontanCrawler::new());
+}
+
+#[test]
+fn golden_idntimes() {
+    check_golden("tests/htmls/idntimes", &IdnTimesCrawler::new());
+}
+
+#[test]
+fn golden_bbcindonesia() {
+    check_golden("tests/htmls/bbcindonesia", &BbcIndonesiaCrawler::new());
+}
+
+#[test]
+fn golden_jawapos() {
+    check_golden(
+        "tests/htmls/jawapos",
+        &JawaPosCrawler::new().with_radar_hosts(RADAR_HOSTS),
+    );
+}
+
+#[test]
+fn golden_pikiranrakyat() {
+    check_golden("tests/htmls/pikiranrakyat", &PikiranRakyatCrawler::new());
+}
+
+#[test]
+fn golden_mediaindonesia() {
+    check_golden("tests/htmls/mediaindonesia", &MediaIndonesiaCrawler::new());
+}
@@ -0,0 +1,91 @@
+//! The selector health check, over a detik article after a made up redesign renamed the
+//! title meta and the body container (`tests/health/detik_redesigned.html`).
+
+use axum::{routing::get, Router};
+use indonesian_media_crawler::detik::{DetikArticle, DetikCrawler};
+use indonesian_media_crawler::health::Canary;
+use indonesian_media_crawler::memory::MemoryStorage;
+use indonesian_media_crawler::{run_scrapper, CrawlContext, Crawler, CrawlerError, RunOptions};
+use reqwest::Url;
+use scraper::Html;
+use tokio::time::Duration;
+
+const REDESIGNED: &str = "tests/health/detik_redesigned.html";
+const URL: &str =
+    "https://news.detik.com/berita/d-6453785/kpu-tetapkan-17-partai-politik-peserta-pemilu-2024";
+
+fn check(path: &str) -> indonesian_media_crawler::health::HealthReport {
+    let html = std::fs::read_to_string(path).expect("Invalid fixture path");
+    let url = Url::parse(URL).unwrap();
+    DetikCrawler::new().health_check(&CrawlContext::new(&url), &Html::parse_document(&html))
+}
+
+#[test]
+fn redesigned_page_reports_missing_fields() {
+    let report = check(REDESIGNED);
+    assert_eq!(report.not_article, None);
+    assert_eq!(report.missing, vec!["title", "paragraphs"]);
+    assert_eq!(report.paragraphs, 0);
+    assert!(!report.is_healthy());
+}
+
+#[test]
+fn captured_page_healthy() {
+    let report = check("tests/htmls/1.html");
+    assert!(report.missing.is_empty(), "{}", report);
+    assert!(report.is_healthy());
+}
+
+/// Serve the redesigned page on every path, returning its url.
+fn start_site() -> String {
+    let html = std::fs::read_to_string(REDESIGNED).expect("Invalid fixture path");
+    let app = Router::new().fallback(get(move || async move { axum::response::Html(html) }));
+    let server = axum::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(app.into_make_service());
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    format!("http://{}/berita/d-6453785/kpu", addr)
+}
+
+#[tokio::test]
+async fn unhealthy_canary_aborts_crawl() {
+    let url = start_site();
+    let storage = MemoryStorage::<DetikArticle>::new();
+    let options = RunOptions {
+        request_delay: Duration::from_millis(10),
+        canary: Some(Canary::new(url.as_str())),
+        ..RunOptions::default()
+    };
+    let result = run_scrapper(
+        DetikCrawler::new(),
+        storage.clone(),
+        vec![url.clone()],
+        options,
+    )
+    .await;
+    match result {
+        Err(CrawlerError::UnhealthyCanary(canary, report)) => {
+            assert_eq!(canary, url);
+            assert_eq!(report.missing, vec!["title", "paragraphs"]);
+        }
+        other => panic!("Crawled with an unhealthy canary: {:?}", other.map(|_| ())),
+    }
+}
+
+#[tokio::test]
+async fn unhealthy_canary_only_logged() {
+    let url = start_site();
+    let options = RunOptions {
+        request_delay: Duration::from_millis(10),
+        canary: Some(Canary {
+            abort: false,
+            ..Canary::new(url.as_str())
+        }),
+        ..RunOptions::default()
+    };
+    // Stopped from the start, the crawl returns once the canary is checked
+    options.controller.stop();
+    let storage = MemoryStorage::<DetikArticle>::new();
+    run_scrapper(DetikCrawler::new(), storage, vec![url], options)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,99 @@
+//! A site of a library consumer on the bare engine, which has to build with
+//! `--no-default-features`: no detik crawler and no sqlx, only `Crawler`, `Storage` and
+//! `run_scrapper` with the in-memory storage.
+
+use indonesian_media_crawler::memory::MemoryStorage;
+use indonesian_media_crawler::{
+    run_scrapper, Article, CrawlContext, Crawler, CrawlerResult, ExtractedLink, RunOptions, Storage,
+};
+use reqwest::Url;
+use scraper::{Html, Selector};
+
+#[derive(Debug, Clone)]
+struct Page {
+    title: Option<String>,
+    paragraphs: Vec<String>,
+}
+
+impl Article for Page {
+    fn get_paragraphs(&self) -> &[String] {
+        &self.paragraphs
+    }
+
+    fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+}
+
+struct PageCrawler;
+
+impl Crawler for PageCrawler {
+    type Document = Page;
+
+    fn can_be_scrapped(&self, doc: &Html) -> bool {
+        doc.select(&Selector::parse("article").unwrap())
+            .next()
+            .is_some()
+    }
+
+    fn crawl(&self, ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        let links = self.extract_urls(ctx, doc);
+        if !self.can_be_scrapped(doc) {
+            return CrawlerResult::Links(links);
+        }
+        let page = Page {
+            title: doc
+                .select(&Selector::parse("h1").unwrap())
+                .next()
+                .map(|h| h.inner_html()),
+            paragraphs: doc
+                .select(&Selector::parse("article p").unwrap())
+                .map(|p| p.inner_html())
+                .collect(),
+        };
+        CrawlerResult::document_and_links(page, links)
+    }
+
+    fn extract_links(&self, ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        doc.select(&Selector::parse("a").unwrap())
+            .filter_map(|a| {
+                let url = ctx.url.join(a.value().attr("href")?).ok()?;
+                Some(ExtractedLink {
+                    url: url.into(),
+                    text: a.text().collect::<String>().trim().to_string(),
+                    rel: a.value().attr("rel").map(ToString::to_string),
+                })
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn crawl_without_default_features() {
+    let url = Url::parse("https://example.com/news/").unwrap();
+    let doc = Html::parse_document(
+        r#"<h1>Judul</h1><article><p>Satu.</p><p>Dua.</p></article><a href="2">next</a>"#,
+    );
+    match PageCrawler.crawl(&CrawlContext::new(&url), &doc) {
+        CrawlerResult::DocumentAndLinks(page, links) => {
+            assert_eq!(page.title.as_deref(), Some("Judul"));
+            assert_eq!(page.paragraphs, vec!["Satu.", "Dua."]);
+            assert_eq!(links.other, vec!["https://example.com/news/2"]);
+        }
+        _ => panic!("Not an article"),
+    }
+}
+
+#[tokio::test]
+async fn memory_storage_without_sqlite() {
+    let storage = MemoryStorage::<Page>::new();
+    storage
+        .queued_insert_batch(&["https://example.com/"])
+        .await
+        .unwrap();
+    assert_eq!(storage.queued_count().await.unwrap(), 1);
+
+    // The whole engine is available for a custom site and storage
+    let _ = run_scrapper::<PageCrawler, MemoryStorage<Page>>;
+    let _ = RunOptions::default();
+}
@@ -0,0 +1,131 @@
+//! The two stages of a crawl: a page is fetched while the one before it is parsed, even with a
+//! single fetcher and a single parser.
+
+use axum::{extract::Path, response::Html as HtmlResponse, routing::get, Router};
+use indonesian_media_crawler::memory::MemoryStorage;
+use indonesian_media_crawler::{
+    run_scrapper, Article, CrawlContext, Crawler, CrawlerResult, ExtractedLink, RunOptions, Storage,
+};
+use scraper::Html;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::time::{sleep, timeout, Duration};
+
+const PAGES: usize = 6;
+
+/// When each fetch or parse started and ended.
+type Spans = Arc<Mutex<Vec<(Instant, Instant)>>>;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Page {
+    paragraphs: Vec<String>,
+}
+
+impl Article for Page {
+    fn get_paragraphs(&self) -> &[String] {
+        &self.paragraphs
+    }
+}
+
+/// Takes a while to parse each page, as a CPU-bound crawler would.
+struct SlowCrawler {
+    parses: Spans,
+}
+
+impl Crawler for SlowCrawler {
+    type Document = Page;
+
+    fn can_be_scrapped(&self, _doc: &Html) -> bool {
+        true
+    }
+
+    fn crawl(&self, _ctx: &CrawlContext, _doc: &Html) -> CrawlerResult<Self::Document> {
+        let start = Instant::now();
+        std::thread::sleep(Duration::from_millis(150));
+        self.parses.lock().unwrap().push((start, Instant::now()));
+        let page = Page {
+            paragraphs: vec!["Isi".to_string()],
+        };
+        CrawlerResult::document_and_links(page, vec![])
+    }
+
+    fn extract_links(&self, _ctx: &CrawlContext, _doc: &Html) -> Vec<ExtractedLink> {
+        vec![]
+    }
+}
+
+/// A site taking a while to answer each page.
+fn start_site(fetches: Spans) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new().route(
+        "/page/:id",
+        get(move |Path(id): Path<u32>| {
+            let fetches = Arc::clone(&fetches);
+            async move {
+                let start = Instant::now();
+                sleep(Duration::from_millis(100)).await;
+                fetches.lock().unwrap().push((start, Instant::now()));
+                HtmlResponse(format!("<html><body><p>Halaman {}</p></body></html>", id))
+            }
+        }),
+    );
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service()),
+    );
+    addr
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn fetching_overlaps_parsing() {
+    let fetches = Spans::default();
+    let parses = Spans::default();
+    let site = start_site(Arc::clone(&fetches));
+    let seeds = (0..PAGES)
+        .map(|i| format!("http://{}/page/{}", site, i))
+        .collect();
+    let options = RunOptions {
+        max_fetching: 1,
+        max_parsing: 1,
+        request_delay: Duration::from_millis(10),
+        ..RunOptions::default()
+    };
+
+    let storage = MemoryStorage::<Page>::new();
+    let controller = options.controller.clone();
+    let watched = storage.clone();
+    tokio::spawn(async move {
+        while (watched.results_count().await.unwrap() as usize) < PAGES
+            || watched.running_count().await.unwrap() > 0
+        {
+            sleep(Duration::from_millis(20)).await;
+        }
+        controller.stop();
+    });
+    let crawler = SlowCrawler {
+        parses: Arc::clone(&parses),
+    };
+    timeout(
+        Duration::from_secs(30),
+        run_scrapper(crawler, storage.clone(), seeds, options),
+    )
+    .await
+    .expect("Crawl did not finish")
+    .unwrap();
+
+    let fetches = fetches.lock().unwrap();
+    let parses = parses.lock().unwrap();
+    assert_eq!((fetches.len(), parses.len()), (PAGES, PAGES));
+    let overlapping = fetches
+        .iter()
+        .filter(|fetch| {
+            parses
+                .iter()
+                .any(|parse| fetch.0 < parse.1 && parse.0 < fetch.1)
+        })
+        .count();
+    assert!(overlapping >= PAGES / 2, "{} overlapping", overlapping);
+}
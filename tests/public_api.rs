@@ -0,0 +1,56 @@
+//! The exports downstream crates build on. This fails to compile when one of them is renamed,
+//! moved or removed, so such a change is a deliberate one.
+
+use indonesian_media_crawler::detik::{DetikArticle, DetikCrawler, DetikData};
+use indonesian_media_crawler::generic::{GenericArticle, GenericArticleTable, SiteStorage};
+use indonesian_media_crawler::memory::MemoryStorage;
+use indonesian_media_crawler::{
+    http_client, run_scrapper, Article, AsyncCrawler, Bucket, CrawlContext, CrawlController,
+    CrawlStats, Crawler, CrawlerError, CrawlerResult, FetchContext, ResultSummary, ResultTable,
+    RunOptions, Site, SqliteStorage, Storage, Table, UrlBucket, UrlTable, WarnedEntry, WarnedTable,
+};
+
+fn article<A: Article>() {}
+fn crawler<C: Crawler>() {}
+fn async_crawler<C: AsyncCrawler>() {}
+fn storage<S: Storage>() {}
+fn url_bucket<B: UrlBucket>() {}
+fn table<T: Table>() {}
+fn result_table<T: ResultTable>() {}
+
+#[test]
+fn exports() {
+    article::<DetikArticle>();
+    article::<GenericArticle>();
+
+    crawler::<DetikCrawler>();
+    async_crawler::<DetikCrawler>();
+
+    storage::<DetikData>();
+    storage::<SiteStorage>();
+    storage::<SqliteStorage<GenericArticleTable>>();
+    storage::<MemoryStorage<DetikArticle>>();
+
+    url_bucket::<UrlTable>();
+    table::<UrlTable>();
+    table::<WarnedTable>();
+    result_table::<GenericArticleTable>();
+
+    let _ = run_scrapper::<DetikCrawler, DetikData>;
+    let _ = http_client;
+    let _: Option<(
+        CrawlContext,
+        FetchContext,
+        CrawlerResult<DetikArticle>,
+        CrawlerError,
+        ResultSummary,
+        WarnedEntry,
+    )> = None;
+    let _ = (
+        RunOptions::default(),
+        CrawlController::default(),
+        CrawlStats::default(),
+        Site::Detik,
+        Bucket::Queued,
+    );
+}
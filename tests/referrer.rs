@@ -0,0 +1,130 @@
+//! The page each stored article's link was first found on, threaded from the queue to the
+//! results of every backend.
+
+use axum::{http::Uri, response::Html, Router};
+use indonesian_media_crawler::detik::{DetikArticle, DetikData};
+use indonesian_media_crawler::memory::MemoryStorage;
+use indonesian_media_crawler::testing::MockCrawler;
+use indonesian_media_crawler::{run_scrapper, CrawlerResult, RunOptions, Storage};
+use std::collections::BTreeMap;
+use std::net::{SocketAddr, TcpListener};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::time::{sleep, timeout, Duration};
+
+fn start_site() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new().fallback(move |uri: Uri| async move {
+        Html(MockCrawler::<DetikArticle>::page(&format!(
+            "http://{}{}",
+            addr, uri
+        )))
+    });
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service()),
+    );
+    addr
+}
+
+fn article(title: &str) -> DetikArticle {
+    DetikArticle {
+        title: Some(title.to_string()),
+        published_date: None,
+        description: None,
+        thumbnail_url: None,
+        author: None,
+        desk: None,
+        category: None,
+        language: None,
+        keywords: vec![],
+        keywords_raw: None,
+        videos: vec![],
+        photos: vec![],
+        body_selector: None,
+        paragraphs: vec!["p".to_string()],
+    }
+}
+
+/// Crawl a hub linking to an article and to the other seed, an article too, into `storage`,
+/// returning the referrer stored with each result as read from `watched`, the same data.
+async fn crawl<S: Storage<Record = DetikArticle> + Send + Sync + 'static>(
+    storage: S,
+    watched: S,
+) -> (String, BTreeMap<String, Option<String>>) {
+    let site = start_site();
+    let base = format!("http://{}", site);
+    let hub = format!("{}/hub", base);
+    let crawler = MockCrawler::new()
+        .with(
+            "/a/",
+            CrawlerResult::document_and_links(article("Berita"), vec![]),
+        )
+        .with(
+            "/hub",
+            CrawlerResult::Links(vec![format!("{}/a/1", base), format!("{}/a/seed", base)]),
+        );
+    let options = RunOptions {
+        disable_politeness_for_hosts: vec![site.to_string()],
+        ..RunOptions::default()
+    };
+
+    let controller = options.controller.clone();
+    let watched = Arc::new(watched);
+    let watching = Arc::clone(&watched);
+    tokio::spawn(async move {
+        while watching.results_count().await.unwrap() < 2
+            || watching.running_count().await.unwrap() > 0
+        {
+            sleep(Duration::from_millis(20)).await;
+        }
+        controller.stop();
+    });
+    let seeds = vec![format!("{}/a/seed", base), hub.clone()];
+    timeout(
+        Duration::from_secs(30),
+        run_scrapper(crawler, storage, seeds, options),
+    )
+    .await
+    .expect("Crawl did not finish")
+    .unwrap();
+
+    let referrers = watched
+        .results_recent(10)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|result| (result.url.replace(&base, ""), result.referrer))
+        .collect();
+    (hub, referrers)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn article_referrer_is_hub() {
+    let memory = MemoryStorage::new();
+    let (hub, memory) = crawl(memory.clone(), memory).await;
+    assert_eq!(
+        memory,
+        BTreeMap::from([
+            ("/a/1".to_string(), Some(hub)),
+            ("/a/seed".to_string(), None)
+        ])
+    );
+
+    if Path::new("test_referrer.db").is_file() {
+        std::fs::remove_file("test_referrer.db").unwrap();
+    }
+    let sqlite = DetikData::new("test_referrer").await.unwrap();
+    let watched = DetikData::new("test_referrer").await.unwrap();
+    let (hub, sqlite) = crawl(sqlite, watched).await;
+    assert_eq!(
+        sqlite,
+        BTreeMap::from([
+            ("/a/1".to_string(), Some(hub)),
+            ("/a/seed".to_string(), None)
+        ])
+    );
+    std::fs::remove_file("test_referrer.db").unwrap();
+}
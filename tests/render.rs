@@ -0,0 +1,72 @@
+//! Compares the markdown and plain text renderings of a fully populated and a sparse
+//! `DetikArticle` with `tests/render/*.md` and `*.txt`. Set `UPDATE_GOLDEN=1` to regenerate
+//! them.
+
+use indonesian_media_crawler::detik::{DetikArticle, DetikCrawler};
+use indonesian_media_crawler::{fixture, CrawlContext, Crawler, CrawlerResult};
+use pretty_assertions::StrComparison;
+use scraper::Html;
+use std::path::Path;
+
+fn full() -> DetikArticle {
+    let dir = Path::new("tests/htmls");
+    let urls = fixture::urls(dir).expect("Invalid urls.json");
+    let html = std::fs::read_to_string(dir.join("travel.html")).expect("Invalid fixture file");
+    let ctx = CrawlContext::new(&urls["travel"]);
+    match DetikCrawler::new().crawl(&ctx, &Html::parse_document(&html)) {
+        CrawlerResult::DocumentAndLinks(mut article, _) => {
+            article.title = article.title.map(|t| format!("{} [Foto] #1", t));
+            article
+        }
+        _ => panic!("Not an article"),
+    }
+}
+
+fn sparse() -> DetikArticle {
+    DetikArticle {
+        title: None,
+        published_date: None,
+        description: None,
+        thumbnail_url: None,
+        author: None,
+        desk: None,
+        category: None,
+        language: None,
+        keywords: vec![],
+        keywords_raw: None,
+        videos: vec![],
+        photos: vec![],
+        body_selector: None,
+        paragraphs: vec![
+            "<strong>Jakarta</strong> - Paragraf pertama &amp; satu-satunya.".to_string(),
+        ],
+    }
+}
+
+fn check(name: &str, actual: &str) {
+    let path = Path::new("tests/render").join(name);
+    if std::env::var("UPDATE_GOLDEN").is_ok_and(|v| v == "1") {
+        std::fs::write(&path, actual).expect("Write expected file");
+        return;
+    }
+    let expected =
+        std::fs::read_to_string(&path).unwrap_or_else(|_| panic!("{} is missing", path.display()));
+    assert!(
+        expected == actual,
+        "{} differs from the rendering:\n{}",
+        path.display(),
+        StrComparison::new(&expected, actual)
+    );
+}
+
+#[test]
+fn markdown() {
+    check("full.md", &full().to_markdown());
+    check("sparse.md", &sparse().to_markdown());
+}
+
+#[test]
+fn plaintext() {
+    check("full.txt", &full().to_plaintext());
+    check("sparse.txt", &sparse().to_plaintext());
+}
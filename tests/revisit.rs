@@ -0,0 +1,177 @@
+//! Stored articles fetched again after `RunOptions::revisit_after`: an article edited since
+//! gets a revision and its new version stored, an unchanged one is left alone.
+
+use axum::{extract::Path, routing::get, Router};
+use indonesian_media_crawler::detik::{DetikArticle, DetikData};
+use indonesian_media_crawler::memory::MemoryStorage;
+use indonesian_media_crawler::{
+    run_scrapper, CrawlContext, Crawler, CrawlerResult, ExtractedLink, RunOptions, Storage,
+};
+use scraper::{Html, Selector};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, timeout, Duration};
+
+struct PageCrawler;
+
+impl Crawler for PageCrawler {
+    type Document = DetikArticle;
+
+    fn can_be_scrapped(&self, _doc: &Html) -> bool {
+        true
+    }
+
+    fn crawl(&self, _ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        let text = |selector: &str| {
+            doc.select(&Selector::parse(selector).unwrap())
+                .map(|e| e.inner_html())
+                .collect::<Vec<_>>()
+        };
+        let article = DetikArticle {
+            title: text("title").pop(),
+            published_date: None,
+            description: None,
+            thumbnail_url: None,
+            author: None,
+            desk: None,
+            category: None,
+            language: None,
+            keywords: vec![],
+            keywords_raw: None,
+            videos: vec![],
+            photos: vec![],
+            body_selector: None,
+            paragraphs: text("p"),
+        };
+        CrawlerResult::document_and_links(article, vec![])
+    }
+
+    fn extract_links(&self, _ctx: &CrawlContext, _doc: &Html) -> Vec<ExtractedLink> {
+        vec![]
+    }
+}
+
+type Fetches = Arc<Mutex<HashMap<String, u32>>>;
+
+/// `/edited` gets a new last paragraph from its second fetch on, `/same` never changes.
+fn start_site(fetches: Fetches) -> SocketAddr {
+    let app = Router::new().route(
+        "/:page",
+        get(move |Path(page): Path<String>| async move {
+            let fetch = {
+                let mut fetches = fetches.lock().unwrap();
+                let count = fetches.entry(page.clone()).or_default();
+                *count += 1;
+                *count
+            };
+            let last = if page == "edited" && fetch > 1 {
+                "Ralat: korban dua orang"
+            } else {
+                "Korban satu orang"
+            };
+            axum::response::Html(format!(
+                "<html><title>{}</title><body><p>Banjir di Jakarta</p><p>{}</p></body></html>",
+                page, last
+            ))
+        }),
+    );
+    let server = axum::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(app.into_make_service());
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+/// Crawl both pages into `storage` until each was fetched twice, returning `watched`, the
+/// same data, and their urls.
+async fn crawl<S>(storage: S, watched: S) -> (Arc<S>, String, String)
+where
+    S: Storage<Record = DetikArticle> + Send + Sync + 'static,
+{
+    let fetches = Fetches::default();
+    let site = start_site(Arc::clone(&fetches));
+    let edited = format!("http://{}/edited", site);
+    let same = format!("http://{}/same", site);
+
+    let options = RunOptions {
+        request_delay: Duration::from_millis(10),
+        revisit_after: Some(Duration::from_secs(1)),
+        ..RunOptions::default()
+    };
+    let controller = options.controller.clone();
+    let watched = Arc::new(watched);
+    let watching = Arc::clone(&watched);
+    tokio::spawn(async move {
+        loop {
+            let revisited = {
+                let fetches = fetches.lock().unwrap();
+                fetches.len() == 2 && fetches.values().all(|&n| n >= 2)
+            };
+            if revisited && watching.running_count().await.unwrap() == 0 {
+                break;
+            }
+            sleep(Duration::from_millis(50)).await;
+        }
+        controller.stop();
+    });
+    timeout(
+        Duration::from_secs(30),
+        run_scrapper(
+            PageCrawler,
+            storage,
+            vec![edited.clone(), same.clone()],
+            options,
+        ),
+    )
+    .await
+    .expect("Articles never revisited")
+    .unwrap();
+    (watched, edited, same)
+}
+
+async fn check<S: Storage<Record = DetikArticle>>(storage: &S, edited: &str, same: &str) {
+    assert_eq!(storage.results_count().await.unwrap(), 2);
+
+    let revisions = storage.revisions_get(edited).await.unwrap();
+    assert_eq!(revisions.len(), 1);
+    assert_eq!(
+        revisions[0].diff,
+        "paragraphs: 1 changed, 0 added, 0 removed"
+    );
+    assert_eq!(
+        revisions[0].paragraphs,
+        vec!["Banjir di Jakarta", "Ralat: korban dua orang"]
+    );
+    let stored = storage.results_get(edited).await.unwrap().unwrap();
+    assert_eq!(stored.paragraphs, revisions[0].paragraphs);
+    assert_eq!(stored.title.as_deref(), Some("edited"));
+
+    assert!(storage.revisions_get(same).await.unwrap().is_empty());
+    let stored = storage.results_get(same).await.unwrap().unwrap();
+    assert_eq!(
+        stored.paragraphs,
+        vec!["Banjir di Jakarta", "Korban satu orang"]
+    );
+    assert!(storage.visited_is_exists(edited).await.unwrap());
+    assert!(storage.visited_is_exists(same).await.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn edited_article_revised_in_memory() {
+    let storage = MemoryStorage::<DetikArticle>::new();
+    let (storage, edited, same) = crawl(storage.clone(), storage).await;
+    check(storage.as_ref(), &edited, &same).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn edited_article_revised_in_sqlite() {
+    if std::path::Path::new("test_revisit.db").is_file() {
+        std::fs::remove_file("test_revisit.db").unwrap();
+    }
+    let storage = DetikData::new("test_revisit").await.unwrap();
+    let watched = DetikData::new("test_revisit").await.unwrap();
+    let (storage, edited, same) = crawl(storage, watched).await;
+    check(storage.as_ref(), &edited, &same).await;
+
+    std::fs::remove_file("test_revisit.db").unwrap();
+}
@@ -0,0 +1,101 @@
+//! Crawls pages taking known times to parse: the ones past `RunOptions::slow_parse` are told
+//! to the `CrawlObserver` as slow, and the slowest ones end up first in the report.
+
+use axum::{http::Uri, response::Html, Router};
+use indonesian_media_crawler::memory::MemoryStorage;
+use indonesian_media_crawler::quality::CrawlObserver;
+use indonesian_media_crawler::report::StopReason;
+use indonesian_media_crawler::testing::MockCrawler;
+use indonesian_media_crawler::{run_scrapper, Article, CrawlerResult, ParseTiming, RunOptions};
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex};
+use tokio::time::{timeout, Duration};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Page {
+    paragraphs: Vec<String>,
+}
+
+impl Article for Page {
+    fn get_paragraphs(&self) -> &[String] {
+        &self.paragraphs
+    }
+}
+
+/// The pages parsed, with whether they were slow.
+#[derive(Debug, Default)]
+struct Parses(Mutex<Vec<(ParseTiming, bool)>>);
+
+impl CrawlObserver for Parses {
+    fn on_parsed(&self, timing: &ParseTiming, slow: bool) {
+        self.0.lock().unwrap().push((timing.clone(), slow));
+    }
+}
+
+fn start_site() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new().fallback(move |uri: Uri| async move {
+        Html(MockCrawler::<Page>::page(&format!(
+            "http://{}{}",
+            addr, uri
+        )))
+    });
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service()),
+    );
+    addr
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn slow_parses_flagged_and_reported() {
+    let page = |paragraphs: usize| Page {
+        paragraphs: vec!["Isi.".to_string(); paragraphs],
+    };
+    let crawler = MockCrawler::new()
+        .with("/slow/", CrawlerResult::document_and_links(page(3), vec![]))
+        .with("/a/", CrawlerResult::document_and_links(page(1), vec![]))
+        .with_delay("/slow/", Duration::from_millis(300));
+    let parses = Arc::new(Parses::default());
+    let options = RunOptions {
+        max_fetching: 1,
+        request_delay: Duration::from_millis(10),
+        follow_links: false,
+        slow_parse: Some(Duration::from_millis(200)),
+        observer: Some(parses.clone()),
+        ..RunOptions::default()
+    };
+
+    let base = format!("http://{}", start_site());
+    let slow = format!("{}/slow/1", base);
+    let mut urls: Vec<_> = (0..4).map(|i| format!("{}/a/{}", base, i)).collect();
+    urls.insert(2, slow.clone());
+    let report = timeout(
+        Duration::from_secs(30),
+        run_scrapper(crawler, MemoryStorage::new(), urls, options),
+    )
+    .await
+    .expect("Crawl did not finish")
+    .unwrap();
+    assert_eq!(report.stop_reason, StopReason::Idle);
+
+    let parses = parses.0.lock().unwrap().clone();
+    assert_eq!(parses.len(), 5);
+    let flagged: Vec<_> = parses
+        .iter()
+        .filter(|(_, slow)| *slow)
+        .map(|(timing, _)| timing.url.as_str())
+        .collect();
+    assert_eq!(flagged, vec![slow.as_str()]);
+
+    assert_eq!(report.slowest_parses.len(), 5);
+    let slowest = &report.slowest_parses[0];
+    assert_eq!(slowest.url, slow);
+    assert!(slowest.duration_ms >= 300, "{:?}", slowest);
+    assert_eq!(slowest.paragraphs, 3);
+    assert!(report.slowest_parses[1..]
+        .iter()
+        .all(|timing| timing.duration_ms < 200 && timing.paragraphs == 1));
+}
@@ -0,0 +1,389 @@
+#![cfg(feature = "http-api")]
+
+use axum::{extract::Path, routing::get, Router};
+use indonesian_media_crawler::detik::{DetikArticle, DetikData};
+use indonesian_media_crawler::memory::MemoryStorage;
+use indonesian_media_crawler::{
+    run_scrapper, CrawlContext, CrawlController, Crawler, CrawlerResult, ExtractedLink,
+    IngestConfig, RunOptions, Storage,
+};
+use scraper::{Html, Selector};
+use serde_json::Value;
+use std::net::{SocketAddr, TcpListener};
+use tokio::time::{sleep, timeout, Duration, Instant};
+
+struct PageCrawler;
+
+impl Crawler for PageCrawler {
+    type Document = DetikArticle;
+
+    fn can_be_scrapped(&self, _doc: &Html) -> bool {
+        true
+    }
+
+    fn crawl(&self, _ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        let title = doc
+            .select(&Selector::parse("title").unwrap())
+            .next()
+            .map(|t| t.inner_html());
+        let paragraphs = doc
+            .select(&Selector::parse("p").unwrap())
+            .map(|p| p.inner_html())
+            .collect();
+        let article = DetikArticle {
+            title,
+            published_date: None,
+            description: None,
+            thumbnail_url: None,
+            author: None,
+            desk: None,
+            category: None,
+            language: None,
+            keywords: vec![],
+            keywords_raw: None,
+            videos: vec![],
+            photos: vec![],
+            body_selector: None,
+            paragraphs,
+        };
+        CrawlerResult::document_and_links(article, vec![])
+    }
+
+    fn extract_links(&self, _ctx: &CrawlContext, _doc: &Html) -> Vec<ExtractedLink> {
+        vec![]
+    }
+}
+
+async fn page(Path(id): Path<u32>) -> axum::response::Html<String> {
+    sleep(Duration::from_millis(200)).await;
+    if id == 0 {
+        return axum::response::Html("<html><title>Empty</title></html>".to_string());
+    }
+    axum::response::Html(format!(
+        "<html><title>Page {}</title><body><p>Body {}</p></body></html>",
+        id, id
+    ))
+}
+
+fn start_site() -> SocketAddr {
+    let app = Router::new().route("/page/:id", get(page));
+    let server = axum::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(app.into_make_service());
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+fn free_addr() -> SocketAddr {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+}
+
+async fn get_json(url: &str) -> Value {
+    let body = reqwest::get(url).await.unwrap().text().await.unwrap();
+    serde_json::from_str(&body).unwrap()
+}
+
+async fn post_json(url: &str) -> Value {
+    let body = reqwest::Client::new()
+        .post(url)
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    serde_json::from_str(&body).unwrap()
+}
+
+async fn wait_for_results(api: &str, at_least: u64) -> Value {
+    let deadline = Instant::now() + Duration::from_secs(15);
+    loop {
+        let status = get_json(&format!("{}/status", api)).await;
+        if status["counts"]["results"].as_u64().unwrap() >= at_least {
+            return status;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "crawl did not progress: {}",
+            status
+        );
+        sleep(Duration::from_millis(100)).await;
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn status_api_reports_and_controls_the_crawl() {
+    if std::path::Path::new("test_status_api.db").is_file() {
+        std::fs::remove_file("test_status_api.db").unwrap();
+    }
+    let site = start_site();
+    let status_addr = free_addr();
+    let api = format!("http://{}", status_addr);
+
+    let controller = CrawlController::new();
+    controller.pause();
+    let options = RunOptions {
+        max_fetching: 1,
+        max_parsing: 1,
+        controller: controller.clone(),
+        status_addr: Some(status_addr),
+        ..RunOptions::default()
+    };
+    let seeds = (0..30)
+        .map(|i| format!("http://{}/page/{}", site, i))
+        .collect();
+    let storage = DetikData::new("test_status_api").await.unwrap();
+    let crawl = tokio::spawn(run_scrapper(PageCrawler, storage, seeds, options));
+
+    wait_for_api(&api).await;
+
+    sleep(Duration::from_millis(1500)).await;
+    let status = get_json(&format!("{}/status", api)).await;
+    assert!(status["session_id"].is_string());
+    assert!(status["uptime_secs"].is_u64());
+    assert!(status["rates"]["extracted_per_minute"].is_f64());
+    assert_eq!(status["paused"], Value::Bool(true));
+    assert_eq!(status["counts"]["queued"], 30);
+    assert_eq!(status["counts"]["results"], 0);
+    assert_eq!(status["fetched"], 0);
+
+    assert_eq!(
+        post_json(&format!("{}/resume", api)).await["paused"],
+        Value::Bool(false)
+    );
+    wait_for_results(&api, 2).await;
+
+    assert_eq!(
+        post_json(&format!("{}/pause", api)).await["paused"],
+        Value::Bool(true)
+    );
+    // Let the fetches already in flight finish
+    sleep(Duration::from_millis(1500)).await;
+    let before = get_json(&format!("{}/status", api)).await;
+    sleep(Duration::from_millis(2500)).await;
+    let after = get_json(&format!("{}/status", api)).await;
+    assert_eq!(before["fetched"], after["fetched"]);
+    assert_eq!(before["counts"]["results"], after["counts"]["results"]);
+    assert!(after["counts"]["queued"].as_u64().unwrap() > 0);
+    assert_eq!(after["latency"]["fetch"]["count"], after["fetched"]);
+    assert_eq!(
+        after["latency_by_host"]["127.0.0.1"]["first_byte"]["count"],
+        after["fetched"]
+    );
+
+    let recent = get_json(&format!("{}/recent?n=1", api)).await;
+    let recent = recent.as_array().unwrap();
+    assert_eq!(recent.len(), 1);
+    assert!(recent[0]["url"].as_str().unwrap().starts_with("http://"));
+    assert!(recent[0]["title"].as_str().unwrap().starts_with("Page "));
+    assert!(recent[0].get("published_date").is_some());
+
+    let warned = get_json(&format!("{}/warned?n=50", api)).await;
+    let warned = warned.as_array().unwrap();
+    assert_eq!(warned.len(), 1);
+    assert_eq!(warned[0]["url"], format!("http://{}/page/0", site));
+    assert_eq!(warned[0]["reason"], "empty_paragraphs");
+
+    controller.stop();
+    timeout(Duration::from_secs(5), crawl)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert!(reqwest::get(format!("{}/status", api)).await.is_err());
+
+    std::fs::remove_file("test_status_api.db").unwrap();
+}
+
+async fn wait_for_api(api: &str) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while reqwest::get(format!("{}/status", api)).await.is_err() {
+        assert!(Instant::now() < deadline, "status API never came up");
+        sleep(Duration::from_millis(50)).await;
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn ingestion_api_queues_urls_and_serves_articles() {
+    if std::path::Path::new("test_ingest_api.db").is_file() {
+        std::fs::remove_file("test_ingest_api.db").unwrap();
+    }
+    let site = start_site();
+    let status_addr = free_addr();
+    let api = format!("http://{}", status_addr);
+    let page = |id: u32| format!("http://{}/page/{}", site, id);
+
+    let controller = CrawlController::new();
+    let options = RunOptions {
+        controller: controller.clone(),
+        status_addr: Some(status_addr),
+        ingest: Some(IngestConfig {
+            priority: 5,
+            ..IngestConfig::new("secret")
+        }),
+        ..RunOptions::default()
+    };
+    let storage = DetikData::new("test_ingest_api").await.unwrap();
+    let crawl = tokio::spawn(run_scrapper(PageCrawler, storage, vec![page(1)], options));
+    wait_for_api(&api).await;
+    wait_for_results(&api, 1).await;
+
+    let client = reqwest::Client::new();
+    let enqueue = |token: Option<&str>, urls: Vec<String>| {
+        let mut request = client
+            .post(format!("{}/enqueue", api))
+            .header("content-type", "application/json")
+            .body(serde_json::to_string(&urls).unwrap());
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        request.send()
+    };
+    for token in [None, Some("wrong")] {
+        let response = enqueue(token, vec![page(9)]).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+    }
+
+    let urls = vec![
+        format!("{}#komentar", page(2)),
+        page(3),
+        page(3),
+        page(1),
+        "ftp://files.detik.com/1".to_string(),
+    ];
+    let response = enqueue(Some("secret"), urls).await.unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let enqueued: Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(enqueued["queued"], serde_json::json!([page(2), page(3)]));
+    assert_eq!(
+        enqueued["duplicates"],
+        serde_json::json!([page(3), page(1)])
+    );
+    assert_eq!(enqueued["rejected"].as_object().unwrap().len(), 1);
+    assert!(enqueued["rejected"]["ftp://files.detik.com/1"].is_string());
+
+    // The posted urls are crawled like the seeds
+    wait_for_results(&api, 3).await;
+    let article = |token: &str, url: &str| {
+        client
+            .get(format!("{}/article", api))
+            .query(&[("url", url)])
+            .bearer_auth(token)
+            .send()
+    };
+    let response = article("secret", &page(2)).await.unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let stored: Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(stored["url"], page(2));
+    assert_eq!(stored["title"], "Page 2");
+    assert_eq!(stored["paragraphs"], serde_json::json!(["Body 2"]));
+    assert_eq!(stored["referrer"], Value::Null);
+    assert_eq!(
+        article("secret", &page(9)).await.unwrap().status(),
+        reqwest::StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        article("wrong", &page(2)).await.unwrap().status(),
+        reqwest::StatusCode::UNAUTHORIZED
+    );
+    // The server waits for the connections kept alive to close before stopping
+    drop(client);
+
+    controller.stop();
+    timeout(Duration::from_secs(5), crawl)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+
+    std::fs::remove_file("test_ingest_api.db").unwrap();
+}
+
+/// The urls posted with the `IngestConfig::priority` are claimed before the ones queued
+/// earlier.
+#[tokio::test(flavor = "multi_thread")]
+async fn ingested_urls_claimed_first() {
+    let site = start_site();
+    let status_addr = free_addr();
+    let api = format!("http://{}", status_addr);
+    let page = |id: u32| format!("http://{}/page/{}", site, id);
+    let controller = CrawlController::new();
+    controller.pause();
+    let options = RunOptions {
+        controller: controller.clone(),
+        status_addr: Some(status_addr),
+        ingest: Some(IngestConfig {
+            priority: 5,
+            ..IngestConfig::new("secret")
+        }),
+        ..RunOptions::default()
+    };
+    let storage = MemoryStorage::<DetikArticle>::new();
+    let seeds = (1..=3).map(page).collect();
+    let crawl = tokio::spawn(run_scrapper(PageCrawler, storage.clone(), seeds, options));
+    wait_for_api(&api).await;
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/enqueue", api))
+        .bearer_auth("secret")
+        .header("content-type", "application/json")
+        .body(serde_json::to_string(&[page(4)]).unwrap())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+    // Claimed while the crawl is paused, as another worker sharing the storage would
+    let claimed: Vec<String> = storage
+        .queued_claim_n("other", 2)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|queued| queued.url.to_string())
+        .collect();
+    assert_eq!(claimed, vec![page(4), page(1)]);
+
+    controller.stop();
+    timeout(Duration::from_secs(5), crawl)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn ingestion_api_disabled_by_default() {
+    let site = start_site();
+    let status_addr = free_addr();
+    let api = format!("http://{}", status_addr);
+    let controller = CrawlController::new();
+    controller.pause();
+    let options = RunOptions {
+        controller: controller.clone(),
+        status_addr: Some(status_addr),
+        ..RunOptions::default()
+    };
+    let storage = MemoryStorage::<DetikArticle>::new();
+    let seeds = vec![format!("http://{}/page/1", site)];
+    let crawl = tokio::spawn(run_scrapper(PageCrawler, storage, seeds, options));
+    wait_for_api(&api).await;
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/enqueue", api))
+        .bearer_auth("")
+        .header("content-type", "application/json")
+        .body(format!("[\"http://{}/page/2\"]", site))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+
+    controller.stop();
+    timeout(Duration::from_secs(5), crawl)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+}
@@ -0,0 +1,395 @@
+//! A crawl whose storage stops taking writes, like on a full disk: it's paused rather than
+//! fetching pages it can't store, resumes once a canary write goes through again, and stops
+//! once too many failed, with `RunOptions::storage_outage`.
+
+use axum::{http::Uri, response::Html, Router};
+use chrono::{DateTime, FixedOffset};
+use indonesian_media_crawler::memory::MemoryStorage;
+use indonesian_media_crawler::outage::StorageOutage;
+use indonesian_media_crawler::report::{CrawlReport, StopReason};
+use indonesian_media_crawler::testing::MockCrawler;
+use indonesian_media_crawler::{
+    run_scrapper, Article, Bucket, CrawlerError, CrawlerResult, ExtractionStatus, QueuedUrl,
+    ResultEntry, ResultSummary, Revision, RunOptions, Storage, UrlBucket, WarnedEntry,
+};
+use std::net::{SocketAddr, TcpListener};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::time::{sleep, timeout, Duration};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Page {
+    paragraphs: Vec<String>,
+}
+
+impl Article for Page {
+    fn get_paragraphs(&self) -> &[String] {
+        &self.paragraphs
+    }
+}
+
+/// A `MemoryStorage` whose writes all fail as on a full disk once `fills_after` results were
+/// stored, until it's freed.
+#[derive(Debug, Clone)]
+struct FullDisk {
+    inner: MemoryStorage<Page>,
+    fills_after: usize,
+    stored: Arc<AtomicUsize>,
+    full: Arc<AtomicBool>,
+}
+
+impl FullDisk {
+    fn new(fills_after: usize) -> Self {
+        FullDisk {
+            inner: MemoryStorage::new(),
+            fills_after,
+            stored: Default::default(),
+            full: Default::default(),
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.full.load(Ordering::SeqCst)
+    }
+
+    fn free(&self) {
+        self.full.store(false, Ordering::SeqCst);
+    }
+
+    fn write(&self) -> Result<(), CrawlerError> {
+        if self.is_full() {
+            return Err(CrawlerError::StorageUnavailable(
+                "database or disk is full".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for FullDisk {
+    type Record = Page;
+
+    fn bucket(&self, bucket: Bucket) -> &dyn UrlBucket {
+        self.inner.bucket(bucket)
+    }
+
+    async fn queued_insert<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        self.write()?;
+        self.inner.queued_insert(item).await
+    }
+
+    async fn queued_insert_batch<I: AsRef<str> + Send + Sync>(
+        &self,
+        items: &[I],
+    ) -> Result<(), CrawlerError> {
+        self.write()?;
+        self.inner.queued_insert_batch(items).await
+    }
+
+    async fn queued_delete<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        self.write()?;
+        self.inner.queued_delete(item).await
+    }
+
+    async fn queued_insert_with_meta<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        referrer: Option<&str>,
+        depth: u32,
+    ) -> Result<(), CrawlerError> {
+        self.write()?;
+        self.inner
+            .queued_insert_with_meta(item, referrer, depth)
+            .await
+    }
+
+    async fn queued_insert_with_priority<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        priority: i32,
+    ) -> Result<(), CrawlerError> {
+        self.write()?;
+        self.inner.queued_insert_with_priority(item, priority).await
+    }
+
+    async fn running_insert<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        self.write()?;
+        self.inner.running_insert(item).await
+    }
+
+    async fn running_delete<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        self.write()?;
+        self.inner.running_delete(item).await
+    }
+
+    async fn visited_insert<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        self.write()?;
+        self.inner.visited_insert(item).await
+    }
+
+    async fn visited_delete<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        self.write()?;
+        self.inner.visited_delete(item).await
+    }
+
+    async fn queued_claim_n(
+        &self,
+        worker_id: &str,
+        n: u32,
+    ) -> Result<Vec<QueuedUrl>, CrawlerError> {
+        self.write()?;
+        self.inner.queued_claim_n(worker_id, n).await
+    }
+
+    async fn running_claim<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        worker_id: &str,
+    ) -> Result<bool, CrawlerError> {
+        self.write()?;
+        self.inner.running_claim(item, worker_id).await
+    }
+
+    async fn running_touch(
+        &self,
+        worker_id: &str,
+        now: DateTime<FixedOffset>,
+    ) -> Result<(), CrawlerError> {
+        self.write()?;
+        self.inner.running_touch(worker_id, now).await
+    }
+
+    async fn running_reap(
+        &self,
+        stale_before: DateTime<FixedOffset>,
+    ) -> Result<Vec<String>, CrawlerError> {
+        self.write()?;
+        self.inner.running_reap(stale_before).await
+    }
+
+    async fn running_release(&self, worker_id: &str) -> Result<Vec<String>, CrawlerError> {
+        self.write()?;
+        self.inner.running_release(worker_id).await
+    }
+
+    async fn running_requeue<I: AsRef<str> + Send>(&self, item: I) -> Result<(), CrawlerError> {
+        self.write()?;
+        self.inner.running_requeue(item).await
+    }
+
+    async fn results_count(&self) -> Result<u32, CrawlerError> {
+        self.inner.results_count().await
+    }
+
+    async fn results_recent(&self, n: u32) -> Result<Vec<ResultSummary>, CrawlerError> {
+        self.inner.results_recent(n).await
+    }
+
+    async fn results_get(&self, url: &str) -> Result<Option<ResultEntry>, CrawlerError> {
+        self.inner.results_get(url).await
+    }
+
+    async fn results_insert_with_referrer<I: AsRef<str> + Send>(
+        &self,
+        record: (I, Page),
+        referrer: Option<&str>,
+    ) -> Result<(), CrawlerError> {
+        self.write()?;
+        self.inner
+            .results_insert_with_referrer(record, referrer)
+            .await?;
+        if self.stored.fetch_add(1, Ordering::SeqCst) + 1 == self.fills_after {
+            self.full.store(true, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+
+    async fn results_insert_metadata_only<I: AsRef<str> + Send>(
+        &self,
+        record: (I, Page),
+        referrer: Option<&str>,
+    ) -> Result<(), CrawlerError> {
+        self.write()?;
+        self.inner
+            .results_insert_metadata_only(record, referrer)
+            .await
+    }
+
+    async fn results_status(&self, url: &str) -> Result<Option<ExtractionStatus>, CrawlerError> {
+        self.inner.results_status(url).await
+    }
+
+    async fn results_revise<I: AsRef<str> + Send>(
+        &self,
+        record: (I, Page),
+        diff: &str,
+    ) -> Result<(), CrawlerError> {
+        self.write()?;
+        self.inner.results_revise(record, diff).await
+    }
+
+    async fn revisions_get(&self, url: &str) -> Result<Vec<Revision>, CrawlerError> {
+        self.inner.revisions_get(url).await
+    }
+
+    async fn revisit_schedule(
+        &self,
+        url: &str,
+        due_at: DateTime<FixedOffset>,
+    ) -> Result<(), CrawlerError> {
+        self.write()?;
+        self.inner.revisit_schedule(url, due_at).await
+    }
+
+    async fn revisit_due(
+        &self,
+        now: DateTime<FixedOffset>,
+        n: u32,
+    ) -> Result<Vec<String>, CrawlerError> {
+        self.write()?;
+        self.inner.revisit_due(now, n).await
+    }
+
+    async fn warned_insert<I: AsRef<str> + Send>(
+        &self,
+        item: I,
+        reason: &str,
+    ) -> Result<(), CrawlerError> {
+        self.write()?;
+        self.inner.warned_insert(item, reason).await
+    }
+
+    async fn warned_count(&self) -> Result<u32, CrawlerError> {
+        self.inner.warned_count().await
+    }
+
+    async fn warned_recent(&self, n: u32) -> Result<Vec<WarnedEntry>, CrawlerError> {
+        self.inner.warned_recent(n).await
+    }
+
+    async fn warned_get(&self, item: &str) -> Result<Option<WarnedEntry>, CrawlerError> {
+        self.inner.warned_get(item).await
+    }
+
+    async fn warned_delete(&self, item: &str) -> Result<(), CrawlerError> {
+        self.write()?;
+        self.inner.warned_delete(item).await
+    }
+
+    async fn warned_schedule(
+        &self,
+        item: &str,
+        reason: &str,
+        attempts: u32,
+        next_retry_at: Option<DateTime<FixedOffset>>,
+    ) -> Result<(), CrawlerError> {
+        self.write()?;
+        self.inner
+            .warned_schedule(item, reason, attempts, next_retry_at)
+            .await
+    }
+
+    async fn warned_due(
+        &self,
+        now: DateTime<FixedOffset>,
+        n: u32,
+    ) -> Result<Vec<WarnedEntry>, CrawlerError> {
+        self.inner.warned_due(now, n).await
+    }
+}
+
+/// A site counting the pages it served.
+fn start_site(served: Arc<AtomicUsize>) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new().fallback(move |uri: Uri| {
+        let served = Arc::clone(&served);
+        async move {
+            served.fetch_add(1, Ordering::SeqCst);
+            Html(MockCrawler::<Page>::page(&format!(
+                "http://{}{}",
+                addr, uri
+            )))
+        }
+    });
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service()),
+    );
+    addr
+}
+
+fn crawler() -> MockCrawler<Page> {
+    let page = Page {
+        paragraphs: vec!["Isi.".to_string()],
+    };
+    MockCrawler::new().with("/a/", CrawlerResult::document_and_links(page, vec![]))
+}
+
+/// A list of `ARTICLES` articles, crawled until none is left.
+const ARTICLES: usize = 20;
+
+fn options(storage_outage: StorageOutage) -> RunOptions {
+    RunOptions {
+        max_fetching: 2,
+        request_delay: Duration::from_millis(10),
+        follow_links: false,
+        storage_outage,
+        ..RunOptions::default()
+    }
+}
+
+async fn crawl(storage: &FullDisk, base: &str, options: RunOptions) -> CrawlReport {
+    let urls = (0..ARTICLES).map(|i| format!("{}/a/{}", base, i)).collect();
+    timeout(
+        Duration::from_secs(30),
+        run_scrapper(crawler(), storage.clone(), urls, options),
+    )
+    .await
+    .expect("Crawl did not finish")
+    .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn paused_until_writes_work_again() {
+    let served = Arc::new(AtomicUsize::new(0));
+    let base = format!("http://{}", start_site(Arc::clone(&served)));
+    let storage = FullDisk::new(5);
+    let outage = StorageOutage::new(Duration::from_millis(100), 100);
+
+    let disk = storage.clone();
+    let freed = tokio::spawn(async move {
+        while !disk.is_full() {
+            sleep(Duration::from_millis(10)).await;
+        }
+        // The fetches in flight when it filled up aside, nothing is fetched while it's full
+        sleep(Duration::from_millis(300)).await;
+        let paused = served.load(Ordering::SeqCst);
+        sleep(Duration::from_millis(500)).await;
+        assert_eq!(served.load(Ordering::SeqCst), paused);
+        disk.free();
+    });
+    let report = crawl(&storage, &base, options(outage)).await;
+    freed.await.unwrap();
+
+    // The urls held during the outage crawled once it's over
+    assert_eq!(report.stop_reason, StopReason::Idle);
+    assert_eq!(storage.results_count().await.unwrap(), ARTICLES as u32);
+    assert!(storage.queued_is_empty().await.unwrap());
+    assert_eq!(storage.running_count().await.unwrap(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stopped_after_failed_canaries() {
+    let base = format!("http://{}", start_site(Default::default()));
+    let storage = FullDisk::new(5);
+    let outage = StorageOutage::new(Duration::from_millis(50), 3);
+    let report = crawl(&storage, &base, options(outage)).await;
+
+    assert_eq!(report.stop_reason, StopReason::StorageUnavailable);
+    assert_eq!(storage.results_count().await.unwrap(), 5);
+    assert!(report.fetched < ARTICLES as u64);
+    assert_eq!(report.failed_tasks, 0);
+}
@@ -0,0 +1,303 @@
+//! Runs the same sequence of `Storage` calls on every backend and checks they all answer the
+//! same way, whether they implement a method themselves or get the default over their buckets.
+
+use chrono::DateTime;
+use futures::TryStreamExt;
+use indonesian_media_crawler::detik::{DetikArticle, DetikData};
+use indonesian_media_crawler::generic::{GenericArticle, SiteStorage};
+use indonesian_media_crawler::memory::MemoryStorage;
+use indonesian_media_crawler::recrawl::LinkSnapshot;
+use indonesian_media_crawler::{HostState, HubState, ResultEntry, Storage, WarnedEntry};
+use std::path::Path;
+
+fn detik_article(title: &str) -> DetikArticle {
+    DetikArticle {
+        title: Some(title.to_string()),
+        published_date: None,
+        description: None,
+        thumbnail_url: None,
+        author: None,
+        desk: None,
+        category: None,
+        language: None,
+        keywords: vec![],
+        keywords_raw: None,
+        videos: vec![],
+        photos: vec![],
+        body_selector: None,
+        paragraphs: vec!["p".to_string()],
+    }
+}
+
+fn generic_article(title: &str) -> GenericArticle {
+    GenericArticle {
+        title: Some(title.to_string()),
+        paragraphs: vec!["p".to_string()],
+        ..GenericArticle::default()
+    }
+}
+
+/// What `storage` answers along the way, as lines to compare between backends.
+async fn transcript<S: Storage>(storage: &S, article: fn(&str) -> S::Record) -> Vec<String> {
+    let mut out = vec![];
+    macro_rules! note {
+        ($call:expr) => {
+            out.push(format!(
+                "{} = {:?}",
+                stringify!($call),
+                $call.await.unwrap()
+            ))
+        };
+    }
+
+    note!(storage.queued_insert_batch(&["1", "2", "3", "2"]));
+    note!(storage.queued_insert("4"));
+    note!(storage.queued_insert("1"));
+    note!(storage.queued_get());
+    note!(storage.queued_get_n(2));
+    note!(storage.queued_get_n(10));
+    note!(storage.queued_count());
+    note!(storage.queued_is_empty());
+    note!(storage.queued_stream().try_collect::<Vec<_>>());
+    note!(storage.queued_is_exists("3"));
+    note!(storage.queued_is_exists("5"));
+    let oldest = storage.queued_oldest_at().await.unwrap();
+    out.push(format!("queued_oldest_at = {:?}", oldest.is_some()));
+
+    note!(storage.running_insert("1"));
+    note!(storage.queued_delete("1"));
+    note!(storage.queued_delete("missing"));
+    note!(storage.running_insert("5"));
+    note!(storage.running_get());
+    note!(storage.running_count());
+    note!(storage.running_is_exists("5"));
+    note!(storage.merge_queue_and_running());
+    note!(storage.queued_get());
+    note!(storage.running_count());
+
+    note!(storage.visited_insert("6"));
+    note!(storage.visited_insert("6"));
+    note!(storage.running_insert("7"));
+    for url in ["6", "7", "8", "2"] {
+        note!(storage.queued_insert_unseen(url));
+    }
+    note!(storage.queued_insert_with_meta("9", Some("8"), 1));
+    note!(storage.queued_insert_with_meta("9", Some("other"), 5));
+    note!(storage.queued_insert_with_meta("6", Some("8"), 1));
+    note!(storage.queued_insert_with_priority("10", 5));
+    note!(storage.queued_insert_with_priority("9", 5));
+    note!(storage.queued_insert_prioritized("11", Some("8"), 2, 10));
+    note!(storage.queued_insert_prioritized("11", None, 0, 5));
+    note!(storage.queued_get());
+    note!(storage.queued_stream().try_collect::<Vec<_>>());
+    note!(storage.visited_stream().try_collect::<Vec<_>>());
+    note!(storage.visited_count());
+    note!(storage.visited_is_exists("6"));
+    note!(storage.visited_delete("6"));
+    note!(storage.visited_is_exists("6"));
+    note!(storage.running_delete("7"));
+    note!(storage.running_is_exists("7"));
+
+    note!(storage.results_insert(("a", article("A"))));
+    note!(storage.results_insert(("b", article("B"))));
+    note!(storage.results_insert(("a", article("A again"))));
+    note!(storage.results_insert_with_referrer(("c", article("C")), Some("b")));
+    note!(storage.results_insert_with_referrer(("c", article("C again")), Some("a")));
+    note!(storage.results_insert_with_referrer(("a", article("A again")), Some("b")));
+    note!(storage.results_count());
+    let mut recent: Vec<_> = storage
+        .results_recent(10)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|r| (r.url, r.title, r.referrer))
+        .collect();
+    recent.sort();
+    out.push(format!("results_recent = {:?}", recent));
+    let get = |entry: Option<ResultEntry>| {
+        entry.map(|r| {
+            (
+                r.url,
+                r.title,
+                r.referrer,
+                r.paragraphs,
+                r.created_at.is_some(),
+            )
+        })
+    };
+    out.push(format!(
+        "results_get(c) = {:?}",
+        get(storage.results_get("c").await.unwrap())
+    ));
+    out.push(format!(
+        "results_get(missing) = {:?}",
+        get(storage.results_get("missing").await.unwrap())
+    ));
+    note!(storage.results_revise(("c", article("C edited")), "title changed"));
+    note!(storage.results_revise(("d", article("D")), "stored again"));
+    note!(storage.results_count());
+    out.push(format!(
+        "results_get(c) = {:?}",
+        get(storage.results_get("c").await.unwrap())
+    ));
+    let revisions: Vec<_> = storage
+        .revisions_get("c")
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|r| (r.url, r.title, r.paragraphs, r.diff, r.created_at.is_some()))
+        .collect();
+    out.push(format!("revisions_get(c) = {:?}", revisions));
+    note!(storage.revisions_get("a"));
+
+    note!(storage.results_insert_metadata_only(("m", article("M")), Some("a")));
+    note!(storage.results_insert_metadata_only(("c", article("C metadata")), None));
+    note!(storage.results_status("m"));
+    note!(storage.results_status("c"));
+    note!(storage.results_status("missing"));
+    note!(storage.results_insert_with_referrer(("m", article("M full")), Some("b")));
+    note!(storage.results_status("m"));
+    out.push(format!(
+        "results_get(m) = {:?}",
+        get(storage.results_get("m").await.unwrap())
+    ));
+
+    note!(storage.warned_insert("w", "empty_paragraphs"));
+    note!(storage.warned_insert("w", "paywalled"));
+    note!(storage.warned_count());
+    let warned: Vec<_> = storage
+        .warned_recent(10)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|w| (w.url, w.reason))
+        .collect();
+    out.push(format!("warned_recent = {:?}", warned));
+
+    let now = DateTime::parse_from_rfc3339("2022-12-01T10:00:00+07:00").unwrap();
+    let hour = chrono::Duration::hours(1);
+    note!(storage.warned_schedule("x", "http_5xx", 0, Some(now + hour)));
+    note!(storage.warned_schedule("y", "timeout", 1, Some(now - hour)));
+    note!(storage.warned_schedule("z", "timeout", 2, None));
+    let due = |due: Vec<WarnedEntry>| {
+        due.into_iter()
+            .map(|w| (w.url, w.attempts, w.next_retry_at))
+            .collect::<Vec<_>>()
+    };
+    out.push(format!(
+        "warned_due(now) = {:?}",
+        due(storage.warned_due(now, 10).await.unwrap())
+    ));
+    out.push(format!(
+        "warned_due(now + 2h) = {:?}",
+        due(storage.warned_due(now + hour * 2, 1).await.unwrap())
+    ));
+    note!(storage.revisit_schedule("r1", now + hour));
+    note!(storage.revisit_schedule("r2", now - hour));
+    note!(storage.revisit_schedule("r3", now));
+    note!(storage.revisit_schedule("r1", now - hour * 2));
+    note!(storage.revisit_due(now, 2));
+    note!(storage.revisit_due(now, 10));
+    note!(storage.revisit_due(now + hour, 10));
+    let backoff = HostState {
+        host: "news.detik.com".to_string(),
+        multiplier: 2,
+        resume_at: now + hour,
+        updated_at: now,
+    };
+    note!(storage.host_state_get("news.detik.com"));
+    note!(storage.host_state_put(&backoff));
+    note!(storage.host_state_put(&HostState {
+        multiplier: 4,
+        ..backoff.clone()
+    }));
+    note!(storage.host_state_get("news.detik.com"));
+    note!(storage.host_state_get("finance.detik.com"));
+    let hub = HubState {
+        url: "https://news.detik.com/indeks".to_string(),
+        rate: 2.5,
+        recrawls: 3,
+        interval: std::time::Duration::from_secs(4 * 3600),
+        crawled_at: now,
+    };
+    note!(storage.hubs_get());
+    note!(storage.hub_put(&hub));
+    note!(storage.hub_put(&HubState {
+        url: format!("https://news.detik.com/{}", "x".repeat(600)),
+        interval: std::time::Duration::from_secs(600),
+        ..hub.clone()
+    }));
+    note!(storage.hub_put(&HubState {
+        rate: 0.5,
+        ..hub.clone()
+    }));
+    note!(storage.hub_get("https://news.detik.com/indeks"));
+    note!(storage.hub_get("https://finance.detik.com/indeks"));
+    note!(storage.hubs_get());
+    let snapshot = LinkSnapshot::new(&["https://news.detik.com/a", "https://news.detik.com/b"]);
+    note!(storage.hub_snapshot_get("https://news.detik.com/indeks"));
+    note!(storage.hub_snapshot_put("https://news.detik.com/indeks", &snapshot));
+    note!(storage.hub_snapshot_put(
+        &format!("https://news.detik.com/{}", "x".repeat(600)),
+        &LinkSnapshot::default()
+    ));
+    note!(storage.hub_snapshot_get("https://news.detik.com/indeks"));
+    note!(storage.hub_snapshot_get(&format!("https://news.detik.com/{}", "x".repeat(600))));
+
+    let z = storage.warned_get("z").await.unwrap().unwrap();
+    out.push(format!("warned_get(z) = {:?}", (z.reason, z.attempts)));
+    note!(storage.warned_delete("z"));
+    note!(storage.warned_get("z"));
+    note!(storage.warned_count());
+
+    let mut queued = storage.queued_get().await.unwrap();
+    queued.sort();
+    out.push(format!("queued_get = {:?}", queued));
+    let mut claimed = storage.queued_claim_n("a", 2).await.unwrap();
+    claimed.extend(storage.queued_claim_n("b", 10).await.unwrap());
+    claimed.sort_by(|a, b| a.url.cmp(&b.url));
+    out.push(format!("queued_claim_n = {:?}", claimed));
+    note!(storage.queued_count());
+    note!(storage.queued_is_empty());
+    let oldest = storage.queued_oldest_at().await.unwrap();
+    out.push(format!("queued_oldest_at = {:?}", oldest.is_some()));
+    note!(storage.running_count());
+    note!(storage.running_claim("https://news.detik.com/claimed", "b"));
+    note!(storage.running_claim("https://news.detik.com/claimed", "a"));
+    let later = chrono::Local::now() + chrono::Duration::hours(1);
+    note!(storage.running_touch("b", later.into()));
+    let reaped = storage.running_reap(later.into()).await.unwrap();
+    out.push(format!("running_reap = {}", reaped.len()));
+    note!(storage.running_release("b"));
+    note!(storage.running_count());
+
+    out
+}
+
+async fn remove(path: &str) {
+    if Path::new(path).is_file() {
+        tokio::fs::remove_file(path).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn backends_agree() {
+    let memory = transcript(&MemoryStorage::new(), detik_article).await;
+
+    remove("test_parity.db").await;
+    let detik = transcript(&DetikData::new("test_parity").await.unwrap(), detik_article).await;
+    remove("test_parity.db").await;
+
+    remove("test_parity_site.db").await;
+    let site = transcript(
+        &SiteStorage::new("site", "test_parity_site.db")
+            .await
+            .unwrap(),
+        generic_article,
+    )
+    .await;
+    remove("test_parity_site.db").await;
+
+    pretty_assertions::assert_eq!(memory, detik);
+    pretty_assertions::assert_eq!(memory, site);
+}
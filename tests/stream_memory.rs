@@ -0,0 +1,98 @@
+//! Streams a queue of 100k urls out of SQLite while a counting global allocator tracks the
+//! bytes held: reading the queue a url at a time must not grow with the queue, unlike
+//! `queued_get`. Kept to one test so no other test allocates meanwhile.
+
+use futures::TryStreamExt;
+use indonesian_media_crawler::detik::DetikData;
+use indonesian_media_crawler::Storage;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::future::Future;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct Counting;
+
+static HELD: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+impl Counting {
+    fn grow(&self, size: usize) {
+        let held = HELD.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK.fetch_max(held, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.grow(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        HELD.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        HELD.fetch_sub(layout.size(), Ordering::Relaxed);
+        self.grow(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// What `f` returns, and how many more bytes were held at most while it ran.
+async fn peak<T>(f: impl Future<Output = T>) -> (T, usize) {
+    let before = HELD.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    let result = f.await;
+    (result, PEAK.load(Ordering::Relaxed).saturating_sub(before))
+}
+
+const URLS: usize = 100_000;
+
+#[tokio::test]
+async fn streamed_queue() {
+    if Path::new("test_stream_memory.db").is_file() {
+        std::fs::remove_file("test_stream_memory.db").unwrap();
+    }
+    let storage = DetikData::new("test_stream_memory").await.unwrap();
+    let urls: Vec<String> = (0..URLS)
+        .map(|i| {
+            format!(
+                "https://news.detik.com/berita/d-{}/judul-berita-nomor-{}",
+                i, i
+            )
+        })
+        .collect();
+    storage.queued_insert_batch(&urls).await.unwrap();
+    drop(urls);
+    assert!(!storage.queued_is_empty().await.unwrap());
+
+    let (streamed, streaming) = peak(async {
+        let mut count = 0;
+        let mut last = String::new();
+        let mut stream = storage.queued_stream();
+        while let Some(url) = stream.try_next().await.unwrap() {
+            count += 1;
+            last = url;
+        }
+        (count, last)
+    })
+    .await;
+    let (loaded, loading) = peak(storage.queued_get()).await;
+    let loaded = loaded.unwrap();
+    println!(
+        "{} urls: {} bytes held at most streaming, {} loading",
+        URLS, streaming, loading
+    );
+
+    assert_eq!(streamed.0, URLS);
+    assert_eq!(streamed.1, loaded[URLS - 1]);
+    assert!(loading > 5_000_000, "{} bytes", loading);
+    assert!(streaming < 1_000_000, "{} bytes", streaming);
+
+    std::fs::remove_file("test_stream_memory.db").unwrap();
+}
@@ -0,0 +1,84 @@
+//! Serves the free and the paywalled tempo fixtures and checks only the free article is
+//! stored, the premium one being recorded as warned.
+
+use axum::{http::Uri, response::Html, Router};
+use indonesian_media_crawler::memory::MemoryStorage;
+use indonesian_media_crawler::tempo::{TempoArticle, TempoCrawler, PAYWALLED};
+use indonesian_media_crawler::{run_scrapper, RunOptions, Storage};
+use std::net::{SocketAddr, TcpListener};
+use tokio::time::{sleep, timeout, Duration};
+
+const FREE: &str = "/nasional/free";
+const PAYWALLED_PATH: &str = "/majalah/paywalled";
+
+fn start_site() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new().fallback(|uri: Uri| async move {
+        let fixture = match uri.path() {
+            FREE => "nasional-free.html",
+            PAYWALLED_PATH => "majalah-paywalled.html",
+            _ => return Html(String::new()),
+        };
+        Html(std::fs::read_to_string(format!("tests/htmls/tempo/{}", fixture)).unwrap())
+    });
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service()),
+    );
+    addr
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn paywalled_article_is_never_stored() {
+    let site = start_site();
+    let base = format!("http://{}", site);
+    let (free, paywalled) = (
+        format!("{}{}", base, FREE),
+        format!("{}{}", base, PAYWALLED_PATH),
+    );
+
+    // Links of the fixtures point to tempo.co and must not be followed
+    let crawler = TempoCrawler::new()
+        .with_allowed_hosts(["127.0.0.1"])
+        .with_http(true);
+    let storage = MemoryStorage::<TempoArticle>::new();
+    let options = RunOptions {
+        disable_politeness_for_hosts: vec![site.to_string()],
+        ..RunOptions::default()
+    };
+    let controller = options.controller.clone();
+
+    let watched = storage.clone();
+    tokio::spawn(async move {
+        while watched.results_count().await.unwrap() + watched.warned_count().await.unwrap() < 2
+            || watched.running_count().await.unwrap() > 0
+        {
+            sleep(Duration::from_millis(20)).await;
+        }
+        controller.stop();
+    });
+
+    timeout(
+        Duration::from_secs(30),
+        run_scrapper(
+            crawler,
+            storage.clone(),
+            vec![free.clone(), paywalled.clone()],
+            options,
+        ),
+    )
+    .await
+    .expect("Crawl did not finish")
+    .unwrap();
+
+    assert_eq!(storage.results_count().await.unwrap(), 1);
+    assert!(storage.result(&free).is_some());
+    assert!(storage.result(&paywalled).is_none());
+
+    let warned = storage.warned_recent(10).await.unwrap();
+    assert_eq!(warned.len(), 1);
+    assert_eq!(warned[0].url, paywalled);
+    assert_eq!(warned[0].reason.as_deref(), Some(PAYWALLED));
+}
@@ -0,0 +1,118 @@
+//! Crawls a fixed list of urls without following their links, and stops once the queue is
+//! drained, with `RunOptions::follow_links` and `RunOptions::exit_when_idle`.
+
+use axum::{http::Uri, response::Html, Router};
+use indonesian_media_crawler::memory::MemoryStorage;
+use indonesian_media_crawler::report::{CrawlReport, StopReason};
+use indonesian_media_crawler::testing::MockCrawler;
+use indonesian_media_crawler::{run_scrapper, Article, CrawlerResult, RunOptions, Storage};
+use std::net::{SocketAddr, TcpListener};
+use tokio::time::{timeout, Duration};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Page {
+    paragraphs: Vec<String>,
+}
+
+impl Article for Page {
+    fn get_paragraphs(&self) -> &[String] {
+        &self.paragraphs
+    }
+}
+
+fn start_site() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new().fallback(move |uri: Uri| async move {
+        Html(MockCrawler::<Page>::page(&format!(
+            "http://{}{}",
+            addr, uri
+        )))
+    });
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service()),
+    );
+    addr
+}
+
+/// Articles linking to a related article each, and a hub linking to three articles.
+fn crawler(base: &str) -> MockCrawler<Page> {
+    let page = Page {
+        paragraphs: vec!["Isi.".to_string()],
+    };
+    MockCrawler::new()
+        .with(
+            "/a/",
+            CrawlerResult::document_and_links(page, vec![format!("{}/related", base)]),
+        )
+        .with(
+            "/related",
+            CrawlerResult::Links(vec![format!("{}/a/related", base)]),
+        )
+        .with(
+            "/hub",
+            CrawlerResult::Links((0..3).map(|i| format!("{}/a/{}", base, i)).collect()),
+        )
+}
+
+async fn crawl(
+    storage: &MemoryStorage<Page>,
+    base: &str,
+    urls: Vec<String>,
+    options: RunOptions,
+) -> CrawlReport {
+    timeout(
+        Duration::from_secs(30),
+        run_scrapper(crawler(base), storage.clone(), urls, options),
+    )
+    .await
+    .expect("Crawl did not finish")
+    .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_crawled_without_links() {
+    let site = start_site();
+    let base = format!("http://{}", site);
+    let urls: Vec<String> = (0..40).map(|i| format!("{}/a/{}", base, i)).collect();
+    let storage = MemoryStorage::<Page>::new();
+    let options = RunOptions {
+        disable_politeness_for_hosts: vec![site.to_string()],
+        follow_links: false,
+        ..RunOptions::default()
+    };
+    let report = crawl(&storage, &base, urls.clone(), options).await;
+
+    assert_eq!(report.stop_reason, StopReason::Idle);
+    assert_eq!(report.extracted, 40);
+    assert_eq!(storage.results_count().await.unwrap(), 40);
+    for url in &urls {
+        assert!(storage.results_get(url).await.unwrap().is_some(), "{}", url);
+    }
+    // The related pages linked were never queued
+    assert_eq!(storage.visited_count().await.unwrap(), 40);
+    assert!(storage.queued_is_empty().await.unwrap());
+    assert_eq!(storage.running_count().await.unwrap(), 0);
+    assert_eq!(report.failed_tasks, 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn links_followed_until_idle() {
+    let site = start_site();
+    let base = format!("http://{}", site);
+    let storage = MemoryStorage::<Page>::new();
+    let options = RunOptions {
+        disable_politeness_for_hosts: vec![site.to_string()],
+        exit_when_idle: true,
+        ..RunOptions::default()
+    };
+    let report = crawl(&storage, &base, vec![format!("{}/hub", base)], options).await;
+
+    assert_eq!(report.stop_reason, StopReason::Idle);
+    // The 3 articles of the hub and the one linked from the related page
+    assert_eq!(storage.results_count().await.unwrap(), 4);
+    assert_eq!(storage.visited_count().await.unwrap(), 6);
+    assert!(storage.queued_is_empty().await.unwrap());
+}
@@ -0,0 +1,122 @@
+//! Enqueues the links of a large site, every page linking to the same navigation, into a
+//! `SqliteStorage` with and without its visited cache: the queues are the same, the cached
+//! one looking up few of the links.
+
+use indonesian_media_crawler::detik::DetikData;
+use indonesian_media_crawler::visited_cache::VisitedCacheStats;
+use indonesian_media_crawler::Storage;
+use std::collections::HashSet;
+use std::path::Path;
+
+const PAGES: usize = 300;
+const NAVIGATION: usize = 50;
+
+fn navigation() -> Vec<String> {
+    (0..NAVIGATION)
+        .map(|i| format!("https://news.detik.com/kanal-{}", i))
+        .collect()
+}
+
+fn article(i: usize) -> String {
+    format!("https://news.detik.com/berita/d-{}/judul", i)
+}
+
+/// Visit the navigation, then every article page, queueing the navigation and the next
+/// two articles from each.
+async fn crawl(storage: &DetikData) {
+    for url in navigation() {
+        storage.visited_insert(&url).await.unwrap();
+    }
+    for page in 0..PAGES {
+        let referrer = article(page);
+        storage.visited_insert(&referrer).await.unwrap();
+        let links = navigation()
+            .into_iter()
+            .chain([article(page + 1), article(page + 2)]);
+        for link in links {
+            storage
+                .queued_insert_with_meta(link, Some(&referrer), 1)
+                .await
+                .unwrap();
+        }
+    }
+}
+
+async fn queued(storage: &DetikData) -> HashSet<String> {
+    storage.queued_get().await.unwrap().into_iter().collect()
+}
+
+fn remove(name: &str) {
+    let path = format!("{}.db", name);
+    if Path::new(&path).is_file() {
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+#[tokio::test]
+async fn fewer_lookups_same_queue() {
+    remove("test_visited_uncached");
+    remove("test_visited_cached");
+    let uncached = DetikData::new("test_visited_uncached").await.unwrap();
+    let cached = DetikData::new("test_visited_cached")
+        .await
+        .unwrap()
+        .with_visited_cache(1000)
+        .await
+        .unwrap();
+    crawl(&uncached).await;
+    crawl(&cached).await;
+    // The articles, the navigation being visited before it's linked
+    assert_eq!(queued(&cached).await, queued(&uncached).await);
+    assert_eq!(queued(&cached).await.len(), PAGES + 1);
+
+    let stats = cached.visited_cache_stats().unwrap();
+    // Every navigation link answered from memory, the articles not visited yet when they're
+    // linked left to the storage but for the false positives of the filter
+    assert_eq!(stats.hits as usize, PAGES * NAVIGATION);
+    assert_eq!((stats.misses + stats.lookups()) as usize, PAGES * 2);
+    assert_eq!(stats.verified, 0);
+    assert!(stats.false_positives < 20, "{:?}", stats);
+    assert_eq!(uncached.visited_cache_stats(), None);
+    remove("test_visited_uncached");
+    remove("test_visited_cached");
+}
+
+#[tokio::test]
+async fn reopened_cache_verifies_once() {
+    remove("test_visited_reopened");
+    crawl(&DetikData::new("test_visited_reopened").await.unwrap()).await;
+    let storage = DetikData::new("test_visited_reopened")
+        .await
+        .unwrap()
+        .with_visited_cache(1000)
+        .await
+        .unwrap();
+    for _ in 0..3 {
+        for url in navigation() {
+            storage.queued_insert_unseen(&url).await.unwrap();
+        }
+    }
+    let stats = storage.visited_cache_stats().unwrap();
+    // Visited before the cache, looked up the first time only
+    assert_eq!(
+        stats,
+        VisitedCacheStats {
+            hits: 2 * NAVIGATION as u64,
+            misses: 0,
+            verified: NAVIGATION as u64,
+            false_positives: 0,
+        }
+    );
+    assert!(queued(&storage)
+        .await
+        .iter()
+        .all(|url| !url.contains("kanal")));
+
+    // Taken out of visited, it's queued again
+    let revisited = &navigation()[0];
+    storage.visited_delete(revisited).await.unwrap();
+    storage.queued_insert_unseen(revisited).await.unwrap();
+    assert!(queued(&storage).await.contains(revisited));
+    remove("test_visited_reopened");
+}
@@ -0,0 +1,396 @@
+//! Several workers crawling into one storage, as processes sharing a database would: each
+//! queued url is claimed by a single worker, and the urls of a worker that stops sending
+//! heartbeats are queued again.
+
+use axum::{extract::Path as AxumPath, routing::get, Router};
+use chrono::{DateTime, Duration, FixedOffset};
+use futures::future;
+use indonesian_media_crawler::detik::{DetikArticle, DetikData};
+use indonesian_media_crawler::memory::MemoryStorage;
+use indonesian_media_crawler::{
+    run_scrapper, CrawlContext, CrawlController, Crawler, CrawlerResult, ExtractedLink, RunOptions,
+    Storage,
+};
+use scraper::{Html, Selector};
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration as StdDuration;
+use tokio::sync::Barrier;
+use tokio::time::{sleep, timeout};
+
+const URLS: usize = 120;
+
+fn urls() -> Vec<String> {
+    (0..URLS)
+        .map(|i| format!("https://news.detik.com/berita/d-{}/judul", i))
+        .collect()
+}
+
+/// The urls `worker_id` claims, at most `n`.
+async fn claim<S: Storage>(storage: &S, worker_id: &str, n: u32) -> Vec<String> {
+    let claimed = storage.queued_claim_n(worker_id, n).await.unwrap();
+    claimed
+        .into_iter()
+        .map(|queued| queued.url.to_string())
+        .collect()
+}
+
+/// Claim and "crawl" urls until the queue is empty, returning the ones this worker got.
+/// Starts along with the other worker waiting on `start`.
+async fn work<S: Storage + Send>(storage: S, worker_id: &str, start: Arc<Barrier>) -> Vec<String> {
+    start.wait().await;
+    let mut crawled = vec![];
+    loop {
+        let claimed = claim(&storage, worker_id, 7).await;
+        if claimed.is_empty() {
+            return crawled;
+        }
+        for url in claimed {
+            tokio::task::yield_now().await;
+            storage.visited_insert(&url).await.unwrap();
+            storage.running_delete(&url).await.unwrap();
+            crawled.push(url);
+        }
+    }
+}
+
+fn assert_disjoint(a: &[String], b: &[String]) {
+    let a: HashSet<&String> = a.iter().collect();
+    let b: HashSet<&String> = b.iter().collect();
+    assert!(a.is_disjoint(&b), "{:?}", a.intersection(&b));
+    let all: HashSet<String> = a.union(&b).map(|url| url.to_string()).collect();
+    assert_eq!(all, urls().into_iter().collect());
+}
+
+async fn sqlite(name: &str) -> DetikData {
+    DetikData::new(name).await.unwrap()
+}
+
+async fn remove(path: &str) {
+    for path in [
+        path.to_string(),
+        format!("{}-wal", path),
+        format!("{}-shm", path),
+    ] {
+        if Path::new(&path).is_file() {
+            tokio::fs::remove_file(path).await.unwrap();
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn claims_are_disjoint() {
+    let memory = MemoryStorage::<DetikArticle>::new();
+    memory.queued_insert_batch(&urls()).await.unwrap();
+    let start = Arc::new(Barrier::new(2));
+    let a = tokio::spawn(work(memory.clone(), "a", Arc::clone(&start)));
+    let b = tokio::spawn(work(memory.clone(), "b", start));
+    assert_disjoint(&a.await.unwrap(), &b.await.unwrap());
+    assert_eq!(memory.running_count().await.unwrap(), 0);
+
+    // Two connections to the same file, like two processes
+    remove("test_workers.db").await;
+    sqlite("test_workers")
+        .await
+        .queued_insert_batch(&urls())
+        .await
+        .unwrap();
+    let start = Arc::new(Barrier::new(2));
+    let (a, b) = (sqlite("test_workers").await, sqlite("test_workers").await);
+    let a = tokio::spawn(work(a, "a", Arc::clone(&start)));
+    let b = tokio::spawn(work(b, "b", start));
+    assert_disjoint(&a.await.unwrap(), &b.await.unwrap());
+    let storage = sqlite("test_workers").await;
+    assert_eq!(storage.visited_count().await.unwrap(), URLS as u32);
+    assert_eq!(storage.running_count().await.unwrap(), 0);
+    remove("test_workers.db").await;
+}
+
+/// Worker `a` claims urls and crashes, `b` keeps sending heartbeats: only the urls of `a`
+/// are queued again once its heartbeat is stale.
+async fn crash_recovery<S: Storage>(storage: &S) {
+    storage.queued_insert_batch(&urls()[..10]).await.unwrap();
+    let start: DateTime<FixedOffset> = chrono::Local::now().into();
+    let crashed = claim(storage, "a", 4).await;
+    let alive = claim(storage, "b", 4).await;
+    assert_eq!(crashed, urls()[..4]);
+    assert_eq!(alive, urls()[4..8]);
+
+    storage
+        .running_touch("b", start + Duration::minutes(3))
+        .await
+        .unwrap();
+    assert!(storage
+        .running_reap(start - Duration::minutes(2))
+        .await
+        .unwrap()
+        .is_empty());
+    let mut reaped = storage
+        .running_reap(start + Duration::minutes(1))
+        .await
+        .unwrap();
+    reaped.sort();
+    assert_eq!(reaped, crashed);
+    assert_eq!(storage.running_count().await.unwrap(), 4);
+
+    // Queued again behind the urls never claimed, and claimed by the next worker
+    let mut next = claim(storage, "c", 10).await;
+    assert_eq!(next.len(), 6);
+    next.sort();
+    assert!(crashed.iter().all(|url| next.contains(url)));
+
+    // A worker stopping gives back its claims
+    let mut released = storage.running_release("b").await.unwrap();
+    released.sort();
+    assert_eq!(released, alive);
+    assert!(storage.running_release("b").await.unwrap().is_empty());
+    assert_eq!(storage.queued_count().await.unwrap(), 4);
+}
+
+#[tokio::test]
+async fn crashed_worker_recovered() {
+    crash_recovery(&MemoryStorage::<DetikArticle>::new()).await;
+
+    remove("test_workers_crash.db").await;
+    crash_recovery(&sqlite("test_workers_crash").await).await;
+    remove("test_workers_crash.db").await;
+}
+
+/// The queued urls visited since are dropped by the claim, and a url is claimed directly
+/// only while neither running nor visited.
+async fn claims_skip_visited<S: Storage>(storage: &S) {
+    let urls = urls();
+    storage.queued_insert_batch(&urls[..3]).await.unwrap();
+    storage.visited_insert(&urls[1]).await.unwrap();
+    assert_eq!(
+        claim(storage, "a", 10).await,
+        vec![urls[0].clone(), urls[2].clone()]
+    );
+    assert_eq!(storage.queued_count().await.unwrap(), 0);
+
+    storage.queued_insert(&urls[3]).await.unwrap();
+    assert!(storage.running_claim(&urls[3], "a").await.unwrap());
+    assert!(!storage.queued_is_exists(&urls[3]).await.unwrap());
+    assert!(!storage.running_claim(&urls[3], "b").await.unwrap());
+    assert!(!storage.running_claim(&urls[1], "b").await.unwrap());
+    assert_eq!(storage.running_count().await.unwrap(), 3);
+    let mut released = storage.running_release("a").await.unwrap();
+    released.sort();
+    assert_eq!(
+        released,
+        vec![urls[0].clone(), urls[2].clone(), urls[3].clone()]
+    );
+}
+
+#[tokio::test]
+async fn visited_urls_not_claimed() {
+    claims_skip_visited(&MemoryStorage::<DetikArticle>::new()).await;
+
+    remove("test_workers_visited.db").await;
+    claims_skip_visited(&sqlite("test_workers_visited").await).await;
+    remove("test_workers_visited.db").await;
+}
+
+/// The urls of a higher priority are claimed first, the oldest first among the same.
+async fn claims_by_priority<S: Storage>(storage: &S) {
+    let urls = urls();
+    storage.queued_insert_batch(&urls[..3]).await.unwrap();
+    storage
+        .queued_insert_prioritized(&urls[3], Some(&urls[0]), 1, 10)
+        .await
+        .unwrap();
+    storage
+        .queued_insert_with_priority(&urls[4], 5)
+        .await
+        .unwrap();
+    storage
+        .queued_insert_prioritized(&urls[5], Some(&urls[0]), 1, 10)
+        .await
+        .unwrap();
+    assert_eq!(
+        claim(storage, "a", 2).await,
+        vec![urls[3].clone(), urls[5].clone()]
+    );
+    assert_eq!(
+        claim(storage, "a", 2).await,
+        vec![urls[4].clone(), urls[0].clone()]
+    );
+    assert_eq!(claim(storage, "a", 10).await, urls[1..3]);
+}
+
+#[tokio::test]
+async fn urls_claimed_by_priority() {
+    claims_by_priority(&MemoryStorage::<DetikArticle>::new()).await;
+
+    remove("test_workers_priority.db").await;
+    claims_by_priority(&sqlite("test_workers_priority").await).await;
+    remove("test_workers_priority.db").await;
+}
+
+/// A url claimed directly keeps the referrer and depth it was queued with, through being
+/// released and claimed again.
+async fn claims_keep_meta<S: Storage>(storage: &S) {
+    let urls = urls();
+    storage
+        .queued_insert_with_meta(&urls[1], Some(&urls[0]), 3)
+        .await
+        .unwrap();
+    assert!(storage.running_claim(&urls[1], "a").await.unwrap());
+    assert_eq!(
+        storage.running_release("a").await.unwrap(),
+        vec![urls[1].clone()]
+    );
+
+    let claimed = storage.queued_claim_n("b", 10).await.unwrap();
+    assert_eq!(claimed.len(), 1);
+    assert_eq!(&*claimed[0].url, urls[1]);
+    assert_eq!(claimed[0].referrer.as_deref(), Some(urls[0].as_str()));
+    assert_eq!(claimed[0].depth, 3);
+}
+
+#[tokio::test]
+async fn claimed_urls_keep_meta() {
+    claims_keep_meta(&MemoryStorage::<DetikArticle>::new()).await;
+
+    remove("test_workers_meta.db").await;
+    claims_keep_meta(&sqlite("test_workers_meta").await).await;
+    remove("test_workers_meta.db").await;
+}
+
+/// A claimed url left for later is queued again with its priority and how the crawl came to
+/// it, still claimed before the older urls.
+async fn requeues_keep_priority<S: Storage>(storage: &S) {
+    let urls = urls();
+    storage.queued_insert(&urls[0]).await.unwrap();
+    storage
+        .queued_insert_prioritized(&urls[2], Some(&urls[1]), 2, 5)
+        .await
+        .unwrap();
+    assert_eq!(claim(storage, "a", 1).await, [urls[2].clone()]);
+
+    storage.running_requeue(&urls[2]).await.unwrap();
+    assert_eq!(storage.running_count().await.unwrap(), 0);
+    let claimed = storage.queued_claim_n("b", 1).await.unwrap();
+    assert_eq!(&*claimed[0].url, urls[2]);
+    assert_eq!(claimed[0].referrer.as_deref(), Some(urls[1].as_str()));
+    assert_eq!((claimed[0].depth, claimed[0].priority), (2, 5));
+
+    // Not running, queued like a seed
+    storage.running_requeue(&urls[3]).await.unwrap();
+    assert_eq!(
+        claim(storage, "b", 10).await,
+        [urls[0].clone(), urls[3].clone()]
+    );
+}
+
+#[tokio::test]
+async fn requeued_urls_keep_priority() {
+    requeues_keep_priority(&MemoryStorage::<DetikArticle>::new()).await;
+
+    remove("test_workers_requeue.db").await;
+    requeues_keep_priority(&sqlite("test_workers_requeue").await).await;
+    remove("test_workers_requeue.db").await;
+}
+
+/// Follows every link of a page, none of them being an article.
+struct LinkCrawler;
+
+impl Crawler for LinkCrawler {
+    type Document = DetikArticle;
+
+    fn can_be_scrapped(&self, _doc: &Html) -> bool {
+        false
+    }
+
+    fn crawl(&self, ctx: &CrawlContext, doc: &Html) -> CrawlerResult<Self::Document> {
+        CrawlerResult::Links(self.extract_urls(ctx, doc))
+    }
+
+    fn extract_links(&self, ctx: &CrawlContext, doc: &Html) -> Vec<ExtractedLink> {
+        doc.select(&Selector::parse("a[href]").unwrap())
+            .filter_map(|a| ctx.url.join(a.value().attr("href")?).ok())
+            .map(|url| ExtractedLink::from(url.to_string()))
+            .collect()
+    }
+}
+
+/// `/flooded` and `/sibling` link to each other and to themselves, counting the fetches of
+/// each page.
+fn start_site(fetches: Arc<Mutex<HashMap<String, u32>>>) -> SocketAddr {
+    let app = Router::new().route(
+        "/:page",
+        get(move |AxumPath(page): AxumPath<String>| async move {
+            *fetches.lock().unwrap().entry(page).or_default() += 1;
+            // Slow enough for the links to be queued again while a page is fetched
+            sleep(StdDuration::from_millis(50)).await;
+            axum::response::Html(
+                r#"<a href="/flooded">a</a><a href="/sibling">b</a><a href="/flooded">c</a>"#,
+            )
+        }),
+    );
+    let server = axum::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(app.into_make_service());
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+/// 100 copies of a url queued before two workers start, and more found while it's fetched:
+/// it's fetched once.
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn flooded_url_fetched_once() {
+    let fetches = Arc::new(Mutex::new(HashMap::new()));
+    let site = start_site(Arc::clone(&fetches));
+    let flooded = format!("http://{}/flooded", site);
+
+    remove("test_workers_flood.db").await;
+    let storage = sqlite("test_workers_flood").await;
+    future::try_join_all((0..100).map(|_| storage.queued_insert(&flooded)))
+        .await
+        .unwrap();
+
+    let controller = CrawlController::new();
+    let worker = |worker_id: &str| {
+        let options = RunOptions {
+            request_delay: StdDuration::from_millis(1),
+            worker_id: worker_id.to_string(),
+            controller: controller.clone(),
+            ..RunOptions::default()
+        };
+        let initial_queue = vec![flooded.clone(); 100];
+        async move {
+            run_scrapper(
+                LinkCrawler,
+                sqlite("test_workers_flood").await,
+                initial_queue,
+                options,
+            )
+            .await
+        }
+    };
+    let a = tokio::spawn(worker("a"));
+    let b = tokio::spawn(worker("b"));
+
+    timeout(StdDuration::from_secs(30), async {
+        loop {
+            let done = storage.visited_count().await.unwrap() == 2
+                && storage.queued_is_empty().await.unwrap()
+                && storage.running_count().await.unwrap() == 0;
+            if done {
+                break;
+            }
+            sleep(StdDuration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("Pages never crawled");
+    controller.stop();
+    a.await.unwrap().unwrap();
+    b.await.unwrap().unwrap();
+
+    let fetches = fetches.lock().unwrap().clone();
+    assert_eq!(fetches.get("flooded"), Some(&1));
+    assert_eq!(fetches.get("sibling"), Some(&1));
+    remove("test_workers_flood.db").await;
+}